@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::unwrap_used)]
+
+//! Verification of the proofs of store of a consensus observer block payload, one proof after
+//! the other as before, in parallel, and in parallel with every proof already cached.
+
+use aptos_consensus::consensus_observer::network_message::{BlockPayload, BlockTransactionPayload};
+use aptos_consensus_types::proof_of_store::{BatchId, BatchInfo, ProofCache, ProofOfStore};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use gaptos::{
+    aptos_crypto::HashValue,
+    aptos_types::{
+        aggregate_signature::PartialSignatures,
+        block_info::BlockInfo,
+        epoch_state::EpochState,
+        validator_verifier::{random_validator_verifier, ValidatorVerifier},
+        PeerId,
+    },
+};
+use std::collections::BTreeMap;
+
+const EPOCH: u64 = 1;
+const VALIDATORS: usize = 20;
+
+fn create_block_payload(num_proofs: usize) -> (BlockPayload, EpochState) {
+    let (signers, validator_verifier) = random_validator_verifier(VALIDATORS, None, false);
+    let proofs = (0..num_proofs)
+        .map(|i| {
+            let batch_info = BatchInfo::new(
+                PeerId::ZERO,
+                BatchId::new(i as u64),
+                EPOCH,
+                u64::MAX,
+                HashValue::random(),
+                1,
+                1,
+                0,
+            );
+            let signatures = signers
+                .iter()
+                .map(|signer| (signer.author(), signer.sign(&batch_info).unwrap()))
+                .collect::<BTreeMap<_, _>>();
+            let multi_signature = validator_verifier
+                .aggregate_signatures(PartialSignatures::new(signatures).signatures_iter())
+                .unwrap();
+            ProofOfStore::new(batch_info, multi_signature)
+        })
+        .collect();
+    let transaction_payload =
+        BlockTransactionPayload::new_quorum_store_inline_hybrid(vec![], proofs, None, vec![]);
+    let block_info = BlockInfo::new(EPOCH, 1, HashValue::random(), HashValue::zero(), 0, 0, None);
+    (BlockPayload::new(block_info, transaction_payload), EpochState::new(EPOCH, validator_verifier))
+}
+
+fn verify_sequentially(
+    block_payload: &BlockPayload,
+    validator_verifier: &ValidatorVerifier,
+    proof_cache: &ProofCache,
+) {
+    for proof_of_store in &block_payload.transaction_payload.payload_proofs() {
+        proof_of_store.verify(validator_verifier, proof_cache).unwrap();
+    }
+}
+
+fn payload_verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("observer_payload_verification");
+    for num_proofs in [8, 32, 64] {
+        let (block_payload, epoch_state) = create_block_payload(num_proofs);
+        let proof_cache_capacity = num_proofs as u64;
+
+        group.bench_with_input(BenchmarkId::new("sequential", num_proofs), &(), |b, _| {
+            b.iter_batched(
+                || ProofCache::new(proof_cache_capacity),
+                |proof_cache| {
+                    verify_sequentially(&block_payload, &epoch_state.verifier, &proof_cache)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", num_proofs), &(), |b, _| {
+            b.iter_batched(
+                || ProofCache::new(proof_cache_capacity),
+                |proof_cache| {
+                    block_payload.verify_payload_signatures(&epoch_state, &proof_cache).unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        let warm_cache = ProofCache::new(proof_cache_capacity);
+        block_payload.verify_payload_signatures(&epoch_state, &warm_cache).unwrap();
+        group.bench_with_input(BenchmarkId::new("cached", num_proofs), &(), |b, _| {
+            b.iter(|| block_payload.verify_payload_signatures(&epoch_state, &warm_cache).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, payload_verification);
+criterion_main!(benches);
@@ -327,6 +327,10 @@ impl Block {
         self.block_data.validator_txns()
     }
 
+    pub fn system_txns(&self) -> Option<&Vec<Vec<u8>>> {
+        self.block_data.system_txns()
+    }
+
     /// Verifies that the proposal and the QC are correctly signed.
     /// If this is the genesis block, we skip these checks.
     pub fn validate_signature(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
@@ -368,6 +372,10 @@ impl Block {
                 self.payload().map_or(true, |p| p.is_empty()),
                 "Reconfiguration suffix should not carry payload"
             );
+            ensure!(
+                self.system_txns().map_or(true, |txns| txns.is_empty()),
+                "Reconfiguration suffix should not carry system txns"
+            );
         }
         if let Some(failed_authors) = self.block_data().failed_authors() {
             // when validating for being well formed,
@@ -144,6 +144,14 @@ impl BlockData {
         }
     }
 
+    /// System transactions injected by the proposer, `None` for blocks that cannot carry any.
+    pub fn system_txns(&self) -> Option<&Vec<Vec<u8>>> {
+        match &self.block_type {
+            BlockType::ProposalExt(proposal_ext) => proposal_ext.system_txns(),
+            _ => None,
+        }
+    }
+
     pub fn dag_nodes(&self) -> Option<&Vec<HashValue>> {
         if let BlockType::DAGBlock { node_digests: nodes_digests, .. } = &self.block_type {
             Some(nodes_digests)
@@ -348,6 +356,32 @@ impl BlockData {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_proposal_with_system_txns(
+        validator_txns: Vec<ValidatorTransaction>,
+        system_txns: Vec<Vec<u8>>,
+        payload: Payload,
+        author: Author,
+        failed_authors: Vec<(Round, Author)>,
+        round: Round,
+        timestamp_usecs: u64,
+        quorum_cert: QuorumCert,
+    ) -> Self {
+        Self {
+            epoch: quorum_cert.certified_block().epoch(),
+            round,
+            timestamp_usecs,
+            quorum_cert,
+            block_type: BlockType::ProposalExt(ProposalExt::V1 {
+                validator_txns,
+                system_txns,
+                payload,
+                author,
+                failed_authors,
+            }),
+        }
+    }
+
     /// It's a reconfiguration suffix block if the parent block's executed state indicates next
     /// epoch.
     pub fn is_reconfiguration_suffix(&self) -> bool {
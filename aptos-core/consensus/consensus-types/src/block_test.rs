@@ -7,7 +7,9 @@ use crate::{
         block_test_utils::{certificate_for_genesis, *},
         Block,
     },
+    block_data::{BlockData, BlockType},
     common::{Author, Payload},
+    proposal_ext::ProposalExt,
     quorum_cert::QuorumCert,
     vote_data::VoteData,
 };
@@ -275,3 +277,47 @@ fn test_deserialize_ignores_untrusted_block_number() {
     assert_eq!(decoded.block_number(), None);
     assert_eq!(decoded.id(), block.id());
 }
+
+#[test]
+fn test_system_txns_survive_serialization() {
+    let signer = ValidatorSigner::random(None);
+    let system_txns = vec![vec![1, 2, 3], vec![], vec![4]];
+    let block_data = BlockData::new_proposal_with_system_txns(
+        vec![],
+        system_txns.clone(),
+        Payload::empty(false, true),
+        signer.author(),
+        vec![(0, signer.author())],
+        1,
+        1,
+        certificate_for_genesis(),
+    );
+    let block = Block::new_proposal_from_block_data(block_data, &signer).unwrap();
+
+    let decoded: Block = bcs::from_bytes(&bcs::to_bytes(&block).unwrap()).unwrap();
+    assert!(matches!(
+        decoded.block_data().block_type(),
+        BlockType::ProposalExt(ProposalExt::V1 { .. })
+    ));
+    assert_eq!(decoded.system_txns(), Some(&system_txns));
+    assert_eq!(decoded.block_data(), block.block_data());
+    assert_eq!(decoded.id(), block.id());
+    assert_eq!(decoded.signature(), block.signature());
+
+    // Blocks without system txns keep their encoding
+    let block_data = bcs::from_bytes::<BlockData>(
+        &bcs::to_bytes(&BlockData::new_proposal_ext(
+            vec![],
+            Payload::empty(false, true),
+            signer.author(),
+            vec![],
+            1,
+            1,
+            certificate_for_genesis(),
+        ))
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(matches!(block_data.block_type(), BlockType::ProposalExt(ProposalExt::V0 { .. })));
+    assert_eq!(block_data.system_txns(), None);
+}
@@ -18,30 +18,50 @@ pub enum ProposalExt {
         /// immediately preceeding rounds that didn't produce a successful block.
         failed_authors: Vec<(Round, Author)>,
     },
+    /// V0 plus the system transactions the proposer's `SystemTxnProvider` injected, e.g.
+    /// bridged deposits. They are opaque to consensus and executed ahead of the payload.
+    V1 {
+        validator_txns: Vec<ValidatorTransaction>,
+        system_txns: Vec<Vec<u8>>,
+        payload: Payload,
+        author: Author,
+        failed_authors: Vec<(Round, Author)>,
+    },
 }
 
 impl ProposalExt {
     pub fn author(&self) -> &Author {
         match self {
-            ProposalExt::V0 { author, .. } => author,
+            ProposalExt::V0 { author, .. } | ProposalExt::V1 { author, .. } => author,
         }
     }
 
     pub fn failed_authors(&self) -> &Vec<(Round, Author)> {
         match self {
-            ProposalExt::V0 { failed_authors, .. } => failed_authors,
+            ProposalExt::V0 { failed_authors, .. } | ProposalExt::V1 { failed_authors, .. } => {
+                failed_authors
+            }
         }
     }
 
     pub fn validator_txns(&self) -> Option<&Vec<ValidatorTransaction>> {
         match self {
-            ProposalExt::V0 { validator_txns, .. } => Some(validator_txns),
+            ProposalExt::V0 { validator_txns, .. } | ProposalExt::V1 { validator_txns, .. } => {
+                Some(validator_txns)
+            }
         }
     }
 
     pub fn payload(&self) -> Option<&Payload> {
         match self {
-            ProposalExt::V0 { payload, .. } => Some(payload),
+            ProposalExt::V0 { payload, .. } | ProposalExt::V1 { payload, .. } => Some(payload),
+        }
+    }
+
+    pub fn system_txns(&self) -> Option<&Vec<Vec<u8>>> {
+        match self {
+            ProposalExt::V0 { .. } => None,
+            ProposalExt::V1 { system_txns, .. } => Some(system_txns),
         }
     }
 }
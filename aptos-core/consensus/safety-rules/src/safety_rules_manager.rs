@@ -13,7 +13,7 @@ use crate::{
 };
 use gaptos::{
     aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService},
-    aptos_crypto::bls12381::PublicKey,
+    aptos_crypto::bls12381::{PrivateKey, PublicKey},
     aptos_global_constants::CONSENSUS_KEY,
     aptos_infallible::RwLock,
     aptos_logger::{info, warn},
@@ -76,16 +76,7 @@ pub fn storage(config: &SafetyRulesConfig) -> PersistentSafetyStorage {
         let timer = Instant::now();
         let blob = config.initial_safety_rules_config.identity_blob().unwrap();
         if let Some(sk) = blob.consensus_private_key {
-            let pk_hex = hex::encode(PublicKey::from(&sk).to_bytes());
-            let storage_key = format!("{}_{}", CONSENSUS_KEY, pk_hex);
-            match storage.internal_store().set(storage_key.as_str(), sk) {
-                Ok(_) => {
-                    info!("Setting {storage_key} succeeded.");
-                }
-                Err(e) => {
-                    warn!("Setting {storage_key} failed with internal store set error: {e}");
-                }
-            }
+            store_consensus_key(&mut storage, sk);
         }
         info!("Overriding key work time: {:?}", timer.elapsed());
 
@@ -93,6 +84,41 @@ pub fn storage(config: &SafetyRulesConfig) -> PersistentSafetyStorage {
     }
 }
 
+/// Stores `sk` under its public key, where `consensus_sk_by_pk` looks for it.
+fn store_consensus_key(storage: &mut PersistentSafetyStorage, sk: PrivateKey) -> bool {
+    let pk_hex = hex::encode(PublicKey::from(&sk).to_bytes());
+    let storage_key = format!("{}_{}", CONSENSUS_KEY, pk_hex);
+    match storage.internal_store().set(storage_key.as_str(), sk) {
+        Ok(_) => {
+            info!("Setting {storage_key} succeeded.");
+            true
+        }
+        Err(e) => {
+            warn!("Setting {storage_key} failed with internal store set error: {e}");
+            false
+        }
+    }
+}
+
+/// Reads the identity blob again and stores its consensus key. This is how a key rotated in
+/// the identity file while the node runs reaches the storage before the epoch that uses it.
+/// Keys stored earlier are kept, so the node can still sign in epochs that use them.
+pub fn reload_identity_consensus_key(
+    config: &SafetyRulesConfig,
+    storage: &mut PersistentSafetyStorage,
+) -> Option<PublicKey> {
+    let blob = match config.initial_safety_rules_config.identity_blob() {
+        Ok(blob) => blob,
+        Err(e) => {
+            warn!("Failed to read the identity blob: {e}");
+            return None;
+        }
+    };
+    let sk = blob.consensus_private_key?;
+    let pk = PublicKey::from(&sk);
+    store_consensus_key(storage, sk).then_some(pk)
+}
+
 enum SafetyRulesWrapper {
     Local(Arc<RwLock<SafetyRules>>),
     Process(ProcessService),
@@ -159,3 +185,87 @@ impl SafetyRulesManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::{
+        aptos_crypto::Uniform,
+        aptos_secure_storage::InMemoryStorage,
+        aptos_types::{account_address::AccountAddress, waypoint::Waypoint},
+    };
+    use std::path::Path;
+
+    /// Writes an identity file holding `sk`. JSON is valid YAML, which the file is parsed as.
+    fn write_identity(path: &Path, author: AccountAddress, sk: &PrivateKey) {
+        let identity = serde_json::json!({
+            "account_address": author.to_hex(),
+            "consensus_private_key": hex::encode(sk.to_bytes()),
+            "network_private_key": hex::encode([1u8; 32]),
+        });
+        std::fs::write(path, identity.to_string()).unwrap();
+    }
+
+    fn config_with_identity(path: &Path) -> SafetyRulesConfig {
+        let initial_safety_rules_config = serde_json::from_value(serde_json::json!({
+            "from_file": {
+                "identity_blob_path": path,
+                "waypoint": { "from_config": Waypoint::default().to_string() },
+            }
+        }))
+        .unwrap();
+        SafetyRulesConfig { initial_safety_rules_config, ..Default::default() }
+    }
+
+    fn storage_with_key(author: AccountAddress, sk: PrivateKey) -> PersistentSafetyStorage {
+        PersistentSafetyStorage::initialize(
+            Storage::from(InMemoryStorage::new()),
+            author,
+            sk,
+            Waypoint::default(),
+            true,
+        )
+    }
+
+    #[test]
+    fn test_reload_stores_the_rotated_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identity.yaml");
+        let author = AccountAddress::random();
+        let mut rng = rand::thread_rng();
+        let old_sk = PrivateKey::generate(&mut rng);
+        let new_sk = PrivateKey::generate(&mut rng);
+        let old_pk = PublicKey::from(&old_sk);
+        let new_pk = PublicKey::from(&new_sk);
+
+        let mut storage = storage_with_key(author, old_sk);
+        assert!(storage.consensus_sk_by_pk(new_pk.clone()).is_err());
+
+        // The operator puts the new key in the identity file while the node runs
+        write_identity(&path, author, &new_sk);
+        let reloaded = reload_identity_consensus_key(&config_with_identity(&path), &mut storage);
+
+        assert_eq!(reloaded, Some(new_pk.clone()));
+        assert_eq!(PublicKey::from(&storage.consensus_sk_by_pk(new_pk.clone()).unwrap()), new_pk);
+        // Epochs that still list the old key can sign with it
+        assert_eq!(PublicKey::from(&storage.consensus_sk_by_pk(old_pk.clone()).unwrap()), old_pk);
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_the_old_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identity.yaml");
+        let author = AccountAddress::random();
+        let old_sk = PrivateKey::generate(&mut rand::thread_rng());
+        let old_pk = PublicKey::from(&old_sk);
+        let mut storage = storage_with_key(author, old_sk);
+
+        // The identity file is missing
+        assert_eq!(reload_identity_consensus_key(&config_with_identity(&path), &mut storage), None);
+        // The identity file cannot be parsed
+        std::fs::write(&path, "consensus_private_key: [").unwrap();
+        assert_eq!(reload_identity_consensus_key(&config_with_identity(&path), &mut storage), None);
+
+        assert_eq!(PublicKey::from(&storage.consensus_sk_by_pk(old_pk.clone()).unwrap()), old_pk);
+    }
+}
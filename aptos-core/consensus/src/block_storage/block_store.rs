@@ -5,10 +5,13 @@
 use crate::{
     block_storage::{
         block_tree::BlockTree,
+        forensics::{halt_on_mismatch, ReplayedBlock},
         pending_blocks::PendingBlocks,
+        recovery_api::RecoveryApi,
         tracing::{observe_block, BlockStage},
         BlockReader,
     },
+    error::StateRootMismatch,
     payload_manager::TPayloadManager,
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData, RootInfo},
     pipeline::execution_client::TExecutionClient,
@@ -26,7 +29,7 @@ use aptos_consensus_types::{
 };
 use aptos_executor_types::StateComputeResult;
 use aptos_mempool::core_mempool::transaction::VerifiedTxn;
-use block_buffer_manager::{block_buffer_manager::BlockHashRef, get_block_buffer_manager};
+use block_buffer_manager::{block_buffer_manager::BlockHashRef, BlockBufferManager};
 use futures::executor::block_on;
 use gaptos::{
     api_types::{
@@ -48,6 +51,8 @@ use gaptos::{
     },
 };
 use once_cell::sync::Lazy;
+use proposer_reth_map::EpochContext;
+use runtime_config::{Knob, RuntimeConfigRegistry};
 
 #[cfg(test)]
 use std::collections::VecDeque;
@@ -58,7 +63,7 @@ use std::sync::atomic::Ordering;
 use std::{
     collections::{BTreeMap, HashMap},
     io::Read,
-    sync::Arc,
+    sync::{Arc, OnceLock},
     time::Duration,
 };
 
@@ -137,6 +142,25 @@ pub struct BlockStore {
     /// Mapping from validator address to their index in the ordered validator set.
     /// Used during recovery to compute proposer_index for blocks.
     validator_indices: HashMap<AccountAddress, usize>,
+    /// Used during recovery to resolve the execution metadata of blocks.
+    epoch_context: Arc<EpochContext>,
+    block_buffer_manager: Arc<BlockBufferManager>,
+    /// Replays the blocks block sync verified as committed.
+    recovery_api: Arc<dyn RecoveryApi>,
+}
+
+/// Can be changed at runtime through the `batch_commit_size` knob.
+pub(crate) fn batch_commit_size_knob() -> &'static Knob {
+    static KNOB: OnceLock<Arc<Knob>> = OnceLock::new();
+    KNOB.get_or_init(|| {
+        RuntimeConfigRegistry::global().register(
+            "batch_commit_size",
+            "Ordered blocks accumulated before they are sent for execution, 0 sends each block",
+            "BATCH_COMMIT_SIZE",
+            0,
+            1_000,
+        )
+    })
 }
 
 impl BlockStore {
@@ -154,6 +178,9 @@ impl BlockStore {
         enable_randomness: bool,
         require_block_randomness: bool,
         validator_indices: HashMap<AccountAddress, usize>,
+        epoch_context: Arc<EpochContext>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+        recovery_api: Arc<dyn RecoveryApi>,
     ) -> Self {
         let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
         let (root, blocks, quorum_certs) = initial_data.take();
@@ -175,6 +202,9 @@ impl BlockStore {
             enable_randomness,
             require_block_randomness,
             validator_indices,
+            epoch_context,
+            block_buffer_manager,
+            recovery_api,
         ));
         block_on(block_store.recover_blocks());
         block_store
@@ -194,6 +224,9 @@ impl BlockStore {
         enable_randomness: bool,
         require_block_randomness: bool,
         validator_indices: HashMap<AccountAddress, usize>,
+        epoch_context: Arc<EpochContext>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+        recovery_api: Arc<dyn RecoveryApi>,
     ) -> Self {
         let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
         let (root, blocks, quorum_certs) = initial_data.take();
@@ -215,6 +248,9 @@ impl BlockStore {
             enable_randomness,
             require_block_randomness,
             validator_indices,
+            epoch_context,
+            block_buffer_manager,
+            recovery_api,
         )
         .await;
         block_store.recover_blocks().await;
@@ -291,7 +327,17 @@ impl BlockStore {
                 .send_for_execution(qc.into_wrapped_ledger_info(), true, epoch_change_block_number)
                 .await
             {
-                error!("recover_blocks: failed to commit blocks: {e}");
+                match e.downcast_ref::<StateRootMismatch>() {
+                    Some(_) if halt_on_mismatch() => panic!("recover_blocks: {e:#}"),
+                    Some(mismatch) => error!(
+                        "recover_blocks: {e:#}. RECOVERY_HALT_ON_STATE_ROOT_MISMATCH is false, so \
+                         the node keeps running with an execution layer that diverged from the \
+                         committed chain at block {}. Recovery stopped there, repair the \
+                         execution layer before relying on this node",
+                        mismatch.block_number
+                    ),
+                    None => error!("recover_blocks: failed to commit blocks: {e:#}"),
+                }
                 break;
             }
         }
@@ -414,6 +460,9 @@ impl BlockStore {
         enable_randomness: bool,
         require_block_randomness: bool,
         validator_indices: HashMap<AccountAddress, usize>,
+        epoch_context: Arc<EpochContext>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+        recovery_api: Arc<dyn RecoveryApi>,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_ordered_cert, root_commit_cert) = root;
         let root_round = root_block.round();
@@ -452,6 +501,9 @@ impl BlockStore {
             enable_randomness,
             require_block_randomness,
             validator_indices,
+            epoch_context,
+            block_buffer_manager,
+            recovery_api,
         };
 
         // Skip ancestors of the root. They can appear in recovery data when an
@@ -723,33 +775,61 @@ impl BlockStore {
                     extra_data,
                     enable_randomness: self.enable_randomness,
                 };
-                get_block_buffer_manager()
-                    .set_ordered_blocks(BlockId(*p_block.parent_id()), block, p_block.round())
-                    .await
-                    .context("Failed to set ordered blocks during recovery")?;
-                let compute_res = get_block_buffer_manager()
-                    .get_executed_res(BlockId(*p_block.id()), block_number, p_block.block().epoch())
+                let compute_res = self
+                    .recovery_api
+                    .apply_block(
+                        BlockId(*p_block.parent_id()),
+                        block,
+                        p_block.round(),
+                        crate::state_computer::block_execution_meta_util(
+                            &self.epoch_context,
+                            p_block.block(),
+                            proposer_index,
+                        ),
+                    )
                     .await
                     .context(format!(
-                        "Failed to get executed result for block {} during recovery",
+                        "Failed to apply block {} during recovery",
                         p_block.block().id()
                     ))?;
-                let compute_res = compute_res.execution_output;
                 commit_blocks.push(BlockHashRef {
                     block_id: BlockId(*p_block.id()),
                     num: block_number,
-                    hash: Some(compute_res.data),
+                    hash: Some(compute_res.execution_output.data),
                     persist_notifier: None,
                 });
                 if let Some(block_hash) = maybe_block_hash {
-                    assert_eq!(block_hash.data, compute_res.data);
-                    let mut persist_notifiers = get_block_buffer_manager()
-                        .set_commit_blocks(&commit_blocks, p_block.block().epoch())
-                        .await
-                        .context("Failed to set commit blocks during recovery")?;
-                    for notifier in persist_notifiers.iter_mut() {
-                        let _ = notifier.recv().await;
+                    if HashValue::new(block_hash.data) != compute_res.root_hash() {
+                        let parent_state_root = block_number.checked_sub(1).and_then(|parent| {
+                            self.storage
+                                .consensus_db()
+                                .ledger_db
+                                .metadata_db()
+                                .get_block_hash(parent)
+                        });
+                        let mismatch = ReplayedBlock {
+                            block_id: p_block.id(),
+                            parent_id: p_block.parent_id(),
+                            block_number,
+                            epoch: p_block.epoch(),
+                            round: p_block.round(),
+                            timestamp_usecs: p_block.block().timestamp_usecs(),
+                            parent_state_root,
+                            txns: &txns,
+                            compute_result: &compute_res,
+                        }
+                        .mismatch(HashValue::new(block_hash.data));
+                        return Err(mismatch.into());
                     }
+                    let commit_payloads = self
+                        .block_buffer_manager
+                        .take_commit_payloads(&commit_blocks, p_block.block().epoch())
+                        .await;
+                    self.storage.consensus_db().put_commit_payloads(&commit_payloads)?;
+                    self.recovery_api
+                        .commit_blocks(&commit_blocks, p_block.block().epoch())
+                        .await
+                        .context("Failed to commit blocks during recovery")?;
                     commit_blocks.clear();
                 }
             }
@@ -770,10 +850,7 @@ impl BlockStore {
             // `send_for_execution` call will see the same root and `path_from_ordered_root`
             // will grow until it exceeds the batch size, at which point we flush.
             // Used by `gravity_e2e/cluster_test_cases/single_node/test_batch_exec.py`.
-            let batch_commit_size = std::env::var("BATCH_COMMIT_SIZE")
-                .ok()
-                .and_then(|v| v.parse::<usize>().ok())
-                .unwrap_or(0);
+            let batch_commit_size = batch_commit_size_knob().get() as usize;
             if batch_commit_size > 0 && blocks_to_commit.len() <= batch_commit_size {
                 info!(
                     "blocks_to_commit len {} <= BATCH_COMMIT_SIZE {}, skip sending for batch accumulation",
@@ -898,6 +975,9 @@ impl BlockStore {
             self.enable_randomness,
             self.require_block_randomness,
             self.validator_indices.clone(),
+            self.epoch_context.clone(),
+            self.block_buffer_manager.clone(),
+            self.recovery_api.clone(),
         )
         .await;
 
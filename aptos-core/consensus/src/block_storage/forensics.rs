@@ -0,0 +1,170 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Evidence of a block that block sync replayed to a different state root than the committed
+//! chain has for it. The execution layer is then out of step with the other validators, and
+//! what was executed is written to a file before recovery stops, see [`StateRootMismatch`].
+
+use crate::error::StateRootMismatch;
+use aptos_executor_types::StateComputeResult;
+use gaptos::{
+    aptos_crypto::HashValue, aptos_logger::prelude::*, aptos_types::transaction::SignedTransaction,
+};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+#[cfg(test)]
+#[path = "forensics_test.rs"]
+mod forensics_test;
+
+/// Directory the reports are written to.
+/// Can be configured via CONSENSUS_FORENSICS_DIR environment variable, defaults to
+/// `gravity-forensics` in the temporary directory
+fn forensics_dir() -> PathBuf {
+    std::env::var("CONSENSUS_FORENSICS_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| std::env::temp_dir().join("gravity-forensics"), PathBuf::from)
+}
+
+/// Whether the node panics on a mismatch, as it did before the reports existed, instead of
+/// stopping recovery and leaving the decision to the operator.
+/// Can be configured via RECOVERY_HALT_ON_STATE_ROOT_MISMATCH environment variable, read once.
+/// Defaults to true, also when the value is not a bool, which is logged.
+pub(crate) fn halt_on_mismatch() -> bool {
+    static HALT: OnceLock<bool> = OnceLock::new();
+    *HALT.get_or_init(|| match std::env::var("RECOVERY_HALT_ON_STATE_ROOT_MISMATCH") {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            error!(
+                "Invalid RECOVERY_HALT_ON_STATE_ROOT_MISMATCH {:?}: {}, halting on mismatches",
+                value, e
+            );
+            true
+        }),
+        Err(_) => true,
+    })
+}
+
+#[derive(Serialize)]
+struct TxnRecord {
+    hash: String,
+    sender: String,
+    sequence_number: u64,
+}
+
+#[derive(Serialize)]
+struct TxnStatusRecord {
+    hash: String,
+    sender: String,
+    nonce: u64,
+    discarded: bool,
+}
+
+#[derive(Serialize)]
+struct MismatchReport {
+    block_id: String,
+    parent_id: String,
+    block_number: u64,
+    epoch: u64,
+    round: u64,
+    timestamp_usecs: u64,
+    /// Committed state root of the parent block, `None` if it is not in the ledger.
+    parent_state_root: Option<String>,
+    expected_state_root: String,
+    computed_state_root: String,
+    txns: Vec<TxnRecord>,
+    /// Per-transaction results of the execution layer, `None` if it did not report them.
+    txn_status: Option<Vec<TxnStatusRecord>>,
+    events: Vec<String>,
+}
+
+/// A replayed block and what the execution layer made of it.
+pub(crate) struct ReplayedBlock<'a> {
+    pub block_id: HashValue,
+    pub parent_id: HashValue,
+    pub block_number: u64,
+    pub epoch: u64,
+    pub round: u64,
+    pub timestamp_usecs: u64,
+    pub parent_state_root: Option<HashValue>,
+    pub txns: &'a [SignedTransaction],
+    pub compute_result: &'a StateComputeResult,
+}
+
+impl ReplayedBlock<'_> {
+    /// Writes the report of the mismatch and returns the error describing it. A report that
+    /// cannot be written is logged, the error is returned regardless.
+    pub(crate) fn mismatch(&self, expected: HashValue) -> StateRootMismatch {
+        let computed = self.compute_result.root_hash();
+        let dump = match self.write_report(&forensics_dir(), expected, computed) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                error!(
+                    "failed to write the state root mismatch report of block {}: {:#}",
+                    self.block_number, e
+                );
+                None
+            }
+        };
+        StateRootMismatch {
+            block_id: self.block_id,
+            block_number: self.block_number,
+            epoch: self.epoch,
+            expected,
+            computed,
+            dump,
+        }
+    }
+
+    fn write_report(
+        &self,
+        dir: &Path,
+        expected: HashValue,
+        computed: HashValue,
+    ) -> anyhow::Result<PathBuf> {
+        let output = &self.compute_result.execution_output;
+        let report = MismatchReport {
+            block_id: self.block_id.to_hex(),
+            parent_id: self.parent_id.to_hex(),
+            block_number: self.block_number,
+            epoch: self.epoch,
+            round: self.round,
+            timestamp_usecs: self.timestamp_usecs,
+            parent_state_root: self.parent_state_root.map(|root| root.to_hex()),
+            expected_state_root: expected.to_hex(),
+            computed_state_root: computed.to_hex(),
+            txns: self
+                .txns
+                .iter()
+                .map(|txn| TxnRecord {
+                    hash: txn.committed_hash().to_hex(),
+                    sender: txn.sender().to_hex_literal(),
+                    sequence_number: txn.sequence_number(),
+                })
+                .collect(),
+            txn_status: output.txn_status.as_ref().as_ref().map(|statuses| {
+                statuses
+                    .iter()
+                    .map(|status| TxnStatusRecord {
+                        hash: hex::encode(status.txn_hash),
+                        sender: hex::encode(status.sender),
+                        nonce: status.nonce,
+                        discarded: status.is_discarded,
+                    })
+                    .collect()
+            }),
+            events: output.events.iter().map(|event| format!("{event:?}")).collect(),
+        };
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "state_root_mismatch_{}_{}.json",
+            self.block_number,
+            self.block_id.short_str()
+        ));
+        std::fs::write(&path, serde_json::to_vec_pretty(&report)?)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::ReplayedBlock;
+use aptos_executor_types::StateComputeResult;
+use gaptos::aptos_crypto::HashValue;
+
+#[test]
+fn test_mismatch_report_records_both_roots() {
+    let dir = tempfile::tempdir().unwrap();
+    let computed = HashValue::random();
+    let expected = HashValue::random();
+    let compute_result = StateComputeResult::with_root_hash(computed);
+    let block = ReplayedBlock {
+        block_id: HashValue::random(),
+        parent_id: HashValue::random(),
+        block_number: 7,
+        epoch: 2,
+        round: 11,
+        timestamp_usecs: 1_000,
+        parent_state_root: None,
+        txns: &[],
+        compute_result: &compute_result,
+    };
+
+    let path = block.write_report(dir.path(), expected, computed).unwrap();
+
+    let report: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(report["block_number"], 7);
+    assert_eq!(report["expected_state_root"], expected.to_hex());
+    assert_eq!(report["computed_state_root"], computed.to_hex());
+    assert_eq!(report["parent_state_root"], serde_json::Value::Null);
+    assert!(report["txns"].as_array().unwrap().is_empty());
+}
@@ -9,6 +9,7 @@ use aptos_consensus_types::{
     timeout_2chain::TwoChainTimeoutCertificate,
     wrapped_ledger_info::WrappedLedgerInfo,
 };
+pub(crate) use block_store::batch_commit_size_knob;
 pub use block_store::{
     sync_manager::{BlockRetriever, NeedFetchResult},
     BlockStore,
@@ -18,7 +19,9 @@ use std::{sync::Arc, time::Duration};
 
 mod block_store;
 mod block_tree;
+mod forensics;
 pub mod pending_blocks;
+pub mod recovery_api;
 pub mod tracing;
 
 pub trait BlockReader: Send + Sync {
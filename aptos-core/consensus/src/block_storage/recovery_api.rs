@@ -0,0 +1,75 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Application side of block sync over the consensus network.
+//!
+//! Lagging nodes learn the highest committed block and epoch of their peers from `SyncInfo`
+//! (pushed with proposals and votes, or pulled with `SyncInfoRequest`), fetch the missing range
+//! with `BlockRetrieval` and verify it against the quorum and commit certificates before the
+//! block store replays it. The replay itself goes through [`RecoveryApi`], so the execution
+//! layer no longer has to be able to sync on its own: by default the blocks are fed to the
+//! node's block buffer manager, and embedders may pass their own implementation through
+//! `ConsensusAdapterArgs::with_recovery_api`.
+
+use aptos_executor_types::StateComputeResult;
+use async_trait::async_trait;
+use block_buffer_manager::{
+    block_buffer_manager::{BlockExecutionMeta, BlockHashRef},
+    BlockBufferManager,
+};
+use gaptos::api_types::{u256_define::BlockId, ExternalBlock};
+use std::sync::Arc;
+
+/// Applies blocks that consensus has already verified as committed.
+#[async_trait]
+pub trait RecoveryApi: Send + Sync {
+    /// Executes `block` on top of `parent_id` and returns its execution result.
+    async fn apply_block(
+        &self,
+        parent_id: BlockId,
+        block: ExternalBlock,
+        round: u64,
+        execution_meta: BlockExecutionMeta,
+    ) -> anyhow::Result<StateComputeResult>;
+
+    /// Commits applied blocks and returns once they are persisted.
+    async fn commit_blocks(&self, blocks: &[BlockHashRef], epoch: u64) -> anyhow::Result<()>;
+}
+
+/// Replays blocks through the block buffer manager, the same path live consensus uses.
+pub struct BlockBufferRecovery {
+    block_buffer_manager: Arc<BlockBufferManager>,
+}
+
+impl BlockBufferRecovery {
+    pub fn new(block_buffer_manager: Arc<BlockBufferManager>) -> Self {
+        Self { block_buffer_manager }
+    }
+}
+
+#[async_trait]
+impl RecoveryApi for BlockBufferRecovery {
+    async fn apply_block(
+        &self,
+        parent_id: BlockId,
+        block: ExternalBlock,
+        round: u64,
+        execution_meta: BlockExecutionMeta,
+    ) -> anyhow::Result<StateComputeResult> {
+        let block_id = block.block_meta.block_id;
+        let block_number = block.block_meta.block_number;
+        let epoch = block.block_meta.epoch;
+        self.block_buffer_manager
+            .set_ordered_blocks(parent_id, block, round, execution_meta)
+            .await?;
+        self.block_buffer_manager.get_executed_res(block_id, block_number, epoch).await
+    }
+
+    async fn commit_blocks(&self, blocks: &[BlockHashRef], epoch: u64) -> anyhow::Result<()> {
+        let persist_notifiers = self.block_buffer_manager.set_commit_blocks(blocks, epoch).await?;
+        for mut notifier in persist_notifiers {
+            let _ = notifier.recv().await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::NetworkPlayground, test_utils::consensus_runtime, twins::twins_node::SMRNode,
+};
+use fail::FailScenario;
+use futures::StreamExt;
+use gaptos::aptos_types::{
+    block_info::BlockInfo, on_chain_config::ProposerElectionType::RotatingProposer,
+};
+use std::{collections::HashMap, time::Duration};
+use tokio::{runtime::Runtime, time::timeout};
+
+/// Initial round timeout of the nodes, short enough to get past dropped messages quickly.
+const ROUND_TIMEOUT_MS: u64 = 1_000;
+
+/// How long `assert_liveness` waits for the requested commits.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+const VOTE_FAILPOINT: &str = "consensus::send::vote";
+const EXECUTE_FAILPOINT: &str = "consensus::pipeline::execute";
+const PRE_COMMIT_FAILPOINT: &str = "consensus::pipeline::pre_commit";
+
+/// A fault to inject into a running cluster.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// Every node drops this percentage of its outgoing votes.
+    DropVotes { percent: u32 },
+    /// Executing each ordered block takes `delay` longer on `node`, or on every node if `None`.
+    DelayExecution { node: Option<usize>, delay: Duration },
+    /// `node` panics the next time it pre-commits a block. It keeps voting but never commits
+    /// again.
+    CrashAtPreCommit { node: usize },
+}
+
+/// Whether the current thread belongs to the runtime of `node`. `SMRNode` runs each node on
+/// its own runtime whose threads are named `twin-<id>-<n>`.
+fn on_node(node: usize) -> bool {
+    let prefix = format!("twin-{node}-");
+    std::thread::current().name().is_some_and(|name| name.starts_with(&prefix))
+}
+
+impl Fault {
+    fn inject(&self) {
+        let result = match self.clone() {
+            Fault::DropVotes { percent } => fail::cfg(VOTE_FAILPOINT, &format!("{percent}%return")),
+            Fault::DelayExecution { node, delay } => {
+                fail::cfg_callback(EXECUTE_FAILPOINT, move || {
+                    if node.map_or(true, on_node) {
+                        std::thread::sleep(delay);
+                    }
+                })
+            }
+            Fault::CrashAtPreCommit { node } => {
+                fail::cfg_callback(PRE_COMMIT_FAILPOINT, move || {
+                    if on_node(node) {
+                        panic!("injected crash at pre-commit on node {node}");
+                    }
+                })
+            }
+        };
+        result.unwrap_or_else(|e| panic!("failed to inject {self:?}: {e}"));
+    }
+}
+
+/// In-process validators with fault injection and invariant checks.
+///
+/// Holds the global failpoint lock for its whole lifetime, so clusters in different tests
+/// never see each other's faults.
+pub struct ChaosCluster {
+    nodes: Vec<SMRNode>,
+    /// Ledger infos each node has committed so far, in commit order.
+    commits: Vec<Vec<BlockInfo>>,
+    runtime: Runtime,
+    _scenario: FailScenario<'static>,
+}
+
+impl ChaosCluster {
+    pub fn start(num_nodes: usize) -> Self {
+        let scenario = FailScenario::setup();
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let nodes = runtime.block_on(SMRNode::start_num_nodes_with_round_timeout(
+            num_nodes,
+            &mut playground,
+            RotatingProposer(1),
+            ROUND_TIMEOUT_MS,
+        ));
+        runtime.spawn(playground.start());
+        Self { nodes, commits: vec![vec![]; num_nodes], runtime, _scenario: scenario }
+    }
+
+    pub fn inject(&self, fault: Fault) {
+        fault.inject();
+    }
+
+    /// Removes every injected fault. A crashed node stays crashed.
+    pub fn heal(&self) {
+        for failpoint in [VOTE_FAILPOINT, EXECUTE_FAILPOINT, PRE_COMMIT_FAILPOINT] {
+            fail::remove(failpoint);
+        }
+    }
+
+    /// Waits until each of `nodes` has committed `new_commits` more times.
+    pub fn assert_liveness(&mut self, nodes: &[usize], new_commits: usize) {
+        let Self { nodes: smr_nodes, commits, runtime, .. } = self;
+        let result = runtime.block_on(timeout(LIVENESS_TIMEOUT, async {
+            for &node in nodes {
+                for _ in 0..new_commits {
+                    let ledger_info = smr_nodes[node]
+                        .commit_cb_receiver
+                        .next()
+                        .await
+                        .unwrap_or_else(|| panic!("commit channel of node {node} closed"));
+                    commits[node].push(ledger_info.commit_info().clone());
+                }
+            }
+        }));
+        if result.is_err() {
+            panic!(
+                "nodes {nodes:?} did not each commit {new_commits} more times within {:?}",
+                LIVENESS_TIMEOUT
+            );
+        }
+    }
+
+    /// Checks that commits only move forward on every node and that no two nodes committed
+    /// different blocks in the same round.
+    pub fn assert_safety(&mut self) {
+        for (node, smr_node) in self.nodes.iter_mut().enumerate() {
+            while let Ok(Some(ledger_info)) = smr_node.commit_cb_receiver.try_next() {
+                self.commits[node].push(ledger_info.commit_info().clone());
+            }
+        }
+
+        let mut committed: HashMap<(u64, u64), (usize, BlockInfo)> = HashMap::new();
+        for (node, commits) in self.commits.iter().enumerate() {
+            for pair in commits.windows(2) {
+                assert!(
+                    (pair[0].epoch(), pair[0].round()) < (pair[1].epoch(), pair[1].round()),
+                    "node {node} committed {} after {}",
+                    pair[1],
+                    pair[0]
+                );
+            }
+            for info in commits {
+                let (first_node, first) = committed
+                    .entry((info.epoch(), info.round()))
+                    .or_insert_with(|| (node, info.clone()));
+                assert_eq!(
+                    first.id(),
+                    info.id(),
+                    "node {first_node} committed {first} but node {node} committed {info}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fault injection for in-process validators.
+//!
+//! [`chaos_cluster::ChaosCluster`] starts validators on the twins network playground, injects
+//! [`chaos_cluster::Fault`]s through the consensus failpoints and checks that the survivors keep
+//! committing and never commit conflicting blocks. Failpoints are process wide, so the tests
+//! here need the `failpoints` feature:
+//!
+//! cargo test -p aptos-consensus --features failpoints chaos
+
+mod chaos_cluster;
+mod pipeline_chaos_test;
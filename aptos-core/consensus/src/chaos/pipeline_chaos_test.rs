@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chaos::chaos_cluster::{ChaosCluster, Fault};
+use std::time::Duration;
+
+#[test]
+/// Losing a share of the votes costs rounds to timeouts, but the nodes keep committing the same
+/// blocks, and run at full speed again once the votes get through.
+fn commits_through_dropped_votes() {
+    let mut cluster = ChaosCluster::start(4);
+    cluster.inject(Fault::DropVotes { percent: 30 });
+    cluster.assert_liveness(&[0, 1, 2, 3], 3);
+
+    cluster.heal();
+    cluster.assert_liveness(&[0, 1, 2, 3], 3);
+    cluster.assert_safety();
+}
+
+#[test]
+/// A node with slow execution falls behind but commits the same blocks as the others.
+fn slow_execution_does_not_fork() {
+    let mut cluster = ChaosCluster::start(4);
+    cluster.inject(Fault::DelayExecution { node: Some(1), delay: Duration::from_millis(200) });
+    cluster.assert_liveness(&[0, 1, 2, 3], 3);
+    cluster.assert_safety();
+}
+
+#[test]
+/// One node crashing at pre-commit leaves a quorum that keeps committing.
+fn crash_at_pre_commit_leaves_quorum_live() {
+    let mut cluster = ChaosCluster::start(4);
+    cluster.inject(Fault::CrashAtPreCommit { node: 0 });
+    cluster.assert_liveness(&[1, 2, 3], 3);
+    cluster.assert_safety();
+}
@@ -17,6 +17,7 @@ use gaptos::{
         transaction::SignedTransaction,
     },
 };
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Formatter},
@@ -24,6 +25,10 @@ use std::{
     sync::Arc,
 };
 
+/// Proofs of store verified per rayon job. Each one is an aggregate signature verification, so
+/// a few per job already outweigh the scheduling cost.
+const PROOFS_PER_VERIFICATION_BATCH: usize = 4;
+
 /// Types of messages that can be sent between the consensus publisher and observer
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ConsensusObserverMessage {
@@ -638,25 +643,27 @@ impl BlockPayload {
 
     /// Verifies that the block payload proofs are correctly signed according
     /// to the current epoch state. Returns an error if the data is invalid.
-    pub fn verify_payload_signatures(&self, epoch_state: &EpochState) -> Result<(), Error> {
-        // Create a dummy proof cache to verify the proofs
-        let proof_cache = ProofCache::new(1);
-
-        // TODO: parallelize the verification of the proof signatures!
-
-        // Verify each of the proof signatures
+    /// Proofs found in `proof_cache` are not verified again.
+    pub fn verify_payload_signatures(
+        &self,
+        epoch_state: &EpochState,
+        proof_cache: &ProofCache,
+    ) -> Result<(), Error> {
+        // Verify the proof signatures in parallel, a batch of proofs per job
         let validator_verifier = &epoch_state.verifier;
-        for proof_of_store in &self.transaction_payload.payload_proofs() {
-            if let Err(error) = proof_of_store.verify(validator_verifier, &proof_cache) {
-                return Err(Error::InvalidMessageError(format!(
-                    "Failed to verify the proof of store for batch: {:?}, Error: {:?}",
-                    proof_of_store.info(),
-                    error
-                )));
-            }
-        }
-
-        Ok(()) // All proofs are correctly signed
+        self.transaction_payload
+            .payload_proofs()
+            .par_iter()
+            .with_min_len(PROOFS_PER_VERIFICATION_BATCH)
+            .try_for_each(|proof_of_store| {
+                proof_of_store.verify(validator_verifier, proof_cache).map_err(|error| {
+                    Error::InvalidMessageError(format!(
+                        "Failed to verify the proof of store for batch: {:?}, Error: {:?}",
+                        proof_of_store.info(),
+                        error
+                    ))
+                })
+            })
     }
 }
 
@@ -1135,7 +1142,7 @@ mod test {
         let epoch_state = EpochState::new(current_epoch, ValidatorVerifier::new(vec![]));
 
         // Verify the block payload signatures and ensure it passes
-        block_payload.verify_payload_signatures(&epoch_state).unwrap();
+        block_payload.verify_payload_signatures(&epoch_state, &ProofCache::new(1)).unwrap();
 
         // Create an epoch state for the current epoch (with a non-empty verifier)
         let validator_signer = ValidatorSigner::random(None);
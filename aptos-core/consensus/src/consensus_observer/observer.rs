@@ -27,7 +27,9 @@ use crate::{
     pipeline::execution_client::TExecutionClient,
     state_replication::StateComputerCommitCallBackType,
 };
-use aptos_consensus_types::{pipeline, pipelined_block::PipelinedBlock};
+use aptos_consensus_types::{
+    pipeline, pipelined_block::PipelinedBlock, proof_of_store::ProofCache,
+};
 use futures::{
     future::{AbortHandle, Abortable},
     StreamExt,
@@ -59,6 +61,7 @@ use gaptos::{
     },
     move_core_types::account_address::AccountAddress,
 };
+use proposer_reth_map::EpochContext;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::{sync::mpsc::UnboundedSender, time::interval};
 use tokio_stream::wrappers::IntervalStream;
@@ -127,6 +130,14 @@ impl ConsensusObserver {
         // Get the consensus observer config
         let consensus_observer_config = node_config.consensus_observer;
 
+        // Create the proof cache shared by the payload verifications, sized like the one of
+        // the validator quorum store
+        let proof_cache = ProofCache::builder()
+            .max_capacity(node_config.consensus.proof_cache_capacity)
+            .initial_capacity(1_000)
+            .time_to_live(Duration::from_secs(20))
+            .build();
+
         // Create the consensus observer
         Self {
             node_config,
@@ -135,7 +146,8 @@ impl ConsensusObserver {
             quorum_store_enabled: false, // Updated on epoch changes
             root: Arc::new(Mutex::new(root)),
             ordered_block_store: OrderedBlockStore::new(consensus_observer_config),
-            block_payload_store: BlockPayloadStore::new(consensus_observer_config),
+            block_payload_store: BlockPayloadStore::new(consensus_observer_config)
+                .with_proof_cache(proof_cache),
             pending_block_store: PendingBlockStore::new(consensus_observer_config),
             execution_client,
             sync_handle: None,
@@ -418,6 +430,7 @@ impl ConsensusObserver {
             req: CommitMessage::Decision(pipeline::commit_decision::CommitDecision::new(
                 commit_decision.commit_proof().clone(),
             )),
+            sender: None,
             protocol: ProtocolId::ConsensusDirectSendCompressed,
             response_sender,
         };
@@ -514,7 +527,8 @@ impl ConsensusObserver {
         let epoch_state = self.get_epoch_state();
         let verified_payload = if block_epoch == epoch_state.epoch {
             // Verify the block proof signatures
-            if let Err(error) = block_payload.verify_payload_signatures(&epoch_state) {
+            let proof_cache = self.block_payload_store.proof_cache();
+            if let Err(error) = block_payload.verify_payload_signatures(&epoch_state, proof_cache) {
                 error!(LogSchema::new(LogEntry::ConsensusObserver).message(&format!(
                     "Failed to verify block payload signatures! Ignoring block: {:?}. Error: {:?}",
                     block_payload.block, error
@@ -1009,15 +1023,12 @@ impl ConsensusObserver {
     /// Waits for a new epoch to start
     async fn wait_for_epoch_start(&mut self) {
         // Extract the epoch state and on-chain configs
-        let (epoch_state, consensus_config, execution_config, randomness_config) = if let Some(
-            reconfig_events,
-        ) =
-            &mut self.reconfig_events
-        {
-            extract_on_chain_configs(&self.node_config, reconfig_events).await
-        } else {
-            panic!("Reconfig events are required to wait for a new epoch to start! Something has gone wrong!")
-        };
+        let (epoch_state, epoch_context, consensus_config, execution_config, randomness_config) =
+            if let Some(reconfig_events) = &mut self.reconfig_events {
+                extract_on_chain_configs(&self.node_config, reconfig_events).await
+            } else {
+                panic!("Reconfig events are required to wait for a new epoch to start! Something has gone wrong!")
+            };
 
         // Update the local epoch state and quorum store config
         self.epoch_state = Some(epoch_state.clone());
@@ -1057,6 +1068,7 @@ impl ConsensusObserver {
                 rand_msg_rx,
                 0,
                 None,
+                epoch_context,
             )
             .await;
     }
@@ -1187,7 +1199,13 @@ fn check_root_epoch_and_round(
 async fn extract_on_chain_configs(
     node_config: &NodeConfig,
     reconfig_events: &mut ReconfigNotificationListener<DbBackedOnChainConfig>,
-) -> (Arc<EpochState>, OnChainConsensusConfig, OnChainExecutionConfig, OnChainRandomnessConfig) {
+) -> (
+    Arc<EpochState>,
+    Arc<EpochContext>,
+    OnChainConsensusConfig,
+    OnChainExecutionConfig,
+    OnChainRandomnessConfig,
+) {
     // Fetch the next reconfiguration notification
     let reconfig_notification =
         reconfig_events.next().await.expect("Failed to get reconfig notification!");
@@ -1200,6 +1218,7 @@ async fn extract_on_chain_configs(
         epoch: on_chain_configs.epoch(),
         verifier: Arc::new((&validator_set).into()),
     });
+    let epoch_context = Arc::new(EpochContext::new(on_chain_configs.epoch(), &validator_set));
 
     // Extract the consensus config (or use the default if it's missing)
     let onchain_consensus_config: anyhow::Result<OnChainConsensusConfig> = on_chain_configs.get();
@@ -1244,7 +1263,7 @@ async fn extract_on_chain_configs(
     );
 
     // Return the extracted epoch state and on-chain configs
-    (epoch_state, consensus_config, execution_config, onchain_randomness_config)
+    (epoch_state, epoch_context, consensus_config, execution_config, onchain_randomness_config)
 }
 
 /// Logs the received message using an appropriate log level
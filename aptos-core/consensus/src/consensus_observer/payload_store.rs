@@ -7,7 +7,9 @@ use crate::consensus_observer::{
     metrics,
     network_message::{BlockPayload, OrderedBlock},
 };
-use aptos_consensus_types::{common::Round, pipelined_block::PipelinedBlock};
+use aptos_consensus_types::{
+    common::Round, pipelined_block::PipelinedBlock, proof_of_store::ProofCache,
+};
 use gaptos::{
     aptos_config::config::ConsensusObserverConfig,
     aptos_infallible::Mutex,
@@ -19,6 +21,9 @@ use std::{
     sync::Arc,
 };
 
+/// Proofs of store cached by a store built without a shared cache
+const DEFAULT_PROOF_CACHE_CAPACITY: u64 = 1_000;
+
 /// The status of the block payload
 pub enum BlockPayloadStatus {
     AvailableAndVerified(BlockPayload),
@@ -33,11 +38,29 @@ pub struct BlockPayloadStore {
 
     // Block transaction payloads (indexed by epoch and round)
     block_payloads: Arc<Mutex<BTreeMap<(u64, Round), BlockPayloadStatus>>>,
+
+    // The proofs of store already verified, shared by all payloads
+    proof_cache: ProofCache,
 }
 
 impl BlockPayloadStore {
     pub fn new(consensus_observer_config: ConsensusObserverConfig) -> Self {
-        Self { consensus_observer_config, block_payloads: Arc::new(Mutex::new(BTreeMap::new())) }
+        Self {
+            consensus_observer_config,
+            block_payloads: Arc::new(Mutex::new(BTreeMap::new())),
+            proof_cache: ProofCache::new(DEFAULT_PROOF_CACHE_CAPACITY),
+        }
+    }
+
+    /// Verifies payload signatures through `proof_cache` instead of a store-local one
+    pub fn with_proof_cache(mut self, proof_cache: ProofCache) -> Self {
+        self.proof_cache = proof_cache;
+        self
+    }
+
+    /// Returns the cache of the proofs of store already verified
+    pub fn proof_cache(&self) -> &ProofCache {
+        &self.proof_cache
     }
 
     /// Returns true iff all the payloads for the given blocks
@@ -216,7 +239,9 @@ impl BlockPayloadStore {
                     if let BlockPayloadStatus::AvailableAndUnverified(block_payload) =
                         entry.get_mut()
                     {
-                        if let Err(error) = block_payload.verify_payload_signatures(epoch_state) {
+                        if let Err(error) =
+                            block_payload.verify_payload_signatures(epoch_state, &self.proof_cache)
+                        {
                             // Log the verification failure
                             error!(
                                 LogSchema::new(LogEntry::ConsensusObserver).message(&format!(
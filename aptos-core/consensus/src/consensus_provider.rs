@@ -60,6 +60,7 @@ pub fn start_consensus(
     let storage = Arc::new(StorageWriteProxy::new(
         gravity_args.consensus_db.as_ref().unwrap().clone(),
         aptos_db.reader.clone(),
+        gravity_args.block_buffer_manager.clone(),
     ));
     let quorum_store_db = Arc::new(QuorumStoreDB::new(node_config.storage.dir()));
 
@@ -71,6 +72,7 @@ pub fn start_consensus(
     let g_executor = GravityBlockExecutor::new(
         BlockExecutor::new(aptos_db),
         gravity_args.consensus_db.as_ref().unwrap().clone(),
+        gravity_args.block_buffer_manager.clone(),
     );
     let executor = Arc::new(g_executor);
     let execution_proxy = ExecutionProxy::new(
@@ -80,6 +82,7 @@ pub fn start_consensus(
         runtime.handle(),
         TransactionFilter::new(node_config.execution.transaction_filter.clone()),
         node_config.consensus.enable_pre_commit,
+        gravity_args.block_buffer_manager.clone(),
     );
 
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
@@ -128,6 +131,8 @@ pub fn start_consensus(
         vtxn_pool,
         rand_storage,
         consensus_publisher,
+        gravity_args.block_buffer_manager.clone(),
+        gravity_args.recovery_api.clone(),
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_service_events, self_receiver);
@@ -140,6 +145,7 @@ pub fn start_consensus(
 }
 
 /// A helper function to start the consensus observer
+#[allow(clippy::unwrap_used)]
 pub fn start_consensus_observer(
     node_config: &NodeConfig,
     observer_network_client: NetworkClient<ConsensusObserverMessage>,
@@ -149,6 +155,7 @@ pub fn start_consensus_observer(
     consensus_to_mempool_sender: mpsc::Sender<QuorumStoreRequest>,
     aptos_db: DbReaderWriter,
     reconfig_events: Option<ReconfigNotificationListener<DbBackedOnChainConfig>>,
+    gravity_args: &ConsensusAdapterArgs,
 ) -> Runtime {
     // Create a consensus observer runtime
     let runtime = gaptos::aptos_runtimes::spawn_named_runtime("observer".into(), None);
@@ -184,13 +191,21 @@ pub fn start_consensus_observer(
             consensus_to_mempool_sender.clone(),
             node_config.consensus.mempool_executed_txn_timeout_ms,
         ));
+        // Execute through the block buffer manager like a validator does, so the ordered
+        // blocks reach the execution layer
+        let executor = GravityBlockExecutor::new(
+            BlockExecutor::new(aptos_db.clone()),
+            gravity_args.consensus_db.as_ref().unwrap().clone(),
+            gravity_args.block_buffer_manager.clone(),
+        );
         let execution_proxy = ExecutionProxy::new(
-            Arc::new(BlockExecutor::new(aptos_db.clone())),
+            Arc::new(executor),
             txn_notifier,
             state_sync_notifier,
             runtime.handle(),
             TransactionFilter::new(node_config.execution.transaction_filter.clone()),
             node_config.consensus.enable_pre_commit,
+            gravity_args.block_buffer_manager.clone(),
         );
 
         // Create the execution proxy client
@@ -219,6 +219,86 @@ fn test_unwind_to_block() {
     assert!(db.get_highest_2chain_timeout_certificate().unwrap().is_none());
 }
 
+#[test]
+fn test_prune_keeps_retention_and_epoch_proofs() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+
+    // Epoch 1 ends at block 300, epoch 2 runs up to block 1000 with a commit every 10 blocks.
+    for block_number in 1..=1000u64 {
+        let epoch = if block_number <= 300 { 1 } else { 2 };
+        db.put::<BlockNumberSchema>(&(epoch, HashValue::random()), &block_number).unwrap();
+        db.put_randomness(&vec![(block_number, vec![0; 32])]).unwrap();
+        if block_number % 10 == 0 {
+            db.put_commit_payloads(&[(block_number, vec![1; 32])]).unwrap();
+            let next_epoch_state =
+                (block_number == 300).then(|| EpochState::new(2, ValidatorVerifier::new(vec![])));
+            let info = BlockInfo::new(
+                epoch,
+                block_number,
+                HashValue::random(),
+                HashValue::random(),
+                0,
+                block_number,
+                next_epoch_state,
+            );
+            let li = LedgerInfoWithSignatures::new(
+                LedgerInfo::new_with_block_info(
+                    info,
+                    HashValue::zero(),
+                    HashValue::random(),
+                    block_number,
+                ),
+                AggregateSignature::empty(),
+            );
+            db.put::<LedgerInfoSchema>(&block_number, &li).unwrap();
+        }
+    }
+    db.put::<EpochByBlockNumberSchema>(&300, &1).unwrap();
+    // Blocks that were never committed, one per epoch
+    let genesis = Block::make_genesis_block();
+    db.put::<BlockSchema>(&(1, genesis.id()), &genesis).unwrap();
+    db.put::<BlockSchema>(&(2, genesis.id()), &genesis).unwrap();
+    db.ledger_db.metadata_db().update_latest_ledger_info().unwrap();
+
+    // Keeping the current epoch drops epoch 1, except for its epoch-ending ledger info.
+    let summary = db.prune(PruneRetention { epochs: Some(1), blocks: None }, 900).unwrap();
+    assert_eq!(
+        summary,
+        PruneSummary {
+            pruned_below: 301,
+            blocks: 301,
+            ledger_infos: 29,
+            randomness: 300,
+            commit_payloads: 30
+        }
+    );
+    assert!(db.get::<LedgerInfoSchema>(&300).unwrap().is_some());
+    assert!(db.get::<BlockSchema>(&(1, genesis.id())).unwrap().is_none());
+    assert!(db.get::<BlockSchema>(&(2, genesis.id())).unwrap().is_some());
+    assert_eq!(db.get_pruned_below().unwrap(), 301);
+
+    // The recent blocks below the finalized one are kept whatever the retention says.
+    let summary = db.prune(PruneRetention { epochs: None, blocks: Some(100) }, 900).unwrap();
+    assert_eq!(
+        summary,
+        PruneSummary {
+            pruned_below: 644,
+            blocks: 343,
+            ledger_infos: 34,
+            randomness: 343,
+            commit_payloads: 34
+        }
+    );
+    assert_eq!(db.get_commit_payloads(0, 660).unwrap(), vec![(650, vec![1; 32])]);
+
+    // Nothing above the latest committed ledger info is pruned.
+    let summary = db.prune(PruneRetention { epochs: None, blocks: Some(0) }, 5000).unwrap();
+    assert_eq!(summary.pruned_below, 1000 - RECENT_BLOCKS_RANGE);
+    assert!(db.get_randomness(1000 - RECENT_BLOCKS_RANGE).unwrap().is_some());
+    assert!(db.get::<LedgerInfoSchema>(&300).unwrap().is_some());
+}
+
 #[test]
 fn test_reputation_anchor_uses_lagged_commit_and_epoch_boundary() {
     let tmp_dir = TempPath::new();
@@ -359,3 +439,97 @@ fn test_reputation_anchor_skips_epoch_boundary_without_epoch_block_info() {
         })
     );
 }
+
+#[test]
+fn test_double_sign_evidence_by_epoch() {
+    use crate::pipeline::evidence::DoubleSignEvidence;
+    use aptos_consensus_types::pipeline::commit_vote::CommitVote;
+    use gaptos::aptos_types::validator_signer::ValidatorSigner;
+
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+    let signer = ValidatorSigner::random(None);
+    let evidence = |epoch: u64, round: u64| {
+        let vote = || {
+            let block_info =
+                BlockInfo::new(epoch, round, HashValue::random(), HashValue::zero(), 0, 0, None);
+            let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+            CommitVote::new(signer.author(), ledger_info, &signer).unwrap()
+        };
+        DoubleSignEvidence { first: vote(), second: vote() }
+    };
+
+    let in_epoch_1 = evidence(1, 7);
+    let in_epoch_2 = evidence(2, 3);
+    db.save_double_sign_evidence(&in_epoch_2).unwrap();
+    db.save_double_sign_evidence(&in_epoch_1).unwrap();
+
+    assert_eq!(
+        db.get_double_sign_evidence(None).unwrap(),
+        vec![in_epoch_1.clone(), in_epoch_2.clone()]
+    );
+    assert_eq!(db.get_double_sign_evidence(Some(2)).unwrap(), vec![in_epoch_2]);
+    assert!(db.get_double_sign_evidence(Some(3)).unwrap().is_empty());
+}
+
+#[test]
+fn test_get_block_by_number() {
+    use aptos_consensus_types::block::block_test_utils::placeholder_certificate_for_block;
+
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+    let signer = gaptos::aptos_types::validator_signer::ValidatorSigner::random(None);
+    let genesis = Block::make_genesis_block();
+
+    // Block 2 ends epoch 1, block 3 is in the current epoch 2
+    let mut parent_qc = certificate_for_genesis();
+    for (epoch, block_number) in [(1u64, 1u64), (1, 2), (2, 3)] {
+        let block = Block::new_proposal(
+            Payload::empty(false, true),
+            block_number,
+            block_number,
+            parent_qc.clone(),
+            &signer,
+            Vec::new(),
+        )
+        .unwrap();
+        db.put::<BlockSchema>(&(epoch, block.id()), &block).unwrap();
+        db.save_block_numbers(vec![(epoch, block_number, block.id())]).unwrap();
+        parent_qc = placeholder_certificate_for_block(
+            &[signer.clone()],
+            block.id(),
+            block_number,
+            genesis.id(),
+            0,
+        );
+        if block_number == 2 {
+            let info = BlockInfo::new(
+                epoch,
+                block_number,
+                block.id(),
+                HashValue::random(),
+                0,
+                block_number,
+                Some(EpochState::new(2, ValidatorVerifier::new(vec![]))),
+            );
+            let li = LedgerInfoWithSignatures::new(
+                LedgerInfo::new_with_block_info(
+                    info,
+                    HashValue::zero(),
+                    HashValue::random(),
+                    block_number,
+                ),
+                AggregateSignature::empty(),
+            );
+            db.put::<LedgerInfoSchema>(&block_number, &li).unwrap();
+            db.put::<EpochByBlockNumberSchema>(&block_number, &epoch).unwrap();
+        }
+    }
+
+    for block_number in 1..=3 {
+        let block = db.get_block_by_number(block_number).unwrap().unwrap();
+        assert_eq!(block.block_number(), Some(block_number));
+        assert_eq!(block.round(), block_number);
+    }
+    assert!(db.get_block_by_number(4).unwrap().is_none());
+}
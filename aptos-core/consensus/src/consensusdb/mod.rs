@@ -8,6 +8,7 @@ mod ledger_db;
 pub mod schema;
 
 use crate::error::DbError;
+pub use crate::pipeline::evidence::DoubleSignEvidence;
 use anyhow::Result;
 use aptos_consensus_types::{
     block::Block, pipelined_block::PipelinedBlock, quorum_cert::QuorumCert,
@@ -21,26 +22,32 @@ use gaptos::{
         Options, DB, DEFAULT_COLUMN_FAMILY_NAME,
     },
     aptos_storage_interface::AptosDbError,
-    aptos_types::randomness::{RandMetadata, Randomness},
+    aptos_types::{
+        account_address::AccountAddress,
+        randomness::{RandMetadata, Randomness},
+    },
 };
 use ledger_db::LedgerDb;
 use rocksdb::ReadOptions;
 use schema::{
     block::BLOCK_NUMBER_CF_NAME,
     single_entry::{SingleEntryKey, SingleEntrySchema},
-    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, EPOCH_BY_BLOCK_NUMBER_CF_NAME,
-    LEDGER_INFO_CF_NAME, NODE_CF_NAME, QC_CF_NAME, RANDOMNESS_CF_NAME, SINGLE_ENTRY_CF_NAME,
+    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, COMMIT_PAYLOAD_CF_NAME, DAG_VOTE_CF_NAME,
+    DOUBLE_SIGN_EVIDENCE_CF_NAME, EPOCH_BY_BLOCK_NUMBER_CF_NAME, LEDGER_INFO_CF_NAME, NODE_CF_NAME,
+    QC_CF_NAME, RANDOMNESS_CF_NAME, SINGLE_ENTRY_CF_NAME,
 };
 pub use schema::{
     block::{BlockNumberSchema, BlockSchema},
+    commit_payload::CommitPayloadSchema,
     dag::{CertifiedNodeSchema, DagVoteSchema, NodeSchema},
     epoch_by_block_number::EpochByBlockNumberSchema,
+    evidence::DoubleSignEvidenceSchema,
     ledger_info::LedgerInfoSchema,
     quorum_certificate::QCSchema,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::Iterator,
     path::{Path, PathBuf},
     sync::Arc,
@@ -50,6 +57,9 @@ use std::{
 /// The name of the consensus db file
 pub const CONSENSUS_DB_NAME: &str = "consensus_db";
 const RECENT_BLOCKS_RANGE: u64 = 256;
+/// Block numbers of ledger infos, randomness and commit payloads deleted per write batch when
+/// pruning.
+const PRUNE_CHUNK_BLOCKS: u64 = 10_000;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct CommittedBlockAnchor {
@@ -58,6 +68,27 @@ pub struct CommittedBlockAnchor {
     pub block_hash: HashValue,
 }
 
+/// Committed history kept by [`ConsensusDB::prune`]. When both limits are set, the one that
+/// keeps more history wins.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PruneRetention {
+    /// Keep the blocks of the newest `epochs` epochs, the current one included.
+    pub epochs: Option<u64>,
+    /// Keep the newest `blocks` blocks below the finalized one.
+    pub blocks: Option<u64>,
+}
+
+/// What a [`ConsensusDB::prune`] call deleted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PruneSummary {
+    /// Committed history below this block number is gone.
+    pub pruned_below: u64,
+    pub blocks: usize,
+    pub ledger_infos: usize,
+    pub randomness: usize,
+    pub commit_payloads: usize,
+}
+
 /// Creates new physical DB checkpoint in directory specified by `checkpoint_path`.
 pub fn create_checkpoint<P: AsRef<Path> + Clone>(db_path: P, checkpoint_path: P) -> Result<()> {
     let start = Instant::now();
@@ -113,6 +144,8 @@ impl ConsensusDB {
             BLOCK_NUMBER_CF_NAME,
             EPOCH_BY_BLOCK_NUMBER_CF_NAME,
             RANDOMNESS_CF_NAME,
+            DOUBLE_SIGN_EVIDENCE_CF_NAME,
+            COMMIT_PAYLOAD_CF_NAME,
             "ordered_anchor_id", // deprecated CF
         ];
 
@@ -138,6 +171,15 @@ impl ConsensusDB {
         Self { db, node_config_set, ledger_db }
     }
 
+    /// Creates a physical checkpoint of this already-open DB in `checkpoint_path`, which can
+    /// then be opened with [`ConsensusDB::new`] as a point-in-time read copy.
+    pub fn create_checkpoint_in<P: AsRef<Path>>(&self, checkpoint_path: P) -> Result<()> {
+        let consensus_db_checkpoint_path = checkpoint_path.as_ref().join(CONSENSUS_DB_NAME);
+        std::fs::remove_dir_all(&consensus_db_checkpoint_path).unwrap_or(());
+        self.db.create_checkpoint(&consensus_db_checkpoint_path)?;
+        Ok(())
+    }
+
     /// Returns the newest committed execution block whose consensus round is no newer than
     /// `target_round`. At the beginning of an epoch, the previous epoch's reconfiguration block
     /// is used because its post-state contains the new validator set and reset performance data.
@@ -431,6 +473,44 @@ impl ConsensusDB {
         Ok(block)
     }
 
+    /// Looks up an ordered block by its block number. A block carrying a ledger info is found by
+    /// the committed block id, any other one by scanning the block numbers of its epoch only.
+    pub fn get_block_by_number(&self, block_number: u64) -> Result<Option<Block>, DbError> {
+        if let Some(ledger_info) = self.get::<LedgerInfoSchema>(&block_number)? {
+            let commit_info = ledger_info.ledger_info().commit_info();
+            if let Some(block) = self.get_block(commit_info.epoch(), commit_info.id())? {
+                if block.block_number() == Some(block_number) {
+                    return Ok(Some(block));
+                }
+            }
+        }
+        // A block belongs to the first epoch ending at or after it, or to the current one
+        let epoch_ends = self.get_range::<EpochByBlockNumberSchema>(&block_number, &u64::MAX)?;
+        let epoch = match epoch_ends.into_iter().next() {
+            Some((_, epoch)) => epoch,
+            None => self.get_max_epoch(),
+        };
+        let start_key = (epoch, HashValue::zero());
+        let end_key = (epoch, HashValue::new([u8::MAX; HashValue::LENGTH]));
+        let found = self
+            .get_range_with_filter::<BlockNumberSchema, _>(&start_key, &end_key, |(_, bn)| {
+                *bn == block_number
+            })?
+            .into_iter()
+            .next();
+        match found {
+            Some(((epoch, block_id), _)) => self.get_block(epoch, block_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the execution block hash recorded in the ledger info committed at `block_number`.
+    /// Only the last block of each commit carries a ledger info, so blocks committed in the
+    /// middle of a batch return `None`.
+    pub fn get_committed_block_hash(&self, block_number: u64) -> Option<HashValue> {
+        self.ledger_db.metadata_db().get_block_hash(block_number)
+    }
+
     pub fn get_qc(&self, epoch: u64, block_id: HashValue) -> Result<Option<QuorumCert>, DbError> {
         self.get::<QCSchema>(&(epoch, block_id))
     }
@@ -477,10 +557,194 @@ impl ConsensusDB {
         Ok(self.get::<schema::randomness::RandomnessSchema>(&block_number)?)
     }
 
+    /// Store the payloads the execution layer attached to committed blocks
+    pub fn put_commit_payloads(&self, blocks: &[(u64, Vec<u8>)]) -> Result<(), DbError> {
+        if blocks.is_empty() {
+            return Ok(());
+        }
+        let mut batch = SchemaBatch::new();
+        for (block_number, payload) in blocks {
+            batch.put::<CommitPayloadSchema>(block_number, payload)?;
+        }
+        self.commit(batch)
+    }
+
+    /// Get the commit payloads of the blocks in `start..end`, ordered by block number
+    pub fn get_commit_payloads(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<(u64, Vec<u8>)>, DbError> {
+        self.get_range::<CommitPayloadSchema>(&start, &end)
+    }
+
+    /// Store evidence of a validator signing conflicting commit votes. Pruning never deletes it.
+    pub fn save_double_sign_evidence(&self, evidence: &DoubleSignEvidence) -> Result<(), DbError> {
+        self.put::<DoubleSignEvidenceSchema>(
+            &(evidence.epoch(), evidence.round(), evidence.author()),
+            evidence,
+        )
+    }
+
+    /// Get the double sign evidence of `epoch`, or of every epoch, ordered by epoch and round
+    pub fn get_double_sign_evidence(
+        &self,
+        epoch: Option<u64>,
+    ) -> Result<Vec<DoubleSignEvidence>, DbError> {
+        let evidence = match epoch {
+            Some(epoch) => self.get_range::<DoubleSignEvidenceSchema>(
+                &(epoch, 0, AccountAddress::ZERO),
+                &(epoch.saturating_add(1), 0, AccountAddress::ZERO),
+            )?,
+            None => self.get_all::<DoubleSignEvidenceSchema>()?,
+        };
+        Ok(evidence.into_iter().map(|(_, evidence)| evidence).collect())
+    }
+
+    /// Returns the block number below which committed history has been pruned.
+    pub fn get_pruned_below(&self) -> Result<u64, DbError> {
+        let pruned_below = self.db.get::<SingleEntrySchema>(&SingleEntryKey::PrunedBelow)?;
+        Ok(pruned_below.and_then(|bytes| bytes.try_into().ok()).map_or(0, u64::from_be_bytes))
+    }
+
+    /// Returns the lowest block number `retention` keeps once the execution layer has
+    /// persisted `finalized_block_number`. Nothing above the latest committed ledger info is
+    /// pruned, and the `RECENT_BLOCKS_RANGE` blocks below the finalized one are always kept
+    /// because recovery reloads them.
+    pub fn prune_target(
+        &self,
+        retention: PruneRetention,
+        finalized_block_number: u64,
+    ) -> Result<u64, DbError> {
+        let committed = self
+            .ledger_db
+            .metadata_db()
+            .get_latest_ledger_info()
+            .map_or(0, |ledger_info| ledger_info.ledger_info().block_number());
+        let finalized = finalized_block_number.min(committed);
+
+        let by_blocks = retention.blocks.map(|blocks| finalized.saturating_sub(blocks));
+        let by_epochs = match retention.epochs {
+            Some(epochs) => {
+                // The last block of every epoch that ended before the finalized block
+                let epoch_ends = self.get_range::<EpochByBlockNumberSchema>(&0, &finalized)?;
+                Some(
+                    epoch_ends
+                        .into_iter()
+                        .rev()
+                        .nth(epochs.saturating_sub(1) as usize)
+                        .map_or(0, |(block_number, _)| block_number + 1),
+                )
+            }
+            None => None,
+        };
+        let target = match (by_blocks, by_epochs) {
+            (Some(by_blocks), Some(by_epochs)) => by_blocks.min(by_epochs),
+            (Some(target), None) | (None, Some(target)) => target,
+            (None, None) => 0,
+        };
+        Ok(target.min(finalized.saturating_sub(RECENT_BLOCKS_RANGE)))
+    }
+
+    /// Deletes committed history below [`Self::prune_target`]: blocks, QCs, block numbers,
+    /// ledger infos, randomness and commit payloads. Epochs whose committed blocks are all pruned
+    /// lose their uncommitted blocks too. Epoch-ending ledger infos and the epoch index are kept,
+    /// since epoch change proofs and block retrieval by epoch are built from them.
+    pub fn prune(
+        &self,
+        retention: PruneRetention,
+        finalized_block_number: u64,
+    ) -> Result<PruneSummary, DbError> {
+        let pruned_below = self.get_pruned_below()?;
+        let target = self.prune_target(retention, finalized_block_number)?;
+        let mut summary = PruneSummary { pruned_below, ..Default::default() };
+        if target <= pruned_below {
+            return Ok(summary);
+        }
+
+        // Step 1: (epoch, block_id)-keyed CFs, one batch per epoch, oldest epoch first.
+        for epoch in 0..=self.get_max_epoch() {
+            let start_key = (epoch, HashValue::zero());
+            let end_key = (epoch, HashValue::new([u8::MAX; HashValue::LENGTH]));
+            let block_numbers = self.get_range::<BlockNumberSchema>(&start_key, &end_key)?;
+            if block_numbers.is_empty() {
+                continue;
+            }
+            let epoch_retained = block_numbers.iter().any(|(_, number)| *number >= target);
+            let mut keys: HashSet<(u64, HashValue)> = block_numbers
+                .iter()
+                .filter(|(_, number)| *number < target)
+                .map(|(key, _)| *key)
+                .collect();
+            if !epoch_retained {
+                keys.extend(
+                    self.get_range::<BlockSchema>(&start_key, &end_key)?
+                        .into_iter()
+                        .map(|(key, _)| key),
+                );
+            }
+
+            let mut batch = SchemaBatch::new();
+            for key in &keys {
+                batch.delete::<BlockSchema>(key)?;
+                batch.delete::<QCSchema>(key)?;
+                batch.delete::<BlockNumberSchema>(key)?;
+            }
+            self.commit(batch)?;
+            summary.blocks += keys.len();
+
+            if epoch_retained {
+                // Later epochs only hold blocks at or above the target.
+                break;
+            }
+        }
+
+        // Step 2: block_number-keyed CFs, in chunks from where the last prune stopped.
+        let mut start = pruned_below;
+        while start < target {
+            let end = target.min(start.saturating_add(PRUNE_CHUNK_BLOCKS));
+            let mut batch = SchemaBatch::new();
+            for (block_number, ledger_info) in self.get_range::<LedgerInfoSchema>(&start, &end)? {
+                if !ledger_info.ledger_info().ends_epoch() {
+                    batch.delete::<LedgerInfoSchema>(&block_number)?;
+                    summary.ledger_infos += 1;
+                }
+            }
+            for (block_number, _) in
+                self.get_range::<schema::randomness::RandomnessSchema>(&start, &end)?
+            {
+                batch.delete::<schema::randomness::RandomnessSchema>(&block_number)?;
+                summary.randomness += 1;
+            }
+            for (block_number, _) in self.get_range::<CommitPayloadSchema>(&start, &end)? {
+                batch.delete::<CommitPayloadSchema>(&block_number)?;
+                summary.commit_payloads += 1;
+            }
+            batch.put::<SingleEntrySchema>(
+                &SingleEntryKey::PrunedBelow,
+                &end.to_be_bytes().to_vec(),
+            )?;
+            self.commit(batch)?;
+            start = end;
+        }
+        summary.pruned_below = target;
+
+        info!(
+            "ConsensusDB::prune: pruned below block {}, deleted {} blocks, {} ledger_infos, {} \
+             randomness entries, {} commit payloads",
+            target,
+            summary.blocks,
+            summary.ledger_infos,
+            summary.randomness,
+            summary.commit_payloads
+        );
+        Ok(summary)
+    }
+
     /// Unwind the consensus DB to the given target block number.
     /// All data for blocks with block_number > target_block_number will be deleted.
     /// This includes: blocks, QCs, block numbers, ledger info, epoch-by-block-number,
-    /// randomness, last vote, and highest 2-chain timeout certificate.
+    /// randomness, commit payloads, last vote, and highest 2-chain timeout certificate.
     pub fn unwind_to_block(
         &self,
         target_block_number: u64,
@@ -624,6 +888,13 @@ impl ConsensusDB {
             batch.delete::<schema::randomness::RandomnessSchema>(bn)?;
         }
 
+        // CommitPayloadSchema
+        let commit_payload_entries =
+            self.get_range::<CommitPayloadSchema>(&range_start, &u64::MAX)?;
+        for (bn, _) in &commit_payload_entries {
+            batch.delete::<CommitPayloadSchema>(bn)?;
+        }
+
         // Step 3: Clear stale vote and timeout certificate.
         batch.delete::<schema::single_entry::SingleEntrySchema>(
             &schema::single_entry::SingleEntryKey::LastVote,
@@ -650,11 +921,13 @@ impl ConsensusDB {
 
         info!(
             "ConsensusDB::unwind_to_block complete: deleted {} blocks, \
-             {} ledger_infos, {} epoch_entries, {} randomness entries. Target: {}",
+             {} ledger_infos, {} epoch_entries, {} randomness entries, {} commit payloads. \
+             Target: {}",
             deleted_blocks,
             ledger_entries.len(),
             epoch_entries.len(),
             randomness_entries.len(),
+            commit_payload_entries.len(),
             target_block_number
         );
 
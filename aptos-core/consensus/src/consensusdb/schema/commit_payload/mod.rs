@@ -0,0 +1,42 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for commit payloads.
+//!
+//! Opaque payload the execution layer attached to a committed block, e.g. its receipts root or
+//! event digests, identified by the block number like the ledger info that commits it.
+//! ```text
+//! |<---key---->|<--value-->|
+//! | block num  |  payload  |
+//! ```
+
+use super::{ensure_slice_len_eq, COMMIT_PAYLOAD_CF_NAME};
+use crate::define_schema;
+use anyhow::Result;
+use gaptos::aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+
+define_schema!(CommitPayloadSchema, u64, Vec<u8>, COMMIT_PAYLOAD_CF_NAME);
+
+impl KeyCodec<CommitPayloadSchema> for u64 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, std::mem::size_of::<Self>())?;
+        Ok(u64::from_be_bytes(data.try_into()?))
+    }
+}
+
+impl ValueCodec<CommitPayloadSchema> for Vec<u8> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.clone())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(data.to_vec())
+    }
+}
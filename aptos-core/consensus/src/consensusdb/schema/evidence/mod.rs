@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for double sign evidence.
+//!
+//! BCS-encoded evidence identified by the epoch and round of the conflicting commit votes and
+//! the validator that signed them.
+//! ```text
+//! |<----------key---------->|<-------value------->|
+//! | epoch | round | author  | DoubleSignEvidence  |
+//! ```
+
+use super::{ensure_slice_len_eq, DOUBLE_SIGN_EVIDENCE_CF_NAME};
+use crate::{define_schema, pipeline::evidence::DoubleSignEvidence};
+use anyhow::Result;
+use gaptos::{
+    aptos_schemadb::{
+        schema::{KeyCodec, ValueCodec},
+        ColumnFamilyName,
+    },
+    aptos_types::account_address::AccountAddress,
+};
+
+define_schema!(
+    DoubleSignEvidenceSchema,
+    (u64, u64, AccountAddress),
+    DoubleSignEvidence,
+    DOUBLE_SIGN_EVIDENCE_CF_NAME
+);
+
+impl KeyCodec<DoubleSignEvidenceSchema> for (u64, u64, AccountAddress) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (epoch, round, author) = self;
+        let mut key_bytes = Vec::with_capacity(16 + AccountAddress::LENGTH);
+        key_bytes.extend_from_slice(&epoch.to_be_bytes());
+        key_bytes.extend_from_slice(&round.to_be_bytes());
+        key_bytes.extend_from_slice(author.as_ref());
+        Ok(key_bytes)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 16 + AccountAddress::LENGTH)?;
+        let epoch = u64::from_be_bytes(data[0..8].try_into()?);
+        let round = u64::from_be_bytes(data[8..16].try_into()?);
+        let author = AccountAddress::from_bytes(&data[16..])?;
+        Ok((epoch, round, author))
+    }
+}
+
+impl ValueCodec<DoubleSignEvidenceSchema> for DoubleSignEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub(crate) mod block;
+pub mod commit_payload;
 pub(crate) mod dag;
 pub mod epoch_by_block_number;
+pub mod evidence;
 pub mod ledger_info;
 pub(crate) mod quorum_certificate;
 pub(crate) mod randomness;
@@ -15,6 +17,8 @@ use anyhow::{ensure, Result};
 pub const LEDGER_INFO_CF_NAME: ColumnFamilyName = "ledger_info";
 pub const EPOCH_BY_BLOCK_NUMBER_CF_NAME: ColumnFamilyName = "epoch_by_block_number";
 pub const RANDOMNESS_CF_NAME: ColumnFamilyName = "randomness";
+pub const DOUBLE_SIGN_EVIDENCE_CF_NAME: ColumnFamilyName = "double_sign_evidence";
+pub const COMMIT_PAYLOAD_CF_NAME: ColumnFamilyName = "commit_payload";
 
 pub(crate) fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
     ensure!(data.len() == len, "Unexpected data len {}, expected {}.", data.len(), len,);
@@ -37,6 +37,8 @@ pub enum SingleEntryKey {
     LastVote = 0,
     // Two chain timeout cert
     Highest2ChainTimeoutCert = 1,
+    // Committed history below this block number has been pruned
+    PrunedBelow = 2,
 }
 
 impl KeyCodec<SingleEntrySchema> for SingleEntryKey {
@@ -30,6 +30,185 @@ use gaptos::{
 use once_cell::sync::Lazy;
 use std::sync::Arc;
 
+/// Number of payload pulls that missed their deadline, by whether a partial proposal was made.
+pub static PAYLOAD_PULL_DEADLINE_EXCEEDED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_payload_pull_deadline_exceeded_count",
+        "Number of payload pulls that missed their deadline",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+/// Number of commit messages verified together by the buffer manager.
+pub static COMMIT_MSG_VERIFY_BATCH_SIZE: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "aptos_consensus_commit_msg_verify_batch_size",
+        "Number of commit messages verified together in one batch",
+        exponential_buckets(1.0, 2.0, 10).unwrap()
+    )
+    .unwrap()
+});
+
+/// Number of randomness rounds decided, see `rand::rand_gen`.
+pub static RAND_ROUNDS_COMPLETED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_rand_rounds_completed_count",
+        "Number of randomness rounds decided"
+    )
+    .unwrap()
+});
+
+/// Number of randomness shares accepted, by fast or slow path.
+pub static RAND_SHARES_RECEIVED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_rand_shares_received_count",
+        "Number of randomness shares accepted",
+        &["path"]
+    )
+    .unwrap()
+});
+
+/// Number of randomness rounds whose shares could not be aggregated. Each one stalls
+/// randomness, and the chain with it, until the epoch changes.
+pub static RAND_RECONSTRUCTION_FAILURES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_rand_reconstruction_failures_count",
+        "Number of randomness rounds whose shares could not be aggregated"
+    )
+    .unwrap()
+});
+
+/// Epoch of the last failed randomness aggregation, -1 if there was none.
+pub static RAND_LAST_RECONSTRUCTION_FAILURE_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
+    let gauge = register_int_gauge!(
+        "aptos_consensus_rand_last_reconstruction_failure_epoch",
+        "Epoch of the last failed randomness aggregation"
+    )
+    .unwrap();
+    gauge.set(-1);
+    gauge
+});
+
+/// Unix time in seconds at which randomness was last decided.
+pub static RAND_LAST_DECIDED_TIMESTAMP: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_rand_last_decided_timestamp_secs",
+        "Unix time at which randomness was last decided"
+    )
+    .unwrap()
+});
+
+/// Number of conflicting commit vote pairs detected, see `pipeline::evidence`.
+pub static DOUBLE_SIGN_EVIDENCE_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_double_sign_evidence_count",
+        "Number of validators caught signing conflicting commit votes"
+    )
+    .unwrap()
+});
+
+/// Number of payloads refused because they need a protocol feature that is not active yet, by
+/// feature and by whether this node or a peer proposed them.
+pub static GATED_PAYLOAD_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_gated_payload_rejected_count",
+        "Number of payloads refused because their protocol feature is not active",
+        &["feature", "proposer"]
+    )
+    .unwrap()
+});
+
+/// Number of rounds in which the leader proposed nothing because it had no transactions, see
+/// `ProposalGenerator::with_skip_empty_blocks`.
+pub static SKIPPED_EMPTY_PROPOSAL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_skipped_empty_proposal_count",
+        "Number of rounds in which the leader skipped an empty proposal"
+    )
+    .unwrap()
+});
+
+/// Block size limit derived from the execution reports of recent blocks, see
+/// `liveness::execution_feedback`.
+pub static PROPOSER_EXECUTION_FEEDBACK_MAX_TXNS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_proposer_execution_feedback_max_txns",
+        "Max block txns derived from the gas and execution time of recent blocks"
+    )
+    .unwrap()
+});
+
+/// Number of peer proposals not voted for because their timestamp is too far ahead of the local
+/// clock.
+pub static PROPOSAL_TIMESTAMP_DRIFT_REJECTED_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_proposal_timestamp_drift_rejected_count",
+        "Number of proposals rejected because their timestamp is too far in the future"
+    )
+    .unwrap()
+});
+
+/// Number of batches the quorum store refused to store, by author and by the limit they hit:
+/// the author's own quota, the global cap over all authors, or an expiration in the past.
+pub static QUORUM_STORE_REJECTED_BATCH_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_quorum_store_rejected_batch_count",
+        "Number of batches the quorum store refused to store",
+        &["author", "reason"]
+    )
+    .unwrap()
+});
+
+/// Number of times the execution root timed out waiting for the execution layer, by whether
+/// the buffer manager retried it or reset the pipeline, see `pipeline::execution_watchdog`.
+pub static EXECUTION_TIMEOUT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_execution_timeout_count",
+        "Number of times a block timed out waiting for the execution layer",
+        &["action"]
+    )
+    .unwrap()
+});
+
+/// Number of misbehaviors of peers, by kind, see `peer_score`.
+pub static PEER_MISBEHAVIOR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_peer_misbehavior_count",
+        "Number of misbehaviors of peers sending consensus messages",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+/// Number of messages dropped because their peer was banned or over its rate limit.
+pub static PEER_DROPPED_MSGS_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_consensus_peer_dropped_msgs_count",
+        "Number of consensus messages dropped by the peer scores"
+    )
+    .unwrap()
+});
+
+/// Rounds between the highest round peers proved to be in with a verified sync info and the
+/// local round, read by the readiness probe.
+pub static ROUNDS_BEHIND: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_rounds_behind",
+        "Rounds the highest certified peer round is ahead of the local round"
+    )
+    .unwrap()
+});
+
+/// Unix time in seconds `ROUNDS_BEHIND` was last set, 0 if it never was.
+pub static ROUNDS_BEHIND_UPDATED_AT: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_rounds_behind_updated_at_secs",
+        "Unix time the rounds behind gauge was last set"
+    )
+    .unwrap()
+});
+
 pub fn log_executor_error_occurred(
     e: ExecutorError,
     counter: &Lazy<IntCounterVec>,
@@ -5,6 +5,7 @@
 use crate::{
     block_storage::{
         pending_blocks::PendingBlocks,
+        recovery_api::RecoveryApi,
         tracing::{observe_block, BlockStage},
         BlockReader, BlockStore,
     },
@@ -13,9 +14,12 @@ use crate::{
     error::{error_kind, DbError},
     liveness::{
         cached_proposer_election::CachedProposerElection,
+        execution_feedback::{ExecutionFeedback, ExecutionFeedbackConfig},
         leader_reputation::{
-            extract_epoch_to_proposers, AptosDBBackend, ConsensusDBReputationAnchorBackend,
-            LeaderReputation, ProposerAndVoterHeuristic, ReputationHeuristic,
+            extract_epoch_to_proposers, AptosDBBackend, ConsensusDBProposalHistory,
+            ConsensusDBReputationAnchorBackend, LatencyAwareHeuristic, LeaderReputation,
+            ProposerAndVoterHeuristic, ReputationHeuristic, LATENCY_PENALTY_WEIGHT,
+            LATENCY_THRESHOLD_PERCENT,
         },
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -39,8 +43,10 @@ use crate::{
         mixed::MixedPayloadClient, user::quorum_store_client::QuorumStoreClient, PayloadClient,
     },
     payload_manager::{DirectMempoolPayloadManager, TPayloadManager},
+    peer_score::{peer_scores, verification_failure},
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
     pipeline::execution_client::TExecutionClient,
+    protocol_features::{ProtocolFeature, ProtocolFeatures},
     quorum_store::{
         quorum_store_builder::{DirectMempoolInnerBuilder, InnerBuilder, QuorumStoreBuilder},
         quorum_store_coordinator::CoordinatorCommand,
@@ -51,12 +57,12 @@ use crate::{
         types::{AugmentedData, RandConfig},
     },
     recovery_manager::RecoveryManager,
-    round_manager::{self, RoundManager, UnverifiedEvent, VerifiedEvent},
+    round_manager::{self, max_timestamp_drift, RoundManager, UnverifiedEvent, VerifiedEvent},
     util::time_service::TimeService,
 };
 use anyhow::{anyhow, bail, ensure, Context};
 use aptos_consensus_types::{
-    common::{Author, Round},
+    common::{Author, Payload, Round},
     delayed_qc_msg::DelayedQcMsg,
     epoch_retrieval::EpochRetrievalRequest,
     proof_of_store::ProofCache,
@@ -64,7 +70,7 @@ use aptos_consensus_types::{
 };
 use aptos_mempool::QuorumStoreRequest;
 use aptos_safety_rules::{safety_rules_manager, PersistentSafetyStorage, SafetyRulesManager};
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::{BlockBufferManager, ConsensusEvent};
 use fail::fail_point;
 use futures::{
     channel::{
@@ -80,7 +86,7 @@ use gaptos::{
     aptos_config::{
         config::{
             ConsensusConfig, DagConsensusConfig, ExecutionConfig, NodeConfig, NodeType,
-            QcAggregatorType,
+            QcAggregatorType, SafetyRulesConfig,
         },
         network_id::{NetworkId, PeerNetworkId},
     },
@@ -117,7 +123,7 @@ use gaptos::{
 };
 use itertools::Itertools;
 use mini_moka::sync::Cache;
-use proposer_reth_map;
+use proposer_reth_map::EpochContext;
 use rand::{prelude::StdRng, thread_rng, Rng, SeedableRng};
 use std::{
     cmp::Ordering,
@@ -135,6 +141,10 @@ const PROPOSER_ELECTION_CACHING_WINDOW_ADDITION: usize = 3;
 /// used for fetching data from DB.
 const PROPOSER_ROUND_BEHIND_STORAGE_BUFFER: usize = 10;
 
+#[cfg(test)]
+#[path = "epoch_manager_test.rs"]
+mod epoch_manager_test;
+
 #[allow(clippy::large_enum_variant)]
 pub enum LivenessStorageData {
     FullRecoveryData(RecoveryData),
@@ -154,6 +164,7 @@ pub struct EpochManager<P: OnChainConfigProvider> {
     network_sender: ConsensusNetworkClient<NetworkClient<ConsensusMsg>>,
     timeout_sender: gaptos::aptos_channels::Sender<Round>,
     quorum_store_enabled: bool,
+    protocol_features: ProtocolFeatures,
     quorum_store_to_mempool_sender: Sender<QuorumStoreRequest>,
     execution_client: Arc<dyn TExecutionClient>,
     storage: Arc<dyn PersistentLivenessStorage>,
@@ -191,11 +202,15 @@ pub struct EpochManager<P: OnChainConfigProvider> {
     proof_cache: ProofCache,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     pending_blocks: Arc<Mutex<PendingBlocks>>,
+    // Shared with the execution client, pipeline and block store of the current epoch
+    epoch_context: Arc<EpochContext>,
     key_storage: PersistentSafetyStorage,
     // For fast block sync from VFN
     sync_info_tx: mpsc::Sender<Option<(Author, Box<SyncInfo>)>>,
     sync_info_rx: mpsc::Receiver<Option<(Author, Box<SyncInfo>)>>,
     inflight_request_sync_info: bool,
+    block_buffer_manager: Arc<BlockBufferManager>,
+    recovery_api: Arc<dyn RecoveryApi>,
 }
 
 /// For non-validator consensus paths (sync-path BlockRetrieval / SyncInfoRequest /
@@ -229,6 +244,8 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         vtxn_pool: VTxnPoolState,
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+        recovery_api: Arc<dyn RecoveryApi>,
     ) -> Self {
         let node_type = NodeType::extract_from_config(node_config);
         // Read author from identity_blob_path in safety_rules config
@@ -272,6 +289,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             timeout_sender,
             // This default value is updated at epoch start
             quorum_store_enabled: true,
+            protocol_features: ProtocolFeatures::default(),
             quorum_store_to_mempool_sender,
             execution_client,
             storage,
@@ -306,10 +324,13 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 .build(),
             consensus_publisher,
             pending_blocks: Arc::new(Mutex::new(PendingBlocks::new())),
+            epoch_context: Arc::new(EpochContext::default()),
             key_storage,
             sync_info_tx,
             sync_info_rx,
             inflight_request_sync_info: false,
+            block_buffer_manager,
+            recovery_api,
         }
     }
 
@@ -384,6 +405,25 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                                 proposer_window_size,
                                 leader_reputation_type.use_reputation_window_from_stale_end(),
                             ));
+                        // Once activated for the epoch, leadership is also steered away from
+                        // proposers that are slow or fail rounds in the committed blocks up to
+                        // the reputation anchor
+                        let heuristic: Box<dyn ReputationHeuristic> = if self
+                            .protocol_features
+                            .is_active(ProtocolFeature::LatencyAwareReputation)
+                        {
+                            Box::new(LatencyAwareHeuristic::new(
+                                heuristic,
+                                Arc::new(ConsensusDBProposalHistory::new(
+                                    self.storage.consensus_db(),
+                                )),
+                                proposer_window_size,
+                                LATENCY_THRESHOLD_PERCENT,
+                                LATENCY_PENALTY_WEIGHT,
+                            ))
+                        } else {
+                            heuristic
+                        };
                         (
                             heuristic,
                             std::cmp::max(proposer_window_size, voter_window_size),
@@ -772,10 +812,34 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             self.config.quorum_store_pull_timeout_ms,
             self.config.wait_for_full_blocks_above_recent_fill_threshold,
             self.config.wait_for_full_blocks_above_pending_blocks,
-        );
+        )
+        .with_empty_payload(Payload::empty(
+            self.quorum_store_enabled,
+            self.config.quorum_store.allow_batches_without_pos_in_proposal,
+        ));
         (payload_manager, payload_client, quorum_store_builder)
     }
 
+    /// Tells embedders about the new epoch, and about its validators when they changed.
+    fn publish_epoch_events(&self, epoch_state: &EpochState) {
+        let validators = |state: &EpochState| -> Vec<(AccountAddress, u64)> {
+            state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .map(|address| (address, state.verifier.get_voting_power(&address).unwrap_or(0)))
+                .collect()
+        };
+        let epoch = epoch_state.epoch;
+        self.block_buffer_manager.publish_event(ConsensusEvent::EpochStarted { epoch });
+        let new_validators = validators(epoch_state);
+        if self.epoch_state.as_deref().map(validators).as_ref() != Some(&new_validators) {
+            self.block_buffer_manager.publish_event(ConsensusEvent::ValidatorSetUpdated {
+                epoch,
+                validators: new_validators,
+            });
+        }
+    }
+
     fn set_epoch_start_metrics(&self, epoch_state: &EpochState) {
         counters::EPOCH.set(epoch_state.epoch as i64);
         counters::CURRENT_EPOCH_VALIDATORS.set(epoch_state.verifier.len() as i64);
@@ -855,6 +919,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 rand_msg_rx,
                 recovery_data.root_block().round(),
                 Some(self.storage.consensus_db().clone()),
+                self.epoch_context.clone(),
             )
             .await;
         let consensus_sk = consensus_key;
@@ -890,6 +955,9 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 onchain_randomness_config.randomness_enabled(),
                 require_block_randomness,
                 validator_indices,
+                self.epoch_context.clone(),
+                self.block_buffer_manager.clone(),
+                self.recovery_api.clone(),
             )
             .await,
         );
@@ -902,6 +970,14 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             .config
             .max_blocks_per_receiving_request(onchain_consensus_config.quorum_store_enabled());
 
+        // Older validators cannot decode proposals with system txns, so they are only proposed
+        // and accepted once the feature is active. The provider is looked up for every proposal
+        // rather than here, so one registered after the epoch started is not ignored until the
+        // next epoch, during which this node would reject every proposal with system txns.
+        let system_txn_provider = self
+            .protocol_features
+            .is_active(ProtocolFeature::SystemTxns)
+            .then(|| self.block_buffer_manager.registered_system_txn_provider());
         let validator_components = if self.is_current_epoch_validator {
             info!(epoch = epoch, "Create ProposerElection");
             let proposer_election =
@@ -927,7 +1003,39 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 self.quorum_store_enabled,
                 onchain_consensus_config.effective_validator_txn_config(),
                 self.config.quorum_store.allow_batches_without_pos_in_proposal,
-            );
+            )
+            // Configured via CONSENSUS_MIN_BLOCK_INTERVAL_MS, disabled by default.
+            .with_min_block_interval(Duration::from_millis(
+                std::env::var("CONSENSUS_MIN_BLOCK_INTERVAL_MS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0),
+            ))
+            .with_protocol_features(self.protocol_features.clone());
+            // Configured via CONSENSUS_SKIP_EMPTY_BLOCKS, disabled by default. The leader waits
+            // for payload for half the initial round timeout before it skips the round.
+            let skip_empty_blocks = std::env::var("CONSENSUS_SKIP_EMPTY_BLOCKS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false);
+            let proposal_generator = if skip_empty_blocks {
+                proposal_generator.with_skip_empty_blocks(Duration::from_millis(
+                    self.config.round_initial_timeout_ms / 2,
+                ))
+            } else {
+                proposal_generator
+            };
+            let proposal_generator = match ExecutionFeedbackConfig::from_env() {
+                Some(config) => proposal_generator.with_execution_feedback(ExecutionFeedback::new(
+                    config,
+                    self.block_buffer_manager.clone(),
+                )),
+                None => proposal_generator,
+            };
+            let proposal_generator = match &system_txn_provider {
+                Some(provider) => proposal_generator.with_system_txn_provider(provider.clone()),
+                None => proposal_generator,
+            };
             Some(round_manager::ValidatorComponents::new(
                 Arc::new(UnequivocalProposerElection::new(proposer_election)),
                 Arc::new(proposal_generator),
@@ -954,7 +1062,13 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             fast_rand_config,
             validator_components,
             fullnode_side_network_id(self.node_type),
-        );
+        )
+        .with_protocol_features(self.protocol_features.clone())
+        .with_max_timestamp_drift(max_timestamp_drift());
+
+        if let Some(provider) = system_txn_provider {
+            round_manager = round_manager.with_system_txn_provider(provider);
+        }
 
         round_manager.init(last_vote).await;
 
@@ -1157,9 +1271,11 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             payload.get().expect("failed to get ValidatorSet from payload");
         info!("validator_set read from config storage is : {:?}", validator_set);
 
-        // Update global proposer reth address map for current epoch
+        self.epoch_context = Arc::new(EpochContext::new(payload.epoch(), &validator_set));
+        // Keep the deprecated global map in sync for callers outside consensus
+        #[allow(deprecated)]
         proposer_reth_map::update_proposer_reth_index_map(&validator_set);
-        info!("Updated proposer reth address map for epoch {}", payload.epoch());
+        info!("Updated epoch context for epoch {}", payload.epoch());
 
         self.is_current_epoch_validator = false;
         if self.node_type.is_validator() {
@@ -1176,6 +1292,8 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             epoch: payload.epoch(),
             verifier: Arc::new((&validator_set).into()),
         });
+        self.publish_epoch_events(&epoch_state);
+        peer_scores().set_validators(epoch_state.verifier.get_ordered_account_addresses_iter());
 
         self.epoch_state = Some(epoch_state.clone());
 
@@ -1188,6 +1306,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             payload.get();
         let onchain_jwk_consensus_config: anyhow::Result<OnChainJWKConsensusConfig> = payload.get();
         let dkg_state = payload.get::<DKGState>();
+        let onchain_features: anyhow::Result<Features> = payload.get();
 
         if let Err(error) = &onchain_consensus_config {
             error!("Failed to read on-chain consensus config {}", error);
@@ -1209,6 +1328,19 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             epoch = epoch_state.epoch,
             "OnChainConsensusConfig loaded for new epoch: {:#?}", consensus_config,
         );
+        // Features flagged onchain during the last epoch take effect from this one
+        self.protocol_features = ProtocolFeatures::from_onchain(onchain_features.as_ref().ok());
+        info!(
+            epoch = epoch_state.epoch,
+            "Active protocol features: {:?}",
+            self.protocol_features.active().map(|feature| feature.to_string()).collect::<Vec<_>>(),
+        );
+        self.block_buffer_manager
+            .set_signed_commit_payloads(
+                epoch_state.epoch,
+                self.protocol_features.is_active(ProtocolFeature::SignedCommitPayloads),
+            )
+            .await;
         let execution_config = onchain_execution_config
             .unwrap_or_else(|_| OnChainExecutionConfig::default_if_missing());
         let onchain_randomness_config_seq_num = onchain_randomness_config_seq_num
@@ -1465,6 +1597,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 rand_msg_rx,
                 highest_committed_round,
                 Some(self.storage.consensus_db().clone()),
+                self.epoch_context.clone(),
             )
             .await;
 
@@ -1611,6 +1744,11 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                                 error = ?e,
                                 unverified_event = unverified_event
                             );
+                            if let Some(misbehavior) =
+                                verification_failure(e.inner()).filter(|_| peer_id != my_peer_id)
+                            {
+                                peer_scores().record(peer_id, misbehavior);
+                            }
                         }
                     }
                 })
@@ -1993,7 +2131,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         // initial start of the processor
         let reconfig_notification = self.await_reconfig_notification().await;
         // Update block buffer manager with the correct epoch from epoch_state
-        get_block_buffer_manager().init_epoch(reconfig_notification.on_chain_configs.epoch()).await;
+        self.block_buffer_manager.init_epoch(reconfig_notification.on_chain_configs.epoch()).await;
         self.start_new_epoch(reconfig_notification.on_chain_configs).await;
 
         let mut request_sync_info_interval = tokio::time::interval(Duration::from_millis(
@@ -2068,18 +2206,38 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         OnChainJWKConsensusConfig::Off
     }
 
-    fn load_consensus_key(&self, vv: &ValidatorVerifier) -> anyhow::Result<PrivateKey> {
-        match vv.get_public_key(&self.author) {
-            Some(pk) => self
-                .key_storage
-                .consensus_sk_by_pk(pk)
-                .map_err(|e| anyhow!("could not find sk by pk: {:?}", e)),
-            None => {
-                warn!("could not find my pk in validator set, loading default sk!");
-                self.key_storage
-                    .default_consensus_sk()
-                    .map_err(|e| anyhow!("could not load default sk: {e}"))
+    fn load_consensus_key(&mut self, vv: &ValidatorVerifier) -> anyhow::Result<PrivateKey> {
+        load_consensus_key(&self.author, vv, &self.config.safety_rules, &mut self.key_storage)
+    }
+}
+
+/// Loads the secret key for the public key that `vv` lists for `author`.
+fn load_consensus_key(
+    author: &Author,
+    vv: &ValidatorVerifier,
+    config: &SafetyRulesConfig,
+    key_storage: &mut PersistentSafetyStorage,
+) -> anyhow::Result<PrivateKey> {
+    match vv.get_public_key(author) {
+        Some(pk) => {
+            if let Ok(sk) = key_storage.consensus_sk_by_pk(pk.clone()) {
+                return Ok(sk);
+            }
+            // The key was rotated onchain, the new one may have been put in the identity file
+            // after the node started
+            let reloaded = safety_rules_manager::reload_identity_consensus_key(config, key_storage);
+            if reloaded.as_ref() == Some(&pk) {
+                info!("Loaded the rotated consensus key {} from the identity file", pk);
             }
+            key_storage
+                .consensus_sk_by_pk(pk)
+                .map_err(|e| anyhow!("could not find sk by pk: {:?}", e))
+        }
+        None => {
+            warn!("could not find my pk in validator set, loading default sk!");
+            key_storage
+                .default_consensus_sk()
+                .map_err(|e| anyhow!("could not load default sk: {e}"))
         }
     }
 }
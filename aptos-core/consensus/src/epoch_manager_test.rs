@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::load_consensus_key;
+use aptos_consensus_types::common::Author;
+use aptos_safety_rules::PersistentSafetyStorage;
+use gaptos::{
+    aptos_config::config::SafetyRulesConfig,
+    aptos_crypto::{
+        bls12381::{PrivateKey, PublicKey},
+        Uniform,
+    },
+    aptos_secure_storage::{InMemoryStorage, Storage},
+    aptos_types::{
+        validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+        waypoint::Waypoint,
+    },
+};
+use std::path::Path;
+
+/// Writes an identity file holding `sk`, with the layout `gravity_cli genesis generate-key` uses.
+fn write_identity(path: &Path, author: Author, sk: &PrivateKey) {
+    let identity = format!(
+        "account_address: \"{}\"\nconsensus_private_key: \"{}\"\nnetwork_private_key: \"{}\"\n",
+        author.to_hex(),
+        hex::encode(sk.to_bytes()),
+        hex::encode([1u8; 32]),
+    );
+    std::fs::write(path, identity).unwrap();
+}
+
+fn config_with_identity(path: &Path) -> SafetyRulesConfig {
+    let initial_safety_rules_config = serde_yaml::from_str(&format!(
+        "from_file:\n  identity_blob_path: {}\n  waypoint:\n    from_config: \"{}\"\n",
+        path.display(),
+        Waypoint::default(),
+    ))
+    .unwrap();
+    SafetyRulesConfig { initial_safety_rules_config, ..Default::default() }
+}
+
+/// The validator set of an epoch that lists `pk` for `author`.
+fn verifier_with(author: Author, pk: &PublicKey) -> ValidatorVerifier {
+    ValidatorVerifier::new(vec![ValidatorConsensusInfo::new(author, pk.clone(), 1)])
+}
+
+#[test]
+fn test_load_rotated_consensus_key_across_epochs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("identity.yaml");
+    let author = Author::random();
+    let mut rng = rand::thread_rng();
+    let old_sk = PrivateKey::generate(&mut rng);
+    let new_sk = PrivateKey::generate(&mut rng);
+    let old_pk = PublicKey::from(&old_sk);
+    let new_pk = PublicKey::from(&new_sk);
+
+    write_identity(&path, author, &old_sk);
+    let config = config_with_identity(&path);
+    let mut key_storage = PersistentSafetyStorage::initialize(
+        Storage::from(InMemoryStorage::new()),
+        author,
+        old_sk,
+        Waypoint::default(),
+        true,
+    );
+    let old_epoch = verifier_with(author, &old_pk);
+    let new_epoch = verifier_with(author, &new_pk);
+
+    let sk = load_consensus_key(&author, &old_epoch, &config, &mut key_storage).unwrap();
+    assert_eq!(PublicKey::from(&sk), old_pk);
+
+    // The operator puts the new key in the identity file before the rotation takes effect
+    write_identity(&path, author, &new_sk);
+    let sk = load_consensus_key(&author, &new_epoch, &config, &mut key_storage).unwrap();
+    assert_eq!(PublicKey::from(&sk), new_pk);
+
+    // Replaying the epoch before the rotation still finds the old key
+    let sk = load_consensus_key(&author, &old_epoch, &config, &mut key_storage).unwrap();
+    assert_eq!(PublicKey::from(&sk), old_pk);
+}
+
+#[test]
+fn test_failed_reload_keeps_the_old_consensus_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("identity.yaml");
+    let author = Author::random();
+    let mut rng = rand::thread_rng();
+    let old_sk = PrivateKey::generate(&mut rng);
+    let old_pk = PublicKey::from(&old_sk);
+    let new_pk = PublicKey::from(&PrivateKey::generate(&mut rng));
+
+    write_identity(&path, author, &old_sk);
+    let config = config_with_identity(&path);
+    let mut key_storage = PersistentSafetyStorage::initialize(
+        Storage::from(InMemoryStorage::new()),
+        author,
+        old_sk,
+        Waypoint::default(),
+        true,
+    );
+
+    let old_epoch = verifier_with(author, &old_pk);
+    let new_epoch = verifier_with(author, &new_pk);
+
+    // The identity file still holds the old key, so the rotated one cannot be loaded
+    assert!(load_consensus_key(&author, &new_epoch, &config, &mut key_storage).is_err());
+    // Nor can it when the identity file is gone
+    std::fs::remove_file(&path).unwrap();
+    assert!(load_consensus_key(&author, &new_epoch, &config, &mut key_storage).is_err());
+
+    let sk = load_consensus_key(&author, &old_epoch, &config, &mut key_storage).unwrap();
+    assert_eq!(PublicKey::from(&sk), old_pk);
+}
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::pipeline;
+use gaptos::aptos_crypto::HashValue;
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -58,6 +60,30 @@ pub struct VerifyError {
     inner: anyhow::Error,
 }
 
+impl VerifyError {
+    pub fn inner(&self) -> &anyhow::Error {
+        &self.inner
+    }
+}
+
+/// A block replayed during recovery executed to another state root than the committed chain
+/// has for it. The node either halts or stops recovery so the operator can re-sync its
+/// execution layer, see `block_storage::forensics`.
+#[derive(Debug, Error)]
+#[error(
+    "block {block_number} ({block_id}) of epoch {epoch} executed to state root {computed}, the committed chain has {expected}, report: {}",
+    dump.as_ref().map_or_else(|| "not written".to_string(), |path| path.display().to_string())
+)]
+pub struct StateRootMismatch {
+    pub block_id: HashValue,
+    pub block_number: u64,
+    pub epoch: u64,
+    pub expected: HashValue,
+    pub computed: HashValue,
+    /// Report of what was executed, if it could be written.
+    pub dump: Option<PathBuf>,
+}
+
 pub fn error_kind(e: &anyhow::Error) -> &'static str {
     if e.downcast_ref::<aptos_executor_types::ExecutorError>().is_some() {
         return "Execution";
@@ -83,6 +109,9 @@ pub fn error_kind(e: &anyhow::Error) -> &'static str {
     if e.downcast_ref::<VerifyError>().is_some() {
         return "VerifyError";
     }
+    if e.downcast_ref::<StateRootMismatch>().is_some() {
+        return "StateRootMismatch";
+    }
     "InternalError"
 }
 
@@ -188,6 +188,12 @@ impl ExecutionPipeline {
             counters::EXECUTE_BLOCK_WAIT_TIME.observe_duration(command_creation_time.elapsed());
             let block_id = block.block_id;
             debug!("execute_stage received block {}.", block_id);
+            if lifetime_guard.is_cancelled() {
+                result_tx
+                    .send(Err(cancelled_by_reset("execute", block_id)))
+                    .unwrap_or_else(log_failed_to_send_result("execute", block_id));
+                continue;
+            }
             let executor = executor.clone();
             let execution_time = monitor!(
                 "execute_block",
@@ -243,6 +249,12 @@ impl ExecutionPipeline {
         {
             counters::APPLY_LEDGER_WAIT_TIME.observe_duration(command_creation_time.elapsed());
             debug!("ledger_apply stage received block {}.", block_id);
+            if lifetime_guard.is_cancelled() {
+                result_tx
+                    .send(Err(cancelled_by_reset("ledger_apply", block_id)))
+                    .unwrap_or_else(log_failed_to_send_result("ledger_apply", block_id));
+                continue;
+            }
             let res = async {
                 let execution_duration = execution_time?;
                 let executor = executor.clone();
@@ -302,6 +314,12 @@ impl ExecutionPipeline {
             block_rx.recv().await
         {
             debug!("pre_commit stage received block {}.", block_id);
+            if lifetime_guard.is_cancelled() {
+                result_tx
+                    .send(Err(cancelled_by_reset("pre_commit", block_id)))
+                    .unwrap_or_else(log_failed_to_send_result("pre_commit", block_id));
+                continue;
+            }
             let res = async {
                 let executor = executor.clone();
                 monitor!(
@@ -357,6 +375,12 @@ struct PreCommitCommand {
     lifetime_guard: CountedRequest<()>,
 }
 
+fn cancelled_by_reset(stage: &'static str, block_id: HashValue) -> ExecutorError {
+    ExecutorError::InternalError {
+        error: format!("{} of block {} cancelled by pipeline reset", stage, block_id),
+    }
+}
+
 fn log_failed_to_send_result<T>(
     from_stage: &'static str,
     block_id: HashValue,
@@ -3,12 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    consensusdb::ConsensusDB, payload_client::user::quorum_store_client::QuorumStoreClient,
+    block_storage::recovery_api::{BlockBufferRecovery, RecoveryApi},
+    consensusdb::ConsensusDB,
+    payload_client::user::quorum_store_client::QuorumStoreClient,
 };
 use anyhow::Result;
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_types::{BlockExecutorTrait, ExecutorError, ExecutorResult, StateComputeResult};
-use block_buffer_manager::{block_buffer_manager::BlockHashRef, get_block_buffer_manager};
+use block_buffer_manager::{block_buffer_manager::BlockHashRef, BlockBufferManager};
 use gaptos::{
     api_types::u256_define::BlockId,
     aptos_consensus::counters::{APTOS_COMMIT_BLOCKS, APTOS_EXECUTION_TXNS},
@@ -25,11 +27,29 @@ use tokio::runtime::Runtime;
 pub struct ConsensusAdapterArgs {
     pub quorum_store_client: Option<Arc<QuorumStoreClient>>,
     pub consensus_db: Option<Arc<ConsensusDB>>,
+    /// Block buffer shared with this node's execution layer.
+    pub block_buffer_manager: Arc<BlockBufferManager>,
+    /// Replays the blocks block sync verified as committed, through the block buffer unless
+    /// replaced with [`Self::with_recovery_api`].
+    pub recovery_api: Arc<dyn RecoveryApi>,
 }
 
 impl ConsensusAdapterArgs {
-    pub fn new(consensus_db: Arc<ConsensusDB>) -> Self {
-        Self { quorum_store_client: None, consensus_db: Some(consensus_db) }
+    pub fn new(
+        consensus_db: Arc<ConsensusDB>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
+        Self {
+            quorum_store_client: None,
+            consensus_db: Some(consensus_db),
+            recovery_api: Arc::new(BlockBufferRecovery::new(block_buffer_manager.clone())),
+            block_buffer_manager,
+        }
+    }
+
+    pub fn with_recovery_api(mut self, recovery_api: Arc<dyn RecoveryApi>) -> Self {
+        self.recovery_api = recovery_api;
+        self
     }
 
     pub fn set_quorum_store_client(&mut self, quorum_store_client: Option<Arc<QuorumStoreClient>>) {
@@ -37,23 +57,35 @@ impl ConsensusAdapterArgs {
     }
 
     pub fn dummy() -> Self {
-        Self { quorum_store_client: None, consensus_db: None }
+        let block_buffer_manager = BlockBufferManager::new(Default::default());
+        Self {
+            quorum_store_client: None,
+            consensus_db: None,
+            recovery_api: Arc::new(BlockBufferRecovery::new(block_buffer_manager.clone())),
+            block_buffer_manager,
+        }
     }
 }
 
 pub struct GravityBlockExecutor {
     inner: BlockExecutor,
     consensus_db: Arc<ConsensusDB>,
+    block_buffer_manager: Arc<BlockBufferManager>,
     // Option so Drop can take it and call `shutdown_background()`: the executor
     // is dropped from async consensus tasks, where a plain Runtime drop panics.
     runtime: Option<Runtime>,
 }
 
 impl GravityBlockExecutor {
-    pub(crate) fn new(inner: BlockExecutor, consensus_db: Arc<ConsensusDB>) -> Self {
+    pub(crate) fn new(
+        inner: BlockExecutor,
+        consensus_db: Arc<ConsensusDB>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
         Self {
             inner,
             consensus_db,
+            block_buffer_manager,
             runtime: Some(gaptos::aptos_runtimes::spawn_named_runtime("tmp".into(), None)),
         }
     }
@@ -61,6 +93,19 @@ impl GravityBlockExecutor {
     fn runtime(&self) -> &Runtime {
         self.runtime.as_ref().expect("runtime is only taken on drop")
     }
+
+    /// Persists the payloads the execution layer attached to `blocks`, see
+    /// `BlockBufferManager::set_commit_payload`.
+    async fn persist_commit_payloads(
+        &self,
+        blocks: &[BlockHashRef],
+        epoch: u64,
+    ) -> ExecutorResult<()> {
+        let payloads = self.block_buffer_manager.take_commit_payloads(blocks, epoch).await;
+        self.consensus_db.put_commit_payloads(&payloads).map_err(|e| {
+            ExecutorError::internal_err(format!("Failed to persist commit payloads: {e:?}"))
+        })
+    }
 }
 
 impl Drop for GravityBlockExecutor {
@@ -107,7 +152,13 @@ impl BlockExecutorTrait for GravityBlockExecutor {
                 ledger_info_with_sigs.ledger_info().commit_info().id(),
                 ledger_info_with_sigs.ledger_info().block_hash(),
             );
-            txn_metrics::TxnLifeTime::get_txn_life_time().record_block_committed(block_id.clone());
+            let txn_life_time = txn_metrics::TxnLifeTime::get_txn_life_time();
+            // Only the block the ledger info certifies is observed, the ones committed with it
+            // just release their tracking
+            for committed_block_id in &block_ids[..block_ids.len() - 1] {
+                txn_life_time.release_committed_block(*committed_block_id);
+            }
+            txn_life_time.record_block_committed(block_id);
             let block_num = ledger_info_with_sigs.ledger_info().block_number();
             assert!(block_ids.last().unwrap().as_slice() == block_id.as_slice());
             let len = block_ids.len();
@@ -117,6 +168,7 @@ impl BlockExecutorTrait for GravityBlockExecutor {
                 error!("Failed to save_transactions in commit_blocks: {:?}", e);
             }
             let epoch = ledger_info_with_sigs.ledger_info().epoch();
+            let block_buffer_manager = self.block_buffer_manager.clone();
             self.runtime().block_on(async move {
                 let commit_blocks = block_ids
                     .into_iter()
@@ -132,7 +184,8 @@ impl BlockExecutorTrait for GravityBlockExecutor {
                         }
                     })
                     .collect::<Vec<_>>();
-                let mut persist_notifiers = get_block_buffer_manager()
+                self.persist_commit_payloads(&commit_blocks, epoch).await?;
+                let mut persist_notifiers = block_buffer_manager
                     .set_commit_blocks(&commit_blocks, epoch)
                     .await
                     .map_err(|e| {
@@ -195,6 +248,7 @@ impl BlockExecutorTrait for GravityBlockExecutor {
             }
         }
 
+        let block_buffer_manager = self.block_buffer_manager.clone();
         self.runtime().block_on(async move {
             let commit_blocks = block_ids
                 .into_iter()
@@ -209,7 +263,8 @@ impl BlockExecutorTrait for GravityBlockExecutor {
                     }
                 })
                 .collect::<Vec<_>>();
-            let mut persist_notifiers = get_block_buffer_manager()
+            self.persist_commit_payloads(&commit_blocks, epoch).await?;
+            let mut persist_notifiers = block_buffer_manager
                 .set_commit_blocks(&commit_blocks, epoch)
                 .await
                 .map_err(|e| {
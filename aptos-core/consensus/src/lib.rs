@@ -22,6 +22,8 @@ extern crate scopeguard;
 extern crate core;
 
 mod block_storage;
+#[cfg(all(test, feature = "failpoints"))]
+mod chaos;
 pub mod consensusdb;
 mod dag;
 mod epoch_manager;
@@ -37,6 +39,7 @@ mod pending_order_votes;
 mod pending_votes;
 pub mod persistent_liveness_storage;
 mod pipeline;
+pub mod protocol_features;
 pub mod quorum_store;
 mod rand;
 mod recovery_manager;
@@ -63,12 +66,15 @@ pub mod gravity_state_computer;
 /// AptosNet interface.
 pub mod network_interface;
 mod payload_manager;
+/// Peer scores, read by the operator API
+pub mod peer_score;
 mod qc_aggregator;
 mod transaction_deduper;
 mod transaction_filter;
 mod transaction_shuffler;
 mod txn_hash_and_authenticator_deduper;
 
+pub use block_storage::recovery_api::{BlockBufferRecovery, RecoveryApi};
 pub use consensusdb::create_checkpoint;
 /// Required by the smoke tests
 pub use consensusdb::CONSENSUS_DB_NAME;
@@ -77,6 +83,13 @@ pub use quorum_store::quorum_store_db::QUORUM_STORE_DB_NAME;
 #[cfg(feature = "fuzzing")]
 pub use round_manager::round_manager_fuzzing;
 
+/// Registers the consensus knobs with the runtime config registry, so they are listed by the
+/// admin API before consensus first reads them.
+pub fn register_runtime_knobs() {
+    block_storage::batch_commit_size_knob();
+    payload_client::user::quorum_store_client::empty_block_max_wait_knob();
+}
+
 struct IntGaugeGuard {
     gauge: IntGauge,
 }
@@ -0,0 +1,99 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Block size limit derived from what the execution layer reports about recent blocks.
+//!
+//! The execution layer reports the gas and execution time of every block it executes, see
+//! `BlockBufferManager::report_execution`. Gas per second follows the machine and the state
+//! size, which change slowly, so it is estimated over a long window. Gas per transaction follows
+//! the current transaction mix, so it is estimated over the latest blocks only. Together they
+//! give how many transactions a block can hold to execute within the target time, so a burst
+//! of heavy transactions shrinks blocks before the execution pipeline backs up.
+
+use block_buffer_manager::{block_buffer_manager::ExecutionReport, BlockBufferManager};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+#[cfg(test)]
+#[path = "execution_feedback_test.rs"]
+mod execution_feedback_test;
+
+/// Reports needed before the limit applies.
+const MIN_REPORTS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionFeedbackConfig {
+    pub target_execution_time: Duration,
+    /// Latest blocks whose gas and execution time give the gas per second.
+    pub throughput_window: usize,
+    /// Latest blocks whose transactions give the gas per transaction.
+    pub txn_mix_window: usize,
+}
+
+impl ExecutionFeedbackConfig {
+    /// Configured via CONSENSUS_TARGET_BLOCK_EXECUTION_MS, disabled when unset or zero. Read
+    /// once, every epoch uses the same config.
+    pub fn from_env() -> Option<Self> {
+        static CONFIG: OnceLock<Option<ExecutionFeedbackConfig>> = OnceLock::new();
+        *CONFIG.get_or_init(|| {
+            let target_ms = std::env::var("CONSENSUS_TARGET_BLOCK_EXECUTION_MS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|ms| *ms > 0)?;
+            Some(Self {
+                target_execution_time: Duration::from_millis(target_ms),
+                throughput_window: 20,
+                txn_mix_window: 3,
+            })
+        })
+    }
+
+    /// Transactions per block that keep execution within the target time, `None` until enough
+    /// non-empty blocks were reported.
+    pub fn max_block_txns(&self, reports: &[ExecutionReport]) -> Option<u64> {
+        // Empty blocks say nothing about the cost of transactions
+        let reports: Vec<_> =
+            reports.iter().filter(|report| report.txns > 0 && report.gas > 0).collect();
+        if reports.len() < MIN_REPORTS {
+            return None;
+        }
+        let throughput = &reports[reports.len().saturating_sub(self.throughput_window)..];
+        let throughput_gas: u64 = throughput.iter().map(|report| report.gas).sum();
+        let time: Duration = throughput.iter().map(|report| report.execution_time).sum();
+        let txn_mix = &reports[reports.len().saturating_sub(self.txn_mix_window)..];
+        let txn_mix_gas: u64 = txn_mix.iter().map(|report| report.gas).sum();
+        let txn_mix_txns: u64 = txn_mix.iter().map(|report| report.txns).sum();
+        if time.is_zero() {
+            return None;
+        }
+        // gas per second * target time / gas per transaction
+        let max_txns =
+            throughput_gas as u128 * self.target_execution_time.as_nanos() * txn_mix_txns as u128 /
+                (time.as_nanos() * txn_mix_gas as u128);
+        Some(u64::try_from(max_txns).unwrap_or(u64::MAX).max(1))
+    }
+}
+
+/// Reads the execution reports of this node's execution layer.
+#[derive(Clone)]
+pub struct ExecutionFeedback {
+    config: ExecutionFeedbackConfig,
+    block_buffer_manager: Arc<BlockBufferManager>,
+}
+
+impl ExecutionFeedback {
+    pub fn new(
+        config: ExecutionFeedbackConfig,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
+        Self { config, block_buffer_manager }
+    }
+
+    pub fn max_block_txns(&self) -> Option<u64> {
+        let reports =
+            self.block_buffer_manager.recent_execution_reports(self.config.throughput_window);
+        self.config.max_block_txns(&reports)
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::ExecutionFeedbackConfig;
+use block_buffer_manager::block_buffer_manager::ExecutionReport;
+use std::time::Duration;
+
+fn report(block_number: u64, txns: u64, gas: u64, execution_ms: u64) -> ExecutionReport {
+    ExecutionReport { block_number, txns, gas, execution_time: Duration::from_millis(execution_ms) }
+}
+
+fn config() -> ExecutionFeedbackConfig {
+    ExecutionFeedbackConfig {
+        target_execution_time: Duration::from_millis(500),
+        throughput_window: 20,
+        txn_mix_window: 2,
+    }
+}
+
+#[test]
+fn test_limit_needs_non_empty_blocks() {
+    let reports = [report(1, 100, 2_100_000, 100), report(2, 0, 0, 5), report(3, 0, 0, 5)];
+    assert_eq!(config().max_block_txns(&reports), None);
+}
+
+#[test]
+fn test_limit_follows_throughput_and_txn_mix() {
+    // 21k gas transfers at 21M gas per second: 500 of them fit in 500ms
+    let transfers: Vec<_> = (1..=4).map(|n| report(n, 100, 2_100_000, 100)).collect();
+    assert_eq!(config().max_block_txns(&transfers), Some(500));
+
+    // Ten times heavier transactions at the same gas per second fit ten times fewer
+    let mut heavy = transfers.clone();
+    heavy.extend((5..=6).map(|n| report(n, 10, 2_100_000, 100)));
+    assert_eq!(config().max_block_txns(&heavy), Some(50));
+
+    // A slower execution layer halves the limit
+    let slow: Vec<_> = (1..=4).map(|n| report(n, 100, 2_100_000, 200)).collect();
+    assert_eq!(config().max_block_txns(&slow), Some(250));
+}
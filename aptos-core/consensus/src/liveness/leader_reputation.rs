@@ -35,10 +35,11 @@ use gaptos::{
         account_config::NewBlockEvent, epoch_change::EpochChangeProof, epoch_state::EpochState,
     },
 };
+use itertools::Itertools;
 
 use std::{
     cmp::max,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     sync::Arc,
 };
@@ -736,6 +737,264 @@ impl ReputationHeuristic for ProposerAndVoterHeuristic {
     }
 }
 
+/// Who proposed a committed block and who failed to propose before it. Indices are positions in
+/// the proposer candidates of `epoch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedProposal {
+    pub epoch: u64,
+    pub block_number: u64,
+    pub timestamp_usecs: u64,
+    /// `None` for NIL blocks.
+    pub proposer_index: Option<u64>,
+    pub failed_proposer_indices: Vec<u64>,
+}
+
+/// Committed blocks, which every validator that committed them reads the same.
+pub trait ProposalHistoryBackend: Send + Sync {
+    /// The proposal committed at `block_number`, with indices into `candidates`, or `None` when
+    /// this node does not have the block.
+    fn get_committed_proposal(
+        &self,
+        candidates: &[Author],
+        block_number: u64,
+    ) -> Option<CommittedProposal>;
+}
+
+/// Committed proposals kept by [`ConsensusDBProposalHistory`].
+const MAX_CACHED_PROPOSALS: usize = 4096;
+
+/// Reads committed blocks from the consensus DB, which keeps them across restarts.
+pub struct ConsensusDBProposalHistory {
+    consensus_db: Arc<ConsensusDB>,
+    /// Committed blocks never change, and the windows of consecutive anchors mostly overlap.
+    cache: Mutex<BTreeMap<u64, CommittedProposal>>,
+}
+
+impl ConsensusDBProposalHistory {
+    pub fn new(consensus_db: Arc<ConsensusDB>) -> Self {
+        Self { consensus_db, cache: Mutex::new(BTreeMap::new()) }
+    }
+}
+
+impl ProposalHistoryBackend for ConsensusDBProposalHistory {
+    fn get_committed_proposal(
+        &self,
+        candidates: &[Author],
+        block_number: u64,
+    ) -> Option<CommittedProposal> {
+        if let Some(proposal) = self.cache.lock().get(&block_number) {
+            return Some(proposal.clone());
+        }
+        let block = match self.consensus_db.get_block_by_number(block_number) {
+            Ok(block) => block?,
+            Err(error) => {
+                warn!(error = ?error, block_number = block_number, "Failed to read committed block");
+                return None;
+            }
+        };
+        let index_of = |author: &Author| {
+            candidates.iter().position(|candidate| candidate == author).map(|i| i as u64)
+        };
+        let proposal = CommittedProposal {
+            epoch: block.epoch(),
+            block_number,
+            timestamp_usecs: block.timestamp_usecs(),
+            proposer_index: block.author().and_then(|author| index_of(&author)),
+            failed_proposer_indices: block
+                .block_data()
+                .failed_authors()
+                .map_or(vec![], |authors| {
+                    authors.iter().filter_map(|(_round, author)| index_of(author)).collect()
+                }),
+        };
+        let mut cache = self.cache.lock();
+        if cache.len() == MAX_CACHED_PROPOSALS {
+            cache.pop_first();
+        }
+        cache.insert(block_number, proposal.clone());
+        Some(proposal)
+    }
+}
+
+/// A proposer needs this many timed proposals in the window before it can be judged slow.
+const MIN_TIMED_PROPOSALS: usize = 3;
+/// How far above the median latency, or above this share of failed rounds, a proposer is
+/// penalized by [`LatencyAwareHeuristic`].
+pub const LATENCY_THRESHOLD_PERCENT: u32 = 50;
+/// Weight [`LatencyAwareHeuristic`] gives a slow or failing proposer.
+pub const LATENCY_PENALTY_WEIGHT: u64 = 1;
+
+/// Biases the weights of an inner heuristic away from slow and failing proposers, based on the
+/// blocks committed up to the reputation anchor.
+///
+/// The latency of a proposal is the time between the timestamps of the block and its parent.
+/// Blocks that follow failed rounds are not timed, as their latency includes the timeouts of
+/// the failed leaders. Within the `window_size` committed blocks up to the anchor, a proposer
+/// gets `penalty_weight` when
+///  * its median latency is more than `threshold_percent` above the median of all proposals, or
+///  * more than `threshold_percent` of its rounds failed.
+///
+/// Only the committed blocks up to the anchor are read, so every validator computes the same
+/// weights. When the window reaches back into the previous epoch, every validator uses the inner
+/// weights. A validator missing a block of the window, e.g. one bootstrapped from a checkpoint,
+/// logs an error and uses the inner weights.
+pub struct LatencyAwareHeuristic {
+    inner: Box<dyn ReputationHeuristic>,
+    backend: Arc<dyn ProposalHistoryBackend>,
+    window_size: usize,
+    threshold_percent: u32,
+    penalty_weight: u64,
+}
+
+impl LatencyAwareHeuristic {
+    pub fn new(
+        inner: Box<dyn ReputationHeuristic>,
+        backend: Arc<dyn ProposalHistoryBackend>,
+        window_size: usize,
+        threshold_percent: u32,
+        penalty_weight: u64,
+    ) -> Self {
+        Self { inner, backend, window_size, threshold_percent, penalty_weight }
+    }
+
+    /// Indices of the candidates to penalize, or `None` when the window is not all in `epoch`.
+    fn slow_or_failing(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        block_number: u64,
+    ) -> Result<Option<HashSet<usize>>> {
+        // The first block of the window is only needed as the parent of the second one
+        let Some(first) = block_number.checked_sub(self.window_size as u64) else {
+            return Ok(None);
+        };
+        let mut proposals = Vec::with_capacity(self.window_size + 1);
+        // Newest first, so a window reaching into a pruned epoch stops at its end
+        for number in (first..=block_number).rev() {
+            let proposal = self
+                .backend
+                .get_committed_proposal(candidates, number)
+                .ok_or_else(|| anyhow!("committed block {} is not in the consensus DB", number))?;
+            if proposal.epoch != epoch {
+                return Ok(None);
+            }
+            proposals.push(proposal);
+        }
+        proposals.reverse();
+
+        let num_candidates = candidates.len();
+        let mut latencies = vec![vec![]; num_candidates];
+        let mut succeeded = vec![0u64; num_candidates];
+        let mut failed = vec![0u64; num_candidates];
+        for (parent, proposal) in proposals.iter().tuple_windows() {
+            for index in &proposal.failed_proposer_indices {
+                if let Some(count) = failed.get_mut(*index as usize) {
+                    *count += 1;
+                }
+            }
+            let Some(index) = proposal.proposer_index.map(|index| index as usize) else {
+                continue;
+            };
+            if index >= num_candidates {
+                continue;
+            }
+            succeeded[index] += 1;
+            if proposal.failed_proposer_indices.is_empty() {
+                latencies[index]
+                    .push(proposal.timestamp_usecs.saturating_sub(parent.timestamp_usecs));
+            }
+        }
+
+        let overall = median(latencies.iter().flatten().copied().collect());
+        let threshold = self.threshold_percent as u64;
+        Ok(Some(
+            (0..num_candidates)
+                .filter(|&index| {
+                    let total = succeeded[index] + failed[index];
+                    let failing = total > 0 && failed[index] * 100 > total * threshold;
+                    let slow = latencies[index].len() >= MIN_TIMED_PROPOSALS &&
+                        overall.is_some_and(|overall| {
+                            let own = median(latencies[index].clone()).unwrap_or(0);
+                            own as u128 * 100 > overall as u128 * (100 + threshold as u128)
+                        });
+                    failing || slow
+                })
+                .collect(),
+        ))
+    }
+}
+
+fn median(mut values: Vec<u64>) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2])
+}
+
+impl ReputationHeuristic for LatencyAwareHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        self.inner.get_weights(epoch, epoch_to_candidates, history)
+    }
+
+    fn get_legacy_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        self.inner.get_legacy_weights(epoch, epoch_to_candidates, history)
+    }
+
+    fn get_weights_at_block(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        block_number: u64,
+    ) -> Result<Vec<u64>> {
+        let mut weights =
+            self.inner.get_weights_at_block(epoch, epoch_to_candidates, block_number)?;
+        let candidates = epoch_to_candidates
+            .get(&epoch)
+            .ok_or_else(|| anyhow!("missing proposer candidates for epoch {}", epoch))?;
+        match self.slow_or_failing(epoch, candidates, block_number) {
+            Ok(Some(penalized)) => {
+                for index in penalized {
+                    debug!(
+                        "Validator {} is slow or failing in the {} blocks up to {}. Assigned weight {}",
+                        index, self.window_size, block_number, self.penalty_weight
+                    );
+                    weights[index] = weights[index].min(self.penalty_weight);
+                }
+            }
+            Ok(None) => debug!(
+                "The proposal window up to block {} is not all in epoch {}, latency is not weighed",
+                block_number, epoch
+            ),
+            Err(error) => error!(
+                error = ?error,
+                epoch = epoch,
+                block_number = block_number,
+                "Cannot read the proposal window, latency is not weighed"
+            ),
+        }
+        Ok(weights)
+    }
+
+    fn get_fallback_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+    ) -> Vec<u64> {
+        self.inner.get_fallback_weights(epoch, epoch_to_candidates)
+    }
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
 pub struct LeaderReputation {
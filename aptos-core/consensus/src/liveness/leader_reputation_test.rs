@@ -3,10 +3,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::leader_reputation::{
-    extract_epoch_to_proposers_impl, AptosDBBackend, ProposerAndVoterHeuristic,
+    extract_epoch_to_proposers_impl, AptosDBBackend, CommittedProposal, ConsensusDBProposalHistory,
+    LatencyAwareHeuristic, ProposalHistoryBackend, ProposerAndVoterHeuristic,
 };
 use crate::{
-    consensusdb::CommittedBlockAnchor,
+    consensusdb::{BlockSchema, CommittedBlockAnchor, ConsensusDB},
     liveness::{
         leader_reputation::{
             LeaderReputation, MetadataBackend, NewBlockEventAggregation, ReputationAnchorBackend,
@@ -15,7 +16,10 @@ use crate::{
         proposer_election::{choose_index, ProposerElection},
     },
 };
-use aptos_consensus_types::common::{Author, Round};
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::{Author, Payload, Round},
+};
 use claims::assert_err;
 use gaptos::{
     api_types::{
@@ -29,19 +33,21 @@ use gaptos::{
     aptos_infallible::Mutex,
     aptos_keygen::KeyGen,
     aptos_storage_interface::DbReader,
+    aptos_temppath::TempPath,
     aptos_types::{
         account_address::AccountAddress,
         account_config::{new_block_event_key, NewBlockEvent},
         contract_event::{ContractEvent, EventWithVersion},
         epoch_state::EpochState,
         transaction::Version,
+        validator_signer::ValidatorSigner,
         validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
     },
     move_core_types::{language_storage::TypeTag, move_resource::MoveStructType},
 };
 use itertools::Itertools;
 use num_traits::Pow;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 /// #### NewBlockEventAggregation tests ####
 
@@ -328,6 +334,133 @@ impl ReputationAnchorBackend for ErrorReputationAnchorBackend {
     }
 }
 
+struct ConstantHeuristic(u64);
+
+impl ReputationHeuristic for ConstantHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        _history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        vec![self.0; epoch_to_candidates[&epoch].len()]
+    }
+
+    fn get_weights_at_block(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        _block_number: u64,
+    ) -> anyhow::Result<Vec<u64>> {
+        Ok(self.get_weights(epoch, epoch_to_candidates, &[]))
+    }
+}
+
+struct MockProposalHistory(Vec<CommittedProposal>);
+
+impl ProposalHistoryBackend for MockProposalHistory {
+    fn get_committed_proposal(
+        &self,
+        _candidates: &[Author],
+        block_number: u64,
+    ) -> Option<CommittedProposal> {
+        self.0.iter().find(|p| p.block_number == block_number).cloned()
+    }
+}
+
+#[test]
+fn test_latency_aware_heuristic() {
+    let validators: Vec<_> = (0..4).map(|_| Author::random()).collect();
+    let epoch_to_validators = HashMap::from([(2u64, validators)]);
+
+    // Validator n % 4 proposes block n of epoch 2, block 0 ends epoch 1. Validator 2 takes
+    // 500ms where the others take 100ms, validator 3 fails the rounds before blocks 4 and 8.
+    let mut timestamp_usecs = 0;
+    let proposals: Vec<_> = (0..=13u64)
+        .map(|block_number| {
+            let proposer = block_number % 4;
+            timestamp_usecs += if proposer == 2 { 500_000 } else { 100_000 };
+            CommittedProposal {
+                epoch: if block_number == 0 { 1 } else { 2 },
+                block_number,
+                timestamp_usecs,
+                proposer_index: Some(proposer),
+                failed_proposer_indices: if block_number % 4 == 0 && block_number < 12 {
+                    vec![3]
+                } else {
+                    vec![]
+                },
+            }
+        })
+        .collect();
+    let heuristic = |proposals: Vec<CommittedProposal>| {
+        LatencyAwareHeuristic::new(
+            Box::new(ConstantHeuristic(100)),
+            Arc::new(MockProposalHistory(proposals)),
+            12,
+            25,
+            1,
+        )
+    };
+    let complete = heuristic(proposals.clone());
+
+    assert_eq!(
+        complete.get_weights_at_block(2, &epoch_to_validators, 13).unwrap(),
+        vec![100, 100, 1, 1]
+    );
+    // A window reaching into the previous epoch leaves the inner weights as they are
+    assert_eq!(
+        complete.get_weights_at_block(2, &epoch_to_validators, 12).unwrap(),
+        vec![100, 100, 100, 100]
+    );
+    let epoch_3_validators = HashMap::from([(3u64, epoch_to_validators[&2].clone())]);
+    assert_eq!(
+        complete.get_weights_at_block(3, &epoch_3_validators, 13).unwrap(),
+        vec![100, 100, 100, 100]
+    );
+
+    // So does a block missing from the window
+    let incomplete =
+        heuristic(proposals.into_iter().filter(|proposal| proposal.block_number != 5).collect());
+    assert_eq!(
+        incomplete.get_weights_at_block(2, &epoch_to_validators, 13).unwrap(),
+        vec![100, 100, 100, 100]
+    );
+}
+
+#[test]
+fn test_consensus_db_proposal_history() {
+    let tmp_dir = TempPath::new();
+    let db = Arc::new(ConsensusDB::new(&tmp_dir, &PathBuf::new()));
+    let signer = ValidatorSigner::random(None);
+    let failed = Author::random();
+    let block = Block::new_proposal(
+        Payload::empty(false, true),
+        2,
+        2_000,
+        certificate_for_genesis(),
+        &signer,
+        vec![(1, failed)],
+    )
+    .unwrap();
+    db.put::<BlockSchema>(&(block.epoch(), block.id()), &block).unwrap();
+    db.save_block_numbers(vec![(block.epoch(), 1, block.id())]).unwrap();
+
+    let history = ConsensusDBProposalHistory::new(db);
+    let candidates = vec![failed, signer.author()];
+    let expected = CommittedProposal {
+        epoch: block.epoch(),
+        block_number: 1,
+        timestamp_usecs: 2_000,
+        proposer_index: Some(1),
+        failed_proposer_indices: vec![0],
+    };
+    assert_eq!(history.get_committed_proposal(&candidates, 1), Some(expected.clone()));
+    // Served from the cache the second time
+    assert_eq!(history.get_committed_proposal(&candidates, 1), Some(expected));
+    assert_eq!(history.get_committed_proposal(&candidates, 2), None);
+}
+
 #[test]
 fn test_anchored_performance_cache_is_keyed_by_block_number() {
     let storage = Arc::new(MockPerformanceStorage::new());
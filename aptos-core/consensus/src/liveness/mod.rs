@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub(crate) mod cached_proposer_election;
+pub(crate) mod execution_feedback;
 pub(crate) mod leader_reputation;
 pub(crate) mod proposal_generator;
 pub(crate) mod proposer_election;
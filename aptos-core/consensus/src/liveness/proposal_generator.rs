@@ -2,10 +2,12 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::proposer_election::ProposerElection;
+use super::{execution_feedback::ExecutionFeedback, proposer_election::ProposerElection};
 use crate::{
     block_storage::{BlockReader, BlockStore},
+    counters,
     payload_client::PayloadClient,
+    protocol_features::{ProtocolFeature, ProtocolFeatures},
     util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
@@ -16,8 +18,13 @@ use aptos_consensus_types::{
     pipelined_block::ExecutionSummary,
     quorum_cert::QuorumCert,
 };
-use futures::future::BoxFuture;
+use block_buffer_manager::{SystemTxnContext, SystemTxnProvider};
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt,
+};
 use gaptos::{
+    api_types::u256_define::BlockId,
     aptos_config::config::{
         ChainHealthBackoffValues, ExecutionBackpressureConfig, PipelineBackpressureValues,
     },
@@ -38,13 +45,16 @@ use itertools::Itertools;
 use std::{
     collections::{BTreeMap, HashSet},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 #[cfg(test)]
 #[path = "proposal_generator_test.rs"]
 mod proposal_generator_test;
 
+/// How often the leader polls for payload while it waits to skip an empty proposal.
+const EMPTY_PROPOSAL_POLL_INTERVAL: Duration = Duration::from_millis(30);
+
 #[derive(Clone)]
 pub struct ChainHealthBackoffConfig {
     backoffs: BTreeMap<usize, ChainHealthBackoffValues>,
@@ -270,6 +280,22 @@ pub struct ProposalGenerator {
     vtxn_config: ValidatorTxnConfig,
 
     allow_batches_without_pos_in_proposal: bool,
+
+    /// Minimum time between the timestamps of a proposal and its parent.
+    min_block_interval: Duration,
+
+    /// How long to wait for transactions before proposing nothing rather than an empty block,
+    /// see `with_skip_empty_blocks`.
+    skip_empty_blocks_wait: Option<Duration>,
+
+    /// Protocol features active in this epoch. Payloads that need another one are not proposed.
+    protocol_features: ProtocolFeatures,
+
+    /// Limits blocks to what the execution layer gets through in the target execution time.
+    execution_feedback: Option<ExecutionFeedback>,
+
+    /// Source of the system transactions of each proposal.
+    system_txn_provider: Option<Arc<dyn SystemTxnProvider>>,
 }
 
 impl ProposalGenerator {
@@ -312,9 +338,57 @@ impl ProposalGenerator {
             quorum_store_enabled,
             vtxn_config,
             allow_batches_without_pos_in_proposal,
+            min_block_interval: Duration::ZERO,
+            skip_empty_blocks_wait: None,
+            protocol_features: ProtocolFeatures::default(),
+            execution_feedback: None,
+            system_txn_provider: None,
         }
     }
 
+    /// Holds each proposal back until `min_block_interval` has passed since its parent, so
+    /// chains with little traffic produce fewer, fuller blocks. Proposals after a
+    /// reconfiguration are not delayed. Keep it well below the round timeout.
+    pub fn with_min_block_interval(mut self, min_block_interval: Duration) -> Self {
+        self.min_block_interval = min_block_interval;
+        self
+    }
+
+    /// Makes the leader keep polling for payload for up to `wait_for_payload` when it has no
+    /// transactions, validator transactions or system transactions and no uncommitted block
+    /// carries transactions, and propose nothing if none arrived, so idle chains do not fill
+    /// their history with empty blocks. The leader proposes as soon as transactions show up
+    /// within the wait; rounds that stay idle end in a timeout, so keep the wait below the round
+    /// timeout. Blocks after a reconfiguration are still proposed.
+    pub fn with_skip_empty_blocks(mut self, wait_for_payload: Duration) -> Self {
+        self.skip_empty_blocks_wait = Some(wait_for_payload);
+        self
+    }
+
+    /// Sets the protocol features of the epoch. None are active by default.
+    pub fn with_protocol_features(mut self, protocol_features: ProtocolFeatures) -> Self {
+        self.protocol_features = protocol_features;
+        self
+    }
+
+    /// Caps the transactions of each proposal by the gas and execution time the execution layer
+    /// reported for recent blocks. Disabled by default.
+    pub fn with_execution_feedback(mut self, execution_feedback: ExecutionFeedback) -> Self {
+        self.execution_feedback = Some(execution_feedback);
+        self
+    }
+
+    /// Proposes the system transactions of `system_txn_provider` along with the payload. Only
+    /// set while the `system_txns` protocol feature is active, as older validators cannot
+    /// decode such proposals.
+    pub fn with_system_txn_provider(
+        mut self,
+        system_txn_provider: Arc<dyn SystemTxnProvider>,
+    ) -> Self {
+        self.system_txn_provider = Some(system_txn_provider);
+        self
+    }
+
     pub fn author(&self) -> Author {
         self.author
     }
@@ -346,12 +420,13 @@ impl ProposalGenerator {
     /// 2. The round is provided by the caller.
     /// 3. In case a given round is not greater than the calculated parent, return an OldRound
     /// error.
+    /// 4. Returns None if empty blocks are skipped and no payload arrived in time.
     pub async fn generate_proposal(
         &self,
         round: Round,
         proposer_election: Arc<dyn ProposerElection + Send + Sync>,
         wait_callback: BoxFuture<'static, ()>,
-    ) -> anyhow::Result<BlockData> {
+    ) -> anyhow::Result<Option<BlockData>> {
         {
             let mut last_round_generated = self.last_round_generated.lock();
             if *last_round_generated < round {
@@ -364,10 +439,14 @@ impl ProposalGenerator {
         let hqc = self.ensure_highest_quorum_cert(round)?;
 
         // println!("the block store is {}", self.block_store.get_block_tree().read());
-        let (validator_txns, payload, timestamp) = if hqc.certified_block().has_reconfiguration() {
+        let (validator_txns, system_txns, payload, timestamp) = if hqc
+            .certified_block()
+            .has_reconfiguration()
+        {
             // Reconfiguration rule - we propose empty blocks with parents' timestamp
             // after reconfiguration until it's committed
             (
+                vec![],
                 vec![],
                 Payload::empty(
                     self.quorum_store_enabled,
@@ -383,6 +462,8 @@ impl ProposalGenerator {
                 .block_store
                 .path_from_commit_root(hqc.certified_block().id())
                 .ok_or_else(|| format_err!("HQC {} already pruned", hqc.certified_block().id()))?;
+            let uncommitted_txns =
+                pending_blocks.iter().any(|block| !block.payload().map_or(true, |p| p.is_empty()));
             // Avoid txn manager long poll if the root block has txns, so that the leader can
             // deliver the commit proof to others without delay.
             pending_blocks.push(self.block_store.commit_root());
@@ -400,6 +481,12 @@ impl ProposalGenerator {
                 .iter()
                 .any(|block| !block.payload().map_or(true, |txns| txns.is_empty()));
 
+            if !self.min_block_interval.is_zero() {
+                let parent_timestamp =
+                    Duration::from_micros(hqc.certified_block().timestamp_usecs());
+                self.time_service.wait_until(parent_timestamp + self.min_block_interval).await;
+            }
+
             // All proposed blocks in a branch are guaranteed to have increasing timestamps
             // since their predecessor block will not be added to the BlockStore until
             // the local time exceeds it.
@@ -446,29 +533,59 @@ impl ProposalGenerator {
                 .flatten()
                 .map(ValidatorTransaction::hash)
                 .collect();
-            let validator_txn_filter =
-                vtxn_pool::TransactionFilter::PendingTxnHashSet(pending_validator_txn_hashes);
-            let (validator_txns, mut payload) = self
-                .payload_client
-                .pull_payload(
-                    self.quorum_store_poll_time.saturating_sub(proposal_delay),
-                    self.max_block_txns,
-                    max_block_txns_after_filtering,
-                    max_txns_from_block_to_execute.unwrap_or(max_block_txns_after_filtering),
-                    max_block_bytes,
-                    // TODO: Set max_inline_txns and max_inline_bytes correctly
-                    self.max_inline_txns,
-                    self.max_inline_bytes,
-                    validator_txn_filter,
-                    payload_filter,
-                    wait_callback,
-                    pending_ordering,
-                    pending_blocks.len(),
-                    max_fill_fraction,
-                    timestamp,
-                )
-                .await
-                .context("Fail to retrieve payload")?;
+            let system_txns = match &self.system_txn_provider {
+                Some(provider) => provider.system_txns(&SystemTxnContext {
+                    epoch: hqc.certified_block().epoch(),
+                    round,
+                    timestamp_usecs: timestamp.as_micros() as u64,
+                    parent_id: BlockId(*hqc.certified_block().id()),
+                }),
+                None => vec![],
+            };
+            let may_skip_empty = system_txns.is_empty() && !uncommitted_txns;
+            let mut wait_callback = Some(wait_callback);
+            let wait_start = Instant::now();
+            let (validator_txns, mut payload) = loop {
+                let validator_txn_filter = vtxn_pool::TransactionFilter::PendingTxnHashSet(
+                    pending_validator_txn_hashes.clone(),
+                );
+                let (validator_txns, payload) = self
+                    .payload_client
+                    .pull_payload(
+                        self.quorum_store_poll_time.saturating_sub(proposal_delay),
+                        self.max_block_txns,
+                        max_block_txns_after_filtering,
+                        max_txns_from_block_to_execute.unwrap_or(max_block_txns_after_filtering),
+                        max_block_bytes,
+                        // TODO: Set max_inline_txns and max_inline_bytes correctly
+                        self.max_inline_txns,
+                        self.max_inline_bytes,
+                        validator_txn_filter,
+                        payload_filter.clone(),
+                        wait_callback.take().unwrap_or_else(|| future::ready(()).boxed()),
+                        pending_ordering,
+                        pending_blocks.len(),
+                        max_fill_fraction,
+                        timestamp,
+                    )
+                    .await
+                    .context("Fail to retrieve payload")?;
+                let skip_wait = match self.skip_empty_blocks_wait {
+                    Some(wait)
+                        if may_skip_empty && payload.is_empty() && validator_txns.is_empty() =>
+                    {
+                        wait
+                    }
+                    _ => break (validator_txns, payload),
+                };
+                // Nothing to propose yet, keep waiting for transactions within the round
+                if wait_start.elapsed() >= skip_wait {
+                    counters::SKIPPED_EMPTY_PROPOSAL_COUNT.inc();
+                    debug!("Nothing to propose in round {}, skipping the empty proposal", round);
+                    return Ok(None);
+                }
+                tokio::time::sleep(EMPTY_PROPOSAL_POLL_INTERVAL).await;
+            };
             // TODO(gravity_byteyue): Consider how to process the validator transaction
             if !payload.is_direct() &&
                 max_txns_from_block_to_execute.is_some() &&
@@ -476,7 +593,15 @@ impl ProposalGenerator {
             {
                 payload = payload.transform_to_quorum_store_v2(max_txns_from_block_to_execute);
             }
-            (validator_txns, payload, timestamp.as_micros() as u64)
+            if let Err(e) = self.protocol_features.check_payload(&payload) {
+                if let Some(feature) = ProtocolFeature::required_by(&payload) {
+                    counters::GATED_PAYLOAD_REJECTED_COUNT
+                        .with_label_values(&[&feature.to_string(), "self"])
+                        .inc();
+                }
+                bail!("Refusing to propose in round {}: {:#}", round, e);
+            }
+            (validator_txns, system_txns, payload, timestamp.as_micros() as u64)
         };
 
         let quorum_cert = hqc.as_ref().clone();
@@ -487,7 +612,18 @@ impl ProposalGenerator {
             proposer_election,
         );
 
-        let block = if self.vtxn_config.enabled() {
+        let block = if !system_txns.is_empty() {
+            BlockData::new_proposal_with_system_txns(
+                validator_txns,
+                system_txns,
+                payload,
+                self.author,
+                failed_authors,
+                round,
+                timestamp,
+                quorum_cert,
+            )
+        } else if self.vtxn_config.enabled() {
             BlockData::new_proposal_ext(
                 validator_txns,
                 payload,
@@ -508,7 +644,7 @@ impl ProposalGenerator {
             )
         };
 
-        Ok(block)
+        Ok(Some(block))
     }
 
     async fn calculate_max_block_sizes(
@@ -565,6 +701,15 @@ impl ProposalGenerator {
             0.0
         });
 
+        let mut execution_feedback_applied = false;
+        if let Some(limit) =
+            self.execution_feedback.as_ref().and_then(ExecutionFeedback::max_block_txns)
+        {
+            counters::PROPOSER_EXECUTION_FEEDBACK_MAX_TXNS.set(limit as i64);
+            values_max_block_txns_after_filtering.push(limit);
+            execution_feedback_applied = limit < self.max_block_txns_after_filtering;
+        }
+
         let max_block_txns_after_filtering = values_max_block_txns_after_filtering
             .into_iter()
             .min()
@@ -593,6 +738,7 @@ impl ProposalGenerator {
 
         if pipeline_backpressure.is_some() ||
             execution_backpressure_applied ||
+            execution_feedback_applied ||
             chain_health_backoff.is_some()
         {
             warn!(
@@ -604,6 +750,7 @@ impl ProposalGenerator {
                 max_block_bytes = max_block_bytes,
                 is_pipeline_backpressure = pipeline_backpressure.is_some(),
                 is_execution_backpressure = execution_backpressure_applied,
+                is_execution_feedback = execution_feedback_applied,
                 is_chain_health_backoff = chain_health_backoff.is_some(),
                 round = round,
                 "Proposal generation backpressure details",
@@ -618,6 +765,7 @@ impl ProposalGenerator {
                 max_block_bytes = max_block_bytes,
                 is_pipeline_backpressure = pipeline_backpressure.is_some(),
                 is_execution_backpressure = execution_backpressure_applied,
+                is_execution_feedback = execution_feedback_applied,
                 is_chain_health_backoff = chain_health_backoff.is_some(),
                 round = round,
                 "Proposal generation backpressure details",
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::BlockReader,
+    block_storage::{BlockReader, BlockStore},
     liveness::{
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -11,16 +11,22 @@ use crate::{
         rotating_proposer_election::RotatingProposer,
         unequivocal_proposer_election::UnequivocalProposerElection,
     },
-    test_utils::{build_empty_tree, MockPayloadManager, TreeInserter},
+    test_utils::{build_empty_tree, MockPayloadManager, MockSystemTxnProvider, TreeInserter},
     util::mock_time_service::SimulatedTimeService,
 };
 use aptos_consensus_types::{
-    block::{block_test_utils::certificate_for_genesis, Block},
+    block::{
+        block_test_utils::{certificate_for_genesis, random_payload},
+        Block,
+    },
     common::Author,
 };
 use futures::{future::BoxFuture, FutureExt};
 use gaptos::aptos_types::{on_chain_config::ValidatorTxnConfig, validator_signer::ValidatorSigner};
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 fn empty_callback() -> BoxFuture<'static, ()> {
     async move {}.boxed()
@@ -58,6 +64,7 @@ async fn test_proposal_generation_empty_tree() {
     let proposal_data = proposal_generator
         .generate_proposal(1, proposer_election.clone(), empty_callback())
         .await
+        .unwrap()
         .unwrap();
     let proposal = Block::new_proposal_from_block_data(proposal_data, &signer).unwrap();
     assert_eq!(proposal.parent_id(), genesis.id());
@@ -106,6 +113,7 @@ async fn test_proposal_generation_parent() {
     let original_res = proposal_generator
         .generate_proposal(10, proposer_election.clone(), empty_callback())
         .await
+        .unwrap()
         .unwrap();
     // With no certifications the parent is genesis
     // generate proposals for an empty tree.
@@ -120,6 +128,7 @@ async fn test_proposal_generation_parent() {
     let a1_child_res = proposal_generator
         .generate_proposal(11, proposer_election.clone(), empty_callback())
         .await
+        .unwrap()
         .unwrap();
     assert_eq!(a1_child_res.parent_id(), a1.id());
     assert_eq!(a1_child_res.round(), 11);
@@ -135,6 +144,7 @@ async fn test_proposal_generation_parent() {
     let b1_child_res = proposal_generator
         .generate_proposal(15, proposer_election.clone(), empty_callback())
         .await
+        .unwrap()
         .unwrap();
     assert_eq!(b1_child_res.parent_id(), b1.id());
     assert_eq!(b1_child_res.round(), 15);
@@ -218,6 +228,7 @@ async fn test_correct_failed_authors() {
     let result = proposal_generator
         .generate_proposal(6, proposer_election.clone(), empty_callback())
         .await
+        .unwrap()
         .unwrap();
     // With no certifications the parent is genesis
     // generate proposals for an empty tree.
@@ -230,3 +241,201 @@ async fn test_correct_failed_authors() {
     assert_eq!(result.failed_authors().unwrap()[3], (4, peer1));
     assert_eq!(result.failed_authors().unwrap()[4], (5, peer2));
 }
+
+#[tokio::test]
+async fn test_proposal_generation_min_block_interval() {
+    let signer = ValidatorSigner::random(None);
+    let block_store = build_empty_tree().await;
+    let proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store.clone(),
+        Arc::new(MockPayloadManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        Duration::ZERO,
+        1,
+        1,
+        10,
+        1,
+        10,
+        10,
+        1,
+        PipelineBackpressureConfig::new_no_backoff(),
+        ChainHealthBackoffConfig::new_no_backoff(),
+        false,
+        ValidatorTxnConfig::default_disabled(),
+        true,
+    )
+    .with_min_block_interval(Duration::from_secs(5));
+    let proposer_election = Arc::new(UnequivocalProposerElection::new(Arc::new(
+        RotatingProposer::new(vec![signer.author()], 1),
+    )));
+    let genesis = block_store.ordered_root();
+
+    let proposal_data = proposal_generator
+        .generate_proposal(1, proposer_election, empty_callback())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(proposal_data.timestamp_usecs() >= genesis.timestamp_usecs() + 5_000_000);
+}
+
+fn skipping_proposal_generator(
+    block_store: Arc<BlockStore>,
+    author: Author,
+    wait_for_payload: Duration,
+) -> ProposalGenerator {
+    ProposalGenerator::new(
+        author,
+        block_store,
+        Arc::new(MockPayloadManager::new_empty()),
+        Arc::new(SimulatedTimeService::new()),
+        Duration::ZERO,
+        1,
+        1,
+        10,
+        1,
+        10,
+        10,
+        1,
+        PipelineBackpressureConfig::new_no_backoff(),
+        ChainHealthBackoffConfig::new_no_backoff(),
+        false,
+        ValidatorTxnConfig::default_disabled(),
+        true,
+    )
+    .with_skip_empty_blocks(wait_for_payload)
+}
+
+#[tokio::test]
+async fn test_proposal_generation_skips_empty_blocks() {
+    let signer = ValidatorSigner::random(None);
+    let proposer_election = Arc::new(UnequivocalProposerElection::new(Arc::new(
+        RotatingProposer::new(vec![signer.author()], 1),
+    )));
+
+    let proposal_generator =
+        skipping_proposal_generator(build_empty_tree().await, signer.author(), Duration::ZERO);
+    let proposal_data = proposal_generator
+        .generate_proposal(1, proposer_election.clone(), empty_callback())
+        .await
+        .unwrap();
+    assert!(proposal_data.is_none());
+
+    // Without the option the same round gets an empty block
+    let proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        build_empty_tree().await,
+        Arc::new(MockPayloadManager::new_empty()),
+        Arc::new(SimulatedTimeService::new()),
+        Duration::ZERO,
+        1,
+        1,
+        10,
+        1,
+        10,
+        10,
+        1,
+        PipelineBackpressureConfig::new_no_backoff(),
+        ChainHealthBackoffConfig::new_no_backoff(),
+        false,
+        ValidatorTxnConfig::default_disabled(),
+        true,
+    );
+    let proposal_data = proposal_generator
+        .generate_proposal(1, proposer_election, empty_callback())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(proposal_data.payload().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_proposal_generation_waits_for_payload_before_skipping() {
+    let signer = ValidatorSigner::random(None);
+    let proposer_election = Arc::new(UnequivocalProposerElection::new(Arc::new(
+        RotatingProposer::new(vec![signer.author()], 1),
+    )));
+
+    let proposal_generator = skipping_proposal_generator(
+        build_empty_tree().await,
+        signer.author(),
+        Duration::from_millis(100),
+    );
+    let start = Instant::now();
+    let proposal_data =
+        proposal_generator.generate_proposal(1, proposer_election, empty_callback()).await.unwrap();
+    assert!(proposal_data.is_none());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_proposal_generation_skip_empty_keeps_uncommitted_txns_moving() {
+    let mut inserter = TreeInserter::default().await;
+    let block_store = inserter.block_store();
+    let proposal_generator = skipping_proposal_generator(
+        block_store.clone(),
+        inserter.signer().author(),
+        Duration::ZERO,
+    );
+    let proposer_election = Arc::new(UnequivocalProposerElection::new(Arc::new(
+        RotatingProposer::new(vec![inserter.signer().author()], 1),
+    )));
+    let genesis = block_store.ordered_root();
+    let a1 = block_store
+        .insert_block_with_qc(inserter.create_block_with_qc(
+            certificate_for_genesis(),
+            genesis.timestamp_usecs() + 1,
+            1,
+            random_payload(1),
+            vec![],
+        ))
+        .await
+        .unwrap();
+    inserter.insert_qc_for_block(a1.as_ref(), None);
+
+    // a1 is not committed yet, the empty block is needed to commit its transactions
+    let proposal_data = proposal_generator
+        .generate_proposal(2, proposer_election, empty_callback())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(proposal_data.parent_id(), a1.id());
+    assert!(proposal_data.payload().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_proposal_generation_injects_system_txns() {
+    let signer = ValidatorSigner::random(None);
+    let block_store = build_empty_tree().await;
+    let proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store.clone(),
+        Arc::new(MockPayloadManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        Duration::ZERO,
+        1,
+        1,
+        10,
+        1,
+        10,
+        10,
+        1,
+        PipelineBackpressureConfig::new_no_backoff(),
+        ChainHealthBackoffConfig::new_no_backoff(),
+        false,
+        ValidatorTxnConfig::default_disabled(),
+        true,
+    )
+    .with_system_txn_provider(Arc::new(MockSystemTxnProvider));
+    let proposer_election = Arc::new(UnequivocalProposerElection::new(Arc::new(
+        RotatingProposer::new(vec![signer.author()], 1),
+    )));
+
+    let proposal_data = proposal_generator
+        .generate_proposal(1, proposer_election, empty_callback())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(proposal_data.system_txns(), Some(&vec![1u64.to_be_bytes().to_vec()]));
+    assert!(proposal_data.payload().is_some());
+}
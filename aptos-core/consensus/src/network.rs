@@ -11,6 +11,7 @@ use crate::{
     logging::{LogEvent, LogSchema},
     monitor,
     network_interface::{ConsensusMsg, ConsensusNetworkClient, RPC},
+    peer_score::{peer_scores, Misbehavior},
     pipeline::commit_reliable_broadcast::CommitMessage,
     quorum_store::types::{Batch, BatchMsg, BatchRequest, BatchResponse},
     rand::rand_gen::{
@@ -120,6 +121,8 @@ pub struct IncomingDAGRequest {
 #[derive(Debug)]
 pub struct IncomingCommitRequest {
     pub req: CommitMessage,
+    /// The peer that sent the message, `None` if it was not received from a peer.
+    pub sender: Option<Author>,
     pub protocol: ProtocolId,
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
@@ -661,7 +664,8 @@ pub struct NetworkTask {
         (AccountAddress, Discriminant<IncomingRpcRequest>),
         (AccountAddress, IncomingRpcRequest),
     >,
-    all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
+    /// Events with whether they are messages of this node to itself.
+    all_events: Box<dyn Stream<Item = (bool, Event<ConsensusMsg>)> + Send + Unpin>,
 }
 
 impl NetworkTask {
@@ -693,8 +697,8 @@ impl NetworkTask {
 
         // Collect all the network events into a single stream
         let network_events: Vec<_> = network_and_events.into_values().collect();
-        let network_events = select_all(network_events).fuse();
-        let all_events = Box::new(select(network_events, self_receiver));
+        let network_events = select_all(network_events).map(|event| (false, event)).fuse();
+        let all_events = Box::new(select(network_events, self_receiver.map(|event| (true, event))));
 
         (
             NetworkTask { consensus_messages_tx, quorum_store_messages_tx, rpc_tx, all_events },
@@ -719,8 +723,14 @@ impl NetworkTask {
     }
 
     pub async fn start(mut self) {
-        while let Some(message) = self.all_events.next().await {
+        while let Some((from_self, message)) = self.all_events.next().await {
             debug!("NetworkTask received message {:?}", message);
+            let peer_id = match &message {
+                Event::Message(peer_id, _) | Event::RpcRequest(peer_id, ..) => *peer_id,
+            };
+            if !from_self && !peer_scores().admit(peer_id) {
+                continue;
+            }
             monitor!(
                 "network_main_loop",
                 match message {
@@ -742,6 +752,7 @@ impl NetworkTask {
                                 let req_with_callback =
                                     IncomingRpcRequest::CommitRequest(IncomingCommitRequest {
                                         req: CommitMessage::Vote(*commit_vote),
+                                        sender: Some(peer_id),
                                         protocol: RPC[0],
                                         response_sender: tx,
                                     });
@@ -757,6 +768,7 @@ impl NetworkTask {
                                 let req_with_callback =
                                     IncomingRpcRequest::CommitRequest(IncomingCommitRequest {
                                         req: CommitMessage::Decision(*commit_decision),
+                                        sender: Some(peer_id),
                                         protocol: RPC[0],
                                         response_sender: tx,
                                     });
@@ -806,6 +818,7 @@ impl NetworkTask {
                             }
                             _ => {
                                 warn!(remote_peer = peer_id, "Unexpected direct send msg");
+                                peer_scores().record(peer_id, Misbehavior::ProtocolViolation);
                                 continue;
                             }
                         }
@@ -849,6 +862,7 @@ impl NetworkTask {
                             ConsensusMsg::CommitMessage(req) => {
                                 IncomingRpcRequest::CommitRequest(IncomingCommitRequest {
                                     req: *req,
+                                    sender: Some(peer_id),
                                     protocol,
                                     response_sender: callback,
                                 })
@@ -869,6 +883,7 @@ impl NetworkTask {
                             }
                             _ => {
                                 warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
+                                peer_scores().record(peer_id, Misbehavior::ProtocolViolation);
                                 continue;
                             }
                         };
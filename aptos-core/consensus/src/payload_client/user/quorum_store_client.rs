@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::QuorumStoreError, monitor, payload_client::user::UserPayloadClient};
+use crate::{
+    counters::PAYLOAD_PULL_DEADLINE_EXCEEDED_COUNT, error::QuorumStoreError, monitor,
+    payload_client::user::UserPayloadClient,
+};
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
     request_response::{GetPayloadCommand, GetPayloadResponse},
@@ -9,12 +12,140 @@ use aptos_consensus_types::{
 use fail::fail_point;
 use futures::future::BoxFuture;
 use futures_channel::{mpsc, oneshot};
-use gaptos::{aptos_consensus::counters::WAIT_FOR_FULL_BLOCKS_TRIGGERED, aptos_logger::info};
-use std::time::{Duration, Instant};
+use gaptos::{
+    aptos_consensus::counters::WAIT_FOR_FULL_BLOCKS_TRIGGERED,
+    aptos_logger::{info, warn},
+};
+use runtime_config::{Knob, RuntimeConfigRegistry};
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
 use tokio::time::{sleep, timeout};
 
+#[cfg(test)]
+#[path = "quorum_store_client_test.rs"]
+mod quorum_store_client_test;
+
 const NO_TXN_DELAY: u64 = 30;
 
+/// How a payload pull that misses its deadline is handled.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PartialProposalConfig {
+    /// Overrides the pull timeout from the node config when set.
+    /// Configured via CONSENSUS_PAYLOAD_PULL_DEADLINE_MS.
+    pub pull_deadline_ms: Option<u64>,
+    /// Propose the batches pulled before the deadline instead of failing the round when the
+    /// deadline is missed. Configured via CONSENSUS_PARTIAL_PROPOSAL_ENABLED, disabled by default.
+    pub enabled: bool,
+    /// Requests the payload is pulled in when partial proposals are enabled, each for an equal
+    /// share of the block limits. A missed deadline leaves the batches of the answered requests
+    /// to propose. Configured via CONSENSUS_PARTIAL_PROPOSAL_CHUNKS, default 4.
+    pub chunks: u64,
+    /// Recent block fill fraction at or above which a missed deadline still fails the round:
+    /// blocks are full, so an under-filled proposal would cost more than waiting for the next
+    /// one. Configured via CONSENSUS_PARTIAL_PROPOSAL_MAX_FILL_FRACTION, default 1.0.
+    pub max_recent_fill_fraction: f32,
+}
+
+/// How long the leader keeps polling the quorum store for transactions before proposing an
+/// empty block, configured via CONSENSUS_EMPTY_BLOCK_MAX_WAIT_MS. Zero, the default, proposes
+/// empty blocks right away. The wait is skipped while uncommitted blocks carry transactions,
+/// since the empty block is then needed to commit them. Keep it well below the round timeout.
+/// Can be changed at runtime through the `consensus_empty_block_max_wait_ms` knob.
+pub(crate) fn empty_block_max_wait_knob() -> &'static Knob {
+    static KNOB: OnceLock<Arc<Knob>> = OnceLock::new();
+    KNOB.get_or_init(|| {
+        RuntimeConfigRegistry::global().register(
+            "consensus_empty_block_max_wait_ms",
+            "How long the leader waits for transactions before proposing an empty block",
+            "CONSENSUS_EMPTY_BLOCK_MAX_WAIT_MS",
+            0,
+            10_000,
+        )
+    })
+}
+
+fn empty_block_max_wait() -> Duration {
+    Duration::from_millis(empty_block_max_wait_knob().get())
+}
+
+fn partial_proposal_config() -> PartialProposalConfig {
+    static CONFIG: OnceLock<PartialProposalConfig> = OnceLock::new();
+    *CONFIG.get_or_init(|| PartialProposalConfig {
+        pull_deadline_ms: std::env::var("CONSENSUS_PAYLOAD_PULL_DEADLINE_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok()),
+        enabled: std::env::var("CONSENSUS_PARTIAL_PROPOSAL_ENABLED")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .unwrap_or(false),
+        chunks: std::env::var("CONSENSUS_PARTIAL_PROPOSAL_CHUNKS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|chunks| *chunks > 0)
+            .unwrap_or(4),
+        max_recent_fill_fraction: std::env::var("CONSENSUS_PARTIAL_PROPOSAL_MAX_FILL_FRACTION")
+            .ok()
+            .and_then(|s| s.parse::<f32>().ok())
+            .unwrap_or(1.0),
+    })
+}
+
+/// Limits of a single pull from the quorum store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PullLimits {
+    pub max_items: u64,
+    pub max_items_after_filtering: u64,
+    pub soft_max_items_after_filtering: u64,
+    pub max_bytes: u64,
+    pub max_inline_items: u64,
+    pub max_inline_bytes: u64,
+}
+
+impl PullLimits {
+    /// Shares `from..to` of `chunks` equal shares of the limits combined. The last share takes
+    /// the remainder, so the shares add up to the limits.
+    pub(crate) fn shares(&self, from: u64, to: u64, chunks: u64) -> Self {
+        let share = |limit: u64| {
+            if to == chunks {
+                limit - limit / chunks * from
+            } else {
+                limit / chunks * (to - from)
+            }
+        };
+        Self {
+            max_items: share(self.max_items),
+            max_items_after_filtering: share(self.max_items_after_filtering),
+            soft_max_items_after_filtering: share(self.soft_max_items_after_filtering),
+            max_bytes: share(self.max_bytes),
+            max_inline_items: share(self.max_inline_items),
+            max_inline_bytes: share(self.max_inline_bytes),
+        }
+    }
+
+    /// Whether nothing can be pulled within the limits.
+    fn is_empty(&self) -> bool {
+        self.max_items == 0 || self.max_items_after_filtering == 0 || self.max_bytes == 0
+    }
+}
+
+/// `exclude` plus the batches or transactions of `pulled`, so the next pull does not return them
+/// again.
+fn exclude_pulled(exclude: &PayloadFilter, pulled: &Payload) -> PayloadFilter {
+    match (exclude.clone(), PayloadFilter::from(&vec![pulled])) {
+        (PayloadFilter::InQuorumStore(mut batches), PayloadFilter::InQuorumStore(pulled)) => {
+            batches.extend(pulled);
+            PayloadFilter::InQuorumStore(batches)
+        }
+        (PayloadFilter::DirectMempool(mut txns), PayloadFilter::DirectMempool(pulled)) => {
+            txns.extend(pulled);
+            PayloadFilter::DirectMempool(txns)
+        }
+        (PayloadFilter::Empty, filter) | (filter, _) => filter,
+    }
+}
+
 /// Client that pulls blocks from Quorum Store
 #[derive(Clone)]
 pub struct QuorumStoreClient {
@@ -24,6 +155,9 @@ pub struct QuorumStoreClient {
     pull_timeout_ms: u64,
     wait_for_full_blocks_above_recent_fill_threshold: f32,
     wait_for_full_blocks_above_pending_blocks: usize,
+    partial_proposal: PartialProposalConfig,
+    /// Payload proposed when the pull deadline is missed before anything was pulled.
+    empty_payload: Option<Payload>,
 }
 
 impl QuorumStoreClient {
@@ -33,51 +167,156 @@ impl QuorumStoreClient {
         wait_for_full_blocks_above_recent_fill_threshold: f32,
         wait_for_full_blocks_above_pending_blocks: usize,
     ) -> Self {
+        let partial_proposal = partial_proposal_config();
         Self {
             consensus_to_quorum_store_sender,
-            pull_timeout_ms,
+            pull_timeout_ms: partial_proposal.pull_deadline_ms.unwrap_or(pull_timeout_ms),
             wait_for_full_blocks_above_recent_fill_threshold,
             wait_for_full_blocks_above_pending_blocks,
+            partial_proposal,
+            empty_payload: None,
+        }
+    }
+
+    /// Sets the payload shape proposed when a pull misses its deadline before any batch was
+    /// pulled.
+    pub fn with_empty_payload(mut self, empty_payload: Payload) -> Self {
+        self.empty_payload = Some(empty_payload);
+        self
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_partial_proposal(mut self, partial_proposal: PartialProposalConfig) -> Self {
+        self.partial_proposal = partial_proposal;
+        self
+    }
+
+    /// Decides what to propose after the quorum store failed to answer before the deadline,
+    /// given the payload pulled until then.
+    fn payload_on_deadline(
+        &self,
+        pulled: Option<Payload>,
+        recent_max_fill_fraction: f32,
+    ) -> anyhow::Result<Payload, QuorumStoreError> {
+        let config = &self.partial_proposal;
+        let proposable =
+            config.enabled && recent_max_fill_fraction < config.max_recent_fill_fraction;
+        match (pulled, &self.empty_payload) {
+            (Some(pulled), _) if proposable => {
+                PAYLOAD_PULL_DEADLINE_EXCEEDED_COUNT.with_label_values(&["partial"]).inc();
+                warn!(
+                    pull_timeout_ms = self.pull_timeout_ms,
+                    recent_max_fill_fraction = recent_max_fill_fraction,
+                    payload_len = pulled.len(),
+                    "[consensus] payload pull missed its deadline, proposing partial payload"
+                );
+                Ok(pulled)
+            }
+            (None, Some(empty_payload)) if proposable => {
+                PAYLOAD_PULL_DEADLINE_EXCEEDED_COUNT.with_label_values(&["empty"]).inc();
+                warn!(
+                    pull_timeout_ms = self.pull_timeout_ms,
+                    recent_max_fill_fraction = recent_max_fill_fraction,
+                    "[consensus] payload pull missed its deadline before any batch was pulled, \
+                    proposing empty payload"
+                );
+                Ok(empty_payload.clone())
+            }
+            _ => {
+                PAYLOAD_PULL_DEADLINE_EXCEEDED_COUNT.with_label_values(&["failed"]).inc();
+                Err(anyhow::anyhow!("[consensus] did not receive GetBlockResponse on time").into())
+            }
+        }
+    }
+
+    /// Pulls a payload within the pull deadline.
+    ///
+    /// With partial proposals enabled the limits are pulled in `chunks` requests, each excluding
+    /// what the previous ones returned, until the quorum store runs out of batches. A missed
+    /// deadline then proposes the chunks answered so far instead of failing the round.
+    ///
+    /// Shares that round down to nothing are merged into the next one. A share the quorum store
+    /// answers empty, e.g. because its next batch is larger than the share, is retried with
+    /// everything left of the limits; only an empty answer to that ends the pull.
+    async fn pull_with_deadline(
+        &self,
+        limits: PullLimits,
+        exclude: &PayloadFilter,
+        block_timestamp: Duration,
+        recent_max_fill_fraction: f32,
+    ) -> anyhow::Result<Payload, QuorumStoreError> {
+        let deadline = Instant::now() + Duration::from_millis(self.pull_timeout_ms);
+        let chunks = if self.partial_proposal.enabled { self.partial_proposal.chunks } else { 1 };
+        let mut pulled: Option<Payload> = None;
+        let mut start = 0;
+        let mut pull_rest = false;
+        while start < chunks {
+            let mut end = if pull_rest { chunks } else { start + 1 };
+            while end < chunks && limits.shares(start, end, chunks).is_empty() {
+                end += 1;
+            }
+            let exclude = match &pulled {
+                Some(pulled) => exclude_pulled(exclude, pulled),
+                None => exclude.clone(),
+            };
+            let Some(payload) = self
+                .pull_internal(
+                    limits.shares(start, end, chunks),
+                    true,
+                    exclude,
+                    block_timestamp,
+                    deadline.saturating_duration_since(Instant::now()),
+                )
+                .await?
+            else {
+                return self.payload_on_deadline(pulled, recent_max_fill_fraction);
+            };
+            if payload.is_empty() {
+                // An empty answer to everything left means the quorum store has nothing more
+                // to give
+                if end == chunks {
+                    return Ok(pulled.unwrap_or(payload));
+                }
+                pull_rest = true;
+                continue;
+            }
+            pulled = Some(match pulled {
+                Some(pulled) => pulled.extend(payload),
+                None => payload,
+            });
+            start = end;
         }
+        Ok(pulled.expect("at least one chunk is pulled"))
     }
 
     async fn pull_internal(
         &self,
-        max_items: u64,
-        max_items_after_filtering: u64,
-        soft_max_items_after_filtering: u64,
-        max_bytes: u64,
-        max_inline_items: u64,
-        max_inline_bytes: u64,
+        limits: PullLimits,
         return_non_full: bool,
         exclude_payloads: PayloadFilter,
         block_timestamp: Duration,
-    ) -> anyhow::Result<Payload, QuorumStoreError> {
+        pull_timeout: Duration,
+    ) -> anyhow::Result<Option<Payload>, QuorumStoreError> {
         let (callback, callback_rcv) = oneshot::channel();
         let req = GetPayloadCommand::GetPayloadRequest(
-            max_items,
-            max_items_after_filtering,
-            soft_max_items_after_filtering,
-            max_bytes,
-            max_inline_items,
-            max_inline_bytes,
+            limits.max_items,
+            limits.max_items_after_filtering,
+            limits.soft_max_items_after_filtering,
+            limits.max_bytes,
+            limits.max_inline_items,
+            limits.max_inline_bytes,
             return_non_full,
-            exclude_payloads.clone(),
+            exclude_payloads,
             callback,
             block_timestamp,
         );
         // send to shared mempool
         self.consensus_to_quorum_store_sender.clone().try_send(req).map_err(anyhow::Error::from)?;
-        // wait for response
-        match monitor!(
-            "pull_payload",
-            timeout(Duration::from_millis(self.pull_timeout_ms), callback_rcv).await
-        ) {
-            Err(_) => {
-                Err(anyhow::anyhow!("[consensus] did not receive GetBlockResponse on time").into())
-            }
+        // wait for response, `None` means the deadline was missed
+        match monitor!("pull_payload", timeout(pull_timeout, callback_rcv).await) {
+            Err(_) => Ok(None),
             Ok(resp) => match resp.map_err(anyhow::Error::from)?? {
-                GetPayloadResponse::GetPayloadResponse(payload) => Ok(payload),
+                GetPayloadResponse::GetPayloadResponse(payload) => Ok(Some(payload)),
             },
         }
     }
@@ -104,7 +343,9 @@ impl UserPayloadClient for QuorumStoreClient {
         let return_non_full = recent_max_fill_fraction <
             self.wait_for_full_blocks_above_recent_fill_threshold &&
             pending_uncommitted_blocks < self.wait_for_full_blocks_above_pending_blocks;
-        let return_empty = pending_ordering && return_non_full;
+        // Blocks waiting to be ordered carry transactions and need the next block to commit, so
+        // an empty payload goes out right away
+        let return_empty = pending_ordering;
 
         WAIT_FOR_FULL_BLOCKS_TRIGGERED.observe(if !return_non_full { 1.0 } else { 0.0 });
 
@@ -117,23 +358,19 @@ impl UserPayloadClient for QuorumStoreClient {
         let start_time = Instant::now();
 
         let payload = loop {
-            // Make sure we don't wait more than expected, due to thread scheduling
-            // delays/processing time consumed let done = start_time.elapsed() >=
-            // max_poll_time; TODO(gravity_byteyue): maybe we don't need to set true as
-            // default
-            let done = true;
+            // Non-full payloads are always accepted, empty ones are retried until the configured
+            // wait is over.
+            let done = start_time.elapsed() >= empty_block_max_wait();
+            let limits = PullLimits {
+                max_items,
+                max_items_after_filtering,
+                soft_max_items_after_filtering,
+                max_bytes,
+                max_inline_items,
+                max_inline_bytes,
+            };
             let payload = self
-                .pull_internal(
-                    max_items,
-                    max_items_after_filtering,
-                    soft_max_items_after_filtering,
-                    max_bytes,
-                    max_inline_items,
-                    max_inline_bytes,
-                    return_non_full || return_empty || done,
-                    exclude.clone(),
-                    block_timestamp,
-                )
+                .pull_with_deadline(limits, &exclude, block_timestamp, recent_max_fill_fraction)
                 .await?;
             if payload.is_empty() && !return_empty && !done {
                 if let Some(callback) = callback_wrapper.take() {
@@ -0,0 +1,164 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{PartialProposalConfig, PullLimits, QuorumStoreClient};
+use crate::test_utils::{consensus_runtime, create_signed_transaction, timed_block_on};
+use aptos_consensus_types::{
+    common::{Payload, PayloadFilter},
+    request_response::{GetPayloadCommand, GetPayloadResponse},
+};
+use futures::StreamExt;
+use futures_channel::mpsc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const LIMITS: PullLimits = PullLimits {
+    max_items: 100,
+    max_items_after_filtering: 100,
+    soft_max_items_after_filtering: 100,
+    max_bytes: 1000,
+    max_inline_items: 10,
+    max_inline_bytes: 100,
+};
+
+/// What the mock quorum store does with each request, in order. `Some(n)` answers with `n`
+/// transactions, `None` never answers.
+type Answers = Vec<Option<usize>>;
+
+/// Requests seen by the mock quorum store: the item limit and the number of excluded txns.
+type Requests = Arc<Mutex<Vec<(u64, usize)>>>;
+
+fn client(
+    runtime: &tokio::runtime::Runtime,
+    enabled: bool,
+    chunks: u64,
+    answers: Answers,
+) -> (QuorumStoreClient, Requests) {
+    let (sender, mut receiver) = mpsc::channel(16);
+    let requests = Requests::default();
+    let seen = requests.clone();
+    runtime.spawn(async move {
+        let mut answers = answers.into_iter();
+        // Unanswered callbacks are kept alive so the client times out instead of erroring
+        let mut pending = vec![];
+        while let Some(GetPayloadCommand::GetPayloadRequest(
+            max_items,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+            exclude,
+            callback,
+            _,
+        )) = receiver.next().await
+        {
+            let excluded = match exclude {
+                PayloadFilter::DirectMempool(txns) => txns.len(),
+                _ => 0,
+            };
+            seen.lock().unwrap().push((max_items, excluded));
+            match answers.next().flatten() {
+                Some(txns) => {
+                    let payload = Payload::DirectMempool(
+                        (0..txns).map(|_| create_signed_transaction(1)).collect(),
+                    );
+                    let _ = callback.send(Ok(GetPayloadResponse::GetPayloadResponse(payload)));
+                }
+                None => pending.push(callback),
+            }
+        }
+    });
+    let config = PartialProposalConfig {
+        pull_deadline_ms: None,
+        enabled,
+        chunks,
+        max_recent_fill_fraction: 1.0,
+    };
+    let client = QuorumStoreClient::new(sender, 200, 1.0, usize::MAX)
+        .with_partial_proposal(config)
+        .with_empty_payload(Payload::empty(false, false));
+    (client, requests)
+}
+
+fn pull(runtime: &tokio::runtime::Runtime, client: &QuorumStoreClient) -> Option<usize> {
+    timed_block_on(runtime, async {
+        client
+            .pull_with_deadline(LIMITS, &PayloadFilter::DirectMempool(vec![]), Duration::ZERO, 0.0)
+            .await
+            .ok()
+            .map(|payload| payload.len())
+    })
+}
+
+#[test]
+fn test_chunk_limits_add_up() {
+    let chunks: Vec<_> = (0..3).map(|index| LIMITS.shares(index, index + 1, 3)).collect();
+    assert_eq!(chunks.iter().map(|limits| limits.max_items).collect::<Vec<_>>(), vec![33, 33, 34]);
+    assert_eq!(chunks.iter().map(|limits| limits.max_bytes).sum::<u64>(), LIMITS.max_bytes);
+    assert_eq!(LIMITS.shares(0, 1, 1), LIMITS);
+}
+
+#[test]
+fn test_missed_deadline_fails_when_disabled() {
+    let runtime = consensus_runtime();
+    let (client, requests) = client(&runtime, false, 4, vec![None]);
+    assert_eq!(pull(&runtime, &client), None);
+    assert_eq!(*requests.lock().unwrap(), vec![(100, 0)]);
+}
+
+#[test]
+fn test_missed_deadline_proposes_pulled_batches() {
+    let runtime = consensus_runtime();
+    let (client, requests) = client(&runtime, true, 2, vec![Some(1), None]);
+    assert_eq!(pull(&runtime, &client), Some(1));
+    // The second chunk excludes what the first one pulled
+    assert_eq!(*requests.lock().unwrap(), vec![(50, 0), (50, 1)]);
+}
+
+#[test]
+fn test_missed_deadline_before_any_batch_proposes_empty_payload() {
+    let runtime = consensus_runtime();
+    let (client, _) = client(&runtime, true, 2, vec![None]);
+    assert_eq!(pull(&runtime, &client), Some(0));
+}
+
+#[test]
+fn test_chunks_are_combined() {
+    let runtime = consensus_runtime();
+    let (client, requests) = client(&runtime, true, 3, vec![Some(2), Some(3), Some(1)]);
+    assert_eq!(pull(&runtime, &client), Some(6));
+    assert_eq!(*requests.lock().unwrap(), vec![(33, 0), (33, 2), (34, 5)]);
+}
+
+#[test]
+fn test_empty_answer_to_everything_left_stops_pulling() {
+    let runtime = consensus_runtime();
+    let (client, requests) = client(&runtime, true, 3, vec![Some(2), Some(0), Some(0)]);
+    assert_eq!(pull(&runtime, &client), Some(2));
+    // The empty share is retried with the rest of the limits
+    assert_eq!(*requests.lock().unwrap(), vec![(33, 0), (33, 2), (67, 2)]);
+}
+
+#[test]
+fn test_shares_rounding_to_nothing_are_merged() {
+    assert_eq!(LIMITS.shares(0, 3, 150).max_items, 0);
+    assert_eq!(LIMITS.shares(0, 150, 150), LIMITS);
+
+    let runtime = consensus_runtime();
+    let (client, requests) = client(&runtime, true, 150, vec![Some(3)]);
+    assert_eq!(pull(&runtime, &client), Some(3));
+    assert_eq!(*requests.lock().unwrap(), vec![(100, 0)]);
+}
+
+#[test]
+fn test_batch_larger_than_a_share_is_pulled_with_the_rest() {
+    let runtime = consensus_runtime();
+    // The first share cannot fit the next batch, so the quorum store answers it empty
+    let (client, requests) = client(&runtime, true, 2, vec![Some(0), Some(5)]);
+    assert_eq!(pull(&runtime, &client), Some(5));
+    assert_eq!(*requests.lock().unwrap(), vec![(50, 0), (100, 0)]);
+}
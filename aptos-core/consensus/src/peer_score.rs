@@ -0,0 +1,391 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scores of the peers sending consensus messages.
+//!
+//! Every misbehavior of a peer, a message with a signature that does not verify, a message it
+//! must not send or a burst of messages over the rate limit, adds a penalty to its score. The
+//! score halves every half-life, so an occasional invalid message is forgotten while a peer that
+//! keeps misbehaving reaches the ban score.
+//!
+//! Scoring only observes by default: bans are reported but no message is dropped. With
+//! enforcement on, the network task drops all messages of a banned peer until the ban expires,
+//! and the peer stays connected but is ignored. Peers of the current validator set are never
+//! rate limited nor banned, even with enforcement on: dropping their messages could stall
+//! consensus, so they are only scored.
+//!
+//! Messages of this node to itself are never scored.
+
+use crate::counters::{PEER_DROPPED_MSGS_COUNT, PEER_MISBEHAVIOR_COUNT};
+use gaptos::{
+    aptos_infallible::{Mutex, RwLock},
+    aptos_logger::prelude::*,
+    aptos_types::{validator_verifier::VerifyError, PeerId},
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// Window over which the message rate of a peer is measured.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// A message with a signature that does not verify.
+    InvalidMessage,
+    /// A message the peer must not send over the network, or over that protocol.
+    ProtocolViolation,
+    /// More messages in one rate window than the limit allows.
+    Spam,
+}
+
+impl Misbehavior {
+    fn penalty(self) -> f64 {
+        match self {
+            Misbehavior::InvalidMessage => 10.0,
+            Misbehavior::ProtocolViolation => 5.0,
+            Misbehavior::Spam => 2.0,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Misbehavior::InvalidMessage => "invalid_message",
+            Misbehavior::ProtocolViolation => "protocol_violation",
+            Misbehavior::Spam => "spam",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PeerScoreConfig {
+    /// Whether messages of banned peers and over the rate limit are dropped, rather than only
+    /// reported.
+    pub enforce: bool,
+    pub max_msgs_per_window: u64,
+    pub ban_score: f64,
+    pub ban_duration: Duration,
+    pub half_life: Duration,
+}
+
+impl PeerScoreConfig {
+    /// Can be configured via CONSENSUS_PEER_SCORE_ENFORCE (default false),
+    /// CONSENSUS_PEER_MAX_MSGS_PER_SEC (default 2000), CONSENSUS_PEER_BAN_SCORE (default 100),
+    /// CONSENSUS_PEER_BAN_SECS (default 300) and CONSENSUS_PEER_SCORE_HALF_LIFE_SECS (default 60)
+    /// environment variables
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr + std::fmt::Debug>(name: &str, default: T) -> T
+        where
+            T::Err: std::fmt::Display,
+        {
+            match std::env::var(name) {
+                Ok(value) => value.parse().unwrap_or_else(|e| {
+                    error!("Invalid {} {:?}: {}, using {:?}", name, value, e, default);
+                    default
+                }),
+                Err(_) => default,
+            }
+        }
+        Self {
+            enforce: var("CONSENSUS_PEER_SCORE_ENFORCE", false),
+            max_msgs_per_window: var("CONSENSUS_PEER_MAX_MSGS_PER_SEC", 2000),
+            ban_score: var("CONSENSUS_PEER_BAN_SCORE", 100.0),
+            ban_duration: Duration::from_secs(var("CONSENSUS_PEER_BAN_SECS", 300)),
+            half_life: Duration::from_secs(var("CONSENSUS_PEER_SCORE_HALF_LIFE_SECS", 60).max(1)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PeerScore {
+    score: f64,
+    updated: Option<Instant>,
+    banned_until: Option<Instant>,
+    window_start: Option<Instant>,
+    window_msgs: u64,
+    invalid_messages: u64,
+    protocol_violations: u64,
+    spam_windows: u64,
+    dropped_msgs: u64,
+}
+
+impl PeerScore {
+    fn decay(&mut self, half_life: Duration, now: Instant) {
+        if let Some(updated) = self.updated {
+            let half_lives =
+                now.saturating_duration_since(updated).as_secs_f64() / half_life.as_secs_f64();
+            self.score *= 0.5f64.powf(half_lives);
+        }
+        self.updated = Some(now);
+    }
+
+    fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until.is_some_and(|until| now < until)
+    }
+}
+
+/// Score of one peer as reported to operators.
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerScoreSnapshot {
+    pub peer: String,
+    pub score: f64,
+    /// Seconds until the ban of the peer expires, `None` if it is not banned. Reported even
+    /// where the ban is not enforced.
+    pub banned_for_secs: Option<u64>,
+    pub invalid_messages: u64,
+    pub protocol_violations: u64,
+    pub spam_windows: u64,
+    pub dropped_msgs: u64,
+}
+
+pub struct PeerScoreboard {
+    config: PeerScoreConfig,
+    /// The validator set of the current epoch.
+    validators: RwLock<HashSet<PeerId>>,
+    peers: Mutex<HashMap<PeerId, PeerScore>>,
+}
+
+impl PeerScoreboard {
+    pub fn new(config: PeerScoreConfig) -> Self {
+        Self { config, validators: RwLock::new(HashSet::new()), peers: Mutex::new(HashMap::new()) }
+    }
+
+    /// Sets the validator set of the new epoch, whose messages are never dropped.
+    pub fn set_validators(&self, validators: impl IntoIterator<Item = PeerId>) {
+        *self.validators.write() = validators.into_iter().collect();
+    }
+
+    fn is_validator(&self, peer: &PeerId) -> bool {
+        self.validators.read().contains(peer)
+    }
+
+    /// Whether a message of `peer` is processed. Counts the message of a peer outside the
+    /// validator set against its rate limit and, with enforcement on, refuses it while the peer
+    /// is banned or over the limit.
+    pub fn admit(&self, peer: PeerId) -> bool {
+        self.admit_at(peer, Instant::now())
+    }
+
+    fn admit_at(&self, peer: PeerId, now: Instant) -> bool {
+        if self.is_validator(&peer) {
+            return true;
+        }
+        let mut peers = self.peers.lock();
+        let entry = peers.entry(peer).or_default();
+        if (!entry.is_banned(now) && self.within_rate_limit(peer, entry, now)) ||
+            !self.config.enforce
+        {
+            return true;
+        }
+        entry.dropped_msgs += 1;
+        PEER_DROPPED_MSGS_COUNT.inc();
+        false
+    }
+
+    /// Counts a message in the rate window of the peer, returns whether it is within the limit.
+    fn within_rate_limit(&self, peer: PeerId, entry: &mut PeerScore, now: Instant) -> bool {
+        if entry
+            .window_start
+            .is_none_or(|start| now.saturating_duration_since(start) >= RATE_WINDOW)
+        {
+            entry.window_start = Some(now);
+            entry.window_msgs = 0;
+        }
+        entry.window_msgs += 1;
+        // Only the first message over the limit is penalized
+        if entry.window_msgs == self.config.max_msgs_per_window + 1 {
+            self.penalize(peer, entry, Misbehavior::Spam, now);
+        }
+        entry.window_msgs <= self.config.max_msgs_per_window
+    }
+
+    pub fn record(&self, peer: PeerId, misbehavior: Misbehavior) {
+        self.record_at(peer, misbehavior, Instant::now())
+    }
+
+    fn record_at(&self, peer: PeerId, misbehavior: Misbehavior, now: Instant) {
+        let mut peers = self.peers.lock();
+        let entry = peers.entry(peer).or_default();
+        self.penalize(peer, entry, misbehavior, now);
+    }
+
+    fn penalize(
+        &self,
+        peer: PeerId,
+        entry: &mut PeerScore,
+        misbehavior: Misbehavior,
+        now: Instant,
+    ) {
+        PEER_MISBEHAVIOR_COUNT.with_label_values(&[misbehavior.as_str()]).inc();
+        match misbehavior {
+            Misbehavior::InvalidMessage => entry.invalid_messages += 1,
+            Misbehavior::ProtocolViolation => entry.protocol_violations += 1,
+            Misbehavior::Spam => entry.spam_windows += 1,
+        }
+        entry.decay(self.config.half_life, now);
+        entry.score += misbehavior.penalty();
+        if entry.score >= self.config.ban_score && !entry.is_banned(now) {
+            entry.banned_until = Some(now + self.config.ban_duration);
+            let enforced = if !self.config.enforce {
+                "not enforced"
+            } else if self.is_validator(&peer) {
+                "not enforced on validators"
+            } else {
+                "enforced"
+            };
+            warn!(
+                remote_peer = peer,
+                "banning peer for {:?} ({}), score {:.1} after {:?}",
+                self.config.ban_duration,
+                enforced,
+                entry.score,
+                misbehavior
+            );
+        }
+    }
+
+    /// Scores of the peers that misbehaved, highest first.
+    pub fn snapshot(&self) -> Vec<PeerScoreSnapshot> {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn snapshot_at(&self, now: Instant) -> Vec<PeerScoreSnapshot> {
+        let mut peers = self.peers.lock();
+        let mut snapshot: Vec<_> = peers
+            .iter_mut()
+            .filter(|(_, entry)| entry.updated.is_some())
+            .map(|(peer, entry)| {
+                entry.decay(self.config.half_life, now);
+                PeerScoreSnapshot {
+                    peer: peer.to_hex_literal(),
+                    score: entry.score,
+                    banned_for_secs: entry
+                        .banned_until
+                        .filter(|until| now < *until)
+                        .map(|until| until.duration_since(now).as_secs()),
+                    invalid_messages: entry.invalid_messages,
+                    protocol_violations: entry.protocol_violations,
+                    spam_windows: entry.spam_windows,
+                    dropped_msgs: entry.dropped_msgs,
+                }
+            })
+            .collect();
+        snapshot.sort_by(|a, b| b.score.total_cmp(&a.score));
+        snapshot
+    }
+}
+
+/// The misbehavior a message that failed verification with `error` counts as, `None` if the
+/// failure may not be the sender's fault. Only a signature that does not verify is scored: a
+/// signer outside the validator set or too little voting power depend on this node's view of
+/// the validator set, and the other checks, e.g. of batch expirations, on its clock and config.
+pub fn verification_failure(error: &anyhow::Error) -> Option<Misbehavior> {
+    match error.chain().find_map(|e| e.downcast_ref::<VerifyError>())? {
+        VerifyError::UnknownAuthor | VerifyError::TooLittleVotingPower { .. } => None,
+        _ => Some(Misbehavior::InvalidMessage),
+    }
+}
+
+/// The scoreboard of this node, shared by the network task and the message verification.
+pub fn peer_scores() -> &'static PeerScoreboard {
+    static SCOREBOARD: OnceLock<PeerScoreboard> = OnceLock::new();
+    SCOREBOARD.get_or_init(|| PeerScoreboard::new(PeerScoreConfig::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scoreboard() -> PeerScoreboard {
+        PeerScoreboard::new(PeerScoreConfig {
+            enforce: true,
+            max_msgs_per_window: 3,
+            ban_score: 20.0,
+            ban_duration: Duration::from_secs(60),
+            half_life: Duration::from_secs(10),
+        })
+    }
+
+    #[test]
+    fn bans_repeated_invalid_messages_until_expiry() {
+        let scores = scoreboard();
+        let (peer, start) = (PeerId::random(), Instant::now());
+        let at = |secs| start + Duration::from_secs(secs);
+
+        scores.record_at(peer, Misbehavior::InvalidMessage, start);
+        assert!(scores.admit_at(peer, start));
+        // Half of the first penalty is left, so the second one does not reach the ban score
+        scores.record_at(peer, Misbehavior::InvalidMessage, at(10));
+        assert!(scores.admit_at(peer, at(10)));
+        scores.record_at(peer, Misbehavior::InvalidMessage, at(10));
+        assert!(!scores.admit_at(peer, at(11)));
+        assert_eq!(scores.snapshot_at(at(11))[0].banned_for_secs, Some(59));
+        assert!(scores.admit_at(peer, at(70)));
+        assert_eq!(scores.snapshot_at(at(70))[0].invalid_messages, 3);
+    }
+
+    #[test]
+    fn drops_messages_over_the_rate_limit() {
+        let scores = scoreboard();
+        let (peer, start) = (PeerId::random(), Instant::now());
+        for _ in 0..3 {
+            assert!(scores.admit_at(peer, start));
+        }
+        assert!(!scores.admit_at(peer, start));
+        assert!(!scores.admit_at(peer, start));
+        assert!(scores.admit_at(peer, start + RATE_WINDOW));
+
+        let snapshot = scores.snapshot_at(start + RATE_WINDOW);
+        assert_eq!(snapshot[0].spam_windows, 1);
+        assert_eq!(snapshot[0].dropped_msgs, 2);
+        assert_eq!(snapshot[0].banned_for_secs, None);
+    }
+
+    #[test]
+    fn only_observes_unless_enforced() {
+        let scores = PeerScoreboard::new(PeerScoreConfig { enforce: false, ..scoreboard().config });
+        let (peer, now) = (PeerId::random(), Instant::now());
+        for _ in 0..3 {
+            scores.record_at(peer, Misbehavior::InvalidMessage, now);
+        }
+        for _ in 0..5 {
+            assert!(scores.admit_at(peer, now));
+        }
+        let snapshot = scores.snapshot_at(now);
+        assert!(snapshot[0].banned_for_secs.is_some());
+        assert_eq!(snapshot[0].dropped_msgs, 0);
+    }
+
+    #[test]
+    fn never_drops_messages_of_validators() {
+        let scores = scoreboard();
+        let (validator, other, now) = (PeerId::random(), PeerId::random(), Instant::now());
+        scores.set_validators([validator]);
+        for peer in [validator, other] {
+            for _ in 0..3 {
+                scores.record_at(peer, Misbehavior::InvalidMessage, now);
+            }
+        }
+        for _ in 0..5 {
+            assert!(scores.admit_at(validator, now));
+        }
+        assert!(!scores.admit_at(other, now));
+
+        // Scores of validators are still reported
+        let snapshot = scores.snapshot_at(now);
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot.iter().all(|peer| peer.invalid_messages == 3));
+    }
+
+    #[test]
+    fn scores_only_signature_failures() {
+        let bad_signature = anyhow::Error::from(VerifyError::EmptySignature).context("vote");
+        assert_eq!(verification_failure(&bad_signature), Some(Misbehavior::InvalidMessage));
+        let unknown_author = anyhow::Error::from(VerifyError::UnknownAuthor).context("vote");
+        assert_eq!(verification_failure(&unknown_author), None);
+        assert_eq!(verification_failure(&anyhow::anyhow!("batch expiration too far")), None);
+    }
+}
@@ -16,7 +16,7 @@ use aptos_consensus_types::{
     vote_data::VoteData, wrapped_ledger_info::WrappedLedgerInfo,
 };
 use async_trait::async_trait;
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::BlockBufferManager;
 use gaptos::{
     aptos_crypto::{
         hash::{CryptoHash, ACCUMULATOR_PLACEHOLDER_HASH},
@@ -81,6 +81,7 @@ pub trait PersistentLivenessStorage: Send + Sync {
     // Returns a handle of the consensus db
     fn consensus_db(&self) -> Arc<ConsensusDB>;
 
+    // Returns the committed watermark of the block buffer, where recovery starts from
     async fn latest_commit_block_number(&self) -> u64;
 }
 
@@ -433,12 +434,17 @@ impl RecoveryData {
 pub struct StorageWriteProxy {
     db: Arc<ConsensusDB>,
     aptos_db: Arc<dyn DbReader>,
+    block_buffer_manager: Arc<BlockBufferManager>,
 }
 
 impl StorageWriteProxy {
-    pub fn new(db: Arc<ConsensusDB>, aptos_db: Arc<dyn DbReader>) -> Self {
+    pub fn new(
+        db: Arc<ConsensusDB>,
+        aptos_db: Arc<dyn DbReader>,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
         // let db = Arc::new(ConsensusDB::new(config.storage.dir()));
-        StorageWriteProxy { db, aptos_db }
+        StorageWriteProxy { db, aptos_db, block_buffer_manager }
     }
 }
 
@@ -611,6 +617,6 @@ impl PersistentLivenessStorage for StorageWriteProxy {
     }
 
     async fn latest_commit_block_number(&self) -> u64 {
-        get_block_buffer_manager().latest_commit_block_number().await
+        self.block_buffer_manager.ready_watermarks().await.committed
     }
 }
@@ -7,16 +7,23 @@ use crate::{
     consensus_observer::{
         network_message::ConsensusObserverMessage, publisher::ConsensusPublisher,
     },
-    counters::log_executor_error_occurred,
+    consensusdb::ConsensusDB,
+    counters::{
+        log_executor_error_occurred, COMMIT_MSG_VERIFY_BATCH_SIZE, EXECUTION_TIMEOUT_COUNT,
+    },
+    execution_pipeline::SIG_VERIFY_POOL,
     monitor,
     network::{IncomingCommitRequest, NetworkSender},
     network_interface::ConsensusMsg,
+    peer_score::{peer_scores, verification_failure},
     pipeline::{
         buffer::{Buffer, Cursor},
         buffer_item::BufferItem,
         commit_reliable_broadcast::{AckState, CommitMessage},
+        evidence::DoubleSignDetector,
         execution_schedule_phase::ExecutionRequest,
         execution_wait_phase::{ExecutionResponse, ExecutionWaitRequest},
+        execution_watchdog::{ExecutionWatchdog, WatchdogAction},
         persisting_phase::PersistingRequest,
         pipeline_phase::CountedRequest,
         signing_phase::{SigningRequest, SigningResponse},
@@ -29,7 +36,7 @@ use aptos_consensus_types::{
     pipelined_block::PipelinedBlock,
 };
 use aptos_executor_types::ExecutorResult;
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::BlockBufferManager;
 use bytes::Bytes;
 use futures::{
     channel::{
@@ -54,6 +61,7 @@ use gaptos::{
     },
 };
 use once_cell::sync::OnceCell;
+use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, HashMap},
     f32::consts::E,
@@ -64,11 +72,14 @@ use std::{
 };
 use tokio::time::{Duration, Instant};
 use tokio_retry::strategy::ExponentialBackoff;
+use tokio_util::sync::CancellationToken;
 
 pub const COMMIT_VOTE_BROADCAST_INTERVAL_MS: u64 = 1500;
 pub const COMMIT_VOTE_REBROADCAST_INTERVAL_MS: u64 = 30000;
 pub const LOOP_INTERVAL_MS: u64 = 1500;
 const MAX_CACHED_BLOCK_IDS_PER_AUTHOR_PER_COMMIT_VOTE_ROUND: usize = 2;
+// Upper bound on commit messages verified together, keeps a burst from delaying the first votes.
+const MAX_COMMIT_MSG_VERIFY_BATCH_SIZE: usize = 256;
 
 #[derive(Debug, Default)]
 pub struct ResetAck {}
@@ -148,6 +159,9 @@ pub struct BufferManager {
     epoch_state: Arc<EpochState>,
 
     ongoing_tasks: Arc<AtomicU64>,
+    // Handed to every request sent down the pipeline. Reset cancels it and installs a fresh
+    // one, so in-flight phase work is dropped promptly instead of being waited out.
+    cancel_token: CancellationToken,
     // Since proposal_generator is not aware of reconfiguration any more, the suffix blocks
     // will not have the same timestamp as the reconfig block which violates the invariant
     // that block.timestamp == state.timestamp because no txn is executed in suffix blocks.
@@ -174,6 +188,12 @@ pub struct BufferManager {
     // When a CommitMessage::Decision arrives but the block is not yet in the buffer,
     // the proof is cached here and applied when the block finishes execution.
     pending_commit_proofs: BTreeMap<Round, LedgerInfoWithSignatures>,
+
+    double_sign_detector: DoubleSignDetector,
+
+    execution_watchdog: ExecutionWatchdog,
+
+    block_buffer_manager: Arc<BlockBufferManager>,
 }
 
 /// How an incoming commit vote's round relates to the local commit-vote cache window.
@@ -249,6 +269,7 @@ impl BufferManager {
         consensus_observer_config: ConsensusObserverConfig,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
         max_pending_rounds_in_commit_vote_cache: Round,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> Self {
         let buffer = Buffer::<BufferItem>::new();
 
@@ -298,6 +319,7 @@ impl BufferManager {
 
             epoch_state,
             ongoing_tasks,
+            cancel_token: CancellationToken::new(),
             end_epoch_timestamp: OnceCell::new(),
             previous_commit_time: Instant::now(),
             reset_flag,
@@ -311,9 +333,21 @@ impl BufferManager {
             commit_vote_cache: BTreeMap::new(),
             max_pending_rounds_in_commit_vote_cache,
             pending_commit_proofs: BTreeMap::new(),
+
+            double_sign_detector: DoubleSignDetector::default(),
+
+            execution_watchdog: ExecutionWatchdog::from_env(),
+
+            block_buffer_manager,
         }
     }
 
+    /// Persists the double sign evidence found in incoming commit votes to `consensus_db`.
+    pub fn with_evidence_db(mut self, consensus_db: Arc<ConsensusDB>) -> Self {
+        self.double_sign_detector = self.double_sign_detector.with_consensus_db(consensus_db);
+        self
+    }
+
     fn try_add_pending_commit_proof(&mut self, commit_proof: LedgerInfoWithSignatures) -> bool {
         const MAX_PENDING_COMMIT_PROOFS: usize = 100;
 
@@ -392,7 +426,7 @@ impl BufferManager {
     }
 
     fn create_new_request<Request>(&self, req: Request) -> CountedRequest<Request> {
-        CountedRequest::new(req, self.ongoing_tasks.clone())
+        CountedRequest::new_cancellable(req, self.ongoing_tasks.clone(), self.cancel_token.clone())
     }
 
     fn spawn_retry_request<T: Send + 'static>(
@@ -538,6 +572,10 @@ impl BufferManager {
             self.execution_root
         } else {
             info!("Advance execution root from {:?} to {:?}", cursor, self.execution_root);
+            if self.execution_root.is_some() {
+                let block_id = self.buffer.get(&self.execution_root).block_id();
+                self.execution_watchdog.watch(block_id, Instant::now());
+            }
             // Otherwise do nothing, because the execution wait phase is driven by the response of
             // the execution schedule phase, which is in turn fed as soon as the ordered blocks
             // come in.
@@ -630,12 +668,15 @@ impl BufferManager {
     }
 
     /// Reset any request in buffer manager, this is important to avoid race condition with state
-    /// sync. Internal requests are managed with ongoing_tasks.
+    /// sync. Internal requests are cancelled through `cancel_token` and tracked with ongoing_tasks.
     /// Incoming ordered blocks are pulled, it should only have existing blocks but no new blocks
     /// until reset finishes.
     async fn reset(&mut self) {
+        // Cancel everything issued before the reset; requests created afterwards get a new token.
+        std::mem::replace(&mut self.cancel_token, CancellationToken::new()).cancel();
         self.buffer = Buffer::new();
         self.execution_root = None;
+        self.execution_watchdog.clear();
         self.signing_root = None;
         self.commit_vote_cache.clear();
         self.pending_commit_proofs.clear();
@@ -643,9 +684,9 @@ impl BufferManager {
         self.commit_proof_rb_handle.take();
         // purge the incoming blocks queue
         while let Ok(Some(_)) = self.block_rx.try_next() {}
-        // Wait for ongoing tasks to finish before sending back ack, with a timeout
-        // to prevent permanent deadlock if a task is leaked.
-        get_block_buffer_manager().release_inflight_blocks().await;
+        // Cancelled tasks release their guards as soon as they are dropped, wait for them before
+        // sending back ack, with a timeout to prevent permanent deadlock if a task is leaked.
+        self.block_buffer_manager.release_inflight_blocks().await;
         let reset_deadline = Instant::now() + Duration::from_secs(30);
         while self.ongoing_tasks.load(Ordering::SeqCst) > 0 {
             if Instant::now() >= reset_deadline {
@@ -688,6 +729,52 @@ impl BufferManager {
             .expect("Failed to send execution wait request.");
     }
 
+    /// Schedules the execution root again after its execution failed, unless it has been stuck
+    /// for long enough that the watchdog gives up on it and the pipeline is reset instead.
+    async fn retry_execution_root(&mut self, block_id: HashValue) {
+        let now = Instant::now();
+        let waited = self.execution_watchdog.waited(now);
+        let action = self.execution_watchdog.on_failure(block_id, now);
+        if action != WatchdogAction::Retry {
+            let item = self.buffer.get(&self.execution_root);
+            let blocks = item.get_blocks();
+            let block_numbers = (
+                blocks.first().and_then(|b| b.block().block_number()),
+                blocks.last().and_then(|b| b.block().block_number()),
+            );
+            let watermarks = self.block_buffer_manager.watermarks().await;
+            error!(
+                epoch = item.commit_info().epoch(),
+                round = item.commit_info().round(),
+                block_id = block_id,
+                "Execution of blocks {:?} stuck for {:?}, {}: {} buffered items, {} ongoing tasks, \
+                 block buffer {:?}",
+                block_numbers,
+                waited,
+                if action == WatchdogAction::Reset { "resetting" } else { "retrying once" },
+                self.buffer.len(),
+                self.ongoing_tasks.load(Ordering::SeqCst),
+                watermarks,
+            );
+        }
+        match action {
+            WatchdogAction::Reset => {
+                EXECUTION_TIMEOUT_COUNT.with_label_values(&["reset"]).inc();
+                // Consensus falls behind its peers from here on and state syncs past the block
+                self.reset().await;
+                return;
+            }
+            WatchdogAction::Diagnose => EXECUTION_TIMEOUT_COUNT.with_label_values(&["retry"]).inc(),
+            WatchdogAction::Retry => {}
+        }
+        let mut tx = self.execution_schedule_retry_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            // buffer manager can be dropped at the point of sending retry
+            let _ = tx.send(()).await;
+        });
+    }
+
     async fn retry_schedule_phase(&mut self) {
         let mut cursor = self.execution_root;
         let mut count = 0;
@@ -730,9 +817,8 @@ impl BufferManager {
         let epoch = last_block.block().epoch();
 
         // Path 1: Check if reth-side BlockBufferManager has cached epoch info (suffix block case).
-        if let Some(mut epoch_info) = block_buffer_manager::get_block_buffer_manager()
-            .get_epoch_change_block_info(block_number, epoch)
-            .await
+        if let Some(mut epoch_info) =
+            self.block_buffer_manager.get_epoch_change_block_info(block_number, epoch).await
         {
             info!(
                 "[EpochChange] EpochBlockInfo for suffix block {}: round={}, timestamp={}",
@@ -904,13 +990,14 @@ impl BufferManager {
     /// it scans the whole buffer for a matching blockinfo
     /// if found, try advancing the item to be aggregated
     fn process_commit_message(&mut self, commit_msg: IncomingCommitRequest) -> Option<HashValue> {
-        let IncomingCommitRequest { req, protocol, response_sender } = commit_msg;
+        let IncomingCommitRequest { req, protocol, response_sender, .. } = commit_msg;
         match req {
             CommitMessage::Vote(vote) => {
                 // find the corresponding item
                 let author = vote.author();
                 let commit_info = vote.commit_info().clone();
                 info!("Receive commit vote {} from {}", commit_info, author);
+                self.double_sign_detector.observe(&vote);
                 let target_block_id = vote.commit_info().id();
                 let current_cursor =
                     self.buffer.find_elem_by_key(*self.buffer.head_cursor(), target_block_id);
@@ -1086,21 +1173,20 @@ impl BufferManager {
         let mut interval = tokio::time::interval(Duration::from_millis(LOOP_INTERVAL_MS));
         let mut commit_msg_rx = self.commit_msg_rx.take().expect("commit msg rx must exist");
         let epoch_state = self.epoch_state.clone();
-        let bounded_executor = self.bounded_executor.clone();
         spawn_named!("buffer manager verification", async move {
             while let Some(commit_msg) = commit_msg_rx.next().await {
-                let tx = verified_commit_msg_tx.clone();
-                let epoch_state_clone = epoch_state.clone();
-                bounded_executor
-                    .spawn(async move {
-                        match commit_msg.req.verify(&epoch_state_clone.verifier) {
-                            Ok(_) => {
-                                let _ = tx.unbounded_send(commit_msg);
-                            }
-                            Err(e) => warn!("Invalid commit message: {}", e),
-                        }
-                    })
-                    .await;
+                // Drain whatever else is already queued so the signatures can be checked
+                // together instead of one task per message.
+                let mut batch = vec![commit_msg];
+                while batch.len() < MAX_COMMIT_MSG_VERIFY_BATCH_SIZE {
+                    match commit_msg_rx.next().now_or_never() {
+                        Some(Some(commit_msg)) => batch.push(commit_msg),
+                        _ => break,
+                    }
+                }
+                for commit_msg in verify_commit_msg_batch(batch, epoch_state.clone()).await {
+                    let _ = verified_commit_msg_tx.unbounded_send(commit_msg);
+                }
             }
         });
         while !self.stop {
@@ -1132,12 +1218,7 @@ impl BufferManager {
                     if let Some(block_id) = self.advance_execution_root() {
                         // if the response is for the current execution root, retry the schedule phase
                         if response_block_id == block_id {
-                            let mut tx = self.execution_schedule_retry_tx.clone();
-                            tokio::spawn(async move {
-                                tokio::time::sleep(Duration::from_millis(100)).await;
-                                // buffer manager can be dropped at the point of sending retry
-                                let _ = tx.send(()).await;
-                            });
+                            self.retry_execution_root(block_id).await;
                         }
                     }
                     if self.signing_root.is_none() {
@@ -1197,6 +1278,50 @@ impl BufferManager {
     }
 }
 
+/// Sort key grouping commit messages of the same block together, lower rounds first, so the
+/// votes for a block reach the aggregator back to back once verified.
+fn commit_msg_group_key(msg: &CommitMessage) -> Option<(Round, HashValue)> {
+    match msg {
+        CommitMessage::Vote(vote) => Some((vote.commit_info().round(), vote.commit_info().id())),
+        CommitMessage::Decision(decision) => {
+            Some((decision.round(), decision.ledger_info().commit_info().id()))
+        }
+        CommitMessage::Ack(_) | CommitMessage::Nack => None,
+    }
+}
+
+/// Verifies a batch of incoming commit messages on the signature verification pool and returns
+/// the valid ones grouped per block. Invalid messages are logged and dropped, and those with a
+/// signature that does not verify count against the score of their sender.
+async fn verify_commit_msg_batch(
+    mut batch: Vec<IncomingCommitRequest>,
+    epoch_state: Arc<EpochState>,
+) -> Vec<IncomingCommitRequest> {
+    COMMIT_MSG_VERIFY_BATCH_SIZE.observe(batch.len() as f64);
+    batch.sort_by_key(|commit_msg| commit_msg_group_key(&commit_msg.req));
+    tokio::task::spawn_blocking(move || {
+        SIG_VERIFY_POOL.install(|| {
+            batch
+                .into_par_iter()
+                .filter_map(|commit_msg| match commit_msg.req.verify(&epoch_state.verifier) {
+                    Ok(_) => Some(commit_msg),
+                    Err(e) => {
+                        warn!("Invalid commit message from {:?}: {}", commit_msg.sender, e);
+                        if let (Some(sender), Some(misbehavior)) =
+                            (commit_msg.sender, verification_failure(&e))
+                        {
+                            peer_scores().record(sender, misbehavior);
+                        }
+                        None
+                    }
+                })
+                .collect()
+        })
+    })
+    .await
+    .expect("Failed to spawn_blocking for commit message verification.")
+}
+
 fn reply_nack(protocol: ProtocolId, response_sender: oneshot::Sender<Result<Bytes, RpcError>>) {
     let response = ConsensusMsg::CommitMessage(Box::new(CommitMessage::Nack));
     if let Ok(bytes) = protocol.to_bytes(&response) {
@@ -1290,3 +1415,87 @@ mod commit_vote_round_class_tests {
         ));
     }
 }
+
+#[cfg(test)]
+mod commit_msg_verify_batch_tests {
+    use super::{
+        oneshot, peer_scores, verify_commit_msg_batch, Author, CommitMessage, CommitVote,
+        EpochState, HashValue, IncomingCommitRequest, ProtocolId, Round,
+    };
+    use gaptos::aptos_types::{
+        block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+        validator_verifier::random_validator_verifier,
+    };
+    use std::sync::Arc;
+
+    fn vote_request(
+        author: &ValidatorSigner,
+        key: &ValidatorSigner,
+        ledger_info: &LedgerInfo,
+    ) -> IncomingCommitRequest {
+        let signature = key.sign(ledger_info).unwrap();
+        let vote = CommitVote::new_with_signature(author.author(), ledger_info.clone(), signature);
+        IncomingCommitRequest {
+            req: CommitMessage::Vote(vote),
+            sender: Some(author.author()),
+            protocol: ProtocolId::ConsensusRpcBcs,
+            response_sender: oneshot::channel().0,
+        }
+    }
+
+    fn voters(batch: &[IncomingCommitRequest]) -> Vec<(Round, Author)> {
+        batch
+            .iter()
+            .map(|msg| match &msg.req {
+                CommitMessage::Vote(vote) => (vote.round(), vote.author()),
+                _ => unreachable!("only votes are sent"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn all_valid_votes_are_kept_grouped_per_block() {
+        let (signers, verifier) = random_validator_verifier(3, None, false);
+        let epoch_state = Arc::new(EpochState::new(1, verifier));
+        let later = LedgerInfo::new(BlockInfo::random_with_epoch(1, 6), HashValue::zero());
+        let earlier = LedgerInfo::new(BlockInfo::random_with_epoch(1, 5), HashValue::zero());
+        let batch = vec![
+            vote_request(&signers[0], &signers[0], &later),
+            vote_request(&signers[1], &signers[1], &earlier),
+            vote_request(&signers[2], &signers[2], &later),
+            vote_request(&signers[0], &signers[0], &earlier),
+        ];
+
+        let verified = verify_commit_msg_batch(batch, epoch_state).await;
+
+        let rounds: Vec<_> = voters(&verified).into_iter().map(|(round, _)| round).collect();
+        assert_eq!(rounds, vec![5, 5, 6, 6]);
+    }
+
+    #[tokio::test]
+    async fn a_bad_signature_drops_only_its_vote_and_scores_the_sender() {
+        let (signers, verifier) = random_validator_verifier(3, None, false);
+        let epoch_state = Arc::new(EpochState::new(1, verifier));
+        let ledger_info = LedgerInfo::new(BlockInfo::random_with_epoch(1, 5), HashValue::zero());
+        // signers[1] claims a vote signed with the key of signers[2]
+        let batch = vec![
+            vote_request(&signers[0], &signers[0], &ledger_info),
+            vote_request(&signers[1], &signers[2], &ledger_info),
+            vote_request(&signers[2], &signers[2], &ledger_info),
+        ];
+
+        let verified = verify_commit_msg_batch(batch, epoch_state).await;
+
+        let mut authors: Vec<_> = voters(&verified).into_iter().map(|(_, author)| author).collect();
+        authors.sort();
+        let mut expected = vec![signers[0].author(), signers[2].author()];
+        expected.sort();
+        assert_eq!(authors, expected);
+        let scored = peer_scores()
+            .snapshot()
+            .into_iter()
+            .find(|score| score.peer == signers[1].author().to_hex_literal())
+            .expect("the sender of the bad signature is scored");
+        assert_eq!(scored.invalid_messages, 1);
+    }
+}
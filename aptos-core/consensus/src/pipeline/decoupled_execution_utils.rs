@@ -16,6 +16,7 @@ use crate::{
     state_replication::StateComputer,
 };
 use aptos_consensus_types::common::Author;
+use block_buffer_manager::BlockBufferManager;
 use futures::channel::mpsc::UnboundedReceiver;
 use gaptos::{
     aptos_bounded_executor::BoundedExecutor,
@@ -45,6 +46,7 @@ pub fn prepare_phases_and_buffer_manager(
     consensus_observer_config: ConsensusObserverConfig,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     max_pending_rounds_in_commit_vote_cache: u64,
+    block_buffer_manager: Arc<BlockBufferManager>,
 ) -> (
     PipelinePhase<ExecutionSchedulePhase>,
     PipelinePhase<ExecutionWaitPhase>,
@@ -137,6 +139,7 @@ pub fn prepare_phases_and_buffer_manager(
             consensus_observer_config,
             consensus_publisher,
             max_pending_rounds_in_commit_vote_cache,
+            block_buffer_manager,
         ),
     )
 }
@@ -0,0 +1,156 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of validators that sign conflicting commit votes.
+//!
+//! Every verified commit vote the buffer manager receives goes through [`DoubleSignDetector`].
+//! The first vote of each author in a round is remembered; a later vote from the same author for
+//! the same round but a different ledger info is a double sign. Both signed votes are kept as
+//! [`DoubleSignEvidence`] in ConsensusDB, so anyone can re-verify them against the epoch's
+//! validator set before slashing.
+
+use crate::{consensusdb::ConsensusDB, counters::DOUBLE_SIGN_EVIDENCE_COUNT};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    pipeline::commit_vote::CommitVote,
+};
+use gaptos::aptos_logger::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// Rounds below the highest observed round minus this are forgotten.
+const DOUBLE_SIGN_WINDOW_ROUNDS: Round = 1_000;
+
+/// Two commit votes signed by the same validator for the same round over different ledger
+/// infos.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DoubleSignEvidence {
+    pub first: CommitVote,
+    pub second: CommitVote,
+}
+
+impl DoubleSignEvidence {
+    pub fn author(&self) -> Author {
+        self.first.author()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.first.epoch()
+    }
+
+    pub fn round(&self) -> Round {
+        self.first.round()
+    }
+}
+
+#[derive(Default)]
+pub struct DoubleSignDetector {
+    // First vote of each author and whether it has been reported yet, by round
+    votes: BTreeMap<Round, HashMap<Author, (CommitVote, bool)>>,
+    highest_round: Round,
+    consensus_db: Option<Arc<ConsensusDB>>,
+}
+
+impl DoubleSignDetector {
+    pub fn with_consensus_db(mut self, consensus_db: Arc<ConsensusDB>) -> Self {
+        self.consensus_db = Some(consensus_db);
+        self
+    }
+
+    /// Checks a verified commit vote against the earlier votes of its author and persists the
+    /// evidence if it conflicts with one of them. Only the first conflict of an author in a
+    /// round is reported.
+    pub fn observe(&mut self, vote: &CommitVote) -> Option<DoubleSignEvidence> {
+        let round = vote.round();
+        if round.saturating_add(DOUBLE_SIGN_WINDOW_ROUNDS) <= self.highest_round {
+            return None;
+        }
+        if round > self.highest_round {
+            self.highest_round = round;
+            let lowest_round = round.saturating_sub(DOUBLE_SIGN_WINDOW_ROUNDS);
+            self.votes = self.votes.split_off(&lowest_round);
+        }
+
+        let (first, reported) = self
+            .votes
+            .entry(round)
+            .or_default()
+            .entry(vote.author())
+            .or_insert_with(|| (vote.clone(), false));
+        // Votes are rebroadcast until acked, so the same conflict shows up many times
+        if *reported || first.ledger_info() == vote.ledger_info() {
+            return None;
+        }
+        *reported = true;
+        let evidence = DoubleSignEvidence { first: first.clone(), second: vote.clone() };
+
+        DOUBLE_SIGN_EVIDENCE_COUNT.inc();
+        error!(
+            epoch = evidence.epoch(),
+            round = round,
+            author = vote.author(),
+            "Validator signed conflicting commit votes: {} and {}",
+            evidence.first.commit_info(),
+            evidence.second.commit_info(),
+        );
+        if let Some(consensus_db) = &self.consensus_db {
+            if let Err(e) = consensus_db.save_double_sign_evidence(&evidence) {
+                error!(error = ?e, "Failed to persist double sign evidence");
+            }
+        }
+        Some(evidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::{
+        aptos_crypto::HashValue,
+        aptos_types::{
+            block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+        },
+    };
+
+    fn vote(signer: &ValidatorSigner, block_info: BlockInfo) -> CommitVote {
+        let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+        CommitVote::new(signer.author(), ledger_info, signer).unwrap()
+    }
+
+    #[test]
+    fn detects_conflicting_votes_once() {
+        let signer = ValidatorSigner::random(None);
+        let mut detector = DoubleSignDetector::default();
+        let first = vote(&signer, BlockInfo::random(5));
+        let second = vote(&signer, BlockInfo::random(5));
+
+        assert!(detector.observe(&first).is_none());
+        // A rebroadcast of the same vote is not a double sign
+        assert!(detector.observe(&first).is_none());
+        let evidence = detector.observe(&second).unwrap();
+        assert_eq!(evidence, DoubleSignEvidence { first: first.clone(), second: second.clone() });
+        assert_eq!(evidence.author(), signer.author());
+        assert_eq!(evidence.round(), 5);
+        assert!(detector.observe(&second).is_none());
+        assert!(detector.observe(&first).is_none());
+    }
+
+    #[test]
+    fn ignores_other_rounds_and_authors() {
+        let signer = ValidatorSigner::random(None);
+        let other = ValidatorSigner::random([1u8; 32]);
+        let mut detector = DoubleSignDetector::default();
+
+        assert!(detector.observe(&vote(&signer, BlockInfo::random(5))).is_none());
+        assert!(detector.observe(&vote(&signer, BlockInfo::random(6))).is_none());
+        assert!(detector.observe(&vote(&other, BlockInfo::random(5))).is_none());
+        // Rounds that fell out of the window are no longer tracked
+        assert!(detector
+            .observe(&vote(&signer, BlockInfo::random(6 + DOUBLE_SIGN_WINDOW_ROUNDS)))
+            .is_none());
+        assert!(detector.observe(&vote(&signer, BlockInfo::random(5))).is_none());
+    }
+}
@@ -55,6 +55,7 @@ use gaptos::{
     },
     move_core_types::account_address::AccountAddress,
 };
+use proposer_reth_map::EpochContext;
 use std::sync::Arc;
 
 use super::pipeline_builder::PipelineBuilder;
@@ -77,6 +78,7 @@ pub trait TExecutionClient: Send + Sync {
         rand_msg_rx: aptos_channel::Receiver<AccountAddress, IncomingRandGenRequest>,
         highest_committed_round: Round,
         consensus_db: Option<Arc<ConsensusDB>>,
+        epoch_context: Arc<EpochContext>,
     );
 
     /// This is needed for some DAG tests. Clean this up as a TODO.
@@ -239,7 +241,7 @@ impl ExecutionProxyClient {
                     self.rand_storage.clone(),
                     self.bounded_executor.clone(),
                     &self.consensus_config.rand_rb_config,
-                    consensus_db.unwrap(),
+                    consensus_db.clone().unwrap(),
                 );
 
                 tokio::spawn(rand_manager.start(
@@ -285,7 +287,12 @@ impl ExecutionProxyClient {
             consensus_observer_config,
             consensus_publisher,
             self.consensus_config.max_pending_rounds_in_commit_vote_cache,
+            self.execution_proxy.block_buffer_manager().clone(),
         );
+        let buffer_manager = match consensus_db {
+            Some(consensus_db) => buffer_manager.with_evidence_db(consensus_db),
+            None => buffer_manager,
+        };
 
         tokio::spawn(execution_schedule_phase.start());
         tokio::spawn(execution_wait_phase.start());
@@ -312,6 +319,7 @@ impl TExecutionClient for ExecutionProxyClient {
         rand_msg_rx: aptos_channel::Receiver<AccountAddress, IncomingRandGenRequest>,
         highest_committed_round: Round,
         consensus_db: Option<Arc<ConsensusDB>>,
+        epoch_context: Arc<EpochContext>,
     ) {
         let maybe_rand_msg_tx = self.spawn_decoupled_execution(
             maybe_consensus_key,
@@ -342,6 +350,7 @@ impl TExecutionClient for ExecutionProxyClient {
             block_executor_onchain_config,
             transaction_deduper,
             randomness_enabled,
+            epoch_context,
         );
 
         maybe_rand_msg_tx
@@ -499,6 +508,7 @@ impl TExecutionClient for DummyExecutionClient {
         _rand_msg_rx: aptos_channel::Receiver<AccountAddress, IncomingRandGenRequest>,
         _highest_committed_round: Round,
         _consensus_db: Option<Arc<ConsensusDB>>,
+        _epoch_context: Arc<EpochContext>,
     ) {
     }
 
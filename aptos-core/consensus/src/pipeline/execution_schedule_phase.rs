@@ -91,13 +91,25 @@ impl StatelessPipeline for ExecutionSchedulePhase {
         }
 
         // In the future being returned, wait for the compute results in order.
+        // The spawned task is detached from the wait phase, so it has to observe the reset
+        // cancellation itself to avoid being orphaned.
+        let cancel_token = lifetime_guard.cancel_token().clone();
         let fut = tokio::task::spawn(async move {
-            let mut results = vec![];
-            for (block, fut) in itertools::zip_eq(ordered_blocks, futs) {
-                debug!("try to receive compute result for block {}", block.id());
-                results.push(block.set_execution_result(fut.await?));
+            let wait_results = async move {
+                let mut results = vec![];
+                for (block, fut) in itertools::zip_eq(ordered_blocks, futs) {
+                    debug!("try to receive compute result for block {}", block.id());
+                    results.push(block.set_execution_result(fut.await?));
+                }
+                Ok::<_, ExecutorError>(results)
+            };
+            tokio::select! {
+                biased;
+                _ = cancel_token.cancelled() => {
+                    Err(ExecutorError::internal_err("execution cancelled by pipeline reset"))
+                }
+                res = wait_results => res,
             }
-            Ok(results)
         })
         .map_err(ExecutorError::internal_err)
         .and_then(|res| async { res });
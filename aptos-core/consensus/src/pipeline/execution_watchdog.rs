@@ -0,0 +1,122 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of blocks the execution layer never finishes.
+//!
+//! The buffer manager waits for the result of its execution root, the first ordered item that is
+//! not executed yet, and schedules it again whenever a wait fails. [`ExecutionWatchdog`] bounds
+//! how long that may go on. Once the root has waited for the execution timeout, the buffer
+//! manager logs what the pipeline looks like and retries one more time. If that times out too,
+//! the buffer manager resets instead of waiting forever: consensus then falls behind its peers
+//! and state syncs past the block.
+
+use gaptos::aptos_crypto::HashValue;
+use tokio::time::{Duration, Instant};
+
+/// What the buffer manager does after a failed wait for the execution root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Still within the timeout, schedule the root again.
+    Retry,
+    /// Timed out for the first time, log diagnostics and schedule the root again.
+    Diagnose,
+    /// Timed out again after the extra retry, reset the pipeline.
+    Reset,
+}
+
+struct WatchedRoot {
+    block_id: HashValue,
+    since: Instant,
+    timed_out: bool,
+}
+
+pub struct ExecutionWatchdog {
+    timeout: Duration,
+    root: Option<WatchedRoot>,
+}
+
+impl ExecutionWatchdog {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout, root: None }
+    }
+
+    /// Time the execution root may wait for its result before it counts as stuck.
+    /// Can be configured via CONSENSUS_EXECUTION_TIMEOUT_SECS environment variable, defaults to
+    /// 30
+    pub fn from_env() -> Self {
+        let secs = std::env::var("CONSENSUS_EXECUTION_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(30);
+        Self::new(Duration::from_secs(secs))
+    }
+
+    /// Starts timing `block_id` unless it is already the watched root.
+    pub fn watch(&mut self, block_id: HashValue, now: Instant) {
+        if self.root.as_ref().is_some_and(|root| root.block_id == block_id) {
+            return;
+        }
+        self.root = Some(WatchedRoot { block_id, since: now, timed_out: false });
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+    }
+
+    /// How long the watched root has been waiting since it was watched or last timed out.
+    pub fn waited(&self, now: Instant) -> Duration {
+        self.root.as_ref().map_or(Duration::ZERO, |root| now.saturating_duration_since(root.since))
+    }
+
+    pub fn on_failure(&mut self, block_id: HashValue, now: Instant) -> WatchdogAction {
+        self.watch(block_id, now);
+        let timeout = self.timeout;
+        let root = self.root.as_mut().expect("watched above");
+        if now.saturating_duration_since(root.since) < timeout {
+            return WatchdogAction::Retry;
+        }
+        if root.timed_out {
+            return WatchdogAction::Reset;
+        }
+        root.timed_out = true;
+        root.since = now;
+        WatchdogAction::Diagnose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_once_then_resets() {
+        let mut watchdog = ExecutionWatchdog::new(Duration::from_secs(10));
+        let (block, start) = (HashValue::random(), Instant::now());
+        watchdog.watch(block, start);
+
+        let at = |secs| start + Duration::from_secs(secs);
+        assert_eq!(watchdog.on_failure(block, at(5)), WatchdogAction::Retry);
+        assert_eq!(watchdog.on_failure(block, at(10)), WatchdogAction::Diagnose);
+        // The extra retry gets a full timeout of its own
+        assert_eq!(watchdog.waited(at(15)), Duration::from_secs(5));
+        assert_eq!(watchdog.on_failure(block, at(15)), WatchdogAction::Retry);
+        assert_eq!(watchdog.on_failure(block, at(20)), WatchdogAction::Reset);
+    }
+
+    #[test]
+    fn new_root_starts_over() {
+        let mut watchdog = ExecutionWatchdog::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let (first, second) = (HashValue::random(), HashValue::random());
+        watchdog.watch(first, start);
+        assert_eq!(watchdog.on_failure(first, at(10)), WatchdogAction::Diagnose);
+
+        watchdog.watch(second, at(12));
+        assert_eq!(watchdog.on_failure(second, at(20)), WatchdogAction::Retry);
+        assert_eq!(watchdog.on_failure(second, at(22)), WatchdogAction::Diagnose);
+
+        watchdog.clear();
+        assert_eq!(watchdog.waited(at(30)), Duration::ZERO);
+    }
+}
@@ -28,8 +28,10 @@ pub mod buffer_manager;
 pub mod commit_reliable_broadcast;
 pub mod decoupled_execution_utils;
 pub mod errors;
+pub mod evidence;
 pub mod execution_schedule_phase;
 pub mod execution_wait_phase;
+pub mod execution_watchdog;
 pub mod hashable;
 pub mod persisting_phase;
 pub mod pipeline_phase;
@@ -61,6 +61,9 @@ impl StatelessPipeline for PersistingPhase {
     type Response = PersistingResponse;
 
     const NAME: &'static str = "persisting";
+    // Once the ledger info is committed, the notify, callback and epoch change must follow,
+    // otherwise the node is left half committed.
+    const CANCELLABLE: bool = false;
 
     async fn process(&self, req: PersistingRequest) -> PersistingResponse {
         let PersistingRequest { blocks, commit_ledger_info, callback } = req;
@@ -11,6 +11,7 @@ use crate::{
     execution_pipeline::SIG_VERIFY_POOL,
     monitor,
     payload_manager::TPayloadManager,
+    state_computer::block_execution_meta_util,
     txn_notifier::TxnNotifier,
 };
 use anyhow::anyhow;
@@ -26,7 +27,8 @@ use aptos_consensus_types::{
 };
 use aptos_executor_types::{BlockExecutorTrait, StateComputeResult};
 use aptos_mempool::core_mempool::transaction::VerifiedTxn;
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::BlockBufferManager;
+use fail::fail_point;
 use futures::FutureExt;
 use gaptos::{
     api_types::{
@@ -55,6 +57,7 @@ use gaptos::{
     move_core_types::account_address::AccountAddress,
 };
 use itertools::Itertools;
+use proposer_reth_map::EpochContext;
 use rayon::prelude::*;
 use std::{
     collections::HashMap,
@@ -83,12 +86,14 @@ pub struct PipelineBuilder {
     block_preparer: Arc<BlockPreparer>,
     executor: Arc<dyn BlockExecutorTrait>,
     validators: Arc<[AccountAddress]>,
+    epoch_context: Arc<EpochContext>,
     block_executor_onchain_config: BlockExecutorConfigFromOnchain,
     is_randomness_enabled: bool,
     signer: Arc<ValidatorSigner>,
     state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
     payload_manager: Arc<dyn TPayloadManager>,
     txn_notifier: Arc<dyn TxnNotifier>,
+    block_buffer_manager: Arc<BlockBufferManager>,
     block_metadata: Arc<Mutex<HashMap<BlockId, ExternalBlockMeta>>>,
 }
 
@@ -163,23 +168,27 @@ impl PipelineBuilder {
         block_preparer: Arc<BlockPreparer>,
         executor: Arc<dyn BlockExecutorTrait>,
         validators: Arc<[AccountAddress]>,
+        epoch_context: Arc<EpochContext>,
         block_executor_onchain_config: BlockExecutorConfigFromOnchain,
         is_randomness_enabled: bool,
         signer: Arc<ValidatorSigner>,
         state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
         payload_manager: Arc<dyn TPayloadManager>,
         txn_notifier: Arc<dyn TxnNotifier>,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> Self {
         Self {
             block_preparer,
             executor,
             validators,
+            epoch_context,
             block_executor_onchain_config,
             is_randomness_enabled,
             signer,
             state_sync_notifier,
             payload_manager,
             txn_notifier,
+            block_buffer_manager,
             block_metadata: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -255,7 +264,9 @@ impl PipelineBuilder {
                 block.clone(),
                 self.is_randomness_enabled,
                 self.validators.clone(),
+                self.epoch_context.clone(),
                 self.block_executor_onchain_config.clone(),
+                self.block_buffer_manager.clone(),
             ),
             &mut abort_handles,
         );
@@ -265,6 +276,7 @@ impl PipelineBuilder {
                 parent.ledger_update_fut.clone(),
                 self.executor.clone(),
                 block.clone(),
+                self.block_buffer_manager.clone(),
             ),
             &mut abort_handles,
         );
@@ -276,6 +288,7 @@ impl PipelineBuilder {
                 commit_proof_rx.resubscribe(),
                 self.signer.clone(),
                 block.clone(),
+                self.block_buffer_manager.clone(),
             ),
             &mut abort_handles,
         );
@@ -387,13 +400,18 @@ impl PipelineBuilder {
         block: Arc<Block>,
         is_randomness_enabled: bool,
         validator: Arc<[AccountAddress]>,
+        epoch_context: Arc<EpochContext>,
         onchain_execution_config: BlockExecutorConfigFromOnchain,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> TaskResult<ExecuteResult> {
         parent_block_execute_phase.await?;
         let user_txns = prepare_phase.await?;
         let maybe_rand = randomness_rx.await.map_err(|_| anyhow!("randomness tx cancelled"))?;
 
         let _tracker = Tracker::new("execute", &block);
+        fail_point!("consensus::pipeline::execute", |_| {
+            Err(anyhow!("Injected error in execute").into())
+        });
         // TODO(gravity_lightman_dkg)
         // let metadata_txn = if is_randomness_enabled {
         //     block.new_metadata_with_randomness(&validator, maybe_rand)
@@ -450,6 +468,7 @@ impl PipelineBuilder {
             .author()
             .and_then(|author| validator.iter().position(|&v| v == author).map(|i| i as u64));
 
+        let execution_meta = block_execution_meta_util(&epoch_context, &block, proposer_index);
         let meta_data = ExternalBlockMeta {
             block_id: BlockId(*block.id()),
             block_number: block.block_number().unwrap_or_else(|| panic!("No block number")),
@@ -471,7 +490,7 @@ impl PipelineBuilder {
             ),
         };
         // TODO: add extra_data (validator transactions)
-        get_block_buffer_manager()
+        block_buffer_manager
             .set_ordered_blocks(
                 BlockId::from_bytes(block.parent_id().as_slice()),
                 ExternalBlock {
@@ -481,6 +500,7 @@ impl PipelineBuilder {
                     enable_randomness: is_randomness_enabled,
                 },
                 block.round(),
+                execution_meta,
             )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to push ordered blocks: {}", e))?;
@@ -496,6 +516,7 @@ impl PipelineBuilder {
         parent_block_ledger_update_phase: TaskFuture<LedgerUpdateResult>,
         executor: Arc<dyn BlockExecutorTrait>,
         block: Arc<Block>,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> TaskResult<LedgerUpdateResult> {
         let (_, prev_epoch_end_timestamp) = parent_block_ledger_update_phase.await?;
         execute_phase.await?;
@@ -504,7 +525,7 @@ impl PipelineBuilder {
         let block_number = block.block_number();
         let timestamp = block.timestamp_usecs();
         let epoch = block.epoch();
-        let hash = get_block_buffer_manager()
+        let hash = block_buffer_manager
             .get_executed_res(
                 BlockId::from_bytes(block_id.as_slice()),
                 block_number.unwrap(),
@@ -606,6 +627,7 @@ impl PipelineBuilder {
         mut commit_proof_rx: tokio::sync::broadcast::Receiver<LedgerInfoWithSignatures>,
         signer: Arc<ValidatorSigner>,
         block: Arc<Block>,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> TaskResult<CommitVoteResult> {
         let (compute_result, epoch_end_timestamp) = ledger_update_phase.await?;
         // either order_vote_rx or order_proof_rx can trigger the next phase
@@ -639,7 +661,7 @@ impl PipelineBuilder {
         // We first check if the block is a suffix block by querying the buffer manager.
         // This is necessary because both the epoch change block and its suffix blocks
         // have a StateComputeResult where `has_reconfiguration()` is true.
-        let epoch_block_info = if let Some(epoch_info) = get_block_buffer_manager()
+        let epoch_block_info = if let Some(epoch_info) = block_buffer_manager
             .get_epoch_change_block_info(block.block_number().unwrap_or(0), block.epoch())
             .await
         {
@@ -705,6 +727,9 @@ impl PipelineBuilder {
 
         let _tracker = Tracker::new("pre_commit", &block);
         tokio::task::spawn_blocking(move || {
+            fail_point!("consensus::pipeline::pre_commit", |_| {
+                Err(anyhow!("Injected error in pre_commit"))
+            });
             executor.pre_commit_block(block.id()).map_err(anyhow::Error::from)
         })
         .await
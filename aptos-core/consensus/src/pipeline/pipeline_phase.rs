@@ -12,6 +12,8 @@ use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
+use tokio_util::sync::CancellationToken;
+
 #[async_trait]
 pub trait StatelessPipeline: Send + Sync {
     type Request;
@@ -19,6 +21,10 @@ pub trait StatelessPipeline: Send + Sync {
 
     const NAME: &'static str;
 
+    /// Whether a reset may abandon a request this phase is processing. Phases whose work must
+    /// not be left half done, like persisting a commit, run every started request to completion.
+    const CANCELLABLE: bool = true;
+
     async fn process(&self, req: Self::Request) -> Self::Response;
 }
 
@@ -46,16 +52,39 @@ impl Drop for TaskGuard {
 pub struct CountedRequest<Request> {
     req: Request,
     guard: TaskGuard,
+    // Cancelled by the buffer manager on reset, so in-flight work belonging to the
+    // previous pipeline generation stops instead of being drained.
+    cancel_token: CancellationToken,
 }
 
 impl<Request> CountedRequest<Request> {
     pub fn new(req: Request, counter: Arc<AtomicU64>) -> Self {
+        Self::new_cancellable(req, counter, CancellationToken::new())
+    }
+
+    pub fn new_cancellable(
+        req: Request,
+        counter: Arc<AtomicU64>,
+        cancel_token: CancellationToken,
+    ) -> Self {
         let guard = TaskGuard::new(counter);
-        Self { req, guard }
+        Self { req, guard, cancel_token }
     }
 
     pub fn spawn<OtherRequest>(&self, other_req: OtherRequest) -> CountedRequest<OtherRequest> {
-        CountedRequest { req: other_req, guard: self.guard.spawn() }
+        CountedRequest {
+            req: other_req,
+            guard: self.guard.spawn(),
+            cancel_token: self.cancel_token.clone(),
+        }
+    }
+
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel_token
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
     }
 }
 
@@ -79,15 +108,29 @@ impl<T: StatelessPipeline> PipelinePhase<T> {
     pub async fn start(mut self) {
         // main loop
         while let Some(counted_req) = self.rx.next().await {
-            let CountedRequest { req, guard: _guard } = counted_req;
-            if self.reset_flag.load(Ordering::SeqCst) {
+            let CountedRequest { req, guard: _guard, cancel_token } = counted_req;
+            if self.reset_flag.load(Ordering::SeqCst) ||
+                (T::CANCELLABLE && cancel_token.is_cancelled())
+            {
                 continue;
             }
             let response = {
                 let _timer = BUFFER_MANAGER_PHASE_PROCESS_SECONDS
                     .with_label_values(&[T::NAME])
                     .start_timer();
-                self.processor.process(req).await
+                if T::CANCELLABLE {
+                    tokio::select! {
+                        biased;
+                        _ = cancel_token.cancelled() => None,
+                        response = self.processor.process(req) => Some(response),
+                    }
+                } else {
+                    Some(self.processor.process(req).await)
+                }
+            };
+            let Some(response) = response else {
+                debug!("{} request cancelled by reset", T::NAME);
+                continue;
             };
             if let Some(tx) = &mut self.maybe_tx {
                 if tx.send(response).await.is_err() {
@@ -29,6 +29,7 @@ use aptos_consensus_types::{
     vote_proposal::VoteProposal,
 };
 use aptos_safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use block_buffer_manager::BlockBufferManager;
 use futures::{channel::oneshot, FutureExt, SinkExt, StreamExt};
 use gaptos::{
     aptos_bounded_executor::BoundedExecutor,
@@ -163,6 +164,7 @@ pub async fn prepare_buffer_manager(
         ConsensusObserverConfig::default(),
         None,
         100,
+        BlockBufferManager::new(Default::default()),
     );
 
     (
@@ -230,8 +232,12 @@ async fn loopback_commit_vote(
         Event::RpcRequest(author, msg, protocol, callback) => {
             if let ConsensusMsg::CommitMessage(msg) = msg {
                 msg.verify(verifier).unwrap();
-                let request =
-                    IncomingCommitRequest { req: *msg, protocol, response_sender: callback };
+                let request = IncomingCommitRequest {
+                    req: *msg,
+                    sender: Some(author),
+                    protocol,
+                    response_sender: callback,
+                };
                 // verify the message and send the message into self loop
                 msg_tx.push(author, request).ok();
             }
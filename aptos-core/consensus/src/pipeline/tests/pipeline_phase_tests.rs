@@ -0,0 +1,97 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    pipeline::{
+        buffer_manager::create_channel,
+        persisting_phase::PersistingPhase,
+        pipeline_phase::{CountedRequest, PipelinePhase, StatelessPipeline},
+    },
+    test_utils::{consensus_runtime, timed_block_on},
+};
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+// Commits in two steps like `ExecutionProxy::commit`: the ledger info first, then the
+// notifications that must follow it.
+struct CommitPhaseForTest<const CANCELLABLE: bool> {
+    ledger_committed: Arc<Notify>,
+    resume: Arc<Notify>,
+    notified: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl<const CANCELLABLE: bool> StatelessPipeline for CommitPhaseForTest<CANCELLABLE> {
+    type Request = u64;
+    type Response = u64;
+
+    const CANCELLABLE: bool = CANCELLABLE;
+    const NAME: &'static str = "commit_for_test";
+
+    async fn process(&self, round: u64) -> u64 {
+        self.ledger_committed.notify_one();
+        self.resume.notified().await;
+        self.notified.store(true, Ordering::SeqCst);
+        round
+    }
+}
+
+// Resets the pipeline while the ledger info of a commit is persisted but its notifications are
+// not, and returns whether they ran and the response of the phase.
+fn reset_during_commit<const CANCELLABLE: bool>() -> (bool, Option<u64>) {
+    let runtime = consensus_runtime();
+    let ledger_committed = Arc::new(Notify::new());
+    let resume = Arc::new(Notify::new());
+    let notified = Arc::new(AtomicBool::new(false));
+    let phase = CommitPhaseForTest::<CANCELLABLE> {
+        ledger_committed: ledger_committed.clone(),
+        resume: resume.clone(),
+        notified: notified.clone(),
+    };
+
+    let (mut in_tx, in_rx) = create_channel::<CountedRequest<u64>>();
+    let (out_tx, mut out_rx) = create_channel::<u64>();
+    let ongoing_tasks = Arc::new(AtomicU64::new(0));
+    runtime.spawn(
+        PipelinePhase::new(in_rx, Some(out_tx), Box::new(phase), Arc::new(AtomicBool::new(false)))
+            .start(),
+    );
+
+    let response = timed_block_on(&runtime, async move {
+        let cancel_token = CancellationToken::new();
+        in_tx
+            .send(CountedRequest::new_cancellable(7, ongoing_tasks.clone(), cancel_token.clone()))
+            .await
+            .unwrap();
+        ledger_committed.notified().await;
+        cancel_token.cancel();
+        resume.notify_one();
+        // The buffer manager waits for in-flight requests to drop before a reset completes
+        while ongoing_tasks.load(Ordering::SeqCst) > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        drop(in_tx);
+        out_rx.next().await
+    });
+    (notified.load(Ordering::SeqCst), response)
+}
+
+#[test]
+fn reset_does_not_interrupt_a_commit() {
+    assert!(!PersistingPhase::CANCELLABLE);
+    assert_eq!(reset_during_commit::<false>(), (true, Some(7)));
+}
+
+#[test]
+fn reset_abandons_cancellable_work() {
+    assert_eq!(reset_during_commit::<true>(), (false, None));
+}
@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Protocol features that validators switch on together at an epoch boundary.
+//!
+//! A rolling upgrade runs old and new binaries side by side, so a behavior only the new binary
+//! understands must not be used as soon as it is deployed. Each [`ProtocolFeature`] is tied to
+//! a bit of the onchain `Features` bitvector, which is read from the reconfiguration payload
+//! at the start of every epoch. A flag set during epoch N therefore takes effect in epoch N+1
+//! on every validator at once. Until then a node neither proposes nor votes for payloads that
+//! need the feature.
+
+use anyhow::bail;
+use aptos_consensus_types::common::Payload;
+use gaptos::aptos_types::on_chain_config::Features;
+use std::{collections::BTreeSet, fmt};
+
+#[cfg(test)]
+#[path = "protocol_features_test.rs"]
+mod protocol_features_test;
+
+/// Gravity flags start at this bit of the onchain `Features` bitvector, well above the flags
+/// inherited from Aptos, so the two never collide.
+const GRAVITY_FEATURE_BASE: u64 = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolFeature {
+    /// Proposals with `Payload::OptQuorumStore`, whose batches can be proposed before their
+    /// proof of store is formed.
+    OptQuorumStorePayload,
+    /// Proposals carrying system transactions of a `SystemTxnProvider`.
+    SystemTxns,
+    /// Commit votes signing the hash of the payload the execution layer attached to the block,
+    /// see `StateComputeResult::root_hash`.
+    SignedCommitPayloads,
+    /// Leader reputation that also penalizes proposers that are slow or fail rounds in the
+    /// committed blocks, see `LatencyAwareHeuristic`.
+    LatencyAwareReputation,
+}
+
+impl ProtocolFeature {
+    pub const ALL: &'static [ProtocolFeature] = &[
+        ProtocolFeature::OptQuorumStorePayload,
+        ProtocolFeature::SystemTxns,
+        ProtocolFeature::SignedCommitPayloads,
+        ProtocolFeature::LatencyAwareReputation,
+    ];
+
+    /// Bit of the onchain `Features` bitvector that activates this feature. Bits are never
+    /// reused once assigned.
+    pub fn flag(self) -> u64 {
+        GRAVITY_FEATURE_BASE +
+            match self {
+                ProtocolFeature::OptQuorumStorePayload => 0,
+                ProtocolFeature::SystemTxns => 1,
+                ProtocolFeature::SignedCommitPayloads => 2,
+                ProtocolFeature::LatencyAwareReputation => 3,
+            }
+    }
+
+    /// The feature a payload needs, if any.
+    pub fn required_by(payload: &Payload) -> Option<ProtocolFeature> {
+        match payload {
+            Payload::OptQuorumStore(_) => Some(ProtocolFeature::OptQuorumStorePayload),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProtocolFeature::OptQuorumStorePayload => "opt_quorum_store_payload",
+            ProtocolFeature::SystemTxns => "system_txns",
+            ProtocolFeature::SignedCommitPayloads => "signed_commit_payloads",
+            ProtocolFeature::LatencyAwareReputation => "latency_aware_reputation",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The protocol features active in one epoch. The default has none active.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolFeatures {
+    active: BTreeSet<ProtocolFeature>,
+}
+
+impl ProtocolFeatures {
+    /// Features activated by the onchain `Features` config of the new epoch. A chain that does
+    /// not publish the config has none active.
+    pub fn from_onchain(features: Option<&Features>) -> Self {
+        let active = match features {
+            Some(features) => ProtocolFeature::ALL
+                .iter()
+                .copied()
+                .filter(|feature| is_flag_set(&features.features, feature.flag()))
+                .collect(),
+            None => BTreeSet::new(),
+        };
+        Self { active }
+    }
+
+    pub fn is_active(&self, feature: ProtocolFeature) -> bool {
+        self.active.contains(&feature)
+    }
+
+    pub fn active(&self) -> impl Iterator<Item = ProtocolFeature> + '_ {
+        self.active.iter().copied()
+    }
+
+    /// Fails if `payload` needs a feature that is not active in this epoch.
+    pub fn check_payload(&self, payload: &Payload) -> anyhow::Result<()> {
+        match ProtocolFeature::required_by(payload) {
+            Some(feature) if !self.is_active(feature) => {
+                bail!("payload needs protocol feature {feature}, which is not active yet")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Same bit order as `Features::is_enabled`: bit `flag % 8` of byte `flag / 8`.
+fn is_flag_set(bitvec: &[u8], flag: u64) -> bool {
+    let byte = (flag / 8) as usize;
+    let mask = 1 << (flag % 8);
+    bitvec.get(byte).map_or(false, |b| b & mask != 0)
+}
@@ -0,0 +1,43 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{ProtocolFeature, ProtocolFeatures};
+use aptos_consensus_types::common::Payload;
+use gaptos::aptos_types::on_chain_config::Features;
+
+fn features_with(flags: &[u64]) -> Features {
+    let len = flags.iter().map(|flag| flag / 8 + 1).max().unwrap_or(0) as usize;
+    let mut features = vec![0u8; len];
+    for flag in flags {
+        features[(flag / 8) as usize] |= 1 << (flag % 8);
+    }
+    Features { features }
+}
+
+#[test]
+fn test_features_follow_onchain_flags() {
+    let feature = ProtocolFeature::OptQuorumStorePayload;
+
+    assert!(!ProtocolFeatures::from_onchain(None).is_active(feature));
+    // Aptos flags do not activate Gravity features
+    let aptos_only = features_with(&[0, 1, 7, 63]);
+    assert!(!ProtocolFeatures::from_onchain(Some(&aptos_only)).is_active(feature));
+    // A bitvector shorter than the flag leaves it inactive
+    let short = Features { features: vec![0xff; 4] };
+    assert!(!ProtocolFeatures::from_onchain(Some(&short)).is_active(feature));
+
+    let enabled = ProtocolFeatures::from_onchain(Some(&features_with(&[3, feature.flag()])));
+    assert!(enabled.is_active(feature));
+    assert_eq!(enabled.active().collect::<Vec<_>>(), vec![feature]);
+}
+
+#[test]
+fn test_ungated_payloads_are_always_allowed() {
+    let none_active = ProtocolFeatures::default();
+    for payload in
+        [Payload::empty(false, false), Payload::empty(true, false), Payload::empty(true, true)]
+    {
+        assert_eq!(ProtocolFeature::required_by(&payload), None);
+        assert!(none_active.check_payload(&payload).is_ok());
+    }
+}
@@ -14,7 +14,10 @@ use aptos_consensus_types::{
     common::{TransactionInProgress, TransactionSummary},
     proof_of_store::{BatchId, BatchInfo},
 };
-use aptos_mempool::QuorumStoreRequest;
+use aptos_mempool::{
+    core_mempool::{batch_formation_policy, BatchFormationPolicy},
+    QuorumStoreRequest,
+};
 use futures_channel::mpsc::Sender;
 use gaptos::{
     aptos_config::config::QuorumStoreConfig,
@@ -25,7 +28,8 @@ use gaptos::{
 };
 use rayon::prelude::*;
 use std::{
-    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
+    cmp::Reverse,
+    collections::{btree_map::Entry, BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque},
     hash::Hash,
     sync::Arc,
     time::{Duration, Instant},
@@ -57,6 +61,47 @@ impl BatchInProgress {
     }
 }
 
+/// Reorders `txns` by gas unit price, highest first, keeping each sender's transactions in
+/// sequence number order. A transaction cannot be included before its sender's earlier ones, so
+/// it is ranked by the lowest price among them and itself; equal ranks keep the pull order.
+/// Returns the ranks, which are non-increasing along the reordered `txns`.
+pub(crate) fn order_by_fee_priority(txns: &mut Vec<SignedTransaction>) -> Vec<u64> {
+    let mut by_sender: HashMap<PeerId, Vec<(usize, SignedTransaction)>> = HashMap::new();
+    for (index, txn) in txns.drain(..).enumerate() {
+        by_sender.entry(txn.sender()).or_default().push((index, txn));
+    }
+
+    let mut chains: HashMap<PeerId, VecDeque<(u64, usize, SignedTransaction)>> = HashMap::new();
+    let mut heads = BinaryHeap::new();
+    for (sender, mut sender_txns) in by_sender {
+        sender_txns.sort_by_key(|(_, txn)| txn.sequence_number());
+        let mut rank = u64::MAX;
+        let chain: VecDeque<_> = sender_txns
+            .into_iter()
+            .map(|(index, txn)| {
+                rank = rank.min(txn.gas_unit_price());
+                (rank, index, txn)
+            })
+            .collect();
+        if let Some((rank, index, _)) = chain.front() {
+            heads.push((*rank, Reverse(*index), sender));
+        }
+        chains.insert(sender, chain);
+    }
+
+    let mut ranks = Vec::with_capacity(chains.values().map(VecDeque::len).sum());
+    while let Some((rank, _, sender)) = heads.pop() {
+        let chain = chains.get_mut(&sender).expect("every head has a chain");
+        let (_, _, txn) = chain.pop_front().expect("heads are only pushed for non-empty chains");
+        txns.push(txn);
+        ranks.push(rank);
+        if let Some((rank, index, _)) = chain.front() {
+            heads.push((*rank, Reverse(*index), sender));
+        }
+    }
+    ranks
+}
+
 pub struct BatchGenerator {
     epoch: u64,
     my_peer_id: PeerId,
@@ -238,9 +283,18 @@ impl BatchGenerator {
         pulled_txns: &mut Vec<SignedTransaction>,
         expiry_time: u64,
     ) -> Vec<Batch> {
-        // Sort by gas, in descending order. This is a stable sort on existing mempool ordering,
-        // so will not reorder accounts or their sequence numbers as long as they have the same gas.
-        pulled_txns.sort_by_key(|txn| u64::MAX - txn.gas_unit_price());
+        // Price each position is bucketed by, non-increasing along pulled_txns.
+        let bucket_prices: Vec<u64> = match batch_formation_policy() {
+            BatchFormationPolicy::GasBuckets => {
+                // Sort by gas, in descending order. This is a stable sort on existing mempool
+                // ordering, so will not reorder accounts or their sequence numbers as long as
+                // they have the same gas.
+                pulled_txns.sort_by_key(|txn| u64::MAX - txn.gas_unit_price());
+                pulled_txns.iter().map(|txn| txn.gas_unit_price()).collect()
+            }
+            BatchFormationPolicy::FeePriority => order_by_fee_priority(pulled_txns),
+        };
+        let num_pulled_txns = pulled_txns.len();
 
         let reverse_buckets_excluding_zero: Vec<_> =
             self.config.batch_buckets.iter().skip(1).rev().cloned().collect();
@@ -252,14 +306,9 @@ impl BatchGenerator {
                 return batches;
             }
 
-            // Search for key in descending gas order
-            let num_txns_in_bucket = match pulled_txns
-                .binary_search_by_key(&(u64::MAX - (*bucket_start - 1), PeerId::ZERO), |txn| {
-                    (u64::MAX - txn.gas_unit_price(), txn.sender())
-                }) {
-                Ok(index) => index,
-                Err(index) => index,
-            };
+            let consumed = num_pulled_txns - pulled_txns.len();
+            let num_txns_in_bucket =
+                bucket_prices[consumed..].partition_point(|price| *price >= *bucket_start);
             if num_txns_in_bucket == 0 {
                 continue;
             }
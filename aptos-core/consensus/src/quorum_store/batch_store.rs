@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    counters::QUORUM_STORE_REJECTED_BATCH_COUNT,
     network::QuorumStoreSender,
     quorum_store::{
         batch_requester::BatchRequester,
@@ -35,6 +36,13 @@ use std::{
 };
 use tokio::sync::oneshot;
 
+/// Limit of a [`QuotaManager`] a batch would exceed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum QuotaExceeded {
+    Batch,
+    Storage,
+}
+
 // Pub(crate) for testing only.
 pub(crate) struct QuotaManager {
     memory_balance: usize,
@@ -59,28 +67,49 @@ impl QuotaManager {
         }
     }
 
-    pub(crate) fn update_quota(&mut self, num_bytes: usize) -> anyhow::Result<StorageMode> {
+    /// Caps over the batches of all authors together, on top of the per-author quotas of the
+    /// quorum store config. Each cap is unlimited unless configured.
+    /// Can be configured via QUORUM_STORE_GLOBAL_MEMORY_QUOTA, QUORUM_STORE_GLOBAL_DB_QUOTA
+    /// (bytes) and QUORUM_STORE_GLOBAL_BATCH_QUOTA environment variables
+    pub(crate) fn global_from_env() -> Self {
+        let quota = |name: &str| global_quota(name, std::env::var(name).ok().as_deref());
+        let db_quota = quota("QUORUM_STORE_GLOBAL_DB_QUOTA");
+        let memory_quota = quota("QUORUM_STORE_GLOBAL_MEMORY_QUOTA").min(db_quota);
+        Self::new(db_quota, memory_quota, quota("QUORUM_STORE_GLOBAL_BATCH_QUOTA"))
+    }
+
+    /// Where a batch of `num_bytes` would be stored, without taking the quota.
+    fn storage_mode(&self, num_bytes: usize) -> Result<StorageMode, QuotaExceeded> {
         if self.batch_balance == 0 {
-            counters::EXCEEDED_BATCH_QUOTA_COUNT.inc();
-            bail!("Batch quota exceeded ");
+            return Err(QuotaExceeded::Batch);
         }
-
-        if self.db_balance >= num_bytes {
-            self.batch_balance -= 1;
-            self.db_balance -= num_bytes;
-
-            if self.memory_balance >= num_bytes {
-                self.memory_balance -= num_bytes;
-                Ok(StorageMode::MemoryAndPersisted)
-            } else {
-                Ok(StorageMode::PersistedOnly)
-            }
+        if self.db_balance < num_bytes {
+            return Err(QuotaExceeded::Storage);
+        }
+        if self.memory_balance >= num_bytes {
+            Ok(StorageMode::MemoryAndPersisted)
         } else {
-            counters::EXCEEDED_STORAGE_QUOTA_COUNT.inc();
-            bail!("Storage quota exceeded ");
+            Ok(StorageMode::PersistedOnly)
+        }
+    }
+
+    fn take_quota(&mut self, num_bytes: usize, storage_mode: StorageMode) {
+        self.batch_balance -= 1;
+        self.db_balance -= num_bytes;
+        if matches!(storage_mode, StorageMode::MemoryAndPersisted) {
+            self.memory_balance -= num_bytes;
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn update_quota(&mut self, num_bytes: usize) -> anyhow::Result<StorageMode> {
+        let storage_mode = self
+            .storage_mode(num_bytes)
+            .map_err(|exceeded| anyhow::anyhow!("{:?} quota exceeded", exceeded))?;
+        self.take_quota(num_bytes, storage_mode);
+        Ok(storage_mode)
+    }
+
     fn assert_quota(balance: usize, to_free: usize, quota: usize, kind: &str) {
         assert!(
             balance + to_free <= quota,
@@ -106,6 +135,16 @@ impl QuotaManager {
     }
 }
 
+/// The global quota `name` is set to, unlimited if unset. A value that is not a number is
+/// rejected with an error, and the quota stays unlimited.
+pub(crate) fn global_quota(name: &str, value: Option<&str>) -> usize {
+    let Some(value) = value else { return usize::MAX };
+    value.trim().parse().unwrap_or_else(|e| {
+        error!("Invalid {} {:?}: {}, the quota is not applied", name, value, e);
+        usize::MAX
+    })
+}
+
 /// Provides in memory representation of stored batches (strong cache), and allows
 /// efficient concurrent readers.
 pub struct BatchStore {
@@ -113,6 +152,7 @@ pub struct BatchStore {
     last_certified_time: AtomicU64,
     db_cache: DashMap<HashValue, PersistedValue>,
     peer_quota: DashMap<PeerId, QuotaManager>,
+    global_quota: Mutex<QuotaManager>,
     expirations: Mutex<TimeExpirations<HashValue>>,
     db: Arc<dyn QuorumStoreStorage>,
     memory_quota: usize,
@@ -138,6 +178,7 @@ impl BatchStore {
             last_certified_time: AtomicU64::new(last_certified_time),
             db_cache: DashMap::new(),
             peer_quota: DashMap::new(),
+            global_quota: Mutex::new(QuotaManager::new(usize::MAX, usize::MAX, usize::MAX)),
             expirations: Mutex::new(TimeExpirations::new()),
             db,
             memory_quota,
@@ -186,14 +227,64 @@ impl BatchStore {
         batch_store
     }
 
+    /// Caps the batches of all authors together, see [`QuotaManager::global_from_env`].
+    pub(crate) fn with_global_quota(mut self, global_quota: QuotaManager) -> Self {
+        self.global_quota = Mutex::new(global_quota);
+        self
+    }
+
     fn epoch(&self) -> u64 {
         *self.epoch.get().expect("Epoch should always be set")
     }
 
+    #[allow(clippy::unwrap_used)]
     fn free_quota(&self, value: PersistedValue) {
-        let mut quota_manager =
-            self.peer_quota.get_mut(&value.author()).expect("No QuotaManager for batch author");
-        quota_manager.free_quota(value.num_bytes() as usize, value.payload_storage_mode());
+        let (num_bytes, storage_mode) = (value.num_bytes() as usize, value.payload_storage_mode());
+        self.peer_quota
+            .get_mut(&value.author())
+            .expect("No QuotaManager for batch author")
+            .free_quota(num_bytes, storage_mode);
+        self.global_quota.lock().unwrap().free_quota(num_bytes, storage_mode);
+    }
+
+    // Takes the quota of a batch from both its author and the global caps. A batch is only
+    // kept in memory if both have memory left.
+    // Note: locks peer_quota, then global_quota.
+    #[allow(clippy::unwrap_used)]
+    fn update_quota(&self, author: PeerId, num_bytes: usize) -> anyhow::Result<StorageMode> {
+        let mut peer_quota = self.peer_quota.entry(author).or_insert_with(|| {
+            QuotaManager::new(self.db_quota, self.memory_quota, self.batch_quota)
+        });
+        let mut global_quota = self.global_quota.lock().unwrap();
+        let modes = (peer_quota.storage_mode(num_bytes), global_quota.storage_mode(num_bytes));
+        let reason = match modes {
+            (Ok(peer_mode), Ok(global_mode)) => {
+                let storage_mode = if peer_mode == StorageMode::MemoryAndPersisted &&
+                    global_mode == StorageMode::MemoryAndPersisted
+                {
+                    StorageMode::MemoryAndPersisted
+                } else {
+                    StorageMode::PersistedOnly
+                };
+                peer_quota.take_quota(num_bytes, storage_mode);
+                global_quota.take_quota(num_bytes, storage_mode);
+                return Ok(storage_mode);
+            }
+            (Err(QuotaExceeded::Batch), _) => {
+                counters::EXCEEDED_BATCH_QUOTA_COUNT.inc();
+                "author_batch_quota"
+            }
+            (Err(QuotaExceeded::Storage), _) => {
+                counters::EXCEEDED_STORAGE_QUOTA_COUNT.inc();
+                "author_storage_quota"
+            }
+            (_, Err(QuotaExceeded::Batch)) => "global_batch_quota",
+            (_, Err(QuotaExceeded::Storage)) => "global_storage_quota",
+        };
+        QUORUM_STORE_REJECTED_BATCH_COUNT
+            .with_label_values(&[author.short_str().as_str(), reason])
+            .inc();
+        bail!("Batch of {} exceeds the {}", author, reason.replace('_', " "));
     }
 
     // Inserts a PersistedValue into the in-memory db_cache. If an entry with a higher
@@ -203,7 +294,8 @@ impl BatchStore {
     // only the metadata is stored in the db-cache).
     // Note: holds db_cache entry lock (due to DashMap), while accessing peer_quota
     // DashMap. Hence, peer_quota reference should never be held while accessing the
-    // db_cache to avoid the deadlock (if needed, order is db_cache, then peer_quota).
+    // db_cache to avoid the deadlock (if needed, order is db_cache, then peer_quota,
+    // then global_quota).
     pub(crate) fn insert_to_cache(&self, value: &PersistedValue) -> anyhow::Result<bool> {
         let digest = *value.digest();
         let author = value.author();
@@ -219,13 +311,8 @@ impl BatchStore {
                     return Ok(false);
                 }
             };
-            let value_to_be_stored = if self
-                .peer_quota
-                .entry(author)
-                .or_insert(QuotaManager::new(self.db_quota, self.memory_quota, self.batch_quota))
-                .update_quota(value.num_bytes() as usize)? ==
-                StorageMode::PersistedOnly
-            {
+            let storage_mode = self.update_quota(author, value.num_bytes() as usize)?;
+            let value_to_be_stored = if storage_mode == StorageMode::PersistedOnly {
                 PersistedValue::new(value.batch_info().clone(), None)
             } else {
                 value.clone()
@@ -265,6 +352,9 @@ impl BatchStore {
             return self.insert_to_cache(value);
         }
         counters::NUM_BATCH_EXPIRED_WHEN_SAVE.inc();
+        QUORUM_STORE_REJECTED_BATCH_COUNT
+            .with_label_values(&[value.author().short_str().as_str(), "expired"])
+            .inc();
         bail!(
             "Incorrect expiration {} in epoch {}, last committed timestamp {}",
             value.expiration(),
@@ -23,10 +23,11 @@ use gaptos::{
     aptos_types::{transaction::SignedTransaction, PeerId},
 };
 use rand::{seq::SliceRandom, thread_rng};
+use runtime_config::{Knob, RuntimeConfigRegistry};
 use std::{
     cmp::min,
     collections::{BTreeMap, HashMap, HashSet},
-    sync::Arc,
+    sync::{Arc, OnceLock},
     time::Duration,
 };
 
@@ -126,6 +127,32 @@ impl BatchQueue {
     }
 }
 
+/// Quorum store back pressure overrides, changeable at runtime. Zero, the default, follows
+/// `back_pressure_total_txn_limit` and `back_pressure_total_proof_limit` of the quorum store
+/// config. Configured via QUORUM_STORE_BACK_PRESSURE_TXN_LIMIT and
+/// QUORUM_STORE_BACK_PRESSURE_PROOF_LIMIT.
+fn back_pressure_knobs() -> &'static (Arc<Knob>, Arc<Knob>) {
+    static KNOBS: OnceLock<(Arc<Knob>, Arc<Knob>)> = OnceLock::new();
+    KNOBS.get_or_init(|| {
+        let registry = RuntimeConfigRegistry::global();
+        let txn_limit = registry.register(
+            "quorum_store_back_pressure_txn_limit",
+            "Txns in unproposed proofs above which batch creation slows down, 0 follows the config",
+            "QUORUM_STORE_BACK_PRESSURE_TXN_LIMIT",
+            0,
+            10_000_000,
+        );
+        let proof_limit = registry.register(
+            "quorum_store_back_pressure_proof_limit",
+            "Unproposed proofs above which batch creation slows down, 0 follows the config",
+            "QUORUM_STORE_BACK_PRESSURE_PROOF_LIMIT",
+            0,
+            1_000_000,
+        );
+        (txn_limit, proof_limit)
+    })
+}
+
 pub struct ProofManager {
     proofs_for_consensus: ProofQueue,
     batch_queue: BatchQueue,
@@ -293,26 +320,39 @@ impl ProofManager {
         }
     }
 
+    /// The txn and proof limits, the runtime overrides if set.
+    fn back_pressure_limits(&self) -> (u64, u64) {
+        let (txn_knob, proof_knob) = back_pressure_knobs();
+        let limit = |knob: &Knob, config: u64| match knob.get() {
+            0 => config,
+            limit => limit,
+        };
+        (
+            limit(txn_knob, self.back_pressure_total_txn_limit),
+            limit(proof_knob, self.back_pressure_total_proof_limit),
+        )
+    }
+
     /// return true when quorum store is back pressured
     pub(crate) fn qs_back_pressure(&self) -> BackPressure {
-        if self.remaining_total_txn_num > self.back_pressure_total_txn_limit ||
-            self.remaining_total_proof_num > self.back_pressure_total_proof_limit
+        let (txn_limit, proof_limit) = self.back_pressure_limits();
+        if self.remaining_total_txn_num > txn_limit || self.remaining_total_proof_num > proof_limit
         {
             sample!(
                 SampleRate::Duration(Duration::from_millis(200)),
                 info!(
                     "Quorum store is back pressured with {} txns, limit: {}, proofs: {}, limit: {}",
                     self.remaining_total_txn_num,
-                    self.back_pressure_total_txn_limit,
+                    txn_limit,
                     self.remaining_total_proof_num,
-                    self.back_pressure_total_proof_limit
+                    proof_limit
                 );
             );
         }
 
         BackPressure {
-            txn_count: self.remaining_total_txn_num > self.back_pressure_total_txn_limit,
-            proof_count: self.remaining_total_proof_num > self.back_pressure_total_proof_limit,
+            txn_count: self.remaining_total_txn_num > txn_limit,
+            proof_count: self.remaining_total_proof_num > proof_limit,
         }
     }
 
@@ -13,7 +13,7 @@ use crate::{
         batch_coordinator::{BatchCoordinator, BatchCoordinatorCommand},
         batch_generator::{BackPressure, BatchGenerator, BatchGeneratorCommand},
         batch_requester::BatchRequester,
-        batch_store::{BatchReader, BatchReaderImpl, BatchStore},
+        batch_store::{BatchReader, BatchReaderImpl, BatchStore, QuotaManager},
         direct_mempool_quorum_store::DirectMempoolQuorumStore,
         network_listener::NetworkListener,
         proof_coordinator::{ProofCoordinator, ProofCoordinatorCommand},
@@ -260,15 +260,18 @@ impl InnerBuilder {
             self.network_sender.clone(),
             self.verifier.clone(),
         );
-        let batch_store = Arc::new(BatchStore::new(
-            self.epoch,
-            last_committed_timestamp,
-            self.quorum_store_storage.clone(),
-            self.config.memory_quota,
-            self.config.db_quota,
-            self.config.batch_quota,
-            signer,
-        ));
+        let batch_store = Arc::new(
+            BatchStore::new(
+                self.epoch,
+                last_committed_timestamp,
+                self.quorum_store_storage.clone(),
+                self.config.memory_quota,
+                self.config.db_quota,
+                self.config.batch_quota,
+                signer,
+            )
+            .with_global_quota(QuotaManager::global_from_env()),
+        );
         self.batch_store = Some(batch_store.clone());
         let batch_reader = Arc::new(BatchReaderImpl::new(batch_store.clone(), batch_requester));
         self.batch_reader = Some(batch_reader.clone());
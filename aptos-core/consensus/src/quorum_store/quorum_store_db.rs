@@ -11,6 +11,7 @@ use crate::{
 use anyhow::Result;
 use aptos_consensus_types::proof_of_store::BatchId;
 use gaptos::{
+    aptos_crypto::HashValue,
     aptos_logger::prelude::*,
     aptos_schemadb::{batch::SchemaBatch, Options, DB},
 };
@@ -58,6 +59,28 @@ impl QuorumStoreDB {
 
         Self { db }
     }
+
+    /// The stored batches with `digest`, of every epoch unless `epoch` is given. Without an
+    /// epoch the whole batch column family is scanned, so this is for debugging only.
+    pub fn get_batches_by_digest(
+        &self,
+        digest: &HashValue,
+        epoch: Option<u64>,
+    ) -> Result<Vec<PersistedValue>> {
+        if let Some(epoch) = epoch {
+            return Ok(self.get_batch(&BatchKey::new(epoch, *digest))?.into_iter().collect());
+        }
+        let mut iter = self.db.iter::<BatchSchema>()?;
+        iter.seek_to_first();
+        let mut batches = vec![];
+        for res in iter {
+            let (key, value) = res?;
+            if key.digest == *digest {
+                batches.push(value);
+            }
+        }
+        Ok(batches)
+    }
 }
 
 impl QuorumStoreStorage for QuorumStoreDB {
@@ -3,8 +3,11 @@
 
 use crate::{
     quorum_store::{
-        batch_coordinator::BatchCoordinatorCommand, batch_generator::BatchGenerator,
-        batch_store::BatchWriter, quorum_store_db::MockQuorumStoreDB, types::PersistedValue,
+        batch_coordinator::BatchCoordinatorCommand,
+        batch_generator::{order_by_fee_priority, BatchGenerator},
+        batch_store::BatchWriter,
+        quorum_store_db::MockQuorumStoreDB,
+        types::PersistedValue,
     },
     test_utils::{
         create_signed_transaction, create_vec_signed_transactions,
@@ -21,7 +24,15 @@ use futures::{
     StreamExt,
 };
 use gaptos::{
-    aptos_config::config::QuorumStoreConfig, aptos_types::transaction::SignedTransaction,
+    aptos_config::config::QuorumStoreConfig,
+    aptos_crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519Signature},
+        PrivateKey, Uniform,
+    },
+    aptos_types::{
+        chain_id::ChainId,
+        transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+    },
     move_core_types::account_address::AccountAddress,
 };
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
@@ -740,3 +751,40 @@ async fn test_remote_batches_in_progress() {
 
     timeout(Duration::from_millis(10_000), join_handle).await.unwrap().unwrap();
 }
+
+fn create_sender_txn(
+    sender: AccountAddress,
+    sequence_number: u64,
+    gas_unit_price: u64,
+) -> SignedTransaction {
+    let raw_transaction = RawTransaction::new(
+        sender,
+        sequence_number,
+        TransactionPayload::GTxnBytes(vec![0; 2]),
+        0,
+        gas_unit_price,
+        0,
+        ChainId::new(10),
+    );
+    let public_key = Ed25519PrivateKey::generate_for_testing().public_key();
+    SignedTransaction::new(raw_transaction, public_key, Ed25519Signature::dummy_signature())
+}
+
+#[test]
+fn test_fee_priority_ordering_respects_sender_nonces() {
+    let (a, b, c) = (AccountAddress::random(), AccountAddress::random(), AccountAddress::random());
+    // a's second transaction pays the most but must wait behind a's cheap first one.
+    let mut txns = vec![
+        create_sender_txn(a, 0, 10),
+        create_sender_txn(b, 0, 50),
+        create_sender_txn(a, 1, 100),
+        create_sender_txn(c, 0, 50),
+        create_sender_txn(b, 1, 20),
+    ];
+
+    let ranks = order_by_fee_priority(&mut txns);
+
+    let order: Vec<_> = txns.iter().map(|txn| (txn.sender(), txn.sequence_number())).collect();
+    assert_eq!(order, vec![(b, 0), (c, 0), (b, 1), (a, 0), (a, 1)]);
+    assert_eq!(ranks, vec![50, 50, 20, 10, 10]);
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::quorum_store::{
-    batch_store::{BatchStore, BatchWriter, QuotaManager},
+    batch_store::{global_quota, BatchStore, BatchWriter, QuotaManager},
     quorum_store_db::QuorumStoreDB,
     types::{BatchKey, PersistedValue, StorageMode},
 };
@@ -78,6 +78,31 @@ fn test_insert_expire() {
     assert_eq!(batch_store.clear_expired_payload(30), vec![BatchKey::new(10, digest)]);
 }
 
+#[test]
+fn test_global_quota() {
+    let tmp_dir = TempPath::new();
+    let db = Arc::new(QuorumStoreDB::new(&tmp_dir));
+    let (signers, _validator_verifier) = random_validator_verifier(4, None, false);
+    // Each author may store 100 bytes, all of them together only 50.
+    let batch_store = BatchStore::new(10, 10, db, 100, 100, 10, signers[0].clone())
+        .with_global_quota(QuotaManager::new(50, 40, 10));
+    let request = |author: AccountAddress| {
+        let batch_id = BatchId::new_for_test(1);
+        let batch_info = BatchInfo::new(author, batch_id, 10, 20, HashValue::random(), 10, 20, 0);
+        PersistedValue::new(batch_info, None)
+    };
+    let (author_1, author_2) = (AccountAddress::random(), AccountAddress::random());
+
+    assert_ok_eq!(batch_store.insert_to_cache(&request(author_1)), true);
+    assert_ok_eq!(batch_store.insert_to_cache(&request(author_2)), true);
+    // Within the quota of the author, but over the global one.
+    assert_err!(batch_store.insert_to_cache(&request(author_2)));
+
+    // Expiration frees the global quota as well.
+    assert_eq!(batch_store.clear_expired_payload(20).len(), 2);
+    assert_ok_eq!(batch_store.insert_to_cache(&request(author_2)), true);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_extend_expiration_vs_save() {
     let num_experiments = 2000;
@@ -173,6 +198,15 @@ async fn test_extend_expiration_vs_save() {
     }
 }
 
+#[test]
+fn test_global_quota_from_env_value() {
+    assert_eq!(global_quota("QUOTA", None), usize::MAX);
+    assert_eq!(global_quota("QUOTA", Some("1024")), 1024);
+    assert_eq!(global_quota("QUOTA", Some(" 1024 ")), 1024);
+    assert_eq!(global_quota("QUOTA", Some("1GB")), usize::MAX);
+    assert_eq!(global_quota("QUOTA", Some("")), usize::MAX);
+}
+
 #[test]
 fn test_quota_manager() {
     let mut qm = QuotaManager::new(20, 10, 7);
@@ -10,7 +10,9 @@ use crate::{
 };
 use aptos_consensus_types::proof_of_store::BatchId;
 use claims::assert_ok;
-use gaptos::{aptos_temppath::TempPath, aptos_types::account_address::AccountAddress};
+use gaptos::{
+    aptos_crypto::HashValue, aptos_temppath::TempPath, aptos_types::account_address::AccountAddress,
+};
 
 #[test]
 fn test_db_for_data() {
@@ -78,3 +80,32 @@ fn test_db_for_batch_id() {
         BatchId::new_for_test(2)
     );
 }
+
+#[test]
+fn test_get_batches_by_digest() {
+    let tmp_dir = TempPath::new();
+    let db = QuorumStoreDB::new(&tmp_dir);
+
+    let source = AccountAddress::random();
+    let signed_txns = create_vec_signed_transactions(10);
+    let epoch_1: PersistedValue =
+        Batch::new(BatchId::new_for_test(1), signed_txns.clone(), 1, 20, source, 0).into();
+    let epoch_2: PersistedValue =
+        Batch::new(BatchId::new_for_test(1), signed_txns, 2, 20, source, 0).into();
+    let other: PersistedValue =
+        Batch::new(BatchId::new_for_test(2), create_vec_signed_transactions(5), 1, 20, source, 0)
+            .into();
+    assert_eq!(epoch_1.digest(), epoch_2.digest());
+    for batch in [&epoch_1, &epoch_2, &other] {
+        assert_ok!(db.save_batch(batch.clone()));
+    }
+
+    let digest = *epoch_1.digest();
+    assert_eq!(
+        db.get_batches_by_digest(&digest, None).unwrap(),
+        vec![epoch_1.clone(), epoch_2.clone()]
+    );
+    assert_eq!(db.get_batches_by_digest(&digest, Some(2)).unwrap(), vec![epoch_2]);
+    assert!(db.get_batches_by_digest(&digest, Some(3)).unwrap().is_empty());
+    assert!(db.get_batches_by_digest(&HashValue::random(), None).unwrap().is_empty());
+}
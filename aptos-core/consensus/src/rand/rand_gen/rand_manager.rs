@@ -3,6 +3,7 @@
 
 use crate::{
     consensusdb::ConsensusDB,
+    counters::{RAND_LAST_DECIDED_TIMESTAMP, RAND_ROUNDS_COMPLETED},
     logging::{LogEvent, LogSchema},
     network::{IncomingRandGenRequest, NetworkSender, TConsensusMsg},
     pipeline::buffer_manager::{OrderedBlocks, ResetAck, ResetRequest, ResetSignal},
@@ -34,7 +35,7 @@ use gaptos::{
     aptos_channels::aptos_channel,
     aptos_config::config::ReliableBroadcastConfig,
     aptos_consensus::counters::RAND_QUEUE_SIZE,
-    aptos_infallible::Mutex,
+    aptos_infallible::{duration_since_epoch, Mutex},
     aptos_logger::{error, info, spawn_named, trace, warn},
     aptos_network::{protocols::network::RpcError, ProtocolId},
     aptos_reliable_broadcast::{DropGuard, ReliableBroadcast},
@@ -202,6 +203,8 @@ impl<S: TShare, D: TAugmentedData> RandManager<S, D> {
             randomness = randomness.randomness(),
             "Processing decisioned randomness."
         );
+        RAND_ROUNDS_COMPLETED.inc();
+        RAND_LAST_DECIDED_TIMESTAMP.set(duration_since_epoch().as_secs() as i64);
         if let Some(block) = self.block_queue.item_mut(randomness.round()) {
             block.set_randomness(randomness.round(), randomness);
         }
@@ -3,6 +3,9 @@
 
 use crate::{
     block_storage::tracing::{observe_block, BlockStage},
+    counters::{
+        RAND_LAST_RECONSTRUCTION_FAILURE_EPOCH, RAND_RECONSTRUCTION_FAILURES, RAND_SHARES_RECEIVED,
+    },
     rand::rand_gen::{
         rand_manager::Sender,
         types::{PathType, RandConfig, RandShare, TShare, FUTURE_ROUNDS_TO_ACCEPT},
@@ -68,6 +71,8 @@ impl<S: TShare> ShareAggregator<S> {
                     // for this and all subsequent rounds until the epoch changes and RandStore
                     // is recreated. The aggregation is deterministic so retrying won't help.
                     // The epoch change mechanism provides eventual recovery.
+                    RAND_RECONSTRUCTION_FAILURES.inc();
+                    RAND_LAST_RECONSTRUCTION_FAILURE_EPOCH.set(rand_metadata.metadata.epoch as i64);
                     error!(
                         epoch = rand_metadata.metadata.epoch,
                         round = rand_metadata.metadata.round,
@@ -277,6 +282,8 @@ impl<S: TShare> RandStore<S> {
         };
 
         rand_item.add_share(share, rand_config)?;
+        let path_label = if path == PathType::Fast { "fast" } else { "slow" };
+        RAND_SHARES_RECEIVED.with_label_values(&[path_label]).inc();
         rand_item.try_aggregate(rand_config, self.decision_tx.clone());
         Ok(rand_item.has_decision())
     }
@@ -22,6 +22,7 @@ use crate::{
     pending_order_votes::{OrderVoteReceptionResult, PendingOrderVotes},
     pending_votes::VoteReceptionResult,
     persistent_liveness_storage::PersistentLivenessStorage,
+    protocol_features::{ProtocolFeature, ProtocolFeatures},
     quorum_store::types::BatchMsg,
     rand::rand_gen::types::{FastShare, RandConfig, Share, TShare},
     util::is_vtxn_expected,
@@ -46,6 +47,7 @@ use aptos_consensus_types::{
 #[cfg(test)]
 use aptos_safety_rules::ConsensusState;
 use aptos_safety_rules::TSafetyRules;
+use block_buffer_manager::{SystemTxnContext, SystemTxnProvider};
 use counters::{
     ORDER_CERT_CREATED_WITHOUT_BLOCK_IN_BLOCK_STORE, ORDER_VOTE_ADDED, ORDER_VOTE_BROADCASTED,
     ORDER_VOTE_OTHER_ERRORS, PROPOSAL_VOTE_ADDED, PROPOSAL_VOTE_BROADCASTED, PROPOSED_VTXN_BYTES,
@@ -55,11 +57,12 @@ use fail::fail_point;
 use futures::{channel::oneshot, stream::FuturesUnordered, Future, FutureExt, StreamExt};
 use futures_channel::mpsc::UnboundedReceiver;
 use gaptos::{
+    api_types::u256_define::BlockId,
     aptos_channels::aptos_channel,
     aptos_config::{config::ConsensusConfig, network_id::NetworkId},
     aptos_consensus::counters,
     aptos_crypto::HashValue,
-    aptos_infallible::{checked, Mutex},
+    aptos_infallible::{checked, duration_since_epoch, Mutex},
     aptos_logger::prelude::*,
     aptos_network::application::interface::NetworkClientInterface,
     aptos_types::{
@@ -76,12 +79,75 @@ use gaptos::{
 };
 use lru::LruCache;
 use serde::Serialize;
-use std::{mem::Discriminant, pin::Pin, sync::Arc, time::Duration};
+use std::{
+    mem::Discriminant,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 use tokio::{
     sync::oneshot as TokioOneshot,
     time::{sleep, Instant},
 };
 
+/// How far a proposal timestamp may be ahead of the local clock, used when
+/// CONSENSUS_MAX_TIMESTAMP_DRIFT_MS is unset.
+pub const DEFAULT_MAX_TIMESTAMP_DRIFT: Duration = Duration::from_secs(5);
+
+/// CONSENSUS_MAX_TIMESTAMP_DRIFT_MS, read once. An unparsable value is logged and the default
+/// is used.
+pub fn max_timestamp_drift() -> Duration {
+    static DRIFT: OnceLock<Duration> = OnceLock::new();
+    *DRIFT.get_or_init(|| match std::env::var("CONSENSUS_MAX_TIMESTAMP_DRIFT_MS") {
+        Ok(value) => value.parse().map(Duration::from_millis).unwrap_or_else(|e| {
+            error!(
+                "Invalid CONSENSUS_MAX_TIMESTAMP_DRIFT_MS {:?}: {}, using {:?}",
+                value, e, DEFAULT_MAX_TIMESTAMP_DRIFT
+            );
+            DEFAULT_MAX_TIMESTAMP_DRIFT
+        }),
+        Err(_) => DEFAULT_MAX_TIMESTAMP_DRIFT,
+    })
+}
+
+/// Fails if a proposal carries system txns this voter must not vote for: the feature is not
+/// active, this node has no provider, or its provider rejects them.
+pub(crate) fn check_system_txns(
+    proposal: &Block,
+    protocol_features: &ProtocolFeatures,
+    provider: Option<&Arc<dyn SystemTxnProvider>>,
+) -> anyhow::Result<()> {
+    let Some(system_txns) = proposal.system_txns() else { return Ok(()) };
+    let author = proposal.author().expect("Proposal should be verified having an author");
+    if !protocol_features.is_active(ProtocolFeature::SystemTxns) {
+        crate::counters::GATED_PAYLOAD_REJECTED_COUNT
+            .with_label_values(&[&ProtocolFeature::SystemTxns.to_string(), "peer"])
+            .inc();
+        bail!(
+            "Proposal {} from {}: system txns need protocol feature {}, which is not active yet",
+            proposal.id(),
+            author,
+            ProtocolFeature::SystemTxns
+        );
+    }
+    let provider = provider.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Proposal {} from {} carries system txns, but no provider is set",
+            proposal.id(),
+            author
+        )
+    })?;
+    let ctx = SystemTxnContext {
+        epoch: proposal.epoch(),
+        round: proposal.round(),
+        timestamp_usecs: proposal.timestamp_usecs(),
+        parent_id: BlockId(*proposal.parent_id()),
+    };
+    provider.verify(&ctx, system_txns).with_context(|| {
+        format!("Proposal {} from {} has invalid system txns", proposal.id(), author)
+    })
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub enum UnverifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
@@ -254,6 +320,11 @@ pub struct RoundManager {
     /// validator (sync-path BlockRetrieval). Pre-computed by `EpochManager` from the
     /// node's static `NodeType`; `RoundManager` stays `NodeType`-agnostic.
     non_validator_network_id: NetworkId,
+    protocol_features: ProtocolFeatures,
+    max_timestamp_drift: Duration,
+    system_txn_provider: Option<Arc<dyn SystemTxnProvider>>,
+    // The highest round a peer proved to be in with a verified sync info
+    highest_known_round: Round,
 }
 
 pub(crate) struct ValidatorComponents {
@@ -316,9 +387,38 @@ impl RoundManager {
             wait_change_epoch_flag: false,
             validator_components,
             non_validator_network_id,
+            protocol_features: ProtocolFeatures::default(),
+            max_timestamp_drift: DEFAULT_MAX_TIMESTAMP_DRIFT,
+            highest_known_round: 0,
+            system_txn_provider: None,
         }
     }
 
+    /// Sets the protocol features of the epoch. Proposals whose payload needs a feature that is
+    /// not active are rejected. None are active by default.
+    pub fn with_protocol_features(mut self, protocol_features: ProtocolFeatures) -> Self {
+        self.protocol_features = protocol_features;
+        self
+    }
+
+    /// Sets how far a proposal timestamp may be ahead of the local clock. The execution layer
+    /// exposes block timestamps as the EVM `TIMESTAMP`, so a proposer with a fast clock must not
+    /// push them ahead of real time.
+    pub fn with_max_timestamp_drift(mut self, max_timestamp_drift: Duration) -> Self {
+        self.max_timestamp_drift = max_timestamp_drift;
+        self
+    }
+
+    /// Checks the system transactions of proposals against `system_txn_provider`. Without a
+    /// provider, proposals carrying system transactions are rejected.
+    pub fn with_system_txn_provider(
+        mut self,
+        system_txn_provider: Arc<dyn SystemTxnProvider>,
+    ) -> Self {
+        self.system_txn_provider = Some(system_txn_provider);
+        self
+    }
+
     fn is_validator(&self) -> bool {
         self.validator_components.is_some()
     }
@@ -454,7 +554,7 @@ impl RoundManager {
     ) -> anyhow::Result<()> {
         let epoch = epoch_state.epoch;
         Self::log_collected_vote_stats(epoch_state.clone(), &new_round_event);
-        let proposal_msg = Self::generate_proposal(
+        let Some(proposal_msg) = Self::generate_proposal(
             epoch,
             new_round_event,
             sync_info,
@@ -463,7 +563,11 @@ impl RoundManager {
             safety_rules,
             proposer_election,
         )
-        .await?;
+        .await?
+        else {
+            // Empty blocks are skipped and no payload arrived within the round
+            return Ok(());
+        };
         #[cfg(feature = "failpoints")]
         {
             if Self::check_whether_to_inject_reconfiguration_error() {
@@ -550,7 +654,7 @@ impl RoundManager {
     async fn generate_proposal_for_test(
         &self,
         new_round_event: NewRoundEvent,
-    ) -> anyhow::Result<ProposalMsg> {
+    ) -> anyhow::Result<Option<ProposalMsg>> {
         Self::generate_proposal(
             self.epoch_state().epoch,
             new_round_event,
@@ -571,7 +675,7 @@ impl RoundManager {
         proposal_generator: Arc<ProposalGenerator>,
         safety_rules: Arc<Mutex<MetricsSafetyRules>>,
         proposer_election: Arc<dyn ProposerElection + Send + Sync>,
-    ) -> anyhow::Result<ProposalMsg> {
+    ) -> anyhow::Result<Option<ProposalMsg>> {
         // Proposal generator will ensure that at most one proposal is generated per round
         let callback_sync_info = sync_info.clone();
         let callback = async move {
@@ -579,9 +683,12 @@ impl RoundManager {
         }
         .boxed();
 
-        let proposal = proposal_generator
+        let Some(proposal) = proposal_generator
             .generate_proposal(new_round_event.round, proposer_election, callback)
-            .await?;
+            .await?
+        else {
+            return Ok(None);
+        };
         let signature = safety_rules.lock().sign_proposal(&proposal)?;
         let signed_proposal =
             Block::new_proposal_from_block_data_and_signature(proposal, signature);
@@ -593,7 +700,7 @@ impl RoundManager {
             Self::new_log_with_round_epoch(LogEvent::Propose, new_round_event.round, epoch),
             "{}", signed_proposal
         );
-        Ok(ProposalMsg::new(signed_proposal, sync_info))
+        Ok(Some(ProposalMsg::new(signed_proposal, sync_info)))
     }
 
     /// Process the proposal message:
@@ -681,6 +788,8 @@ impl RoundManager {
                 VerifyError::from(e)
             })?;
             SYNC_INFO_RECEIVED_WITH_NEWER_CERT.inc();
+            self.highest_known_round =
+                self.highest_known_round.max(sync_info.highest_round().saturating_add(1));
             let result =
                 self.block_store.add_certs(sync_info, self.create_block_retriever(author)).await;
             self.process_certificates().await?;
@@ -704,6 +813,7 @@ impl RoundManager {
         author: Author,
     ) -> anyhow::Result<bool> {
         if message_round < self.round_state.current_round() {
+            self.update_rounds_behind();
             info!(
                 "Stale proposal {}, current round {}",
                 message_round,
@@ -711,7 +821,9 @@ impl RoundManager {
             );
             return Ok(false);
         }
-        self.sync_up(sync_info, author).await?;
+        let synced = self.sync_up(sync_info, author).await;
+        self.update_rounds_behind();
+        synced?;
         ensure!(
             message_round == self.round_state.current_round(),
             "After sync, round {} doesn't match local {}. Local Sync Info: {}. Remote Sync Info: {}",
@@ -723,6 +835,15 @@ impl RoundManager {
         Ok(true)
     }
 
+    /// Sets the rounds the local round is behind the highest round peers proved to be in, and
+    /// when, so the readiness probe can tell a stale reading from a node in sync.
+    fn update_rounds_behind(&self) {
+        let rounds_behind =
+            self.highest_known_round.saturating_sub(self.round_state.current_round());
+        counters::ROUNDS_BEHIND.set(rounds_behind as i64);
+        counters::ROUNDS_BEHIND_UPDATED_AT.set(duration_since_epoch().as_secs() as i64);
+    }
+
     /// Process the SyncInfo sent by peers to catch up to latest state.
     pub async fn process_sync_info_msg(
         &mut self,
@@ -842,13 +963,29 @@ impl RoundManager {
 
         let author = proposal.author().expect("Proposal should be verified having an author");
 
+        // A ProposalExt without validator txns only carries system txns, checked below
         if !self.vtxn_config.enabled() &&
-            matches!(proposal.block_data().block_type(), BlockType::ProposalExt(_))
+            matches!(proposal.block_data().block_type(), BlockType::ProposalExt(_)) &&
+            (proposal.system_txns().is_none() ||
+                proposal.validator_txns().map_or(false, |vtxns| !vtxns.is_empty()))
         {
             counters::UNEXPECTED_PROPOSAL_EXT_COUNT.inc();
             bail!("ProposalExt unexpected while the feature is disabled.");
         }
 
+        check_system_txns(&proposal, &self.protocol_features, self.system_txn_provider.as_ref())?;
+
+        if let Some(payload) = proposal.payload() {
+            if let Err(e) = self.protocol_features.check_payload(payload) {
+                if let Some(feature) = ProtocolFeature::required_by(payload) {
+                    crate::counters::GATED_PAYLOAD_REJECTED_COUNT
+                        .with_label_values(&[&feature.to_string(), "peer"])
+                        .inc();
+                }
+                bail!("Proposal {} from {}: {:#}", proposal.id(), author, e);
+            }
+        }
+
         if let Some(vtxns) = proposal.validator_txns() {
             for vtxn in vtxns {
                 ensure!(
@@ -938,6 +1075,20 @@ impl RoundManager {
 
         let block_time_since_epoch = Duration::from_micros(proposal.timestamp_usecs());
 
+        // Timestamps strictly increase from the parent, see `Block::verify_well_formed`
+        let latest_allowed = duration_since_epoch() + self.max_timestamp_drift;
+        if block_time_since_epoch > latest_allowed {
+            crate::counters::PROPOSAL_TIMESTAMP_DRIFT_REJECTED_COUNT.inc();
+            bail!(
+                "[RoundManager] Proposal {} from {} has timestamp {:?}, more than {:?} ahead of \
+                the local clock",
+                proposal.id(),
+                author,
+                block_time_since_epoch,
+                self.max_timestamp_drift,
+            );
+        }
+
         ensure!(
             block_time_since_epoch < self.round_state.current_round_deadline(),
             "[RoundManager] Waiting until proposal block timestamp usecs {:?} \
@@ -7,7 +7,7 @@
 #![allow(clippy::unwrap_used)]
 
 use crate::{
-    block_storage::{pending_blocks::PendingBlocks, BlockStore},
+    block_storage::{pending_blocks::PendingBlocks, recovery_api::BlockBufferRecovery, BlockStore},
     liveness::{
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -28,6 +28,7 @@ use crate::{
 };
 use aptos_consensus_types::proposal_msg::ProposalMsg;
 use aptos_safety_rules::{test_utils, SafetyRules, TSafetyRules};
+use block_buffer_manager::BlockBufferManager;
 use futures::{channel::mpsc, executor::block_on};
 use futures_channel::mpsc::unbounded;
 use gaptos::{
@@ -58,6 +59,7 @@ use gaptos::{
 };
 use maplit::hashmap;
 use once_cell::sync::Lazy;
+use proposer_reth_map::EpochContext;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::runtime::Runtime;
 
@@ -75,7 +77,7 @@ pub fn generate_corpus_proposal() -> Vec<u8> {
             })
             .await;
         // serialize and return proposal
-        serde_json::to_vec(&proposal.unwrap()).unwrap()
+        serde_json::to_vec(&proposal.unwrap().unwrap()).unwrap()
     })
 }
 
@@ -90,6 +92,7 @@ fn build_empty_store(
 ) -> Arc<BlockStore> {
     let (_commit_cb_sender, _commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
 
+    let block_buffer_manager = BlockBufferManager::new(Default::default());
     Arc::new(BlockStore::new(
         storage,
         initial_data,
@@ -104,6 +107,9 @@ fn build_empty_store(
         false,
         false,
         HashMap::new(), // validator_indices: empty for fuzzing
+        Arc::new(EpochContext::default()),
+        block_buffer_manager.clone(),
+        Arc::new(BlockBufferRecovery::new(block_buffer_manager)),
     ))
 }
 
@@ -5,7 +5,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::{pending_blocks::PendingBlocks, BlockReader, BlockStore},
+    block_storage::{
+        pending_blocks::PendingBlocks, recovery_api::BlockBufferRecovery, BlockReader, BlockStore,
+    },
     liveness::{
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -22,11 +24,12 @@ use crate::{
     payload_manager::DirectMempoolPayloadManager,
     persistent_liveness_storage::RecoveryData,
     pipeline::buffer_manager::OrderedBlocks,
-    round_manager::{RoundManager, ValidatorComponents},
+    protocol_features::{ProtocolFeature, ProtocolFeatures},
+    round_manager::{check_system_txns, RoundManager, ValidatorComponents},
     test_utils::{
         consensus_runtime, create_vec_signed_transactions,
         mock_execution_client::MockExecutionClient, timed_block_on, MockPayloadManager,
-        MockStorage, TreeInserter,
+        MockStorage, MockSystemTxnProvider, TreeInserter,
     },
     util::time_service::{ClockTimeService, TimeService},
 };
@@ -35,6 +38,7 @@ use aptos_consensus_types::{
         block_test_utils::{certificate_for_genesis, gen_test_certificate},
         Block,
     },
+    block_data::BlockData,
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
     common::{Author, Payload, Round},
     pipeline::commit_decision::CommitDecision,
@@ -44,6 +48,7 @@ use aptos_consensus_types::{
     vote_msg::VoteMsg,
 };
 use aptos_safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
+use block_buffer_manager::{BlockBufferManager, SystemTxnProvider};
 use futures::{
     channel::{mpsc, oneshot},
     executor::block_on,
@@ -77,7 +82,7 @@ use gaptos::{
         jwks::QuorumCertifiedUpdate,
         ledger_info::LedgerInfo,
         on_chain_config::{
-            ConsensusAlgorithmConfig, ConsensusConfigV1, OnChainConsensusConfig,
+            ConsensusAlgorithmConfig, ConsensusConfigV1, Features, OnChainConsensusConfig,
             OnChainJWKConsensusConfig, OnChainRandomnessConfig, ValidatorTxnConfig,
         },
         transaction::SignedTransaction,
@@ -88,6 +93,7 @@ use gaptos::{
     },
 };
 use maplit::hashmap;
+use proposer_reth_map::EpochContext;
 use std::{
     collections::HashMap,
     iter::FromIterator,
@@ -287,6 +293,7 @@ impl NodeSetup {
         ));
         let time_service = Arc::new(ClockTimeService::new(executor));
 
+        let block_buffer_manager = BlockBufferManager::new(Default::default());
         let block_store = Arc::new(BlockStore::new(
             storage.clone(),
             initial_data,
@@ -301,6 +308,9 @@ impl NodeSetup {
             false,
             false,
             HashMap::new(), // validator_indices: empty for tests
+            Arc::new(EpochContext::default()),
+            block_buffer_manager.clone(),
+            Arc::new(BlockBufferRecovery::new(block_buffer_manager)),
         ));
 
         let proposer_election = Self::create_proposer_election(proposers.clone());
@@ -1137,6 +1147,51 @@ async fn reject_invalid_failed_authors() {
     });
 }
 
+#[tokio::test]
+async fn no_vote_on_proposal_ahead_of_the_local_clock() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut node = NodeSetup::create_nodes(
+        &mut playground,
+        runtime.handle().clone(),
+        1,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .pop()
+    .unwrap();
+    let genesis_qc = certificate_for_genesis();
+    let max_drift = Duration::from_secs(5);
+    node.round_manager.max_timestamp_drift = max_drift;
+
+    let ahead = gaptos::aptos_infallible::duration_since_epoch() + max_drift * 2;
+    let block = Block::new_proposal(
+        Payload::empty(false, true),
+        1,
+        ahead.as_micros() as u64,
+        genesis_qc.clone(),
+        &node.signer,
+        vec![],
+    )
+    .unwrap();
+    let proposal = ProposalMsg::new(
+        block,
+        SyncInfo::new(genesis_qc.clone(), genesis_qc.into_wrapped_ledger_info(), None),
+    );
+
+    let rejected = crate::counters::PROPOSAL_TIMESTAMP_DRIFT_REJECTED_COUNT.get();
+    timed_block_on(&runtime, async {
+        let error = node.round_manager.process_proposal_msg(proposal).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("ahead of the local clock"), "{:#}", error);
+    });
+    assert!(crate::counters::PROPOSAL_TIMESTAMP_DRIFT_REJECTED_COUNT.get() > rejected);
+    assert!(node.round_manager.round_state.vote_sent().is_none());
+}
+
 #[tokio::test]
 async fn response_on_block_retrieval() {
     let runtime = consensus_runtime();
@@ -2213,3 +2268,56 @@ async fn no_vote_on_proposal_ext_when_receiving_limit_exceeded() {
         assert!(node.round_manager.process_proposal(valid_block).await.is_ok());
     });
 }
+
+fn system_txns_active() -> ProtocolFeatures {
+    let flag = ProtocolFeature::SystemTxns.flag();
+    let mut features = vec![0u8; (flag / 8 + 1) as usize];
+    features[(flag / 8) as usize] |= 1 << (flag % 8);
+    ProtocolFeatures::from_onchain(Some(&Features { features }))
+}
+
+fn proposal_with_system_txns(system_txns: Vec<Vec<u8>>, signer: &ValidatorSigner) -> Block {
+    let block_data = BlockData::new_proposal_with_system_txns(
+        vec![],
+        system_txns,
+        Payload::empty(false, true),
+        signer.author(),
+        vec![],
+        1,
+        1,
+        certificate_for_genesis(),
+    );
+    Block::new_proposal_from_block_data(block_data, signer).unwrap()
+}
+
+#[test]
+fn voter_checks_system_txns_against_its_provider() {
+    let signer = ValidatorSigner::random(None);
+    let active = system_txns_active();
+    let provider: Arc<dyn SystemTxnProvider> = Arc::new(MockSystemTxnProvider);
+    let valid = proposal_with_system_txns(vec![1u64.to_be_bytes().to_vec()], &signer);
+    let invalid = proposal_with_system_txns(vec![2u64.to_be_bytes().to_vec()], &signer);
+
+    check_system_txns(&valid, &active, Some(&provider)).unwrap();
+    assert!(check_system_txns(&invalid, &active, Some(&provider)).is_err());
+    assert!(check_system_txns(&valid, &active, None).is_err());
+    assert!(check_system_txns(&valid, &ProtocolFeatures::default(), Some(&provider)).is_err());
+
+    // A provider registered after the epoch started is used for the next proposal
+    let manager = BlockBufferManager::new(Default::default());
+    let registered = manager.registered_system_txn_provider();
+    assert!(check_system_txns(&valid, &active, Some(&registered)).is_err());
+    manager.set_system_txn_provider(provider);
+    check_system_txns(&valid, &active, Some(&registered)).unwrap();
+
+    let without_system_txns = Block::new_proposal(
+        Payload::empty(false, true),
+        1,
+        1,
+        certificate_for_genesis(),
+        &signer,
+        vec![],
+    )
+    .unwrap();
+    check_system_txns(&without_system_txns, &ProtocolFeatures::default(), None).unwrap();
+}
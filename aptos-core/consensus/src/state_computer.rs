@@ -38,7 +38,7 @@ use gaptos::{
     aptos_logger::prelude::*,
 };
 
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::{block_buffer_manager::BlockExecutionMeta, BlockBufferManager};
 use counters::APTOS_EXECUTION_TXNS;
 use fail::fail_point;
 use futures::{future::BoxFuture, SinkExt, StreamExt};
@@ -58,6 +58,7 @@ use gaptos::{
         vm_status::{DiscardedVMStatus, StatusCode},
     },
 };
+use proposer_reth_map::EpochContext;
 use std::{boxed::Box, iter::once, sync::Arc, time::Duration};
 
 /// JWK type name constants for consistent usage across SDK ↔ reth boundary
@@ -88,6 +89,7 @@ impl LogicalTime {
 #[derive(Clone)]
 struct MutableState {
     validators: Arc<[AccountAddress]>,
+    epoch_context: Arc<EpochContext>,
     payload_manager: Arc<dyn TPayloadManager>,
     transaction_shuffler: Arc<dyn TransactionShuffler>,
     block_executor_onchain_config: BlockExecutorConfigFromOnchain,
@@ -105,6 +107,7 @@ pub struct ExecutionProxy {
     write_mutex: AsyncMutex<LogicalTime>,
     transaction_filter: Arc<TransactionFilter>,
     execution_pipeline: ExecutionPipeline,
+    block_buffer_manager: Arc<BlockBufferManager>,
     state: RwLock<Option<MutableState>>,
 }
 
@@ -129,6 +132,7 @@ impl ExecutionProxy {
         handle: &tokio::runtime::Handle,
         txn_filter: TransactionFilter,
         enable_pre_commit: bool,
+        block_buffer_manager: Arc<BlockBufferManager>,
     ) -> Self {
         let (tx, mut rx) = gaptos::aptos_channels::new::<NotificationType>(
             10,
@@ -157,10 +161,15 @@ impl ExecutionProxy {
             write_mutex: AsyncMutex::new(LogicalTime::new(0, 0)),
             transaction_filter: Arc::new(txn_filter),
             execution_pipeline,
+            block_buffer_manager,
             state: RwLock::new(None),
         }
     }
 
+    pub(crate) fn block_buffer_manager(&self) -> &Arc<BlockBufferManager> {
+        &self.block_buffer_manager
+    }
+
     fn transactions_to_commit(
         &self,
         executed_block: &PipelinedBlock,
@@ -200,6 +209,7 @@ impl ExecutionProxy {
     pub fn pipeline_builder(&self, commit_signer: Arc<ValidatorSigner>) -> PipelineBuilder {
         let MutableState {
             validators,
+            epoch_context,
             payload_manager,
             transaction_shuffler,
             block_executor_onchain_config,
@@ -217,12 +227,14 @@ impl ExecutionProxy {
             block_preparer,
             self.executor.clone(),
             validators,
+            epoch_context,
             block_executor_onchain_config,
             is_randomness_enabled,
             commit_signer,
             self.state_sync_notifier.clone(),
             payload_manager,
             self.txn_notifier.clone(),
+            self.block_buffer_manager.clone(),
         )
     }
 
@@ -347,6 +359,24 @@ pub fn process_validator_transactions_util(
     extra_data
 }
 
+/// Public utility function to resolve the execution metadata of a block from its proposer's
+/// index in the current validator set
+pub fn block_execution_meta_util(
+    epoch_context: &EpochContext,
+    block: &Block,
+    proposer_index: Option<u64>,
+) -> BlockExecutionMeta {
+    let proposer_reth_address = proposer_index
+        .and_then(|index| epoch_context.reth_address_by_index(index))
+        .and_then(|address| <[u8; 20]>::try_from(address).ok());
+    // The block buffer manager stamps the soft deadline once the block is ordered
+    BlockExecutionMeta {
+        proposer_reth_address,
+        soft_deadline: None,
+        system_txns: block.system_txns().cloned().unwrap_or_default(),
+    }
+}
+
 /// Public utility function to process a single validator transaction
 pub fn process_single_validator_transaction_util(
     txn: &ValidatorTransaction,
@@ -452,7 +482,7 @@ impl StateComputer for ExecutionProxy {
         let validator_txns = block.validator_txns();
         let extra_data = process_validator_transactions_util(validator_txns.map(|v| &**v), block);
 
-        let MutableState { validators, .. } =
+        let MutableState { validators, epoch_context, .. } =
             self.state.read().as_ref().cloned().expect("must be set within an epoch");
 
         // Look up the proposer's index in the validator set (None for NIL blocks)
@@ -460,6 +490,7 @@ impl StateComputer for ExecutionProxy {
             .author()
             .and_then(|author| validators.iter().position(|&v| v == author).map(|i| i as u64));
 
+        let execution_meta = block_execution_meta_util(&epoch_context, block, proposer_index);
         let meta_data = ExternalBlockMeta {
             block_id: BlockId(*block.id()),
             block_number: block.block_number().unwrap_or_else(|| panic!("No block number")),
@@ -500,12 +531,14 @@ impl StateComputer for ExecutionProxy {
         let block_id_hashvalue = block.id();
         let block_round = block.round();
         let enable_randomness = self.state.read().as_ref().unwrap().is_randomness_enabled;
+        let cancel_token = lifetime_guard.cancel_token().clone();
+        let block_buffer_manager = self.block_buffer_manager.clone();
         Box::pin(async move {
             let block_id = meta_data.block_id;
             let block_timestamp = meta_data.usecs;
             txn_metrics::TxnLifeTime::get_txn_life_time()
                 .record_executing(block_id_hashvalue.clone());
-            get_block_buffer_manager()
+            block_buffer_manager
                 .set_ordered_blocks(
                     BlockId::from_bytes(parent_block_id.as_slice()),
                     ExternalBlock {
@@ -515,13 +548,27 @@ impl StateComputer for ExecutionProxy {
                         enable_randomness,
                     },
                     block_round,
+                    execution_meta,
                 )
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to push ordered blocks: {}", e))?;
             let u_ts = meta_data.usecs;
-            let compute_result = get_block_buffer_manager()
-                .get_executed_res(block_id, meta_data.block_number, meta_data.epoch)
-                .await?;
+            // Waiting for the execution result can take arbitrarily long, bail out as soon as
+            // the pipeline is reset so the block doesn't outlive its epoch.
+            let compute_result = tokio::select! {
+                biased;
+                _ = cancel_token.cancelled() => {
+                    return Err(ExecutorError::internal_err(format!(
+                        "execution of block {} cancelled by pipeline reset",
+                        block_id_hashvalue
+                    )));
+                }
+                res = block_buffer_manager.get_executed_res(
+                    block_id,
+                    meta_data.block_number,
+                    meta_data.epoch,
+                ) => res?,
+            };
             txn_metrics::TxnLifeTime::get_txn_life_time().record_executed(block_id_hashvalue);
             update_counters_for_compute_res(&compute_result.execution_output);
 
@@ -719,6 +766,7 @@ impl StateComputer for ExecutionProxy {
         block_executor_onchain_config: BlockExecutorConfigFromOnchain,
         transaction_deduper: Arc<dyn TransactionDeduper>,
         randomness_enabled: bool,
+        epoch_context: Arc<EpochContext>,
     ) {
         *self.state.write() = Some(MutableState {
             validators: epoch_state
@@ -726,6 +774,7 @@ impl StateComputer for ExecutionProxy {
                 .get_ordered_account_addresses_iter()
                 .collect::<Vec<_>>()
                 .into(),
+            epoch_context,
             payload_manager,
             transaction_shuffler,
             block_executor_onchain_config,
@@ -881,6 +930,7 @@ async fn test_commit_sync_race() {
         &tokio::runtime::Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        BlockBufferManager::new(Default::default()),
     );
 
     executor.new_epoch(
@@ -890,6 +940,7 @@ async fn test_commit_sync_race() {
         BlockExecutorConfigFromOnchain::new_no_block_limit(),
         create_transaction_deduper(TransactionDeduperType::NoDedup),
         false,
+        Arc::new(EpochContext::default()),
     );
     executor.commit(&[], generate_li(1, 1), callback.clone()).await.unwrap();
     executor.commit(&[], generate_li(1, 10), callback).await.unwrap();
@@ -16,6 +16,7 @@ use aptos_consensus_types::{
     common::{Payload, RejectedTransactionSummary},
 };
 use aptos_executor_types::{BlockExecutorTrait, ExecutorError, ExecutorResult, StateComputeResult};
+use block_buffer_manager::BlockBufferManager;
 use gaptos::{
     aptos_config::config::transaction_filter_type::Filter,
     aptos_consensus_notifications::{ConsensusNotificationSender, Error},
@@ -30,6 +31,7 @@ use gaptos::{
         validator_txn::ValidatorTransaction,
     },
 };
+use proposer_reth_map::EpochContext;
 use std::{
     sync::{atomic::AtomicU64, Arc},
     time::Duration,
@@ -131,6 +133,7 @@ fn execution_proxy_with_payload_manager(
         &Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        BlockBufferManager::new(Default::default()),
     );
     execution_proxy.new_epoch(
         &EpochState::empty(),
@@ -139,6 +142,7 @@ fn execution_proxy_with_payload_manager(
         BlockExecutorConfigFromOnchain::new_no_block_limit(),
         Arc::new(NoOpDeduper {}),
         false,
+        Arc::new(EpochContext::default()),
     );
     execution_proxy
 }
@@ -218,6 +222,7 @@ async fn schedule_compute_should_discover_validator_txns() {
         &Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        BlockBufferManager::new(Default::default()),
     );
 
     let validator_txn_0 = ValidatorTransaction::dummy(vec![0xFF; 99]);
@@ -241,6 +246,7 @@ async fn schedule_compute_should_discover_validator_txns() {
         BlockExecutorConfigFromOnchain::new_no_block_limit(),
         Arc::new(NoOpDeduper {}),
         false,
+        Arc::new(EpochContext::default()),
     );
 
     // Ensure the dummy executor has received the txns.
@@ -314,6 +320,7 @@ async fn commit_should_discover_validator_txns() {
         &tokio::runtime::Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        BlockBufferManager::new(Default::default()),
     );
 
     let validator_txn_0 = ValidatorTransaction::dummy(vec![0xFF; 99]);
@@ -17,6 +17,7 @@ use gaptos::{
         ledger_info::LedgerInfoWithSignatures, randomness::Randomness,
     },
 };
+use proposer_reth_map::EpochContext;
 use std::sync::Arc;
 
 pub type StateComputerCommitCallBackType =
@@ -62,6 +63,7 @@ pub trait StateComputer: Send + Sync {
         block_executor_onchain_config: BlockExecutorConfigFromOnchain,
         transaction_deduper: Arc<dyn TransactionDeduper>,
         randomness_enabled: bool,
+        epoch_context: Arc<EpochContext>,
     );
 
     // Reconfigure to clear epoch state at end of epoch.
@@ -21,6 +21,7 @@ use aptos_consensus_types::{
     pipelined_block::PipelinedBlock,
 };
 use aptos_executor_types::ExecutorResult;
+use fail::fail_point;
 use futures::{channel::mpsc, SinkExt};
 use futures_channel::mpsc::UnboundedSender;
 use gaptos::{
@@ -39,6 +40,7 @@ use gaptos::{
     },
     move_core_types::account_address::AccountAddress,
 };
+use proposer_reth_map::EpochContext;
 use std::{collections::HashMap, sync::Arc};
 
 pub struct MockExecutionClient {
@@ -67,6 +69,10 @@ impl MockExecutionClient {
     pub async fn commit_to_storage(&self, blocks: OrderedBlocks) -> ExecutorResult<()> {
         let OrderedBlocks { ordered_blocks, ordered_proof, callback } = blocks;
 
+        // Stands in for the execute and pre-commit stages of the real pipeline, so it honours the
+        // same failpoints.
+        fail_point!("consensus::pipeline::execute");
+        fail_point!("consensus::pipeline::pre_commit");
         self.consensus_db.commit_to_storage(ordered_proof.ledger_info().clone());
         // mock sending commit notif to state sync
         let mut txns = vec![];
@@ -104,6 +110,7 @@ impl TExecutionClient for MockExecutionClient {
         _rand_msg_rx: aptos_channel::Receiver<AccountAddress, IncomingRandGenRequest>,
         _highest_committed_round: Round,
         _consensus_db: Option<Arc<ConsensusDB>>,
+        _epoch_context: Arc<EpochContext>,
     ) {
     }
 
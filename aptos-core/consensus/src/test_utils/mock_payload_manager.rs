@@ -27,13 +27,19 @@ use std::time::Duration;
 pub struct MockPayloadManager {
     // used non-mocked PayloadClient to test interaction with shared mempool
     _quorum_store_client: Option<QuorumStoreClient>,
+    empty: bool,
 }
 
 impl MockPayloadManager {
     pub fn new(consensus_to_quorum_store_sender: Option<mpsc::Sender<GetPayloadCommand>>) -> Self {
         let quorum_store_client =
             consensus_to_quorum_store_sender.map(|s| QuorumStoreClient::new(s, 1, 1.1, 100));
-        Self { _quorum_store_client: quorum_store_client }
+        Self { _quorum_store_client: quorum_store_client, empty: false }
+    }
+
+    /// A payload manager that never has transactions to propose.
+    pub fn new_empty() -> Self {
+        Self { _quorum_store_client: None, empty: true }
     }
 }
 
@@ -71,6 +77,9 @@ impl PayloadClient for MockPayloadManager {
         _recent_fill_fraction: f32,
         _block_timestamp: Duration,
     ) -> Result<(Vec<ValidatorTransaction>, Payload), QuorumStoreError> {
+        if self.empty {
+            return Ok((vec![], Payload::empty(false, true)));
+        }
         // generate 1k txn is too slow with coverage instrumentation
         Ok((vec![ValidatorTransaction::dummy(vec![0xFF; 1])], random_payload(10)))
     }
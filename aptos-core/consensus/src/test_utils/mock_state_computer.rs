@@ -27,6 +27,7 @@ use gaptos::{
         ledger_info::LedgerInfoWithSignatures, randomness::Randomness,
     },
 };
+use proposer_reth_map::EpochContext;
 use std::{sync::Arc, time::Duration};
 
 pub struct EmptyStateComputer {
@@ -81,6 +82,7 @@ impl StateComputer for EmptyStateComputer {
         _: BlockExecutorConfigFromOnchain,
         _: Arc<dyn TransactionDeduper>,
         _: bool,
+        _: Arc<EpochContext>,
     ) {
     }
 
@@ -152,6 +154,7 @@ impl StateComputer for RandomComputeResultStateComputer {
         _: BlockExecutorConfigFromOnchain,
         _: Arc<dyn TransactionDeduper>,
         _: bool,
+        _: Arc<EpochContext>,
     ) {
     }
 
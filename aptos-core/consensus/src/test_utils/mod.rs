@@ -4,7 +4,7 @@
 
 #![allow(clippy::unwrap_used)]
 use crate::{
-    block_storage::{BlockReader, BlockStore},
+    block_storage::{recovery_api::BlockBufferRecovery, BlockReader, BlockStore},
     payload_manager::DirectMempoolPayloadManager,
 };
 use aptos_consensus_types::{
@@ -14,11 +14,13 @@ use aptos_consensus_types::{
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
 };
+use block_buffer_manager::BlockBufferManager;
 use gaptos::{
     aptos_crypto::{HashValue, PrivateKey, Uniform},
     aptos_logger::Level,
     aptos_types::{ledger_info::LedgerInfo, validator_signer::ValidatorSigner},
 };
+use proposer_reth_map::EpochContext;
 use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
 use tokio::{runtime, time::timeout};
 
@@ -82,6 +84,7 @@ pub async fn build_simple_tree() -> (Vec<Arc<PipelinedBlock>>, Arc<BlockStore>)
 
 pub async fn build_empty_tree() -> Arc<BlockStore> {
     let (initial_data, storage) = EmptyStorage::start_for_testing().await;
+    let block_buffer_manager = BlockBufferManager::new(Default::default());
     Arc::new(BlockStore::new(
         storage,
         initial_data,
@@ -96,6 +99,9 @@ pub async fn build_empty_tree() -> Arc<BlockStore> {
         false,
         false,
         HashMap::new(), // validator_indices: empty for tests
+        Arc::new(EpochContext::default()),
+        block_buffer_manager.clone(),
+        Arc::new(BlockBufferRecovery::new(block_buffer_manager)),
     ))
 }
 
@@ -209,6 +215,26 @@ pub fn placeholder_sync_info() -> SyncInfo {
     )
 }
 
+/// Proposes one system txn holding the round and accepts only what it would have proposed.
+#[cfg(test)]
+pub(crate) struct MockSystemTxnProvider;
+
+#[cfg(test)]
+impl block_buffer_manager::SystemTxnProvider for MockSystemTxnProvider {
+    fn system_txns(&self, ctx: &block_buffer_manager::SystemTxnContext) -> Vec<Vec<u8>> {
+        vec![ctx.round.to_be_bytes().to_vec()]
+    }
+
+    fn verify(
+        &self,
+        ctx: &block_buffer_manager::SystemTxnContext,
+        txns: &[Vec<u8>],
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(txns == self.system_txns(ctx), "unexpected system txns {:?}", txns);
+        Ok(())
+    }
+}
+
 fn nocapture() -> bool {
     ::std::env::args().any(|arg| arg == "--nocapture")
 }
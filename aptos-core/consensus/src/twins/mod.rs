@@ -3,4 +3,4 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
-mod twins_node;
+pub(crate) mod twins_node;
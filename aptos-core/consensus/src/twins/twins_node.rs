@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_storage::recovery_api::BlockBufferRecovery,
     epoch_manager::EpochManager,
     network::NetworkTask,
     network_interface::{ConsensusNetworkClient, DIRECT_SEND, RPC},
@@ -16,6 +17,7 @@ use crate::{
 };
 use aptos_consensus_types::common::{Author, Round};
 use aptos_mempool::mocks::MockSharedMempool;
+use block_buffer_manager::BlockBufferManager;
 use futures::{channel::mpsc, StreamExt};
 use gaptos::{
     aptos_bounded_executor::BoundedExecutor,
@@ -144,6 +146,7 @@ impl SMRNode {
         let quorum_store_storage = Arc::new(MockQuorumStoreDB::new());
         let bounded_executor = BoundedExecutor::new(2, playground.handle());
 
+        let block_buffer_manager = BlockBufferManager::new(Default::default());
         let epoch_mgr = EpochManager::new(
             &config,
             time_service,
@@ -160,6 +163,8 @@ impl SMRNode {
             vtxn_pool,
             Arc::new(InMemRandDb::new()),
             None, // None,
+            block_buffer_manager.clone(),
+            Arc::new(BlockBufferRecovery::new(block_buffer_manager)),
         );
         let (network_task, network_receiver) =
             NetworkTask::new(network_service_events, self_receiver);
@@ -195,6 +200,38 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ProposerElectionType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        // Disable timeout in twins test to avoid flakiness
+        Self::start_nodes(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            2_000_000,
+        )
+        .await
+    }
+
+    /// Starts a given number of nodes without twins, with round timeouts enabled so that the
+    /// nodes recover from lost messages.
+    pub async fn start_num_nodes_with_round_timeout(
+        num_nodes: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ProposerElectionType,
+        round_initial_timeout_ms: u64,
+    ) -> Vec<Self> {
+        Self::start_nodes(num_nodes, 0, playground, proposer_type, None, round_initial_timeout_ms)
+            .await
+    }
+
+    async fn start_nodes(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ProposerElectionType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        round_initial_timeout_ms: u64,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm { nodes: mut node_configs } =
@@ -262,8 +299,7 @@ impl SMRNode {
                 .expect("Unable to produce waypoint with the provided LedgerInfo");
             config.consensus.safety_rules.test.as_mut().unwrap().waypoint = Some(waypoint);
             config.base.waypoint = WaypointConfig::FromConfig(waypoint);
-            // Disable timeout in twins test to avoid flakiness
-            config.consensus.round_initial_timeout_ms = 2_000_000;
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
 
             let author = author_from_config(&config);
 
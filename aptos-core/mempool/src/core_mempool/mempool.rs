@@ -12,12 +12,17 @@ use crate::{
     },
 };
 use gaptos::{
-    api_types::{account::ExternalAccountAddress, u256_define::TxnHash},
+    api_types::{
+        account::ExternalAccountAddress, u256_define::TxnHash, VerifiedTxn as ApiVerifiedTxn,
+        GLOBAL_CRYPTO_TXN_HASHER,
+    },
     aptos_config::config::NodeConfig,
     aptos_crypto::HashValue,
+    aptos_logger::info,
     aptos_mempool::shared_mempool::types::CoreMempoolTrait,
     aptos_types::{
         account_address::AccountAddress,
+        chain_id::ChainId,
         mempool_status::{MempoolStatus, MempoolStatusCode},
         transaction::{use_case::UseCaseKey, SignedTransaction, TransactionPayload},
         vm_status::DiscardedVMStatus,
@@ -29,8 +34,9 @@ use std::{
     time::{Duration, Instant},
 };
 
-use super::transaction::VerifiedTxn;
-use block_buffer_manager::TxPool;
+use super::transaction::{batch_formation_policy, BatchFormationPolicy, VerifiedTxn};
+use block_buffer_manager::{PooledTxn, TxPool, TxPoolStats};
+use tiny_keccak::{Hasher, Keccak};
 
 /// Per-entry age cache for `read_timeline` deduplication (mempool-broadcast
 /// impl-d §3). Replaces the previous "global wipe" `HashSet`: each entry now
@@ -84,11 +90,105 @@ impl TxnCache {
     }
 }
 
+/// Where a transaction handed to `add_txn` came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TxnSource {
+    /// Submitted by a client of this node.
+    Client,
+    /// Broadcast by a peer; forwarded to our own peers like a local transaction.
+    Peer,
+    /// Broadcast by a peer whose transactions the shared mempool marks as not to be
+    /// re-broadcast (`TimelineState::NonQualified`), e.g. an upstream node. Forwarding it
+    /// would only echo it back towards where it came from.
+    PeerNonQualified,
+}
+
+impl TxnSource {
+    fn rebroadcast(self) -> bool {
+        !matches!(self, TxnSource::PeerNonQualified)
+    }
+}
+
+struct SeenEntry {
+    first_seen: Instant,
+    source: TxnSource,
+}
+
+/// Transactions recently handed to the reth pool through `add_txn`, tagged with their
+/// source. Peers keep re-sending a transaction until it is committed; within `ttl` of the
+/// first delivery those copies are answered without touching the pool, and transactions
+/// that must not be re-broadcast are kept out of the broadcast snapshot for as long as
+/// they stay in the pool. A transaction is recorded while the pool verifies it and forgotten
+/// if the pool rejects it.
+struct SeenTxns {
+    entries: HashMap<TxnHash, SeenEntry>,
+    size: usize,
+    ttl: Duration,
+}
+
+impl SeenTxns {
+    fn new(size: usize, ttl: Duration) -> Self {
+        Self { entries: HashMap::new(), size, ttl }
+    }
+
+    fn is_duplicate(&self, hash: &TxnHash) -> bool {
+        self.entries.get(hash).is_some_and(|e| e.first_seen.elapsed() < self.ttl)
+    }
+
+    /// Keeps the source of the first delivery when a transaction comes back after the TTL.
+    /// Returns the time it was recorded at, see [`Self::forget`].
+    fn record(&mut self, hash: TxnHash, source: TxnSource) -> Instant {
+        let now = Instant::now();
+        if let Some(entry) = self.entries.get_mut(&hash) {
+            entry.first_seen = now;
+            return now;
+        }
+        if self.entries.len() >= self.size {
+            let ttl = self.ttl;
+            self.entries.retain(|_, e| now.duration_since(e.first_seen) < ttl);
+        }
+        if self.entries.len() < self.size {
+            self.entries.insert(hash, SeenEntry { first_seen: now, source });
+        }
+        now
+    }
+
+    /// Drops the entry recorded at `recorded_at`, unless a later delivery recorded it again.
+    fn forget(&mut self, hash: &TxnHash, recorded_at: Instant) {
+        if self.entries.get(hash).is_some_and(|e| e.first_seen == recorded_at) {
+            self.entries.remove(hash);
+        }
+    }
+
+    fn rebroadcast(&self, hash: &TxnHash) -> bool {
+        self.entries.get(hash).map_or(true, |e| e.source.rebroadcast())
+    }
+}
+
+/// Hashes transaction bytes the way the execution layer does, see `GLOBAL_CRYPTO_TXN_HASHER`.
+/// Nodes without an execution layer (kv_node, bench) install no hasher and fall back to the
+/// keccak256 of the bytes, which is what reth hashes with.
+fn txn_hash_of(bytes: &Vec<u8>) -> TxnHash {
+    if let Some(hasher) = GLOBAL_CRYPTO_TXN_HASHER.get() {
+        return TxnHash::from_bytes(&hasher(bytes));
+    }
+    let mut out = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(bytes);
+    keccak.finalize(&mut out);
+    TxnHash::from_bytes(&out)
+}
+
 /// A per-round snapshot of `pool.pending_transactions()` sliced by sender
 /// bucket. Amortises N peer × M bucket × 2 priority `pool.pending_*` calls
 /// down to ≈ one per `max_age` window. See impl-d §5.
+///
+/// Shards and their transactions are reference-counted: a `read_timeline`
+/// call only bumps the shard's count instead of deep-copying every
+/// transaction in it, and only the transactions actually dispatched are
+/// cloned into the broadcast batch.
 struct Snapshot {
-    shards: HashMap<MempoolSenderBucket, Vec<SnapshotEntry>>,
+    shards: HashMap<MempoolSenderBucket, Arc<[SnapshotEntry]>>,
     taken_at: Instant,
     max_age: Duration,
     /// False until the first refresh runs, so `read_timeline` can tell
@@ -97,10 +197,9 @@ struct Snapshot {
     initialized: bool,
 }
 
-#[derive(Clone)]
 struct SnapshotEntry {
     hash: TxnHash,
-    txn: SignedTransaction,
+    txn: Arc<SignedTransaction>,
 }
 
 /// Mempool-local self-observation of which `(bucket, priority)` slots have
@@ -140,10 +239,13 @@ impl ObservedTopology {
 }
 
 pub struct Mempool {
-    pool: Box<dyn TxPool>,
+    pool: Arc<dyn TxPool>,
     txn_cache: Arc<Mutex<TxnCache>>,
     snapshot: Arc<Mutex<Snapshot>>,
     topology: Arc<Mutex<ObservedTopology>>,
+    seen_txns: Arc<Mutex<SeenTxns>>,
+    // Transactions for any other chain are rejected when set
+    chain_id: Option<ChainId>,
     num_sender_buckets: u8,
 }
 
@@ -179,70 +281,11 @@ impl CoreMempoolTrait for Mempool {
         _before: Option<Instant>,
         priority_of_receiver: BroadcastPeerPriority,
     ) -> (Vec<(SignedTransaction, u64)>, MultiBucketTimelineIndexIds) {
-        // Self-observe topology: this call IS proof that
-        // (sender_bucket, priority_of_receiver) is currently an active slot.
-        let target_slot: TargetSlot = (sender_bucket, priority_discriminant(&priority_of_receiver));
-        let priority_count = {
-            let mut topo = self.topology.lock().unwrap();
-            topo.observe(target_slot);
-            topo.priority_count_for_bucket(sender_bucket)
-        };
-
-        let shard: Vec<SnapshotEntry> = {
-            let mut snap = self.snapshot.lock().unwrap();
-            if !snap.initialized || snap.taken_at.elapsed() >= snap.max_age {
-                self.refresh_snapshot_locked(&mut snap);
-            }
-            snap.shards.get(&sender_bucket).cloned().unwrap_or_default()
-        };
-
-        let now = Instant::now();
-        let mut out: Vec<(SignedTransaction, u64)> = Vec::with_capacity(count.min(shard.len()));
-        let mut cache = self.txn_cache.lock().unwrap();
-
-        for entry in shard {
-            if out.len() >= count {
-                break;
-            }
-            // PR #722 review point 3: the TTL cache is now self-sufficient
-            // for failover semantics. Primary first-sighting dispatches
-            // immediately. Failover first-sighting seeds a placeholder so
-            // the TTL clock starts here. Within the `cache.ttl` grace,
-            // Primary can still claim the placeholder (preserves the
-            // Primary-first invariant). After the grace elapses, Failover
-            // takes over — no dependency on `priority.rs` promotion.
-            let dispatch = match cache.entries.get(&entry.hash) {
-                None => matches!(priority_of_receiver, BroadcastPeerPriority::Primary),
-                Some(e) if !e.dispatched => match priority_of_receiver {
-                    BroadcastPeerPriority::Primary => true,
-                    BroadcastPeerPriority::Failover => {
-                        now.duration_since(e.last_dispatched_at) >= cache.ttl
-                    }
-                },
-                Some(e) if now.duration_since(e.last_dispatched_at) < cache.ttl => false,
-                Some(e) if e.last_target == target_slot && priority_count >= 2 => false,
-                Some(_) => true,
-            };
-            if !dispatch {
-                // Failover first-sighting seeds a placeholder so the TTL
-                // clock starts. `or_insert` (not `insert`) preserves the
-                // original first_seen_at across repeated Failover ticks
-                // during the grace window.
-                if matches!(priority_of_receiver, BroadcastPeerPriority::Failover) {
-                    cache.entries.entry(entry.hash).or_insert(CacheEntry {
-                        last_dispatched_at: now,
-                        last_target: target_slot,
-                        dispatched: false,
-                    });
-                }
-                continue;
-            }
-            out.push((entry.txn, 0));
-            cache.entries.insert(
-                entry.hash,
-                CacheEntry { last_dispatched_at: now, last_target: target_slot, dispatched: true },
-            );
-        }
+        let out: Vec<(SignedTransaction, u64)> = self
+            .read_timeline_shared(sender_bucket, count, priority_of_receiver)
+            .into_iter()
+            .map(|txn| (Arc::unwrap_or_clone(txn), 0))
+            .collect();
         let len = out.len();
         (out, MultiBucketTimelineIndexIds { id_per_bucket: vec![0; len] })
     }
@@ -264,18 +307,57 @@ impl CoreMempoolTrait for Mempool {
         txn: SignedTransaction,
         _ranking_score: u64,
         _sequence_info: u64,
-        _timeline_state: gaptos::aptos_mempool::core_mempool::TimelineState,
-        _client_submitted: bool,
+        timeline_state: gaptos::aptos_mempool::core_mempool::TimelineState,
+        client_submitted: bool,
         _ready_time_at_sender: Option<u64>,
         _priority: Option<BroadcastPeerPriority>,
     ) -> MempoolStatus {
         if !matches!(txn.payload(), TransactionPayload::GTxnBytes(_)) {
             return MempoolStatus::new(MempoolStatusCode::UnknownStatus);
         }
+        if let Some(chain_id) = self.chain_id {
+            if txn.chain_id() != chain_id {
+                return MempoolStatus::new(MempoolStatusCode::VmError).with_message(format!(
+                    "{:?}: transaction is for chain {}, this node runs chain {}",
+                    DiscardedVMStatus::BAD_CHAIN_ID,
+                    txn.chain_id(),
+                    chain_id
+                ));
+            }
+        }
 
         let verfited_txn = crate::core_mempool::transaction::VerifiedTxn::from(txn);
-        let res = self.pool.add_external_txn(verfited_txn.into());
-        if res {
+        // The committed hash travels with the transaction and a peer can set it to anything, so
+        // the dedup key is hashed from the bytes that go into the pool.
+        let hash = txn_hash_of(verfited_txn.bytes());
+        let verfited_txn: ApiVerifiedTxn = verfited_txn.into();
+        let source = if client_submitted {
+            TxnSource::Client
+        } else if matches!(
+            timeline_state,
+            gaptos::aptos_mempool::core_mempool::TimelineState::NonQualified
+        ) {
+            TxnSource::PeerNonQualified
+        } else {
+            TxnSource::Peer
+        };
+        // Copies arriving while the pool verifies the transaction are answered from the cache,
+        // but one the pool rejects is forgotten: a resend of one rejected for now (e.g. an
+        // unfunded sender) gets in.
+        let recorded_at = {
+            let mut seen_txns = self.seen_txns.lock().unwrap();
+            if seen_txns.is_duplicate(&hash) {
+                return MempoolStatus::new(MempoolStatusCode::Accepted);
+            }
+            seen_txns.record(hash, source)
+        };
+        let seen_txns = self.seen_txns.clone();
+        let on_verified = Box::new(move |admitted: bool| {
+            if !admitted {
+                seen_txns.lock().unwrap().forget(&hash, recorded_at);
+            }
+        });
+        if self.pool.add_external_txn_deferred(verfited_txn, on_verified) {
             MempoolStatus::new(MempoolStatusCode::Accepted)
         } else {
             MempoolStatus::new(MempoolStatusCode::UnknownStatus)
@@ -338,10 +420,14 @@ impl Mempool {
             .ok()
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(1500);
+        let seen_ttl_secs = std::env::var("MEMPOOL_SEEN_TXN_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(30);
         let num_sender_buckets = config.mempool.num_sender_buckets.max(1);
 
         Self {
-            pool,
+            pool: Arc::from(pool),
             txn_cache: Arc::new(Mutex::new(TxnCache::new(100_000, Duration::from_secs(ttl_secs)))),
             snapshot: Arc::new(Mutex::new(Snapshot {
                 shards: HashMap::new(),
@@ -352,24 +438,147 @@ impl Mempool {
             topology: Arc::new(Mutex::new(ObservedTopology::new(Duration::from_millis(
                 topology_ttl_ms,
             )))),
+            seen_txns: Arc::new(Mutex::new(SeenTxns::new(
+                100_000,
+                Duration::from_secs(seen_ttl_secs),
+            ))),
+            chain_id: None,
             num_sender_buckets,
         }
     }
 
+    /// Rejects transactions tagged with any chain other than `chain_id`. The check is
+    /// advisory: the tag is the `ExternalChainId` of the transaction wrapper, which the peer
+    /// that gossiped it sets, not the chain id it was signed for. The pool decodes the payload
+    /// and checks the signed chain id, e.g. the EIP-155 chain id of an EVM transaction.
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Returns a handle that keeps working after the mempool is moved into the shared mempool
+    /// runtime, so reconfiguration events can reach the pooled transactions.
+    pub fn epoch_change_hook(&self) -> EpochChangeHook {
+        EpochChangeHook {
+            pool: self.pool.clone(),
+            snapshot: self.snapshot.clone(),
+            topology: self.topology.clone(),
+        }
+    }
+
+    /// Returns a read-only view for the inspection API, see [`MempoolInspector`].
+    pub fn inspector(&self) -> MempoolInspector {
+        MempoolInspector {
+            pool: self.pool.clone(),
+            txn_cache: self.txn_cache.clone(),
+            snapshot: self.snapshot.clone(),
+            seen_txns: self.seen_txns.clone(),
+            num_sender_buckets: self.num_sender_buckets,
+        }
+    }
+
+    /// Selects up to `count` transactions of `sender_bucket` to broadcast to a
+    /// peer in the `priority_of_receiver` slot, applying the per-transaction
+    /// TTL dedup. Returns shared handles into the current snapshot, so callers
+    /// that can serialize from a reference pay no per-transaction copy;
+    /// `read_timeline` clones at the `CoreMempoolTrait` boundary.
+    pub fn read_timeline_shared(
+        &self,
+        sender_bucket: MempoolSenderBucket,
+        count: usize,
+        priority_of_receiver: BroadcastPeerPriority,
+    ) -> Vec<Arc<SignedTransaction>> {
+        // Self-observe topology: this call IS proof that
+        // (sender_bucket, priority_of_receiver) is currently an active slot.
+        let target_slot: TargetSlot = (sender_bucket, priority_discriminant(&priority_of_receiver));
+        let priority_count = {
+            let mut topo = self.topology.lock().unwrap();
+            topo.observe(target_slot);
+            topo.priority_count_for_bucket(sender_bucket)
+        };
+
+        let shard: Arc<[SnapshotEntry]> = {
+            let mut snap = self.snapshot.lock().unwrap();
+            if !snap.initialized || snap.taken_at.elapsed() >= snap.max_age {
+                self.refresh_snapshot_locked(&mut snap);
+            }
+            snap.shards.get(&sender_bucket).cloned().unwrap_or_else(|| Arc::new([]))
+        };
+
+        let now = Instant::now();
+        let mut out: Vec<Arc<SignedTransaction>> = Vec::with_capacity(count.min(shard.len()));
+        let mut cache = self.txn_cache.lock().unwrap();
+
+        for entry in shard.iter() {
+            if out.len() >= count {
+                break;
+            }
+            // PR #722 review point 3: the TTL cache is now self-sufficient
+            // for failover semantics. Primary first-sighting dispatches
+            // immediately. Failover first-sighting seeds a placeholder so
+            // the TTL clock starts here. Within the `cache.ttl` grace,
+            // Primary can still claim the placeholder (preserves the
+            // Primary-first invariant). After the grace elapses, Failover
+            // takes over — no dependency on `priority.rs` promotion.
+            let dispatch = match cache.entries.get(&entry.hash) {
+                None => matches!(priority_of_receiver, BroadcastPeerPriority::Primary),
+                Some(e) if !e.dispatched => match priority_of_receiver {
+                    BroadcastPeerPriority::Primary => true,
+                    BroadcastPeerPriority::Failover => {
+                        now.duration_since(e.last_dispatched_at) >= cache.ttl
+                    }
+                },
+                Some(e) if now.duration_since(e.last_dispatched_at) < cache.ttl => false,
+                Some(e) if e.last_target == target_slot && priority_count >= 2 => false,
+                Some(_) => true,
+            };
+            if !dispatch {
+                // Failover first-sighting seeds a placeholder so the TTL
+                // clock starts. `or_insert` (not `insert`) preserves the
+                // original first_seen_at across repeated Failover ticks
+                // during the grace window.
+                if matches!(priority_of_receiver, BroadcastPeerPriority::Failover) {
+                    cache.entries.entry(entry.hash).or_insert(CacheEntry {
+                        last_dispatched_at: now,
+                        last_target: target_slot,
+                        dispatched: false,
+                    });
+                }
+                continue;
+            }
+            out.push(entry.txn.clone());
+            cache.entries.insert(
+                entry.hash,
+                CacheEntry { last_dispatched_at: now, last_target: target_slot, dispatched: true },
+            );
+        }
+        out
+    }
+
     fn refresh_snapshot_locked(&self, snap: &mut Snapshot) {
         let mut shards: HashMap<MempoolSenderBucket, Vec<SnapshotEntry>> = HashMap::new();
         let mut alive: HashSet<TxnHash> = HashSet::new();
+        let mut seen = self.seen_txns.lock().unwrap();
         for txn in self.pool.get_broadcast_txns(None) {
             let bucket = sender_to_bucket(txn.sender(), self.num_sender_buckets);
             let hash = TxnHash::from_bytes(txn.committed_hash().as_slice());
             alive.insert(hash);
+            if !seen.rebroadcast(&hash) {
+                continue;
+            }
             let signed: SignedTransaction = VerifiedTxn::from(txn).into();
-            shards.entry(bucket).or_default().push(SnapshotEntry { hash, txn: signed });
+            shards.entry(bucket).or_default().push(SnapshotEntry { hash, txn: Arc::new(signed) });
         }
-        snap.shards = shards;
+        snap.shards = shards.into_iter().map(|(bucket, shard)| (bucket, shard.into())).collect();
         snap.taken_at = Instant::now();
         snap.initialized = true;
 
+        // Sources are only needed while the transaction is pooled; past the TTL nothing else
+        // needs the entry.
+        let (now, ttl) = (Instant::now(), seen.ttl);
+        seen.entries.retain(|h, e| alive.contains(h) || now.duration_since(e.first_seen) < ttl);
+        drop(seen);
+
         // Lazy GC: drop cache entries whose hash is no longer alive in the
         // reth pool (committed / replaced / evicted). Then cap by size.
         let mut cache = self.txn_cache.lock().unwrap();
@@ -444,8 +653,12 @@ impl Mempool {
         let mut transactions = vec![];
         let mut total_bytes: u64 = 0;
         let best_txns = self.pool.best_txns(Some(filter), max_txns as usize, max_bytes);
+        let price_txns = batch_formation_policy() == BatchFormationPolicy::FeePriority;
         for txn in best_txns {
-            let signed_txn: SignedTransaction = VerifiedTxn::from(txn).into();
+            // Gas-bucketed batching stable-sorts by price, which is only nonce-safe while every
+            // price is equal, so real prices are attached only for fee-priority batching.
+            let gas_unit_price = if price_txns { self.pool.gas_unit_price(&txn) } else { 0 };
+            let signed_txn = VerifiedTxn::from(txn).into_signed_transaction(gas_unit_price);
             let txn_bytes = signed_txn.raw_txn_bytes_len() as u64;
             // Authoritatively enforce the byte budget so proposers never build a
             // payload that exceeds max_receiving_block_bytes, which receivers reject
@@ -471,9 +684,182 @@ impl Mempool {
     }
 }
 
+/// Resets the mempool state that depends on the validator set when the node enters a new epoch.
+///
+/// Transactions admitted under the previous epoch's on-chain config can be invalid in the
+/// first blocks of the new one; their re-validation is left to the execution layer through
+/// [`TxPool::on_new_epoch`]. The dispatch history is kept: it records which transactions were
+/// already broadcast, which does not change with the validator set. So are the transactions seen
+/// recently, which peers keep re-sending across the epoch boundary.
+#[derive(Clone)]
+pub struct EpochChangeHook {
+    pool: Arc<dyn TxPool>,
+    snapshot: Arc<Mutex<Snapshot>>,
+    topology: Arc<Mutex<ObservedTopology>>,
+}
+
+impl EpochChangeHook {
+    pub fn on_new_epoch(&self, epoch: u64) {
+        // Peers map to different (bucket, priority) slots under the new validator set, so the
+        // observed topology no longer says which slots are served.
+        self.topology.lock().unwrap().last_seen.clear();
+
+        self.pool.on_new_epoch(epoch);
+        // The next broadcast reads the pool the execution layer just re-validated
+        self.snapshot.lock().unwrap().initialized = false;
+        info!("Mempool entered epoch {}", epoch);
+    }
+}
+
+/// Broadcast state of one sender bucket.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SenderBucketStats {
+    pub bucket: MempoolSenderBucket,
+    /// Transactions of the bucket in the last broadcast snapshot.
+    pub snapshot_txns: usize,
+    /// Transactions handed to a peer within the dispatch TTL.
+    pub dispatched: usize,
+    /// Transactions a failover peer saw first, waiting for the primary peer to take them.
+    pub awaiting_primary: usize,
+}
+
+/// What the mempool knows about the pooled transactions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MempoolStats {
+    /// Pending and parked counts of the execution layer pool, if it reports them.
+    pub pool: Option<TxPoolStats>,
+    pub buckets: Vec<SenderBucketStats>,
+    /// Age of the broadcast snapshot, `None` before the first broadcast.
+    pub snapshot_age: Option<Duration>,
+    /// Transactions accepted recently, by where they came from.
+    pub seen_from_clients: usize,
+    pub seen_from_peers: usize,
+    pub seen_from_upstream: usize,
+}
+
+/// A pooled transaction of one sender, with its broadcast state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountTxnState {
+    pub txn: PooledTxn,
+    /// Handed to a peer within the dispatch TTL.
+    pub dispatched: bool,
+    /// Rebroadcast to peers, false for transactions that came from upstream.
+    pub rebroadcast: bool,
+}
+
+/// The pooled transactions of one sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountMempoolState {
+    pub bucket: MempoolSenderBucket,
+    /// Ordered by nonce.
+    pub txns: Vec<AccountTxnState>,
+    /// Nonce of the next transaction of the sender on chain, if the pool can read it.
+    pub committed_nonce: Option<u64>,
+    /// Lowest nonce missing while a higher one is pooled, counting from the committed nonce, or
+    /// from the first pooled one if it is unknown. The transactions above it stay parked until
+    /// it arrives.
+    pub nonce_gap: Option<u64>,
+}
+
+/// See [`AccountMempoolState::nonce_gap`], `pooled` is ordered by nonce.
+fn nonce_gap(pooled: &[PooledTxn], committed_nonce: Option<u64>) -> Option<u64> {
+    let mut next = committed_nonce.or_else(|| pooled.first().map(|txn| txn.nonce))?;
+    for txn in pooled {
+        if txn.nonce > next {
+            return Some(next);
+        }
+        if txn.nonce == next {
+            next += 1;
+        }
+    }
+    None
+}
+
+/// Read-only view of the mempool for the inspection API. Like [`EpochChangeHook`], it keeps
+/// working after the mempool is moved into the shared mempool runtime.
+#[derive(Clone)]
+pub struct MempoolInspector {
+    pool: Arc<dyn TxPool>,
+    txn_cache: Arc<Mutex<TxnCache>>,
+    snapshot: Arc<Mutex<Snapshot>>,
+    seen_txns: Arc<Mutex<SeenTxns>>,
+    num_sender_buckets: u8,
+}
+
+impl MempoolInspector {
+    pub fn stats(&self) -> MempoolStats {
+        let mut buckets: Vec<SenderBucketStats> = (0..self.num_sender_buckets)
+            .map(|bucket| SenderBucketStats { bucket, ..Default::default() })
+            .collect();
+        let snapshot_age = {
+            let snap = self.snapshot.lock().unwrap();
+            for (bucket, shard) in &snap.shards {
+                if let Some(stats) = buckets.get_mut(*bucket as usize) {
+                    stats.snapshot_txns = shard.len();
+                }
+            }
+            snap.initialized.then(|| snap.taken_at.elapsed())
+        };
+        {
+            let cache = self.txn_cache.lock().unwrap();
+            let now = Instant::now();
+            for entry in cache.entries.values() {
+                let Some(stats) = buckets.get_mut(entry.last_target.0 as usize) else { continue };
+                if !entry.dispatched {
+                    stats.awaiting_primary += 1;
+                } else if now.duration_since(entry.last_dispatched_at) < cache.ttl {
+                    stats.dispatched += 1;
+                }
+            }
+        }
+        let mut stats =
+            MempoolStats { pool: self.pool.stats(), buckets, snapshot_age, ..Default::default() };
+        for entry in self.seen_txns.lock().unwrap().entries.values() {
+            match entry.source {
+                TxnSource::Client => stats.seen_from_clients += 1,
+                TxnSource::Peer => stats.seen_from_peers += 1,
+                TxnSource::PeerNonQualified => stats.seen_from_upstream += 1,
+            }
+        }
+        stats
+    }
+
+    /// Size of the execution layer pool, without walking the pool or the broadcast state.
+    pub fn pool_size(&self) -> Option<TxPoolStats> {
+        self.pool.size()
+    }
+
+    /// `None` if the pool cannot list the transactions of a sender.
+    pub fn account(&self, sender: &ExternalAccountAddress) -> Option<AccountMempoolState> {
+        let pooled = self.pool.sender_txns(sender)?;
+        let committed_nonce = self.pool.committed_nonce(sender);
+        let nonce_gap = nonce_gap(&pooled, committed_nonce);
+        let now = Instant::now();
+        let cache = self.txn_cache.lock().unwrap();
+        let seen = self.seen_txns.lock().unwrap();
+        let txns = pooled
+            .into_iter()
+            .map(|txn| {
+                let dispatched = cache.entries.get(&txn.hash).is_some_and(|e| {
+                    e.dispatched && now.duration_since(e.last_dispatched_at) < cache.ttl
+                });
+                let rebroadcast = seen.rebroadcast(&txn.hash);
+                AccountTxnState { txn, dispatched, rebroadcast }
+            })
+            .collect();
+        Some(AccountMempoolState {
+            bucket: sender_to_bucket(sender, self.num_sender_buckets),
+            txns,
+            committed_nonce,
+            nonce_gap,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use block_buffer_manager::OnTxnVerified;
     use gaptos::api_types::{
         account::ExternalChainId, VerifiedTxn as ApiVerifiedTxn, GLOBAL_CRYPTO_TXN_HASHER,
     };
@@ -526,13 +912,17 @@ mod tests {
             ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
                 Box::new(self.0.lock().unwrap().clone().into_iter())
             }
-            fn add_external_txn(&self, _t: ApiVerifiedTxn) -> bool {
-                false
+            fn add_external_txn(&self, t: ApiVerifiedTxn) -> bool {
+                self.0.lock().unwrap().push(t);
+                true
+            }
+            fn remove_txns(&self, t: Vec<ApiVerifiedTxn>) {
+                let removed: HashSet<_> = t.iter().map(|txn| txn.committed_hash()).collect();
+                self.0.lock().unwrap().retain(|txn| !removed.contains(&txn.committed_hash()));
             }
-            fn remove_txns(&self, _t: Vec<ApiVerifiedTxn>) {}
         }
         Mempool {
-            pool: Box::new(Shared(txns)),
+            pool: Arc::new(Shared(txns)),
             txn_cache: Arc::new(Mutex::new(TxnCache::new(100_000, ttl))),
             snapshot: Arc::new(Mutex::new(Snapshot {
                 shards: HashMap::new(),
@@ -541,6 +931,8 @@ mod tests {
                 initialized: false,
             })),
             topology: Arc::new(Mutex::new(ObservedTopology::new(Duration::from_secs(10)))),
+            seen_txns: Arc::new(Mutex::new(SeenTxns::new(100_000, ttl))),
+            chain_id: None,
             num_sender_buckets: num_buckets,
         }
     }
@@ -561,6 +953,16 @@ mod tests {
         .0
     }
 
+    #[test]
+    fn read_timeline_shared_does_not_copy_snapshot() {
+        let txns = Arc::new(StdMutex::new(vec![mk_txn(0, 0, 1), mk_txn(0, 1, 2)]));
+        let m = mempool_with(txns, Duration::from_secs(60), Duration::from_secs(60), 1);
+        let out = m.read_timeline_shared(0, 1, BroadcastPeerPriority::Primary);
+        assert_eq!(out.len(), 1);
+        let snap = m.snapshot.lock().unwrap();
+        assert!(Arc::ptr_eq(&out[0], &snap.shards[&0][0].txn));
+    }
+
     #[test]
     fn first_dispatch_then_in_ttl_suppress() {
         let txns = Arc::new(StdMutex::new(vec![mk_txn(0, 0, 1)]));
@@ -572,6 +974,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn epoch_change_resets_topology_and_keeps_dispatch_history() {
+        let txns = Arc::new(StdMutex::new(vec![mk_txn(0, 0, 8), mk_txn(0, 1, 9)]));
+        let m = mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1);
+        assert_eq!(read(&m, 0, BroadcastPeerPriority::Primary, 16).len(), 2);
+        assert!(!m.topology.lock().unwrap().last_seen.is_empty());
+
+        m.epoch_change_hook().on_new_epoch(2);
+        assert!(m.topology.lock().unwrap().last_seen.is_empty());
+        assert_eq!(txns.lock().unwrap().len(), 2);
+        assert!(
+            read(&m, 0, BroadcastPeerPriority::Primary, 16).is_empty(),
+            "dispatch history must survive the epoch change"
+        );
+    }
+
     #[test]
     fn failover_cannot_steal_first_dispatch() {
         // A Failover tick that lands before any Primary tick must NOT take the
@@ -729,6 +1147,288 @@ mod tests {
         );
     }
 
+    fn add_from_peer(
+        m: &mut Mempool,
+        txn: ApiVerifiedTxn,
+        timeline_state: gaptos::aptos_mempool::core_mempool::TimelineState,
+    ) -> MempoolStatus {
+        let signed = VerifiedTxn::from(txn).into_signed_transaction(0);
+        m.add_txn(signed, 0, 0, timeline_state, false, None, None)
+    }
+
+    #[test]
+    fn duplicate_peer_deliveries_reach_pool_once() {
+        let txns = Arc::new(StdMutex::new(vec![]));
+        let mut m =
+            mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1);
+        for _ in 0..3 {
+            let status = add_from_peer(
+                &mut m,
+                mk_txn(0, 0, 50),
+                gaptos::aptos_mempool::core_mempool::TimelineState::NotReady,
+            );
+            assert_eq!(status.code, MempoolStatusCode::Accepted);
+        }
+        assert_eq!(txns.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn non_qualified_peer_txns_are_not_rebroadcast() {
+        use gaptos::aptos_mempool::core_mempool::TimelineState as PeerTimelineState;
+        let txns = Arc::new(StdMutex::new(vec![]));
+        let mut m =
+            mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1);
+        add_from_peer(&mut m, mk_txn(0, 0, 51), PeerTimelineState::NonQualified);
+        add_from_peer(&mut m, mk_txn(0, 1, 52), PeerTimelineState::NotReady);
+        assert_eq!(txns.lock().unwrap().len(), 2);
+
+        let out = read(&m, 0, BroadcastPeerPriority::Primary, 16);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0.sequence_number(), 1);
+    }
+
+    #[test]
+    fn dedup_ignores_the_peer_supplied_hash() {
+        let txns = Arc::new(StdMutex::new(vec![]));
+        let mut m =
+            mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1);
+        // Two different transactions that claim the same committed hash
+        for (seq, body_seed) in [(0, 63), (1, 64)] {
+            let mut txn = VerifiedTxn::from(mk_txn(0, seq, body_seed));
+            txn.committed_hash = HashValue::new([63; 32]);
+            let status = m.add_txn(
+                txn.into_signed_transaction(0),
+                0,
+                0,
+                gaptos::aptos_mempool::core_mempool::TimelineState::NotReady,
+                false,
+                None,
+                None,
+            );
+            assert_eq!(status.code, MempoolStatusCode::Accepted);
+        }
+        assert_eq!(txns.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn seen_txns_survive_an_epoch_change() {
+        let txns = Arc::new(StdMutex::new(vec![]));
+        let mut m =
+            mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1);
+        let state = || gaptos::aptos_mempool::core_mempool::TimelineState::NotReady;
+        add_from_peer(&mut m, mk_txn(0, 0, 65), state());
+        m.epoch_change_hook().on_new_epoch(2);
+        add_from_peer(&mut m, mk_txn(0, 0, 65), state());
+        assert_eq!(txns.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejected_txns_are_not_remembered() {
+        struct Rejecting;
+        impl TxPool for Rejecting {
+            fn best_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+                _l: usize,
+                _max_bytes: u64,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn get_broadcast_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn add_external_txn(&self, _t: ApiVerifiedTxn) -> bool {
+                false
+            }
+            fn remove_txns(&self, _t: Vec<ApiVerifiedTxn>) {}
+        }
+        let m = mempool_with(
+            Arc::new(StdMutex::new(vec![])),
+            Duration::from_secs(60),
+            Duration::from_millis(0),
+            1,
+        );
+        let mut m = Mempool { pool: Arc::new(Rejecting), ..m };
+        let status = add_from_peer(
+            &mut m,
+            mk_txn(0, 0, 66),
+            gaptos::aptos_mempool::core_mempool::TimelineState::NotReady,
+        );
+        assert_eq!(status.code, MempoolStatusCode::UnknownStatus);
+        assert!(m.seen_txns.lock().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn txns_are_remembered_while_verified_and_forgotten_once_rejected() {
+        struct Deferred(Arc<StdMutex<Vec<OnTxnVerified>>>);
+        impl TxPool for Deferred {
+            fn best_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+                _l: usize,
+                _max_bytes: u64,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn get_broadcast_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn add_external_txn(&self, _t: ApiVerifiedTxn) -> bool {
+                unreachable!("add_txn hands transactions over with add_external_txn_deferred")
+            }
+            fn add_external_txn_deferred(
+                &self,
+                _t: ApiVerifiedTxn,
+                on_verified: OnTxnVerified,
+            ) -> bool {
+                self.0.lock().unwrap().push(on_verified);
+                true
+            }
+            fn remove_txns(&self, _t: Vec<ApiVerifiedTxn>) {}
+        }
+        let pending = Arc::new(StdMutex::new(vec![]));
+        let m = mempool_with(
+            Arc::new(StdMutex::new(vec![])),
+            Duration::from_secs(60),
+            Duration::from_millis(0),
+            1,
+        );
+        let mut m = Mempool { pool: Arc::new(Deferred(pending.clone())), ..m };
+        let state = || gaptos::aptos_mempool::core_mempool::TimelineState::NotReady;
+
+        assert_eq!(
+            add_from_peer(&mut m, mk_txn(0, 0, 67), state()).code,
+            MempoolStatusCode::Accepted
+        );
+        // A copy arriving while the first one is verified is not handed to the pool
+        add_from_peer(&mut m, mk_txn(0, 0, 67), state());
+        assert_eq!(pending.lock().unwrap().len(), 1);
+        assert_eq!(m.seen_txns.lock().unwrap().entries.len(), 1);
+
+        // Rejected by verification, so a resend is verified again
+        (pending.lock().unwrap().pop().unwrap())(false);
+        assert!(m.seen_txns.lock().unwrap().entries.is_empty());
+        add_from_peer(&mut m, mk_txn(0, 0, 67), state());
+        assert_eq!(pending.lock().unwrap().len(), 1);
+
+        (pending.lock().unwrap().pop().unwrap())(true);
+        assert_eq!(m.seen_txns.lock().unwrap().entries.len(), 1);
+        add_from_peer(&mut m, mk_txn(0, 0, 67), state());
+        assert!(pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn other_chain_txns_are_rejected() {
+        let txns = Arc::new(StdMutex::new(vec![]));
+        let mut m =
+            mempool_with(txns.clone(), Duration::from_secs(60), Duration::from_millis(0), 1)
+                .with_chain_id(ChainId::new(1));
+        let state = || gaptos::aptos_mempool::core_mempool::TimelineState::NotReady;
+        assert_eq!(
+            add_from_peer(&mut m, mk_txn(0, 0, 56), state()).code,
+            MempoolStatusCode::Accepted
+        );
+        let other_chain = ApiVerifiedTxn::new(vec![57; 32], mk_addr(0), 1, ExternalChainId::new(2));
+        assert_eq!(add_from_peer(&mut m, other_chain, state()).code, MempoolStatusCode::VmError);
+        assert_eq!(txns.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn inspector_reports_dispatch_and_nonce_gaps() {
+        let txns = Arc::new(StdMutex::new(vec![mk_txn(0, 0, 60), mk_txn(1, 0, 61)]));
+        let mut m = mempool_with(txns, Duration::from_secs(60), Duration::from_millis(0), 2);
+        add_from_peer(
+            &mut m,
+            mk_txn(1, 1, 62),
+            gaptos::aptos_mempool::core_mempool::TimelineState::NonQualified,
+        );
+        assert_eq!(read(&m, 0, BroadcastPeerPriority::Primary, 16).len(), 1);
+        assert!(read(&m, 1, BroadcastPeerPriority::Failover, 16).is_empty());
+
+        let stats = m.inspector().stats();
+        assert_eq!(stats.pool, None);
+        assert_eq!(stats.seen_from_upstream, 1);
+        let buckets: Vec<_> = stats
+            .buckets
+            .iter()
+            .map(|b| (b.bucket, b.snapshot_txns, b.dispatched, b.awaiting_primary))
+            .collect();
+        assert_eq!(buckets, vec![(0, 1, 1, 0), (1, 1, 0, 1)]);
+
+        struct GappedPool;
+        impl TxPool for GappedPool {
+            fn best_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+                _l: usize,
+                _max_bytes: u64,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn get_broadcast_txns(
+                &self,
+                _f: Option<Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>>,
+            ) -> Box<dyn Iterator<Item = ApiVerifiedTxn>> {
+                Box::new(std::iter::empty())
+            }
+            fn add_external_txn(&self, _t: ApiVerifiedTxn) -> bool {
+                false
+            }
+            fn remove_txns(&self, _t: Vec<ApiVerifiedTxn>) {}
+            fn sender_txns(&self, _sender: &ExternalAccountAddress) -> Option<Vec<PooledTxn>> {
+                let txn = |nonce, parked| PooledTxn {
+                    hash: TxnHash::from_bytes(&[nonce as u8; 32]),
+                    nonce,
+                    parked,
+                    age: Duration::ZERO,
+                };
+                Some(vec![txn(3, false), txn(4, false), txn(7, true)])
+            }
+            fn committed_nonce(&self, sender: &ExternalAccountAddress) -> Option<u64> {
+                // Only the second sender's committed nonce is known
+                (*sender == mk_addr(2)).then_some(1)
+            }
+        }
+        let inspector = MempoolInspector { pool: Arc::new(GappedPool), ..m.inspector() };
+        let account = inspector.account(&mk_addr(1)).unwrap();
+        assert_eq!(account.bucket, 1);
+        assert_eq!(account.committed_nonce, None);
+        assert_eq!(account.nonce_gap, Some(5));
+        assert_eq!(account.txns.iter().map(|t| t.txn.nonce).collect::<Vec<_>>(), vec![3, 4, 7]);
+
+        let account = inspector.account(&mk_addr(2)).unwrap();
+        assert_eq!(account.committed_nonce, Some(1));
+        assert_eq!(account.nonce_gap, Some(1));
+    }
+
+    #[test]
+    fn nonce_gap_counts_from_the_committed_nonce() {
+        let pooled = |nonces: &[u64]| -> Vec<PooledTxn> {
+            nonces
+                .iter()
+                .map(|&nonce| PooledTxn {
+                    hash: TxnHash::from_bytes(&[nonce as u8; 32]),
+                    nonce,
+                    parked: false,
+                    age: Duration::ZERO,
+                })
+                .collect()
+        };
+        assert_eq!(nonce_gap(&pooled(&[3, 4, 7]), None), Some(5));
+        assert_eq!(nonce_gap(&pooled(&[3, 4]), None), None);
+        assert_eq!(nonce_gap(&pooled(&[3, 4]), Some(3)), None);
+        assert_eq!(nonce_gap(&pooled(&[3, 4]), Some(2)), Some(2));
+        // Transactions below the committed nonce are about to be removed
+        assert_eq!(nonce_gap(&pooled(&[2, 3, 5]), Some(3)), Some(4));
+        assert_eq!(nonce_gap(&pooled(&[]), Some(3)), None);
+    }
+
     // A TxPool that hands back a fixed set of txns, honoring the `limit` argument
     // (like the real reth pool) so get_batch_inner's own capping can be exercised.
     fn batch_mempool(txns: Vec<ApiVerifiedTxn>) -> Mempool {
@@ -757,7 +1457,7 @@ mod tests {
             fn remove_txns(&self, _t: Vec<ApiVerifiedTxn>) {}
         }
         Mempool {
-            pool: Box::new(BatchPool(txns)),
+            pool: Arc::new(BatchPool(txns)),
             txn_cache: Arc::new(Mutex::new(TxnCache::new(100_000, Duration::from_secs(60)))),
             snapshot: Arc::new(Mutex::new(Snapshot {
                 shards: HashMap::new(),
@@ -766,6 +1466,8 @@ mod tests {
                 initialized: false,
             })),
             topology: Arc::new(Mutex::new(ObservedTopology::new(Duration::from_secs(10)))),
+            seen_txns: Arc::new(Mutex::new(SeenTxns::new(100_000, Duration::from_secs(60)))),
+            chain_id: None,
             num_sender_buckets: 1,
         }
     }
@@ -4,12 +4,16 @@
 
 // mod index;
 mod mempool;
+pub mod rate_limit;
 pub mod transaction;
 // mod transaction_store;
 
 pub use self::{
-    mempool::Mempool as CoreMempool,
-    transaction::TimelineState,
+    mempool::{
+        AccountMempoolState, AccountTxnState, EpochChangeHook, Mempool as CoreMempool,
+        MempoolInspector, MempoolStats, SenderBucketStats,
+    },
+    transaction::{batch_formation_policy, BatchFormationPolicy, TimelineState},
     // transaction_store::TXN_INDEX_ESTIMATED_BYTES,
 };
 #[cfg(test)]
@@ -0,0 +1,221 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-sender rate limiting of transactions entering the pool. The sender a gossiped
+//! transaction claims is not checked by the core mempool, so the limiter is meant to be charged
+//! with the signer recovered from the transaction, by the pool that recovers it.
+
+use gaptos::{
+    aptos_metrics_core::{register_int_counter_vec, IntCounterVec},
+    aptos_types::account_address::AccountAddress,
+};
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use runtime_config::{Knob, RuntimeConfigRegistry};
+use std::{sync::Arc, time::Instant};
+
+/// Upper bound on tracked senders; the least recently seen one is forgotten when it is reached.
+const MAX_TRACKED_SENDERS: usize = 100_000;
+
+static RATE_LIMITED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_mempool_rate_limited_txns_total",
+        "Transactions rejected because their sender exceeded a mempool rate limit",
+        &["limit"]
+    )
+    .unwrap()
+});
+
+/// Sustained rates one sender may add at. Each unset limit is not enforced. A transaction
+/// larger than the bytes limit is never admitted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SenderRateLimits {
+    pub txns_per_sec: Option<f64>,
+    pub bytes_per_sec: Option<f64>,
+}
+
+/// The `mempool_sender_max_txns_per_sec` and `mempool_sender_max_bytes_per_sec` knobs,
+/// configured via MEMPOOL_SENDER_MAX_TXNS_PER_SEC and MEMPOOL_SENDER_MAX_BYTES_PER_SEC and
+/// changeable at runtime. Zero, the default, disables a limit.
+struct SenderRateLimitKnobs {
+    txns_per_sec: Arc<Knob>,
+    bytes_per_sec: Arc<Knob>,
+}
+
+impl SenderRateLimitKnobs {
+    fn register() -> Self {
+        let registry = RuntimeConfigRegistry::global();
+        Self {
+            txns_per_sec: registry.register_fraction(
+                "mempool_sender_max_txns_per_sec",
+                "Transactions one sender may add to the mempool per second, 0 disables the limit",
+                "MEMPOOL_SENDER_MAX_TXNS_PER_SEC",
+                0.0,
+                1_000_000.0,
+            ),
+            bytes_per_sec: registry.register_fraction(
+                "mempool_sender_max_bytes_per_sec",
+                "Bytes one sender may add to the mempool per second, 0 disables the limit",
+                "MEMPOOL_SENDER_MAX_BYTES_PER_SEC",
+                0.0,
+                (1u64 << 30) as f64,
+            ),
+        }
+    }
+
+    fn limits(&self) -> SenderRateLimits {
+        let rate = |knob: &Knob| Some(knob.get_f64()).filter(|rate| *rate > 0.0);
+        SenderRateLimits {
+            txns_per_sec: rate(&self.txns_per_sec),
+            bytes_per_sec: rate(&self.bytes_per_sec),
+        }
+    }
+}
+
+/// Token buckets holding up to one second of allowance, and at least one transaction so that
+/// rates below one per second admit one every so often.
+struct SenderBudget {
+    txns: f64,
+    bytes: f64,
+    refilled_at: Instant,
+}
+
+fn txns_burst(txns_per_sec: f64) -> f64 {
+    txns_per_sec.max(1.0)
+}
+
+pub struct SenderRateLimiter {
+    limits: SenderRateLimits,
+    knobs: Option<SenderRateLimitKnobs>,
+    budgets: LruCache<AccountAddress, SenderBudget>,
+}
+
+impl SenderRateLimiter {
+    pub fn new(limits: SenderRateLimits) -> Self {
+        Self::with_capacity(limits, MAX_TRACKED_SENDERS)
+    }
+
+    fn with_capacity(limits: SenderRateLimits, capacity: usize) -> Self {
+        Self { limits, knobs: None, budgets: LruCache::new(capacity) }
+    }
+
+    /// A limiter following the runtime config knobs, so its limits can change while it runs.
+    pub fn from_knobs() -> Self {
+        let knobs = SenderRateLimitKnobs::register();
+        Self { knobs: Some(knobs), ..Self::new(SenderRateLimits::default()) }
+    }
+
+    /// Charges one transaction of `bytes` to `sender`. Returns false, charging nothing, if
+    /// that would exceed one of the sender's limits.
+    pub fn try_acquire(&mut self, sender: AccountAddress, bytes: usize, now: Instant) -> bool {
+        if let Some(knobs) = &self.knobs {
+            self.limits = knobs.limits();
+        }
+        let limits = self.limits;
+        if limits == SenderRateLimits::default() {
+            return true;
+        }
+        if !self.budgets.contains(&sender) {
+            // Evicts the least recently seen sender at capacity. One idle for a second is back to
+            // a full budget, so forgetting it changes nothing.
+            self.budgets.put(
+                sender,
+                SenderBudget {
+                    txns: limits.txns_per_sec.map_or(0.0, txns_burst),
+                    bytes: limits.bytes_per_sec.unwrap_or_default(),
+                    refilled_at: now,
+                },
+            );
+        }
+        let budget = self.budgets.get_mut(&sender).expect("the sender was just tracked");
+        let elapsed = now.duration_since(budget.refilled_at).as_secs_f64();
+        budget.refilled_at = now;
+        if let Some(rate) = limits.txns_per_sec {
+            budget.txns = (budget.txns + elapsed * rate).min(txns_burst(rate));
+        }
+        if let Some(rate) = limits.bytes_per_sec {
+            budget.bytes = (budget.bytes + elapsed * rate).min(rate);
+        }
+        if limits.txns_per_sec.is_some() && budget.txns < 1.0 {
+            RATE_LIMITED_TXNS.with_label_values(&["txns"]).inc();
+            return false;
+        }
+        if limits.bytes_per_sec.is_some() && budget.bytes < bytes as f64 {
+            RATE_LIMITED_TXNS.with_label_values(&["bytes"]).inc();
+            return false;
+        }
+        budget.txns -= 1.0;
+        budget.bytes -= bytes as f64;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn limits_each_sender_separately() {
+        let mut limiter = SenderRateLimiter::new(SenderRateLimits {
+            txns_per_sec: Some(2.0),
+            bytes_per_sec: None,
+        });
+        let (alice, bob) = (AccountAddress::random(), AccountAddress::random());
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(alice, 100, now));
+        assert!(limiter.try_acquire(alice, 100, now));
+        assert!(!limiter.try_acquire(alice, 100, now));
+        assert!(limiter.try_acquire(bob, 100, now));
+        // Half a second refills one transaction
+        assert!(limiter.try_acquire(alice, 100, now + Duration::from_millis(500)));
+        assert!(!limiter.try_acquire(alice, 100, now + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn limits_bytes() {
+        let mut limiter = SenderRateLimiter::new(SenderRateLimits {
+            txns_per_sec: None,
+            bytes_per_sec: Some(1_000.0),
+        });
+        let sender = AccountAddress::random();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(sender, 600, now));
+        assert!(!limiter.try_acquire(sender, 600, now));
+        assert!(limiter.try_acquire(sender, 400, now));
+        assert!(limiter.try_acquire(sender, 600, now + Duration::from_millis(600)));
+    }
+
+    #[test]
+    fn fractional_rates_admit_one_txn_at_a_time() {
+        let mut limiter = SenderRateLimiter::new(SenderRateLimits {
+            txns_per_sec: Some(0.5),
+            bytes_per_sec: None,
+        });
+        let sender = AccountAddress::random();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(sender, 100, now));
+        assert!(!limiter.try_acquire(sender, 100, now + Duration::from_secs(1)));
+        assert!(limiter.try_acquire(sender, 100, now + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn forgets_the_least_recently_seen_sender() {
+        let limits = SenderRateLimits { txns_per_sec: Some(1.0), bytes_per_sec: None };
+        let mut limiter = SenderRateLimiter::with_capacity(limits, 2);
+        let (alice, bob, carol) =
+            (AccountAddress::random(), AccountAddress::random(), AccountAddress::random());
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire(alice, 100, now));
+        assert!(limiter.try_acquire(bob, 100, now));
+        // Alice is seen again, so Bob is the one forgotten to make room for Carol
+        assert!(!limiter.try_acquire(alice, 100, now));
+        assert!(limiter.try_acquire(carol, 100, now));
+        assert!(!limiter.try_acquire(alice, 100, now));
+        assert!(limiter.try_acquire(bob, 100, now));
+    }
+}
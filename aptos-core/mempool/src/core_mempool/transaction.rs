@@ -56,24 +56,33 @@ impl From<&SignedTransaction> for VerifiedTxn {
 
 impl From<VerifiedTxn> for SignedTransaction {
     fn from(val: VerifiedTxn) -> Self {
-        let raw_txn = RawTransaction::new(
-            val.sender,
-            val.sequence_number,
-            TransactionPayload::GTxnBytes(val.bytes.clone()),
-            u64::MAX,
-            0,
-            u64::MAX,
-            val.chain_id,
-        );
-        SignedTransaction::new_with_committed_hash(
-            raw_txn,
-            GLOBAL_PUBLIC_KEY.clone(),
-            GLOBAL_SIGNATURE.clone(),
-            val.committed_hash,
-        )
+        val.into_signed_transaction(0)
     }
 }
 
+/// How the quorum store batch generator orders pulled transactions into batches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchFormationPolicy {
+    /// Keep the pool order and split it into the configured gas buckets.
+    GasBuckets,
+    /// Order by gas unit price, highest first, without letting a sender's transaction overtake
+    /// one of its lower sequence numbers. Prices are taken from the pool, so batches reflect
+    /// what each transaction actually pays.
+    FeePriority,
+}
+
+/// Configured via QUORUM_STORE_BATCH_FORMATION_POLICY (`gas_buckets` or `fee_priority`).
+pub fn batch_formation_policy() -> BatchFormationPolicy {
+    static POLICY: std::sync::OnceLock<BatchFormationPolicy> = std::sync::OnceLock::new();
+    *POLICY.get_or_init(|| {
+        match std::env::var("QUORUM_STORE_BATCH_FORMATION_POLICY").as_deref() {
+            Ok("fee_priority") => BatchFormationPolicy::FeePriority,
+            // Default gas_buckets
+            _ => BatchFormationPolicy::GasBuckets,
+        }
+    })
+}
+
 impl VerifiedTxn {
     pub fn new(
         bytes: Vec<u8>,
@@ -85,6 +94,24 @@ impl VerifiedTxn {
         Self { bytes, sender, sequence_number, chain_id, committed_hash }
     }
 
+    pub fn into_signed_transaction(self, gas_unit_price: u64) -> SignedTransaction {
+        let raw_txn = RawTransaction::new(
+            self.sender,
+            self.sequence_number,
+            TransactionPayload::GTxnBytes(self.bytes),
+            u64::MAX,
+            gas_unit_price,
+            u64::MAX,
+            self.chain_id,
+        );
+        SignedTransaction::new_with_committed_hash(
+            raw_txn,
+            GLOBAL_PUBLIC_KEY.clone(),
+            GLOBAL_SIGNATURE.clone(),
+            self.committed_hash,
+        )
+    }
+
     pub fn bytes(&self) -> &Vec<u8> {
         &self.bytes
     }
@@ -1,6 +1,7 @@
+use crate::report::ReportFormat;
 use api::GravityNodeArgs;
 use clap::Parser;
-use std::ffi::OsString;
+use std::{ffi::OsString, path::PathBuf};
 
 /// This is the entrypoint to the executable.
 #[derive(Debug, Parser)]
@@ -17,6 +18,31 @@ pub(crate) struct Cli {
 
     #[arg(long)]
     pub port: Option<u16>,
+
+    /// Transactions per second the leader submits while fewer than `max_inflight` are waiting
+    /// for their commit.
+    #[arg(long, default_value_t = 1000)]
+    pub target_tps: u64,
+
+    /// Seconds of load before latencies are measured, so the pipeline is filled.
+    #[arg(long, default_value_t = 30)]
+    pub warmup_secs: u64,
+
+    /// Seconds of steady-state load that are measured.
+    #[arg(long, default_value_t = 120)]
+    pub duration_secs: u64,
+
+    /// Most submitted transactions waiting for their commit, 10 seconds of the target rate if
+    /// not set.
+    #[arg(long)]
+    pub max_inflight: Option<u64>,
+
+    /// Where the leader writes the report, the log if not set.
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+    pub report_format: ReportFormat,
 }
 
 impl Cli {
@@ -0,0 +1,96 @@
+//! A no-op execution layer. It returns a result for every ordered block right away and marks
+//! committed blocks persisted, so the bench measures consensus alone.
+
+use crate::load::{txn_key, LatencyTracker};
+use block_buffer_manager::{BlockBufferManager, EpochChangeInProgress};
+use gaptos::aptos_crypto::HashValue;
+use log::warn;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Starts the execute and commit loops on the block buffer manager of the node.
+pub(crate) fn spawn_executor(bbm: Arc<BlockBufferManager>, tracker: Arc<LatencyTracker>) {
+    tokio::spawn(execute_loop(bbm.clone(), tracker.clone()));
+    tokio::spawn(commit_loop(bbm, tracker));
+}
+
+async fn execute_loop(bbm: Arc<BlockBufferManager>, tracker: Arc<LatencyTracker>) {
+    let mut next = bbm.latest_commit_block_number().await + 1;
+    let mut epoch = bbm.get_current_epoch().await;
+    loop {
+        let blocks = match bbm.get_ordered_blocks(next, None, epoch).await {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                if e.is::<EpochChangeInProgress>() || epoch != bbm.get_current_epoch().await {
+                    let (new_epoch, epoch_change_block_number) = bbm.consume_epoch_change().await;
+                    next = epoch_change_block_number + 1;
+                    epoch = new_epoch;
+                } else {
+                    warn!("failed to get ordered blocks from {}: {}", next, e);
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                }
+                continue;
+            }
+        };
+        for (block, _parent_id, _execution_meta) in blocks {
+            let meta = &block.block_meta;
+            // Every node has to compute the same hash for the commit votes to match
+            let hash = *HashValue::sha3_256_of(meta.block_id.as_bytes());
+            let txns = block.txns.iter().map(txn_key).collect();
+            tracker.executed(meta.block_number, meta.epoch, txns);
+            if let Err(e) = bbm
+                .set_compute_res(
+                    meta.block_id,
+                    hash,
+                    meta.block_number,
+                    meta.epoch,
+                    Arc::new(None),
+                    vec![],
+                )
+                .await
+            {
+                warn!("failed to set the result of block {}: {}", meta.block_number, e);
+                break;
+            }
+            next = meta.block_number + 1;
+        }
+    }
+}
+
+async fn commit_loop(bbm: Arc<BlockBufferManager>, tracker: Arc<LatencyTracker>) {
+    let mut next = bbm.latest_commit_block_number().await + 1;
+    loop {
+        // Blocks are committed in the epoch they were executed in
+        let Some(epoch) = tracker.executed_epoch(next) else {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            continue;
+        };
+        let blocks = match bbm.get_committed_blocks(next, None, epoch).await {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                warn!("failed to get committed blocks from {}: {}", next, e);
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        let Some(last) = blocks.last().map(|block| block.num) else { continue };
+        let now = Instant::now();
+        for block in &blocks {
+            tracker.committed(block.num, now);
+        }
+        next = last + 1;
+        if let Err(e) = bbm.set_state(last, last).await {
+            warn!("failed to set state at block {}: {}", last, e);
+        }
+        // Nothing is stored, so a block is persisted as soon as it is committed
+        for block in blocks {
+            if let Some(persist_notifier) = block.persist_notifier {
+                let _ = persist_notifier.send(()).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Closed-loop load. The generator submits transactions at the target rate, but only while
+//! fewer than the in-flight limit are waiting for their commit, so an overloaded chain slows the
+//! generator down instead of queueing an ever growing backlog. The [`LatencyTracker`] follows
+//! every transaction from its submission to the commit of its block.
+//!
+//! Latencies are not taken from the journeys of `TxnLifeTime`. Journeys only exist for
+//! transactions recorded when they are added to the Aptos mempool, which the bench pool
+//! bypasses. The tracker takes the submission and commit times itself.
+
+use crate::{should_produce_txn, txn::RawTxn};
+use block_buffer_manager::TxPool;
+use gaptos::api_types::{account::ExternalAccountAddress, u256_define::TxnHash, VerifiedTxn};
+use log::info;
+use rand::Rng;
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type TxFilterFn = Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>;
+
+/// Sender and sequence number of a transaction.
+pub(crate) type TxnKey = ([u8; 32], u64);
+
+/// How often the generator submits the transactions that became due.
+const GENERATE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Time after its submission a transaction that did not commit expires and stops counting as
+/// in flight, so transactions lost on the way do not stall the closed loop.
+const IN_FLIGHT_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub(crate) fn txn_key(txn: &VerifiedTxn) -> TxnKey {
+    (txn.sender().bytes(), txn.seq_number())
+}
+
+/// Latency of one measured transaction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Sample {
+    pub committed_at: Instant,
+    pub latency: Duration,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    submitted: HashMap<TxnKey, Instant>,
+    /// Executed but not yet committed blocks with their epoch and transactions.
+    executed: BTreeMap<u64, (u64, Vec<TxnKey>)>,
+    measured_submissions: u64,
+    /// Measured transactions that expired before their commit.
+    measured_expired: u64,
+    samples: Vec<Sample>,
+}
+
+/// What the tracker measured after the warm-up.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Measurements {
+    pub submitted: u64,
+    pub expired: u64,
+    pub samples: Vec<Sample>,
+}
+
+/// Submission and commit times of the transactions of the bench. Only transactions submitted
+/// after the warm-up are measured.
+pub(crate) struct LatencyTracker {
+    measure_from: Instant,
+    state: Mutex<TrackerState>,
+}
+
+impl LatencyTracker {
+    pub(crate) fn new(measure_from: Instant) -> Self {
+        Self { measure_from, state: Mutex::new(TrackerState::default()) }
+    }
+
+    pub(crate) fn measure_from(&self) -> Instant {
+        self.measure_from
+    }
+
+    /// Transactions submitted but not committed yet.
+    pub(crate) fn in_flight(&self) -> u64 {
+        self.state.lock().unwrap().submitted.len() as u64
+    }
+
+    fn submitted(&self, key: TxnKey, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if now >= self.measure_from {
+            state.measured_submissions += 1;
+        }
+        state.submitted.insert(key, now);
+    }
+
+    /// Drops the transactions submitted more than [`IN_FLIGHT_TIMEOUT`] before `now`.
+    fn expire(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let mut expired = 0;
+        state.submitted.retain(|_, submitted_at| {
+            let live = now.saturating_duration_since(*submitted_at) < IN_FLIGHT_TIMEOUT;
+            if !live && *submitted_at >= self.measure_from {
+                expired += 1;
+            }
+            live
+        });
+        state.measured_expired += expired;
+    }
+
+    pub(crate) fn executed(&self, block_number: u64, epoch: u64, txns: Vec<TxnKey>) {
+        self.state.lock().unwrap().executed.insert(block_number, (epoch, txns));
+    }
+
+    /// Epoch the block was executed in, `None` if it is not executed yet.
+    pub(crate) fn executed_epoch(&self, block_number: u64) -> Option<u64> {
+        self.state.lock().unwrap().executed.get(&block_number).map(|(epoch, _)| *epoch)
+    }
+
+    pub(crate) fn committed(&self, block_number: u64, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let Some((_, txns)) = state.executed.remove(&block_number) else { return };
+        for key in txns {
+            // Transactions of other nodes are not tracked
+            let Some(submitted_at) = state.submitted.remove(&key) else { continue };
+            if submitted_at >= self.measure_from {
+                state.samples.push(Sample { committed_at: now, latency: now - submitted_at });
+            }
+        }
+    }
+
+    pub(crate) fn measurements(&self) -> Measurements {
+        let state = self.state.lock().unwrap();
+        Measurements {
+            submitted: state.measured_submissions,
+            expired: state.measured_expired,
+            samples: state.samples.clone(),
+        }
+    }
+}
+
+/// Pool consensus pulls the generated transactions from. A transaction leaves the pool once it
+/// is handed out for a batch.
+#[derive(Clone, Default)]
+pub(crate) struct BenchPool {
+    txns: Arc<Mutex<VecDeque<VerifiedTxn>>>,
+}
+
+impl TxPool for BenchPool {
+    fn best_txns(
+        &self,
+        filter: Option<TxFilterFn>,
+        limit: usize,
+        _max_bytes: u64,
+    ) -> Box<dyn Iterator<Item = VerifiedTxn>> {
+        let mut txns = self.txns.lock().unwrap();
+        let mut best = Vec::with_capacity(limit.min(txns.len()));
+        while best.len() < limit {
+            let Some(txn) = txns.pop_front() else { break };
+            // Rejected transactions are already in a batch
+            let included = filter.as_ref().is_none_or(|filter| {
+                let hash = TxnHash::from_bytes(txn.committed_hash().as_slice());
+                filter((txn.sender().clone(), txn.seq_number(), hash))
+            });
+            if included {
+                best.push(txn);
+            }
+        }
+        Box::new(best.into_iter())
+    }
+
+    fn get_broadcast_txns(
+        &self,
+        _filter: Option<TxFilterFn>,
+    ) -> Box<dyn Iterator<Item = VerifiedTxn>> {
+        Box::new(vec![].into_iter())
+    }
+
+    fn add_external_txn(&self, _txn: VerifiedTxn) -> bool {
+        false
+    }
+
+    fn remove_txns(&self, _txns: Vec<VerifiedTxn>) {}
+}
+
+/// Submits transactions to `pool` at `target_tps` until `until`, keeping at most `max_inflight`
+/// of them waiting for their commit. Nothing is submitted while production is switched off.
+pub(crate) async fn run_load(
+    pool: BenchPool,
+    tracker: Arc<LatencyTracker>,
+    target_tps: u64,
+    max_inflight: u64,
+    until: Instant,
+) {
+    info!("start load at {} tps with at most {} transactions in flight", target_tps, max_inflight);
+    let mut interval = tokio::time::interval(GENERATE_INTERVAL);
+    let mut last_tick = Instant::now();
+    let mut due = 0f64;
+    let mut generated = 0u64;
+    while Instant::now() < until {
+        interval.tick().await;
+        let now = Instant::now();
+        let elapsed = now - last_tick;
+        last_tick = now;
+        if !should_produce_txn().await {
+            due = 0.0;
+            continue;
+        }
+        due += target_tps as f64 * elapsed.as_secs_f64();
+        tracker.expire(now);
+        let room = max_inflight.saturating_sub(tracker.in_flight());
+        let count = (due as u64).min(room);
+        // A backlog that did not fit is dropped, the closed loop runs below the target then
+        due = if count < due as u64 { due.fract() } else { due - count as f64 };
+
+        let txns: Vec<VerifiedTxn> = (0..count)
+            .map(|_| {
+                generated += 1;
+                let raw_txn = RawTxn {
+                    account: generate_random_address(),
+                    sequence_number: 1,
+                    key: format!("bench_key_{}", generated),
+                    val: format!("bench_value_{}", generated),
+                };
+                let txn = raw_txn.into_verified();
+                tracker.submitted(txn_key(&txn), now);
+                txn
+            })
+            .collect();
+        pool.txns.lock().unwrap().extend(txns);
+    }
+    info!("load finished after {} transactions", generated);
+}
+
+fn generate_random_address() -> ExternalAccountAddress {
+    let mut rng = rand::thread_rng();
+    let random_bytes: [u8; 32] = rng.gen();
+    ExternalAccountAddress::new(random_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_submissions_expire() {
+        let start = Instant::now();
+        let tracker = LatencyTracker::new(start + Duration::from_secs(1));
+        tracker.submitted(([1; 32], 1), start);
+        tracker.submitted(([2; 32], 1), start + Duration::from_secs(1));
+        tracker.submitted(([3; 32], 1), start + Duration::from_secs(2));
+
+        tracker.expire(start + IN_FLIGHT_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(tracker.in_flight(), 1);
+        let measurements = tracker.measurements();
+        // The first one was submitted during the warm-up
+        assert_eq!((measurements.submitted, measurements.expired), (2, 1));
+
+        // An expired transaction that commits after all is not sampled
+        tracker.executed(1, 1, vec![([2; 32], 1), ([3; 32], 1)]);
+        tracker.committed(1, start + IN_FLIGHT_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(tracker.in_flight(), 0);
+        assert_eq!(tracker.measurements().samples.len(), 1);
+    }
+}
@@ -1,75 +1,118 @@
 mod cli;
+mod executor;
 mod kv;
+mod load;
+mod report;
 mod stateful_mempool;
 mod txn;
 
-use std::{sync::Arc, thread};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use api::{
     check_bootstrap_config,
     consensus_api::{ConsensusEngine, ConsensusEngineArgs},
     NodeConfig,
 };
-use block_buffer_manager::block_buffer_manager::EmptyTxPool;
+use block_buffer_manager::BlockBufferManager;
 use clap::Parser;
 use cli::Cli;
+use executor::spawn_executor;
 use flexi_logger::{FileSpec, Logger, WriteMode};
-use gaptos::api_types::{account::ExternalAccountAddress, ExecTxn};
 use kv::KvStore;
-use log::info;
+use load::{run_load, BenchPool, LatencyTracker};
+use log::{error, info};
 use once_cell::sync::OnceCell;
-use rand::Rng;
+use report::{BenchReport, ReportFormat};
 use tokio::sync::RwLock;
-use txn::RawTxn;
 use warp::Filter;
 
+/// Load and report settings of the bench, see [`Cli`].
+struct BenchConfig {
+    target_tps: u64,
+    warmup: Duration,
+    duration: Duration,
+    max_inflight: u64,
+    report: Option<PathBuf>,
+    report_format: ReportFormat,
+}
+
+impl BenchConfig {
+    fn from_cli(cli: &Cli) -> Self {
+        Self {
+            target_tps: cli.target_tps,
+            warmup: Duration::from_secs(cli.warmup_secs),
+            duration: Duration::from_secs(cli.duration_secs),
+            max_inflight: cli.max_inflight.unwrap_or(cli.target_tps * 10),
+            report: cli.report.clone(),
+            report_format: cli.report_format,
+        }
+    }
+}
+
 struct TestConsensusLayer {
-    consensus_engine: Arc<ConsensusEngine>,
+    _consensus_engine: Arc<ConsensusEngine>,
+    block_buffer_manager: Arc<BlockBufferManager>,
+    pool: BenchPool,
 }
 
 impl TestConsensusLayer {
     async fn new(node_config: NodeConfig) -> Self {
+        let block_buffer_manager = BlockBufferManager::new(Default::default());
+        let pool = BenchPool::default();
         Self {
-            consensus_engine: ConsensusEngine::init(
+            _consensus_engine: ConsensusEngine::init(
                 ConsensusEngineArgs {
                     node_config,
-                    chain_id: 1337,
+                    chain_id: txn::BENCH_CHAIN_ID,
                     latest_block_number: 0,
                     config_storage: None,
+                    config_reads: None,
+                    block_buffer_manager: block_buffer_manager.clone(),
+                    trusted_checkpoint: None,
+                    genesis_bundle: None,
+                    recovery_api: None,
                 },
-                EmptyTxPool::boxed(),
+                Box::new(pool.clone()),
             )
             .await,
+            block_buffer_manager,
+            pool,
         }
     }
 
-    async fn random_txns(num: u64) -> Vec<ExecTxn> {
-        let mut txns = Vec::with_capacity(num as usize);
-        for i in 0..num {
-            let key = format!("random_key_{}", i);
-            let val = format!("random_value_{}", i);
-            let raw_txn =
-                RawTxn { account: generate_random_address(), sequence_number: 1, key, val };
-            let exec_txn = ExecTxn::RawTxn(raw_txn.to_bytes());
-            txns.push(exec_txn);
+    /// Executes blocks on every node. The leader also runs the load through the warm-up and the
+    /// steady state, then reports and exits.
+    async fn run(self, config: BenchConfig) {
+        let tracker = Arc::new(LatencyTracker::new(Instant::now() + config.warmup));
+        spawn_executor(self.block_buffer_manager.clone(), tracker.clone());
+        if !*IS_LEADER.get().expect("No is leader set") {
+            return std::future::pending().await;
         }
-        txns
-    }
 
-    async fn run(self) {
-        loop {
-            if should_produce_txn().await {
-                info!("start produce new txn");
-                let txn_num_in_block =
-                    std::env::var("BLOCK_TXN_NUMS").map(|s| s.parse().unwrap()).unwrap_or(1000);
-                TestConsensusLayer::random_txns(txn_num_in_block).await.into_iter().for_each(
-                    |txn| {
-                        tokio::spawn(async move { todo!() });
-                    },
-                );
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await
+        let until = tracker.measure_from() + config.duration;
+        run_load(self.pool.clone(), tracker.clone(), config.target_tps, config.max_inflight, until)
+            .await;
+        let report = BenchReport::new(
+            config.target_tps,
+            config.warmup,
+            config.duration,
+            &tracker.measurements(),
+            tracker.measure_from(),
+        );
+        match &config.report {
+            Some(path) => match report.write(path, config.report_format) {
+                Ok(()) => info!("bench report written to {}", path.display()),
+                Err(e) => error!("failed to write bench report to {}: {}", path.display(), e),
+            },
+            None => info!("bench report:\n{}", report.render(config.report_format)),
         }
+        log::logger().flush();
+        std::process::exit(0);
     }
 }
 
@@ -100,16 +143,10 @@ pub async fn should_produce_txn() -> bool {
     *IS_LEADER.get().expect("No is leader set") && get_produce_txn().await
 }
 
-fn generate_random_address() -> ExternalAccountAddress {
-    let mut rng = rand::thread_rng();
-    let random_bytes: [u8; 32] = rng.gen();
-    ExternalAccountAddress::new(random_bytes)
-}
-
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
-    let gcei_config = check_bootstrap_config(cli.gravity_node_config.node_config_path.clone());
+    let (gcei_config, _) = check_bootstrap_config(cli.gravity_node_config.node_config_path.clone());
     Logger::try_with_str("info")
         .unwrap()
         .log_to_file(FileSpec::default().directory(cli.log_dir.clone()))
@@ -123,6 +160,7 @@ async fn main() {
     IS_LEADER.set(cli.leader).expect("Failed to set is leader");
     PRODUCE_TXN.set(RwLock::new(true)).expect("Failed to initialize PRODUCE_TXN");
     let port = cli.port.clone();
+    let bench_config = BenchConfig::from_cli(&cli);
 
     cli.run(move || {
         tokio::spawn(async move {
@@ -131,7 +169,7 @@ async fn main() {
             let _ = thread::spawn(move || {
                 tokio::runtime::Runtime::new().unwrap().block_on(async move {
                     let cl = TestConsensusLayer::new(gcei_config).await;
-                    cl.run().await
+                    cl.run(bench_config).await
                 });
             });
 
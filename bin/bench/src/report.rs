@@ -0,0 +1,165 @@
+//! Result of a bench run: commit latency percentiles and the achieved throughput of the steady
+//! state, written as JSON or as a CSV header and row.
+
+use crate::load::Measurements;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ReportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct BenchReport {
+    pub target_tps: u64,
+    pub warmup_secs: u64,
+    pub duration_secs: u64,
+    /// Transactions submitted in the steady state.
+    pub submitted: u64,
+    /// Transactions submitted in the steady state and committed before it ended.
+    pub committed: u64,
+    /// Transactions submitted in the steady state that did not commit in time and stopped
+    /// counting as in flight.
+    pub expired: u64,
+    /// Commits per second in the steady state, of transactions submitted in it.
+    pub achieved_tps: f64,
+    pub p50_latency_ms: f64,
+    pub p95_latency_ms: f64,
+    pub p99_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+impl BenchReport {
+    /// Summarizes the samples committed within `duration` after the warm-up ended.
+    pub(crate) fn new(
+        target_tps: u64,
+        warmup: Duration,
+        duration: Duration,
+        measurements: &Measurements,
+        measure_from: Instant,
+    ) -> Self {
+        let measure_until = measure_from + duration;
+        let mut latencies: Vec<Duration> = measurements
+            .samples
+            .iter()
+            .filter(|sample| sample.committed_at <= measure_until)
+            .map(|sample| sample.latency)
+            .collect();
+        latencies.sort_unstable();
+        Self {
+            target_tps,
+            warmup_secs: warmup.as_secs(),
+            duration_secs: duration.as_secs(),
+            submitted: measurements.submitted,
+            committed: latencies.len() as u64,
+            expired: measurements.expired,
+            achieved_tps: latencies.len() as f64 / duration.as_secs_f64().max(f64::EPSILON),
+            p50_latency_ms: percentile_ms(&latencies, 50.0),
+            p95_latency_ms: percentile_ms(&latencies, 95.0),
+            p99_latency_ms: percentile_ms(&latencies, 99.0),
+            max_latency_ms: latencies.last().map_or(0.0, |latency| latency.as_secs_f64() * 1e3),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "target_tps,warmup_secs,duration_secs,submitted,committed,expired,achieved_tps,\
+             p50_latency_ms,p95_latency_ms,p99_latency_ms,max_latency_ms\n\
+             {},{},{},{},{},{},{:.2},{:.3},{:.3},{:.3},{:.3}\n",
+            self.target_tps,
+            self.warmup_secs,
+            self.duration_secs,
+            self.submitted,
+            self.committed,
+            self.expired,
+            self.achieved_tps,
+            self.p50_latency_ms,
+            self.p95_latency_ms,
+            self.p99_latency_ms,
+            self.max_latency_ms
+        )
+    }
+
+    pub(crate) fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).expect("report serializes to json")
+            }
+            ReportFormat::Csv => self.to_csv(),
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path, format: ReportFormat) -> std::io::Result<()> {
+        std::fs::write(path, self.render(format))
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies in milliseconds, 0 without latencies.
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1].as_secs_f64() * 1e3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::Sample;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let latencies: Vec<Duration> = (1..=100).map(ms).collect();
+        assert_eq!(percentile_ms(&latencies, 50.0), 50.0);
+        assert_eq!(percentile_ms(&latencies, 99.0), 99.0);
+        assert_eq!(percentile_ms(&latencies, 100.0), 100.0);
+        assert_eq!(percentile_ms(&[ms(7)], 50.0), 7.0);
+        assert_eq!(percentile_ms(&[ms(7)], 0.0), 7.0);
+        assert_eq!(percentile_ms(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn report_covers_the_commits_of_the_steady_state() {
+        let measure_from = Instant::now();
+        let sample = |committed_after: u64, latency: u64| Sample {
+            committed_at: measure_from + Duration::from_secs(committed_after),
+            latency: ms(latency),
+        };
+        let measurements = Measurements {
+            submitted: 5,
+            expired: 1,
+            // The last one committed after the steady state ended
+            samples: vec![sample(1, 30), sample(2, 10), sample(9, 20), sample(11, 500)],
+        };
+        let report = BenchReport::new(
+            100,
+            Duration::from_secs(5),
+            Duration::from_secs(10),
+            &measurements,
+            measure_from,
+        );
+        assert_eq!((report.submitted, report.committed, report.expired), (5, 3, 1));
+        assert_eq!(report.achieved_tps, 0.3);
+        assert_eq!(report.p50_latency_ms, 20.0);
+        assert_eq!(report.max_latency_ms, 30.0);
+
+        let csv = report.render(ReportFormat::Csv);
+        let (header, row) = csv.trim_end().split_once('\n').unwrap();
+        assert_eq!(header.split(',').count(), row.split(',').count());
+        assert!(row.starts_with("100,5,10,5,3,1,0.30,"));
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["committed"], 3);
+    }
+}
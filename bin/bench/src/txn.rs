@@ -4,6 +4,9 @@ use gaptos::api_types::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Chain the bench nodes run, which their transactions are tagged with.
+pub const BENCH_CHAIN_ID: u64 = 1337;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct RawTxn {
     pub(crate) account: ExternalAccountAddress,
@@ -39,7 +42,12 @@ impl RawTxn {
 
     pub fn into_verified(self) -> VerifiedTxn {
         let bytes = self.to_bytes();
-        VerifiedTxn::new(bytes, self.account, self.sequence_number, ExternalChainId::new(0))
+        VerifiedTxn::new(
+            bytes,
+            self.account,
+            self.sequence_number,
+            ExternalChainId::new(BENCH_CHAIN_ID),
+        )
     }
 
     pub fn account(&self) -> ExternalAccountAddress {
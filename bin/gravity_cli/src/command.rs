@@ -1,7 +1,8 @@
 use crate::{
-    completions::CompletionsCommand, dkg::DKGCommand, doctor::DoctorCommand, epoch::EpochCommand,
-    genesis::GenesisCommand, init::InitCommand, node::NodeCommand, output::OutputFormat,
-    stake::StakeCommand, status::StatusCommand, unwind::UnwindCommand, validator::ValidatorCommand,
+    completions::CompletionsCommand, debug::DebugCommand, dkg::DKGCommand, doctor::DoctorCommand,
+    epoch::EpochCommand, genesis::GenesisCommand, init::InitCommand, node::NodeCommand,
+    output::OutputFormat, simulate::SimulateCommand, stake::StakeCommand, status::StatusCommand,
+    tx::TxCommand, unwind::UnwindCommand, validator::ValidatorCommand,
 };
 use build_info::{build_information, BUILD_PKG_VERSION};
 use clap::{Parser, Subcommand};
@@ -70,6 +71,12 @@ pub enum SubCommands {
     Init(InitCommand),
     /// Diagnose config, connectivity, and deployment issues
     Doctor(DoctorCommand),
+    /// Offline debugging tools
+    Debug(DebugCommand),
+    /// Transaction submission tools
+    Tx(TxCommand),
+    /// Offline capacity planning tools
+    Simulate(SimulateCommand),
 }
 
 pub trait Executable {
@@ -1,17 +1,8 @@
-use alloy_primitives::{address, Address};
 use std::fmt::{Debug, Formatter};
 
-/// ValidatorManagement contract address (from SystemAddresses.VALIDATOR_MANAGER)
-pub const VALIDATOR_MANAGER_ADDRESS: Address = address!("00000000000000000000000000000001625F2001");
-
-/// Staking contract address (from SystemAddresses.STAKING)
-pub const STAKING_ADDRESS: Address = address!("00000000000000000000000000000001625F2000");
-
-/// Reconfiguration contract address (from SystemAddresses.RECONFIGURATION)
-pub const RECONFIGURATION_ADDRESS: Address = address!("00000000000000000000000000000001625F2003");
-
-/// EpochConfig contract address (from SystemAddresses.EPOCH_CONFIG)
-pub const EPOCH_CONFIG_ADDRESS: Address = address!("00000000000000000000000000000001625F1005");
+pub use system_contracts::{
+    EPOCH_CONFIG_ADDRESS, RECONFIGURATION_ADDRESS, STAKING_ADDRESS, VALIDATOR_MANAGER_ADDRESS,
+};
 
 // Define contract interface using alloy_sol_macro
 alloy_sol_macro::sol! {
@@ -0,0 +1,107 @@
+use alloy_primitives::B256;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types::eth::BlockNumberOrTag;
+use anyhow::Result;
+use aptos_consensus::consensusdb::ConsensusDB;
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::command::Executable;
+
+/// Load an ordered block from consensusdb and compare the committed block hash with the one
+/// the execution layer holds for the same block number.
+///
+/// Nothing is re-executed. `RecoveryApi` only applies blocks through a running node's block
+/// buffer manager, which executes and then commits them on top of the node's head, so it cannot
+/// run an already committed block again from outside the node. The check compares the node's
+/// stored execution result with the hash the quorum signed instead. A mismatch means this node
+/// diverged from the quorum at that block.
+#[derive(Debug, Parser)]
+pub struct CheckBlockCommand {
+    /// Block number to check.
+    #[arg(long)]
+    pub block_number: u64,
+
+    /// Path to the consensus DB data directory.
+    /// This is typically `<deploy-path>/data/consensus_db`.
+    #[arg(long)]
+    pub datadir: PathBuf,
+
+    /// RPC URL of the node's execution layer. Without it only the consensus side is printed.
+    #[clap(long, env = "GRAVITY_RPC_URL")]
+    pub rpc_url: Option<String>,
+}
+
+impl Executable for CheckBlockCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl CheckBlockCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        if !self.datadir.exists() {
+            return Err(anyhow::anyhow!("Consensus DB path does not exist: {:?}", self.datadir));
+        }
+
+        // The node config path is not needed for read-only inspection.
+        let consensus_db = ConsensusDB::new(&self.datadir, &PathBuf::new());
+        let block = consensus_db
+            .get_block_by_number(self.block_number)
+            .map_err(|e| anyhow::anyhow!("Failed to read consensus DB: {e:?}"))?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Block {} not found in consensus DB", self.block_number)
+            })?;
+
+        println!("Ordered block {}:", self.block_number);
+        println!("  block id:     {}", block.id());
+        println!("  epoch/round:  {}/{}", block.epoch(), block.round());
+        println!("  timestamp:    {} us", block.timestamp_usecs());
+        println!("  payload txns: {}", block.payload().map_or(0, |payload| payload.len()));
+
+        let committed_hash = consensus_db
+            .get_committed_block_hash(self.block_number)
+            .map(|hash| B256::from_slice(hash.as_ref()));
+        match committed_hash {
+            Some(hash) => println!("  committed hash: {hash}"),
+            None => println!("  committed hash: <no ledger info stored for this block>"),
+        }
+
+        let Some(rpc_url) = self.rpc_url else {
+            println!("No --rpc-url given, skipping execution layer comparison.");
+            return Ok(());
+        };
+
+        let provider = ProviderBuilder::new().connect_http(rpc_url.parse()?);
+        let executed = provider
+            .get_block_by_number(BlockNumberOrTag::Number(self.block_number))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Execution layer has no block {}", self.block_number))?;
+
+        println!("Execution layer block {}:", self.block_number);
+        println!("  block hash:   {}", executed.header.hash);
+        println!("  state root:   {}", executed.header.state_root);
+        println!("  txns:         {}", executed.transactions.len());
+
+        match committed_hash {
+            Some(hash) if hash != executed.header.hash => Err(anyhow::anyhow!(
+                "Block {} diverged: committed hash {hash}, execution layer hash {}",
+                self.block_number,
+                executed.header.hash
+            )),
+            Some(_) => {
+                println!("Block {} matches the committed hash.", self.block_number);
+                Ok(())
+            }
+            None => {
+                println!(
+                    "Block {} has no committed hash to compare against; check the last block of \
+                     its commit instead.",
+                    self.block_number
+                );
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use clap::Parser;
+
+use crate::{command::Executable, output::OutputFormat};
+use serde::{Deserialize, Serialize};
+
+/// Fetch a quorum store batch by digest from the node's quorum store DB.
+///
+/// Prints the batch info and a summary of every stored transaction. The digest recomputed from
+/// the stored transactions is shown next to the requested one, so a batch whose content does
+/// not hash to its digest stands out.
+#[derive(Debug, Parser)]
+pub struct GetBatchCommand {
+    /// Server address and port (e.g., 127.0.0.1:1024)
+    #[clap(long, env = "GRAVITY_SERVER_URL")]
+    pub server_url: Option<String>,
+
+    /// Batch digest, hex encoded with or without `0x`
+    #[clap(long)]
+    pub digest: String,
+
+    /// Only look up the batch of this epoch instead of scanning every epoch
+    #[clap(long)]
+    pub epoch: Option<u64>,
+
+    /// Bearer token of the node's debug endpoints
+    #[clap(long, env = "GRAVITY_DEBUG_TOKEN")]
+    pub debug_token: Option<String>,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct BatchTxn {
+    sender: String,
+    sequence_number: u64,
+    expiration_timestamp_secs: u64,
+    hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct Batch {
+    epoch: u64,
+    digest: String,
+    author: String,
+    batch_id: String,
+    expiration: u64,
+    num_txns: u64,
+    num_bytes: u64,
+    gas_bucket_start: u64,
+    computed_digest: Option<String>,
+    txns: Vec<BatchTxn>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    error: String,
+}
+
+impl Executable for GetBatchCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl GetBatchCommand {
+    fn normalize_url(url: &str) -> String {
+        let url = url.trim_end_matches('/');
+        if url.starts_with("https://") || url.starts_with("http://") {
+            url.to_string()
+        } else {
+            format!("http://{url}")
+        }
+    }
+
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let server_url = self.server_url.ok_or_else(|| {
+            anyhow::anyhow!(
+                "--server-url is required. Set via CLI flag, GRAVITY_SERVER_URL env var, or ~/.gravity/config.toml"
+            )
+        })?;
+
+        let base_url = Self::normalize_url(&server_url);
+        let url = match self.epoch {
+            Some(epoch) => format!("{base_url}/debug/batch/{}/{epoch}", self.digest),
+            None => format!("{base_url}/debug/batch/{}", self.digest),
+        };
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+
+        let mut request = client.get(&url);
+        if let Some(token) = &self.debug_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+
+        let status_code = response.status();
+        if !status_code.is_success() {
+            let error_msg = match response.json::<ErrorResponse>().await {
+                Ok(error_response) => format!("HTTP {}: {}", status_code, error_response.error),
+                Err(_) => format!("HTTP {status_code}"),
+            };
+            return Err(anyhow::anyhow!("Failed to get batch: {error_msg}"));
+        }
+
+        let batches: Vec<Batch> = response.json().await?;
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&batches)?);
+            }
+            _ => {
+                for batch in &batches {
+                    println!("Batch {} in epoch {}", batch.digest, batch.epoch);
+                    println!("  Author:      {}", batch.author);
+                    println!("  Batch id:    {}", batch.batch_id);
+                    println!("  Expiration:  {}", batch.expiration);
+                    println!(
+                        "  Size:        {} txns, {} bytes, gas bucket {}",
+                        batch.num_txns, batch.num_bytes, batch.gas_bucket_start
+                    );
+                    match &batch.computed_digest {
+                        Some(computed) if *computed == batch.digest => {
+                            println!("  Content:     matches digest")
+                        }
+                        Some(computed) => println!("  Content:     MISMATCH, hashes to {computed}"),
+                        None => println!("  Content:     not stored, only the batch info is"),
+                    }
+                    for txn in &batch.txns {
+                        println!(
+                            "    {} seq {} expires {} hash {}",
+                            txn.sender,
+                            txn.sequence_number,
+                            txn.expiration_timestamp_secs,
+                            txn.hash
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
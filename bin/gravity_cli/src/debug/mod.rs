@@ -0,0 +1,33 @@
+use clap::Parser;
+
+use crate::command::Executable;
+
+pub mod check_block;
+pub mod get_batch;
+pub mod pool_diff;
+
+#[derive(Debug, Parser)]
+pub struct DebugCommand {
+    #[command(subcommand)]
+    pub command: SubCommands,
+}
+
+#[derive(Debug, Parser)]
+pub enum SubCommands {
+    /// Compare an ordered block's committed hash with the execution layer's stored block
+    CheckBlock(check_block::CheckBlockCommand),
+    /// Diff the transactions consensus pulled against reth's txpool
+    PoolDiff(pool_diff::PoolDiffCommand),
+    /// Fetch a quorum store batch by digest with a summary of its transactions
+    GetBatch(get_batch::GetBatchCommand),
+}
+
+impl Executable for DebugCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        match self.command {
+            SubCommands::CheckBlock(check_cmd) => check_cmd.execute(),
+            SubCommands::PoolDiff(pool_diff_cmd) => pool_diff_cmd.execute(),
+            SubCommands::GetBatch(get_batch_cmd) => get_batch_cmd.execute(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{command::Executable, output::OutputFormat};
+
+/// Compare the transactions consensus has pulled from the node's pool with reth's txpool.
+///
+/// Calls the node's `gravity_txpoolDiff` RPC. Transactions only on the consensus side were
+/// selected for a proposal and then dropped or replaced in reth; nonce conflicts mean the two
+/// sides hold different transactions for the same sender nonce.
+#[derive(Debug, Parser)]
+pub struct PoolDiffCommand {
+    /// RPC URL of the node's execution layer.
+    #[clap(long, env = "GRAVITY_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Maximum entries listed per category.
+    #[arg(long, default_value_t = 100)]
+    pub limit: usize,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolEntry {
+    hash: String,
+    sender: String,
+    nonce: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NonceConflict {
+    sender: String,
+    nonce: u64,
+    consensus_hash: String,
+    reth_hash: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PoolDiff {
+    consensus_count: usize,
+    reth_count: usize,
+    consensus_only_count: usize,
+    consensus_only: Vec<PoolEntry>,
+    reth_only_count: usize,
+    reth_only: Vec<PoolEntry>,
+    nonce_conflicts: Vec<NonceConflict>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<PoolDiff>,
+    error: Option<serde_json::Value>,
+}
+
+impl Executable for PoolDiffCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl PoolDiffCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let rpc_url = self.rpc_url.ok_or_else(|| {
+            anyhow::anyhow!(
+                "--rpc-url is required. Set via CLI flag, GRAVITY_RPC_URL env var, or ~/.gravity/config.toml"
+            )
+        })?;
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "gravity_txpoolDiff",
+            "params": [self.limit],
+        });
+        let response: RpcResponse =
+            reqwest::Client::new().post(&rpc_url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.error {
+            return Err(anyhow::anyhow!("gravity_txpoolDiff failed: {error}"));
+        }
+        let diff = response.result.ok_or_else(|| anyhow::anyhow!("Empty RPC response"))?;
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            }
+            _ => {
+                println!("Consensus side: {} txns", diff.consensus_count);
+                println!("Reth txpool:    {} txns", diff.reth_count);
+                println!();
+                println!("Only in consensus ({}):", diff.consensus_only_count);
+                for entry in &diff.consensus_only {
+                    println!("  {} {} nonce {}", entry.hash, entry.sender, entry.nonce);
+                }
+                println!("Only in reth ({}):", diff.reth_only_count);
+                for entry in &diff.reth_only {
+                    println!("  {} {} nonce {}", entry.hash, entry.sender, entry.nonce);
+                }
+                println!("Nonce conflicts ({}):", diff.nonce_conflicts.len());
+                for conflict in &diff.nonce_conflicts {
+                    println!(
+                        "  {} nonce {}: consensus {} / reth {}",
+                        conflict.sender,
+                        conflict.nonce,
+                        conflict.consensus_hash,
+                        conflict.reth_hash
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
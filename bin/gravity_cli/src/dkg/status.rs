@@ -20,6 +20,18 @@ pub struct DKGStatusResponse {
     round: u64,
     block_number: u64,
     participating_nodes: usize,
+    /// Missing when the node predates randomness health reporting
+    #[serde(default)]
+    randomness: Option<RandomnessHealth>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RandomnessHealth {
+    rounds_completed: u64,
+    shares_received: u64,
+    reconstruction_failures: u64,
+    last_decided_timestamp_secs: u64,
+    stalled: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -87,6 +99,14 @@ impl StatusCommand {
                 println!("  Current Round: {}", status.round);
                 println!("  Current Block Number: {}", status.block_number);
                 println!("  Participating Nodes: {}", status.participating_nodes);
+                if let Some(randomness) = &status.randomness {
+                    println!("Randomness:");
+                    println!("  Rounds Completed: {}", randomness.rounds_completed);
+                    println!("  Shares Received: {}", randomness.shares_received);
+                    println!("  Reconstruction Failures: {}", randomness.reconstruction_failures);
+                    println!("  Last Decided At: {}", randomness.last_decided_timestamp_secs);
+                    println!("  Stalled: {}", randomness.stalled);
+                }
             }
         }
 
@@ -0,0 +1,53 @@
+use alloy_primitives::B256;
+use clap::Parser;
+use genesis_bundle::GenesisBundle;
+use std::{fs, path::PathBuf};
+
+use crate::{command::Executable, genesis::waypoint::GenerateWaypoint};
+
+#[derive(Debug, Parser)]
+pub struct BuildBundle {
+    /// Genesis configuration JSON file, the same input as generate-waypoint
+    #[clap(long, value_parser)]
+    pub input_file: PathBuf,
+
+    /// Chain id of the execution genesis
+    #[clap(long)]
+    pub chain_id: u64,
+
+    /// Genesis block hash of the execution layer, 0x prefixed hex
+    #[clap(long)]
+    pub execution_genesis_hash: B256,
+
+    /// Output genesis bundle file path
+    #[clap(long, value_parser)]
+    pub output_file: PathBuf,
+}
+
+impl Executable for BuildBundle {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        println!("--- Build Genesis Bundle Start ---");
+        println!("Reading input file: {:?}", self.input_file);
+
+        let validator_set = GenerateWaypoint::genesis_validator_set(&self.input_file)?;
+        let waypoint = GenerateWaypoint::genesis_waypoint(validator_set.clone())?;
+        println!("Validators: {}", validator_set.active_validators.len());
+        println!("Generated waypoint: {waypoint}");
+        println!("Chain id: {}", self.chain_id);
+        println!("Execution genesis hash: {}", self.execution_genesis_hash);
+
+        let bundle = GenesisBundle {
+            chain_id: self.chain_id,
+            waypoint,
+            validator_set,
+            execution_genesis_hash: self.execution_genesis_hash.0,
+        };
+
+        println!("--- Write Output File ---");
+        fs::write(&self.output_file, bcs::to_bytes(&bundle)?)?;
+        println!("Genesis bundle written to: {:?}", self.output_file);
+        println!("--- Build Genesis Bundle Success ---");
+
+        Ok(())
+    }
+}
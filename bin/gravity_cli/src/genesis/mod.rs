@@ -1,11 +1,14 @@
 mod account;
+mod bundle;
 mod key;
 mod secret_manager;
 mod waypoint;
 
 use clap::{Parser, Subcommand};
 
-use crate::genesis::{account::GenerateAccount, key::GenerateKey, waypoint::GenerateWaypoint};
+use crate::genesis::{
+    account::GenerateAccount, bundle::BuildBundle, key::GenerateKey, waypoint::GenerateWaypoint,
+};
 
 #[derive(Debug, Parser)]
 pub struct GenesisCommand {
@@ -18,4 +21,5 @@ pub enum SubCommands {
     GenerateKey(GenerateKey),
     GenerateWaypoint(GenerateWaypoint),
     GenerateAccount(GenerateAccount),
+    BuildBundle(BuildBundle),
 }
@@ -9,7 +9,10 @@ use gaptos::{
     },
 };
 use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use crate::command::Executable;
 
@@ -45,17 +48,14 @@ pub struct GenerateWaypoint {
 
 impl GenerateWaypoint {
     /// Load genesis configuration from JSON file
-    fn load_genesis_config(&self) -> Result<GenesisConfig, anyhow::Error> {
-        let content = fs::read_to_string(&self.input_file)?;
+    fn load_genesis_config(input_file: &Path) -> Result<GenesisConfig, anyhow::Error> {
+        let content = fs::read_to_string(input_file)?;
         let config: GenesisConfig = serde_json::from_str(&content)?;
         Ok(config)
     }
 
     /// Generate validator set from genesis configuration
-    fn generate_validator_set(
-        &self,
-        config: &GenesisConfig,
-    ) -> Result<ValidatorSet, anyhow::Error> {
+    fn generate_validator_set(config: &GenesisConfig) -> Result<ValidatorSet, anyhow::Error> {
         let mut validators = Vec::new();
 
         for (i, v) in config.validators.iter().enumerate() {
@@ -111,17 +111,23 @@ impl GenerateWaypoint {
         Ok(ValidatorSet::new(validators))
     }
 
-    /// Generate a waypoint from the genesis configuration
-    pub fn generate_waypoint(&self) -> Result<String, anyhow::Error> {
-        let config = self.load_genesis_config()?;
-        let validator_set = self.generate_validator_set(&config)?;
+    /// Genesis validator set of the genesis configuration in `input_file`
+    pub(crate) fn genesis_validator_set(input_file: &Path) -> Result<ValidatorSet, anyhow::Error> {
+        let config = Self::load_genesis_config(input_file)?;
+        Self::generate_validator_set(&config)
+    }
 
-        // For now, generate a simple waypoint hash
-        // In a real implementation, this would use the validator set to create a proper waypoint
+    /// Waypoint of the genesis ledger info that installs `validator_set`
+    pub(crate) fn genesis_waypoint(validator_set: ValidatorSet) -> Result<Waypoint, anyhow::Error> {
         let ledger_info_with_signatures =
             LedgerInfoWithSignatures::genesis(*ACCUMULATOR_PLACEHOLDER_HASH, validator_set);
-        let waypoint_hash =
-            Waypoint::new_epoch_boundary(ledger_info_with_signatures.ledger_info())?;
+        Ok(Waypoint::new_epoch_boundary(ledger_info_with_signatures.ledger_info())?)
+    }
+
+    /// Generate a waypoint from the genesis configuration
+    pub fn generate_waypoint(&self) -> Result<String, anyhow::Error> {
+        let validator_set = Self::genesis_validator_set(&self.input_file)?;
+        let waypoint_hash = Self::genesis_waypoint(validator_set)?;
         let waypoint_string = format!("{waypoint_hash}");
 
         Ok(waypoint_string)
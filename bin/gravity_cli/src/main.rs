@@ -2,6 +2,7 @@ pub mod command;
 pub mod completions;
 pub mod config;
 pub mod contract;
+pub mod debug;
 pub mod dkg;
 pub mod doctor;
 pub mod epoch;
@@ -11,8 +12,10 @@ pub mod init;
 pub mod node;
 pub mod output;
 pub mod signer;
+pub mod simulate;
 pub mod stake;
 pub mod status;
+pub mod tx;
 pub mod unwind;
 pub mod util;
 pub mod validator;
@@ -44,14 +47,20 @@ fn main() {
             genesis::SubCommands::GenerateKey(gck) => gck.execute(),
             genesis::SubCommands::GenerateWaypoint(gw) => gw.execute(),
             genesis::SubCommands::GenerateAccount(generate_account) => generate_account.execute(),
+            genesis::SubCommands::BuildBundle(build_bundle) => build_bundle.execute(),
         },
         command::SubCommands::Validator(validator_cmd) => match validator_cmd.command {
             validator::SubCommands::Join(join_cmd) => join_cmd.execute(),
             validator::SubCommands::Leave(leave_cmd) => leave_cmd.execute(),
+            validator::SubCommands::RotateKey(rotate_key_cmd) => rotate_key_cmd.execute(),
             validator::SubCommands::List(mut list_cmd) => {
                 list_cmd.output_format = output_format;
                 list_cmd.execute()
             }
+            validator::SubCommands::Evidence(mut evidence_cmd) => {
+                evidence_cmd.output_format = output_format;
+                evidence_cmd.execute()
+            }
         },
         command::SubCommands::Stake(stake_cmd) => match stake_cmd.command {
             stake::SubCommands::Create(mut create_cmd) => {
@@ -66,6 +75,15 @@ fn main() {
         command::SubCommands::Node(node_cmd) => match node_cmd.command {
             node::SubCommands::Start(start_cmd) => start_cmd.execute(),
             node::SubCommands::Stop(stop_cmd) => stop_cmd.execute(),
+            node::SubCommands::Prune(prune_cmd) => prune_cmd.execute(),
+            node::SubCommands::EpochInfo(mut epoch_info_cmd) => {
+                epoch_info_cmd.output_format = output_format;
+                epoch_info_cmd.execute()
+            }
+            node::SubCommands::ConsensusState(mut consensus_state_cmd) => {
+                consensus_state_cmd.output_format = output_format;
+                consensus_state_cmd.execute()
+            }
         },
         command::SubCommands::Dkg(dkg_cmd) => match dkg_cmd.command {
             dkg::SubCommands::Status(mut status_cmd) => {
@@ -91,6 +109,29 @@ fn main() {
             doctor_cmd.output_format = output_format;
             doctor_cmd.execute()
         }
+        command::SubCommands::Debug(debug_cmd) => match debug_cmd.command {
+            debug::SubCommands::CheckBlock(check_cmd) => check_cmd.execute(),
+            debug::SubCommands::PoolDiff(mut pool_diff_cmd) => {
+                pool_diff_cmd.output_format = output_format;
+                pool_diff_cmd.execute()
+            }
+            debug::SubCommands::GetBatch(mut get_batch_cmd) => {
+                get_batch_cmd.output_format = output_format;
+                get_batch_cmd.execute()
+            }
+        },
+        command::SubCommands::Tx(tx_cmd) => match tx_cmd.command {
+            tx::SubCommands::Flood(mut flood_cmd) => {
+                flood_cmd.output_format = output_format;
+                flood_cmd.execute()
+            }
+        },
+        command::SubCommands::Simulate(simulate_cmd) => match simulate_cmd.command {
+            simulate::SubCommands::Consensus(mut consensus_cmd) => {
+                consensus_cmd.output_format = output_format;
+                consensus_cmd.execute()
+            }
+        },
     };
 
     if let Err(e) = result {
@@ -133,11 +174,27 @@ fn apply_config_defaults(cmd: &mut Command, profile: &Option<config::ProfileConf
                     c.gas_price = profile.gas_price;
                 }
             }
+            validator::SubCommands::RotateKey(ref mut c) => {
+                if c.rpc_url.is_none() {
+                    c.rpc_url.clone_from(&profile.rpc_url);
+                }
+                if c.gas_limit.is_none() {
+                    c.gas_limit = profile.gas_limit;
+                }
+                if c.gas_price.is_none() {
+                    c.gas_price = profile.gas_price;
+                }
+            }
             validator::SubCommands::List(ref mut c) => {
                 if c.rpc_url.is_none() {
                     c.rpc_url.clone_from(&profile.rpc_url);
                 }
             }
+            validator::SubCommands::Evidence(ref mut c) => {
+                if c.server_url.is_none() {
+                    c.server_url.clone_from(&profile.server_url);
+                }
+            }
         },
         command::SubCommands::Stake(ref mut s) => match &mut s.command {
             stake::SubCommands::Create(ref mut c) => {
@@ -168,6 +225,17 @@ fn apply_config_defaults(cmd: &mut Command, profile: &Option<config::ProfileConf
                     c.deploy_path.clone_from(&profile.deploy_path);
                 }
             }
+            node::SubCommands::Prune(_) => {}
+            node::SubCommands::EpochInfo(ref mut c) => {
+                if c.server_url.is_none() {
+                    c.server_url.clone_from(&profile.server_url);
+                }
+            }
+            node::SubCommands::ConsensusState(ref mut c) => {
+                if c.server_url.is_none() {
+                    c.server_url.clone_from(&profile.server_url);
+                }
+            }
         },
         command::SubCommands::Dkg(ref mut d) => match &mut d.command {
             dkg::SubCommands::Status(ref mut c) => {
@@ -207,6 +275,30 @@ fn apply_config_defaults(cmd: &mut Command, profile: &Option<config::ProfileConf
                 c.deploy_path.clone_from(&profile.deploy_path);
             }
         }
+        command::SubCommands::Debug(ref mut d) => match &mut d.command {
+            debug::SubCommands::CheckBlock(ref mut c) => {
+                if c.rpc_url.is_none() {
+                    c.rpc_url.clone_from(&profile.rpc_url);
+                }
+            }
+            debug::SubCommands::PoolDiff(ref mut c) => {
+                if c.rpc_url.is_none() {
+                    c.rpc_url.clone_from(&profile.rpc_url);
+                }
+            }
+            debug::SubCommands::GetBatch(ref mut c) => {
+                if c.server_url.is_none() {
+                    c.server_url.clone_from(&profile.server_url);
+                }
+            }
+        },
+        command::SubCommands::Tx(ref mut t) => match &mut t.command {
+            tx::SubCommands::Flood(ref mut c) => {
+                if c.rpc_urls.is_empty() {
+                    c.rpc_urls.extend(profile.rpc_url.clone());
+                }
+            }
+        },
         // Genesis, Unwind, Completions, Init don't use profile config
         _ => {}
     }
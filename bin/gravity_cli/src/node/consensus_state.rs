@@ -0,0 +1,142 @@
+use clap::Parser;
+
+use crate::{command::Executable, output::OutputFormat};
+use serde::{Deserialize, Serialize};
+
+/// Show the consensus state of a running node: its round, the last committed block and how many
+/// ordered blocks wait in the block buffer.
+#[derive(Debug, Parser)]
+pub struct ConsensusStateCommand {
+    /// Server address and port (e.g., 127.0.0.1:1024)
+    #[clap(long, env = "GRAVITY_SERVER_URL")]
+    pub server_url: Option<String>,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(super) struct Validator {
+    pub address: String,
+    pub voting_power: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(super) struct LedgerInfo {
+    pub epoch: u64,
+    pub round: u64,
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub(super) struct Pipeline {
+    pub ordered: u64,
+    pub executed: u64,
+    pub committed: u64,
+    pub persisted: u64,
+}
+
+/// Response of `GET /consensus/state`.
+#[derive(Deserialize, Serialize, Debug)]
+pub(super) struct ConsensusState {
+    pub epoch: u64,
+    pub round: u64,
+    pub validators: Option<Vec<Validator>>,
+    pub total_voting_power: Option<u128>,
+    pub last_committed: LedgerInfo,
+    pub pipeline: Option<Pipeline>,
+    pub pending_blocks: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn normalize_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        format!("http://{url}")
+    }
+}
+
+/// Fetches `/consensus/state` from the node at `server_url`.
+pub(super) async fn fetch_consensus_state(
+    server_url: Option<String>,
+) -> Result<ConsensusState, anyhow::Error> {
+    let server_url = server_url.ok_or_else(|| {
+        anyhow::anyhow!(
+            "--server-url is required. Set via CLI flag, GRAVITY_SERVER_URL env var, or ~/.gravity/config.toml"
+        )
+    })?;
+    let url = format!("{}/consensus/state", normalize_url(&server_url));
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()?;
+
+    let response = client.get(&url).send().await?;
+
+    let status_code = response.status();
+    if !status_code.is_success() {
+        let error_msg = match response.json::<ErrorResponse>().await {
+            Ok(error_response) => format!("HTTP {}: {}", status_code, error_response.error),
+            Err(_) => format!("HTTP {status_code}"),
+        };
+        return Err(anyhow::anyhow!("Failed to get consensus state: {error_msg}"));
+    }
+
+    Ok(response.json().await?)
+}
+
+impl Executable for ConsensusStateCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl ConsensusStateCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let state = fetch_consensus_state(self.server_url).await?;
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&state)?);
+            }
+            _ => {
+                let committed = &state.last_committed;
+                println!("Epoch:           {}", state.epoch);
+                println!("Round:           {}", state.round);
+                println!(
+                    "Last committed:  block {} (epoch {}, round {})",
+                    committed.block_number, committed.epoch, committed.round
+                );
+                println!("  Hash:          0x{}", committed.block_hash);
+                match &state.pipeline {
+                    Some(pipeline) => {
+                        println!(
+                            "Block buffer:    ordered {}, executed {}, committed {}, persisted {}",
+                            pipeline.ordered,
+                            pipeline.executed,
+                            pipeline.committed,
+                            pipeline.persisted
+                        );
+                        println!(
+                            "  Pending:       {} blocks",
+                            state.pending_blocks.unwrap_or_default()
+                        );
+                    }
+                    None => println!("Block buffer:    not initialized"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
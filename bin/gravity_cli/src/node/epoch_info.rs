@@ -0,0 +1,62 @@
+use clap::Parser;
+
+use crate::{
+    command::Executable, node::consensus_state::fetch_consensus_state, output::OutputFormat,
+};
+
+/// Show the epoch a running node is in and the validators of that epoch.
+#[derive(Debug, Parser)]
+pub struct EpochInfoCommand {
+    /// Server address and port (e.g., 127.0.0.1:1024)
+    #[clap(long, env = "GRAVITY_SERVER_URL")]
+    pub server_url: Option<String>,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+impl Executable for EpochInfoCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl EpochInfoCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let state = fetch_consensus_state(self.server_url).await?;
+
+        match self.output_format {
+            OutputFormat::Json => {
+                let info = serde_json::json!({
+                    "epoch": state.epoch,
+                    "round": state.round,
+                    "validators": state.validators,
+                    "total_voting_power": state.total_voting_power,
+                });
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            }
+            _ => {
+                println!("Epoch:  {}", state.epoch);
+                println!("Round:  {}", state.round);
+                match (&state.validators, state.total_voting_power) {
+                    (Some(validators), Some(total_voting_power)) => {
+                        println!(
+                            "Validators ({}, total voting power {}):",
+                            validators.len(),
+                            total_voting_power
+                        );
+                        println!("  {:<68} VOTING POWER", "ADDRESS");
+                        for validator in validators {
+                            println!("  {:<68} {}", validator.address, validator.voting_power);
+                        }
+                    }
+                    _ => println!("Validators: unavailable, see the node logs"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
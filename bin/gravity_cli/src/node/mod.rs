@@ -1,9 +1,15 @@
+mod consensus_state;
+mod epoch_info;
+mod prune;
 mod start;
 mod stop;
 
 use clap::{Parser, Subcommand};
 
-use crate::node::{start::StartCommand, stop::StopCommand};
+use crate::node::{
+    consensus_state::ConsensusStateCommand, epoch_info::EpochInfoCommand, prune::PruneCommand,
+    start::StartCommand, stop::StopCommand,
+};
 
 #[derive(Debug, Parser)]
 pub struct NodeCommand {
@@ -15,4 +21,9 @@ pub struct NodeCommand {
 pub enum SubCommands {
     Start(StartCommand),
     Stop(StopCommand),
+    Prune(PruneCommand),
+    /// Show the current epoch and its validators, queried from a running node
+    EpochInfo(EpochInfoCommand),
+    /// Show the round, last committed block and block buffer depth of a running node
+    ConsensusState(ConsensusStateCommand),
 }
@@ -0,0 +1,76 @@
+use aptos_consensus::consensusdb::{ConsensusDB, PruneRetention};
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::command::Executable;
+
+/// Prune committed history from the consensus DB of a stopped node.
+///
+/// Deletes blocks, QCs, ledger infos and randomness older than the retention, like a node
+/// started with CONSENSUS_PRUNE_KEEP_EPOCHS or CONSENSUS_PRUNE_KEEP_BLOCKS does. Epoch-ending
+/// ledger infos are kept. Nothing past `--execution-block`, the latest committed ledger info or
+/// the recent blocks a restart reloads is pruned.
+#[derive(Debug, Parser)]
+pub struct PruneCommand {
+    /// Path to the consensus DB data directory.
+    /// This is typically `<deploy-path>/data/consensus_db`.
+    #[clap(long)]
+    pub consensus_db_path: PathBuf,
+
+    /// Keep the blocks of the newest N epochs, the current one included
+    #[clap(long)]
+    pub keep_epochs: Option<u64>,
+
+    /// Keep the newest N blocks below the execution block
+    #[clap(long)]
+    pub keep_blocks: Option<u64>,
+
+    /// Highest block the execution layer has persisted, e.g. `eth_blockNumber` before the node
+    /// was stopped
+    #[clap(long)]
+    pub execution_block: u64,
+
+    /// Print what would be pruned without deleting anything
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+impl Executable for PruneCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let retention = PruneRetention { epochs: self.keep_epochs, blocks: self.keep_blocks };
+        if retention == PruneRetention::default() {
+            return Err(anyhow::anyhow!("--keep-epochs or --keep-blocks is required"));
+        }
+        if !self.consensus_db_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Consensus DB path does not exist: {:?}",
+                self.consensus_db_path
+            ));
+        }
+
+        let consensus_db = ConsensusDB::new(&self.consensus_db_path, &PathBuf::new());
+        let pruned_below = consensus_db
+            .get_pruned_below()
+            .map_err(|e| anyhow::anyhow!("Failed to read prune progress: {e:?}"))?;
+        let target = consensus_db
+            .prune_target(retention, self.execution_block)
+            .map_err(|e| anyhow::anyhow!("Failed to compute prune target: {e:?}"))?;
+        if target <= pruned_below {
+            println!("Nothing to prune: history below block {pruned_below} is already pruned.");
+            return Ok(());
+        }
+        if self.dry_run {
+            println!("Would prune blocks {pruned_below}..{target} (dry run).");
+            return Ok(());
+        }
+
+        let summary = consensus_db
+            .prune(retention, self.execution_block)
+            .map_err(|e| anyhow::anyhow!("Failed to prune consensus DB: {e:?}"))?;
+        println!(
+            "Pruned below block {}: {} blocks, {} ledger infos, {} randomness entries.",
+            summary.pruned_below, summary.blocks, summary.ledger_infos, summary.randomness
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use anyhow::Result;
+use clap::Parser;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+use crate::{command::Executable, output::OutputFormat, util::percentile};
+
+/// Simulate round progression offline to estimate the TPS and finality a network can reach.
+///
+/// Each round the leader proposes a block and waits for a quorum of votes, one network round
+/// trip per validator drawn from `--rtt-ms`. A round whose quorum takes longer than the round
+/// timeout fails. A certified block is ordered once its child is certified in the next round,
+/// then executed in order and committed after another quorum of commit votes. The leader holds
+/// back its proposal while the number of ordered but uncommitted blocks is at the backlog limit.
+///
+/// Every combination of `--batch-size`, `--backlog-limit` and `--round-timeout-ms` is simulated
+/// with the same latency samples, so the results can be compared against each other. The model
+/// ignores bandwidth, proposal size and leader failures; treat its numbers as upper bounds.
+#[derive(Debug, Parser)]
+pub struct ConsensusCommand {
+    /// Number of validators
+    #[clap(long)]
+    pub validators: usize,
+
+    /// Measured round trip times between validators in milliseconds, sampled uniformly.
+    /// Repeat the flag or pass a comma separated list.
+    #[clap(long, value_delimiter = ',', required = true)]
+    pub rtt_ms: Vec<f64>,
+
+    /// Execution time of a block regardless of its size, in milliseconds
+    #[clap(long, default_value_t = 0.0)]
+    pub execution_ms: f64,
+
+    /// Additional execution time per transaction, in microseconds
+    #[clap(long, default_value_t = 0.0)]
+    pub execution_us_per_txn: f64,
+
+    /// Maximum transactions per block to try
+    #[clap(long, value_delimiter = ',', default_value = "1000,2000,5000")]
+    pub batch_size: Vec<u64>,
+
+    /// Maximum ordered but uncommitted blocks to try
+    #[clap(long, value_delimiter = ',', default_value = "10,20")]
+    pub backlog_limit: Vec<usize>,
+
+    /// Round timeouts to try, in milliseconds
+    #[clap(long, value_delimiter = ',', default_value = "1000")]
+    pub round_timeout_ms: Vec<f64>,
+
+    /// Offered load in transactions per second (unset means blocks are always full)
+    #[clap(long)]
+    pub offered_tps: Option<f64>,
+
+    /// Number of rounds to simulate for each combination
+    #[clap(long, default_value_t = 10_000)]
+    pub rounds: u64,
+
+    /// Seed for sampling the round trip times
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+/// One combination of the swept parameters together with the measured inputs.
+#[derive(Debug, Clone)]
+struct Scenario {
+    validators: usize,
+    rtt_ms: Vec<f64>,
+    execution_ms: f64,
+    execution_us_per_txn: f64,
+    batch_size: u64,
+    backlog_limit: usize,
+    round_timeout_ms: f64,
+    offered_tps: Option<f64>,
+    rounds: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScenarioReport {
+    batch_size: u64,
+    backlog_limit: usize,
+    round_timeout_ms: f64,
+    tps: f64,
+    blocks_per_sec: f64,
+    avg_block_txns: f64,
+    /// Fraction of rounds that timed out
+    timeout_rate: f64,
+    finality_p50_ms: f64,
+    finality_p99_ms: f64,
+}
+
+/// splitmix64, good enough to sample latencies reproducibly.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn sample(&mut self, samples: &[f64]) -> f64 {
+        samples[(self.next_u64() % samples.len() as u64) as usize]
+    }
+}
+
+impl Executable for ConsensusCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        if self.validators == 0 {
+            return Err(anyhow::anyhow!("--validators must be at least 1"));
+        }
+        if self.rtt_ms.iter().any(|rtt| !rtt.is_finite() || *rtt < 0.0) {
+            return Err(anyhow::anyhow!("--rtt-ms values must be non-negative"));
+        }
+        if self.batch_size.contains(&0) || self.backlog_limit.contains(&0) {
+            return Err(anyhow::anyhow!("--batch-size and --backlog-limit must be at least 1"));
+        }
+
+        let mut reports = Vec::new();
+        for &batch_size in &self.batch_size {
+            for &backlog_limit in &self.backlog_limit {
+                for &round_timeout_ms in &self.round_timeout_ms {
+                    let scenario = Scenario {
+                        validators: self.validators,
+                        rtt_ms: self.rtt_ms.clone(),
+                        execution_ms: self.execution_ms,
+                        execution_us_per_txn: self.execution_us_per_txn,
+                        batch_size,
+                        backlog_limit,
+                        round_timeout_ms,
+                        offered_tps: self.offered_tps,
+                        rounds: self.rounds,
+                    };
+                    reports.push(simulate(&scenario, self.seed));
+                }
+            }
+        }
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            }
+            _ => print_reports(&reports),
+        }
+
+        Ok(())
+    }
+}
+
+/// Time until `2f + 1` of the validators, the leader included, have answered.
+fn quorum_delay(scenario: &Scenario, rng: &mut Rng) -> f64 {
+    let quorum = 2 * ((scenario.validators - 1) / 3) + 1;
+    let mut delays: Vec<f64> = std::iter::once(0.0)
+        .chain((1..scenario.validators).map(|_| rng.sample(&scenario.rtt_ms)))
+        .collect();
+    delays.sort_by(f64::total_cmp);
+    delays[quorum - 1]
+}
+
+fn simulate(scenario: &Scenario, seed: u64) -> ScenarioReport {
+    let mut rng = Rng(seed);
+    // All times are in milliseconds since the first proposal.
+    let mut now = 0.0;
+    let mut pool = 0.0;
+    let mut pool_updated_at = 0.0;
+    // (proposed at, txns) of certified blocks that are not ordered yet
+    let mut certified: Vec<(f64, u64)> = Vec::new();
+    let mut parent_certified = false;
+    // commit times of ordered blocks, in commit order
+    let mut uncommitted: VecDeque<f64> = VecDeque::new();
+    let mut executor_free_at = 0.0;
+    let mut last_commit_at = 0.0;
+    let mut finality = Vec::new();
+    let mut committed_txns = 0;
+    let mut timeouts = 0;
+
+    for _ in 0..scenario.rounds {
+        while uncommitted.front().is_some_and(|commit_at| *commit_at <= now) {
+            uncommitted.pop_front();
+        }
+        if uncommitted.len() >= scenario.backlog_limit {
+            now = uncommitted[uncommitted.len() - scenario.backlog_limit];
+            while uncommitted.front().is_some_and(|commit_at| *commit_at <= now) {
+                uncommitted.pop_front();
+            }
+        }
+
+        let txns = match scenario.offered_tps {
+            Some(tps) => {
+                pool += tps * (now - pool_updated_at) / 1000.0;
+                pool_updated_at = now;
+                (pool.floor() as u64).min(scenario.batch_size)
+            }
+            None => scenario.batch_size,
+        };
+        let proposed_at = now;
+        let delay = quorum_delay(scenario, &mut rng);
+        if delay > scenario.round_timeout_ms {
+            // The block is dropped and its transactions stay in the pool.
+            now += scenario.round_timeout_ms + quorum_delay(scenario, &mut rng);
+            timeouts += 1;
+            parent_certified = false;
+            continue;
+        }
+        now += delay;
+        pool -= txns as f64;
+
+        if parent_certified {
+            for (block_proposed_at, block_txns) in certified.drain(..) {
+                let execution = scenario.execution_ms +
+                    scenario.execution_us_per_txn * block_txns as f64 / 1000.0;
+                executor_free_at = f64::max(now, executor_free_at) + execution;
+                let commit_at =
+                    f64::max(last_commit_at, executor_free_at + quorum_delay(scenario, &mut rng));
+                last_commit_at = commit_at;
+                uncommitted.push_back(commit_at);
+                finality.push(commit_at - block_proposed_at);
+                committed_txns += block_txns;
+            }
+        }
+        certified.push((proposed_at, txns));
+        parent_certified = true;
+    }
+
+    let committed_blocks = finality.len();
+    finality.sort_by(f64::total_cmp);
+    let elapsed_secs = last_commit_at / 1000.0;
+    let per_sec = |n: f64| if elapsed_secs > 0.0 { n / elapsed_secs } else { 0.0 };
+    ScenarioReport {
+        batch_size: scenario.batch_size,
+        backlog_limit: scenario.backlog_limit,
+        round_timeout_ms: scenario.round_timeout_ms,
+        tps: per_sec(committed_txns as f64),
+        blocks_per_sec: per_sec(committed_blocks as f64),
+        avg_block_txns: if committed_blocks > 0 {
+            committed_txns as f64 / committed_blocks as f64
+        } else {
+            0.0
+        },
+        timeout_rate: if scenario.rounds > 0 {
+            timeouts as f64 / scenario.rounds as f64
+        } else {
+            0.0
+        },
+        finality_p50_ms: percentile(&finality, 50.0),
+        finality_p99_ms: percentile(&finality, 99.0),
+    }
+}
+
+fn print_reports(reports: &[ScenarioReport]) {
+    println!(
+        "{:>10} {:>8} {:>10} {:>10} {:>10} {:>10} {:>9} {:>10} {:>10}",
+        "batch",
+        "backlog",
+        "timeout",
+        "tps",
+        "blocks/s",
+        "txns/blk",
+        "timeouts",
+        "p50 ms",
+        "p99 ms"
+    );
+    for report in reports {
+        println!(
+            "{:>10} {:>8} {:>10.0} {:>10.1} {:>10.2} {:>10.1} {:>8.2}% {:>10.1} {:>10.1}",
+            report.batch_size,
+            report.backlog_limit,
+            report.round_timeout_ms,
+            report.tps,
+            report.blocks_per_sec,
+            report.avg_block_txns,
+            report.timeout_rate * 100.0,
+            report.finality_p50_ms,
+            report.finality_p99_ms
+        );
+    }
+    if let Some(best) = reports.iter().max_by(|a, b| a.tps.total_cmp(&b.tps)) {
+        println!();
+        println!(
+            "Highest TPS: batch {}, backlog {}, timeout {:.0}ms ({:.1} tps, p99 finality {:.1}ms)",
+            best.batch_size,
+            best.backlog_limit,
+            best.round_timeout_ms,
+            best.tps,
+            best.finality_p99_ms
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(round_timeout_ms: f64, execution_ms: f64) -> Scenario {
+        Scenario {
+            validators: 4,
+            rtt_ms: vec![100.0],
+            execution_ms,
+            execution_us_per_txn: 0.0,
+            batch_size: 100,
+            backlog_limit: 10,
+            round_timeout_ms,
+            offered_tps: None,
+            rounds: 1_000,
+        }
+    }
+
+    #[test]
+    fn throughput_is_bounded_by_rounds_execution_and_timeouts() {
+        // One block of 100 txns per 100ms round, committed one round trip after its child is
+        // certified.
+        let report = simulate(&scenario(1_000.0, 0.0), 0);
+        assert!((report.tps - 1_000.0).abs() < 5.0, "tps {}", report.tps);
+        assert_eq!(report.finality_p50_ms, 300.0);
+        assert_eq!(report.timeout_rate, 0.0);
+
+        // Execution takes two rounds per block, so the backlog limit throttles proposals.
+        let report = simulate(&scenario(1_000.0, 200.0), 0);
+        assert!((report.tps - 500.0).abs() < 5.0, "tps {}", report.tps);
+
+        // No quorum ever forms within the timeout.
+        let report = simulate(&scenario(50.0, 0.0), 0);
+        assert_eq!(report.tps, 0.0);
+        assert_eq!(report.timeout_rate, 1.0);
+    }
+}
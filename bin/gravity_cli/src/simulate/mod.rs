@@ -0,0 +1,25 @@
+use clap::Parser;
+
+use crate::command::Executable;
+
+pub mod consensus;
+
+#[derive(Debug, Parser)]
+pub struct SimulateCommand {
+    #[command(subcommand)]
+    pub command: SubCommands,
+}
+
+#[derive(Debug, Parser)]
+pub enum SubCommands {
+    /// Estimate TPS and finality for consensus parameters from measured latencies
+    Consensus(consensus::ConsensusCommand),
+}
+
+impl Executable for SimulateCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        match self.command {
+            SubCommands::Consensus(consensus_cmd) => consensus_cmd.execute(),
+        }
+    }
+}
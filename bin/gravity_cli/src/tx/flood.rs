@@ -0,0 +1,417 @@
+use alloy_network::{eip2718::Encodable2718, Ethereum, TransactionBuilder};
+use alloy_primitives::{hex, Address, Bytes};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types::eth::TransactionRequest;
+use anyhow::Result;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::Semaphore,
+    task::JoinSet,
+    time::{interval, MissedTickBehavior},
+};
+
+use crate::{
+    command::Executable,
+    output::OutputFormat,
+    signer::SignerArgs,
+    util::{parse_ether, percentile},
+};
+
+/// Submit transactions round-robin across several nodes and report how they were received.
+///
+/// Transactions are read from `--txn-file`, or generated as `--count` transfers signed by the
+/// given key with consecutive nonces from the sender's pending nonce. Each one is sent with
+/// `eth_sendRawTransaction` to the next endpoint in turn. Only admission into the nodes' pools is
+/// measured, not inclusion in blocks.
+#[derive(Debug, Parser)]
+pub struct FloodCommand {
+    /// RPC URLs of the nodes to submit to. Repeat the flag or pass a comma separated list.
+    #[clap(long = "rpc-url", env = "GRAVITY_RPC_URL", value_delimiter = ',')]
+    pub rpc_urls: Vec<String>,
+
+    /// File with one hex-encoded signed transaction per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    #[clap(long, conflicts_with_all = ["count", "to", "value"])]
+    pub txn_file: Option<PathBuf>,
+
+    /// Number of transfers to generate when no --txn-file is given
+    #[clap(long, default_value_t = 1000)]
+    pub count: u64,
+
+    /// Recipient of the generated transfers (defaults to the sender itself)
+    #[clap(long)]
+    pub to: Option<String>,
+
+    /// Amount of each generated transfer in ETH
+    #[clap(long, default_value = "0")]
+    pub value: String,
+
+    /// Gas limit of each generated transfer
+    #[clap(long, default_value_t = 21_000)]
+    pub gas_limit: u64,
+
+    /// Gas price of the generated transfers in wei (defaults to the node's eth_gasPrice)
+    #[clap(long)]
+    pub gas_price: Option<u128>,
+
+    /// Maximum number of submissions in flight across all endpoints
+    #[clap(long, default_value_t = 16)]
+    pub concurrency: usize,
+
+    /// Maximum number of submissions started per second (unlimited if unset)
+    #[clap(long)]
+    pub rate: Option<u64>,
+
+    /// Timeout of a single submission in milliseconds
+    #[clap(long, default_value_t = 5_000)]
+    pub timeout_ms: u64,
+
+    #[clap(flatten)]
+    pub signer: SignerArgs,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    error: Option<RpcError>,
+}
+
+/// Outcome of one `eth_sendRawTransaction` call.
+struct Submission {
+    endpoint: usize,
+    latency: Duration,
+    result: Result<(), String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LatencyStats {
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointStats {
+    url: String,
+    submitted: u64,
+    accepted: u64,
+    rejected: u64,
+    latency: LatencyStats,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FloodReport {
+    submitted: u64,
+    accepted: u64,
+    rejected: u64,
+    elapsed_secs: f64,
+    submitted_per_sec: f64,
+    latency: LatencyStats,
+    endpoints: Vec<EndpointStats>,
+    /// rejection reason -> number of transactions
+    errors: BTreeMap<String, u64>,
+}
+
+impl Executable for FloodCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl FloodCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        if self.rpc_urls.is_empty() {
+            return Err(anyhow::anyhow!(
+                "--rpc-url is required. Set via CLI flag, GRAVITY_RPC_URL env var, or ~/.gravity/config.toml"
+            ));
+        }
+        if self.concurrency == 0 {
+            return Err(anyhow::anyhow!("--concurrency must be at least 1"));
+        }
+
+        let txns = match &self.txn_file {
+            Some(path) => read_txn_file(path)?,
+            None => self.generate_transfers().await?,
+        };
+        if txns.is_empty() {
+            return Err(anyhow::anyhow!("No transactions to submit"));
+        }
+        eprintln!(
+            "Submitting {} txns to {} endpoints (concurrency {}, rate {})",
+            txns.len(),
+            self.rpc_urls.len(),
+            self.concurrency,
+            self.rate.map_or("unlimited".to_string(), |rate| format!("{rate}/s")),
+        );
+
+        let client =
+            reqwest::Client::builder().timeout(Duration::from_millis(self.timeout_ms)).build()?;
+        let semaphore = Arc::new(Semaphore::new(self.concurrency));
+        let mut ticker = self.rate.filter(|rate| *rate > 0).map(|rate| {
+            let mut ticker = interval(Duration::from_secs_f64(1.0 / rate as f64));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker
+        });
+
+        let start = Instant::now();
+        let mut tasks = JoinSet::new();
+        for (i, txn) in txns.into_iter().enumerate() {
+            if let Some(ticker) = ticker.as_mut() {
+                ticker.tick().await;
+            }
+            let permit = semaphore.clone().acquire_owned().await?;
+            let endpoint = i % self.rpc_urls.len();
+            let url = self.rpc_urls[endpoint].clone();
+            let client = client.clone();
+            tasks.spawn(async move {
+                let sent_at = Instant::now();
+                let result = send_raw_transaction(&client, &url, txn).await;
+                drop(permit);
+                Submission { endpoint, latency: sent_at.elapsed(), result }
+            });
+        }
+        let mut submissions = Vec::with_capacity(tasks.len());
+        while let Some(submission) = tasks.join_next().await {
+            submissions.push(submission?);
+        }
+        let report = FloodReport::new(&self.rpc_urls, &submissions, start.elapsed());
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+            _ => report.print(),
+        }
+
+        Ok(())
+    }
+
+    /// Signs `count` transfers from the signer's account, starting at its pending nonce.
+    async fn generate_transfers(&self) -> Result<Vec<Bytes>> {
+        let resolved = self.signer.resolve().await?;
+        let provider = ProviderBuilder::new().connect_http(self.rpc_urls[0].parse()?);
+        let chain_id = provider.get_chain_id().await?;
+        let nonce = provider.get_transaction_count(resolved.address).pending().await?;
+        let gas_price = match self.gas_price {
+            Some(gas_price) => gas_price,
+            None => provider.get_gas_price().await?,
+        };
+        let to = match &self.to {
+            Some(to) => Address::from_str(to)?,
+            None => resolved.address,
+        };
+        let value = parse_ether(&self.value)?;
+        eprintln!(
+            "Signing {} transfers from {:?} to {:?} (chain {}, nonce {}, gas price {})",
+            self.count, resolved.address, to, chain_id, nonce, gas_price
+        );
+
+        let mut txns = Vec::with_capacity(self.count as usize);
+        for i in 0..self.count {
+            let request = TransactionRequest::default()
+                .with_from(resolved.address)
+                .with_to(to)
+                .with_value(value)
+                .with_nonce(nonce + i)
+                .with_chain_id(chain_id)
+                .with_gas_limit(self.gas_limit)
+                .with_gas_price(gas_price);
+            let envelope = <TransactionRequest as TransactionBuilder<Ethereum>>::build(
+                request,
+                &resolved.wallet,
+            )
+            .await?;
+            txns.push(Bytes::from(envelope.encoded_2718()));
+        }
+        Ok(txns)
+    }
+}
+
+fn read_txn_file(path: &Path) -> Result<Vec<Bytes>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            Bytes::from_str(line)
+                .map_err(|e| anyhow::anyhow!("Invalid transaction on line {}: {e}", i + 1))
+        })
+        .collect()
+}
+
+async fn send_raw_transaction(
+    client: &reqwest::Client,
+    url: &str,
+    txn: Bytes,
+) -> Result<(), String> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_sendRawTransaction",
+        "params": [hex::encode_prefixed(&txn)],
+    });
+    let response: RpcResponse = client
+        .post(url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("invalid response: {e}"))?;
+    match response.error {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
+}
+
+impl LatencyStats {
+    fn new(mut latencies: Vec<Duration>) -> Self {
+        latencies.sort_unstable();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        Self {
+            p50_ms: ms(percentile(&latencies, 50.0)),
+            p90_ms: ms(percentile(&latencies, 90.0)),
+            p99_ms: ms(percentile(&latencies, 99.0)),
+            max_ms: ms(latencies.last().copied().unwrap_or_default()),
+        }
+    }
+}
+
+impl FloodReport {
+    fn new(rpc_urls: &[String], submissions: &[Submission], elapsed: Duration) -> Self {
+        let endpoints = rpc_urls
+            .iter()
+            .enumerate()
+            .map(|(endpoint, url)| {
+                let own: Vec<&Submission> =
+                    submissions.iter().filter(|s| s.endpoint == endpoint).collect();
+                let accepted = own.iter().filter(|s| s.result.is_ok()).count() as u64;
+                EndpointStats {
+                    url: url.clone(),
+                    submitted: own.len() as u64,
+                    accepted,
+                    rejected: own.len() as u64 - accepted,
+                    latency: LatencyStats::new(own.iter().map(|s| s.latency).collect()),
+                }
+            })
+            .collect();
+        let mut errors = BTreeMap::new();
+        for submission in submissions {
+            if let Err(e) = &submission.result {
+                *errors.entry(e.clone()).or_insert(0) += 1;
+            }
+        }
+        let submitted = submissions.len() as u64;
+        let accepted = submissions.iter().filter(|s| s.result.is_ok()).count() as u64;
+        let elapsed_secs = elapsed.as_secs_f64();
+        Self {
+            submitted,
+            accepted,
+            rejected: submitted - accepted,
+            elapsed_secs,
+            submitted_per_sec: if elapsed_secs > 0.0 {
+                submitted as f64 / elapsed_secs
+            } else {
+                0.0
+            },
+            latency: LatencyStats::new(submissions.iter().map(|s| s.latency).collect()),
+            endpoints,
+            errors,
+        }
+    }
+
+    fn print(&self) {
+        println!(
+            "Submitted {} txns in {:.2}s ({:.1} txns/s)",
+            self.submitted, self.elapsed_secs, self.submitted_per_sec
+        );
+        println!("Accepted: {}, rejected: {}", self.accepted, self.rejected);
+        println!(
+            "Latency: p50 {:.1}ms, p90 {:.1}ms, p99 {:.1}ms, max {:.1}ms",
+            self.latency.p50_ms, self.latency.p90_ms, self.latency.p99_ms, self.latency.max_ms
+        );
+        println!();
+        println!("Per endpoint:");
+        for endpoint in &self.endpoints {
+            println!(
+                "  {}: {} submitted, {} accepted, {} rejected, p50 {:.1}ms, p99 {:.1}ms",
+                endpoint.url,
+                endpoint.submitted,
+                endpoint.accepted,
+                endpoint.rejected,
+                endpoint.latency.p50_ms,
+                endpoint.latency.p99_ms
+            );
+        }
+        if !self.errors.is_empty() {
+            println!();
+            println!("Rejections:");
+            let mut errors: Vec<_> = self.errors.iter().collect();
+            errors.sort_by(|a, b| b.1.cmp(a.1));
+            for (error, count) in errors {
+                println!("  {count:>6} {error}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(endpoint: usize, latency_ms: u64, result: Result<(), &str>) -> Submission {
+        Submission {
+            endpoint,
+            latency: Duration::from_millis(latency_ms),
+            result: result.map_err(str::to_string),
+        }
+    }
+
+    #[test]
+    fn report_groups_by_endpoint_and_reason() {
+        let urls = vec!["http://a".to_string(), "http://b".to_string()];
+        let submissions = vec![
+            submission(0, 10, Ok(())),
+            submission(1, 20, Err("nonce too low")),
+            submission(0, 30, Ok(())),
+            submission(1, 40, Err("nonce too low")),
+            submission(0, 50, Err("already known")),
+        ];
+        let report = FloodReport::new(&urls, &submissions, Duration::from_secs(1));
+
+        assert_eq!((report.submitted, report.accepted, report.rejected), (5, 2, 3));
+        assert_eq!(report.endpoints[0].submitted, 3);
+        assert_eq!(report.endpoints[0].accepted, 2);
+        assert_eq!(report.endpoints[1].rejected, 2);
+        assert_eq!(report.errors["nonce too low"], 2);
+        assert_eq!(report.errors["already known"], 1);
+        assert_eq!(report.latency.p50_ms, 30.0);
+        assert_eq!(report.latency.max_ms, 50.0);
+    }
+}
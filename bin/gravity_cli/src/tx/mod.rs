@@ -0,0 +1,25 @@
+use clap::Parser;
+
+use crate::command::Executable;
+
+pub mod flood;
+
+#[derive(Debug, Parser)]
+pub struct TxCommand {
+    #[command(subcommand)]
+    pub command: SubCommands,
+}
+
+#[derive(Debug, Parser)]
+pub enum SubCommands {
+    /// Submit a batch of transactions round-robin across nodes and report acceptance and latency
+    Flood(flood::FloodCommand),
+}
+
+impl Executable for TxCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        match self.command {
+            SubCommands::Flood(flood_cmd) => flood_cmd.execute(),
+        }
+    }
+}
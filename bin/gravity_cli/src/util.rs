@@ -48,3 +48,13 @@ pub fn parse_ether(eth_amount: &str) -> Result<U256, anyhow::Error> {
 
     U256::from_str(&wei_str).map_err(|e| anyhow::anyhow!("Failed to parse ether: {e}"))
 }
+
+/// Nearest-rank percentile of `sorted`, which must be in ascending order. The default value of
+/// `T` when `sorted` is empty.
+pub fn percentile<T: Copy + Default>(sorted: &[T], p: f64) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
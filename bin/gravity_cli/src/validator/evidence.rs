@@ -0,0 +1,103 @@
+use clap::Parser;
+
+use crate::{command::Executable, output::OutputFormat};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Parser)]
+pub struct EvidenceCommand {
+    /// Server address and port (e.g., 127.0.0.1:1024)
+    #[clap(long, env = "GRAVITY_SERVER_URL")]
+    pub server_url: Option<String>,
+
+    /// Only show evidence from this epoch
+    #[clap(long)]
+    pub epoch: Option<u64>,
+
+    /// Output format
+    #[clap(skip)]
+    pub output_format: OutputFormat,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct DoubleSignEvidence {
+    epoch: u64,
+    round: u64,
+    author: String,
+    first_block_id: String,
+    second_block_id: String,
+    evidence: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    error: String,
+}
+
+impl Executable for EvidenceCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+impl EvidenceCommand {
+    fn normalize_url(url: &str) -> String {
+        let url = url.trim_end_matches('/');
+        if url.starts_with("https://") || url.starts_with("http://") {
+            url.to_string()
+        } else {
+            format!("http://{url}")
+        }
+    }
+
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let server_url = self.server_url.ok_or_else(|| {
+            anyhow::anyhow!(
+                "--server-url is required. Set via CLI flag, GRAVITY_SERVER_URL env var, or ~/.gravity/config.toml"
+            )
+        })?;
+
+        let base_url = Self::normalize_url(&server_url);
+        let url = match self.epoch {
+            Some(epoch) => format!("{base_url}/consensus/evidence/{epoch}"),
+            None => format!("{base_url}/consensus/evidence"),
+        };
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+
+        let response = client.get(&url).send().await?;
+
+        let status_code = response.status();
+        if !status_code.is_success() {
+            let error_msg = match response.json::<ErrorResponse>().await {
+                Ok(error_response) => format!("HTTP {}: {}", status_code, error_response.error),
+                Err(_) => format!("HTTP {status_code}"),
+            };
+            return Err(anyhow::anyhow!("Failed to get double sign evidence: {error_msg}"));
+        }
+
+        let evidence: Vec<DoubleSignEvidence> = response.json().await?;
+
+        match self.output_format {
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&evidence)?);
+            }
+            _ => {
+                if evidence.is_empty() {
+                    println!("No double sign evidence recorded");
+                }
+                for e in &evidence {
+                    println!("Epoch {} round {}: validator {}", e.epoch, e.round, e.author);
+                    println!("  Signed block {}", e.first_block_id);
+                    println!("  Signed block {}", e.second_block_id);
+                    println!("  Evidence (BCS): {}", e.evidence);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
+mod evidence;
 mod join;
 mod leave;
 mod list;
+mod rotate_key;
 
 use clap::{Parser, Subcommand};
 
-use crate::validator::{join::JoinCommand, leave::LeaveCommand, list::ListCommand};
+use crate::validator::{
+    evidence::EvidenceCommand, join::JoinCommand, leave::LeaveCommand, list::ListCommand,
+    rotate_key::RotateKeyCommand,
+};
 
 #[derive(Debug, Parser)]
 pub struct ValidatorCommand {
@@ -17,5 +22,7 @@ pub enum SubCommands {
     Join(JoinCommand),
     Leave(LeaveCommand),
     List(ListCommand),
+    Evidence(EvidenceCommand),
+    RotateKey(RotateKeyCommand),
     // TODO: other commands
 }
@@ -0,0 +1,185 @@
+use alloy_primitives::{Address, Bytes, TxKind, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types::eth::{TransactionInput, TransactionRequest};
+use alloy_sol_types::{SolCall, SolEvent, SolType};
+use clap::Parser;
+use std::str::FromStr;
+
+use crate::{
+    command::Executable,
+    contract::{status_from_u8, ValidatorManagement, ValidatorRecord, VALIDATOR_MANAGER_ADDRESS},
+    signer::SignerArgs,
+    util::format_ether,
+};
+
+#[derive(Debug, Parser)]
+pub struct RotateKeyCommand {
+    /// RPC URL for gravity node
+    #[clap(long, env = "GRAVITY_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Gas limit for the transaction
+    #[clap(long, env = "GRAVITY_GAS_LIMIT")]
+    pub gas_limit: Option<u64>,
+
+    /// Gas price in wei
+    #[clap(long, env = "GRAVITY_GAS_PRICE")]
+    pub gas_price: Option<u128>,
+
+    /// StakePool address (validator identity)
+    #[clap(long)]
+    pub stake_pool: String,
+
+    /// New consensus public key (96 hex characters, 48 bytes BLS key)
+    #[clap(long)]
+    pub consensus_public_key: String,
+
+    /// Proof of possession for the new consensus key (192 hex characters, 96 bytes).
+    /// It can be found in the `consensus_pop` field of the identity.yaml
+    /// file generated by `gravity_cli genesis generate-key`.
+    #[clap(long)]
+    pub consensus_pop: String,
+
+    #[clap(flatten)]
+    pub signer: SignerArgs,
+}
+
+impl Executable for RotateKeyCommand {
+    fn execute(self) -> Result<(), anyhow::Error> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.execute_async())
+    }
+}
+
+/// Strips an optional `0x` and checks that `value` is `len` hex characters.
+fn parse_hex<'a>(value: &'a str, len: usize, label: &str) -> Result<&'a str, anyhow::Error> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    if value.len() != len {
+        return Err(anyhow::anyhow!(
+            "Invalid {label}: expected {len} hex characters ({} bytes), got {} characters",
+            len / 2,
+            value.len()
+        ));
+    }
+    if !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!("Invalid {label}: contains non-hexadecimal characters"));
+    }
+    Ok(value)
+}
+
+impl RotateKeyCommand {
+    async fn execute_async(self) -> Result<(), anyhow::Error> {
+        let rpc_url = self.rpc_url.ok_or_else(|| {
+            anyhow::anyhow!(
+                "--rpc-url is required. Set via CLI flag, GRAVITY_RPC_URL env var, or ~/.gravity/config.toml"
+            )
+        })?;
+        let gas_limit = self.gas_limit.unwrap_or(2_000_000);
+        let gas_price = self.gas_price.unwrap_or(100_000_000_000);
+        // Cryptographic PoP verification is performed on-chain by ValidatorManagement
+        let consensus_pk = parse_hex(&self.consensus_public_key, 96, "consensus public key")?;
+        let consensus_pop = parse_hex(&self.consensus_pop, 192, "consensus proof of possession")?;
+        let new_pubkey = hex::decode(consensus_pk)?;
+
+        // 1. Initialize Provider and Wallet
+        println!("1. Initializing connection...");
+
+        println!("   RPC URL: {rpc_url}");
+        let resolved = self.signer.resolve().await?;
+        let wallet_address = resolved.address;
+        println!("   Wallet address: {wallet_address:?}");
+
+        println!("   Contract address: {VALIDATOR_MANAGER_ADDRESS:?}");
+
+        let provider =
+            ProviderBuilder::new().wallet(resolved.wallet).connect_http(rpc_url.parse()?);
+
+        let chain_id = provider.get_chain_id().await?;
+        println!("   Chain ID: {chain_id}\n");
+
+        // 2. Check validator information
+        println!("2. Checking validator information...");
+        let stake_pool = Address::from_str(&self.stake_pool)?;
+        let call = ValidatorManagement::getValidatorCall { stakePool: stake_pool };
+        let input: Bytes = call.abi_encode().into();
+        let result = provider
+            .call(TransactionRequest {
+                from: Some(wallet_address),
+                to: Some(TxKind::Call(VALIDATOR_MANAGER_ADDRESS)),
+                input: TransactionInput::new(input),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("StakePool is not registered as a validator: {e}"))?;
+        let validator_record = <ValidatorRecord as SolType>::abi_decode(&result)
+            .map_err(|e| anyhow::anyhow!("Failed to decode validator record: {e}"))?;
+
+        println!("   Validator information:");
+        println!("   - Validator: {}", validator_record.validator);
+        println!("   - Moniker: {}", validator_record.moniker);
+        println!("   - Status: {:?}", status_from_u8(validator_record.status));
+        println!(
+            "   - Current consensus public key: {}",
+            hex::encode(&validator_record.consensusPubkey)
+        );
+        if validator_record.consensusPubkey.as_ref() == new_pubkey.as_slice() {
+            return Err(anyhow::anyhow!("The new consensus key is already the registered one"));
+        }
+        println!();
+
+        // 3. Rotate the consensus key
+        println!("3. Rotating consensus key...");
+        println!("   New consensus public key: {consensus_pk}");
+        let call = ValidatorManagement::rotateConsensusKeyCall {
+            stakePool: stake_pool,
+            newPubkey: new_pubkey.into(),
+            newPop: hex::decode(consensus_pop)?.into(),
+        };
+        let input: Bytes = call.abi_encode().into();
+        let pending_tx = provider
+            .send_transaction(TransactionRequest {
+                from: Some(wallet_address),
+                to: Some(TxKind::Call(VALIDATOR_MANAGER_ADDRESS)),
+                input: TransactionInput::new(input),
+                gas: Some(gas_limit),
+                gas_price: Some(gas_price),
+                ..Default::default()
+            })
+            .await?;
+        let tx_hash = *pending_tx.tx_hash();
+        println!("   Transaction hash: {tx_hash}");
+        let _ = pending_tx
+            .with_required_confirmations(2)
+            .with_timeout(Some(std::time::Duration::from_secs(60)))
+            .watch()
+            .await?;
+
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or(anyhow::anyhow!("Failed to get transaction receipt"))?;
+        println!(
+            "   Transaction confirmed, block number: {}",
+            receipt.block_number.ok_or(anyhow::anyhow!("Failed to get block number"))?
+        );
+        println!("   Gas used: {}", receipt.gas_used);
+        println!(
+            "   Transaction cost: {} ETH",
+            format_ether(U256::from(receipt.effective_gas_price) * U256::from(receipt.gas_used))
+        );
+
+        let rotated = receipt
+            .logs()
+            .iter()
+            .any(|log| ValidatorManagement::ConsensusKeyRotated::decode_log(&log.inner).is_ok());
+        if !rotated {
+            println!("   Key rotation event not found\n");
+            return Err(anyhow::anyhow!("Failed to find ConsensusKeyRotated event"));
+        }
+        println!("   Consensus key rotated!\n");
+
+        println!("The new key is used from the next epoch. The node loads it from its identity");
+        println!("file at the epoch boundary, so consensus_private_key there must be the new key.");
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use std::{env, fs, path::PathBuf};
+
+/// Exposes the linked gravity-reth revision as `GRETH_GIT_REV`. It is read from the workspace
+/// lock file so it always matches the revision actually compiled in.
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lock_path = manifest_dir.join("../../Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+
+    let rev = fs::read_to_string(&lock_path)
+        .ok()
+        .and_then(|lock| {
+            lock.lines().find_map(|line| {
+                line.strip_prefix("source = \"git+https://github.com/Galxe/gravity-reth")
+                    .and_then(|source| source.rsplit_once('#'))
+                    .map(|(_, rev)| rev.trim_end_matches('"').to_string())
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GRETH_GIT_REV={rev}");
+}
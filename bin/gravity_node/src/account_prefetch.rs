@@ -0,0 +1,202 @@
+//! Warms the state for the ordered blocks queued behind the executing ones.
+//!
+//! The execution loop queues the accounts a block touches once it has recovered its senders.
+//! A dedicated thread reads them from the post-state of the block's parent, the state the block
+//! executes on, or from the latest persisted state while the parent is not canonical yet. The
+//! reads only bring the accounts into the page cache, so execution does not pay for cold reads.
+//! The queue is bounded and a block that does not fit is not prefetched, so prefetching never
+//! holds back the push of a block to the pipe.
+
+use alloy_consensus::Transaction;
+use alloy_primitives::Address;
+use greth_compat::{reth_primitives::TransactionSigned, AccountStateReader, ChainStateReader};
+use std::{
+    collections::HashSet,
+    sync::{
+        mpsc::{self, SyncSender, TrySendError},
+        Arc,
+    },
+};
+use tracing::warn;
+
+/// Blocks waiting to be prefetched, beyond which blocks are not prefetched.
+const PREFETCH_QUEUE_CAPACITY: usize = 16;
+
+struct PrefetchRequest {
+    parent_number: u64,
+    accounts: HashSet<Address>,
+}
+
+pub(crate) struct AccountPrefetcher {
+    requests: SyncSender<PrefetchRequest>,
+}
+
+impl AccountPrefetcher {
+    /// Starts the prefetch thread, which stops once the prefetcher is dropped.
+    pub(crate) fn spawn(provider: Arc<dyn ChainStateReader>) -> Self {
+        let (requests, rx) = mpsc::sync_channel::<PrefetchRequest>(PREFETCH_QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("account-prefetch".to_string())
+            .spawn(move || {
+                while let Ok(request) = rx.recv() {
+                    prefetch(provider.as_ref(), &request);
+                }
+            })
+            .expect("failed to spawn the account prefetch thread");
+        Self { requests }
+    }
+
+    /// Queues the accounts of the child of block `parent_number`. Returns false if the queue
+    /// is full and they are not prefetched.
+    pub(crate) fn queue(&self, parent_number: u64, accounts: HashSet<Address>) -> bool {
+        match self.requests.try_send(PrefetchRequest { parent_number, accounts }) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Senders and recipients of a block's transactions.
+pub(crate) fn touched_accounts(
+    senders: &[Address],
+    transactions: &[TransactionSigned],
+) -> HashSet<Address> {
+    let recipients = transactions.iter().filter_map(|txn| txn.to());
+    senders.iter().copied().chain(recipients).collect()
+}
+
+/// Opens the state the child of `parent_number` executes on, falling back to the latest
+/// persisted state.
+fn parent_state(
+    provider: &dyn ChainStateReader,
+    parent_number: u64,
+) -> Option<Box<dyn AccountStateReader>> {
+    match provider.state_at(parent_number) {
+        Ok(Some(state)) => return Some(state),
+        Ok(None) => {}
+        Err(e) => warn!("failed to open the state at block {} for prefetch: {}", parent_number, e),
+    }
+    provider
+        .latest_state()
+        .inspect_err(|e| warn!("failed to open latest state for account prefetch: {}", e))
+        .ok()
+}
+
+fn prefetch(provider: &dyn ChainStateReader, request: &PrefetchRequest) {
+    let Some(state) = parent_state(provider, request.parent_number) else { return };
+    for account in &request.accounts {
+        let _ = state.account(account);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxLegacy};
+    use alloy_primitives::TxKind;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use greth_compat::AccountState;
+    use std::sync::Mutex;
+
+    /// Records which state every account read goes to.
+    #[derive(Default)]
+    struct RecordingProvider {
+        canonical: u64,
+        reads: Arc<Mutex<Vec<(&'static str, Address)>>>,
+    }
+
+    struct RecordingState {
+        name: &'static str,
+        reads: Arc<Mutex<Vec<(&'static str, Address)>>>,
+    }
+
+    impl AccountStateReader for RecordingState {
+        fn account(&self, address: &Address) -> anyhow::Result<Option<AccountState>> {
+            self.reads.lock().unwrap().push((self.name, *address));
+            Ok(None)
+        }
+
+        fn has_code(&self, _address: &Address) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+    }
+
+    impl RecordingProvider {
+        fn state(&self, name: &'static str) -> Box<dyn AccountStateReader> {
+            Box::new(RecordingState { name, reads: self.reads.clone() })
+        }
+    }
+
+    impl ChainStateReader for RecordingProvider {
+        fn chain_id(&self) -> u64 {
+            1
+        }
+
+        fn latest_block_number(&self) -> anyhow::Result<u64> {
+            Ok(self.canonical)
+        }
+
+        fn latest_state(&self) -> anyhow::Result<Box<dyn AccountStateReader>> {
+            Ok(self.state("latest"))
+        }
+
+        fn state_at(&self, number: u64) -> anyhow::Result<Option<Box<dyn AccountStateReader>>> {
+            Ok((number <= self.canonical).then(|| self.state("parent")))
+        }
+
+        fn block_gas_used(&self, _number: u64) -> anyhow::Result<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn reads_from_the_parent_state_once_it_is_canonical() {
+        let provider = RecordingProvider { canonical: 5, ..Default::default() };
+        let accounts = HashSet::from([Address::repeat_byte(1)]);
+        prefetch(&provider, &PrefetchRequest { parent_number: 5, accounts: accounts.clone() });
+        prefetch(&provider, &PrefetchRequest { parent_number: 6, accounts });
+        assert_eq!(
+            *provider.reads.lock().unwrap(),
+            vec![("parent", Address::repeat_byte(1)), ("latest", Address::repeat_byte(1))]
+        );
+    }
+
+    #[test]
+    fn a_full_queue_does_not_block() {
+        let (requests, _rx) = mpsc::sync_channel(1);
+        let prefetcher = AccountPrefetcher { requests };
+        assert!(prefetcher.queue(1, HashSet::new()));
+        assert!(!prefetcher.queue(2, HashSet::new()));
+    }
+
+    #[test]
+    fn prefetches_queued_blocks() {
+        let provider = Arc::new(RecordingProvider::default());
+        let reads = provider.reads.clone();
+        let prefetcher = AccountPrefetcher::spawn(provider);
+        assert!(prefetcher.queue(0, HashSet::from([Address::repeat_byte(2)])));
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while reads.lock().unwrap().is_empty() {
+            assert!(std::time::Instant::now() < deadline, "the queued block was not prefetched");
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(*reads.lock().unwrap(), vec![("parent", Address::repeat_byte(2))]);
+    }
+
+    #[test]
+    fn touches_senders_and_recipients_once() {
+        let signer = PrivateKeySigner::random();
+        let transfer = |to: TxKind| -> TransactionSigned {
+            let txn = TxLegacy { chain_id: Some(1), to, gas_limit: 21_000, ..Default::default() };
+            let signature = signer.sign_hash_sync(&txn.signature_hash()).unwrap();
+            txn.into_signed(signature).into()
+        };
+        let (sender, recipient) = (signer.address(), Address::repeat_byte(3));
+        let transactions = [transfer(TxKind::Call(recipient)), transfer(TxKind::Create)];
+        assert_eq!(
+            touched_accounts(&[sender, sender], &transactions),
+            HashSet::from([sender, recipient])
+        );
+    }
+}
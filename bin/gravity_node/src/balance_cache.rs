@@ -0,0 +1,242 @@
+use alloy_primitives::{Address, U256};
+use dashmap::{DashMap, DashSet};
+use gaptos::aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use once_cell::sync::Lazy;
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Upper bound on cached senders. Once reached, stale entries are swept before inserting and
+/// new senders are simply not cached (admission falls back to the pool) until space frees up.
+const BALANCE_CACHE_CAPACITY: usize = 100_000;
+
+const PRECHECK_OUTCOME_ADMIT: &str = "admit";
+const PRECHECK_OUTCOME_BYPASS: &str = "bypass";
+const PRECHECK_OUTCOME_INSUFFICIENT_BALANCE: &str = "insufficient_balance";
+const PRECHECK_OUTCOME_NONCE_TOO_LOW: &str = "nonce_too_low";
+
+static BALANCE_PRECHECK_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_mempool_balance_precheck_total",
+        "Outcome of the cached balance/nonce pre-check for external transactions",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+/// Maximum age of a cached sender snapshot before it is ignored.
+/// Can be configured via BALANCE_PRECHECK_STALENESS_MS environment variable, 0 disables the
+/// pre-check entirely.
+fn balance_precheck_staleness() -> Duration {
+    static STALENESS: OnceLock<Duration> = OnceLock::new();
+    *STALENESS.get_or_init(|| {
+        let ms = std::env::var("BALANCE_PRECHECK_STALENESS_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(2000); // Default 2000ms
+        Duration::from_millis(ms)
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AccountSnapshot {
+    balance: U256,
+    nonce: u64,
+    refreshed_at: Instant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PrecheckOutcome {
+    /// The cached state says the sender can pay for the transaction.
+    Admit,
+    /// No fresh state for the sender, let the pool validate it.
+    Bypass,
+    /// The sender's committed balance is below the transaction's maximum cost.
+    InsufficientBalance,
+    /// The nonce has already been used by a committed transaction.
+    NonceTooLow,
+}
+
+impl PrecheckOutcome {
+    pub(crate) fn is_rejected(self) -> bool {
+        matches!(self, Self::InsufficientBalance | Self::NonceTooLow)
+    }
+
+    fn as_label(self) -> &'static str {
+        match self {
+            Self::Admit => PRECHECK_OUTCOME_ADMIT,
+            Self::Bypass => PRECHECK_OUTCOME_BYPASS,
+            Self::InsufficientBalance => PRECHECK_OUTCOME_INSUFFICIENT_BALANCE,
+            Self::NonceTooLow => PRECHECK_OUTCOME_NONCE_TOO_LOW,
+        }
+    }
+}
+
+/// Committed balance/nonce of recently active senders, used to reject obviously unpayable
+/// external transactions without a round trip to the execution layer.
+///
+/// The cache is fed from commit events: senders and recipients of executed blocks are
+/// invalidated as soon as the execution result arrives, and refreshed from the latest state
+/// once the block is committed and persisted. Value moved by contract calls to other accounts
+/// is invisible to it, so snapshots older than the staleness bound are ignored rather than
+/// trusted.
+pub(crate) struct BalanceCache {
+    entries: DashMap<Address, AccountSnapshot>,
+    pending_refresh: DashSet<Address>,
+    max_staleness: Duration,
+    capacity: usize,
+}
+
+pub(crate) type SharedBalanceCache = Arc<BalanceCache>;
+
+impl BalanceCache {
+    pub(crate) fn new() -> Self {
+        Self::with_limits(balance_precheck_staleness(), BALANCE_CACHE_CAPACITY)
+    }
+
+    fn with_limits(max_staleness: Duration, capacity: usize) -> Self {
+        Self { entries: DashMap::new(), pending_refresh: DashSet::new(), max_staleness, capacity }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.max_staleness.is_zero()
+    }
+
+    /// Drops the cached state of accounts touched by transactions that were just executed and
+    /// schedules them for a refresh once the block is committed.
+    pub(crate) fn invalidate(&self, accounts: impl IntoIterator<Item = Address>) {
+        if !self.is_enabled() {
+            return;
+        }
+        for account in accounts {
+            self.entries.remove(&account);
+            if self.pending_refresh.len() < self.capacity {
+                self.pending_refresh.insert(account);
+            }
+        }
+    }
+
+    /// Drops every cached snapshot, e.g. when an epoch change may have moved the gas
+    /// parameters the snapshots were checked against.
+    pub(crate) fn clear(&self) {
+        self.entries.clear();
+        self.pending_refresh.clear();
+    }
+
+    /// Returns and clears the accounts waiting for a refresh.
+    pub(crate) fn take_pending_refresh(&self) -> Vec<Address> {
+        let senders: Vec<Address> = self.pending_refresh.iter().map(|sender| *sender).collect();
+        for sender in &senders {
+            self.pending_refresh.remove(sender);
+        }
+        senders
+    }
+
+    pub(crate) fn update(&self, sender: Address, balance: U256, nonce: u64) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&sender) {
+            let now = Instant::now();
+            self.entries.retain(|_, snapshot| {
+                now.duration_since(snapshot.refreshed_at) <= self.max_staleness
+            });
+            if self.entries.len() >= self.capacity {
+                return;
+            }
+        }
+        self.entries
+            .insert(sender, AccountSnapshot { balance, nonce, refreshed_at: Instant::now() });
+    }
+
+    /// Checks a transaction against the cached committed state of its sender. Anything the
+    /// cache cannot answer with confidence is bypassed to the pool's own validation.
+    pub(crate) fn precheck(&self, sender: Address, nonce: u64, max_cost: U256) -> PrecheckOutcome {
+        let outcome = self.classify(sender, nonce, max_cost);
+        BALANCE_PRECHECK_TOTAL.with_label_values(&[outcome.as_label()]).inc();
+        outcome
+    }
+
+    fn classify(&self, sender: Address, nonce: u64, max_cost: U256) -> PrecheckOutcome {
+        if !self.is_enabled() {
+            return PrecheckOutcome::Bypass;
+        }
+        let Some(snapshot) = self.entries.get(&sender).map(|entry| *entry) else {
+            return PrecheckOutcome::Bypass;
+        };
+        if snapshot.refreshed_at.elapsed() > self.max_staleness {
+            return PrecheckOutcome::Bypass;
+        }
+        if nonce < snapshot.nonce {
+            PrecheckOutcome::NonceTooLow
+        } else if snapshot.balance < max_cost {
+            PrecheckOutcome::InsufficientBalance
+        } else {
+            PrecheckOutcome::Admit
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> BalanceCache {
+        BalanceCache::with_limits(Duration::from_secs(60), 2)
+    }
+
+    #[test]
+    fn unknown_sender_is_bypassed() {
+        let cache = cache();
+        assert_eq!(cache.classify(Address::ZERO, 0, U256::from(1)), PrecheckOutcome::Bypass);
+    }
+
+    #[test]
+    fn rejects_unpayable_and_used_nonces() {
+        let cache = cache();
+        let sender = Address::repeat_byte(1);
+        cache.update(sender, U256::from(100), 5);
+
+        assert_eq!(cache.classify(sender, 5, U256::from(100)), PrecheckOutcome::Admit);
+        assert_eq!(
+            cache.classify(sender, 7, U256::from(101)),
+            PrecheckOutcome::InsufficientBalance
+        );
+        assert_eq!(cache.classify(sender, 4, U256::from(1)), PrecheckOutcome::NonceTooLow);
+    }
+
+    #[test]
+    fn invalidated_sender_is_bypassed_until_refreshed() {
+        let cache = cache();
+        let sender = Address::repeat_byte(1);
+        cache.update(sender, U256::ZERO, 0);
+        cache.invalidate([sender]);
+
+        assert_eq!(cache.classify(sender, 0, U256::from(1)), PrecheckOutcome::Bypass);
+        assert_eq!(cache.take_pending_refresh(), vec![sender]);
+        assert!(cache.take_pending_refresh().is_empty());
+    }
+
+    #[test]
+    fn stale_or_disabled_cache_is_bypassed() {
+        let sender = Address::repeat_byte(1);
+
+        let disabled = BalanceCache::with_limits(Duration::ZERO, 2);
+        disabled.update(sender, U256::ZERO, 0);
+        assert_eq!(disabled.classify(sender, 0, U256::from(1)), PrecheckOutcome::Bypass);
+
+        let stale = BalanceCache::with_limits(Duration::from_millis(1), 2);
+        stale.update(sender, U256::ZERO, 0);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(stale.classify(sender, 0, U256::from(1)), PrecheckOutcome::Bypass);
+    }
+
+    #[test]
+    fn full_cache_skips_new_senders() {
+        let cache = cache();
+        cache.update(Address::repeat_byte(1), U256::ZERO, 0);
+        cache.update(Address::repeat_byte(2), U256::ZERO, 0);
+        cache.update(Address::repeat_byte(3), U256::ZERO, 0);
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(!cache.entries.contains_key(&Address::repeat_byte(3)));
+    }
+}
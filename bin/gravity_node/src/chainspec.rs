@@ -30,8 +30,8 @@
 //! going through this parser silently skips the override. Concretely this
 //! includes:
 //!
-//! - `greth::reth_chainspec::ChainSpecBuilder` and direct struct-literal construction in tests /
-//!   fixtures inside greth.
+//! - greth's `ChainSpecBuilder` and direct struct-literal construction in tests / fixtures inside
+//!   greth.
 //! - A future CLI command that bypasses [`crate::cli::Cli`].
 //! - Anywhere in this binary that calls `From<Genesis> for ChainSpec` directly on a hand-built
 //!   `Genesis` for a gravity chain id.
@@ -52,7 +52,7 @@
 //! is no in-place override path by design.
 
 use alloy_genesis::Genesis;
-use greth::{
+use greth_compat::{
     reth::chainspec::EthereumChainSpecParser,
     reth_chainspec::ChainSpec,
     reth_cli::chainspec::{parse_genesis, ChainSpecParser},
@@ -221,7 +221,7 @@ fn apply_overrides(g: &mut Genesis) -> Vec<OverrideEvent> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use greth::reth_chainspec::{
+    use greth_compat::reth_chainspec::{
         ChainSpecBuilder, EthereumHardfork, EthereumHardforks, ForkCondition, GravityHardfork,
     };
 
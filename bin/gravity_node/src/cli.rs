@@ -2,7 +2,7 @@ use crate::chainspec::GravityChainSpecParser;
 use api::GravityNodeArgs;
 use build_info::{build_information, BUILD_PKG_VERSION};
 use clap::{value_parser, Parser};
-use greth::{
+use greth_compat::{
     reth::cli::Commands,
     reth_chainspec::ChainSpec,
     reth_cli::chainspec::ChainSpecParser,
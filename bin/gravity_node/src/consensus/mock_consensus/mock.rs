@@ -6,13 +6,16 @@ use std::{
 };
 use tracing::{info, warn};
 
-use super::mempool::{Mempool, TxnId};
+use super::{
+    mempool::{Mempool, TxnId},
+    simulation::{Leader, SimulatedNetwork, SimulationConfig},
+};
 use gaptos::api_types::{
     account::ExternalAccountAddress, events::contract_event::GravityEvent, u256_define::BlockId,
     ExternalBlock, ExternalBlockMeta, ExternalPayloadAttr, VerifiedTxn,
 };
 
-use block_buffer_manager::{block_buffer_manager::BlockHashRef, get_block_buffer_manager, TxPool};
+use block_buffer_manager::{block_buffer_manager::BlockHashRef, BlockBufferManager, TxPool};
 
 pub struct MockConsensus {
     pool: Arc<tokio::sync::Mutex<Mempool>>,
@@ -20,6 +23,9 @@ pub struct MockConsensus {
     executed_jam_wait: Arc<(Mutex<u64>, Condvar)>,
     epoch: Arc<AtomicU64>,
     epoch_start_block_number: Arc<AtomicU64>,
+    /// One per in-process node, all ordered the same blocks.
+    nodes: Vec<Arc<BlockBufferManager>>,
+    simulation: SimulationConfig,
 }
 
 static ORDERED_INTERVAL_MS: OnceLock<u64> = OnceLock::new();
@@ -47,19 +53,31 @@ fn get_max_executed_gap() -> u64 {
 }
 
 impl MockConsensus {
-    pub async fn new(pool: Box<dyn TxPool>) -> Self {
+    pub async fn new(pool: Box<dyn TxPool>, block_buffer_manager: Arc<BlockBufferManager>) -> Self {
+        Self::with_nodes(pool, vec![block_buffer_manager], SimulationConfig::from_env()).await
+    }
+
+    /// Orders blocks across several in-process nodes, drawing transactions from `pool`. A block
+    /// is committed once every node executed it to the same hash.
+    pub async fn with_nodes(
+        pool: Box<dyn TxPool>,
+        nodes: Vec<Arc<BlockBufferManager>>,
+        simulation: SimulationConfig,
+    ) -> Self {
+        assert!(!nodes.is_empty(), "mock consensus needs at least one node");
         let genesis_block_id = BlockId([
             141, 91, 216, 66, 168, 139, 218, 32, 132, 186, 161, 251, 250, 51, 34, 197, 38, 71, 196,
             135, 49, 116, 247, 25, 67, 147, 163, 137, 28, 58, 62, 73,
         ]);
-        let mut block_number_to_block_id = HashMap::new();
-        // Genesis block is at epoch 0
-        block_number_to_block_id.insert(0u64, (0, genesis_block_id));
-        // Initialize with epoch 1 to match the mock consensus epoch
-        get_block_buffer_manager()
-            .init(0, block_number_to_block_id, 1)
-            .await
-            .expect("failed to initialize BlockBufferManager in mock consensus");
+        for node in &nodes {
+            let mut block_number_to_block_id = HashMap::new();
+            // Genesis block is at epoch 0
+            block_number_to_block_id.insert(0u64, (0, genesis_block_id));
+            // Initialize with epoch 1 to match the mock consensus epoch
+            node.init(0, block_number_to_block_id, 1)
+                .await
+                .expect("failed to initialize BlockBufferManager in mock consensus");
+        }
 
         Self {
             pool: Arc::new(tokio::sync::Mutex::new(Mempool::new(pool))),
@@ -67,6 +85,8 @@ impl MockConsensus {
             executed_jam_wait: Arc::new((Mutex::new(0), Condvar::new())),
             epoch: Arc::new(AtomicU64::new(1)),
             epoch_start_block_number: Arc::new(AtomicU64::new(0)),
+            nodes,
+            simulation,
         }
     }
 
@@ -75,6 +95,7 @@ impl MockConsensus {
         txns: Vec<VerifiedTxn>,
         attr: ExternalPayloadAttr,
         epoch: u64,
+        leader: &Leader,
     ) -> ExternalBlock {
         let mut hasher = DefaultHasher::new();
         txns.hash(&mut hasher);
@@ -91,9 +112,8 @@ impl MockConsensus {
                 epoch,
                 randomness: None,
                 block_hash: None,
-                // Mock consensus uses index 0 (first validator in the mock set)
-                proposer_index: Some(0),
-                failed_proposer_indices: vec![],
+                proposer_index: Some(leader.proposer_index),
+                failed_proposer_indices: leader.failed_proposer_indices.clone(),
             },
             txns,
             extra_data: Vec::new(), // TODO: add validator transaction extra_data (DKG, JWK)
@@ -106,6 +126,7 @@ impl MockConsensus {
         block_number: u64,
         attr: ExternalPayloadAttr,
         epoch: u64,
+        leader: &Leader,
     ) -> ExternalBlock {
         let max_txn_num: usize = get_max_txn_num();
         let mut txns = Vec::with_capacity(max_txn_num);
@@ -114,12 +135,12 @@ impl MockConsensus {
                 SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() -
                     attr.ts;
             if time_gap > 1 {
-                return Self::construct_block(block_number, txns, attr, epoch);
+                return Self::construct_block(block_number, txns, attr, epoch, leader);
             }
             let has_new_txn = pool.lock().await.get_txns(&mut txns, max_txn_num);
             if !has_new_txn {
                 if !txns.is_empty() {
-                    return Self::construct_block(block_number, txns, attr, epoch);
+                    return Self::construct_block(block_number, txns, attr, epoch, leader);
                 } else {
                     tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
                     continue;
@@ -127,7 +148,7 @@ impl MockConsensus {
             }
 
             if txns.len() > max_txn_num {
-                return Self::construct_block(block_number, txns, attr, epoch);
+                return Self::construct_block(block_number, txns, attr, epoch, leader);
             }
         }
     }
@@ -135,6 +156,8 @@ impl MockConsensus {
     pub async fn run(mut self) {
         let (block_meta_tx, mut block_meta_rx) = tokio::sync::mpsc::channel(8);
         let epoch_start_block_number = self.epoch_start_block_number.clone();
+        let mut network = SimulatedNetwork::new(self.simulation.clone(), &self.nodes);
+        let link_failure = network.failure();
         tokio::spawn({
             let pool = self.pool.clone();
             let mut parent_id = self.genesis_block_id;
@@ -147,7 +170,7 @@ impl MockConsensus {
                 loop {
                     if current_epoch != epoch.load(std::sync::atomic::Ordering::SeqCst) {
                         current_epoch = epoch.load(std::sync::atomic::Ordering::SeqCst);
-                        get_block_buffer_manager().release_inflight_blocks().await;
+                        network.release_inflight_blocks();
                         let mut pool = pool.lock().await;
                         pool.reset_epoch();
                         drop(pool);
@@ -161,19 +184,18 @@ impl MockConsensus {
                             .unwrap()
                             .as_secs(),
                     };
+                    let leader = network.next_leader();
                     let block = Self::check_and_construct_block(
                         &pool,
                         block_number,
                         attr.clone(),
                         current_epoch,
+                        &leader,
                     )
                     .await;
 
                     let head_meta = block.block_meta.clone();
-                    get_block_buffer_manager()
-                        .set_ordered_blocks(parent_id, block, 0)
-                        .await
-                        .unwrap();
+                    network.order(parent_id, &block, leader.round);
                     parent_id = head_meta.block_id;
                     let _ = block_meta_tx.send(head_meta).await;
                     // wait if there's large gap between executed block and ordered block
@@ -217,23 +239,39 @@ impl MockConsensus {
             let block_number = block_meta.block_number;
             let epoch = block_meta.epoch;
 
-            let res = loop {
-                match get_block_buffer_manager()
-                    .get_executed_res(block_id, block_number, epoch)
-                    .await
-                {
-                    Ok(r) => {
-                        break r;
-                    }
-                    Err(e) => {
-                        let msg = format!("{e}");
-                        warn!("get executed result failed: {}", msg);
-                        if !msg.contains("get_executed_res timeout") {
-                            panic!("get executed result failed: {msg}");
+            let mut results = Vec::with_capacity(self.nodes.len());
+            for (index, node) in self.nodes.iter().enumerate() {
+                let res = loop {
+                    match node.get_executed_res(block_id, block_number, epoch).await {
+                        Ok(r) => {
+                            break r;
+                        }
+                        Err(e) => {
+                            let msg = format!("{e}");
+                            warn!("get executed result failed: {}", msg);
+                            if !msg.contains("get_executed_res timeout") {
+                                panic!("get executed result failed: {msg}");
+                            }
+                            // The node may never receive the block
+                            if let Some(failure) = link_failure.get() {
+                                panic!(
+                                    "node {index} cannot execute block {block_number}: {failure}"
+                                );
+                            }
                         }
                     }
-                }
-            };
+                };
+                results.push(res);
+            }
+            let res = results.swap_remove(0);
+            for (index, other) in results.iter().enumerate() {
+                assert_eq!(
+                    other.execution_output.data,
+                    res.execution_output.data,
+                    "node {} diverged from node 0 at block {block_number}",
+                    index + 1
+                );
+            }
 
             {
                 let (lock, cvar) = self.executed_jam_wait.as_ref();
@@ -248,7 +286,9 @@ impl MockConsensus {
                 hash: Some(res.execution_output.data),
                 persist_notifier: None,
             }];
-            get_block_buffer_manager().set_commit_blocks(&commit_blocks, epoch).await.unwrap();
+            for node in &self.nodes {
+                node.set_commit_blocks(&commit_blocks, epoch).await.unwrap();
+            }
             self.process_epoch_change(&res.execution_output.events, block_number);
             let committed_txns = res
                 .execution_output
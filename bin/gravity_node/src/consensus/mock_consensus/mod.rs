@@ -1,2 +1,3 @@
 pub mod mempool;
 pub mod mock;
+pub mod simulation;
@@ -0,0 +1,337 @@
+//! Deterministic multi-node mode of the mock consensus.
+//!
+//! The mock leader orders every block to several in-process nodes, each behind its own
+//! `BlockBufferManager`, through a simulated link with a configurable latency. Faults are drawn
+//! from a seeded generator, so a seed replays the same skipped rounds, duplicate deliveries and
+//! delays at the same blocks. This exercises the execution layer integration of several nodes
+//! without AptosBFT networking.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use block_buffer_manager::{block_buffer_manager::BlockExecutionMeta, BlockBufferManager};
+use gaptos::api_types::{u256_define::BlockId, ExternalBlock};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{error, info};
+
+/// Consecutive rounds a single block may skip, so a skip rate of 1000 per mille still orders
+/// blocks.
+const MAX_SKIPPED_ROUNDS: usize = 8;
+
+#[derive(Clone, Debug, Default)]
+pub struct SimulationConfig {
+    /// Seed of the fault and latency schedule.
+    pub seed: u64,
+    /// Delay before node `i` receives an ordered block, zero for nodes past the end.
+    pub latencies: Vec<Duration>,
+    /// Random extra delay, up to this, added per node and block.
+    pub jitter: Duration,
+    /// Chance, per mille, that the leader of a round fails and the round is skipped.
+    pub skip_round_per_mille: u32,
+    /// Chance, per mille, that a node receives an ordered block twice.
+    pub duplicate_delivery_per_mille: u32,
+}
+
+fn env_u64(name: &str) -> u64 {
+    std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(0)
+}
+
+impl SimulationConfig {
+    /// Configured via MOCK_SIM_SEED, MOCK_SIM_LATENCIES_MS (comma separated, one per node),
+    /// MOCK_SIM_JITTER_MS, MOCK_SIM_SKIP_ROUND_PER_MILLE and MOCK_SIM_DUPLICATE_PER_MILLE. All
+    /// default to zero, which orders every block once and without delay.
+    pub fn from_env() -> Self {
+        let latencies = std::env::var("MOCK_SIM_LATENCIES_MS")
+            .map(|s| {
+                s.split(',')
+                    .map(|ms| Duration::from_millis(ms.trim().parse().unwrap_or(0)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            seed: env_u64("MOCK_SIM_SEED"),
+            latencies,
+            jitter: Duration::from_millis(env_u64("MOCK_SIM_JITTER_MS")),
+            skip_round_per_mille: env_u64("MOCK_SIM_SKIP_ROUND_PER_MILLE") as u32,
+            duplicate_delivery_per_mille: env_u64("MOCK_SIM_DUPLICATE_PER_MILLE") as u32,
+        }
+    }
+}
+
+/// SplitMix64, so a seed replays the same schedule on every platform and dependency version.
+struct FaultRng(u64);
+
+impl FaultRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn per_mille(&mut self, chance: u32) -> bool {
+        self.next_u64() % 1000 < chance as u64
+    }
+
+    fn up_to(&mut self, max: Duration) -> Duration {
+        let max_nanos = max.as_nanos() as u64;
+        if max_nanos == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.next_u64() % (max_nanos + 1))
+    }
+}
+
+/// Proposer of a block and the proposers of the rounds skipped before it.
+pub struct Leader {
+    pub round: u64,
+    pub proposer_index: u64,
+    pub failed_proposer_indices: Vec<u64>,
+}
+
+enum LinkMessage {
+    Ordered { parent_id: BlockId, block: ExternalBlock, round: u64, duplicate: bool },
+    ReleaseInflightBlocks,
+}
+
+/// First link that stopped, after which its node never receives the blocks behind. Waiting for
+/// such a node to execute a block would never end, so the mock checks it on every timeout.
+#[derive(Clone, Default)]
+pub struct LinkFailure(Arc<Mutex<Option<String>>>);
+
+impl LinkFailure {
+    fn set(&self, failure: String) {
+        error!("mock consensus link failed: {}", failure);
+        self.0.lock().unwrap().get_or_insert(failure);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Links from the mock leader to every node. Each link delivers in order, so a delayed block
+/// holds back the blocks behind it, like a TCP stream would.
+pub struct SimulatedNetwork {
+    config: SimulationConfig,
+    rng: FaultRng,
+    round: u64,
+    links: Vec<mpsc::UnboundedSender<(Instant, LinkMessage)>>,
+    failure: LinkFailure,
+}
+
+impl SimulatedNetwork {
+    pub fn new(config: SimulationConfig, nodes: &[Arc<BlockBufferManager>]) -> Self {
+        let failure = LinkFailure::default();
+        let links = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                let link = tokio::spawn(Self::run_link(index, node.clone(), rx));
+                let failure = failure.clone();
+                tokio::spawn(async move {
+                    match link.await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => failure.set(e),
+                        Err(e) => failure.set(format!("node {index}: link task failed: {e}")),
+                    }
+                });
+                tx
+            })
+            .collect();
+        Self { rng: FaultRng(config.seed), config, round: 0, links, failure }
+    }
+
+    /// Set once a link stopped delivering.
+    pub fn failure(&self) -> LinkFailure {
+        self.failure.clone()
+    }
+
+    async fn run_link(
+        index: usize,
+        node: Arc<BlockBufferManager>,
+        mut rx: mpsc::UnboundedReceiver<(Instant, LinkMessage)>,
+    ) -> Result<(), String> {
+        while let Some((deliver_at, message)) = rx.recv().await {
+            tokio::time::sleep_until(deliver_at).await;
+            match message {
+                LinkMessage::Ordered { parent_id, block, round, duplicate } => {
+                    let copies = if duplicate { 2 } else { 1 };
+                    for _ in 0..copies {
+                        node.set_ordered_blocks(
+                            parent_id,
+                            block.clone(),
+                            round,
+                            BlockExecutionMeta::default(),
+                        )
+                        .await
+                        .map_err(|e| format!("node {index}: set_ordered_blocks failed: {e}"))?;
+                    }
+                }
+                LinkMessage::ReleaseInflightBlocks => node.release_inflight_blocks().await,
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks the proposer of the next block, skipping the rounds whose leader fails.
+    pub fn next_leader(&mut self) -> Leader {
+        let validators = self.links.len() as u64;
+        let mut failed_proposer_indices = vec![];
+        while failed_proposer_indices.len() < MAX_SKIPPED_ROUNDS &&
+            self.rng.per_mille(self.config.skip_round_per_mille)
+        {
+            failed_proposer_indices.push(self.round % validators);
+            self.round += 1;
+        }
+        if !failed_proposer_indices.is_empty() {
+            info!("mock consensus skipped rounds of proposers {:?}", failed_proposer_indices);
+        }
+        let leader = Leader {
+            round: self.round,
+            proposer_index: self.round % validators,
+            failed_proposer_indices,
+        };
+        self.round += 1;
+        leader
+    }
+
+    /// Sends an ordered block to every node with the latency of its link.
+    pub fn order(&mut self, parent_id: BlockId, block: &ExternalBlock, round: u64) {
+        let now = Instant::now();
+        for (index, link) in self.links.iter().enumerate() {
+            let latency = self.config.latencies.get(index).copied().unwrap_or_default();
+            let deliver_at = now + latency + self.rng.up_to(self.config.jitter);
+            let duplicate = self.rng.per_mille(self.config.duplicate_delivery_per_mille);
+            let message =
+                LinkMessage::Ordered { parent_id, block: block.clone(), round, duplicate };
+            let _ = link.send((deliver_at, message));
+        }
+    }
+
+    /// Releases the blocks ordered past an epoch change, once every node received them.
+    pub fn release_inflight_blocks(&self) {
+        for link in &self.links {
+            let _ = link.send((Instant::now(), LinkMessage::ReleaseInflightBlocks));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use block_buffer_manager::block_buffer_manager::BlockBufferManagerConfig;
+    use gaptos::api_types::ExternalBlockMeta;
+    use std::collections::HashMap;
+
+    fn block(block_number: u64, epoch: u64) -> ExternalBlock {
+        ExternalBlock {
+            block_meta: ExternalBlockMeta {
+                block_id: BlockId([block_number as u8; 32]),
+                block_number,
+                usecs: block_number,
+                epoch,
+                randomness: None,
+                block_hash: None,
+                proposer_index: None,
+                failed_proposer_indices: vec![],
+            },
+            txns: vec![],
+            extra_data: Vec::new(),
+            enable_randomness: false,
+        }
+    }
+
+    async fn nodes(count: usize) -> Vec<Arc<BlockBufferManager>> {
+        let mut nodes = vec![];
+        for _ in 0..count {
+            let node = BlockBufferManager::new(BlockBufferManagerConfig::default());
+            node.init(0, HashMap::from([(0, (0, BlockId([0; 32])))]), 1).await.unwrap();
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    #[tokio::test]
+    async fn every_node_receives_the_ordered_blocks() {
+        let nodes = nodes(3).await;
+        let config = SimulationConfig {
+            seed: 7,
+            latencies: vec![Duration::ZERO, Duration::from_millis(20)],
+            jitter: Duration::from_millis(10),
+            skip_round_per_mille: 0,
+            duplicate_delivery_per_mille: 1000,
+        };
+        let mut network = SimulatedNetwork::new(config, &nodes);
+        for block_number in 1..=3 {
+            let leader = network.next_leader();
+            network.order(
+                BlockId([block_number as u8 - 1; 32]),
+                &block(block_number, 1),
+                leader.round,
+            );
+        }
+
+        for node in &nodes {
+            // Each link delivers the blocks one after the other
+            let mut numbers = vec![];
+            while numbers.len() < 3 {
+                let ordered = node.get_ordered_blocks(numbers.len() as u64 + 1, None, 1).await;
+                numbers.extend(
+                    ordered.unwrap().iter().map(|(block, _, _)| block.block_meta.block_number),
+                );
+            }
+            assert_eq!(numbers, vec![1, 2, 3]);
+        }
+        assert_eq!(network.failure().get(), None);
+    }
+
+    #[tokio::test]
+    async fn a_seed_replays_the_skipped_rounds() {
+        let nodes = nodes(4).await;
+        let config = SimulationConfig { seed: 42, skip_round_per_mille: 500, ..Default::default() };
+        let leaders = |network: &mut SimulatedNetwork| -> Vec<(u64, u64, Vec<u64>)> {
+            (0..32)
+                .map(|_| network.next_leader())
+                .map(|leader| (leader.round, leader.proposer_index, leader.failed_proposer_indices))
+                .collect()
+        };
+        let first = leaders(&mut SimulatedNetwork::new(config.clone(), &nodes));
+        assert_eq!(first, leaders(&mut SimulatedNetwork::new(config, &nodes)));
+        assert!(first.iter().any(|(_, _, failed)| !failed.is_empty()));
+        for (round, proposer, failed) in &first {
+            assert_eq!(*proposer, round % 4);
+            assert!(failed.len() <= MAX_SKIPPED_ROUNDS);
+        }
+
+        let config = SimulationConfig { skip_round_per_mille: 1000, ..Default::default() };
+        let leader = SimulatedNetwork::new(config, &nodes).next_leader();
+        assert_eq!(leader.round, MAX_SKIPPED_ROUNDS as u64);
+        assert_eq!(leader.failed_proposer_indices, vec![0, 1, 2, 3, 0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn a_failed_link_is_reported() {
+        let nodes = nodes(2).await;
+        let mut network = SimulatedNetwork::new(SimulationConfig::default(), &nodes);
+        let failure = network.failure();
+        // The nodes are at epoch 1, a block of a future epoch is an error
+        network.order(BlockId([0; 32]), &block(1, 3), 0);
+
+        let failure = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(failure) = failure.get() {
+                    break failure;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(failure.contains("set_ordered_blocks failed"), "{failure}");
+    }
+}
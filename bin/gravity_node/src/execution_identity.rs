@@ -0,0 +1,130 @@
+use alloy_primitives::B256;
+use gaptos::aptos_metrics_core::{register_int_gauge_vec, IntGaugeVec};
+use once_cell::sync::Lazy;
+use std::{fmt, fs, io, path::Path};
+
+/// File in the consensus storage directory recording the execution layer genesis hash the node
+/// first started with.
+const GENESIS_HASH_FILE: &str = "execution_genesis_hash";
+
+static GRAVITY_EXECUTION_LAYER_INFO: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "gravity_execution_layer_info",
+        "Identity reported by the execution layer at startup",
+        &["greth_rev", "genesis_hash", "features"],
+    )
+    .unwrap()
+});
+
+/// Identity the execution layer reports when it hands its APIs over to consensus, used to
+/// catch mixed-version deployments at boot instead of at the first diverging block.
+#[derive(Clone, Debug)]
+pub(crate) struct ExecutionLayerIdentity {
+    /// gravity-reth revision compiled into this binary.
+    pub greth_rev: &'static str,
+    /// Hash of the genesis block of the loaded chain spec.
+    pub genesis_hash: B256,
+    /// Cargo features that change execution behaviour.
+    pub features: Vec<&'static str>,
+}
+
+impl ExecutionLayerIdentity {
+    pub(crate) fn new(genesis_hash: B256) -> Self {
+        let mut features = Vec::new();
+        if cfg!(feature = "randomness_disabled") {
+            features.push("randomness_disabled");
+        }
+        Self { greth_rev: env!("GRETH_GIT_REV"), genesis_hash, features }
+    }
+
+    /// Logs the identity and exports it as an info metric.
+    pub(crate) fn register(&self) {
+        tracing::info!("Execution layer identity: {}", self);
+        GRAVITY_EXECUTION_LAYER_INFO
+            .with_label_values(&[
+                self.greth_rev,
+                &self.genesis_hash.to_string(),
+                &self.features.join(","),
+            ])
+            .set(1);
+    }
+
+    /// Checks the reported genesis hash against the one consensus was configured with.
+    pub(crate) fn verify_genesis(&self, expected: Option<&str>) -> Result<(), String> {
+        let Some(expected) = expected else {
+            return Ok(());
+        };
+        let expected: B256 = expected
+            .parse()
+            .map_err(|e| format!("invalid expected genesis hash {expected}: {e}"))?;
+        if expected != self.genesis_hash {
+            return Err(format!(
+                "execution layer genesis hash {} does not match expected consensus genesis {}",
+                self.genesis_hash, expected
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the reported genesis hash against the one recorded in the consensus storage
+    /// directory `dir`, so a node swapped onto another chain's execution layer is caught without
+    /// an expected hash being configured. The first start records the hash.
+    pub(crate) fn verify_recorded_genesis(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(GENESIS_HASH_FILE);
+        match fs::read_to_string(&path) {
+            Ok(recorded) => self.verify_genesis(Some(recorded.trim())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                fs::create_dir_all(dir)
+                    .and_then(|_| fs::write(&path, self.genesis_hash.to_string()))
+                    .map_err(|e| format!("failed to record genesis hash in {path:?}: {e}"))?;
+                tracing::info!("Recorded execution layer genesis hash {}", self.genesis_hash);
+                Ok(())
+            }
+            Err(err) => Err(format!("failed to read recorded genesis hash {path:?}: {err}")),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionLayerIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "greth_rev={} genesis_hash={} features=[{}]",
+            self.greth_rev,
+            self.genesis_hash,
+            self.features.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_genesis_accepts_match_or_unset() {
+        let identity = ExecutionLayerIdentity::new(B256::repeat_byte(0xab));
+
+        assert!(identity.verify_genesis(None).is_ok());
+        assert!(identity.verify_genesis(Some(&B256::repeat_byte(0xab).to_string())).is_ok());
+    }
+
+    #[test]
+    fn verify_genesis_rejects_mismatch_and_garbage() {
+        let identity = ExecutionLayerIdentity::new(B256::repeat_byte(0xab));
+
+        assert!(identity.verify_genesis(Some(&B256::repeat_byte(0xcd).to_string())).is_err());
+        assert!(identity.verify_genesis(Some("not-a-hash")).is_err());
+    }
+    #[test]
+    fn verify_recorded_genesis_records_then_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = ExecutionLayerIdentity::new(B256::repeat_byte(0xab));
+
+        // The first start records the hash, later starts must match it
+        assert!(identity.verify_recorded_genesis(dir.path()).is_ok());
+        assert!(identity.verify_recorded_genesis(dir.path()).is_ok());
+        let other = ExecutionLayerIdentity::new(B256::repeat_byte(0xcd));
+        assert!(other.verify_recorded_genesis(dir.path()).is_err());
+    }
+}
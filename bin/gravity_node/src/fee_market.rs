@@ -0,0 +1,310 @@
+//! Fee priority under congestion. reth bounds each sub-pool on its own, so a pool full of
+//! cheap nonce-gapped or underpriced transactions keeps rejecting better paying ones until
+//! they age out. Once the pool as a whole passes its high watermark, the sweep evicts the
+//! non-ready transactions (reth's queued and basefee sub-pools) that pay the least, down to
+//! the low watermark. A sender's transactions are evicted from its highest nonce down, since
+//! evicting a lower one would leave the higher ones behind a nonce gap.
+//!
+//! Transactions are compared by their max fee per gas rather than reth's ranking score, the tip
+//! they pay at the current base fee. Non-ready transactions are the ones that cannot pay the
+//! current base fee or wait on a nonce gap: the former have no tip at all, so the ranking score
+//! would not tell them apart, and both only execute at some later base fee. The max fee is what
+//! a transaction is willing to pay whatever the base fee is then.
+//!
+//! The sweep also quotes the minimum viable gas price, served by `gravity_minGasPrice`: the
+//! pending base fee while there is room among the pending transactions, otherwise one wei more
+//! than the cheapest pending transaction pays.
+
+use alloy_consensus::Transaction;
+use alloy_primitives::{Address, TxHash, U256};
+use gaptos::aptos_metrics_core::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use greth_compat::reth_transaction_pool::{PoolConfig, TransactionPool};
+use jsonrpsee::{types::ErrorObjectOwned, RpcModule};
+use once_cell::sync::Lazy;
+use runtime_config::{Knob, RuntimeConfigRegistry};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::{Arc, OnceLock, RwLock},
+    time::Duration,
+};
+
+static FEE_EVICTED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "gravity_mempool_fee_evicted_txns_total",
+        "Non-ready transactions evicted for paying the least while the pool was full"
+    )
+    .unwrap()
+});
+
+static MIN_GAS_PRICE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "gravity_mempool_min_gas_price",
+        "Minimum gas price a transaction needs to enter the pending sub-pool"
+    )
+    .unwrap()
+});
+
+/// How often the pool is checked against its watermarks and the gas price is quoted.
+pub(crate) const FEE_MARKET_SWEEP_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Fee eviction knobs, changeable at runtime:
+/// - `mempool_fee_eviction_high_watermark_pct`: share of the pool capacity, in percent, above which
+///   the pool counts as full. MEMPOOL_FEE_EVICTION_HIGH_WATERMARK_PCT, default 90.
+/// - `mempool_fee_eviction_low_watermark_pct`: share the sweep evicts down to, at most the high
+///   watermark. MEMPOOL_FEE_EVICTION_LOW_WATERMARK_PCT, default 80.
+/// - `mempool_fee_eviction_capacity`: pool capacity, in transactions, the watermarks are shares of.
+///   0 takes the limits of the reth pool config. MEMPOOL_FEE_EVICTION_CAPACITY, default 0. The
+///   pool's own limits are fixed at startup and are not changed, so a capacity above them lets
+///   reth's eviction act first.
+struct FeeEvictionKnobs {
+    high_watermark_pct: Arc<Knob>,
+    low_watermark_pct: Arc<Knob>,
+    capacity: Arc<Knob>,
+}
+
+fn fee_eviction_knobs() -> &'static FeeEvictionKnobs {
+    static KNOBS: OnceLock<FeeEvictionKnobs> = OnceLock::new();
+    KNOBS.get_or_init(|| {
+        let registry = RuntimeConfigRegistry::global();
+        FeeEvictionKnobs {
+            high_watermark_pct: registry.register(
+                "mempool_fee_eviction_high_watermark_pct",
+                "Pool fill, in percent of its capacity, above which the cheapest txns are evicted",
+                "MEMPOOL_FEE_EVICTION_HIGH_WATERMARK_PCT",
+                90,
+                100,
+            ),
+            low_watermark_pct: registry.register(
+                "mempool_fee_eviction_low_watermark_pct",
+                "Pool fill, in percent of its capacity, the fee eviction evicts down to",
+                "MEMPOOL_FEE_EVICTION_LOW_WATERMARK_PCT",
+                80,
+                100,
+            ),
+            capacity: registry.register(
+                "mempool_fee_eviction_capacity",
+                "Pool capacity the fee eviction watermarks are shares of, 0 follows the pool config",
+                "MEMPOOL_FEE_EVICTION_CAPACITY",
+                0,
+                10_000_000,
+            ),
+        }
+    })
+}
+
+/// Fill thresholds of a pool of `capacity` transactions, in transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Watermarks {
+    high: usize,
+    low: usize,
+}
+
+impl Watermarks {
+    fn new(capacity: usize, high_pct: u64, low_pct: u64) -> Self {
+        let low_pct = low_pct.min(high_pct);
+        let of_capacity = |pct: u64| capacity.saturating_mul(pct as usize) / 100;
+        Self { high: of_capacity(high_pct), low: of_capacity(low_pct) }
+    }
+
+    fn from_knobs(config_capacity: usize) -> Self {
+        let knobs = fee_eviction_knobs();
+        let capacity = match knobs.capacity.get() {
+            0 => config_capacity,
+            capacity => capacity as usize,
+        };
+        Self::new(capacity, knobs.high_watermark_pct.get(), knobs.low_watermark_pct.get())
+    }
+
+    /// Transactions to evict from a pool holding `total`.
+    fn excess(&self, total: usize) -> usize {
+        if total < self.high {
+            return 0;
+        }
+        total - self.low
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeeQuote {
+    pub min_gas_price: U256,
+    pub base_fee: U256,
+    /// Whether the pending sub-pool is at its limit, so a new transaction has to outbid the
+    /// cheapest pending one.
+    pub pending_full: bool,
+}
+
+/// The latest [`FeeQuote`], written by the sweep and read by the RPC.
+#[derive(Default)]
+pub(crate) struct FeeMarket {
+    quote: RwLock<FeeQuote>,
+}
+
+pub(crate) type SharedFeeMarket = Arc<FeeMarket>;
+
+impl FeeMarket {
+    pub(crate) fn quote(&self) -> FeeQuote {
+        *self.quote.read().unwrap()
+    }
+
+    /// Evicts the cheapest non-ready transactions if the pool is over its high watermark and
+    /// quotes the gas price. Returns the number of evicted transactions.
+    pub(crate) fn sweep<P: TransactionPool>(&self, pool: &P, config: &PoolConfig) -> usize {
+        let capacity = config.pending_limit.max_txs +
+            config.basefee_limit.max_txs +
+            config.queued_limit.max_txs;
+        let evicted = evict_cheapest(pool, Watermarks::from_knobs(capacity));
+
+        let base_fee = pool.block_info().pending_basefee;
+        let pending = pool.pending_transactions();
+        let pending_full = pending.len() >= config.pending_limit.max_txs;
+        let min_gas_price = min_gas_price(
+            base_fee,
+            pending_full,
+            pending.iter().filter_map(|txn| txn.transaction.effective_tip_per_gas(base_fee)),
+        );
+        MIN_GAS_PRICE.set(min_gas_price.min(i64::MAX as u128) as i64);
+        *self.quote.write().unwrap() = FeeQuote {
+            min_gas_price: U256::from(min_gas_price),
+            base_fee: U256::from(base_fee),
+            pending_full,
+        };
+        evicted
+    }
+}
+
+/// The gas price a new transaction needs: the base fee while the pending sub-pool has room,
+/// otherwise one wei more than the cheapest of the `pending_tips` on top of it.
+fn min_gas_price(
+    base_fee: u64,
+    pending_full: bool,
+    pending_tips: impl IntoIterator<Item = u128>,
+) -> u128 {
+    let base_fee = base_fee as u128;
+    if !pending_full {
+        return base_fee;
+    }
+    pending_tips.into_iter().min().map_or(base_fee, |tip| base_fee + tip + 1)
+}
+
+/// A non-ready transaction the sweep may evict.
+#[derive(Clone, Copy, Debug)]
+struct EvictionCandidate {
+    sender: Address,
+    nonce: u64,
+    max_fee_per_gas: u128,
+    hash: TxHash,
+}
+
+/// The `excess` candidates to evict, cheapest first. Only the highest nonce left of a sender can
+/// be evicted, so its lower nonces are evicted after it however little they pay.
+fn eviction_order(candidates: Vec<EvictionCandidate>, excess: usize) -> Vec<TxHash> {
+    let mut by_sender: HashMap<Address, Vec<EvictionCandidate>> = HashMap::new();
+    for candidate in candidates {
+        by_sender.entry(candidate.sender).or_default().push(candidate);
+    }
+    // The highest nonce of every sender, the one at the end of its transactions
+    let mut tails = BinaryHeap::new();
+    for (sender, txns) in by_sender.iter_mut() {
+        txns.sort_by_key(|txn| txn.nonce);
+        if let Some(tail) = txns.last() {
+            tails.push(Reverse((tail.max_fee_per_gas, *sender)));
+        }
+    }
+    let mut victims = Vec::with_capacity(excess);
+    while victims.len() < excess {
+        let Some(Reverse((_, sender))) = tails.pop() else { break };
+        let txns = by_sender.get_mut(&sender).expect("every tail has a sender");
+        victims.extend(txns.pop().map(|txn| txn.hash));
+        if let Some(tail) = txns.last() {
+            tails.push(Reverse((tail.max_fee_per_gas, sender)));
+        }
+    }
+    victims
+}
+
+/// Removes the non-ready transactions paying the least until the pool is back at its low
+/// watermark, see [`eviction_order`].
+fn evict_cheapest<P: TransactionPool>(pool: &P, watermarks: Watermarks) -> usize {
+    let excess = watermarks.excess(pool.pool_size().total);
+    if excess == 0 {
+        return 0;
+    }
+    let candidates = pool
+        .queued_transactions()
+        .iter()
+        .map(|txn| EvictionCandidate {
+            sender: txn.sender(),
+            nonce: txn.nonce(),
+            max_fee_per_gas: txn.transaction.max_fee_per_gas(),
+            hash: *txn.hash(),
+        })
+        .collect();
+    let victims = eviction_order(candidates, excess);
+    if victims.is_empty() {
+        return 0;
+    }
+    let evicted = pool.remove_transactions(victims).len();
+    FEE_EVICTED_TXNS.inc_by(evicted as u64);
+    evicted
+}
+
+/// `gravity_minGasPrice()`: the latest [`FeeQuote`], see the module documentation.
+pub(crate) fn rpc_module(fee_market: SharedFeeMarket) -> RpcModule<()> {
+    let mut module = RpcModule::new(());
+    module
+        .register_method("gravity_minGasPrice", move |_, _, _| {
+            Ok::<_, ErrorObjectOwned>(fee_market.quote())
+        })
+        .expect("gravity_minGasPrice is registered once");
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watermarks_are_shares_of_the_capacity() {
+        let watermarks = Watermarks::new(1000, 90, 80);
+        assert_eq!(watermarks, Watermarks { high: 900, low: 800 });
+        assert_eq!(watermarks.excess(899), 0);
+        assert_eq!(watermarks.excess(900), 100);
+        assert_eq!(watermarks.excess(1200), 400);
+
+        // The low watermark is at most the high one
+        assert_eq!(Watermarks::new(1000, 50, 70), Watermarks { high: 500, low: 500 });
+    }
+
+    #[test]
+    fn evicts_the_cheapest_highest_nonces_first() {
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let candidate = |sender, nonce, max_fee_per_gas, hash: u8| EvictionCandidate {
+            sender,
+            nonce,
+            max_fee_per_gas,
+            hash: TxHash::repeat_byte(hash),
+        };
+        // Alice's cheapest transaction has her lowest nonce, it goes only after the others
+        let candidates = vec![
+            candidate(alice, 0, 1, 10),
+            candidate(alice, 1, 5, 11),
+            candidate(alice, 2, 3, 12),
+            candidate(bob, 7, 2, 20),
+            candidate(bob, 8, 4, 21),
+        ];
+        let order = |excess| eviction_order(candidates.clone(), excess);
+        assert_eq!(order(usize::MAX), [12, 21, 20, 11, 10].map(TxHash::repeat_byte).to_vec());
+        assert_eq!(order(2), [12, 21].map(TxHash::repeat_byte).to_vec());
+        assert!(order(0).is_empty());
+    }
+
+    #[test]
+    fn quotes_the_base_fee_until_pending_is_full() {
+        assert_eq!(min_gas_price(100, false, [5, 3]), 100);
+        assert_eq!(min_gas_price(100, true, [5, 3, 9]), 104);
+        assert_eq!(min_gas_price(100, true, []), 100);
+    }
+}
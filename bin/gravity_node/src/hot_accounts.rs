@@ -0,0 +1,166 @@
+use alloy_primitives::Address;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+use tracing::{info, warn};
+
+/// File under the datadir holding the hot account record: raw addresses, hottest first.
+pub(crate) const HOT_ACCOUNTS_FILE: &str = "hot_accounts";
+
+const ADDRESS_LEN: usize = 20;
+
+/// Number of recently active accounts remembered across restarts.
+/// Can be configured via HOT_ACCOUNTS_CAPACITY environment variable, 0 disables tracking and
+/// prefaulting.
+fn hot_accounts_capacity() -> usize {
+    static CAPACITY: OnceLock<usize> = OnceLock::new();
+    *CAPACITY.get_or_init(|| {
+        std::env::var("HOT_ACCOUNTS_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(10_000) // Default 10000 accounts
+    })
+}
+
+/// Committed blocks between two writes of the hot account record.
+/// Can be configured via HOT_ACCOUNTS_PERSIST_INTERVAL_BLOCKS environment variable.
+fn hot_accounts_persist_interval() -> u64 {
+    static INTERVAL: OnceLock<u64> = OnceLock::new();
+    *INTERVAL.get_or_init(|| {
+        std::env::var("HOT_ACCOUNTS_PERSIST_INTERVAL_BLOCKS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|interval| *interval > 0)
+            .unwrap_or(100) // Default 100 blocks
+    })
+}
+
+struct State {
+    /// account -> number of the last executed block that touched it
+    last_seen: HashMap<Address, u64>,
+    last_persisted_block: u64,
+}
+
+/// Compact record of the accounts touched by recent blocks, persisted so that a restarted node
+/// can read them once before resuming consensus. That only brings their database pages into the
+/// OS page cache: the first blocks after a restart still run against cold in-memory execution
+/// caches, but no longer wait on disk reads for these accounts.
+pub(crate) struct HotAccounts {
+    path: PathBuf,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+impl HotAccounts {
+    pub(crate) fn new(datadir: &Path) -> Self {
+        Self {
+            path: datadir.join(HOT_ACCOUNTS_FILE),
+            capacity: hot_accounts_capacity(),
+            state: Mutex::new(State { last_seen: HashMap::new(), last_persisted_block: 0 }),
+        }
+    }
+
+    /// Records the accounts an executed block touched.
+    pub(crate) fn record(&self, block_number: u64, accounts: impl IntoIterator<Item = Address>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        for account in accounts {
+            state.last_seen.insert(account, block_number);
+        }
+        // Let the map grow to twice the capacity before trimming to keep this amortized.
+        if state.last_seen.len() > self.capacity * 2 {
+            let keep: HashSet<Address> =
+                Self::hottest(&state.last_seen, self.capacity).into_iter().collect();
+            state.last_seen.retain(|account, _| keep.contains(account));
+        }
+    }
+
+    /// Writes the record once `committed_block` is far enough past the previous write.
+    /// Failures are logged and retried on the next interval.
+    pub(crate) fn maybe_persist(&self, committed_block: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let accounts = {
+            let mut state = self.state.lock().unwrap();
+            if committed_block < state.last_persisted_block + hot_accounts_persist_interval() {
+                return;
+            }
+            state.last_persisted_block = committed_block;
+            Self::hottest(&state.last_seen, self.capacity)
+        };
+        if let Err(e) = write_accounts(&self.path, &accounts) {
+            warn!("failed to persist hot accounts to {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Accounts recorded before the last shutdown, hottest first. Empty if there is no usable
+    /// record.
+    pub(crate) fn load(&self) -> Vec<Address> {
+        if self.capacity == 0 {
+            return vec![];
+        }
+        match std::fs::read(&self.path) {
+            Ok(bytes) => {
+                let mut accounts = decode_accounts(&bytes);
+                accounts.truncate(self.capacity);
+                info!("loaded {} hot accounts from {:?}", accounts.len(), self.path);
+                accounts
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => {
+                warn!("failed to read hot accounts from {:?}: {}", self.path, e);
+                vec![]
+            }
+        }
+    }
+
+    fn hottest(last_seen: &HashMap<Address, u64>, limit: usize) -> Vec<Address> {
+        let mut accounts: Vec<(Address, u64)> =
+            last_seen.iter().map(|(account, block)| (*account, *block)).collect();
+        accounts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        accounts.truncate(limit);
+        accounts.into_iter().map(|(account, _)| account).collect()
+    }
+}
+
+fn write_accounts(path: &Path, accounts: &[Address]) -> std::io::Result<()> {
+    let mut bytes = Vec::with_capacity(accounts.len() * ADDRESS_LEN);
+    for account in accounts {
+        bytes.extend_from_slice(account.as_slice());
+    }
+    // Write then rename so a crash mid-write never leaves a truncated record behind.
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(tmp, path)
+}
+
+fn decode_accounts(bytes: &[u8]) -> Vec<Address> {
+    bytes.chunks_exact(ADDRESS_LEN).map(Address::from_slice).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persisted_record_keeps_most_recent_accounts() {
+        let dir = tempfile::tempdir().unwrap();
+        let hot_accounts = HotAccounts {
+            path: dir.path().join(HOT_ACCOUNTS_FILE),
+            capacity: 2,
+            state: Mutex::new(State { last_seen: HashMap::new(), last_persisted_block: 0 }),
+        };
+
+        hot_accounts.record(1, [Address::repeat_byte(1), Address::repeat_byte(2)]);
+        hot_accounts.record(2, [Address::repeat_byte(3)]);
+        hot_accounts.record(3, [Address::repeat_byte(1)]);
+        hot_accounts.maybe_persist(1_000);
+
+        assert_eq!(hot_accounts.load(), vec![Address::repeat_byte(1), Address::repeat_byte(3)]);
+    }
+}
@@ -0,0 +1,275 @@
+use alloy_primitives::{Address, Bytes, Signature, TxHash};
+use dashmap::DashMap;
+use gaptos::aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use greth_compat::reth_transaction_pool::TransactionPool;
+use jsonrpsee::{types::ErrorObjectOwned, RpcModule};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Upper bound on tracked deadlines so the RPC cannot be used to grow the map without limit.
+const INCLUSION_DEADLINE_CAPACITY: usize = 100_000;
+
+/// JSON-RPC error code for invalid method parameters.
+const INVALID_PARAMS_CODE: i32 = -32602;
+
+static INCLUSION_DEADLINE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_mempool_inclusion_deadline_total",
+        "Inclusion deadline hints by outcome",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Client-supplied inclusion deadlines (unix milliseconds) keyed by transaction hash.
+///
+/// Deadlines are only hints: payload selection prefers soon-expiring transactions among
+/// equally priced ones, and transactions still pending past their deadline are evicted from
+/// the pool instead of being proposed late.
+pub(crate) struct InclusionDeadlines {
+    deadlines: DashMap<TxHash, u64>,
+    capacity: usize,
+}
+
+pub(crate) type SharedInclusionDeadlines = Arc<InclusionDeadlines>;
+
+impl InclusionDeadlines {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(INCLUSION_DEADLINE_CAPACITY)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self { deadlines: DashMap::new(), capacity }
+    }
+
+    /// Sets the deadline of `hash`. A deadline already set can be extended but never shortened.
+    pub(crate) fn set(&self, hash: TxHash, deadline_ms: u64, now_ms: u64) -> Result<(), String> {
+        if deadline_ms <= now_ms {
+            INCLUSION_DEADLINE_TOTAL.with_label_values(&["rejected"]).inc();
+            return Err(format!("deadline {deadline_ms} is not in the future"));
+        }
+        if let Some(current) = self.get(&hash).filter(|current| deadline_ms < *current) {
+            INCLUSION_DEADLINE_TOTAL.with_label_values(&["rejected"]).inc();
+            return Err(format!("deadline {deadline_ms} would shorten the deadline {current}"));
+        }
+        if self.deadlines.len() >= self.capacity && !self.deadlines.contains_key(&hash) {
+            INCLUSION_DEADLINE_TOTAL.with_label_values(&["rejected"]).inc();
+            return Err("too many pending inclusion deadlines".to_string());
+        }
+        self.deadlines.insert(hash, deadline_ms);
+        INCLUSION_DEADLINE_TOTAL.with_label_values(&["set"]).inc();
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, hash: &TxHash) -> Option<u64> {
+        if self.deadlines.is_empty() {
+            return None;
+        }
+        self.deadlines.get(hash).map(|deadline| *deadline)
+    }
+
+    pub(crate) fn is_expired(&self, hash: &TxHash, now_ms: u64) -> bool {
+        self.get(hash).is_some_and(|deadline| deadline <= now_ms)
+    }
+
+    /// Removes and returns every hash whose deadline has passed.
+    pub(crate) fn take_expired(&self, now_ms: u64) -> Vec<TxHash> {
+        let expired: Vec<TxHash> = self
+            .deadlines
+            .iter()
+            .filter(|entry| *entry.value() <= now_ms)
+            .map(|entry| *entry.key())
+            .collect();
+        for hash in &expired {
+            self.deadlines.remove(hash);
+        }
+        expired
+    }
+
+    /// Records how many expired hints were still pending, i.e. missed their deadline.
+    pub(crate) fn record_missed(&self, count: usize) {
+        INCLUSION_DEADLINE_TOTAL.with_label_values(&["missed"]).inc_by(count as u64);
+    }
+
+    /// Reorders selected transactions so that, within each run of equal gas tier, senders
+    /// with the earliest deadline come first. A sender's transactions share one sort key, so
+    /// the stable sort never reorders nonces of the same sender.
+    pub(crate) fn prioritize<T>(
+        &self,
+        txns: &mut [T],
+        key: impl Fn(&T) -> (u128, Address, TxHash),
+    ) {
+        if self.deadlines.is_empty() {
+            return;
+        }
+        let mut start = 0;
+        while start < txns.len() {
+            let tier = key(&txns[start]).0;
+            let mut end = start + 1;
+            while end < txns.len() && key(&txns[end]).0 == tier {
+                end += 1;
+            }
+            let run = &mut txns[start..end];
+            let mut sender_deadline: HashMap<Address, u64> = HashMap::new();
+            for txn in run.iter() {
+                let (_, sender, hash) = key(txn);
+                let deadline = self.get(&hash).unwrap_or(u64::MAX);
+                let entry = sender_deadline.entry(sender).or_insert(u64::MAX);
+                *entry = (*entry).min(deadline);
+            }
+            run.sort_by_key(|txn| sender_deadline[&key(txn).1]);
+            start = end;
+        }
+    }
+}
+
+/// Message the sender of a transaction signs (EIP-191) to set its inclusion deadline.
+pub(crate) fn deadline_message(hash: &TxHash, deadline_ms: u64) -> String {
+    format!("gravity_setInclusionDeadline:{hash}:{deadline_ms}")
+}
+
+/// Checks that `signature` over [`deadline_message`] is from `sender`, the sender of the
+/// transaction in the pool.
+fn authorize(
+    sender: Address,
+    hash: &TxHash,
+    deadline_ms: u64,
+    signature: &[u8],
+) -> Result<(), String> {
+    let signature =
+        Signature::from_raw(signature).map_err(|e| format!("invalid signature: {e}"))?;
+    let signer = signature
+        .recover_address_from_msg(deadline_message(hash, deadline_ms))
+        .map_err(|e| format!("failed to recover the signer: {e}"))?;
+    if signer != sender {
+        return Err(format!("deadline is signed by {signer}, not by the sender {sender}"));
+    }
+    Ok(())
+}
+
+/// `gravity_setInclusionDeadline(txHash, deadlineUnixMs, signature)`: attaches an inclusion
+/// deadline hint to a pending transaction. Only the sender of the transaction can set it, by
+/// signing [`deadline_message`] with `personal_sign`.
+pub(crate) fn rpc_module<P>(deadlines: SharedInclusionDeadlines, pool: P) -> RpcModule<()>
+where
+    P: TransactionPool + 'static,
+{
+    let mut module = RpcModule::new(());
+    module
+        .register_method("gravity_setInclusionDeadline", move |params, _, _| {
+            let (hash, deadline_ms, signature): (TxHash, u64, Bytes) = params.parse()?;
+            let invalid = |e: String| ErrorObjectOwned::owned(INVALID_PARAMS_CODE, e, None::<()>);
+            let Some(txn) = pool.get(&hash) else {
+                INCLUSION_DEADLINE_TOTAL.with_label_values(&["rejected"]).inc();
+                return Err(invalid(format!("transaction {hash} is not pending")));
+            };
+            authorize(txn.sender(), &hash, deadline_ms, &signature).map_err(|e| {
+                INCLUSION_DEADLINE_TOTAL.with_label_values(&["unauthorized"]).inc();
+                invalid(e)
+            })?;
+            deadlines.set(hash, deadline_ms, now_ms()).map(|_| true).map_err(invalid)
+        })
+        .expect("gravity_setInclusionDeadline is registered once");
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    fn hash(byte: u8) -> TxHash {
+        TxHash::repeat_byte(byte)
+    }
+
+    #[test]
+    fn rejects_past_deadlines_and_respects_capacity() {
+        let deadlines = InclusionDeadlines::with_capacity(1);
+
+        assert!(deadlines.set(hash(1), 100, 100).is_err());
+        assert!(deadlines.set(hash(1), 200, 100).is_ok());
+        assert!(deadlines.set(hash(2), 200, 100).is_err());
+        // Updating an existing hint is allowed when full.
+        assert!(deadlines.set(hash(1), 300, 100).is_ok());
+    }
+
+    #[test]
+    fn never_shortens_a_deadline() {
+        let deadlines = InclusionDeadlines::new();
+        deadlines.set(hash(1), 500, 100).unwrap();
+
+        assert!(deadlines.set(hash(1), 200, 100).is_err());
+        assert_eq!(deadlines.get(&hash(1)), Some(500));
+        assert!(deadlines.set(hash(1), 800, 100).is_ok());
+        assert_eq!(deadlines.get(&hash(1)), Some(800));
+    }
+
+    #[test]
+    fn only_the_sender_can_set_a_deadline() {
+        let sender = PrivateKeySigner::random();
+        let other = PrivateKeySigner::random();
+        let sign = |signer: &PrivateKeySigner, deadline_ms| {
+            signer.sign_message_sync(deadline_message(&hash(1), deadline_ms).as_bytes()).unwrap()
+        };
+
+        let signature = sign(&sender, 500);
+        assert!(authorize(sender.address(), &hash(1), 500, &signature.as_bytes()).is_ok());
+        // The signature covers the deadline and the hash
+        assert!(authorize(sender.address(), &hash(1), 100, &signature.as_bytes()).is_err());
+        assert!(authorize(sender.address(), &hash(2), 500, &signature.as_bytes()).is_err());
+
+        let forged = sign(&other, 500);
+        assert!(authorize(sender.address(), &hash(1), 500, &forged.as_bytes()).is_err());
+        assert!(authorize(sender.address(), &hash(1), 500, &[0u8; 3]).is_err());
+    }
+
+    #[test]
+    fn take_expired_only_returns_passed_deadlines() {
+        let deadlines = InclusionDeadlines::new();
+        deadlines.set(hash(1), 150, 100).unwrap();
+        deadlines.set(hash(2), 500, 100).unwrap();
+
+        assert!(deadlines.is_expired(&hash(1), 200));
+        assert_eq!(deadlines.take_expired(200), vec![hash(1)]);
+        assert!(deadlines.get(&hash(1)).is_none());
+        assert_eq!(deadlines.get(&hash(2)), Some(500));
+    }
+
+    #[test]
+    fn prioritize_within_tier_keeps_sender_order() {
+        let deadlines = InclusionDeadlines::new();
+        let (a, b) = (Address::repeat_byte(0xa), Address::repeat_byte(0xb));
+        // b's second transaction carries the earliest deadline.
+        deadlines.set(hash(4), 1_000, 0).unwrap();
+
+        // (tier, sender, hash)
+        let mut txns = vec![
+            (10, a, hash(1)),
+            (10, b, hash(3)),
+            (10, a, hash(2)),
+            (10, b, hash(4)),
+            (5, a, hash(5)),
+        ];
+        deadlines.prioritize(&mut txns, |txn| *txn);
+
+        assert_eq!(
+            txns,
+            vec![
+                (10, b, hash(3)),
+                (10, b, hash(4)),
+                (10, a, hash(1)),
+                (10, a, hash(2)),
+                (5, a, hash(5))
+            ]
+        );
+    }
+}
@@ -4,7 +4,10 @@ use api::{
     check_bootstrap_config,
     config_storage::ConfigStorageWrapper,
     consensus_api::{ConsensusEngine, ConsensusEngineArgs},
+    genesis_bundle::GenesisBundle,
+    trusted_checkpoint::TrustedCheckpoint,
 };
+use block_buffer_manager::{block_buffer_manager::BlockBufferManagerConfig, BlockBufferManager};
 use consensus::mock_consensus::mock::MockConsensus;
 use gaptos::{
     api_types::{
@@ -14,9 +17,10 @@ use gaptos::{
         relayer::GLOBAL_RELAYER,
     },
     aptos_config::config::RoleType,
+    aptos_types::waypoint::Waypoint,
 };
 use gravity_storage::block_view_storage::BlockViewStorage;
-use greth::{
+use greth_compat::{
     gravity_storage, reth,
     reth_chainspec::ChainSpecProvider,
     reth_cli::chainspec::ChainSpecParser,
@@ -24,12 +28,12 @@ use greth::{
     reth_pipe_exec_layer_ext_v2::{self, ExecutionArgs},
     reth_provider,
     reth_transaction_pool::TransactionPool,
+    ChainStateReader,
 };
-use pprof::{protos::Message, ProfilerGuard};
 use reth::rpc::builder::auth::AuthServerHandle;
 use reth_cli::{
     RethBlockChainProvider, RethCliConfigStorage, RethEthCall, RethPipeExecLayerApi,
-    RethTransactionPool,
+    RethTransactionPool, TxnCache,
 };
 use reth_coordinator::RethCoordinator;
 use reth_db::DatabaseEnv;
@@ -40,24 +44,36 @@ use tokio::{
     sync::{broadcast, oneshot},
 };
 use tracing::{info, warn};
+mod account_prefetch;
+mod balance_cache;
 mod chainspec;
 mod cli;
 mod consensus;
+mod execution_identity;
+mod fee_market;
+mod hot_accounts;
+mod inclusion_deadline;
 mod mempool;
 mod node_metrics;
+mod parked_txns;
+mod pool_diff;
+mod private_txns;
 pub mod relayer;
 mod reth_cli;
 mod reth_coordinator;
+mod sig_verify;
+mod system_contracts;
 use crate::{
-    chainspec::GravityChainSpecParser, cli::Cli, mempool::Mempool, relayer::RelayerWrapper,
-};
-use std::{
-    fs::File,
-    path::PathBuf,
-    sync::{Arc, Mutex},
-    thread,
-    time::{Duration, Instant},
+    chainspec::GravityChainSpecParser,
+    cli::Cli,
+    execution_identity::ExecutionLayerIdentity,
+    fee_market::{FeeMarket, SharedFeeMarket},
+    hot_accounts::HotAccounts,
+    inclusion_deadline::{InclusionDeadlines, SharedInclusionDeadlines},
+    mempool::Mempool,
+    relayer::RelayerWrapper,
 };
+use std::{path::PathBuf, str::FromStr, sync::Arc, thread, time::Duration};
 
 use crate::reth_cli::RethCli;
 use clap::Parser;
@@ -71,6 +87,7 @@ struct ConsensusArgs<EthApi: RethEthCall> {
     pub provider: RethBlockChainProvider,
     pub tx_listener: tokio::sync::mpsc::Receiver<TxHash>,
     pub pool: RethTransactionPool,
+    pub execution_identity: ExecutionLayerIdentity,
 }
 
 // ConsensusAlpha is activated by the genesis `alphaTime` field (seconds,
@@ -95,6 +112,9 @@ fn run_reth(
     cli: Cli<GravityChainSpecParser>,
     execution_args_rx: oneshot::Receiver<ExecutionArgs>,
     mut shutdown: broadcast::Receiver<()>,
+    inclusion_deadlines: SharedInclusionDeadlines,
+    fee_market: SharedFeeMarket,
+    txn_cache: TxnCache,
 ) -> (ConsensusArgs<impl RethEthCall>, u64, oneshot::Receiver<PathBuf>, thread::JoinHandle<()>) {
     let (datadir_tx, datadir_rx) = oneshot::channel::<PathBuf>();
     reth_cli_util::sigsegv_handler::install();
@@ -117,6 +137,21 @@ fn run_reth(
                         .with_types_and_provider::<EthereumNode, BlockchainProvider<_>>()
                         .with_components(EthereumNode::components())
                         .with_add_ons(EthereumAddOns::default())
+                        .extend_rpc_modules(move |ctx| {
+                            let pool = ctx.pool().clone();
+                            ctx.modules.merge_configured(inclusion_deadline::rpc_module(
+                                inclusion_deadlines,
+                                pool.clone(),
+                            ))?;
+                            ctx.modules.merge_configured(fee_market::rpc_module(fee_market))?;
+                            ctx.modules.merge_configured(parked_txns::rpc_module(
+                                pool.clone(),
+                                Arc::new(ctx.provider().clone()),
+                            ))?;
+                            ctx.modules.merge_configured(private_txns::rpc_module(pool.clone()))?;
+                            ctx.modules.merge_configured(pool_diff::rpc_module(pool, txn_cache))?;
+                            Ok(())
+                        })
                         .launch_with_fn(|builder| {
                             let datadir = builder.config().datadir();
                             // Send datadir via oneshot channel
@@ -164,6 +199,8 @@ fn run_reth(
                         });
                     let pool = handle.node.pool;
 
+                    let execution_identity =
+                        ExecutionLayerIdentity::new(chain_spec.genesis_hash());
                     let storage = BlockViewStorage::new(provider.clone());
                     let pipeline_api_v2 = reth_pipe_exec_layer_ext_v2::new_pipe_exec_layer_api(
                         chain_spec,
@@ -179,6 +216,7 @@ fn run_reth(
                         provider,
                         tx_listener: pending_listener,
                         pool,
+                        execution_identity,
                     };
                     let _ = tx.send((args, recover_block_number));
 
@@ -203,77 +241,12 @@ fn run_reth(
     (args, block_number, datadir_rx, reth_thread)
 }
 
-struct ProfilingState {
-    guard: Option<ProfilerGuard<'static>>,
-    profile_count: usize,
-}
-
-fn setup_pprof_profiler() -> Arc<Mutex<ProfilingState>> {
-    let profiling_state = Arc::new(Mutex::new(ProfilingState { guard: None, profile_count: 0 }));
-
-    let profiling_state_clone = profiling_state.clone();
-
-    thread::spawn(move || {
-        let config = 99;
-
-        let start = Instant::now();
-        let max_duration = Duration::from_secs(60 * 30);
-
-        while start.elapsed() < max_duration {
-            let profile_duration = Duration::from_secs(3 * 60);
-
-            {
-                let mut state = profiling_state_clone.lock().unwrap();
-                state.guard = Some(ProfilerGuard::new(config).unwrap());
-                println!("Started profiling session #{}", state.profile_count + 1);
-            }
-
-            thread::sleep(profile_duration);
-            {
-                let mut state = profiling_state_clone.lock().unwrap();
-                if let Some(guard) = state.guard.take() {
-                    if let Ok(report) = guard.report().build() {
-                        let count = state.profile_count;
-
-                        let now = std::time::SystemTime::now();
-                        let formatted_time = {
-                            let elapsed = now.duration_since(std::time::UNIX_EPOCH).unwrap();
-                            let secs = elapsed.as_secs();
-                            let time =
-                                time::OffsetDateTime::from_unix_timestamp(secs as i64).unwrap();
-                            format!("{:02}", time.millisecond())
-                        };
-
-                        let proto_path = format!("profile_{count}_proto_{formatted_time:?}.pb");
-                        if let Ok(mut file) = File::create(&proto_path) {
-                            if let Ok(profile) = report.pprof() {
-                                let mut content = Vec::new();
-                                if profile.write_to_vec(&mut content).is_ok() &&
-                                    std::io::Write::write_all(&mut file, &content).is_ok()
-                                {
-                                    println!("Wrote protobuf to {proto_path}");
-                                }
-                            }
-                        }
-                        state.profile_count += 1;
-                    }
-                }
-            }
-
-            thread::sleep(Duration::from_secs(5));
-        }
-    });
-    profiling_state
-}
-
 fn main() {
     // Set RUST_BACKTRACE before any threads are spawned to avoid UB from std::env::set_var
     if std::env::var_os("RUST_BACKTRACE").is_none() {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 
-    let _profiling_state =
-        if std::env::var("ENABLE_PPROF").is_ok() { Some(setup_pprof_profiler()) } else { None };
     let cli = Cli::parse();
 
     // For utility subcommands (stage, db, init, config, etc.), skip full node initialization
@@ -301,8 +274,45 @@ fn main() {
     // Full node path: requires config, consensus, relayer, etc.
     node_metrics::register_binary_info_metrics();
     let relayer_config_path = cli.gravity_node_config.relayer_config_path.clone();
-    let gcei_config = check_bootstrap_config(cli.gravity_node_config.node_config_path.clone());
+    let expected_genesis_hash = cli.gravity_node_config.expected_genesis_hash.clone();
+    // Role defaults include environment variables, so the config loads before any node thread
+    let (mut gcei_config, role) =
+        check_bootstrap_config(cli.gravity_node_config.node_config_path.clone());
+    if cli.gravity_node_config.observer {
+        // An observer never votes, so it must not join the validator network.
+        if gcei_config.validator_network.is_some() {
+            eprintln!(
+                "Error: --observer cannot be used with a validator_network in the node config"
+            );
+            std::process::exit(1);
+        }
+        gcei_config.consensus_observer.observer_enabled = true;
+        gcei_config.consensus_observer.publisher_enabled = false;
+    }
+    if let Some(role) = role {
+        if let Err(err) = role.check(&gcei_config, cli.gravity_node_config.observer) {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+    }
+    let trusted_checkpoint_source =
+        cli.gravity_node_config.trusted_checkpoint.clone().map(|source| {
+            let waypoint = cli.gravity_node_config.trusted_waypoint.as_deref().unwrap_or_default();
+            match Waypoint::from_str(waypoint) {
+                Ok(waypoint) => (source, waypoint),
+                Err(err) => {
+                    eprintln!("Error: invalid trusted waypoint '{waypoint}': {err}");
+                    std::process::exit(1);
+                }
+            }
+        });
 
+    let genesis_bundle = cli.gravity_node_config.genesis_bundle.as_deref().map(|path| {
+        GenesisBundle::load(path).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        })
+    });
     let (shutdown_tx, _shutdown_rx) = broadcast::channel(1);
     let shutdown_tx_clone = shutdown_tx.clone();
 
@@ -325,30 +335,62 @@ fn main() {
     });
 
     let (execution_args_tx, execution_args_rx) = oneshot::channel();
-    let (consensus_args, latest_block_number, datadir_rx, reth_thread) =
-        run_reth(cli, execution_args_rx, shutdown_tx.subscribe());
+    let inclusion_deadlines = Arc::new(InclusionDeadlines::new());
+    let fee_market = Arc::new(FeeMarket::default());
+    // Shared with the RPC server before the mempool exists, for `gravity_txpoolDiff`.
+    let txn_cache = TxnCache::default();
+    let (consensus_args, latest_block_number, datadir_rx, reth_thread) = run_reth(
+        cli,
+        execution_args_rx,
+        shutdown_tx.subscribe(),
+        inclusion_deadlines.clone(),
+        fee_market.clone(),
+        txn_cache.clone(),
+    );
+    // Refuse to run consensus on top of an execution layer built for a different chain.
+    consensus_args.execution_identity.register();
+    let bundle_genesis_hash =
+        genesis_bundle.as_ref().map(GenesisBundle::execution_genesis_hash_hex);
+    let identity = &consensus_args.execution_identity;
+    // Explicit expectations first, so a mismatching execution layer is never recorded
+    let genesis_check = [expected_genesis_hash.as_deref(), bundle_genesis_hash.as_deref()]
+        .into_iter()
+        .try_for_each(|expected| identity.verify_genesis(expected))
+        .and_then(|_| identity.verify_recorded_genesis(&gcei_config.storage.dir()));
+    if let Err(err) = genesis_check {
+        eprintln!("Error: {err}");
+        let _ = shutdown_tx.send(());
+        std::process::exit(1);
+    }
     let rt = tokio::runtime::Runtime::new().unwrap();
-    let chain_id = {
-        let chain_info = consensus_args.provider.chain_spec().chain;
-        match chain_info.into_kind() {
-            greth::reth_chainspec::ChainKind::Named(n) => n as u64,
-            greth::reth_chainspec::ChainKind::Id(id) => id,
-        }
-    };
+    let chain_id = consensus_args.provider.chain_id();
     let pool = Box::new(Mempool::new(
         consensus_args.pool.clone(),
         gcei_config.base.role == RoleType::FullNode,
         chain_id,
+        inclusion_deadlines,
+        fee_market,
+        txn_cache,
+        Arc::new(consensus_args.provider.clone()),
     ));
     let txn_cache = pool.tx_cache();
+    let balance_cache = pool.balance_cache();
     let shutdown_rx_cli = shutdown_tx.subscribe();
+    let block_buffer_config = BlockBufferManagerConfig::from_env()
+        .with_round_timeout(Duration::from_millis(gcei_config.consensus.round_initial_timeout_ms));
     // `_engine` owns tokio Runtimes; it must be returned out of `block_on` so it
     // drops in this sync context — dropping a Runtime inside an async context
     // panics in tokio's blocking-pool shutdown.
     let (coordinator_result, _engine) = rt.block_on(async move {
         let datadir = datadir_rx.await.expect("datadir should be sent");
-        let client = Arc::new(RethCli::new(consensus_args, txn_cache, shutdown_rx_cli).await);
+        let hot_accounts = Arc::new(HotAccounts::new(&datadir));
+        // Owned by this node; consensus, the reth coordinator and the relayer share it.
+        let block_buffer_manager = BlockBufferManager::new(block_buffer_config);
+        let client = Arc::new(RethCli::new(consensus_args, txn_cache, balance_cache, hot_accounts, block_buffer_manager.clone(), shutdown_rx_cli).await);
         let chain_id = client.chain_id();
+        // Fault the hot accounts' pages in before consensus starts so the first rounds after a
+        // restart do not hit the disk for them. Execution layer caches still start cold.
+        client.prefault_hot_accounts();
 
         let coordinator = Arc::new(RethCoordinator::new(
             client.clone(),
@@ -360,35 +402,68 @@ fn main() {
         if std::env::var("MOCK_CONSENSUS").unwrap_or("false".to_string()).parse::<bool>().unwrap() {
             warn!("MOCK_CONSENSUS is enabled! This disables BFT consensus and should NEVER be used in production.");
             info!("start mock consensus");
-            let mock = MockConsensus::new(pool).await;
+            let mock = MockConsensus::new(pool, block_buffer_manager).await;
             tokio::spawn(async move {
                 mock.run().await;
             });
         } else {
-            let relayer = Arc::new(RelayerWrapper::new(relayer_config_path, datadir));
+            let relayer = Arc::new(RelayerWrapper::new(relayer_config_path, datadir, block_buffer_manager.clone()));
             match GLOBAL_RELAYER.set(relayer) {
                 Ok(_) => {}
                 Err(_) => {
                     panic!("failed to set global relayer");
                 }
             }
+            let trusted_checkpoint = match &trusted_checkpoint_source {
+                Some((source, waypoint)) => match TrustedCheckpoint::load(source, waypoint).await {
+                    Ok(checkpoint) => Some(checkpoint),
+                    Err(err) => {
+                        eprintln!("Error: {err}");
+                        let _ = shutdown_tx.send(());
+                        std::process::exit(1);
+                    }
+                },
+                None => None,
+            };
+            let mut config_storage =
+                ConfigStorageWrapper::new(Arc::new(RethCliConfigStorage::new(client.clone())))
+                    .with_epoch_invalidation(&block_buffer_manager);
+            if let Some(checkpoint) = &trusted_checkpoint {
+                config_storage = config_storage.with_trusted_checkpoint(checkpoint);
+            }
+            let config_reads = config_storage.read_history();
+            let config_storage = Arc::new(config_storage);
+            // Refuse to start rather than panic on the first config read in execution
+            if let Err(err) = client
+                .verify_system_contracts()
+                .and_then(|_| config_storage.check_required_configs().map_err(|e| e.to_string()))
+            {
+                eprintln!("Error: {err}");
+                let _ = shutdown_tx.send(());
+                std::process::exit(1);
+            }
             _engine = Some(
                 ConsensusEngine::init(
                     ConsensusEngineArgs {
                         node_config: gcei_config,
                         chain_id,
                         latest_block_number,
-                        config_storage: Some(Arc::new(ConfigStorageWrapper::new(Arc::new(
-                            RethCliConfigStorage::new(client),
-                        )))),
+                        config_storage: Some(config_storage),
+                        config_reads: Some(config_reads),
+                        block_buffer_manager,
+                        trusted_checkpoint,
+                        genesis_bundle,
+                        recovery_api: None,
                     },
                     pool,
                 )
                 .await,
             );
         }
-        coordinator.send_execution_args().await;
-        let result = coordinator.run().await;
+        let result = match coordinator.send_execution_args().await {
+            Ok(()) => coordinator.run().await,
+            Err(err) => Err(err),
+        };
         if let Err(err) = &result {
             tracing::error!("Reth coordinator stopped with error: {err}");
             let _ = shutdown_tx.send(());
@@ -1,32 +1,45 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     sync::Arc,
     time::{Duration, Instant},
 };
 
-use crate::{reth_cli::TxnCache, RethTransactionPool};
-use alloy_consensus::{transaction::SignerRecoverable, Transaction};
+use crate::{
+    balance_cache::{BalanceCache, SharedBalanceCache},
+    fee_market::{SharedFeeMarket, FEE_MARKET_SWEEP_INTERVAL},
+    inclusion_deadline::{now_ms, SharedInclusionDeadlines},
+    parked_txns,
+    private_txns::BroadcastPolicy,
+    reth_cli::TxnCache,
+    sig_verify::SigVerifier,
+    RethTransactionPool,
+};
+use alloy_consensus::Transaction;
 use alloy_eips::{Decodable2718, Encodable2718};
 use alloy_primitives::Address;
-use block_buffer_manager::TxPool;
-use dashmap::DashMap;
-use gaptos::api_types::{
-    account::{ExternalAccountAddress, ExternalChainId},
-    u256_define::TxnHash,
-    VerifiedTxn,
+use aptos_mempool::core_mempool::rate_limit::SenderRateLimiter;
+use block_buffer_manager::{OnTxnVerified, PooledTxn, TxPool, TxPoolStats};
+use gaptos::{
+    api_types::{
+        account::{ExternalAccountAddress, ExternalChainId},
+        u256_define::TxnHash,
+        VerifiedTxn,
+    },
+    aptos_types::account_address::AccountAddress,
 };
-use greth::{
+use greth_compat::{
     reth_primitives::{Recovered, TransactionSigned},
     reth_transaction_pool::{
         error::PoolErrorKind, BestTransactions, EthPooledTransaction, PoolTransaction,
         TransactionPool, ValidPoolTransaction,
     },
+    ChainStateReader, ExternalAccountAddressExt,
 };
 
 /// Maximum lifetime (TTL) of a txn_cache entry.
 ///
 /// `best_txns()` caches every selected pending transaction into `txn_cache`, and the
-/// only removal path is the committed-hash deletion in `RethCli::push_ordered_block()`
+/// only removal path is the committed-hash deletion in `RethCli::prepare_ordered_block()`
 /// when a transaction is committed. If a transaction is selected and cached but then
 /// replaced / evicted / invalidated and **never committed**, its
 /// `Arc<ValidPoolTransaction>` would linger forever — an attacker can spam free
@@ -37,6 +50,9 @@ const TXN_CACHE_ENTRY_TTL: Duration = Duration::from_secs(300); // 5 minutes
 /// txn_cache background sweep interval: scan and evict expired entries this often.
 const TXN_CACHE_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
 
+/// How often pending transactions that missed their inclusion deadline are evicted.
+const INCLUSION_DEADLINE_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Cache TTL for best transactions (in milliseconds)
 /// Can be configured via MEMPOOL_CACHE_TTL_MS environment variable
 fn cache_ttl() -> Duration {
@@ -78,13 +94,18 @@ impl CachedBest {
 pub struct Mempool {
     pool: RethTransactionPool,
     txn_cache: TxnCache,
+    balance_cache: SharedBalanceCache,
+    inclusion_deadlines: SharedInclusionDeadlines,
     cached_best: Arc<std::sync::Mutex<CachedBest>>,
     // Option so Drop can take it and call `shutdown_background()`: Mempool is
     // Arc'd into the consensus stack and can be dropped from an async context,
     // where a plain Runtime drop panics.
     runtime: Option<tokio::runtime::Runtime>,
+    sig_verifier: SigVerifier,
     enable_broadcast: bool,
+    broadcast_policy: BroadcastPolicy,
     chain_id: u64,
+    chain_state: Arc<dyn ChainStateReader>,
 }
 
 impl Drop for Mempool {
@@ -96,7 +117,15 @@ impl Drop for Mempool {
 }
 
 impl Mempool {
-    pub fn new(pool: RethTransactionPool, enable_broadcast: bool, chain_id: u64) -> Self {
+    pub(crate) fn new(
+        pool: RethTransactionPool,
+        enable_broadcast: bool,
+        chain_id: u64,
+        inclusion_deadlines: SharedInclusionDeadlines,
+        fee_market: SharedFeeMarket,
+        txn_cache: TxnCache,
+        chain_state: Arc<dyn ChainStateReader>,
+    ) -> Self {
         // Debug-only override: GRAVITY_BLACKHOLE_BROADCAST=1 forces this node
         // to keep RPC / consensus / block-sync paths fully healthy but drop
         // every outbound mempool broadcast — reproduces design.md §3.8 silent
@@ -113,7 +142,6 @@ impl Mempool {
             enable_broadcast
         };
         let runtime = tokio::runtime::Runtime::new().unwrap();
-        let txn_cache: TxnCache = Arc::new(DashMap::new());
 
         // Start the background sweeper: periodically drop txn_cache entries older than
         // the TTL, bounding transactions that were selected+cached but never committed,
@@ -142,25 +170,106 @@ impl Mempool {
             });
         }
 
+        // Evict transactions that are still pending once their inclusion deadline has passed,
+        // so a missed deadline is not followed by a late inclusion. Hints whose transaction
+        // already left the pool (committed or replaced) are simply forgotten.
+        {
+            let pool = pool.clone();
+            let inclusion_deadlines = inclusion_deadlines.clone();
+            runtime.spawn(async move {
+                let mut ticker = tokio::time::interval(INCLUSION_DEADLINE_SWEEP_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    let missed: Vec<_> = inclusion_deadlines
+                        .take_expired(now_ms())
+                        .into_iter()
+                        .filter(|hash| pool.contains(hash))
+                        .collect();
+                    if missed.is_empty() {
+                        continue;
+                    }
+                    inclusion_deadlines.record_missed(missed.len());
+                    let removed = pool.remove_transactions(missed);
+                    tracing::debug!(
+                        "inclusion deadline sweep: evicted {} transactions",
+                        removed.len()
+                    );
+                }
+            });
+        }
+
+        // Count nonce-gapped (parked) transactions and evict those whose gap never filled.
+        {
+            let pool = pool.clone();
+            let chain_state = chain_state.clone();
+            runtime.spawn(async move {
+                let mut ticker = tokio::time::interval(parked_txns::PARKED_TXN_SWEEP_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    let evicted = parked_txns::sweep(
+                        &pool,
+                        chain_state.as_ref(),
+                        parked_txns::parked_txn_ttl(),
+                    );
+                    if evicted > 0 {
+                        tracing::debug!("parked txn sweep: evicted {} expired entries", evicted);
+                    }
+                }
+            });
+        }
+
+        // Evict the cheapest non-ready transactions while the pool is full and quote the
+        // minimum viable gas price.
+        {
+            let pool = pool.clone();
+            let fee_market = fee_market.clone();
+            runtime.spawn(async move {
+                let mut ticker = tokio::time::interval(FEE_MARKET_SWEEP_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    let evicted = fee_market.sweep(&pool, pool.config());
+                    if evicted > 0 {
+                        tracing::debug!("fee market sweep: evicted {} transactions", evicted);
+                    }
+                }
+            });
+        }
+
+        let balance_cache = Arc::new(BalanceCache::new());
+        let sig_verifier = {
+            let pool = pool.clone();
+            let balance_cache = balance_cache.clone();
+            let handle = runtime.handle().clone();
+            SigVerifier::spawn(rate_limited(
+                SenderRateLimiter::from_knobs(),
+                move |txn, signer, on_verified| {
+                    admit_external_txn(&pool, &balance_cache, &handle, txn, signer, on_verified)
+                },
+            ))
+        };
+
         Self {
             pool,
             txn_cache,
+            balance_cache,
+            inclusion_deadlines,
             cached_best: Arc::new(std::sync::Mutex::new(CachedBest::new())),
             runtime: Some(runtime),
+            sig_verifier,
             enable_broadcast,
+            broadcast_policy: BroadcastPolicy::from_env(),
             chain_id,
+            chain_state,
         }
     }
 
     pub fn tx_cache(&self) -> TxnCache {
         self.txn_cache.clone()
     }
-}
 
-pub fn convert_account(acc: Address) -> ExternalAccountAddress {
-    let mut bytes = [0u8; 32];
-    bytes[12..].copy_from_slice(acc.as_slice());
-    ExternalAccountAddress::new(bytes)
+    pub(crate) fn balance_cache(&self) -> SharedBalanceCache {
+        self.balance_cache.clone()
+    }
 }
 
 fn to_verified_txn(
@@ -172,7 +281,7 @@ fn to_verified_txn(
     let txn = pool_txn.transaction.transaction().inner();
     VerifiedTxn::new(
         txn.encoded_2718(),
-        convert_account(sender),
+        ExternalAccountAddress::from_evm(sender),
         nonce,
         ExternalChainId::new(chain_id),
     )
@@ -187,12 +296,223 @@ fn to_verified_txn_from_reth_txn(
     let txn = pool_txn.inner();
     VerifiedTxn::new(
         txn.encoded_2718(),
-        convert_account(sender),
+        ExternalAccountAddress::from_evm(sender),
         nonce,
         ExternalChainId::new(chain_id),
     )
 }
 
+/// Filter of the transactions the caller already has, by sender, nonce and hash.
+type TxnFilter = dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool;
+
+/// A transaction `best_txns` proposes.
+struct SelectedTxn {
+    tier: u128,
+    sender: Address,
+    hash: alloy_primitives::TxHash,
+    txn: VerifiedTxn,
+    pool_txn: Arc<ValidPoolTransaction<EthPooledTransaction>>,
+}
+
+/// Pulls transactions off `iter` until `limit` transactions or `max_bytes` are selected,
+/// keeping the nonces of every sender consecutive. Every pending transaction is proposed,
+/// including the local-only ones [`BroadcastPolicy`] keeps out of the broadcast.
+fn select_best(
+    mut iter: impl Iterator<Item = Arc<ValidPoolTransaction<EthPooledTransaction>>>,
+    last_nonces: &mut HashMap<Address, u64>,
+    filter: Option<&TxnFilter>,
+    limit: usize,
+    max_bytes: u64,
+    chain_id: u64,
+) -> Vec<SelectedTxn> {
+    // Drive the iterator by hand so the byte budget is checked *before* pulling the next
+    // item. An adapter like `take_while` would consume (and record the nonce of) one extra
+    // txn past the budget, dropping it until the cache TTL and letting a later pull propose
+    // its successor nonce without it.
+    let mut result = Vec::new();
+    let mut total_bytes: u64 = 0;
+    while result.len() < limit && total_bytes < max_bytes {
+        let pool_txn = match iter.next() {
+            Some(txn) => txn,
+            None => break,
+        };
+        let sender = pool_txn.sender();
+        let nonce = pool_txn.nonce();
+
+        // Enforce nonce ordering: skip transactions that are not consecutive
+        if let Some(&last) = last_nonces.get(&sender) {
+            if nonce != last + 1 {
+                continue;
+            }
+        }
+
+        // Anchor the per-sender nonce for EVERY consecutive tx, BEFORE the
+        // caller's filter runs — including one the filter drops as already
+        // in-flight (its lower nonce was already proposed in an uncommitted
+        // batch and is excluded here via exclude_transactions). Recording only
+        // after the filter would drop this anchor: the next tx from the same
+        // sender would find no last_nonces entry, be treated as first-per-sender,
+        // and bypass the consecutiveness check — letting a future-nonce tx in.
+        last_nonces.insert(sender, nonce);
+
+        // Skip what the caller already has; its nonce stays anchored above
+        let sender_addr = ExternalAccountAddress::from_evm(sender);
+        if let Some(f) = filter {
+            let hash = TxnHash::from_bytes(pool_txn.hash().as_slice());
+            if !f((sender_addr.clone(), nonce, hash)) {
+                continue;
+            }
+        }
+
+        let verified_txn = to_verified_txn(pool_txn.clone(), chain_id);
+        let tx_hash: [u8; 32] = pool_txn.transaction.transaction().inner().hash().0;
+        // max_bytes is a prefetch hint: it caps how far we drain the cached
+        // iterator. It measures payload bytes, which under-count the fully
+        // serialized size the caller (get_batch_inner) enforces authoritatively,
+        // so we keep the txn that crosses the budget and err toward extra
+        // candidates rather than starving the block.
+        total_bytes += verified_txn.bytes().len() as u64;
+        let tier = pool_txn.transaction.priority_fee_or_price();
+        result.push(SelectedTxn {
+            tier,
+            sender,
+            hash: tx_hash.into(),
+            txn: verified_txn,
+            pool_txn,
+        });
+    }
+    result
+}
+
+/// The pending transactions the shared mempool broadcasts: all but the local-only ones, which
+/// `best_txns` still proposes, and those `filter` drops.
+fn broadcast_candidates(
+    pending: &[Arc<ValidPoolTransaction<EthPooledTransaction>>],
+    policy: &BroadcastPolicy,
+    filter: Option<&TxnFilter>,
+    chain_id: u64,
+) -> Vec<VerifiedTxn> {
+    pending
+        .iter()
+        .filter(|txn| !policy.is_local_only(txn))
+        .filter(|txn| {
+            filter.map_or(true, |filter| {
+                let sender = ExternalAccountAddress::from_evm(txn.sender());
+                filter((sender, txn.nonce(), TxnHash::from_bytes(txn.hash().as_slice())))
+            })
+        })
+        .map(|txn| to_verified_txn_from_reth_txn(txn.transaction.transaction().clone(), chain_id))
+        .collect()
+}
+
+/// Passes on to `admit` the transactions within the rate limit of their signer. The limiter is
+/// charged here rather than in the core mempool, which only knows the sender a transaction
+/// claims.
+fn rate_limited(
+    mut limiter: SenderRateLimiter,
+    mut admit: impl FnMut(TransactionSigned, Address, OnTxnVerified),
+) -> impl FnMut(TransactionSigned, Address, OnTxnVerified) {
+    move |txn, signer, on_verified| {
+        let sender = AccountAddress::new(ExternalAccountAddress::from_evm(signer).bytes());
+        if limiter.try_acquire(sender, txn.encode_2718_len(), Instant::now()) {
+            admit(txn, signer, on_verified);
+        } else {
+            tracing::debug!("tx from {:?} rejected by the sender rate limit", signer);
+            on_verified(false);
+        }
+    }
+}
+
+/// Whether `txn` is signed for a chain other than `chain_id`. Transactions signed without a
+/// chain id, i.e. before EIP-155, are left to the pool's validation.
+fn signed_for_other_chain(txn: &TransactionSigned, chain_id: u64) -> bool {
+    txn.chain_id().is_some_and(|signed| signed != chain_id)
+}
+
+/// What a pooled transaction has to satisfy in the current epoch: its signed chain id, the gas
+/// limit of the latest block and the minimum fee cap of the pool.
+#[derive(Clone, Copy, Debug)]
+struct EpochLimits {
+    chain_id: u64,
+    block_gas_limit: u64,
+    min_fee_cap: u128,
+}
+
+impl EpochLimits {
+    fn of(pool: &RethTransactionPool, chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            block_gas_limit: pool.block_info().block_gas_limit,
+            min_fee_cap: pool.config().minimal_protocol_basefee as u128,
+        }
+    }
+
+    fn admits(&self, txn: &TransactionSigned) -> bool {
+        // A pool that has not seen a block yet has no gas limit to check against
+        let within_gas_limit = self.block_gas_limit == 0 || txn.gas_limit() <= self.block_gas_limit;
+        !signed_for_other_chain(txn, self.chain_id) &&
+            within_gas_limit &&
+            txn.max_fee_per_gas() >= self.min_fee_cap
+    }
+}
+
+/// Adds a gossiped transaction whose signer has been recovered to the pool, and reports whether
+/// the pool took it to `on_verified`.
+fn admit_external_txn(
+    pool: &RethTransactionPool,
+    balance_cache: &BalanceCache,
+    runtime: &tokio::runtime::Handle,
+    txn: TransactionSigned,
+    signer: Address,
+    on_verified: OnTxnVerified,
+) {
+    let len = txn.encode_2718_len();
+    let recovered = Recovered::new_unchecked(txn, signer);
+    let pool_txn = EthPooledTransaction::new(recovered, len);
+    let pool = pool.clone();
+    let address = pool_txn.sender();
+    let to = pool_txn.to();
+    // Cheap pre-check against recently committed sender state, so obviously
+    // unpayable transactions never reach the pool validator.
+    let outcome = balance_cache.precheck(address, pool_txn.nonce(), *pool_txn.cost());
+    if outcome.is_rejected() {
+        tracing::debug!("tx rejected by balance pre-check: {:?} {:?} {:?}", address, to, outcome);
+        on_verified(false);
+        return;
+    }
+    runtime.spawn(async move {
+        let result = pool.add_external_transaction(pool_txn).await;
+        on_verified(result.is_ok());
+        if let Err(e) = result {
+            // Three-way classification:
+            //  * PoolErrorKind::Other(_)        — internal failure (DB/IO). Surface
+            //    at WARN so operators see it.
+            //  * is_bad_transaction() == true   — sender produced a malformed /
+            //    protocol-invalid tx. Node correctly rejected; WARN gives
+            //    visibility + monitoring signal without paging.
+            //  * everything else                — recoverable noise
+            //    (AlreadyImported dedup, ReplacementUnderpriced, nonce gap, low
+            //    fee, local config). INFO.
+            match &e.kind {
+                PoolErrorKind::Other(_) => {
+                    tracing::warn!(
+                        "Failed to add transaction (internal): {:?} {:?} {:?}",
+                        address,
+                        to,
+                        e
+                    );
+                }
+                _ if e.is_bad_transaction() => {
+                    tracing::warn!("rejected malformed tx: {:?} {:?} {:?}", address, to, e);
+                }
+                _ => {
+                    tracing::info!("tx not added (recoverable): {:?} {:?} {:?}", address, to, e);
+                }
+            }
+        }
+    });
+}
+
 impl TxPool for Mempool {
     fn best_txns(
         &self,
@@ -212,59 +532,24 @@ impl TxPool for Mempool {
         let chain_id = self.chain_id;
         // Take last_nonces out to avoid borrow conflict with best_txns iterator
         let mut last_nonces = std::mem::take(&mut best_txns.last_nonces);
-        // Drive the cached iterator by hand so the byte budget is checked *before*
-        // pulling the next item. An adapter like `take_while` would consume (and
-        // record the nonce of) one extra txn past the budget, dropping it until the
-        // cache TTL and letting a later pull propose its successor nonce without it.
-        let iter = best_txns.best_txns.as_mut().unwrap();
-        let mut result: Vec<VerifiedTxn> = Vec::new();
-        let mut total_bytes: u64 = 0;
-        while result.len() < limit && total_bytes < max_bytes {
-            let pool_txn = match iter.next() {
-                Some(txn) => txn,
-                None => break,
-            };
-            let sender = pool_txn.sender();
-            let nonce = pool_txn.nonce();
-
-            // Enforce nonce ordering: skip transactions that are not consecutive
-            if let Some(&last) = last_nonces.get(&sender) {
-                if nonce != last + 1 {
-                    continue;
-                }
-            }
-
-            // Anchor the per-sender nonce for EVERY consecutive tx, BEFORE the
-            // caller's filter runs — including one the filter drops as already
-            // in-flight (its lower nonce was already proposed in an uncommitted
-            // batch and is excluded here via exclude_transactions). Recording only
-            // after the filter would drop this anchor: the next tx from the same
-            // sender would find no last_nonces entry, be treated as first-per-sender,
-            // and bypass the consecutiveness check — letting a future-nonce tx in.
-            last_nonces.insert(sender, nonce);
-
-            // transactions from poisoning nonce tracking
-            let sender_addr = convert_account(sender);
-            if let Some(ref f) = filter {
-                let hash = TxnHash::from_bytes(pool_txn.hash().as_slice());
-                if !f((sender_addr.clone(), nonce, hash)) {
-                    continue;
-                }
-            }
-
-            let verified_txn = to_verified_txn(pool_txn.clone(), chain_id);
-            let tx_hash: [u8; 32] = pool_txn.transaction.transaction().inner().hash().0;
-            // max_bytes is a prefetch hint: it caps how far we drain the cached
-            // iterator. It measures payload bytes, which under-count the fully
-            // serialized size the caller (get_batch_inner) enforces authoritatively,
-            // so we keep the txn that crosses the budget and err toward extra
-            // candidates rather than starving the block.
-            total_bytes += verified_txn.bytes().len() as u64;
-            // Record the insertion time so the background sweeper can evict entries
-            // that stay uncommitted past the TTL.
-            txn_cache.insert(tx_hash, (Instant::now(), pool_txn));
-            result.push(verified_txn);
-        }
+        let selected = select_best(
+            best_txns.best_txns.as_mut().unwrap(),
+            &mut last_nonces,
+            filter.as_deref(),
+            limit,
+            max_bytes,
+            chain_id,
+        );
+        // (gas tier, sender, hash, txn), kept so deadline hints can reorder equal tiers below.
+        let mut result: Vec<(u128, Address, alloy_primitives::TxHash, VerifiedTxn)> = selected
+            .into_iter()
+            .map(|selected| {
+                // Record the insertion time so the background sweeper can evict entries
+                // that stay uncommitted past the TTL.
+                txn_cache.insert(selected.hash.0, (Instant::now(), selected.pool_txn));
+                (selected.tier, selected.sender, selected.hash, selected.txn)
+            })
+            .collect();
         // Put last_nonces back
         best_txns.last_nonces = last_nonces;
         if result.is_empty() {
@@ -274,7 +559,11 @@ impl TxPool for Mempool {
                 last_nonces: HashMap::new(),
             };
         }
-        Box::new(result.into_iter())
+        // Reth yields by descending tip, so equal tiers are contiguous; prefer soon-expiring
+        // senders within each tier without breaking per-sender nonce order.
+        self.inclusion_deadlines
+            .prioritize(&mut result, |(tier, sender, hash, _)| (*tier, *sender, *hash));
+        Box::new(result.into_iter().map(|(_, _, _, txn)| txn))
     }
 
     fn get_broadcast_txns(
@@ -284,90 +573,37 @@ impl TxPool for Mempool {
         if !self.enable_broadcast {
             return Box::new(std::iter::empty());
         }
-        let all_txns = self.pool.pending_transactions();
-        let iter = all_txns
-            .iter()
-            .filter_map(move |txn| {
-                let sender = convert_account(txn.sender());
-                let nonce = txn.nonce();
-                let hash = TxnHash::from_bytes(txn.hash().as_slice());
-                if let Some(filter) = &filter {
-                    if !filter((sender, nonce, hash)) {
-                        return None;
-                    }
-                }
-                let verified_txn = to_verified_txn_from_reth_txn(
-                    txn.transaction.transaction().clone(),
-                    self.chain_id,
-                );
-                Some(verified_txn)
-            })
-            .collect::<Vec<_>>();
-        Box::new(iter.into_iter())
+        let pending = self.pool.pending_transactions();
+        let txns = broadcast_candidates(
+            &pending,
+            &self.broadcast_policy,
+            filter.as_deref(),
+            self.chain_id,
+        );
+        Box::new(txns.into_iter())
     }
 
     fn add_external_txn(&self, txn: VerifiedTxn) -> bool {
-        let txn = TransactionSigned::decode_2718(&mut txn.bytes().as_slice());
-        match txn {
-            Ok(txn) => {
-                let signer = match txn.recover_signer() {
-                    Ok(s) => s,
-                    Err(e) => {
-                        tracing::error!("Failed to recover signer for external transaction: {e}");
-                        return false;
-                    }
-                };
-                let len = txn.encode_2718_len();
-                let recovered = Recovered::new_unchecked(txn, signer);
-                let pool_txn = EthPooledTransaction::new(recovered, len);
-                let pool = self.pool.clone();
-                let address = pool_txn.sender();
-                let to = pool_txn.to();
-                self.runtime.as_ref().expect("mempool runtime is only taken on drop").spawn(
-                    async move {
-                        if let Err(e) = pool.add_external_transaction(pool_txn).await {
-                            // Three-way classification:
-                            //  * PoolErrorKind::Other(_)        — internal failure (DB/IO). Surface
-                            //    at WARN so operators see it.
-                            //  * is_bad_transaction() == true   — sender produced a malformed /
-                            //    protocol-invalid tx. Node correctly rejected; WARN gives
-                            //    visibility + monitoring signal without paging.
-                            //  * everything else                — recoverable noise
-                            //    (AlreadyImported dedup, ReplacementUnderpriced, nonce gap, low
-                            //    fee, local config). INFO.
-                            match &e.kind {
-                                PoolErrorKind::Other(_) => {
-                                    tracing::warn!(
-                                        "Failed to add transaction (internal): {:?} {:?} {:?}",
-                                        address,
-                                        to,
-                                        e
-                                    );
-                                }
-                                _ if e.is_bad_transaction() => {
-                                    tracing::warn!(
-                                        "rejected malformed tx: {:?} {:?} {:?}",
-                                        address,
-                                        to,
-                                        e
-                                    );
-                                }
-                                _ => {
-                                    tracing::info!(
-                                        "tx not added (recoverable): {:?} {:?} {:?}",
-                                        address,
-                                        to,
-                                        e
-                                    );
-                                }
-                            }
-                        }
-                    },
+        self.add_external_txn_deferred(txn, Box::new(|_| {}))
+    }
+
+    fn add_external_txn_deferred(&self, txn: VerifiedTxn, on_verified: OnTxnVerified) -> bool {
+        match TransactionSigned::decode_2718(&mut txn.bytes().as_slice()) {
+            // The chain id of the wrapper is set by the peer, the signed one is what counts
+            Ok(txn) if signed_for_other_chain(&txn, self.chain_id) => {
+                tracing::debug!(
+                    "Rejecting transaction {} signed for chain {:?}",
+                    txn.hash(),
+                    txn.chain_id()
                 );
-                true
+                on_verified(false);
+                false
             }
+            // The signer is recovered in a batch, off this thread
+            Ok(txn) => self.sig_verifier.submit(txn, on_verified),
             Err(e) => {
                 tracing::error!("Failed to decode transaction: {}", e);
+                on_verified(false);
                 false
             }
         }
@@ -390,4 +626,196 @@ impl TxPool for Mempool {
         }
         self.pool.remove_transactions(eth_txn_hashes);
     }
+
+    fn on_new_epoch(&self, epoch: u64) {
+        // Transactions were admitted under the previous epoch's limits, evict the ones the new
+        // epoch no longer allows.
+        let limits = EpochLimits::of(&self.pool, self.chain_id);
+        let failed: Vec<_> = self
+            .pool
+            .pooled_transactions()
+            .iter()
+            .filter(|txn| !limits.admits(txn.transaction.transaction().inner()))
+            .map(|txn| *txn.hash())
+            .collect();
+        let evicted =
+            if failed.is_empty() { 0 } else { self.pool.remove_transactions(failed).len() };
+
+        // The cached iterator and balance snapshots were built under the previous epoch's
+        // config; rebuild both from the pool, which re-validates against the latest state.
+        *self.cached_best.lock().unwrap() = CachedBest::new();
+        self.balance_cache.clear();
+        tracing::info!(
+            "mempool entered epoch {}, evicted {} transactions over its limits {:?}",
+            epoch,
+            evicted,
+            limits
+        );
+    }
+
+    fn gas_unit_price(&self, txn: &VerifiedTxn) -> u64 {
+        // The tip is what orders reth's own pool, so batches rank transactions the same way.
+        match TransactionSigned::decode_2718(&mut txn.bytes().as_slice()) {
+            Ok(txn) => u64::try_from(txn.priority_fee_or_price()).unwrap_or(u64::MAX),
+            Err(e) => {
+                tracing::error!("Failed to decode transaction: {}", e);
+                0
+            }
+        }
+    }
+
+    fn stats(&self) -> Option<TxPoolStats> {
+        let now = Instant::now();
+        let pending = self.pool.pending_transactions();
+        let parked = self.pool.queued_transactions();
+        let oldest_txn_age = pending
+            .iter()
+            .chain(&parked)
+            .map(|txn| now.saturating_duration_since(txn.timestamp))
+            .max();
+        Some(TxPoolStats { pending: pending.len(), parked: parked.len(), oldest_txn_age })
+    }
+
+    fn size(&self) -> Option<TxPoolStats> {
+        let size = self.pool.pool_size();
+        Some(TxPoolStats {
+            pending: size.pending,
+            parked: size.basefee + size.queued,
+            oldest_txn_age: None,
+        })
+    }
+
+    fn sender_txns(&self, sender: &ExternalAccountAddress) -> Option<Vec<PooledTxn>> {
+        // Accounts that are not EVM addresses cannot have transactions in the pool
+        let Some(evm_sender) = sender.to_evm() else { return Some(vec![]) };
+        let now = Instant::now();
+        let pooled = self.pool.get_transactions_by_sender(evm_sender);
+        let nonces: BTreeSet<u64> = pooled.iter().map(|txn| txn.nonce()).collect();
+        let first_missing = parked_txns::first_missing_nonce(&nonces, self.committed_nonce(sender));
+        let mut txns: Vec<PooledTxn> = pooled
+            .iter()
+            .map(|txn| PooledTxn {
+                hash: TxnHash::from_bytes(txn.hash().as_slice()),
+                nonce: txn.nonce(),
+                parked: txn.nonce() >= first_missing,
+                age: now.saturating_duration_since(txn.timestamp),
+            })
+            .collect();
+        txns.sort_by_key(|txn| txn.nonce);
+        Some(txns)
+    }
+
+    fn committed_nonce(&self, sender: &ExternalAccountAddress) -> Option<u64> {
+        let sender = sender.to_evm()?;
+        let state = self
+            .chain_state
+            .latest_state()
+            .inspect_err(|e| tracing::warn!("cannot read the committed nonce of {}: {}", sender, e))
+            .ok()?;
+        state.account(&sender).ok().map(|account| account.unwrap_or_default().nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::private_txns::tests::{policy, pool_txn};
+    use alloy_consensus::{SignableTransaction, TxLegacy};
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use aptos_mempool::core_mempool::rate_limit::SenderRateLimits;
+    use greth_compat::reth_transaction_pool::TransactionOrigin;
+
+    fn signed_txn(signer: &PrivateKeySigner, nonce: u64) -> TransactionSigned {
+        let txn = TxLegacy { chain_id: Some(1), nonce, gas_limit: 21_000, ..Default::default() };
+        let signature = signer.sign_hash_sync(&txn.signature_hash()).unwrap();
+        txn.into_signed(signature).into()
+    }
+
+    #[test]
+    fn txns_signed_for_other_chains_are_detected() {
+        let signer = PrivateKeySigner::random();
+        let txn = signed_txn(&signer, 0);
+        assert!(!signed_for_other_chain(&txn, 1));
+        assert!(signed_for_other_chain(&txn, 1337));
+
+        let unprotected = TxLegacy { chain_id: None, gas_limit: 21_000, ..Default::default() };
+        let signature = signer.sign_hash_sync(&unprotected.signature_hash()).unwrap();
+        assert!(!signed_for_other_chain(&unprotected.into_signed(signature).into(), 1337));
+    }
+
+    #[test]
+    fn epoch_limits_check_chain_gas_limit_and_fee_cap() {
+        let signer = PrivateKeySigner::random();
+        let sign = |txn: TxLegacy| -> TransactionSigned {
+            let signature = signer.sign_hash_sync(&txn.signature_hash()).unwrap();
+            txn.into_signed(signature).into()
+        };
+        let txn = |chain_id: u64, gas_limit: u64, gas_price: u128| {
+            sign(TxLegacy { chain_id: Some(chain_id), gas_limit, gas_price, ..Default::default() })
+        };
+        let limits = EpochLimits { chain_id: 1, block_gas_limit: 30_000, min_fee_cap: 7 };
+
+        assert!(limits.admits(&txn(1, 30_000, 7)));
+        assert!(!limits.admits(&txn(2, 21_000, 7)));
+        assert!(!limits.admits(&txn(1, 30_001, 7)));
+        assert!(!limits.admits(&txn(1, 21_000, 6)));
+
+        let unknown_gas_limit = EpochLimits { block_gas_limit: 0, ..limits };
+        assert!(unknown_gas_limit.admits(&txn(1, 1_000_000, 7)));
+    }
+
+    #[test]
+    fn rate_limit_charges_the_recovered_signer() {
+        let (alice, bob) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (admitted_tx, admitted) = std::sync::mpsc::channel();
+        let limiter = SenderRateLimiter::new(SenderRateLimits {
+            txns_per_sec: Some(1.0),
+            bytes_per_sec: None,
+        });
+        let verifier = SigVerifier::spawn(rate_limited(limiter, move |txn, signer, _| {
+            admitted_tx.send((signer, txn.nonce())).unwrap();
+        }));
+
+        for txn in [signed_txn(&alice, 0), signed_txn(&alice, 1), signed_txn(&bob, 0)] {
+            assert!(verifier.submit(txn, Box::new(|_| {})));
+        }
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(admitted.recv_timeout(timeout).unwrap(), (alice.address(), 0));
+        assert_eq!(admitted.recv_timeout(timeout).unwrap(), (bob.address(), 0));
+        assert!(admitted.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn local_only_txns_are_proposed_but_not_broadcast() {
+        let (alice, bob, carol) =
+            (PrivateKeySigner::random(), PrivateKeySigner::random(), PrivateKeySigner::random());
+        let pending = vec![
+            pool_txn(&alice, 0, TransactionOrigin::External),
+            pool_txn(&bob, 0, TransactionOrigin::Private),
+            pool_txn(&carol, 0, TransactionOrigin::External),
+        ];
+        let senders = |txns: Vec<VerifiedTxn>| -> Vec<_> {
+            txns.iter().map(|txn| txn.sender().clone()).collect()
+        };
+
+        let broadcast = broadcast_candidates(&pending, &policy(&[carol.address()]), None, 1);
+        assert_eq!(senders(broadcast), vec![ExternalAccountAddress::from_evm(alice.address())]);
+
+        let proposed = select_best(
+            pending.iter().cloned(),
+            &mut HashMap::new(),
+            None,
+            usize::MAX,
+            u64::MAX,
+            1,
+        );
+        assert_eq!(
+            senders(proposed.into_iter().map(|selected| selected.txn).collect()),
+            [&alice, &bob, &carol]
+                .map(|signer| ExternalAccountAddress::from_evm(signer.address()))
+                .to_vec()
+        );
+    }
 }
@@ -0,0 +1,177 @@
+//! Nonce-gapped transactions live in reth's queued sub-pool, which is our parking lot: it is
+//! bounded by `--txpool.queued-max-count`/`--txpool.queued-max-size`, and reth promotes a
+//! queued transaction to pending as soon as the missing nonces arrive. What reth does not do
+//! is expire them quickly, so this module adds a TTL sweep, metrics and an inspection RPC.
+//!
+//! The queued sub-pool also holds transactions that are only waiting for the base fee to drop
+//! below their fee cap. Those are not parked: the sweep leaves them to reth's own eviction.
+
+use alloy_primitives::{Address, TxHash};
+use gaptos::aptos_metrics_core::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use greth_compat::{
+    reth_transaction_pool::{PoolTransaction, TransactionPool, ValidPoolTransaction},
+    ChainStateReader,
+};
+use jsonrpsee::RpcModule;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+static PARKED_TXNS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!("gravity_mempool_parked_txns", "Nonce-gapped transactions in the pool")
+        .unwrap()
+});
+
+static PARKED_TXNS_EVICTED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "gravity_mempool_parked_txns_evicted_total",
+        "Nonce-gapped transactions evicted after outliving the parking TTL"
+    )
+    .unwrap()
+});
+
+/// How often parked transactions are counted and checked against the TTL.
+pub(crate) const PARKED_TXN_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum time a nonce-gapped transaction may wait for its gap to fill.
+/// Can be configured via MEMPOOL_PARKED_TXN_TTL_SECS environment variable
+pub(crate) fn parked_txn_ttl() -> Duration {
+    static TTL: std::sync::OnceLock<Duration> = std::sync::OnceLock::new();
+    *TTL.get_or_init(|| {
+        let secs = std::env::var("MEMPOOL_PARKED_TXN_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(600); // Default 600s (10 minutes)
+        Duration::from_secs(secs)
+    })
+}
+
+/// First nonce a sender cannot reach: the committed nonce followed by the consecutive `pooled`
+/// nonces. A pooled transaction at or above it waits for a missing nonce. Without the committed
+/// nonce the lowest pooled one is taken, so no transaction is ever wrongly taken for parked.
+pub(crate) fn first_missing_nonce(pooled: &BTreeSet<u64>, committed: Option<u64>) -> u64 {
+    let Some(mut next) = committed.or_else(|| pooled.first().copied()) else {
+        return 0;
+    };
+    while pooled.contains(&next) {
+        next += 1;
+    }
+    next
+}
+
+/// The parked transactions among `queued`, queued transactions of `pool`: those waiting for a
+/// missing nonce of their sender. Queued transactions without a nonce gap, e.g. those waiting for
+/// the base fee, are not parked. The sweep and the RPC both go through it, so they agree on what
+/// is parked.
+fn parked_among<P: TransactionPool>(
+    pool: &P,
+    chain_state: &dyn ChainStateReader,
+    queued: Vec<Arc<ValidPoolTransaction<P::Transaction>>>,
+) -> Vec<Arc<ValidPoolTransaction<P::Transaction>>> {
+    let state = chain_state
+        .latest_state()
+        .inspect_err(|e| tracing::warn!("cannot read committed nonces of parked txns: {}", e))
+        .ok();
+    let mut first_missing: HashMap<Address, u64> = HashMap::new();
+    queued
+        .into_iter()
+        .filter(|txn| {
+            let sender = txn.sender();
+            let first_missing = *first_missing.entry(sender).or_insert_with(|| {
+                let pooled: BTreeSet<u64> =
+                    pool.get_transactions_by_sender(sender).iter().map(|txn| txn.nonce()).collect();
+                let committed = state
+                    .as_ref()
+                    .and_then(|state| state.account(&sender).ok())
+                    .map(|account| account.unwrap_or_default().nonce);
+                first_missing_nonce(&pooled, committed)
+            });
+            txn.nonce() >= first_missing
+        })
+        .collect()
+}
+
+/// How long a transaction has been parked, which is how long it has been in the pool.
+fn parked_for<T: PoolTransaction>(txn: &ValidPoolTransaction<T>, now: Instant) -> Duration {
+    now.saturating_duration_since(txn.timestamp)
+}
+
+/// Updates the parked gauge and evicts parked transactions older than `ttl`. Returns the number
+/// of evicted transactions.
+pub(crate) fn sweep<P: TransactionPool>(
+    pool: &P,
+    chain_state: &dyn ChainStateReader,
+    ttl: Duration,
+) -> usize {
+    let now = Instant::now();
+    let (expired, parked): (Vec<_>, Vec<_>) =
+        parked_among(pool, chain_state, pool.queued_transactions())
+            .into_iter()
+            .partition(|txn| parked_for(txn, now) >= ttl);
+    PARKED_TXNS.set(parked.len() as i64);
+    if expired.is_empty() {
+        return 0;
+    }
+    let evicted = pool.remove_transactions(expired.iter().map(|txn| *txn.hash()).collect()).len();
+    PARKED_TXNS_EVICTED.inc_by(evicted as u64);
+    evicted
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParkedTxn {
+    pub hash: TxHash,
+    pub nonce: u64,
+    pub parked_ms: u64,
+}
+
+/// `gravity_parkedTransactions(address)`: the sender's nonce-gapped transactions, by nonce.
+pub(crate) fn rpc_module<P: TransactionPool + Clone + 'static>(
+    pool: P,
+    chain_state: Arc<dyn ChainStateReader>,
+) -> RpcModule<()> {
+    let mut module = RpcModule::new(());
+    module
+        .register_method("gravity_parkedTransactions", move |params, _, _| {
+            let (sender,): (Address,) = params.parse()?;
+            let now = Instant::now();
+            let queued = pool
+                .queued_transactions()
+                .into_iter()
+                .filter(|txn| txn.sender() == sender)
+                .collect();
+            let mut parked: Vec<ParkedTxn> = parked_among(&pool, chain_state.as_ref(), queued)
+                .into_iter()
+                .map(|txn| ParkedTxn {
+                    hash: *txn.hash(),
+                    nonce: txn.nonce(),
+                    parked_ms: parked_for(&txn, now).as_millis() as u64,
+                })
+                .collect();
+            parked.sort_by_key(|txn| txn.nonce);
+            Ok::<_, jsonrpsee::types::ErrorObjectOwned>(parked)
+        })
+        .expect("gravity_parkedTransactions is registered once");
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_missing_nonce_follows_committed_nonce() {
+        let pooled = BTreeSet::from([5, 6, 8]);
+        // 5 and 6 are reachable from the committed nonce, 8 waits for 7
+        assert_eq!(first_missing_nonce(&pooled, Some(5)), 7);
+        // Nothing is reachable when the committed nonce is below the pooled ones
+        assert_eq!(first_missing_nonce(&pooled, Some(3)), 3);
+        // Without the committed nonce the lowest pooled transaction is never parked
+        assert_eq!(first_missing_nonce(&pooled, None), 7);
+        assert_eq!(first_missing_nonce(&BTreeSet::new(), None), 0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Consensus-side vs reth-side view of pooled transactions, for debugging "missing
+//! transaction" reports. The consensus side is `txn_cache`: every transaction `best_txns`
+//! handed to consensus that has not been committed yet. The reth side is the txpool's pending
+//! and queued sub-pools.
+
+use crate::reth_cli::TxnCache;
+use alloy_primitives::{Address, TxHash};
+use greth_compat::reth_transaction_pool::TransactionPool;
+use jsonrpsee::{types::ErrorObjectOwned, RpcModule};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Entries listed per category unless the caller asks for another limit.
+const DEFAULT_DIFF_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PoolEntry {
+    pub hash: TxHash,
+    pub sender: Address,
+    pub nonce: u64,
+}
+
+/// The same sender nonce maps to different transactions on the two sides, typically a
+/// replacement that landed in reth after consensus pulled the original.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NonceConflict {
+    pub sender: Address,
+    pub nonce: u64,
+    pub consensus_hash: TxHash,
+    pub reth_hash: TxHash,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PoolDiff {
+    pub consensus_count: usize,
+    pub reth_count: usize,
+    /// Pulled by consensus but gone from reth: dropped, evicted or replaced after selection.
+    pub consensus_only_count: usize,
+    pub consensus_only: Vec<PoolEntry>,
+    /// In reth but never pulled by consensus. Normal for fresh or nonce-gapped transactions,
+    /// suspicious when old.
+    pub reth_only_count: usize,
+    pub reth_only: Vec<PoolEntry>,
+    pub nonce_conflicts: Vec<NonceConflict>,
+}
+
+/// Diffs the two sides by hash. Entries whose sender nonce is held by a different hash on
+/// the other side are reported as conflicts only. Lists are ordered by sender and nonce and
+/// cut to `limit`; the counts are not.
+pub(crate) fn diff(consensus: &[PoolEntry], reth: &[PoolEntry], limit: usize) -> PoolDiff {
+    let reth_by_nonce: HashMap<(Address, u64), TxHash> =
+        reth.iter().map(|entry| ((entry.sender, entry.nonce), entry.hash)).collect();
+    let consensus_by_nonce: HashMap<(Address, u64), TxHash> =
+        consensus.iter().map(|entry| ((entry.sender, entry.nonce), entry.hash)).collect();
+
+    let mut nonce_conflicts = Vec::new();
+    let mut consensus_only = Vec::new();
+    for entry in consensus {
+        match reth_by_nonce.get(&(entry.sender, entry.nonce)) {
+            Some(reth_hash) if *reth_hash == entry.hash => {}
+            Some(reth_hash) => nonce_conflicts.push(NonceConflict {
+                sender: entry.sender,
+                nonce: entry.nonce,
+                consensus_hash: entry.hash,
+                reth_hash: *reth_hash,
+            }),
+            None => consensus_only.push(*entry),
+        }
+    }
+    let mut reth_only: Vec<PoolEntry> = reth
+        .iter()
+        .filter(|entry| !consensus_by_nonce.contains_key(&(entry.sender, entry.nonce)))
+        .copied()
+        .collect();
+
+    consensus_only.sort_by_key(|entry| (entry.sender, entry.nonce));
+    reth_only.sort_by_key(|entry| (entry.sender, entry.nonce));
+    nonce_conflicts.sort_by_key(|conflict| (conflict.sender, conflict.nonce));
+    let consensus_only_count = consensus_only.len();
+    let reth_only_count = reth_only.len();
+    consensus_only.truncate(limit);
+    reth_only.truncate(limit);
+    nonce_conflicts.truncate(limit);
+
+    PoolDiff {
+        consensus_count: consensus.len(),
+        reth_count: reth.len(),
+        consensus_only_count,
+        consensus_only,
+        reth_only_count,
+        reth_only,
+        nonce_conflicts,
+    }
+}
+
+fn snapshot<P: TransactionPool>(pool: &P, txn_cache: &TxnCache, limit: usize) -> PoolDiff {
+    let consensus: Vec<PoolEntry> = txn_cache
+        .iter()
+        .map(|entry| {
+            let txn = &entry.value().1;
+            PoolEntry { hash: *txn.hash(), sender: txn.sender(), nonce: txn.nonce() }
+        })
+        .collect();
+    let all = pool.all_transactions();
+    let reth: Vec<PoolEntry> = all
+        .pending
+        .iter()
+        .chain(all.queued.iter())
+        .map(|txn| PoolEntry { hash: *txn.hash(), sender: txn.sender(), nonce: txn.nonce() })
+        .collect();
+    diff(&consensus, &reth, limit)
+}
+
+/// `gravity_txpoolDiff([limit])`: snapshots both pools and reports what differs.
+pub(crate) fn rpc_module<P: TransactionPool + Clone + 'static>(
+    pool: P,
+    txn_cache: TxnCache,
+) -> RpcModule<()> {
+    let mut module = RpcModule::new(());
+    module
+        .register_method("gravity_txpoolDiff", move |params, _, _| {
+            let limit: Option<usize> = params.sequence().optional_next()?;
+            Ok::<_, ErrorObjectOwned>(snapshot(
+                &pool,
+                &txn_cache,
+                limit.unwrap_or(DEFAULT_DIFF_LIMIT),
+            ))
+        })
+        .expect("gravity_txpoolDiff is registered once");
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: u8, sender: u8, nonce: u64) -> PoolEntry {
+        PoolEntry { hash: TxHash::repeat_byte(hash), sender: Address::repeat_byte(sender), nonce }
+    }
+
+    #[test]
+    fn diff_separates_missing_and_conflicting_entries() {
+        let consensus = vec![entry(1, 0xa, 0), entry(2, 0xa, 1), entry(3, 0xb, 0)];
+        let reth = vec![entry(1, 0xa, 0), entry(9, 0xa, 1), entry(4, 0xc, 5)];
+
+        let pool_diff = diff(&consensus, &reth, 10);
+
+        assert_eq!(pool_diff.consensus_only, vec![entry(3, 0xb, 0)]);
+        assert_eq!(pool_diff.reth_only, vec![entry(4, 0xc, 5)]);
+        assert_eq!(
+            pool_diff.nonce_conflicts,
+            vec![NonceConflict {
+                sender: Address::repeat_byte(0xa),
+                nonce: 1,
+                consensus_hash: TxHash::repeat_byte(2),
+                reth_hash: TxHash::repeat_byte(9),
+            }]
+        );
+    }
+}
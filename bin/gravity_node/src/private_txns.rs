@@ -0,0 +1,170 @@
+//! Private order flow. Transactions submitted with `gravity_sendPrivateRawTransaction` enter
+//! reth's pool with the `Private` origin, which keeps them out of reth's own gossip, and the
+//! [`BroadcastPolicy`] keeps them out of the shared mempool broadcast. They remain in the
+//! pool's best transactions, so this node still puts them into its quorum store batches: the
+//! first other nodes to see one are the validators receiving the batch.
+//!
+//! Only validators propose batches. A full node keeps a private transaction until it expires.
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_eips::{Decodable2718, Encodable2718};
+use alloy_primitives::{Address, Bytes};
+use gaptos::aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use greth_compat::{
+    reth_primitives::{Recovered, TransactionSigned},
+    reth_transaction_pool::{
+        EthPooledTransaction, TransactionOrigin, TransactionPool, ValidPoolTransaction,
+    },
+};
+use jsonrpsee::{types::ErrorObjectOwned, RpcModule};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+
+/// JSON-RPC error code for invalid method parameters.
+const INVALID_PARAMS_CODE: i32 = -32602;
+
+/// JSON-RPC error code for a transaction the pool rejected (EIP-1474).
+const TRANSACTION_REJECTED_CODE: i32 = -32003;
+
+static PRIVATE_TXNS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_mempool_private_txns_total",
+        "Transactions submitted through gravity_sendPrivateRawTransaction by outcome",
+        &["outcome"]
+    )
+    .unwrap()
+});
+
+/// Decides which pending transactions the shared mempool must not broadcast.
+pub(crate) struct BroadcastPolicy {
+    local_only_senders: HashSet<Address>,
+}
+
+impl BroadcastPolicy {
+    /// Transactions of the local-only senders are never broadcast, however they were submitted.
+    /// Can be configured via MEMPOOL_LOCAL_ONLY_SENDERS environment variable, a comma separated
+    /// list of addresses
+    pub(crate) fn from_env() -> Self {
+        let local_only_senders = std::env::var("MEMPOOL_LOCAL_ONLY_SENDERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| match s.parse::<Address>() {
+                Ok(address) => Some(address),
+                Err(e) => {
+                    tracing::warn!("ignoring invalid local-only sender {}: {}", s, e);
+                    None
+                }
+            })
+            .collect::<HashSet<_>>();
+        if !local_only_senders.is_empty() {
+            tracing::info!(
+                "transactions of {} local-only senders are not broadcast",
+                local_only_senders.len()
+            );
+        }
+        Self { local_only_senders }
+    }
+
+    pub(crate) fn is_local_only(&self, txn: &ValidPoolTransaction<EthPooledTransaction>) -> bool {
+        txn.origin.is_private() || self.local_only_senders.contains(&txn.sender())
+    }
+}
+
+/// `gravity_sendPrivateRawTransaction(rawTx)`: adds a signed transaction to the pool as
+/// private, see the module documentation. Returns its hash like `eth_sendRawTransaction`.
+pub(crate) fn rpc_module<P>(pool: P) -> RpcModule<()>
+where
+    P: TransactionPool<Transaction = EthPooledTransaction> + Clone + 'static,
+{
+    let mut module = RpcModule::new(());
+    module
+        .register_async_method("gravity_sendPrivateRawTransaction", move |params, _, _| {
+            let pool = pool.clone();
+            async move {
+                let (raw,): (Bytes,) = params.parse()?;
+                let invalid = |e: String| {
+                    PRIVATE_TXNS_TOTAL.with_label_values(&["rejected"]).inc();
+                    ErrorObjectOwned::owned(INVALID_PARAMS_CODE, e, None::<()>)
+                };
+                let txn = TransactionSigned::decode_2718(&mut raw.as_ref())
+                    .map_err(|e| invalid(format!("failed to decode transaction: {e}")))?;
+                let signer = txn
+                    .recover_signer()
+                    .map_err(|e| invalid(format!("failed to recover signer: {e}")))?;
+                let len = txn.encode_2718_len();
+                let pool_txn =
+                    EthPooledTransaction::new(Recovered::new_unchecked(txn, signer), len);
+                let hash = pool
+                    .add_transaction(TransactionOrigin::Private, pool_txn)
+                    .await
+                    .map_err(|e| {
+                        PRIVATE_TXNS_TOTAL.with_label_values(&["rejected"]).inc();
+                        ErrorObjectOwned::owned(
+                            TRANSACTION_REJECTED_CODE,
+                            e.to_string(),
+                            None::<()>,
+                        )
+                    })?;
+                PRIVATE_TXNS_TOTAL.with_label_values(&["accepted"]).inc();
+                Ok::<_, ErrorObjectOwned>(hash)
+            }
+        })
+        .expect("gravity_sendPrivateRawTransaction is registered once");
+    module
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxLegacy};
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use greth_compat::reth_transaction_pool::identifier::{SenderId, TransactionId};
+    use std::{sync::Arc, time::Instant};
+
+    /// A pending transfer of `signer` as the pool holds it.
+    pub(crate) fn pool_txn(
+        signer: &PrivateKeySigner,
+        nonce: u64,
+        origin: TransactionOrigin,
+    ) -> Arc<ValidPoolTransaction<EthPooledTransaction>> {
+        let txn = TxLegacy { chain_id: Some(1), nonce, gas_limit: 21_000, ..Default::default() };
+        let signature = signer.sign_hash_sync(&txn.signature_hash()).unwrap();
+        let txn: TransactionSigned = txn.into_signed(signature).into();
+        let len = txn.encode_2718_len();
+        let sender_id = u64::from_be_bytes(signer.address()[..8].try_into().unwrap());
+        Arc::new(ValidPoolTransaction {
+            transaction: EthPooledTransaction::new(
+                Recovered::new_unchecked(txn, signer.address()),
+                len,
+            ),
+            transaction_id: TransactionId::new(SenderId::from(sender_id), nonce),
+            propagate: !origin.is_private(),
+            timestamp: Instant::now(),
+            origin,
+            authority_ids: None,
+        })
+    }
+
+    pub(crate) fn policy(local_only_senders: &[Address]) -> BroadcastPolicy {
+        BroadcastPolicy { local_only_senders: local_only_senders.iter().copied().collect() }
+    }
+
+    #[test]
+    fn private_txns_and_local_only_senders_are_not_broadcast() {
+        let (alice, bob) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        std::env::set_var(
+            "MEMPOOL_LOCAL_ONLY_SENDERS",
+            format!(" {}, not-an-address,,", bob.address()),
+        );
+        let policy = BroadcastPolicy::from_env();
+        assert_eq!(policy.local_only_senders, HashSet::from([bob.address()]));
+
+        assert!(policy.is_local_only(&pool_txn(&alice, 0, TransactionOrigin::Private)));
+        assert!(!policy.is_local_only(&pool_txn(&alice, 0, TransactionOrigin::External)));
+        assert!(!policy.is_local_only(&pool_txn(&alice, 0, TransactionOrigin::Local)));
+        assert!(policy.is_local_only(&pool_txn(&bob, 0, TransactionOrigin::External)));
+    }
+}
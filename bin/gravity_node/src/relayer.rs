@@ -1,7 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use async_trait::async_trait;
-use block_buffer_manager::get_block_buffer_manager;
+use block_buffer_manager::BlockBufferManager;
 use bytes::Bytes;
 use gaptos::api_types::{
     config_storage::{OnChainConfig, GLOBAL_CONFIG_STORAGE},
@@ -9,7 +9,7 @@ use gaptos::api_types::{
     relayer::{PollResult, Relayer},
     ExecError,
 };
-use greth::reth_pipe_exec_layer_relayer::OracleRelayerManager;
+use greth_compat::reth_pipe_exec_layer_relayer::OracleRelayerManager;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tracing::{info, warn};
@@ -93,10 +93,15 @@ pub struct RelayerWrapper {
     manager: OracleRelayerManager,
     tracker: ProviderProgressTracker,
     config: RelayerConfig,
+    block_buffer_manager: Arc<BlockBufferManager>,
 }
 
 impl RelayerWrapper {
-    pub fn new(config_path: Option<PathBuf>, datadir: PathBuf) -> Self {
+    pub fn new(
+        config_path: Option<PathBuf>,
+        datadir: PathBuf,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
         let config = config_path
             .and_then(|path| match RelayerConfig::from_file(&path) {
                 Ok(cfg) => {
@@ -115,12 +120,12 @@ impl RelayerWrapper {
 
         // update reth commit and use
         let manager = OracleRelayerManager::new(datadir);
-        Self { manager, tracker: ProviderProgressTracker::new(), config }
+        Self { manager, tracker: ProviderProgressTracker::new(), config, block_buffer_manager }
     }
 
     /// Fetch oracle source states from on-chain storage
-    async fn get_oracle_source_states() -> Vec<OracleSourceState> {
-        let block_number = get_block_buffer_manager().latest_commit_block_number().await;
+    async fn get_oracle_source_states(&self) -> Vec<OracleSourceState> {
+        let block_number = self.block_buffer_manager.latest_commit_block_number().await;
         info!("get_oracle_source_states latest commit block number: {}", block_number);
 
         let config_bytes = match GLOBAL_CONFIG_STORAGE
@@ -270,7 +275,7 @@ impl Relayer for RelayerWrapper {
             .ok_or_else(|| ExecError::Other(format!("Provider {uri} not found in local config")))?;
 
         // Get onchain state for this URI using source_type/source_id from URI
-        let oracle_states = Self::get_oracle_source_states().await;
+        let oracle_states = self.get_oracle_source_states().await;
         info!("Oracle states: {:?}", oracle_states);
         let oracle_state =
             Self::find_oracle_state_for_uri(uri, &oracle_states).ok_or_else(|| {
@@ -302,7 +307,7 @@ impl Relayer for RelayerWrapper {
     // All URIs starting with gravity:// are definitely UnsupportedJWK
     async fn get_last_state(&self, uri: &str) -> Result<PollResult, ExecError> {
         // Get onchain state for this URI using source_type/source_id from URI
-        let oracle_states = Self::get_oracle_source_states().await;
+        let oracle_states = self.get_oracle_source_states().await;
         let oracle_state = Self::find_oracle_state_for_uri(uri, &oracle_states);
 
         // Extract nonce and block_number for reconciliation
@@ -1,8 +1,17 @@
-use crate::ConsensusArgs;
+use crate::{
+    account_prefetch::{touched_accounts, AccountPrefetcher},
+    balance_cache::SharedBalanceCache,
+    hot_accounts::HotAccounts,
+    reth_coordinator::StartingBlockIds,
+    ConsensusArgs,
+};
 use alloy_consensus::transaction::SignerRecoverable;
 use alloy_eips::{eip4895::Withdrawals, Decodable2718};
-use alloy_primitives::{Address, TxHash, B256, U256};
-use block_buffer_manager::get_block_buffer_manager;
+use alloy_primitives::{keccak256, Address, TxHash, B256, U256};
+use block_buffer_manager::{
+    block_buffer_manager::{BlockExecutionMeta, ExecutionReport},
+    BlockBufferManager, EpochChangeInProgress,
+};
 use core::panic;
 use dashmap::DashMap;
 use gaptos::api_types::{
@@ -12,33 +21,31 @@ use gaptos::api_types::{
     u256_define::BlockId as ExternalBlockId,
     ExternalBlock, GLOBAL_CRYPTO_TXN_HASHER,
 };
-use greth::reth_transaction_pool::{EthPooledTransaction, ValidPoolTransaction};
+use greth_compat::reth_transaction_pool::{EthPooledTransaction, ValidPoolTransaction};
+#[allow(deprecated)]
 use proposer_reth_map::get_reth_address_by_index;
 
-use alloy_rpc_types_eth::TransactionRequest;
 use gaptos::aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
-use greth::{
-    gravity_storage::block_view_storage::BlockViewStorage,
+use greth_compat::{
     reth::rpc::builder::auth::AuthServerHandle,
-    reth_db::DatabaseEnv,
-    reth_node_api::NodeTypesWithDBAdapter,
-    reth_node_ethereum::EthereumNode,
-    reth_pipe_exec_layer_ext_v2::{ExecutionResult, OrderedBlock, PipeExecLayerApi},
+    reth_pipe_exec_layer_ext_v2::{ExecutionResult, OrderedBlock},
     reth_primitives::TransactionSigned,
-    reth_provider::{providers::BlockchainProvider, BlockNumReader, ChainSpecProvider},
-    reth_rpc_api::eth::{helpers::EthCall, RpcTypes},
+    ChainStateReader, ExecutionPipe, ExternalAccountAddressExt,
 };
 use once_cell::sync::Lazy;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 use std::{
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use tokio::sync::{broadcast, Mutex};
+use tokio::{
+    sync::{broadcast, mpsc, watch, Mutex},
+    task::JoinHandle,
+};
 use tracing::*;
 
 const FILTER_REASON_DECODE_FAILED: &str = "decode_failed";
@@ -66,34 +73,22 @@ static GCEI_COINBASE_FALLBACK_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
-pub(crate) type RethBlockChainProvider =
-    BlockchainProvider<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>;
-
-pub(crate) type RethTransactionPool = greth::reth_transaction_pool::Pool<
-    greth::reth_transaction_pool::TransactionValidationTaskExecutor<
-        greth::reth_transaction_pool::EthTransactionValidator<
-            RethBlockChainProvider,
-            greth::reth_transaction_pool::EthPooledTransaction,
-        >,
-    >,
-    greth::reth_transaction_pool::CoinbaseTipOrdering<
-        greth::reth_transaction_pool::EthPooledTransaction,
-    >,
-    greth::reth_transaction_pool::blobstore::DiskFileBlobStore,
->;
-
-pub(crate) trait RethEthCall:
-    EthCall<NetworkTypes: RpcTypes<TransactionRequest = TransactionRequest>>
-{
-}
-
-impl<T> RethEthCall for T where
-    T: EthCall<NetworkTypes: RpcTypes<TransactionRequest = TransactionRequest>>
-{
+/// Ordered blocks prepared ahead of the one being pushed to the pipe.
+/// Can be configured via EXECUTION_PIPELINE_DEPTH environment variable.
+fn execution_pipeline_depth() -> usize {
+    static DEPTH: OnceLock<usize> = OnceLock::new();
+    *DEPTH.get_or_init(|| {
+        std::env::var("EXECUTION_PIPELINE_DEPTH")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|depth| *depth > 0)
+            .unwrap_or(4) // Default 4 blocks
+    })
 }
 
-pub(crate) type RethPipeExecLayerApi<EthApi> =
-    PipeExecLayerApi<BlockViewStorage<RethBlockChainProvider>, EthApi>;
+pub(crate) use greth_compat::types::{
+    RethBlockChainProvider, RethEthCall, RethPipeExecLayerApi, RethTransactionPool,
+};
 
 /// txn_cache entry value: `(inserted_at, transaction)`.
 /// Recording the insertion time lets the background sweeper evict entries that stay
@@ -102,60 +97,82 @@ pub(crate) type RethPipeExecLayerApi<EthApi> =
 pub(crate) type TxnCacheEntry = (Instant, Arc<ValidPoolTransaction<EthPooledTransaction>>);
 pub(crate) type TxnCache = Arc<DashMap<[u8; 32], TxnCacheEntry>>;
 
-pub struct RethCli<EthApi: RethEthCall> {
+pub struct RethCli {
     _auth: AuthServerHandle,
-    pipe_api: RethPipeExecLayerApi<EthApi>,
+    pipe_api: Box<dyn ExecutionPipe>,
     chain_id: u64,
-    provider: RethBlockChainProvider,
+    provider: Arc<dyn ChainStateReader>,
+    account_prefetcher: AccountPrefetcher,
     _txn_listener: Mutex<tokio::sync::mpsc::Receiver<TxHash>>,
     _pool: RethTransactionPool,
     txn_cache: TxnCache,
+    balance_cache: SharedBalanceCache,
+    hot_accounts: Arc<HotAccounts>,
+    block_buffer_manager: Arc<BlockBufferManager>,
+    /// Push time of the blocks handed to the pipe, by block number, until their result.
+    executing_blocks: DashMap<u64, Instant>,
+    /// Transactions and execution time of the executed blocks, by block number, until their
+    /// gas used can be read and they are reported to the block buffer manager.
+    executed_blocks: DashMap<u64, (u64, Duration)>,
+    /// Recipients of the transactions of the blocks handed to the pipe, by block number, until
+    /// their result. Their balance cache entries are invalidated along with the senders'.
+    block_recipients: DashMap<u64, Vec<Address>>,
     _txn_batch_size: usize,
     current_epoch: AtomicU64,
     shutdown: broadcast::Receiver<()>,
 }
 
-pub fn convert_account(acc: Address) -> ExternalAccountAddress {
-    let mut bytes = [0u8; 32];
-    bytes[12..].copy_from_slice(acc.as_slice());
-    ExternalAccountAddress::new(bytes)
-}
-
 #[allow(clippy::ptr_arg)]
 fn calculate_txn_hash(bytes: &Vec<u8>) -> [u8; 32] {
     alloy_primitives::utils::keccak256(bytes).as_slice().try_into().unwrap()
 }
 
-impl<EthApi: RethEthCall> RethCli<EthApi> {
-    pub async fn new(
+impl RethCli {
+    pub async fn new<EthApi: RethEthCall + 'static>(
         args: ConsensusArgs<EthApi>,
         txn_cache: TxnCache,
+        balance_cache: SharedBalanceCache,
+        hot_accounts: Arc<HotAccounts>,
+        block_buffer_manager: Arc<BlockBufferManager>,
         shutdown: broadcast::Receiver<()>,
     ) -> Self {
-        let chian_info = args.provider.chain_spec().chain;
-        let chain_id = match chian_info.into_kind() {
-            greth::reth_chainspec::ChainKind::Named(n) => n as u64,
-            greth::reth_chainspec::ChainKind::Id(id) => id,
-        };
+        let chain_id = args.provider.chain_id();
+        let provider: Arc<dyn ChainStateReader> = Arc::new(args.provider);
         GLOBAL_CRYPTO_TXN_HASHER.get_or_init(|| Box::new(calculate_txn_hash));
         RethCli {
             _auth: args.engine_api,
-            pipe_api: args.pipeline_api,
+            pipe_api: Box::new(args.pipeline_api),
             chain_id,
-            provider: args.provider,
+            account_prefetcher: AccountPrefetcher::spawn(provider.clone()),
+            provider,
             _txn_listener: Mutex::new(args.tx_listener),
             _pool: args.pool,
             txn_cache,
+            balance_cache,
+            hot_accounts,
+            block_buffer_manager,
+            executing_blocks: DashMap::new(),
+            executed_blocks: DashMap::new(),
+            block_recipients: DashMap::new(),
             _txn_batch_size: 2000,
             current_epoch: AtomicU64::new(0),
             shutdown,
         }
     }
 
+    /// Checks that the system contracts the onchain configs are read from are deployed.
+    pub fn verify_system_contracts(&self) -> Result<(), String> {
+        crate::system_contracts::verify_deployed(self.provider.as_ref())
+    }
+
     pub fn chain_id(&self) -> u64 {
         self.chain_id
     }
 
+    pub fn block_buffer_manager(&self) -> &Arc<BlockBufferManager> {
+        &self.block_buffer_manager
+    }
+
     fn txn_to_signed(
         bytes: &[u8],
         _chain_id: u64,
@@ -175,6 +192,7 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
 
     /// Get reth coinbase address from proposer's validator index
     /// Returns the reth account address of the proposer if found, otherwise returns Address::ZERO
+    #[allow(deprecated)]
     fn get_coinbase_from_proposer_index(proposer_index: Option<u64>) -> Address {
         let index = match proposer_index {
             Some(idx) => idx,
@@ -225,14 +243,25 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
         }
     }
 
-    pub async fn push_ordered_block(
+    /// Recovers the senders of an ordered block and builds what the pipe executes. Blocking,
+    /// it runs on the blocking pool ahead of the block being pushed.
+    fn prepare_ordered_block(
         &self,
         mut block: ExternalBlock,
         parent_id: B256,
-    ) -> Result<(), String> {
+        execution_meta: BlockExecutionMeta,
+    ) -> OrderedBlock {
         trace!("push ordered block {:?} with parent id {}", block, parent_id);
+        // The reth pipe has no system transactions, and this node registers no provider that
+        // would propose any
+        if !execution_meta.system_txns.is_empty() {
+            warn!(
+                "Ignoring {} system txns of block {}, the reth execution layer does not support them",
+                execution_meta.system_txns.len(),
+                block.block_meta.block_number
+            );
+        }
         let system_time = Instant::now();
-        let pipe_api = &self.pipe_api;
 
         let mut senders = vec![None; block.txns.len()];
         let mut transactions = vec![None; block.txns.len()];
@@ -289,6 +318,12 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
         }
         let senders = valid_senders;
         let transactions = valid_transactions;
+        // A block pushed while earlier ones execute waits for them, the prefetch thread warms
+        // the accounts it touches meanwhile
+        if !self.executing_blocks.is_empty() {
+            let parent_number = block.block_meta.block_number.saturating_sub(1);
+            self.account_prefetcher.queue(parent_number, touched_accounts(&senders, &transactions));
+        }
 
         let (randao, randomness) = match block.block_meta.randomness {
             Some(randao) => {
@@ -299,14 +334,18 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
 
         info!("push ordered block time deserialize {:?}ms", system_time.elapsed().as_millis());
 
-        // Get reth coinbase from proposer's validator index
-        let coinbase = Self::get_coinbase_from_proposer_index(block.block_meta.proposer_index);
+        // Prefer the proposer address consensus resolved at ordering time; the index lookup is
+        // only a fallback for blocks ordered without one.
+        let coinbase = match execution_meta.proposer_reth_address {
+            Some(address) => Address::from(address),
+            None => Self::get_coinbase_from_proposer_index(block.block_meta.proposer_index),
+        };
         info!(
             "block_number: {:?} proposer_index: {:?} coinbase: {:?}",
             block.block_meta.block_number, block.block_meta.proposer_index, coinbase
         );
 
-        pipe_api.push_ordered_block(OrderedBlock {
+        OrderedBlock {
             parent_id,
             id: B256::from_slice(block.block_meta.block_id.as_bytes()),
             number: block.block_meta.block_number,
@@ -321,7 +360,29 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
             failed_proposer_indices: block.block_meta.failed_proposer_indices,
             extra_data: block.extra_data,
             randomness,
-        });
+        }
+    }
+
+    fn push_prepared_block(&self, block: OrderedBlock) {
+        if self.balance_cache.is_enabled() {
+            let recipients = block.transactions.iter().filter_map(|txn| txn.to()).collect();
+            self.block_recipients.insert(block.number, recipients);
+        }
+        self.executing_blocks.insert(block.number, Instant::now());
+        self.pipe_api.push_ordered_block(block);
+    }
+
+    /// Hands prepared blocks to the pipe in order. The blocks behind are prepared meanwhile, so
+    /// their signer recovery overlaps the execution of the blocks ahead.
+    async fn push_prepared_blocks(
+        &self,
+        mut prepared_rx: mpsc::Receiver<JoinHandle<OrderedBlock>>,
+    ) -> Result<(), String> {
+        while let Some(prepared) = prepared_rx.recv().await {
+            let block =
+                prepared.await.map_err(|e| format!("failed to prepare ordered block: {e}"))?;
+            self.push_prepared_block(block);
+        }
         Ok(())
     }
 
@@ -356,38 +417,124 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
         Ok(())
     }
 
-    pub async fn start_execution(&self) -> Result<(), String> {
+    /// Reloads the committed balance/nonce of accounts touched by recently executed blocks.
+    /// Failures only leave the accounts uncached, admission then falls back to the pool.
+    fn refresh_balance_cache(&self) {
+        let senders = self.balance_cache.take_pending_refresh();
+        if senders.is_empty() {
+            return;
+        }
+        let state = match self.provider.latest_state() {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("failed to open latest state for balance cache refresh: {}", e);
+                return;
+            }
+        };
+        for sender in senders {
+            match state.account(&sender) {
+                Ok(account) => {
+                    let account = account.unwrap_or_default();
+                    self.balance_cache.update(sender, account.balance, account.nonce);
+                }
+                Err(e) => warn!("failed to read account {} for balance cache: {}", sender, e),
+            }
+        }
+    }
+
+    /// Reads the accounts recorded as hot before the last shutdown so that their database pages
+    /// are in the OS page cache when the first blocks after a restart execute. This does not
+    /// populate the execution layer's in-memory state caches, which `ExecutionPipe` gives no
+    /// access to, so those still start cold. Meant to run before consensus starts.
+    pub fn prefault_hot_accounts(&self) {
+        let accounts = self.hot_accounts.load();
+        if accounts.is_empty() {
+            return;
+        }
+        let start = Instant::now();
+        let state = match self.provider.latest_state() {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("failed to open latest state to prefault hot accounts: {}", e);
+                return;
+            }
+        };
+        let mut failed = 0;
+        for account in &accounts {
+            if state.account(account).is_err() {
+                failed += 1;
+            }
+        }
+        info!(
+            "prefaulted the pages of {} hot accounts in {:?}ms, {} failed",
+            accounts.len(),
+            start.elapsed().as_millis(),
+            failed
+        );
+    }
+
+    /// Hands ordered blocks to the pipe until shutdown, or until the starting block ids are
+    /// republished by a restarted consensus engine.
+    pub async fn start_execution(
+        self: &Arc<Self>,
+        mut restart: watch::Receiver<Option<StartingBlockIds>>,
+    ) -> Result<(), String> {
         let mut start_ordered_block = self
             .provider
-            .recover_block_number()
+            .latest_block_number()
             .map_err(|e| format!("Failed to recover block number: {e}"))? +
             1;
         // Initialize current_epoch from block buffer manager
-        let buffer_epoch = get_block_buffer_manager().get_current_epoch().await;
+        let buffer_epoch = self.block_buffer_manager.get_current_epoch().await;
         self.current_epoch.store(buffer_epoch, Ordering::SeqCst);
         info!("start_execution initialized with epoch {}", buffer_epoch);
+        // When the coordinator restarts a failed loop, blocks executed since the last persisted
+        // one are still buffered as executed; ask for them again instead of waiting for ordered
+        // blocks that will not come. Committed ones cannot be re-delivered, the node then shuts
+        // down and startup recovery replays them from consensusdb.
+        self.executing_blocks.clear();
+        self.executed_blocks.clear();
+        self.block_recipients.clear();
+        let redelivered = self
+            .block_buffer_manager
+            .request_redelivery(start_ordered_block)
+            .await
+            .map_err(|e| format!("Failed to request re-delivery: {e}"))?;
+        if redelivered > 0 {
+            info!("re-delivering {} executed blocks from {}", redelivered, start_ordered_block);
+        }
+
+        let (prepared_tx, prepared_rx) = mpsc::channel(execution_pipeline_depth());
+        let pusher = tokio::spawn({
+            let reth_cli = self.clone();
+            async move { reth_cli.push_prepared_blocks(prepared_rx).await }
+        });
 
         // missing signals between iterations
         let mut shutdown = self.shutdown.resubscribe();
-        loop {
+        'execution: loop {
             let current_epoch = self.current_epoch.load(Ordering::SeqCst);
             // max executing block number
             let exec_blocks = tokio::select! {
-                res = get_block_buffer_manager().get_ordered_blocks(start_ordered_block, None, current_epoch) => res,
+                res = self.block_buffer_manager.get_ordered_blocks(start_ordered_block, None, current_epoch) => res,
                 _ = shutdown.recv() => {
                     info!("Shutdown signal received, stopping execution loop");
                     break;
                 }
+                Ok(()) = restart.changed() => {
+                    info!("Starting block ids republished, stopping execution loop");
+                    break;
+                }
             };
             if let Err(e) = exec_blocks {
                 let from = start_ordered_block;
-                if e.to_string().contains("Buffer is in epoch change") ||
-                    current_epoch != get_block_buffer_manager().get_current_epoch().await
+                if e.is::<EpochChangeInProgress>() ||
+                    current_epoch != self.block_buffer_manager.get_current_epoch().await
                 {
                     // consume_epoch_change returns (new_epoch, epoch_change_block_number)
                     // and resets latest_epoch_change_block_number to 0 atomically.
                     let (new_epoch, epoch_change_block_number) =
-                        get_block_buffer_manager().consume_epoch_change().await;
+                        self.block_buffer_manager.consume_epoch_change().await;
                     start_ordered_block = epoch_change_block_number + 1;
                     let old_epoch = self.current_epoch.swap(new_epoch, Ordering::SeqCst);
                     info!("Buffer is in epoch change, reset start_ordered_block from {} to {}, epoch from {} to {}", 
@@ -405,7 +552,7 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
 
             start_ordered_block =
                 exec_blocks.last().expect("checked non-empty above").0.block_meta.block_number + 1;
-            for (block, parent_id) in exec_blocks {
+            for (block, parent_id, execution_meta) in exec_blocks {
                 info!(
                     "send reth ordered block num {:?} id {:?} epoch {:?} with parent id {}",
                     block.block_meta.block_number,
@@ -414,10 +561,18 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
                     parent_id
                 );
                 let parent_id = B256::from_slice(parent_id.as_bytes());
-                self.push_ordered_block(block, parent_id).await?;
+                let reth_cli = self.clone();
+                let prepared = tokio::task::spawn_blocking(move || {
+                    reth_cli.prepare_ordered_block(block, parent_id, execution_meta)
+                });
+                if prepared_tx.send(prepared).await.is_err() {
+                    // The pusher stopped, its result says why
+                    break 'execution;
+                }
             }
         }
-        Ok(())
+        drop(prepared_tx);
+        pusher.await.map_err(|e| format!("push ordered blocks task join failed: {e}"))?
     }
 
     pub async fn start_commit_vote(&self) -> Result<(), String> {
@@ -426,6 +581,9 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
         const MAX_CONSECUTIVE_ERRORS: u32 = 5;
 
         let mut shutdown = self.shutdown.resubscribe();
+        // The pipe executes one block at a time, a block pushed while its parent executes only
+        // starts once the parent's result is out
+        let mut last_result_at: Option<Instant> = None;
         loop {
             let execution_result = tokio::select! {
                 res = self.recv_compute_res() => res,
@@ -469,37 +627,95 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
             let block_number = execution_result.block_number;
             let tx_infos = execution_result.txs_info;
             let epoch = self.current_epoch.load(Ordering::SeqCst);
-            let txn_status = Arc::new(Some(
+            let txn_status: Vec<TxnStatus> = tx_infos
+                .iter()
+                .map(|tx_info| TxnStatus {
+                    txn_hash: *tx_info.tx_hash,
+                    sender: ExternalAccountAddress::from_evm(tx_info.sender).bytes(),
+                    nonce: tx_info.nonce,
+                    is_discarded: tx_info.is_discarded,
+                })
+                .collect();
+            self.block_buffer_manager
+                .set_commit_payload(block_id, block_number, epoch, txn_outcomes_digest(&txn_status))
+                .await
+                .map_err(|e| format!("failed to set commit payload: {e}"))?;
+            let txn_status = Arc::new(Some(txn_status));
+            let recipients =
+                self.block_recipients.remove(&block_number).map(|(_, recipients)| recipients);
+            self.balance_cache.invalidate(
                 tx_infos
                     .iter()
-                    .map(|tx_info| TxnStatus {
-                        txn_hash: *tx_info.tx_hash,
-                        sender: convert_account(tx_info.sender).bytes(),
-                        nonce: tx_info.nonce,
-                        is_discarded: tx_info.is_discarded,
-                    })
-                    .collect(),
-            ));
+                    .map(|tx_info| tx_info.sender)
+                    .chain(recipients.into_iter().flatten()),
+            );
+            self.hot_accounts.record(block_number, tx_infos.iter().map(|tx_info| tx_info.sender));
+            let txns = tx_infos.len() as u64;
             let events = execution_result.gravity_events;
-            get_block_buffer_manager()
+            self.block_buffer_manager
                 .set_compute_res(block_id, block_hash_data, block_number, epoch, txn_status, events)
                 .await
                 .map_err(|e| format!("failed to set compute res: {e}"))?;
+            let result_at = Instant::now();
+            if let Some((_, pushed_at)) = self.executing_blocks.remove(&block_number) {
+                let started_at = last_result_at.map_or(pushed_at, |last| last.max(pushed_at));
+                self.executed_blocks.insert(block_number, (txns, result_at - started_at));
+            }
+            last_result_at = Some(result_at);
+            // Blocks discarded at an epoch change never get a result
+            self.executing_blocks.retain(|number, _| *number > block_number);
+            self.block_recipients.retain(|number, _| *number > block_number);
         }
         Ok(())
     }
 
+    /// Reports the executed blocks up to `committed` to the block buffer manager, in order, once
+    /// their gas used can be read. Blocks not canonical yet are retried after the next commit,
+    /// those the provider still has no header for once `persisted` passed them are dropped.
+    fn report_executed_blocks(&self, committed: u64, persisted: u64) {
+        let mut numbers: Vec<u64> = self
+            .executed_blocks
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|number| *number <= committed)
+            .collect();
+        numbers.sort_unstable();
+        for block_number in numbers {
+            let gas = match self.provider.block_gas_used(block_number) {
+                Ok(Some(gas)) => Some(gas),
+                Ok(None) if block_number > persisted => break,
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("failed to read the gas used by block {}: {}", block_number, e);
+                    None
+                }
+            };
+            let Some((_, (txns, execution_time))) = self.executed_blocks.remove(&block_number)
+            else {
+                continue;
+            };
+            if let Some(gas) = gas {
+                self.block_buffer_manager.report_execution(ExecutionReport {
+                    block_number,
+                    txns,
+                    gas,
+                    execution_time,
+                });
+            }
+        }
+    }
+
     pub async fn start_commit(&self) -> Result<(), String> {
         let mut start_commit_num = self
             .provider
-            .recover_block_number()
+            .latest_block_number()
             .map_err(|e| format!("Failed to recover block number: {e}"))? +
             1;
         let mut shutdown = self.shutdown.resubscribe();
         loop {
             let epoch = self.current_epoch.load(Ordering::SeqCst);
             let block_ids = tokio::select! {
-                res = get_block_buffer_manager().get_committed_blocks(start_commit_num, None, epoch) => res,
+                res = self.block_buffer_manager.get_committed_blocks(start_commit_num, None, epoch) => res,
                 _ = shutdown.recv() => {
                     info!("Shutdown signal received, stopping commit loop");
                     break;
@@ -535,32 +751,36 @@ impl<EthApi: RethEthCall> RethCli<EthApi> {
 
             let last_block_number = self
                 .provider
-                .recover_block_number()
+                .latest_block_number()
                 .map_err(|e| format!("Failed to recover block number: {e}"))?;
-            get_block_buffer_manager()
+            self.block_buffer_manager
                 .set_state(start_commit_num - 1, last_block_number)
                 .await
                 .map_err(|e| format!("failed to set state: {e}"))?;
+            self.report_executed_blocks(start_commit_num - 1, last_block_number);
+            self.hot_accounts.maybe_persist(start_commit_num - 1);
             for (block_number, persist_notifier) in persist_notifiers {
                 info!("wait_for_block_persistence num {:?} send persist_notifier", block_number);
                 self.wait_for_block_persistence(block_number).await?;
                 let _ = persist_notifier.send(()).await;
             }
+            // Read once the committed blocks are persisted, so the state has their transfers
+            self.refresh_balance_cache();
         }
         Ok(())
     }
 }
-pub struct RethCliConfigStorage<EthApi: RethEthCall> {
-    reth_cli: Arc<RethCli<EthApi>>,
+pub struct RethCliConfigStorage {
+    reth_cli: Arc<RethCli>,
 }
 
-impl<EthApi: RethEthCall> RethCliConfigStorage<EthApi> {
-    pub fn new(reth_cli: Arc<RethCli<EthApi>>) -> Self {
+impl RethCliConfigStorage {
+    pub fn new(reth_cli: Arc<RethCli>) -> Self {
         Self { reth_cli }
     }
 }
 
-impl<EthApi: RethEthCall> ConfigStorage for RethCliConfigStorage<EthApi> {
+impl ConfigStorage for RethCliConfigStorage {
     fn fetch_config_bytes(
         &self,
         config_name: OnChainConfig,
@@ -569,3 +789,15 @@ impl<EthApi: RethEthCall> ConfigStorage for RethCliConfigStorage<EthApi> {
         self.reth_cli.pipe_api.fetch_config_bytes(config_name, block_number)
     }
 }
+
+/// Commit payload of a block: the keccak256 of the hash of every transaction consensus ordered
+/// in it, each followed by whether it was discarded. Discarded transactions are not in the
+/// execution layer block, so its hash says nothing about them.
+fn txn_outcomes_digest(txn_status: &[TxnStatus]) -> Vec<u8> {
+    let mut outcomes = Vec::with_capacity(txn_status.len() * 33);
+    for status in txn_status {
+        outcomes.extend_from_slice(&status.txn_hash);
+        outcomes.push(status.is_discarded as u8);
+    }
+    keccak256(outcomes).to_vec()
+}
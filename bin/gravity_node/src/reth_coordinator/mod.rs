@@ -1,58 +1,99 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use crate::reth_cli::{RethCli, RethEthCall};
+use crate::reth_cli::RethCli;
 use alloy_primitives::B256;
-use block_buffer_manager::get_block_buffer_manager;
-use greth::reth_pipe_exec_layer_ext_v2::ExecutionArgs;
+use greth_compat::reth_pipe_exec_layer_ext_v2::ExecutionArgs;
 use tokio::{
-    sync::{broadcast, oneshot, Mutex},
+    sync::{broadcast, oneshot, watch},
     task::{JoinError, JoinHandle},
 };
 use tracing::info;
 
 const COORDINATOR_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
-pub struct RethCoordinator<EthApi: RethEthCall> {
-    reth_cli: Arc<RethCli<EthApi>>,
-    execution_args_tx: Arc<Mutex<Option<oneshot::Sender<ExecutionArgs>>>>,
+/// How many times a failed execution loop is restarted before the node shuts down.
+const MAX_EXECUTION_RESTARTS: u32 = 3;
+
+/// Pause before restarting a failed execution loop.
+const EXECUTION_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Block ids consensus starts from, by block number.
+pub(crate) type StartingBlockIds = Arc<BTreeMap<u64, B256>>;
+
+pub struct RethCoordinator {
+    reth_cli: Arc<RethCli>,
+    /// Reth's pipe takes its execution args once, when it starts, so only the first publish
+    /// sends them.
+    execution_args_tx: Mutex<Option<oneshot::Sender<ExecutionArgs>>>,
+    /// Republished every time the consensus engine (re)starts. A republish restarts the
+    /// execution loop, which resumes from the block buffer manager of the new engine.
+    starting_block_ids: watch::Sender<Option<StartingBlockIds>>,
     shutdown_tx: broadcast::Sender<()>,
 }
 
-impl<EthApi: RethEthCall> RethCoordinator<EthApi> {
+impl RethCoordinator {
     pub fn new(
-        reth_cli: Arc<RethCli<EthApi>>,
+        reth_cli: Arc<RethCli>,
         _latest_block_number: u64,
         execution_args_tx: oneshot::Sender<ExecutionArgs>,
         shutdown_tx: broadcast::Sender<()>,
     ) -> Self {
         Self {
             reth_cli,
-            execution_args_tx: Arc::new(Mutex::new(Some(execution_args_tx))),
+            execution_args_tx: Mutex::new(Some(execution_args_tx)),
+            starting_block_ids: watch::channel(None).0,
             shutdown_tx,
         }
     }
 
-    pub async fn send_execution_args(&self) {
-        let mut guard = self.execution_args_tx.lock().await;
-        let execution_args_tx = guard.take();
+    /// Publishes the block ids consensus starts from. The first call hands them to reth's pipe.
+    /// Called again after the consensus engine restarts, the running pipe goes on from its own
+    /// state and the execution loop is restarted.
+    pub async fn send_execution_args(&self) -> Result<(), String> {
+        let block_number_to_block_id: BTreeMap<_, _> = self
+            .reth_cli
+            .block_buffer_manager()
+            .block_number_to_block_id()
+            .await
+            .into_iter()
+            .map(|(block_number, block_id)| (block_number, B256::new(block_id.bytes())))
+            .collect();
+        info!("send_execution_args block_number_to_block_id: {:?}", block_number_to_block_id);
+        let execution_args_tx = self.execution_args_tx.lock().unwrap().take();
         if let Some(execution_args_tx) = execution_args_tx {
-            let block_number_to_block_id = get_block_buffer_manager()
-                .block_number_to_block_id()
-                .await
-                .into_iter()
-                .map(|(block_number, block_id)| (block_number, B256::new(block_id.bytes())))
-                .collect();
-            info!("send_execution_args block_number_to_block_id: {:?}", block_number_to_block_id);
-            let execution_args = ExecutionArgs { block_number_to_block_id };
+            let execution_args = ExecutionArgs {
+                block_number_to_block_id: block_number_to_block_id
+                    .iter()
+                    .map(|(number, id)| (*number, *id))
+                    .collect(),
+            };
             execution_args_tx
                 .send(execution_args)
-                .expect("Failed to send execution args: reth receiver may have been dropped");
+                .map_err(|_| "reth's pipe stopped before taking its execution args".to_string())?;
         }
+        let previous =
+            self.starting_block_ids.send_replace(Some(Arc::new(block_number_to_block_id)));
+        if previous.is_some() {
+            info!("consensus restarted, reth keeps its running pipe");
+        }
+        Ok(())
+    }
+
+    /// Runs the execution loop until it fails, the node shuts down or the starting block ids
+    /// are republished.
+    fn spawn_execution(&self) -> JoinHandle<Result<(), String>> {
+        let reth_cli = self.reth_cli.clone();
+        let restart = self.starting_block_ids.subscribe();
+        tokio::spawn(async move { reth_cli.start_execution(restart).await })
     }
 
     pub async fn run(&self) -> Result<(), String> {
-        let reth_cli1 = self.reth_cli.clone();
-        let mut h1 = tokio::spawn(async move { reth_cli1.start_execution().await });
+        let mut starting_block_ids = self.starting_block_ids.subscribe();
+        let mut h1 = self.spawn_execution();
 
         let reth_cli2 = self.reth_cli.clone();
         let mut h2 = tokio::spawn(async move { reth_cli2.start_commit_vote().await });
@@ -61,35 +102,62 @@ impl<EthApi: RethEthCall> RethCoordinator<EthApi> {
         let mut h3 = tokio::spawn(async move { reth_cli3.start_commit().await });
 
         let mut shutdown_rx = self.shutdown_tx.subscribe();
-        tokio::select! {
-            res = &mut h1 => {
-                let result = Self::task_result("start_execution", res);
-                self.signal_shutdown();
-                Self::wait_for_task("start_commit_vote", h2).await;
-                Self::wait_for_task("start_commit", h3).await;
-                result
-            }
-            res = &mut h2 => {
-                let result = Self::task_result("start_commit_vote", res);
-                self.signal_shutdown();
-                Self::wait_for_task("start_execution", h1).await;
-                Self::wait_for_task("start_commit", h3).await;
-                result
-            }
-            res = &mut h3 => {
-                let result = Self::task_result("start_commit", res);
-                self.signal_shutdown();
-                Self::wait_for_task("start_execution", h1).await;
-                Self::wait_for_task("start_commit_vote", h2).await;
-                result
-            }
-            _ = shutdown_rx.recv() => {
-                info!("Shutdown signal received, stopping reth coordinator tasks");
-                self.signal_shutdown();
-                Self::wait_for_task("start_execution", h1).await;
-                Self::wait_for_task("start_commit_vote", h2).await;
-                Self::wait_for_task("start_commit", h3).await;
-                Ok(())
+        let mut execution_restarts = 0;
+        loop {
+            tokio::select! {
+                res = &mut h1 => {
+                    let result = Self::task_result("start_execution", res);
+                    if result.is_ok() && starting_block_ids.has_changed().unwrap_or(false) {
+                        // Stopped for a consensus restart, the new loop resumes from the last
+                        // persisted block of the new engine's block buffer manager
+                        drop(starting_block_ids.borrow_and_update());
+                        info!("consensus restarted, restarting the execution loop");
+                        h1 = self.spawn_execution();
+                        continue;
+                    }
+                    if result.is_err() && execution_restarts < MAX_EXECUTION_RESTARTS {
+                        // The restarted loop resumes from the last persisted block and asks for
+                        // the blocks executed since to be re-delivered.
+                        execution_restarts += 1;
+                        let watermarks =
+                            self.reth_cli.block_buffer_manager().watermarks().await;
+                        tracing::warn!(
+                            "restarting the execution loop ({}/{}), block buffer {:?}",
+                            execution_restarts,
+                            MAX_EXECUTION_RESTARTS,
+                            watermarks
+                        );
+                        tokio::time::sleep(EXECUTION_RESTART_DELAY).await;
+                        h1 = self.spawn_execution();
+                        continue;
+                    }
+                    self.signal_shutdown();
+                    Self::wait_for_task("start_commit_vote", h2).await;
+                    Self::wait_for_task("start_commit", h3).await;
+                    return result;
+                }
+                res = &mut h2 => {
+                    let result = Self::task_result("start_commit_vote", res);
+                    self.signal_shutdown();
+                    Self::wait_for_task("start_execution", h1).await;
+                    Self::wait_for_task("start_commit", h3).await;
+                    return result;
+                }
+                res = &mut h3 => {
+                    let result = Self::task_result("start_commit", res);
+                    self.signal_shutdown();
+                    Self::wait_for_task("start_execution", h1).await;
+                    Self::wait_for_task("start_commit_vote", h2).await;
+                    return result;
+                }
+                _ = shutdown_rx.recv() => {
+                    info!("Shutdown signal received, stopping reth coordinator tasks");
+                    self.signal_shutdown();
+                    Self::wait_for_task("start_execution", h1).await;
+                    Self::wait_for_task("start_commit_vote", h2).await;
+                    Self::wait_for_task("start_commit", h3).await;
+                    return Ok(());
+                }
             }
         }
     }
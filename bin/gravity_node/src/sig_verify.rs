@@ -0,0 +1,214 @@
+//! Signer recovery of gossiped transactions. Recovering one ECDSA signature costs far more
+//! than decoding the transaction, so instead of doing it on the shared mempool's threads one
+//! transaction at a time, transactions are queued and recovered in batches on a dedicated
+//! thread pool.
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_primitives::Address;
+use block_buffer_manager::OnTxnVerified;
+use gaptos::aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
+use greth_compat::reth_primitives::TransactionSigned;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+
+/// Most transactions recovered in one batch.
+const BATCH_SIZE: usize = 256;
+
+/// Transactions waiting for recovery. Gossip beyond this is dropped until the queue drains.
+const QUEUE_SIZE: usize = 16_384;
+
+static SIG_VERIFY_REJECTED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_mempool_sig_verify_rejected_txns_total",
+        "Gossiped transactions dropped before signer recovery or rejected by it",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+/// Number of threads recovering signers.
+/// Can be configured via MEMPOOL_SIG_VERIFY_THREADS environment variable
+fn sig_verify_threads() -> usize {
+    std::env::var("MEMPOOL_SIG_VERIFY_THREADS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|threads| *threads > 0)
+        .unwrap_or(4)
+}
+
+/// A transaction waiting for recovery.
+struct Pending {
+    txn: TransactionSigned,
+    on_verified: OnTxnVerified,
+}
+
+impl Pending {
+    fn reject(self, reason: &str) {
+        SIG_VERIFY_REJECTED_TXNS.with_label_values(&[reason]).inc();
+        (self.on_verified)(false);
+    }
+}
+
+pub(crate) struct SigVerifier {
+    queue: SyncSender<Pending>,
+}
+
+impl SigVerifier {
+    /// Starts the verifier, which hands every transaction with a valid signature to `admit`
+    /// along with the callback to report whether it was admitted. It stops once the
+    /// `SigVerifier` is dropped.
+    pub(crate) fn spawn(
+        admit: impl FnMut(TransactionSigned, Address, OnTxnVerified) + Send + 'static,
+    ) -> Self {
+        let (queue, rx) = mpsc::sync_channel(QUEUE_SIZE);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(sig_verify_threads())
+            .thread_name(|i| format!("mempool-sigverify-{i}"))
+            .build()
+            .expect("failed to build the signature verification pool");
+        std::thread::Builder::new()
+            .name("mempool-sigverify".to_string())
+            .spawn(move || run(rx, pool, admit))
+            .expect("failed to spawn the signature verification thread");
+        Self { queue }
+    }
+
+    /// Queues `txn` for recovery. Returns false if it was dropped because the queue is full.
+    /// `on_verified` is called once in any case.
+    pub(crate) fn submit(&self, txn: TransactionSigned, on_verified: OnTxnVerified) -> bool {
+        match self.queue.try_send(Pending { txn, on_verified }) {
+            Ok(()) => true,
+            Err(TrySendError::Full(pending)) => {
+                pending.reject("queue_full");
+                false
+            }
+            Err(TrySendError::Disconnected(pending)) => {
+                (pending.on_verified)(false);
+                false
+            }
+        }
+    }
+}
+
+fn run(
+    rx: Receiver<Pending>,
+    pool: rayon::ThreadPool,
+    mut admit: impl FnMut(TransactionSigned, Address, OnTxnVerified),
+) {
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        batch.extend(rx.try_iter().take(BATCH_SIZE - 1));
+        verify_batch(&pool, batch, &mut admit);
+    }
+}
+
+fn verify_batch(
+    pool: &rayon::ThreadPool,
+    batch: Vec<Pending>,
+    admit: &mut impl FnMut(TransactionSigned, Address, OnTxnVerified),
+) {
+    let recovered: Vec<_> = pool.install(|| {
+        batch
+            .into_par_iter()
+            .map(|pending| {
+                let signer = pending.txn.recover_signer();
+                (pending, signer)
+            })
+            .collect()
+    });
+    for (pending, signer) in recovered {
+        match signer {
+            Ok(signer) => admit(pending.txn, signer, pending.on_verified),
+            Err(e) => {
+                tracing::warn!("Failed to recover signer for external transaction: {e}");
+                pending.reject("invalid_signature");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, Transaction, TxLegacy};
+    use alloy_primitives::{Signature, U256};
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use std::sync::{Arc, Mutex};
+
+    fn valid_txn(signer: &PrivateKeySigner, nonce: u64) -> TransactionSigned {
+        let txn = TxLegacy { chain_id: Some(1), nonce, gas_limit: 21_000, ..Default::default() };
+        let signature = signer.sign_hash_sync(&txn.signature_hash()).unwrap();
+        txn.into_signed(signature).into()
+    }
+
+    fn invalid_txn(nonce: u64) -> TransactionSigned {
+        let txn = TxLegacy { chain_id: Some(1), nonce, gas_limit: 21_000, ..Default::default() };
+        txn.into_signed(Signature::new(U256::from(1), U256::MAX, false)).into()
+    }
+
+    /// Verifies `txns` as one batch on `threads` threads, returning the nonces admitted and the
+    /// nonces reported rejected.
+    fn verify(threads: usize, txns: Vec<TransactionSigned>) -> (Vec<u64>, Vec<u64>) {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let rejected = Arc::new(Mutex::new(vec![]));
+        let batch = txns
+            .into_iter()
+            .map(|txn| {
+                let (nonce, rejected) = (txn.nonce(), rejected.clone());
+                let on_verified: OnTxnVerified = Box::new(move |admitted| {
+                    if !admitted {
+                        rejected.lock().unwrap().push(nonce);
+                    }
+                });
+                Pending { txn, on_verified }
+            })
+            .collect();
+        let mut admitted = vec![];
+        verify_batch(&pool, batch, &mut |txn, _, _| admitted.push(txn.nonce()));
+        let rejected = rejected.lock().unwrap().clone();
+        (admitted, rejected)
+    }
+
+    #[test]
+    fn invalid_signatures_do_not_reject_valid_txns() {
+        let signer = PrivateKeySigner::random();
+        // Recovered in parallel, so an invalid signature may be recovered before or after the
+        // valid ones around it; the outcome must not depend on it
+        for _ in 0..20 {
+            let txns =
+                (0..64)
+                    .map(|nonce| {
+                        if nonce % 8 == 3 {
+                            invalid_txn(nonce)
+                        } else {
+                            valid_txn(&signer, nonce)
+                        }
+                    })
+                    .collect();
+            let (admitted, rejected) = verify(4, txns);
+            assert_eq!(admitted, (0..64).filter(|nonce| nonce % 8 != 3).collect::<Vec<_>>());
+            assert_eq!(rejected, (0..64).filter(|nonce| nonce % 8 == 3).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn verifier_reports_every_submitted_txn() {
+        let signer = PrivateKeySigner::random();
+        let verifier = SigVerifier::spawn(|_, _, on_verified| on_verified(true));
+        let (tx, rx) = mpsc::channel();
+        for txn in [valid_txn(&signer, 0), invalid_txn(1)] {
+            let (tx, nonce) = (tx.clone(), txn.nonce());
+            let on_verified: OnTxnVerified = Box::new(move |admitted| {
+                tx.send((nonce, admitted)).unwrap();
+            });
+            assert!(verifier.submit(txn, on_verified));
+        }
+        let timeout = Duration::from_secs(10);
+        let mut results =
+            vec![rx.recv_timeout(timeout).unwrap(), rx.recv_timeout(timeout).unwrap()];
+        results.sort();
+        assert_eq!(results, vec![(0, true), (1, false)]);
+    }
+}
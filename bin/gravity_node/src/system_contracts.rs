@@ -0,0 +1,93 @@
+use alloy_primitives::Address;
+use greth_compat::ChainStateReader;
+use system_contracts::{EPOCH_CONFIG_ADDRESS, RECONFIGURATION_ADDRESS, VALIDATOR_MANAGER_ADDRESS};
+
+/// System contracts the execution layer reads the onchain configs from. Calling an address
+/// without code returns empty data, which the execution layer then fails to decode deep in
+/// block execution, so their deployment is checked before consensus starts.
+const SYSTEM_CONTRACTS: [(&str, Address); 3] = [
+    ("ValidatorManager", VALIDATOR_MANAGER_ADDRESS),
+    ("Reconfiguration", RECONFIGURATION_ADDRESS),
+    ("EpochConfig", EPOCH_CONFIG_ADDRESS),
+];
+
+/// Checks that every system contract is deployed in the latest persisted state.
+pub(crate) fn verify_deployed(provider: &dyn ChainStateReader) -> Result<(), String> {
+    let state = provider.latest_state().map_err(|e| format!("failed to read the state: {e}"))?;
+    let mut missing = vec![];
+    for (name, address) in SYSTEM_CONTRACTS {
+        let deployed = state
+            .has_code(&address)
+            .map_err(|e| format!("failed to read the code of {name} at {address}: {e}"))?;
+        if !deployed {
+            missing.push(format!("{name} at {address}"));
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!(
+            "system contracts are not deployed: {}. The genesis of the execution layer does not \
+             match this version of the node",
+            missing.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use greth_compat::{AccountState, AccountStateReader};
+    use std::collections::HashSet;
+
+    /// State where only the contracts in `deployed` have code.
+    #[derive(Clone)]
+    struct DeployedState {
+        deployed: HashSet<Address>,
+    }
+
+    impl AccountStateReader for DeployedState {
+        fn account(&self, _address: &Address) -> anyhow::Result<Option<AccountState>> {
+            Ok(None)
+        }
+
+        fn has_code(&self, address: &Address) -> anyhow::Result<bool> {
+            Ok(self.deployed.contains(address))
+        }
+    }
+
+    impl ChainStateReader for DeployedState {
+        fn chain_id(&self) -> u64 {
+            1
+        }
+
+        fn latest_block_number(&self) -> anyhow::Result<u64> {
+            Ok(0)
+        }
+
+        fn latest_state(&self) -> anyhow::Result<Box<dyn AccountStateReader>> {
+            Ok(Box::new(self.clone()))
+        }
+
+        fn state_at(&self, _number: u64) -> anyhow::Result<Option<Box<dyn AccountStateReader>>> {
+            Ok(Some(Box::new(self.clone())))
+        }
+
+        fn block_gas_used(&self, _number: u64) -> anyhow::Result<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn passes_when_every_contract_is_deployed() {
+        let deployed = SYSTEM_CONTRACTS.iter().map(|(_, address)| *address).collect();
+        assert_eq!(verify_deployed(&DeployedState { deployed }), Ok(()));
+    }
+
+    #[test]
+    fn names_the_missing_contracts() {
+        let deployed = HashSet::from([VALIDATOR_MANAGER_ADDRESS, EPOCH_CONFIG_ADDRESS]);
+        let err = verify_deployed(&DeployedState { deployed }).unwrap_err();
+        assert!(err.contains(&format!("Reconfiguration at {RECONFIGURATION_ADDRESS}")), "{err}");
+        assert!(!err.contains("ValidatorManager"), "{err}");
+    }
+}
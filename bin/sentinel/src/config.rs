@@ -1,6 +1,10 @@
 use anyhow::Result;
 use serde::Deserialize;
-use std::{collections::HashMap, fmt, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::Path,
+};
 
 /// Alert priority levels. P0 is the highest (most critical).
 #[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
@@ -35,6 +39,14 @@ pub struct Config {
     pub chain_monitor: Option<crate::chain_monitor::config::ChainMonitorConfig>,
     /// Optional explorer block-advance monitor (Blockscout v2 API).
     pub explorer_monitor: Option<ExplorerMonitorConfig>,
+    /// Operator scripts/webhooks triggered by health events.
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+    /// Threshold alerts on Prometheus queries or on samples of a node's `/metrics`.
+    #[serde(default)]
+    pub metric_alerts: Vec<MetricAlertConfig>,
+    /// Optional automated remediation of a stalled node.
+    pub remediation: Option<RemediationConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -45,6 +57,9 @@ pub struct ProbeConfig {
     pub check_interval_seconds: u64,
     #[serde(default = "default_probe_threshold")]
     pub failure_threshold: u32,
+    /// Count non-2xx responses as failures, for health endpoints such as `/health/ready`.
+    #[serde(default)]
+    pub expect_success: bool,
 }
 
 fn default_probe_interval() -> u64 {
@@ -83,17 +98,205 @@ fn default_explorer_api_failure_threshold() -> u32 {
     5
 }
 
+/// A threshold alert on a metric. Set either `prometheus_url` and `query`, or `metrics_url`
+/// and `metric`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricAlertConfig {
+    /// Label shown in alert messages
+    pub name: String,
+    /// Prometheus server base URL, e.g. "http://127.0.0.1:9090"
+    pub prometheus_url: Option<String>,
+    /// PromQL instant query, e.g.
+    /// `histogram_quantile(0.99, rate(aptos_txn_added_to_committed_time_seconds_bucket[5m]))`
+    pub query: Option<String>,
+    /// Node metrics endpoint read directly, e.g. "http://127.0.0.1:9001/metrics"
+    pub metrics_url: Option<String>,
+    /// Sample name in the `/metrics` output
+    pub metric: Option<String>,
+    /// Only samples carrying all of these labels are checked.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Breach condition such as "> 5" or "<= 0". Any series meeting it breaches the alert.
+    pub condition: String,
+    #[serde(default = "default_metric_check_interval")]
+    pub check_interval_seconds: u64,
+    /// Consecutive breaching checks before alerting, so a single spike does not page.
+    #[serde(default = "default_metric_breach_checks")]
+    pub breach_checks: u32,
+    #[serde(default = "default_metric_alert_priority")]
+    pub priority: Priority,
+}
+
+fn default_metric_check_interval() -> u64 {
+    30
+}
+
+fn default_metric_breach_checks() -> u32 {
+    1
+}
+
+fn default_metric_alert_priority() -> Priority {
+    Priority::P1
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RemediationConfig {
+    /// JSON-RPC URL of the node whose block height is watched.
+    pub rpc_url: String,
+    /// Label shown in alert messages
+    pub tag: Option<String>,
+    /// Log what would be done and alert, without running any action.
+    #[serde(default = "default_remediation_dry_run")]
+    pub dry_run: bool,
+    /// The node is stalled when its block height has not advanced for this long.
+    #[serde(default = "default_stall_threshold")]
+    pub stall_threshold_seconds: u64,
+    #[serde(default = "default_remediation_check_interval")]
+    pub check_interval_seconds: u64,
+    /// At most this many actions run in any rolling hour, so a node that cannot recover is
+    /// not restarted in a loop.
+    #[serde(default = "default_max_actions_per_hour")]
+    pub max_actions_per_hour: u32,
+    /// Action and script timeout.
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_seconds: u64,
+    /// Escalation ladder: each attempt on the same stall runs the next action, and the last one
+    /// is repeated. A block height advance starts over from the first.
+    pub actions: Vec<RemediationAction>,
+}
+
+fn default_remediation_dry_run() -> bool {
+    true
+}
+
+fn default_stall_threshold() -> u64 {
+    300
+}
+
+fn default_remediation_check_interval() -> u64 {
+    30
+}
+
+fn default_max_actions_per_hour() -> u32 {
+    3
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemediationAction {
+    /// Copies every file matching `paths` to `<file>.<timestamp>` and truncates it, so the node
+    /// keeps writing to its open file descriptor.
+    RotateLogs { paths: Vec<String> },
+    /// Runs the operator script that makes the node state-sync, e.g. by wiping its consensus
+    /// DB and restarting it.
+    StateSync { script: String },
+    /// Restarts the node with `systemctl restart <unit>`, or with a deploy script.
+    Restart { unit: Option<String>, script: Option<String> },
+    /// Runs an arbitrary command, given as program and arguments.
+    Command { command: Vec<String> },
+}
+
+impl fmt::Display for RemediationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemediationAction::RotateLogs { .. } => write!(f, "rotate_logs"),
+            RemediationAction::StateSync { .. } => write!(f, "state_sync"),
+            RemediationAction::Restart { .. } => write!(f, "restart"),
+            RemediationAction::Command { .. } => write!(f, "command"),
+        }
+    }
+}
+
+/// Health events that can trigger a hook.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthEvent {
+    /// Local execution result disagrees with the committed one.
+    ExecutionDivergence,
+    /// On-chain epoch data (validator set, configs) failed to decode.
+    EpochDecodeFailure,
+    /// Free space on a watched filesystem dropped below the threshold.
+    DiskLow,
+}
+
+impl fmt::Display for HealthEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthEvent::ExecutionDivergence => write!(f, "execution_divergence"),
+            HealthEvent::EpochDecodeFailure => write!(f, "epoch_decode_failure"),
+            HealthEvent::DiskLow => write!(f, "disk_low"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HookConfig {
+    pub event: HealthEvent,
+    /// Executable run with the event context as JSON on stdin.
+    pub script: Option<String>,
+    /// URL the event context is POSTed to as JSON.
+    pub webhook: Option<String>,
+    /// Overrides the built-in log regex for log-detected events.
+    pub log_pattern: Option<String>,
+    /// Filesystem path watched by `disk_low` hooks.
+    pub disk_path: Option<String>,
+    #[serde(default = "default_disk_min_free_percent")]
+    pub disk_min_free_percent: f64,
+    #[serde(default = "default_disk_check_interval")]
+    pub disk_check_interval_seconds: u64,
+    /// Script and webhook timeout.
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_seconds: u64,
+    /// Minimum interval between two firings of this hook, so a flapping condition does not
+    /// rerun remediation in a loop.
+    #[serde(default = "default_hook_min_interval")]
+    pub min_interval_seconds: u64,
+}
+
+fn default_disk_min_free_percent() -> f64 {
+    10.0
+}
+
+fn default_disk_check_interval() -> u64 {
+    60
+}
+
+fn default_hook_timeout() -> u64 {
+    30
+}
+
+fn default_hook_min_interval() -> u64 {
+    300
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct MonitoringConfig {
     pub file_patterns: Vec<String>,
     pub recent_file_threshold_seconds: u64,
     pub error_pattern: String,
     pub whitelist_path: Option<String>,
+    /// How often (seconds) the whitelist file is checked for changes and reloaded.
+    #[serde(default = "default_whitelist_reload_interval")]
+    pub whitelist_reload_interval_seconds: u64,
+    /// HTTP API for adding and removing whitelist rules at runtime. Needs `whitelist_path`.
+    pub whitelist_admin: Option<WhitelistAdminConfig>,
     /// Periodic interval (ms) to re-scan file_patterns for new log files.
     /// If omitted, only the initial set of files is monitored (no discovery of new files).
     pub check_interval_ms: Option<u64>,
 }
 
+fn default_whitelist_reload_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WhitelistAdminConfig {
+    /// e.g. "127.0.0.1:9190"
+    pub listen_addr: String,
+    /// Requests must send `Authorization: Bearer <token>`.
+    pub token: String,
+}
+
 /// Per-priority webhook override.
 #[derive(Debug, Deserialize, Clone)]
 pub struct PriorityAlertConfig {
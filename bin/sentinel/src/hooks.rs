@@ -0,0 +1,204 @@
+use crate::config::{HealthEvent, HookConfig};
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::{
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{io::AsyncWriteExt, process::Command, time};
+
+/// Default log regex for each log-detected event, matching the messages gravity_node and
+/// consensus emit for it.
+fn default_log_pattern(event: HealthEvent) -> Option<&'static str> {
+    match event {
+        HealthEvent::ExecutionDivergence => {
+            Some(r"commit_info mismatch|block id mismatch|Block id mismatch")
+        }
+        HealthEvent::EpochDecodeFailure => Some(
+            r"(?i)failed to (decode|deserialize).*(epoch|validator set|config)|Failed to get .* config for new epoch",
+        ),
+        HealthEvent::DiskLow => None,
+    }
+}
+
+struct Hook {
+    config: HookConfig,
+    pattern: Option<Regex>,
+    last_fired: Mutex<Option<Instant>>,
+}
+
+impl Hook {
+    /// Claims the hook for one firing unless it fired within `min_interval_seconds`.
+    fn try_claim(&self) -> bool {
+        let mut last = self.last_fired.lock().unwrap();
+        let now = Instant::now();
+        if let Some(prev) = *last {
+            if now.duration_since(prev) < Duration::from_secs(self.config.min_interval_seconds) {
+                return false;
+            }
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+/// Runs operator-provided scripts and webhooks when a configured health event is detected.
+///
+/// Every firing passes the same structured context: `event`, `timestamp`, `host` and an
+/// event-specific `details` object. Scripts receive it as JSON on stdin, plus
+/// `SENTINEL_HOOK_EVENT` in their environment; webhooks receive it as the POST body.
+#[derive(Clone)]
+pub struct Hooks {
+    hooks: Arc<Vec<Hook>>,
+    client: Client,
+}
+
+impl Hooks {
+    pub fn new(configs: Vec<HookConfig>) -> Result<Self> {
+        let mut hooks = Vec::with_capacity(configs.len());
+        for config in configs {
+            anyhow::ensure!(
+                config.script.is_some() || config.webhook.is_some(),
+                "{} hook needs a script or a webhook",
+                config.event
+            );
+            let pattern = match config.event {
+                HealthEvent::DiskLow => {
+                    anyhow::ensure!(config.disk_path.is_some(), "disk_low hook needs disk_path");
+                    None
+                }
+                event => {
+                    let pattern = config
+                        .log_pattern
+                        .as_deref()
+                        .or(default_log_pattern(event))
+                        .expect("log-detected events have a default pattern");
+                    Some(
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid log_pattern for {event} hook"))?,
+                    )
+                }
+            };
+            hooks.push(Hook { config, pattern, last_fired: Mutex::new(None) });
+        }
+        Ok(Self { hooks: Arc::new(hooks), client: Client::new() })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Fires every log-detected hook whose pattern matches `line`.
+    pub fn on_log_line(&self, line: &str, file: &str) {
+        for index in 0..self.hooks.len() {
+            let hook = &self.hooks[index];
+            let matched = hook.pattern.as_ref().is_some_and(|pattern| pattern.is_match(line));
+            if matched && hook.try_claim() {
+                let details = json!({ "file": file, "line": line });
+                self.spawn_fire(index, details);
+            }
+        }
+    }
+
+    /// Starts one free-space check loop per `disk_low` hook.
+    pub fn spawn_disk_checks(&self) {
+        for index in 0..self.hooks.len() {
+            let config = &self.hooks[index].config;
+            let Some(path) = config.disk_path.clone() else { continue };
+            let interval = Duration::from_secs(config.disk_check_interval_seconds.max(1));
+            let threshold = config.disk_min_free_percent;
+            let hooks = self.clone();
+            tokio::spawn(async move {
+                let mut ticker = time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    let free_percent = match disk_free_percent(&path).await {
+                        Ok(free_percent) => free_percent,
+                        Err(e) => {
+                            eprintln!("Failed to check free space of {path}: {e:?}");
+                            continue;
+                        }
+                    };
+                    if free_percent < threshold && hooks.hooks[index].try_claim() {
+                        let details = json!({
+                            "path": path,
+                            "free_percent": free_percent,
+                            "threshold_percent": threshold,
+                        });
+                        hooks.fire(index, details).await;
+                    }
+                }
+            });
+        }
+    }
+
+    fn spawn_fire(&self, index: usize, details: Value) {
+        let hooks = self.clone();
+        tokio::spawn(async move { hooks.fire(index, details).await });
+    }
+
+    async fn fire(&self, index: usize, details: Value) {
+        let config = &self.hooks[index].config;
+        let context = json!({
+            "event": config.event.to_string(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "host": std::env::var("HOSTNAME").unwrap_or_default(),
+            "details": details,
+        });
+        let timeout = Duration::from_secs(config.timeout_seconds);
+        println!("Hook triggered for {}: {context}", config.event);
+
+        if let Some(script) = &config.script {
+            if let Err(e) = run_script(script, config.event, &context, timeout).await {
+                eprintln!("Hook script {script} for {} failed: {e:?}", config.event);
+            }
+        }
+        if let Some(url) = &config.webhook {
+            let result = self.client.post(url).json(&context).timeout(timeout).send().await;
+            match result {
+                Ok(resp) if resp.status().is_success() => {}
+                Ok(resp) => {
+                    eprintln!("Hook webhook for {} failed: {}", config.event, resp.status())
+                }
+                Err(e) => eprintln!("Hook webhook for {} failed: {e:?}", config.event),
+            }
+        }
+    }
+}
+
+async fn run_script(
+    script: &str,
+    event: HealthEvent,
+    context: &Value,
+    timeout: Duration,
+) -> Result<()> {
+    let mut child = Command::new(script)
+        .env("SENTINEL_HOOK_EVENT", event.to_string())
+        .stdin(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to spawn")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Scripts are free to ignore stdin, so a closed pipe is not an error.
+        stdin.write_all(context.to_string().as_bytes()).await.unwrap_or(());
+    }
+    let status = time::timeout(timeout, child.wait()).await.context("Timed out")??;
+    anyhow::ensure!(status.success(), "exited with {status}");
+    Ok(())
+}
+
+/// Percentage of free space on the filesystem holding `path`, as reported by `df -P`.
+async fn disk_free_percent(path: &str) -> Result<f64> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().await?;
+    anyhow::ensure!(output.status.success(), "df exited with {}", output.status);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> =
+        stdout.lines().nth(1).context("Unexpected df output")?.split_whitespace().collect();
+    let used: f64 = fields.get(2).context("Missing used column")?.parse()?;
+    let available: f64 = fields.get(3).context("Missing available column")?.parse()?;
+    anyhow::ensure!(used + available > 0.0, "Empty filesystem");
+    Ok(available * 100.0 / (used + available))
+}
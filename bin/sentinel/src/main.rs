@@ -2,38 +2,66 @@ mod analyzer;
 mod chain_monitor;
 mod config;
 mod explorer_monitor;
+mod hooks;
+mod metric_monitor;
 mod notifier;
 mod probe;
 mod reader;
+mod remediation;
 mod watcher;
 mod whitelist;
+mod whitelist_admin;
 
 use crate::{
     analyzer::Analyzer,
     config::Config,
     explorer_monitor::ExplorerMonitor,
+    hooks::Hooks,
+    metric_monitor::MetricMonitor,
     notifier::Notifier,
     probe::Probe,
     reader::Reader,
+    remediation::Remediator,
     watcher::Watcher,
     whitelist::{CheckResult, Whitelist},
 };
 use anyhow::{Context, Result};
-use std::{env, time::Duration};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
 use tokio::time;
 
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
 /// Spawn log monitoring as an independent task.
-fn spawn_log_monitor(
+async fn spawn_log_monitor(
     monitoring: config::MonitoringConfig,
     alerting: config::AlertingConfig,
     notifier: Notifier,
+    hooks: Hooks,
 ) -> Result<()> {
-    let mut whitelist = if let Some(ref path) = monitoring.whitelist_path {
-        println!("Loading whitelist from {path}");
+    let whitelist_path = monitoring.whitelist_path.as_ref().map(PathBuf::from);
+    let whitelist = if let Some(ref path) = whitelist_path {
+        println!("Loading whitelist from {path:?}");
         Whitelist::load(path).context("Failed to load whitelist")?
     } else {
         Whitelist::default()
     };
+    let whitelist = Arc::new(Mutex::new(whitelist));
+
+    if let Some(admin) = monitoring.whitelist_admin.clone() {
+        let path = whitelist_path.clone().context("whitelist_admin needs whitelist_path")?;
+        whitelist_admin::spawn(admin, whitelist.clone(), path).await?;
+    }
+    let mut whitelist_modified_at = whitelist_path.as_deref().and_then(modified_at);
+    let mut reload_interval = whitelist_path.as_ref().map(|_| {
+        time::interval(Duration::from_secs(monitoring.whitelist_reload_interval_seconds.max(1)))
+    });
 
     let mut watcher = Watcher::new(monitoring.clone());
     let analyzer = Analyzer::new(&monitoring.error_pattern)?;
@@ -62,13 +90,16 @@ fn spawn_log_monitor(
                     let line = line_event.line();
                     let path = line_event.source();
 
+                    hooks.on_log_line(line, path.to_str().unwrap_or("unknown"));
+
                     if !analyzer.is_error(line) {
                         continue;
                     }
 
                     let file_str = path.to_str().unwrap_or("unknown");
 
-                    match whitelist.check(line, path) {
+                    let check_result = whitelist.lock().unwrap().check(line, path);
+                    match check_result {
                         CheckResult::Skip => continue,
                         CheckResult::Alert { count, priority } => {
                             let msg = format!("{line} [Frequency Alert: >{count}/5min]");
@@ -98,6 +129,25 @@ fn spawn_log_monitor(
                         Err(e) => eprintln!("Discovery error: {e:?}"),
                     }
                 }
+                _ = async { reload_interval.as_mut().unwrap().tick().await },
+                    if reload_interval.is_some() =>
+                {
+                    let path = whitelist_path.as_ref().unwrap();
+                    let modified = modified_at(path);
+                    if modified == whitelist_modified_at {
+                        continue;
+                    }
+                    println!("Whitelist {path:?} changed, reloading");
+                    // Read the file before locking, so log lines are not held up by the IO
+                    match Whitelist::load(path) {
+                        Ok(reloaded) => {
+                            whitelist.lock().unwrap().replace(reloaded);
+                            whitelist_modified_at = modified;
+                        }
+                        // Keep the rules we have, a half-edited file is retried on the next tick
+                        Err(e) => eprintln!("Failed to reload whitelist: {e:?}"),
+                    }
+                }
             }
         }
     });
@@ -125,6 +175,12 @@ async fn main() -> Result<()> {
     // Verify webhook connectivity on startup
     notifier.verify_webhooks().await.context("Webhook verification failed")?;
 
+    let hooks = Hooks::new(config.hooks).context("Invalid hook config")?;
+    if !hooks.is_empty() {
+        println!("Starting health event hooks...");
+        hooks.spawn_disk_checks();
+    }
+
     // Start Probes
     for probe_config in config.probes {
         let probe = Probe::new(probe_config, notifier.clone());
@@ -151,10 +207,30 @@ async fn main() -> Result<()> {
         });
     }
 
+    // Start Metric Alerts
+    for alert_config in config.metric_alerts {
+        let monitor =
+            MetricMonitor::new(alert_config, notifier.clone()).context("Invalid metric alert")?;
+        println!("Starting metric alert {}...", monitor.name());
+        tokio::spawn(async move {
+            monitor.run().await;
+        });
+    }
+
+    // Start Remediation (if configured)
+    if let Some(remediation_cfg) = config.remediation {
+        let remediator = Remediator::new(remediation_cfg, notifier.clone())
+            .context("Invalid remediation config")?;
+        println!("Starting remediation for {}...", remediator.tag());
+        tokio::spawn(async move {
+            remediator.run().await;
+        });
+    }
+
     // Start Log Monitoring (if configured)
     if let Some(monitoring) = config.monitoring {
         println!("Starting log monitoring...");
-        spawn_log_monitor(monitoring, config.alerting, notifier)?;
+        spawn_log_monitor(monitoring, config.alerting, notifier, hooks).await?;
     }
 
     println!("Sentinel started...");
@@ -0,0 +1,401 @@
+use crate::{
+    config::{MetricAlertConfig, Priority},
+    notifier::Notifier,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+use tokio::time::{self, MissedTickBehavior};
+
+/// Consecutive failed reads of the metric before a P0 alert that the alert is blind.
+const FETCH_FAILURE_THRESHOLD: u32 = 5;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+/// A threshold expression such as `> 5` or `<= 0.9`.
+#[derive(Debug, Clone, Copy)]
+struct Condition {
+    op: Op,
+    threshold: f64,
+}
+
+impl Condition {
+    fn parse(expr: &str) -> Result<Self> {
+        let expr = expr.trim();
+        // Two-character operators first, so `>=` is not read as `>`
+        let ops = [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+        ];
+        let (op, rest) = ops
+            .iter()
+            .find_map(|(symbol, op)| expr.strip_prefix(symbol).map(|rest| (*op, rest)))
+            .ok_or_else(|| anyhow!("condition {expr:?} must start with >, >=, <, <=, == or !="))?;
+        let threshold = rest
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| anyhow!("invalid threshold in condition {expr:?}: {e}"))?;
+        Ok(Self { op, threshold })
+    }
+
+    fn breached_by(&self, value: f64) -> bool {
+        match self.op {
+            Op::Gt => value > self.threshold,
+            Op::Ge => value >= self.threshold,
+            Op::Lt => value < self.threshold,
+            Op::Le => value <= self.threshold,
+            Op::Eq => value == self.threshold,
+            Op::Ne => value != self.threshold,
+        }
+    }
+}
+
+enum Source {
+    /// PromQL instant query against a Prometheus server.
+    Prometheus { url: String, query: String },
+    /// A sample read directly from a `/metrics` endpoint.
+    Scrape { url: String, metric: String, labels: BTreeMap<String, String> },
+}
+
+impl Source {
+    fn describe(&self) -> String {
+        match self {
+            Source::Prometheus { url, query } => format!("{query} @ {url}"),
+            Source::Scrape { url, metric, labels } => {
+                format!("{metric}{} @ {url}", format_labels(labels))
+            }
+        }
+    }
+}
+
+/// Prometheus `/api/v1/query` response. Only vector results are supported.
+#[derive(Deserialize)]
+struct QueryResp {
+    status: String,
+    error: Option<String>,
+    data: Option<QueryData>,
+}
+
+#[derive(Deserialize)]
+struct QueryData {
+    result: Vec<QuerySample>,
+}
+
+#[derive(Deserialize)]
+struct QuerySample {
+    metric: BTreeMap<String, String>,
+    /// `[unix_time, "value"]`
+    value: (f64, String),
+}
+
+fn format_labels(labels: &BTreeMap<String, String>) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<_> = labels.iter().map(|(k, v)| format!("{k}=\"{v}\"")).collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Parses `k1="v1",k2="v2"` as found between the braces of an exposition format sample.
+fn parse_labels(s: &str) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    let mut rest = s;
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let mut end = after.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(if escaped == 'n' { '\n' } else { escaped });
+                    }
+                }
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                c => value.push(c),
+            }
+        }
+        labels.insert(key.trim().trim_start_matches(',').trim().to_string(), value);
+        rest = &after[end..];
+    }
+    labels
+}
+
+/// Samples of `metric` whose labels include all of `wanted`, from a Prometheus text exposition.
+fn scrape_samples(
+    body: &str,
+    metric: &str,
+    wanted: &BTreeMap<String, String>,
+) -> Vec<(String, f64)> {
+    let mut samples = vec![];
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(rest) = line.strip_prefix(metric) else { continue };
+        let (labels, value) = match rest.strip_prefix('{') {
+            Some(rest) => match rest.rsplit_once('}') {
+                Some((labels, value)) => (parse_labels(labels), value),
+                None => continue,
+            },
+            // A longer metric name that starts with `metric`
+            None if !rest.starts_with(char::is_whitespace) => continue,
+            None => (BTreeMap::new(), rest),
+        };
+        if wanted.iter().any(|(k, v)| labels.get(k) != Some(v)) {
+            continue;
+        }
+        let Some(Ok(value)) = value.split_whitespace().next().map(str::parse::<f64>) else {
+            continue;
+        };
+        samples.push((format!("{metric}{}", format_labels(&labels)), value));
+    }
+    samples
+}
+
+pub struct MetricMonitor {
+    name: String,
+    source: Source,
+    condition: Condition,
+    condition_expr: String,
+    check_interval: Duration,
+    breach_checks: u32,
+    priority: Priority,
+    client: Client,
+    notifier: Notifier,
+}
+
+impl MetricMonitor {
+    pub fn new(config: MetricAlertConfig, notifier: Notifier) -> Result<Self> {
+        let endpoints = (config.prometheus_url, config.query, config.metrics_url, config.metric);
+        let source = match endpoints {
+            (Some(url), Some(query), None, None) => Source::Prometheus { url, query },
+            (None, None, Some(url), Some(metric)) => {
+                Source::Scrape { url, metric, labels: config.labels }
+            }
+            _ => bail!(
+                "metric alert {:?} needs either prometheus_url and query, or metrics_url and \
+                 metric",
+                config.name
+            ),
+        };
+        let condition = Condition::parse(&config.condition)
+            .with_context(|| format!("metric alert {:?}", config.name))?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Ok(Self {
+            name: config.name,
+            source,
+            condition,
+            condition_expr: config.condition.trim().to_string(),
+            check_interval: Duration::from_secs(config.check_interval_seconds.max(1)),
+            breach_checks: config.breach_checks.max(1),
+            priority: config.priority,
+            client,
+            notifier,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The current value of every series the alert covers.
+    async fn fetch(&self) -> Result<Vec<(String, f64)>> {
+        match &self.source {
+            Source::Prometheus { url, query } => {
+                let url = format!("{}/api/v1/query", url.trim_end_matches('/'));
+                let resp: QueryResp = self
+                    .client
+                    .get(&url)
+                    .query(&[("query", query)])
+                    .send()
+                    .await?
+                    .json()
+                    .await
+                    .context("unexpected Prometheus response, only vector results are supported")?;
+                if resp.status != "success" {
+                    bail!("query failed: {}", resp.error.unwrap_or(resp.status));
+                }
+                let samples = resp.data.map(|data| data.result).unwrap_or_default();
+                samples
+                    .into_iter()
+                    .map(|sample| {
+                        let value = sample.value.1.parse::<f64>().map_err(|e| {
+                            anyhow!("invalid sample value {:?}: {e}", sample.value.1)
+                        })?;
+                        Ok((format_labels(&sample.metric), value))
+                    })
+                    .collect()
+            }
+            Source::Scrape { url, metric, labels } => {
+                let body = self.client.get(url).send().await?.error_for_status()?.text().await?;
+                Ok(scrape_samples(&body, metric, labels))
+            }
+        }
+    }
+
+    pub async fn run(self) {
+        let mut timer = time::interval(self.check_interval);
+        timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut breaches: u32 = 0;
+        // Set while the condition holds and has been alerted, so it is only alerted once
+        let mut alerted = false;
+        let mut fetch_failures: u32 = 0;
+        let mut fetch_alert_sent = false;
+
+        println!(
+            "Metric alert {:?} watching {} {}",
+            self.name,
+            self.source.describe(),
+            self.condition_expr
+        );
+        loop {
+            timer.tick().await;
+            let samples = match self.fetch().await {
+                Ok(samples) => {
+                    if fetch_alert_sent {
+                        println!("Metric alert {:?} can read its metric again", self.name);
+                        fetch_alert_sent = false;
+                    }
+                    fetch_failures = 0;
+                    samples
+                }
+                Err(e) => {
+                    fetch_failures += 1;
+                    eprintln!(
+                        "Metric alert {:?} failed to read {} ({fetch_failures}/{FETCH_FAILURE_THRESHOLD}): {e:#}",
+                        self.name,
+                        self.source.describe()
+                    );
+                    if !fetch_alert_sent && fetch_failures >= FETCH_FAILURE_THRESHOLD {
+                        let msg = format!(
+                            "Metric alert {:?} cannot read its metric ({fetch_failures} consecutive failures)\n  \
+                             source: {}\n  last error: {e:#}",
+                            self.name,
+                            self.source.describe()
+                        );
+                        println!("TRIGGERING ALERT: {msg}");
+                        if let Err(err) = self.notifier.alert(&msg, "METRIC", Priority::P0).await {
+                            eprintln!("Failed to send metric alert: {err:?}");
+                        }
+                        fetch_alert_sent = true;
+                    }
+                    continue;
+                }
+            };
+
+            let breached: Vec<_> =
+                samples.iter().filter(|(_, value)| self.condition.breached_by(*value)).collect();
+            if breached.is_empty() {
+                if alerted {
+                    println!("Metric alert {:?} recovered", self.name);
+                }
+                breaches = 0;
+                alerted = false;
+                continue;
+            }
+
+            breaches += 1;
+            if alerted || breaches < self.breach_checks {
+                continue;
+            }
+            let series: Vec<_> =
+                breached.iter().map(|(series, value)| format!("{series} = {value}")).collect();
+            let msg = format!(
+                "Metric alert {:?} fired: {} for {breaches} checks\n  source: {}\n  {}",
+                self.name,
+                self.condition_expr,
+                self.source.describe(),
+                series.join("\n  ")
+            );
+            println!("TRIGGERING ALERT: {msg}");
+            if let Err(e) = self.notifier.alert(&msg, "METRIC", self.priority).await {
+                eprintln!("Failed to send metric alert: {e:?}");
+            }
+            alerted = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conditions() {
+        let ge = Condition::parse(" >= 0.9 ").unwrap();
+        assert!(ge.breached_by(0.9) && ge.breached_by(1.0) && !ge.breached_by(0.8));
+        let gt = Condition::parse(">5").unwrap();
+        assert!(gt.breached_by(6.0) && !gt.breached_by(5.0));
+        let le = Condition::parse("<= 1").unwrap();
+        assert!(le.breached_by(1.0) && !le.breached_by(1.5));
+        let ne = Condition::parse("!= 0").unwrap();
+        assert!(ne.breached_by(1.0) && !ne.breached_by(0.0));
+        assert!(Condition::parse("== 3").unwrap().breached_by(3.0));
+        assert!(Condition::parse("< -1").unwrap().breached_by(-2.0));
+
+        assert!(Condition::parse("5").is_err());
+        assert!(Condition::parse("=> 5").is_err());
+        assert!(Condition::parse("> five").is_err());
+        assert!(Condition::parse(">").is_err());
+    }
+
+    #[test]
+    fn parses_labels_with_escapes() {
+        let labels = parse_labels(r#"peer="a\"b",kind="x\ny", empty="""#);
+        assert_eq!(labels.get("peer").map(String::as_str), Some("a\"b"));
+        assert_eq!(labels.get("kind").map(String::as_str), Some("x\ny"));
+        assert_eq!(labels.get("empty").map(String::as_str), Some(""));
+        assert_eq!(labels.len(), 3);
+        assert!(parse_labels("").is_empty());
+    }
+
+    #[test]
+    fn scrapes_matching_samples() {
+        let body = "\
+# HELP gravity_height Block height
+# TYPE gravity_height gauge
+gravity_height{role=\"validator\",shard=\"0\"} 42
+gravity_height{role=\"fullnode\"} 40 1700000000000
+gravity_height 7
+gravity_height_total 99
+gravity_height{role=\"validator\"} NaNx
+";
+        let all = scrape_samples(body, "gravity_height", &BTreeMap::new());
+        assert_eq!(
+            all,
+            vec![
+                ("gravity_height{role=\"validator\",shard=\"0\"}".to_string(), 42.0),
+                ("gravity_height{role=\"fullnode\"}".to_string(), 40.0),
+                ("gravity_height".to_string(), 7.0),
+            ]
+        );
+
+        let wanted = BTreeMap::from([("role".to_string(), "validator".to_string())]);
+        let validators = scrape_samples(body, "gravity_height", &wanted);
+        assert_eq!(validators.len(), 1);
+        assert_eq!(validators[0].1, 42.0);
+        assert!(scrape_samples(body, "gravity_missing", &BTreeMap::new()).is_empty());
+    }
+}
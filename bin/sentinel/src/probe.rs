@@ -72,9 +72,16 @@ impl Probe {
         loop {
             timer.tick().await;
             let started = std::time::Instant::now();
-            match self.client.get(&self.config.url).send().await {
-                Ok(_) => {
-                    // Any HTTP response (even non-200) means the service is reachable
+            let result = match self.client.get(&self.config.url).send().await {
+                Ok(response) if self.config.expect_success && !response.status().is_success() => {
+                    Err(format!("[status] HTTP {}", response.status()))
+                }
+                // Otherwise any HTTP response (even non-200) means the service is reachable
+                Ok(_) => Ok(()),
+                Err(e) => Err(format_error(&e)),
+            };
+            match result {
+                Ok(()) => {
                     if failures > 0 {
                         println!(
                             "Probe recovered: {} (after {} failures)",
@@ -84,10 +91,9 @@ impl Probe {
                         recent_errors.clear();
                     }
                 }
-                Err(e) => {
+                Err(detail) => {
                     failures += 1;
                     let elapsed_ms = started.elapsed().as_millis();
-                    let detail = format_error(&e);
                     println!(
                         "Probe failed: {} after {}ms - {} (count: {})",
                         self.config.url, elapsed_ms, detail, failures
@@ -0,0 +1,261 @@
+use crate::{
+    chain_monitor::provider::{build_provider, HttpProvider},
+    config::{Priority, RemediationAction, RemediationConfig},
+    notifier::Notifier,
+};
+use alloy_provider::Provider;
+use anyhow::{Context, Result};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use tokio::{
+    fs::OpenOptions,
+    process::Command,
+    time::{self, MissedTickBehavior},
+};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Watches the block height of a node and runs the configured actions when it stops
+/// advancing.
+pub struct Remediator {
+    config: RemediationConfig,
+    provider: HttpProvider,
+    notifier: Notifier,
+    /// When each action of the last hour ran, oldest first.
+    recent_actions: VecDeque<Instant>,
+}
+
+impl Remediator {
+    pub fn new(config: RemediationConfig, notifier: Notifier) -> Result<Self> {
+        anyhow::ensure!(!config.actions.is_empty(), "remediation needs at least one action");
+        for action in &config.actions {
+            match action {
+                RemediationAction::Restart { unit: None, script: None } => {
+                    anyhow::bail!("restart action needs a unit or a script")
+                }
+                RemediationAction::Command { command } if command.is_empty() => {
+                    anyhow::bail!("command action needs a command")
+                }
+                _ => {}
+            }
+        }
+        let provider = build_provider(&config.rpc_url)?;
+        Ok(Self { config, provider, notifier, recent_actions: VecDeque::new() })
+    }
+
+    pub fn tag(&self) -> String {
+        self.config.tag.clone().unwrap_or_else(|| self.config.rpc_url.clone())
+    }
+
+    /// Claims one action from the hourly budget.
+    fn try_claim(&mut self, now: Instant) -> bool {
+        while self
+            .recent_actions
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= RATE_LIMIT_WINDOW)
+        {
+            self.recent_actions.pop_front();
+        }
+        if self.recent_actions.len() >= self.config.max_actions_per_hour as usize {
+            return false;
+        }
+        self.recent_actions.push_back(now);
+        true
+    }
+
+    pub async fn run(mut self) {
+        let tag = self.tag();
+        let stall_threshold = Duration::from_secs(self.config.stall_threshold_seconds);
+        let mut timer =
+            time::interval(Duration::from_secs(self.config.check_interval_seconds.max(1)));
+        timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut last_height: Option<u64> = None;
+        let mut last_progress = Instant::now();
+        // Actions run for the current stall, indexing the escalation ladder
+        let mut attempts: usize = 0;
+        let mut last_action: Option<Instant> = None;
+        let mut budget_alert_sent = false;
+
+        println!(
+            "Starting remediation for {tag} (stall after {}s{})",
+            self.config.stall_threshold_seconds,
+            if self.config.dry_run { ", dry run" } else { "" }
+        );
+        loop {
+            timer.tick().await;
+            // An unreachable node does not advance either, so read errors count towards the stall
+            match self.provider.get_block_number().await {
+                Ok(height) if last_height.is_none_or(|prev| height > prev) => {
+                    if attempts > 0 {
+                        println!("Node {tag} advancing again at height {height}");
+                    }
+                    last_height = Some(height);
+                    last_progress = Instant::now();
+                    attempts = 0;
+                    last_action = None;
+                    budget_alert_sent = false;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to read block height of {tag}: {e}"),
+            }
+
+            let now = Instant::now();
+            let stalled_for = now.duration_since(last_progress);
+            // Give every action a full stall window to take effect before escalating
+            let since_action = last_action.map_or(stalled_for, |at| now.duration_since(at));
+            if stalled_for < stall_threshold || since_action < stall_threshold {
+                continue;
+            }
+
+            let action = self.config.actions[attempts.min(self.config.actions.len() - 1)].clone();
+            let height = last_height.map_or_else(|| "unknown".to_string(), |h| h.to_string());
+            if !self.try_claim(now) {
+                if !budget_alert_sent {
+                    let msg = format!(
+                        "Remediation budget exhausted for {tag}\n  \
+                         height: {height} (stalled for {}s)\n  \
+                         {} actions ran in the last hour, not running {action}",
+                        stalled_for.as_secs(),
+                        self.config.max_actions_per_hour,
+                    );
+                    println!("TRIGGERING ALERT: {msg}");
+                    if let Err(e) = self.notifier.alert(&msg, "REMEDIATION", Priority::P0).await {
+                        eprintln!("Failed to send remediation alert: {e:?}");
+                    }
+                    budget_alert_sent = true;
+                }
+                continue;
+            }
+            attempts += 1;
+            last_action = Some(now);
+
+            let outcome = if self.config.dry_run {
+                "dry run, not executed".to_string()
+            } else {
+                match self.execute(&action).await {
+                    Ok(()) => "succeeded".to_string(),
+                    Err(e) => format!("failed: {e:#}"),
+                }
+            };
+            let msg = format!(
+                "Remediation on {tag}: {action} (attempt {attempts})\n  \
+                 height: {height} (stalled for {}s)\n  \
+                 result: {outcome}",
+                stalled_for.as_secs(),
+            );
+            println!("TRIGGERING ALERT: {msg}");
+            if let Err(e) = self.notifier.alert(&msg, "REMEDIATION", Priority::P0).await {
+                eprintln!("Failed to send remediation alert: {e:?}");
+            }
+        }
+    }
+
+    async fn execute(&self, action: &RemediationAction) -> Result<()> {
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        match action {
+            RemediationAction::RotateLogs { paths } => rotate_logs(paths).await,
+            RemediationAction::StateSync { script } => run_command(script, &[], timeout).await,
+            RemediationAction::Restart { unit: Some(unit), .. } => {
+                run_command("systemctl", &["restart".to_string(), unit.clone()], timeout).await
+            }
+            RemediationAction::Restart { script: Some(script), .. } => {
+                run_command(script, &[], timeout).await
+            }
+            RemediationAction::Restart { unit: None, script: None } => {
+                unreachable!("checked in Remediator::new")
+            }
+            RemediationAction::Command { command } => {
+                run_command(&command[0], &command[1..], timeout).await
+            }
+        }
+    }
+}
+
+async fn run_command(program: &str, args: &[String], timeout: Duration) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to spawn {program}"))?;
+    let status = time::timeout(timeout, child.wait()).await.context("Timed out")??;
+    anyhow::ensure!(status.success(), "{program} exited with {status}");
+    Ok(())
+}
+
+/// Copies each file matching `patterns` aside and truncates it in place.
+async fn rotate_logs(patterns: &[String]) -> Result<()> {
+    let suffix = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    for pattern in patterns {
+        for path in glob::glob(pattern).with_context(|| format!("Invalid pattern {pattern}"))? {
+            let path = path?;
+            if !path.is_file() {
+                continue;
+            }
+            let rotated = format!("{}.{suffix}", path.display());
+            tokio::fs::copy(&path, &rotated)
+                .await
+                .with_context(|| format!("Failed to copy {path:?} to {rotated}"))?;
+            OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .await?
+                .set_len(0)
+                .await
+                .with_context(|| format!("Failed to truncate {path:?}"))?;
+            println!("Rotated {path:?} to {rotated}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remediator(max_actions_per_hour: u32) -> Remediator {
+        let config: RemediationConfig = toml::from_str(&format!(
+            "rpc_url = \"http://127.0.0.1:8545\"\nmax_actions_per_hour = \
+             {max_actions_per_hour}\n[[actions]]\nkind = \"restart\"\nunit = \"gravity\"\n"
+        ))
+        .unwrap();
+        Remediator::new(config, Notifier::new(toml::from_str("").unwrap())).unwrap()
+    }
+
+    #[test]
+    fn claims_are_limited_per_rolling_hour() {
+        let mut remediator = remediator(2);
+        let start = Instant::now();
+        assert!(remediator.try_claim(start));
+        assert!(remediator.try_claim(start + Duration::from_secs(60)));
+        assert!(!remediator.try_claim(start + Duration::from_secs(120)));
+        // The first claim leaves the window, the second still counts
+        assert!(remediator.try_claim(start + RATE_LIMIT_WINDOW));
+        assert!(!remediator.try_claim(start + RATE_LIMIT_WINDOW + Duration::from_secs(30)));
+        assert!(remediator.try_claim(start + RATE_LIMIT_WINDOW + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn no_claims_without_a_budget() {
+        let mut remediator = remediator(0);
+        assert!(!remediator.try_claim(Instant::now()));
+    }
+
+    #[test]
+    fn rejects_incomplete_actions() {
+        let config = |action: &str| -> RemediationConfig {
+            toml::from_str(&format!("rpc_url = \"http://127.0.0.1:8545\"\n{action}")).unwrap()
+        };
+        let notifier = || Notifier::new(toml::from_str("").unwrap());
+        assert!(Remediator::new(config("actions = []"), notifier()).is_err());
+        assert!(Remediator::new(config("[[actions]]\nkind = \"restart\""), notifier()).is_err());
+        assert!(Remediator::new(
+            config("[[actions]]\nkind = \"command\"\ncommand = []"),
+            notifier()
+        )
+        .is_err());
+    }
+}
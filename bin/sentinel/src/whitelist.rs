@@ -1,10 +1,11 @@
 use crate::config::Priority;
 use anyhow::Result;
-use csv::ReaderBuilder;
+use csv::{ReaderBuilder, Writer};
 use regex::Regex;
+use serde::Serialize;
 use std::{
     collections::{HashMap, VecDeque},
-    fs::File,
+    fs::{self, File},
     path::{Path, PathBuf},
     time::Instant,
 };
@@ -22,8 +23,17 @@ pub enum CheckResult {
     Alert { count: u32, priority: Priority },
 }
 
+/// A whitelist rule as written in the CSV file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleSpec {
+    pub pattern: String,
+    pub threshold: i32,
+    pub priority: String,
+}
+
 /// A whitelist rule with pattern, threshold, and optional priority override.
 pub struct WhitelistRule {
+    source: String,
     pattern: Regex,
     threshold: i32,
     /// Priority for alerts from this rule (default: P0).
@@ -43,7 +53,21 @@ impl WhitelistRule {
             }
         };
 
-        Ok(Self { pattern, threshold, priority, timestamps: HashMap::new() })
+        Ok(Self {
+            source: pattern_str.to_string(),
+            pattern,
+            threshold,
+            priority,
+            timestamps: HashMap::new(),
+        })
+    }
+
+    pub fn spec(&self) -> RuleSpec {
+        RuleSpec {
+            pattern: self.source.clone(),
+            threshold: self.threshold,
+            priority: self.priority.to_string().to_lowercase(),
+        }
     }
 
     fn matches(&self, line: &str) -> bool {
@@ -99,16 +123,7 @@ impl Whitelist {
             };
 
             // Parse optional priority (third column, default: P0)
-            let priority: Priority = if record.len() >= 3 {
-                let p_str = record[2].trim();
-                match p_str.to_lowercase().as_str() {
-                    "p1" => Priority::P1,
-                    "p2" => Priority::P2,
-                    _ => Priority::P0,
-                }
-            } else {
-                Priority::P0
-            };
+            let priority = parse_priority(record.get(2).unwrap_or_default());
 
             match WhitelistRule::new(pattern, threshold, priority) {
                 Ok(rule) => {
@@ -125,6 +140,34 @@ impl Whitelist {
         Ok(Self { rules })
     }
 
+    /// Replaces the rules with the ones of a freshly loaded whitelist. Rules whose pattern is
+    /// unchanged keep their frequency counts.
+    pub fn replace(&mut self, mut reloaded: Whitelist) {
+        for rule in &mut reloaded.rules {
+            if let Some(old) = self.rules.iter_mut().find(|old| old.source == rule.source) {
+                rule.timestamps = std::mem::take(&mut old.timestamps);
+            }
+        }
+        *self = reloaded;
+    }
+
+    pub fn rules(&self) -> Vec<RuleSpec> {
+        self.rules.iter().map(WhitelistRule::spec).collect()
+    }
+
+    /// Adds a rule, replacing any rule with the same pattern.
+    pub fn add_rule(&mut self, rule: WhitelistRule) {
+        self.rules.retain(|old| old.source != rule.source);
+        self.rules.push(rule);
+    }
+
+    /// Returns whether a rule with this pattern existed.
+    pub fn remove_rule(&mut self, pattern: &str) -> bool {
+        let before = self.rules.len();
+        self.rules.retain(|rule| rule.source != pattern);
+        self.rules.len() != before
+    }
+
     /// Check a log line against whitelist rules.
     /// Frequency thresholds are counted per source file path.
     pub fn check(&mut self, line: &str, source: &Path) -> CheckResult {
@@ -168,3 +211,52 @@ impl Whitelist {
         CheckResult::AlwaysAlert
     }
 }
+
+pub fn parse_priority(s: &str) -> Priority {
+    match s.trim().to_lowercase().as_str() {
+        "p1" => Priority::P1,
+        "p2" => Priority::P2,
+        _ => Priority::P0,
+    }
+}
+
+/// Rewrites the whitelist file with the rule for `pattern` replaced by `rule`, or removed if
+/// `rule` is `None`. Comments and other rules are left as they are; a new rule is appended.
+pub fn persist_rule<P: AsRef<Path>>(path: P, pattern: &str, rule: Option<&RuleSpec>) -> Result<()> {
+    let path = path.as_ref();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut lines: Vec<String> = content
+        .lines()
+        .filter(|line| line_pattern(line).as_deref() != Some(pattern))
+        .map(str::to_string)
+        .collect();
+    if let Some(rule) = rule {
+        let threshold = rule.threshold.to_string();
+        let mut writer = Writer::from_writer(vec![]);
+        writer.write_record([&rule.pattern, &threshold, &rule.priority])?;
+        let record = String::from_utf8(writer.into_inner()?)?;
+        lines.push(record.trim_end().to_string());
+    }
+
+    // Write to a temporary file first so a crash never leaves a truncated whitelist
+    let tmp = path.with_extension("csv.tmp");
+    fs::write(&tmp, lines.join("\n") + "\n")?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The pattern of a rule line, `None` for comments and blank lines.
+fn line_pattern(line: &str) -> Option<String> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .flexible(true)
+        .from_reader(line.as_bytes());
+    let record = reader.records().next()?.ok()?;
+    record.get(0).map(|pattern| pattern.trim().to_string())
+}
@@ -0,0 +1,139 @@
+use crate::{
+    config::WhitelistAdminConfig,
+    whitelist::{parse_priority, persist_rule, RuleSpec, Whitelist, WhitelistRule},
+};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use subtle::ConstantTimeEq;
+
+#[derive(Clone)]
+struct AdminState {
+    whitelist: Arc<Mutex<Whitelist>>,
+    path: PathBuf,
+    token: Arc<String>,
+    // Serializes the changes, so the file and the rules in memory see them in the same order.
+    // The whitelist lock itself is never held across the file IO.
+    changes: Arc<tokio::sync::Mutex<()>>,
+}
+
+#[derive(Deserialize)]
+struct AddRule {
+    pattern: String,
+    threshold: i32,
+    priority: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoveRule {
+    pattern: String,
+}
+
+fn error(status: StatusCode, message: impl ToString) -> Response {
+    (status, Json(json!({ "error": message.to_string() }))).into_response()
+}
+
+async fn require_token(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| bool::from(token.as_bytes().ct_eq(state.token.as_bytes())));
+    if !authorized {
+        return error(StatusCode::UNAUTHORIZED, "missing or invalid bearer token");
+    }
+    next.run(request).await
+}
+
+async fn list_rules(State(state): State<AdminState>) -> Json<Vec<RuleSpec>> {
+    Json(state.whitelist.lock().unwrap().rules())
+}
+
+async fn add_rule(State(state): State<AdminState>, Json(body): Json<AddRule>) -> Response {
+    let priority = parse_priority(body.priority.as_deref().unwrap_or_default());
+    let rule = match WhitelistRule::new(&body.pattern, body.threshold, priority) {
+        Ok(rule) => rule,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    let spec = rule.spec();
+    let _change = state.changes.lock().await;
+    // Persist first, so a rule that is active is also there after a restart
+    let persisted = spec.clone();
+    if let Err(e) =
+        persist(&state, move |path| persist_rule(path, &persisted.pattern, Some(&persisted))).await
+    {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"));
+    }
+    state.whitelist.lock().unwrap().add_rule(rule);
+    println!("Whitelist rule added via admin API: {spec:?}");
+    (StatusCode::CREATED, Json(spec)).into_response()
+}
+
+async fn remove_rule(State(state): State<AdminState>, Json(body): Json<RemoveRule>) -> Response {
+    let _change = state.changes.lock().await;
+    let exists =
+        state.whitelist.lock().unwrap().rules().iter().any(|rule| rule.pattern == body.pattern);
+    if !exists {
+        return error(StatusCode::NOT_FOUND, format!("no rule for pattern '{}'", body.pattern));
+    }
+    let pattern = body.pattern.clone();
+    if let Err(e) = persist(&state, move |path| persist_rule(path, &pattern, None)).await {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"));
+    }
+    state.whitelist.lock().unwrap().remove_rule(&body.pattern);
+    println!("Whitelist rule removed via admin API: '{}'", body.pattern);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Runs a write of the whitelist file off the async workers.
+async fn persist(
+    state: &AdminState,
+    write: impl FnOnce(&PathBuf) -> Result<()> + Send + 'static,
+) -> Result<()> {
+    let path = state.path.clone();
+    tokio::task::spawn_blocking(move || write(&path)).await.context("whitelist write panicked")?
+}
+
+/// Serves `GET`, `POST` and `DELETE /whitelist/rules` so rules can be changed without a
+/// restart. Every change is written back to the whitelist file.
+pub async fn spawn(
+    config: WhitelistAdminConfig,
+    whitelist: Arc<Mutex<Whitelist>>,
+    path: PathBuf,
+) -> Result<()> {
+    anyhow::ensure!(!config.token.is_empty(), "whitelist_admin needs a non-empty token");
+    let state = AdminState {
+        whitelist,
+        path,
+        token: Arc::new(config.token),
+        changes: Arc::new(tokio::sync::Mutex::new(())),
+    };
+    let app = Router::new()
+        .route("/whitelist/rules", get(list_rules).post(add_rule).delete(remove_rule))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&config.listen_addr)
+        .await
+        .with_context(|| format!("Failed to bind whitelist admin API on {}", config.listen_addr))?;
+    println!("Whitelist admin API listening on {}", config.listen_addr);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("Whitelist admin API stopped: {e:?}");
+        }
+    });
+    Ok(())
+}
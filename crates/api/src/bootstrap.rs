@@ -1,7 +1,13 @@
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use crate::network::extract_network_ids;
+use crate::{
+    network::extract_network_ids,
+    role_profile::{load_node_config, NodeRole},
+};
 use aptos_consensus::{
+    consensus_observer::{
+        network_message::ConsensusObserverMessage, publisher::ConsensusPublisher,
+    },
     consensusdb::{BlockNumberSchema, ConsensusDB},
     gravity_state_computer::ConsensusAdapterArgs,
     network_interface::ConsensusMsg,
@@ -9,7 +15,7 @@ use aptos_consensus::{
     quorum_store::quorum_store_db::QuorumStoreDB,
 };
 
-use block_buffer_manager::{get_block_buffer_manager, TxPool};
+use block_buffer_manager::{BlockBufferManager, TxPool};
 use gaptos::{
     api_types::u256_define::BlockId,
     aptos_channels::{aptos_channel, message_queues::QueueStyle},
@@ -23,12 +29,17 @@ use gaptos::{
         protocols::network::{NetworkApplicationConfig, NetworkClientConfig, NetworkServiceConfig},
         ProtocolId,
     },
+    aptos_types::chain_id::ChainId,
 };
 
 use aptos_mempool::{
-    core_mempool::CoreMempool, MempoolClientRequest, MempoolSyncMsg, QuorumStoreRequest,
+    core_mempool::{CoreMempool, MempoolInspector},
+    MempoolClientRequest, MempoolSyncMsg, QuorumStoreRequest,
+};
+use futures::{
+    channel::mpsc::{Receiver, Sender},
+    StreamExt,
 };
-use futures::channel::mpsc::{Receiver, Sender};
 use gaptos::{
     aptos_consensus_notifications::ConsensusNotifier,
     aptos_crypto::{hash::GENESIS_BLOCK_ID, HashValue},
@@ -50,7 +61,8 @@ pub struct ApplicationNetworkInterfaces<T> {
     pub network_service_events: NetworkServiceEvents<T>,
 }
 
-pub fn check_bootstrap_config(node_config_path: Option<PathBuf>) -> NodeConfig {
+/// Loads the node config, with the preset of its `node_role` applied, see [`NodeRole`].
+pub fn check_bootstrap_config(node_config_path: Option<PathBuf>) -> (NodeConfig, Option<NodeRole>) {
     // Get the config file path
     let config_path = node_config_path.expect("Config is required to launch node");
     if !config_path.exists() {
@@ -61,9 +73,9 @@ pub fn check_bootstrap_config(node_config_path: Option<PathBuf>) -> NodeConfig {
     }
 
     // A config file exists, attempt to parse the config
-    NodeConfig::load_from_path(config_path.clone()).unwrap_or_else(|error| {
+    load_node_config(&config_path).unwrap_or_else(|error| {
         panic!(
-            "Failed to load the node config file! Given file path: {:?}. Error: {:?}",
+            "Failed to load the node config file! Given file path: {:?}. Error: {}",
             config_path.display(),
             error
         )
@@ -153,6 +165,7 @@ pub fn start_consensus(
     db: DbReaderWriter,
     arg: &mut ConsensusAdapterArgs,
     vtxn_pool: VTxnPoolState,
+    consensus_publisher: Option<Arc<ConsensusPublisher>>,
 ) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
     let consensus_reconfig_subscription = event_subscription_service
         .subscribe_to_reconfigurations()
@@ -167,7 +180,56 @@ pub fn start_consensus(
         db.clone(),
         consensus_reconfig_subscription,
         vtxn_pool,
-        None,
+        consensus_publisher,
+        arg,
+    )
+}
+
+/// Creates the consensus publisher that serves ordered blocks and commit decisions to
+/// subscribed observers, if it is enabled
+pub fn create_consensus_publisher(
+    node_config: &NodeConfig,
+    consensus_observer_network_interfaces: &ApplicationNetworkInterfaces<ConsensusObserverMessage>,
+) -> Option<(Runtime, Arc<ConsensusPublisher>)> {
+    if !node_config.consensus_observer.publisher_enabled {
+        return None;
+    }
+    let runtime = gaptos::aptos_runtimes::spawn_named_runtime("publisher".into(), None);
+    let (consensus_publisher, outbound_message_receiver) = ConsensusPublisher::new(
+        consensus_observer_network_interfaces.network_client.clone(),
+        node_config.consensus_observer,
+    );
+    runtime.spawn(consensus_publisher.clone().start(outbound_message_receiver));
+    Some((runtime, Arc::new(consensus_publisher)))
+}
+
+/// Starts the consensus observer. With the observer enabled the node follows ordered blocks
+/// and commit decisions from a publisher and executes them without voting; otherwise the
+/// runtime only answers subscription requests for the publisher.
+pub fn start_consensus_observer(
+    node_config: &NodeConfig,
+    event_subscription_service: &mut EventSubscriptionService,
+    consensus_observer_network_interfaces: ApplicationNetworkInterfaces<ConsensusObserverMessage>,
+    consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    consensus_notifier: ConsensusNotifier,
+    consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
+    db: DbReaderWriter,
+    arg: &ConsensusAdapterArgs,
+) -> Runtime {
+    let reconfig_subscription = node_config.consensus_observer.observer_enabled.then(|| {
+        event_subscription_service
+            .subscribe_to_reconfigurations()
+            .expect("Consensus observer must subscribe to reconfigurations")
+    });
+    aptos_consensus::consensus_provider::start_consensus_observer(
+        node_config,
+        consensus_observer_network_interfaces.network_client,
+        consensus_observer_network_interfaces.network_service_events,
+        consensus_publisher,
+        Arc::new(consensus_notifier),
+        consensus_to_mempool_sender,
+        db,
+        reconfig_subscription,
         arg,
     )
 }
@@ -241,12 +303,18 @@ pub fn init_mempool(
     mempool_listener: MempoolNotificationListener,
     peers_and_metadata: Arc<PeersAndMetadata>,
     pool: Box<dyn TxPool>,
-) -> Vec<Runtime> {
+    chain_id: ChainId,
+) -> (Vec<Runtime>, MempoolInspector) {
     let mempool_reconfig_subscription = event_subscription_service
         .subscribe_to_reconfigurations()
         .expect("Mempool must subscribe to reconfigurations");
-    let mempool = Box::new(CoreMempool::new(node_config, pool));
-    vec![aptos_mempool::bootstrap(
+    let mut epoch_change_subscription = event_subscription_service
+        .subscribe_to_reconfigurations()
+        .expect("Mempool epoch change hook must subscribe to reconfigurations");
+    let mempool = Box::new(CoreMempool::new(node_config, pool).with_chain_id(chain_id));
+    let epoch_change_hook = mempool.epoch_change_hook();
+    let inspector = mempool.inspector();
+    let runtime = aptos_mempool::bootstrap(
         node_config,
         Arc::clone(&db.reader),
         mempool_interfaces.network_client,
@@ -257,7 +325,24 @@ pub fn init_mempool(
         mempool_reconfig_subscription,
         peers_and_metadata,
         mempool,
-    )]
+    );
+    runtime.spawn(async move {
+        // The first notification carries the epoch the node started in; only later ones are
+        // real epoch changes. Re-notifications of the same epoch (e.g. after sync) are ignored.
+        let mut current_epoch = None;
+        while let Some(notification) = epoch_change_subscription.next().await {
+            let epoch = notification.on_chain_configs.epoch();
+            match current_epoch {
+                Some(current) if epoch <= current => continue,
+                Some(_) => {
+                    epoch_change_hook.on_new_epoch(epoch);
+                }
+                None => {}
+            }
+            current_epoch = Some(epoch);
+        }
+    });
+    (vec![runtime], inspector)
 }
 
 pub fn init_peers_and_metadata(
@@ -270,6 +355,7 @@ pub fn init_peers_and_metadata(
 }
 
 pub async fn init_block_buffer_manager(
+    block_buffer_manager: &Arc<BlockBufferManager>,
     consensus_db: &Arc<ConsensusDB>,
     latest_block_number: u64,
 ) -> anyhow::Result<()> {
@@ -308,8 +394,6 @@ pub async fn init_block_buffer_manager(
         block_number_to_block_id
             .insert(0u64, (0, BlockId::from_bytes(GENESIS_BLOCK_ID.as_slice())));
     }
-    get_block_buffer_manager()
-        .init(latest_block_number, block_number_to_block_id, max_epoch)
-        .await?;
+    block_buffer_manager.init(latest_block_number, block_number_to_block_id, max_epoch).await?;
     Ok(())
 }
@@ -1,16 +1,408 @@
+use crate::trusted_checkpoint::TrustedCheckpoint;
+use block_buffer_manager::{BlockBufferManager, ConsensusEvent};
+use bytes::Bytes;
 use gaptos::{
     api_types::config_storage::{BlockNumber, ConfigStorage, OnChainConfig, OnChainConfigResType},
-    aptos_logger::info,
+    aptos_crypto::HashValue,
+    aptos_logger::{error, info},
+    aptos_metrics_core::{register_int_counter_vec, IntCounterVec},
+    aptos_types::on_chain_config::{OnChainConfig as _, ValidatorSet},
 };
-use std::sync::Arc;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+#[cfg(panic = "unwind")]
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// One onchain config read, kept so the config version the node acted on can be looked up
+/// after the fact.
+#[derive(Clone, Debug, Serialize)]
+pub struct OnChainConfigRead {
+    pub timestamp_ms: u64,
+    pub config: String,
+    pub block_number: String,
+    /// `ok`, `missing` when the execution layer had no value, `unsupported` for configs the
+    /// node does not read from the chain, `undecodable` when the value is not bytes and was
+    /// passed on without a hash, or `failed` when the execution layer panicked reading it.
+    pub outcome: &'static str,
+    pub value_hash: Option<HashValue>,
+    pub value_len: Option<usize>,
+}
+
+/// Number of recent reads kept.
+/// Can be configured via ONCHAIN_CONFIG_READ_HISTORY environment variable
+fn read_history_capacity() -> usize {
+    static CAPACITY: OnceLock<usize> = OnceLock::new();
+    *CAPACITY.get_or_init(|| {
+        std::env::var("ONCHAIN_CONFIG_READ_HISTORY")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1024)
+    })
+}
+
+/// The most recent reads of a [`ConfigStorageWrapper`], shared with the debug routes.
+#[derive(Clone)]
+pub struct ConfigReadHistory {
+    capacity: usize,
+    reads: Arc<Mutex<VecDeque<OnChainConfigRead>>>,
+}
+
+impl ConfigReadHistory {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, reads: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))) }
+    }
+
+    fn record(&self, read: OnChainConfigRead) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut reads = self.reads.lock().unwrap();
+        if reads.len() == self.capacity {
+            reads.pop_front();
+        }
+        reads.push_back(read);
+    }
+
+    /// The recorded reads, oldest first.
+    pub fn recent(&self) -> Vec<OnChainConfigRead> {
+        self.reads.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+static ONCHAIN_CONFIG_CACHE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "gravity_onchain_config_cache_total",
+        "Onchain config reads at a block number by cache outcome",
+        &["config", "outcome"]
+    )
+    .unwrap()
+});
+
+/// Number of onchain config values cached by block number.
+/// Can be configured via ONCHAIN_CONFIG_CACHE_SIZE environment variable, 0 disables the cache
+fn config_cache_capacity() -> usize {
+    static CAPACITY: OnceLock<usize> = OnceLock::new();
+    *CAPACITY.get_or_init(|| {
+        std::env::var("ONCHAIN_CONFIG_CACHE_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(256)
+    })
+}
+
+/// Config values read at a block number, which never change. Reads of the latest value are not
+/// cached. Entries are dropped oldest first when full, and all at once on an epoch change,
+/// after which the previous epoch's blocks are rarely read again.
+struct ConfigCache {
+    capacity: usize,
+    entries: HashMap<(String, u64), OnChainConfigResType>,
+    order: VecDeque<(String, u64)>,
+}
+
+impl ConfigCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, entries: HashMap::new(), order: VecDeque::new() }
+    }
+
+    fn get(&self, key: &(String, u64)) -> Option<OnChainConfigResType> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: (String, u64), value: OnChainConfigResType) {
+        if self.capacity == 0 || self.entries.contains_key(&key) {
+            return;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.entries.insert(key, value);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Why an onchain config could not be read.
+#[derive(Clone, Debug)]
+pub enum ConfigError {
+    /// The execution layer had no value.
+    Missing { config: String, block_number: String },
+    /// The execution layer returned a value that is not bytes.
+    NotBytes { config: String, block_number: String },
+    /// The execution layer panicked reading the value, typically decoding the return data of a
+    /// system contract that is not deployed or has a different ABI.
+    ReadFailed { config: String, block_number: String, message: String },
+    /// The bytes do not decode as the config.
+    Invalid { config: String, block_number: String, returndata: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Missing { config, block_number } => {
+                write!(f, "onchain config {config} at {block_number} is missing")
+            }
+            ConfigError::NotBytes { config, block_number } => {
+                write!(f, "onchain config {config} at {block_number} is not bytes")
+            }
+            ConfigError::ReadFailed { config, block_number, message } => {
+                write!(f, "reading onchain config {config} at {block_number} failed: {message}")
+            }
+            ConfigError::Invalid { config, block_number, returndata, message } => write!(
+                f,
+                "onchain config {config} at {block_number} does not decode: {message}, \
+                 returndata 0x{returndata}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(panic = "unwind")]
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+/// Configs of a trusted checkpoint, BCS encoded like the execution layer returns them.
+struct TrustedConfigs {
+    epoch: u64,
+    block_number: u64,
+    epoch_bytes: Bytes,
+    validator_set: Bytes,
+}
 
 pub struct ConfigStorageWrapper {
     config_storage: Arc<dyn ConfigStorage>,
+    cache: Arc<Mutex<ConfigCache>>,
+    /// Served when the execution layer cannot read the epoch or the validator set at or after
+    /// the checkpoint block yet.
+    trusted: Option<TrustedConfigs>,
+    /// Set once the execution layer answered at or after the checkpoint block, after which the
+    /// checkpoint is never served again.
+    trusted_caught_up: AtomicBool,
+    /// Latest epoch consensus started, the checkpoint is not served past its own epoch.
+    consensus_epoch: Arc<AtomicU64>,
+    read_history: ConfigReadHistory,
 }
 
 impl ConfigStorageWrapper {
     pub fn new(config_storage: Arc<dyn ConfigStorage>) -> Self {
-        Self { config_storage }
+        Self {
+            config_storage,
+            cache: Arc::new(Mutex::new(ConfigCache::new(config_cache_capacity()))),
+            trusted: None,
+            trusted_caught_up: AtomicBool::new(false),
+            consensus_epoch: Arc::new(AtomicU64::new(0)),
+            read_history: ConfigReadHistory::new(read_history_capacity()),
+        }
+    }
+
+    /// The reads of this wrapper, served at `/debug/onchain_config_reads`.
+    pub fn read_history(&self) -> ConfigReadHistory {
+        self.read_history.clone()
+    }
+
+    pub fn with_trusted_checkpoint(mut self, checkpoint: &TrustedCheckpoint) -> Self {
+        let validator_set = bcs::to_bytes(&checkpoint.validator_set)
+            .expect("validator set serialization cannot fail");
+        let epoch_bytes =
+            bcs::to_bytes(&checkpoint.epoch()).expect("u64 serialization cannot fail");
+        self.trusted = Some(TrustedConfigs {
+            epoch: checkpoint.epoch(),
+            block_number: checkpoint.block_number(),
+            epoch_bytes: epoch_bytes.into(),
+            validator_set: validator_set.into(),
+        });
+        self
+    }
+
+    /// Whether a read of `config_name` at `block_number` is one the checkpoint answers for.
+    fn trusted_config(
+        &self,
+        config_name: &OnChainConfig,
+        block_number: &BlockNumber,
+    ) -> Option<&TrustedConfigs> {
+        let trusted = self.trusted.as_ref()?;
+        let covered = match block_number {
+            BlockNumber::Number(number) => *number >= trusted.block_number,
+            _ => true,
+        };
+        (covered && matches!(config_name, OnChainConfig::Epoch | OnChainConfig::ValidatorSet))
+            .then_some(trusted)
+    }
+
+    fn trusted_fallback(
+        &self,
+        config_name: &OnChainConfig,
+        block_number: &BlockNumber,
+    ) -> Option<Bytes> {
+        let trusted = self.trusted_config(config_name, block_number)?;
+        if self.trusted_caught_up.load(Ordering::Relaxed) ||
+            self.consensus_epoch.load(Ordering::Relaxed) > trusted.epoch
+        {
+            return None;
+        }
+        match config_name {
+            OnChainConfig::Epoch => Some(trusted.epoch_bytes.clone()),
+            _ => Some(trusted.validator_set.clone()),
+        }
+    }
+
+    /// Clears the cache whenever consensus starts an epoch, and stops serving the trusted
+    /// checkpoint once consensus is past its epoch. Must be called within a tokio runtime.
+    pub fn with_epoch_invalidation(self, block_buffer_manager: &BlockBufferManager) -> Self {
+        let mut events = block_buffer_manager.subscribe_events();
+        let cache = self.cache.clone();
+        let consensus_epoch = self.consensus_epoch.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(ConsensusEvent::EpochStarted { epoch }) => {
+                        consensus_epoch.fetch_max(epoch, Ordering::Relaxed);
+                        cache.lock().unwrap().clear();
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                        cache.lock().unwrap().clear();
+                    }
+                    Ok(_) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        self
+    }
+
+    /// Reads a config from the execution layer as bytes, turning a panic of the execution layer
+    /// into an error. Values at a block number are served from the cache after the first read.
+    pub fn try_fetch_config_bytes(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Result<Bytes, ConfigError> {
+        let config = format!("{config_name:?}");
+        let block_number_str = format!("{block_number:?}");
+        TryInto::<Bytes>::try_into(self.fetch_value(config_name, block_number)?)
+            .map_err(|_| ConfigError::NotBytes { config, block_number: block_number_str })
+    }
+
+    /// Reads a config as the execution layer returned it, through the cache and the trusted
+    /// checkpoint.
+    fn fetch_value(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Result<OnChainConfigResType, ConfigError> {
+        let config = format!("{config_name:?}");
+        let cache_key = match block_number {
+            BlockNumber::Number(number) => Some((config.clone(), number)),
+            _ => None,
+        };
+        if let Some(key) = &cache_key {
+            if let Some(value) = self.cache.lock().unwrap().get(key) {
+                ONCHAIN_CONFIG_CACHE_TOTAL.with_label_values(&[config.as_str(), "hit"]).inc();
+                return Ok(value);
+            }
+            ONCHAIN_CONFIG_CACHE_TOTAL.with_label_values(&[config.as_str(), "miss"]).inc();
+        }
+        let fallback = self.trusted_fallback(&config_name, &block_number);
+        let checkpointed = self.trusted_config(&config_name, &block_number).is_some() &&
+            matches!(block_number, BlockNumber::Number(_));
+        let value = match (self.fetch_uncached(config_name, block_number), fallback) {
+            (Ok(value), _) => {
+                if checkpointed && !self.trusted_caught_up.swap(true, Ordering::Relaxed) {
+                    info!("execution layer caught up with the trusted checkpoint");
+                }
+                value
+            }
+            (Err(ConfigError::Missing { .. } | ConfigError::ReadFailed { .. }), Some(bytes)) => {
+                info!("serving {} from the trusted checkpoint", config);
+                // Not cached, the execution layer answers once it caught up
+                return Ok(bytes.into());
+            }
+            (Err(e), _) => return Err(e),
+        };
+        if let Some(key) = cache_key {
+            self.cache.lock().unwrap().insert(key, value.clone());
+        }
+        Ok(value)
+    }
+
+    fn fetch_uncached(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Result<OnChainConfigResType, ConfigError> {
+        let config = format!("{config_name:?}");
+        let block_number_str = format!("{block_number:?}");
+        let res = self.read_catching_panics(config_name, block_number).map_err(|message| {
+            ConfigError::ReadFailed {
+                config: config.clone(),
+                block_number: block_number_str.clone(),
+                message,
+            }
+        })?;
+        res.ok_or(ConfigError::Missing { config, block_number: block_number_str })
+    }
+
+    #[cfg(panic = "unwind")]
+    fn read_catching_panics(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Result<Option<OnChainConfigResType>, String> {
+        catch_unwind(AssertUnwindSafe(|| {
+            self.config_storage.fetch_config_bytes(config_name, block_number)
+        }))
+        .map_err(panic_message)
+    }
+
+    /// Profiles built with `panic = "abort"`, such as `quick-release` and `pprof`, cannot catch
+    /// the panic, so there a read of a config whose system contract is missing aborts the node.
+    /// The node checks the system contracts are deployed before it reads any config, which
+    /// catches the usual cause with a readable error on those builds too.
+    #[cfg(not(panic = "unwind"))]
+    fn read_catching_panics(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Result<Option<OnChainConfigResType>, String> {
+        Ok(self.config_storage.fetch_config_bytes(config_name, block_number))
+    }
+
+    /// Reads the configs consensus cannot start without, so a node whose execution layer
+    /// cannot serve them refuses to start instead of panicking later in execution.
+    pub fn check_required_configs(&self) -> Result<(), ConfigError> {
+        self.try_fetch_config_bytes(OnChainConfig::Epoch, BlockNumber::Latest)?;
+        let bytes =
+            self.try_fetch_config_bytes(OnChainConfig::ValidatorSet, BlockNumber::Latest)?;
+        ValidatorSet::deserialize_into_config(&bytes).map_err(|e| ConfigError::Invalid {
+            config: format!("{:?}", OnChainConfig::ValidatorSet),
+            block_number: format!("{:?}", BlockNumber::Latest),
+            returndata: hex::encode(&bytes),
+            message: e.to_string(),
+        })?;
+        Ok(())
     }
 }
 
@@ -21,7 +413,18 @@ impl ConfigStorage for ConfigStorageWrapper {
         block_number: BlockNumber,
     ) -> Option<OnChainConfigResType> {
         info!("fetch_config_bytes: {:?}, block_number: {:?}", config_name, block_number);
-        match config_name {
+        let mut read = OnChainConfigRead {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            config: format!("{config_name:?}"),
+            block_number: format!("{block_number:?}"),
+            outcome: "unsupported",
+            value_hash: None,
+            value_len: None,
+        };
+        let res = match config_name {
             OnChainConfig::Epoch |
             OnChainConfig::ValidatorSet |
             OnChainConfig::JWKConsensusConfig |
@@ -30,13 +433,278 @@ impl ConfigStorage for ConfigStorageWrapper {
             OnChainConfig::OracleState |
             OnChainConfig::DKGState |
             OnChainConfig::ValidatorPerformances |
-            OnChainConfig::ConsensusConfig => {
-                self.config_storage.fetch_config_bytes(config_name, block_number)
+            OnChainConfig::ConsensusConfig |
+            OnChainConfig::Features => {
+                // Passed on as the execution layer returned it, hashed when it is bytes
+                match self.fetch_value(config_name, block_number) {
+                    Ok(value) => {
+                        match TryInto::<Bytes>::try_into(value.clone()) {
+                            Ok(bytes) => {
+                                read.outcome = "ok";
+                                read.value_hash = Some(HashValue::sha3_256_of(&bytes));
+                                read.value_len = Some(bytes.len());
+                            }
+                            Err(_) => read.outcome = "undecodable",
+                        }
+                        Some(value)
+                    }
+                    Err(e) => {
+                        read.outcome = match e {
+                            ConfigError::Missing { .. } => "missing",
+                            ConfigError::NotBytes { .. } | ConfigError::Invalid { .. } => {
+                                "undecodable"
+                            }
+                            ConfigError::ReadFailed { .. } => "failed",
+                        };
+                        error!("{}", e);
+                        None
+                    }
+                }
             }
             _ => {
                 // Return None so the caller can use default config for dev debug
                 None
             }
+        };
+        self.read_history.record(read);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_consensus::protocol_features::{ProtocolFeature, ProtocolFeatures};
+    use gaptos::aptos_types::on_chain_config::{Features, OnChainConfig as _};
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct CountingStorage {
+        reads: AtomicUsize,
+    }
+
+    impl ConfigStorage for CountingStorage {
+        fn fetch_config_bytes(
+            &self,
+            _config_name: OnChainConfig,
+            _block_number: BlockNumber,
+        ) -> Option<OnChainConfigResType> {
+            let reads = self.reads.fetch_add(1, Ordering::SeqCst);
+            Some(Bytes::from(vec![reads as u8]).into())
         }
     }
+
+    #[test]
+    fn caches_reads_at_a_block_number() {
+        let storage = Arc::new(CountingStorage::default());
+        let wrapper = ConfigStorageWrapper::new(storage.clone());
+        let read = |block_number| {
+            wrapper.try_fetch_config_bytes(OnChainConfig::RandomnessConfig, block_number).unwrap()
+        };
+
+        assert_eq!(read(BlockNumber::Number(10)), read(BlockNumber::Number(10)));
+        assert_eq!(storage.reads.load(Ordering::SeqCst), 1);
+        // The latest value changes with every block
+        read(BlockNumber::Latest);
+        read(BlockNumber::Latest);
+        assert_eq!(storage.reads.load(Ordering::SeqCst), 3);
+
+        wrapper.cache.lock().unwrap().clear();
+        assert_ne!(read(BlockNumber::Number(10)), Bytes::from(vec![0u8]));
+        assert_eq!(storage.reads.load(Ordering::SeqCst), 4);
+    }
+
+    struct FixedStorage;
+
+    impl ConfigStorage for FixedStorage {
+        fn fetch_config_bytes(
+            &self,
+            config_name: OnChainConfig,
+            _block_number: BlockNumber,
+        ) -> Option<OnChainConfigResType> {
+            match config_name {
+                OnChainConfig::ValidatorSet => None,
+                OnChainConfig::DKGState => panic!("returndata does not decode"),
+                _ => Some(Bytes::from_static(b"config").into()),
+            }
+        }
+    }
+
+    #[test]
+    fn passes_the_value_on_and_records_the_read() {
+        let wrapper = ConfigStorageWrapper::new(Arc::new(FixedStorage));
+        let value =
+            wrapper.fetch_config_bytes(OnChainConfig::ConsensusConfig, BlockNumber::Number(3));
+        let bytes: Bytes = value.unwrap().try_into().unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"config"));
+        assert!(wrapper
+            .fetch_config_bytes(OnChainConfig::ValidatorSet, BlockNumber::Latest)
+            .is_none());
+        assert!(wrapper.fetch_config_bytes(OnChainConfig::DKGState, BlockNumber::Latest).is_none());
+
+        let reads = wrapper.read_history().recent();
+        let outcomes: Vec<_> = reads.iter().map(|read| read.outcome).collect();
+        assert_eq!(outcomes, ["ok", "missing", "failed"]);
+        assert_eq!(reads[0].value_hash, Some(HashValue::sha3_256_of(b"config")));
+        assert_eq!(reads[0].value_len, Some(6));
+        assert_eq!(reads[1].value_hash, None);
+    }
+
+    #[test]
+    fn read_history_is_bounded_and_per_wrapper() {
+        let history = ConfigReadHistory::new(2);
+        for block in 0..3u64 {
+            history.record(OnChainConfigRead {
+                timestamp_ms: 0,
+                config: "Epoch".to_string(),
+                block_number: block.to_string(),
+                outcome: "ok",
+                value_hash: None,
+                value_len: None,
+            });
+        }
+        let blocks: Vec<_> = history.recent().into_iter().map(|read| read.block_number).collect();
+        assert_eq!(blocks, ["1", "2"]);
+
+        let first = ConfigStorageWrapper::new(Arc::new(FixedStorage));
+        let second = ConfigStorageWrapper::new(Arc::new(FixedStorage));
+        first.fetch_config_bytes(OnChainConfig::Epoch, BlockNumber::Latest);
+        assert_eq!(first.read_history().recent().len(), 1);
+        assert!(second.read_history().recent().is_empty());
+    }
+
+    struct FeaturesStorage(Features);
+
+    impl ConfigStorage for FeaturesStorage {
+        fn fetch_config_bytes(
+            &self,
+            config_name: OnChainConfig,
+            _block_number: BlockNumber,
+        ) -> Option<OnChainConfigResType> {
+            match config_name {
+                OnChainConfig::Features => {
+                    Some(Bytes::from(bcs::to_bytes(&self.0).unwrap()).into())
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn onchain_flags_activate_protocol_features() {
+        let feature = ProtocolFeature::SystemTxns;
+        let mut features = vec![0u8; (feature.flag() / 8 + 1) as usize];
+        features[(feature.flag() / 8) as usize] |= 1 << (feature.flag() % 8);
+        let wrapper = ConfigStorageWrapper::new(Arc::new(FeaturesStorage(Features { features })));
+
+        // The path the epoch manager reads the features of a new epoch through
+        let value = wrapper.fetch_config_bytes(OnChainConfig::Features, BlockNumber::Latest);
+        let bytes: Bytes = value.unwrap().try_into().unwrap();
+        let features = Features::deserialize_into_config(&bytes).unwrap();
+        let active = ProtocolFeatures::from_onchain(Some(&features));
+        assert!(active.is_active(feature));
+        assert!(!active.is_active(ProtocolFeature::OptQuorumStorePayload));
+    }
+
+    /// Serves the configs [`ConfigStorageWrapper::check_required_configs`] reads.
+    struct RequiredStorage {
+        validator_set: Option<Vec<u8>>,
+    }
+
+    impl ConfigStorage for RequiredStorage {
+        fn fetch_config_bytes(
+            &self,
+            config_name: OnChainConfig,
+            _block_number: BlockNumber,
+        ) -> Option<OnChainConfigResType> {
+            match config_name {
+                OnChainConfig::Epoch => Some(Bytes::from(1u64.to_le_bytes().to_vec()).into()),
+                OnChainConfig::ValidatorSet => {
+                    self.validator_set.clone().map(|bytes| Bytes::from(bytes).into())
+                }
+                _ => panic!("{config_name:?} is not a required config"),
+            }
+        }
+    }
+
+    #[test]
+    fn required_configs_must_be_readable_and_decode() {
+        let check = |validator_set| {
+            ConfigStorageWrapper::new(Arc::new(RequiredStorage { validator_set }))
+                .check_required_configs()
+        };
+        let validator_set = bcs::to_bytes(&ValidatorSet::empty()).unwrap();
+        assert!(check(Some(validator_set)).is_ok());
+        assert!(matches!(
+            check(None),
+            Err(ConfigError::Missing { config, .. }) if config == "ValidatorSet"
+        ));
+        assert!(matches!(
+            check(Some(vec![0xff; 3])),
+            Err(ConfigError::Invalid { returndata, .. }) if returndata == "ffffff"
+        ));
+        // The execution layer panics decoding the return data of a missing system contract
+        let wrapper = ConfigStorageWrapper::new(Arc::new(FixedStorage));
+        assert!(matches!(
+            wrapper.try_fetch_config_bytes(OnChainConfig::DKGState, BlockNumber::Latest),
+            Err(ConfigError::ReadFailed { message, .. }) if message == "returndata does not decode"
+        ));
+    }
+
+    /// Execution layer that cannot read the epoch or the validator set at or after block 100
+    /// until it caught up.
+    #[derive(Default)]
+    struct SyncingStorage {
+        caught_up: AtomicBool,
+    }
+
+    impl ConfigStorage for SyncingStorage {
+        fn fetch_config_bytes(
+            &self,
+            _config_name: OnChainConfig,
+            block_number: BlockNumber,
+        ) -> Option<OnChainConfigResType> {
+            let readable = match block_number {
+                BlockNumber::Number(number) => number < 100,
+                _ => true,
+            };
+            (readable || self.caught_up.load(Ordering::SeqCst))
+                .then(|| Bytes::from_static(b"execution").into())
+        }
+    }
+
+    #[test]
+    fn trusted_checkpoint_is_served_in_its_epoch_until_the_execution_layer_caught_up() {
+        let storage = Arc::new(SyncingStorage::default());
+        let mut wrapper = ConfigStorageWrapper::new(storage.clone());
+        wrapper.trusted = Some(TrustedConfigs {
+            epoch: 3,
+            block_number: 100,
+            epoch_bytes: Bytes::from_static(b"epoch"),
+            validator_set: Bytes::from_static(b"validators"),
+        });
+        let read = |config_name, block_number| {
+            wrapper.try_fetch_config_bytes(config_name, BlockNumber::Number(block_number))
+        };
+
+        assert_eq!(read(OnChainConfig::ValidatorSet, 99).unwrap(), &b"execution"[..]);
+        assert_eq!(read(OnChainConfig::ValidatorSet, 100).unwrap(), &b"validators"[..]);
+        assert_eq!(read(OnChainConfig::Epoch, 100).unwrap(), &b"epoch"[..]);
+        assert!(matches!(
+            read(OnChainConfig::ConsensusConfig, 100),
+            Err(ConfigError::Missing { .. })
+        ));
+
+        // Not past the checkpoint epoch
+        wrapper.consensus_epoch.store(4, Ordering::SeqCst);
+        assert!(matches!(read(OnChainConfig::ValidatorSet, 101), Err(ConfigError::Missing { .. })));
+        wrapper.consensus_epoch.store(3, Ordering::SeqCst);
+        assert_eq!(read(OnChainConfig::ValidatorSet, 101).unwrap(), &b"validators"[..]);
+
+        // Nor once the execution layer answered at or after the checkpoint
+        storage.caught_up.store(true, Ordering::SeqCst);
+        assert_eq!(read(OnChainConfig::Epoch, 102).unwrap(), &b"execution"[..]);
+        storage.caught_up.store(false, Ordering::SeqCst);
+        assert!(matches!(read(OnChainConfig::ValidatorSet, 103), Err(ConfigError::Missing { .. })));
+    }
 }
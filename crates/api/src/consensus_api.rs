@@ -2,22 +2,35 @@ use std::{path::PathBuf, sync::Arc};
 
 use crate::{
     bootstrap::{
-        create_dkg_runtime, dkg_network_configuration, init_block_buffer_manager,
-        init_jwk_consensus, init_mempool, init_peers_and_metadata, start_consensus,
-        start_node_inspection_service,
+        create_consensus_publisher, create_dkg_runtime, dkg_network_configuration,
+        init_block_buffer_manager, init_jwk_consensus, init_mempool, init_peers_and_metadata,
+        start_consensus, start_consensus_observer, start_node_inspection_service,
     },
+    config_storage::ConfigReadHistory,
     consensus_mempool_handler::{ConsensusToMempoolHandler, MempoolNotificationHandler},
-    https::https_server,
+    consensus_pruner::{consensus_prune_interval, consensus_prune_retention, run_consensus_pruner},
+    https::{
+        listener::{
+            client_ca_pem_from_env, https_listeners_from_env, primary_routes_from_env,
+            HttpsListener, RouteGroup,
+        },
+        query_replica::QUERY_REPLICA_DIR_NAME,
+        HttpsServer,
+    },
     logger,
     network::{
-        consensus_network_configuration, create_network_interfaces, create_network_runtime,
-        extract_network_configs, jwk_consensus_network_configuration,
-        mempool_network_configuration, register_client_and_service_with_network,
-        ApplicationNetworkHandle,
+        consensus_network_configuration, consensus_observer_network_configuration,
+        create_network_interfaces, create_network_runtime, extract_network_configs,
+        jwk_consensus_network_configuration, mempool_network_configuration,
+        register_client_and_service_with_network, ApplicationNetworkHandle,
     },
+    trusted_checkpoint::TrustedCheckpoint,
+    watermark_metrics::spawn_watermark_metrics,
+};
+use aptos_consensus::{
+    consensusdb::ConsensusDB, gravity_state_computer::ConsensusAdapterArgs, RecoveryApi,
 };
-use aptos_consensus::{consensusdb::ConsensusDB, gravity_state_computer::ConsensusAdapterArgs};
-use block_buffer_manager::TxPool;
+use block_buffer_manager::{BlockBufferManager, ConsensusEvent, TxPool};
 use build_info::build_information;
 use futures::channel::mpsc;
 use gaptos::{
@@ -32,7 +45,11 @@ use gaptos::{
     aptos_types::chain_id::ChainId,
     aptos_validator_transaction_pool::VTxnPoolState,
 };
-use tokio::{runtime::Runtime, sync::Mutex};
+use genesis_bundle::GenesisBundle;
+use tokio::{
+    runtime::Runtime,
+    sync::{broadcast, Mutex},
+};
 
 #[cfg(unix)]
 #[global_allocator]
@@ -41,6 +58,7 @@ static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 pub struct ConsensusEngine {
     #[allow(dead_code)]
     runtimes: Vec<Runtime>,
+    block_buffer_manager: Arc<BlockBufferManager>,
 }
 
 impl Drop for ConsensusEngine {
@@ -77,7 +95,11 @@ struct HttpsServerConfig {
     address: String,
     cert_pem: Option<PathBuf>,
     key_pem: Option<PathBuf>,
+    client_ca_pem: Option<PathBuf>,
+    routes: Vec<RouteGroup>,
     consensus_db: Option<Arc<ConsensusDB>>,
+    query_replica_dir: Option<PathBuf>,
+    extra_listeners: Vec<HttpsListener>,
 }
 
 fn prepare_https_server_config(
@@ -102,7 +124,11 @@ fn prepare_https_server_config(
         address: node_config.https_server_address.clone(),
         cert_pem,
         key_pem,
+        client_ca_pem: client_ca_pem_from_env(),
+        routes: primary_routes_from_env().unwrap_or_else(|e| panic!("{e}")),
         consensus_db: consensus_db_clone,
+        query_replica_dir: Some(node_config.storage.dir().join(QUERY_REPLICA_DIR_NAME)),
+        extra_listeners: https_listeners_from_env().unwrap_or_else(|e| panic!("{e}")),
     }
 }
 
@@ -111,16 +137,40 @@ pub struct ConsensusEngineArgs {
     pub chain_id: u64,
     pub latest_block_number: u64,
     pub config_storage: Option<Arc<dyn ConfigStorage>>,
+    /// Reads of `config_storage`, served at `/debug/onchain_config_reads` when set.
+    pub config_reads: Option<ConfigReadHistory>,
+    /// Buffer shared with the execution layer of this node.
+    pub block_buffer_manager: Arc<BlockBufferManager>,
+    /// Stored in the consensus DB of a fresh node, see [`TrustedCheckpoint::apply`].
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Genesis of a new chain, see [`GenesisBundle::apply`].
+    pub genesis_bundle: Option<GenesisBundle>,
+    /// Replays the blocks block sync verified as committed. `None` replays them through
+    /// `block_buffer_manager`.
+    pub recovery_api: Option<Arc<dyn RecoveryApi>>,
 }
 
 impl ConsensusEngine {
     pub async fn init(args: ConsensusEngineArgs, pool: Box<dyn TxPool>) -> Arc<Self> {
-        let ConsensusEngineArgs { node_config, chain_id, latest_block_number, config_storage } =
-            args;
+        let ConsensusEngineArgs {
+            mut node_config,
+            chain_id,
+            latest_block_number,
+            config_storage,
+            config_reads,
+            block_buffer_manager,
+            trusted_checkpoint,
+            genesis_bundle,
+            recovery_api,
+        } = args;
         // Setup panic handler
         gaptos::aptos_crash_handler::setup_panic_handler();
+        if let Some(bundle) = &genesis_bundle {
+            bundle.apply(chain_id, &mut node_config).unwrap_or_else(|e| panic!("{e}"));
+        }
 
         fail_point_check(&node_config);
+        aptos_consensus::register_runtime_knobs();
         let consensus_db =
             Arc::new(ConsensusDB::new(node_config.storage.dir(), &node_config.node_config_path));
         let peers_and_metadata = init_peers_and_metadata(&node_config, &consensus_db);
@@ -150,6 +200,10 @@ impl ConsensusEngine {
                 }
             }
         }
+        // Needs the config storage, a fresh database reads the genesis validator set from it
+        if let Some(checkpoint) = &trusted_checkpoint {
+            checkpoint.apply(&consensus_db, latest_block_number).unwrap_or_else(|e| panic!("{e}"));
+        }
         let chain_id = ChainId::from(chain_id);
         let network_configs = extract_network_configs(&node_config);
 
@@ -158,6 +212,9 @@ impl ConsensusEngine {
         let mut dkg_network_handle: Option<ApplicationNetworkHandle<DKGMessage>> = None;
         let mut consensus_network_handles = vec![];
         let mut mempool_network_handles = vec![];
+        let mut consensus_observer_network_handles = vec![];
+        let observer_or_publisher_enabled = node_config.consensus_observer.observer_enabled ||
+            node_config.consensus_observer.publisher_enabled;
         for network_config in network_configs.into_iter() {
             // Create a network runtime for the config
             let runtime = create_network_runtime(&network_config);
@@ -229,6 +286,18 @@ impl ConsensusEngine {
             );
             mempool_network_handles.push(mempool_network_handle);
 
+            // Register the consensus observer (both client and server) with the network
+            if observer_or_publisher_enabled {
+                let network_handle = register_client_and_service_with_network(
+                    &mut network_builder,
+                    network_id,
+                    &network_config,
+                    consensus_observer_network_configuration(&node_config),
+                    true,
+                );
+                consensus_observer_network_handles.push(network_handle);
+            }
+
             // Build and start the network on the runtime
             network_builder.build(runtime.handle().clone());
             network_builder.start();
@@ -253,6 +322,13 @@ impl ConsensusEngine {
             mempool_network_configuration(&node_config),
             peers_and_metadata.clone(),
         );
+        let consensus_observer_interfaces = observer_or_publisher_enabled.then(|| {
+            create_network_interfaces(
+                consensus_observer_network_handles,
+                consensus_observer_network_configuration(&node_config),
+                peers_and_metadata.clone(),
+            )
+        });
 
         let dkg_interfaces = dkg_network_handle.map(|handle| {
             create_network_interfaces(
@@ -281,7 +357,7 @@ impl ConsensusEngine {
                 notification_receiver,
             );
         let (_mempool_client_sender, _mempool_client_receiver) = mpsc::channel(1);
-        let mempool_runtime = init_mempool(
+        let (mempool_runtime, mempool_inspector) = init_mempool(
             &node_config,
             &db,
             &mut event_subscription_service,
@@ -291,6 +367,7 @@ impl ConsensusEngine {
             mempool_listener,
             peers_and_metadata,
             pool,
+            chain_id,
         );
         runtimes.extend(mempool_runtime);
 
@@ -316,21 +393,79 @@ impl ConsensusEngine {
             );
             runtimes.push(jwk_consensus_runtime);
         }
-        init_block_buffer_manager(&consensus_db, latest_block_number)
+        init_block_buffer_manager(&block_buffer_manager, &consensus_db, latest_block_number)
             .await
             .expect("failed to initialize BlockBufferManager");
-        let mut args = ConsensusAdapterArgs::new(consensus_db.clone());
-        let (consensus_runtime, _, _) = start_consensus(
-            &node_config,
-            &mut event_subscription_service,
-            consensus_interfaces,
-            consensus_notifier,
-            consensus_to_mempool_sender,
-            db,
-            &mut args,
-            vtxn_pool,
-        );
-        runtimes.push(consensus_runtime);
+        runtimes.push(spawn_watermark_metrics(&block_buffer_manager));
+        if let Some(retention) = consensus_prune_retention() {
+            let runtime = gaptos::aptos_runtimes::spawn_named_runtime("ConsPrune".into(), None);
+            runtime.spawn(run_consensus_pruner(
+                consensus_db.clone(),
+                block_buffer_manager.clone(),
+                retention,
+                consensus_prune_interval(),
+            ));
+            runtimes.push(runtime);
+        }
+        let mut args =
+            ConsensusAdapterArgs::new(consensus_db.clone(), block_buffer_manager.clone());
+        if let Some(recovery_api) = recovery_api {
+            args = args.with_recovery_api(recovery_api);
+        }
+        let consensus_publisher = consensus_observer_interfaces
+            .as_ref()
+            .and_then(|interfaces| create_consensus_publisher(&node_config, interfaces))
+            .map(|(runtime, consensus_publisher)| {
+                runtimes.push(runtime);
+                consensus_publisher
+            });
+        let mut quorum_store_db = None;
+        if node_config.consensus_observer.observer_enabled {
+            // Observers follow the ordered blocks of a publisher instead of voting
+            let observer_runtime = start_consensus_observer(
+                &node_config,
+                &mut event_subscription_service,
+                consensus_observer_interfaces.expect("observer network is registered"),
+                consensus_publisher,
+                consensus_notifier,
+                consensus_to_mempool_sender,
+                db,
+                &args,
+            );
+            runtimes.push(observer_runtime);
+        } else {
+            let (consensus_runtime, _, consensus_quorum_store_db) = start_consensus(
+                &node_config,
+                &mut event_subscription_service,
+                consensus_interfaces,
+                consensus_notifier,
+                consensus_to_mempool_sender.clone(),
+                db.clone(),
+                &mut args,
+                vtxn_pool,
+                consensus_publisher.clone(),
+            );
+            runtimes.push(consensus_runtime);
+            quorum_store_db = Some(consensus_quorum_store_db);
+            if let Some(consensus_observer_interfaces) = consensus_observer_interfaces {
+                // Serves the subscription requests of observers, nothing is executed here
+                let (observer_notifier, _) =
+                    gaptos::aptos_consensus_notifications::new_consensus_notifier_listener_pair(
+                        state_sync_config.state_sync_driver.commit_notification_timeout_ms,
+                    );
+                let observer_runtime = start_consensus_observer(
+                    &node_config,
+                    &mut event_subscription_service,
+                    consensus_observer_interfaces,
+                    consensus_publisher,
+                    observer_notifier,
+                    consensus_to_mempool_sender,
+                    db,
+                    &args,
+                );
+                runtimes.push(observer_runtime);
+            }
+        }
         // Create notification senders and listeners for mempool, consensus and the storage service
         // For Gravity we only use it to notify the mempool for the committed txn gc logic
         let mempool_notifier =
@@ -347,30 +482,43 @@ impl ConsensusEngine {
             consensus_mempool_handler.start().await;
         });
         runtimes.push(runtime);
-        // The HTTP/HTTPS API server exposes consensus/DKG endpoints,
-        // failpoint injection, and heap profiling. None of these are needed in production.
-        // Gate the entire server behind debug_assertions so it is not started in release builds.
-        #[cfg(debug_assertions)]
-        {
-            let https_config = prepare_https_server_config(&node_config, consensus_db.clone());
-            if !https_config.address.is_empty() {
-                let runtime = gaptos::aptos_runtimes::spawn_named_runtime("Http".into(), None);
-                runtime.spawn(async move {
-                    https_server(
-                        https_config.address,
-                        https_config.cert_pem,
-                        https_config.key_pem,
-                        https_config.consensus_db,
-                    )
-                    .await
-                });
-                runtimes.push(runtime);
+        // The HTTP/HTTPS API server exposes consensus/DKG endpoints, failpoint injection and
+        // profiling. Release builds only serve the latter behind a bearer token or client
+        // certificates, see `https::auth`.
+        let https_config = prepare_https_server_config(&node_config, consensus_db.clone());
+        if !https_config.address.is_empty() {
+            let runtime = gaptos::aptos_runtimes::spawn_named_runtime("Http".into(), None);
+            let mut server = HttpsServer::new(
+                https_config.address,
+                https_config.cert_pem,
+                https_config.key_pem,
+                https_config.consensus_db,
+                https_config.query_replica_dir,
+            )
+            .with_client_ca_pem(https_config.client_ca_pem)
+            .with_routes(https_config.routes)
+            .with_extra_listeners(https_config.extra_listeners)
+            .with_mempool_inspector(mempool_inspector)
+            .with_block_buffer_manager(block_buffer_manager.clone());
+            if let Some(quorum_store_db) = quorum_store_db {
+                server = server.with_quorum_store_db(quorum_store_db);
             }
+            if let Some(config_reads) = config_reads {
+                server = server.with_config_reads(config_reads);
+            }
+            runtime.spawn(server.serve());
+            runtimes.push(runtime);
         }
-        let arc_consensus_engine = Arc::new(Self { runtimes });
+        let arc_consensus_engine = Arc::new(Self { runtimes, block_buffer_manager });
         // process new round should be after init retƒh hash
         info!("pass latest_block_number: {:?} to event_subscription_service", latest_block_number);
         let _ = event_subscription_service.lock().await.notify_initial_configs(latest_block_number);
         arc_consensus_engine
     }
+
+    /// Epoch starts, validator set updates, reconfigurations and commits of this node, for
+    /// embedders that keep state tied to them.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ConsensusEvent> {
+        self.block_buffer_manager.subscribe_events()
+    }
 }
@@ -0,0 +1,52 @@
+use aptos_consensus::consensusdb::{ConsensusDB, PruneRetention};
+use block_buffer_manager::BlockBufferManager;
+use gaptos::aptos_logger::{error, info};
+use std::{sync::Arc, time::Duration};
+
+/// How often the pruner runs when CONSENSUS_PRUNE_INTERVAL_SECS is unset.
+const DEFAULT_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name).ok().and_then(|s| s.parse::<u64>().ok())
+}
+
+/// Committed consensus history to keep, configured via CONSENSUS_PRUNE_KEEP_EPOCHS and
+/// CONSENSUS_PRUNE_KEEP_BLOCKS. Pruning is disabled when neither is set.
+pub fn consensus_prune_retention() -> Option<PruneRetention> {
+    let retention = PruneRetention {
+        epochs: env_u64("CONSENSUS_PRUNE_KEEP_EPOCHS"),
+        blocks: env_u64("CONSENSUS_PRUNE_KEEP_BLOCKS"),
+    };
+    (retention != PruneRetention::default()).then_some(retention)
+}
+
+/// How often the pruner runs, configured via CONSENSUS_PRUNE_INTERVAL_SECS.
+pub fn consensus_prune_interval() -> Duration {
+    env_u64("CONSENSUS_PRUNE_INTERVAL_SECS")
+        .filter(|secs| *secs > 0)
+        .map_or(DEFAULT_PRUNE_INTERVAL, Duration::from_secs)
+}
+
+/// Periodically prunes ConsensusDB down to `retention`, never past the block the execution
+/// layer has persisted.
+pub async fn run_consensus_pruner(
+    consensus_db: Arc<ConsensusDB>,
+    block_buffer_manager: Arc<BlockBufferManager>,
+    retention: PruneRetention,
+    interval: Duration,
+) {
+    info!("Pruning ConsensusDB to {:?} every {:?}", retention, interval);
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let finalized_block_number = block_buffer_manager.latest_finalized_block_number().await;
+        let db = consensus_db.clone();
+        let result =
+            tokio::task::spawn_blocking(move || db.prune(retention, finalized_block_number)).await;
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => error!("Failed to prune ConsensusDB: {:?}", e),
+            Err(e) => error!("ConsensusDB pruning panicked: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Admin endpoints listing and changing the knobs of the runtime config registry, so a knob can
+//! be tuned without restarting the node. Changes are not persisted, the environment variable of
+//! a knob still decides its value after a restart.
+
+use crate::https::auth::require_token;
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use gaptos::aptos_logger::info;
+use runtime_config::{KnobValue, KnobView, RuntimeConfigRegistry};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Bearer token of the admin endpoints, which are disabled while it is unset.
+/// Can be configured via GRAVITY_ADMIN_TOKEN environment variable
+pub(crate) fn admin_token() -> Option<String> {
+    std::env::var("GRAVITY_ADMIN_TOKEN").ok().filter(|token| !token.is_empty())
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SetKnobRequest {
+    value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetKnobResponse {
+    pub name: String,
+    pub previous: KnobValue,
+    pub value: KnobValue,
+}
+
+async fn list_knobs() -> Json<Vec<KnobView>> {
+    Json(RuntimeConfigRegistry::global().list())
+}
+
+async fn get_knob(Path(name): Path<String>) -> Response {
+    match RuntimeConfigRegistry::global().get(&name) {
+        Some(knob) => Json(knob).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("unknown knob '{name}'")).into_response(),
+    }
+}
+
+async fn set_knob(Path(name): Path<String>, Json(request): Json<SetKnobRequest>) -> Response {
+    let registry = RuntimeConfigRegistry::global();
+    if registry.get(&name).is_none() {
+        return (StatusCode::NOT_FOUND, format!("unknown knob '{name}'")).into_response();
+    }
+    match registry.set(&name, request.value) {
+        Ok(previous) => {
+            let value = registry.get(&name).expect("knobs are never unregistered").value;
+            info!("Runtime config {} changed from {} to {} via admin API", name, previous, value);
+            Json(SetKnobResponse { name, previous, value }).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, format!("{e:#}")).into_response(),
+    }
+}
+
+/// `GET /admin/config`, `GET /admin/config/:name` and `PUT /admin/config/:name`, all requiring
+/// `Authorization: Bearer <token>`.
+pub(crate) fn admin_routes<S: Clone + Send + Sync + 'static>(token: String) -> Router<S> {
+    let token = Arc::new(token);
+    Router::new()
+        .route("/admin/config", get(list_knobs))
+        .route("/admin/config/:name", get(get_knob).put(set_knob))
+        .layer(middleware::from_fn_with_state(token.clone(), require_token))
+        .with_state(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn admin_routes_require_the_token() {
+        let knob = RuntimeConfigRegistry::global().register(
+            "admin_test_knob",
+            "Changed by the admin route test",
+            "UNSET_ADMIN_TEST_KNOB",
+            1,
+            10,
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/admin/config", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, admin_routes::<()>("secret".to_string())).await.unwrap()
+        });
+        let client = reqwest::Client::new();
+        let set = |token: &str, value: f64| {
+            client
+                .put(format!("{base}/admin_test_knob"))
+                .bearer_auth(token)
+                .json(&SetKnobRequest { value })
+        };
+
+        let status = |request: reqwest::RequestBuilder| async move {
+            request.send().await.unwrap().status()
+        };
+        assert_eq!(status(client.get(&base)).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(client.get(&base).bearer_auth("guess")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(set("guess", 5.0)).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(knob.get(), 1);
+
+        assert_eq!(status(client.get(&base).bearer_auth("secret")).await, StatusCode::OK);
+        assert_eq!(status(set("secret", 11.0)).await, StatusCode::BAD_REQUEST);
+        let response: SetKnobResponse =
+            set("secret", 5.0).send().await.unwrap().json().await.unwrap();
+        assert_eq!(response.previous, KnobValue::Integer(1));
+        assert_eq!(response.value, KnobValue::Integer(5));
+        assert_eq!(knob.get(), 5);
+    }
+}
@@ -0,0 +1,173 @@
+//! Authentication of the sensitive routes. The debug routes inject failpoints and profile the
+//! process, so they require `Authorization: Bearer <token>` once GRAVITY_DEBUG_TOKEN is set.
+//! Independently, a listener with a `client_ca_pem` only accepts TLS clients presenting a
+//! certificate signed by that CA. Client certificates are checked during the handshake, so they
+//! protect every route of the listener: serve the debug routes from their own listener to keep
+//! transaction submission open to everyone, see [`super::listener`].
+//!
+//! Release builds only serve the debug routes on listeners protected by either of the two.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+use subtle::ConstantTimeEq;
+
+/// Bearer token of the debug endpoints.
+/// Can be configured via GRAVITY_DEBUG_TOKEN environment variable
+pub(crate) fn debug_token() -> Option<String> {
+    static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+    TOKEN
+        .get_or_init(|| std::env::var("GRAVITY_DEBUG_TOKEN").ok().filter(|token| !token.is_empty()))
+        .clone()
+}
+
+/// Rejects requests without `Authorization: Bearer <token>`.
+pub(crate) async fn require_token(
+    State(token): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| bool::from(given.as_bytes().ct_eq(token.as_bytes())));
+    if !authorized {
+        return (StatusCode::UNAUTHORIZED, "missing or invalid bearer token").into_response();
+    }
+    next.run(request).await
+}
+
+fn read_pem(path: &Path) -> Result<Vec<rustls_pemfile::Item>, String> {
+    let file = File::open(path).map_err(|e| format!("failed to open {path:?}: {e}"))?;
+    let mut reader = BufReader::new(file);
+    let mut items = vec![];
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)
+        .map_err(|e| format!("failed to read {path:?}: {e}"))?
+    {
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs: Vec<_> = read_pem(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(CertificateDer::from(der)),
+            _ => None,
+        })
+        .collect();
+    if certs.is_empty() {
+        return Err(format!("no certificate in {path:?}"));
+    }
+    Ok(certs)
+}
+
+fn read_key(path: &Path) -> Result<PrivateKeyDer<'static>, String> {
+    read_pem(path)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(der) => Some(PrivateKeyDer::Pkcs8(der.into())),
+            rustls_pemfile::Item::RSAKey(der) => Some(PrivateKeyDer::Pkcs1(der.into())),
+            rustls_pemfile::Item::ECKey(der) => Some(PrivateKeyDer::Sec1(der.into())),
+            _ => None,
+        })
+        .ok_or_else(|| format!("no private key in {path:?}"))
+}
+
+/// TLS settings of a listener, requiring client certificates signed by `client_ca_pem` if set.
+pub(crate) async fn tls_config(
+    cert_pem: &Path,
+    key_pem: &Path,
+    client_ca_pem: Option<&Path>,
+) -> Result<RustlsConfig, String> {
+    let Some(client_ca_pem) = client_ca_pem else {
+        return RustlsConfig::from_pem_file(cert_pem, key_pem)
+            .await
+            .map_err(|e| format!("error {e:?}, cert {cert_pem:?}, key {key_pem:?} doesn't work"));
+    };
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(client_ca_pem)? {
+        roots.add(cert).map_err(|e| format!("invalid client CA in {client_ca_pem:?}: {e}"))?;
+    }
+    let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+        .build()
+        .map_err(|e| format!("invalid client CA in {client_ca_pem:?}: {e}"))?;
+    let mut config = ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(read_certs(cert_pem)?, read_key(key_pem)?)
+        .map_err(|e| format!("error {e:?}, cert {cert_pem:?}, key {key_pem:?} doesn't work"))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(RustlsConfig::from_config(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use rcgen::generate_simple_self_signed;
+    use tower::ServiceExt;
+
+    async fn status(authorization: Option<&str>) -> StatusCode {
+        let app = Router::new()
+            .route("/debug", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(Arc::new("secret".to_string()), require_token));
+        let mut request = Request::builder().uri("/debug");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn require_token_checks_the_bearer_token() {
+        assert_eq!(status(Some("Bearer secret")).await, StatusCode::OK);
+        for authorization in [None, Some("secret"), Some("Bearer secre"), Some("Bearer secrets")] {
+            assert_eq!(status(authorization).await, StatusCode::UNAUTHORIZED, "{authorization:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tls_config_reads_the_client_ca() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: String| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let server = generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert = write("cert.pem", server.serialize_pem().unwrap());
+        let key = write("key.pem", server.serialize_private_key_pem());
+        let ca = generate_simple_self_signed(vec!["operators".to_string()]).unwrap();
+        let client_ca = write("ca.pem", ca.serialize_pem().unwrap());
+
+        assert!(tls_config(&cert, &key, None).await.is_ok());
+        assert!(tls_config(&cert, &key, Some(&client_ca)).await.is_ok());
+
+        let err = tls_config(&cert, &key, Some(&key)).await.unwrap_err();
+        assert!(err.contains("no certificate"), "{err}");
+        let missing = dir.path().join("missing.pem");
+        let err = tls_config(&cert, &key, Some(&missing)).await.unwrap_err();
+        assert!(err.contains("failed to open"), "{err}");
+        let err = tls_config(&cert, &cert, Some(&client_ca)).await.unwrap_err();
+        assert!(err.contains("no private key"), "{err}");
+    }
+}
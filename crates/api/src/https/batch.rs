@@ -0,0 +1,138 @@
+//! Lookup of quorum store batches by digest, to investigate payloads whose batches fail
+//! verification or cannot be reconstructed.
+
+use crate::https::consensus::ErrorResponse;
+use aptos_consensus::quorum_store::{quorum_store_db::QuorumStoreDB, types::PersistedValue};
+use aptos_consensus_types::common::BatchPayload;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::get,
+    Router,
+};
+use gaptos::aptos_crypto::{hash::CryptoHash, HashValue};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTxnResponse {
+    pub sender: String,
+    pub sequence_number: u64,
+    pub expiration_timestamp_secs: u64,
+    pub hash: String, // hex encoded
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchResponse {
+    pub epoch: u64,
+    pub digest: String, // hex encoded
+    pub author: String,
+    pub batch_id: String,
+    pub expiration: u64,
+    pub num_txns: u64,
+    pub num_bytes: u64,
+    pub gas_bucket_start: u64,
+    /// Digest of the stored transactions, `None` if only the batch info is stored.
+    pub computed_digest: Option<String>,
+    pub txns: Vec<BatchTxnResponse>,
+}
+
+impl From<PersistedValue> for BatchResponse {
+    fn from(batch: PersistedValue) -> Self {
+        let txns = batch
+            .summary()
+            .into_iter()
+            .map(|txn| BatchTxnResponse {
+                sender: txn.sender.to_hex_literal(),
+                sequence_number: txn.sequence_number,
+                expiration_timestamp_secs: txn.expiration_timestamp_secs,
+                hash: hex::encode(txn.hash.as_ref()),
+            })
+            .collect();
+        let (info, payload) = batch.unpack();
+        let computed_digest = payload
+            .map(|payload| hex::encode(BatchPayload::new(info.author(), payload).hash().as_ref()));
+        Self {
+            epoch: info.epoch(),
+            digest: hex::encode(info.digest().as_ref()),
+            author: info.author().to_hex_literal(),
+            batch_id: info.batch_id().to_string(),
+            expiration: info.expiration(),
+            num_txns: info.num_txns(),
+            num_bytes: info.num_bytes(),
+            gas_bucket_start: info.gas_bucket_start(),
+            computed_digest,
+            txns,
+        }
+    }
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, JsonResponse(ErrorResponse { error })).into_response()
+}
+
+/// Looks the batches up on a blocking thread, a lookup without an epoch scans every stored
+/// batch.
+async fn get_batches(db: Arc<QuorumStoreDB>, digest: String, epoch: Option<u64>) -> Response {
+    let digest = match HashValue::from_hex(digest.strip_prefix("0x").unwrap_or(&digest)) {
+        Ok(digest) => digest,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("invalid digest: {e}")),
+    };
+    let lookup = tokio::task::spawn_blocking(move || db.get_batches_by_digest(&digest, epoch));
+    match lookup.await {
+        Ok(Ok(batches)) if batches.is_empty() => {
+            error(StatusCode::NOT_FOUND, format!("no stored batch with digest {digest}"))
+        }
+        Ok(Ok(batches)) => {
+            let batches: Vec<BatchResponse> = batches.into_iter().map(Into::into).collect();
+            JsonResponse(batches).into_response()
+        }
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, format!("batch lookup failed: {e}")),
+    }
+}
+
+// example:
+// curl http://127.0.0.1:1024/debug/batch/0x5f3a...
+async fn get_batch(State(db): State<Arc<QuorumStoreDB>>, Path(digest): Path<String>) -> Response {
+    get_batches(db, digest, None).await
+}
+
+// example:
+// curl http://127.0.0.1:1024/debug/batch/0x5f3a.../12
+async fn get_batch_in_epoch(
+    State(db): State<Arc<QuorumStoreDB>>,
+    Path((digest, epoch)): Path<(String, u64)>,
+) -> Response {
+    get_batches(db, digest, Some(epoch)).await
+}
+
+/// `GET /debug/batch/:digest` and `GET /debug/batch/:digest/:epoch`.
+pub(crate) fn batch_routes<S: Clone + Send + Sync + 'static>(
+    quorum_store_db: Arc<QuorumStoreDB>,
+) -> Router<S> {
+    Router::new()
+        .route("/debug/batch/:digest", get(get_batch))
+        .route("/debug/batch/:digest/:epoch", get(get_batch_in_epoch))
+        .with_state(quorum_store_db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lookups_run_against_the_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(QuorumStoreDB::new(dir.path()));
+        let digest = format!("0x{}", HashValue::random().to_hex());
+
+        let response = get_batch(State(db.clone()), Path(digest.clone())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get_batch_in_epoch(State(db.clone()), Path((digest, 1))).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get_batch(State(db), Path("0xzz".to_string())).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
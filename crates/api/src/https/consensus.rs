@@ -44,6 +44,27 @@ pub struct QCInfo {
     pub commit_info_block_id: String, // hex encoded - commit_info().id()
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DoubleSignEvidenceInfo {
+    pub epoch: u64,
+    pub round: u64,
+    pub author: String,          // hex encoded
+    pub first_block_id: String,  // hex encoded
+    pub second_block_id: String, // hex encoded
+    pub evidence: String,        // hex encoded BCS of both signed commit votes
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommitPayloadInfo {
+    pub block_number: u64,
+    pub payload: String, // hex encoded
+    /// Hex encoded BCS of the signed ledger info, if the block ended a commit.
+    pub ledger_info: Option<String>,
+}
+
+/// Block numbers a single commit payload request may span.
+const MAX_COMMIT_PAYLOAD_RANGE: u64 = 1000;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorResponse {
     pub error: String,
@@ -189,7 +210,7 @@ pub fn get_block(
     };
 
     // Get block by epoch and round
-    match get_block_by_round(consensus_db, epoch, round) {
+    match get_block_by_round(&consensus_db, epoch, round) {
         Some(block_info) => {
             info!("Successfully retrieved block for epoch={}, round={}", epoch, round);
             Ok((StatusCode::OK, JsonResponse(block_info)))
@@ -223,7 +244,7 @@ pub fn get_qc(
     };
 
     // Get QC by epoch and round
-    match get_qc_by_round(consensus_db, epoch, round) {
+    match get_qc_by_round(&consensus_db, epoch, round) {
         Some(qc_info) => {
             info!("Successfully retrieved QC for epoch={}, round={}", epoch, round);
             Ok((StatusCode::OK, JsonResponse(qc_info)))
@@ -259,6 +280,100 @@ pub fn get_validator_count_by_epoch(
         }
     };
 
+    let (target_block_number, validator_set) = validator_set_of_epoch(&consensus_db, epoch)?;
+    let validator_count = validator_set.active_validators.len();
+    info!("Epoch {} validator count: {}", epoch, validator_count);
+
+    let response =
+        ValidatorCountResponse { epoch, block_number: target_block_number, validator_count };
+
+    Ok((StatusCode::OK, JsonResponse(response)))
+}
+
+/// Get the recorded evidence of validators signing conflicting commit votes, optionally for
+/// one epoch only
+/// Example: GET /consensus/evidence or GET /consensus/evidence/5
+pub fn get_double_sign_evidence(
+    dkg_state: Arc<DkgState>,
+    epoch: Option<u64>,
+) -> Result<JsonResponse<Vec<DoubleSignEvidenceInfo>>, (StatusCode, JsonResponse<ErrorResponse>)> {
+    info!("Getting double sign evidence for epoch={:?}", epoch);
+
+    let consensus_db = dkg_state.consensus_db().ok_or_else(|| {
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "ConsensusDB is not initialized")
+    })?;
+    let evidence = consensus_db.get_double_sign_evidence(epoch).map_err(|e| {
+        error!("Failed to get double sign evidence: {:?}", e);
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+    })?;
+
+    let mut response = Vec::with_capacity(evidence.len());
+    for evidence in evidence {
+        let bytes = bcs::to_bytes(&evidence).map_err(|e| {
+            error!("Failed to serialize double sign evidence: {:?}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+        })?;
+        response.push(DoubleSignEvidenceInfo {
+            epoch: evidence.epoch(),
+            round: evidence.round(),
+            author: hex::encode(evidence.author().as_ref()),
+            first_block_id: hex::encode(evidence.first.commit_info().id().as_ref()),
+            second_block_id: hex::encode(evidence.second.commit_info().id().as_ref()),
+            evidence: hex::encode(bytes),
+        });
+    }
+    Ok(JsonResponse(response))
+}
+
+/// Get the payloads the execution layer attached to the committed blocks in `start..end`,
+/// with the ledger infos committing them. In epochs that sign commit payloads, the executed
+/// state id of a ledger info is the SHA3-256 of the block hash followed by the SHA3-256 of the
+/// payload
+/// Example: GET /consensus/commit_payloads/100/200
+pub fn get_commit_payloads(
+    dkg_state: Arc<DkgState>,
+    start: u64,
+    end: u64,
+) -> Result<JsonResponse<Vec<CommitPayloadInfo>>, (StatusCode, JsonResponse<ErrorResponse>)> {
+    info!("Getting commit payloads for blocks {}..{}", start, end);
+
+    if end <= start || end - start > MAX_COMMIT_PAYLOAD_RANGE {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Block range must be non-empty and span at most {MAX_COMMIT_PAYLOAD_RANGE}"),
+        ));
+    }
+    let consensus_db = dkg_state.consensus_db().ok_or_else(|| {
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "ConsensusDB is not initialized")
+    })?;
+    let internal_error = |e: &dyn std::fmt::Debug| {
+        error!("Failed to get commit payloads: {:?}", e);
+        error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+    };
+    let payloads = consensus_db.get_commit_payloads(start, end).map_err(|e| internal_error(&e))?;
+
+    let mut response = Vec::with_capacity(payloads.len());
+    for (block_number, payload) in payloads {
+        let ledger_info = consensus_db
+            .get::<LedgerInfoSchema>(&block_number)
+            .map_err(|e| internal_error(&e))?
+            .map(|ledger_info| bcs::to_bytes(&ledger_info))
+            .transpose()
+            .map_err(|e| internal_error(&e))?;
+        response.push(CommitPayloadInfo {
+            block_number,
+            payload: hex::encode(payload),
+            ledger_info: ledger_info.map(hex::encode),
+        });
+    }
+    Ok(JsonResponse(response))
+}
+
+/// The validator set of `epoch` and the block number it is read at, the first block of the epoch
+pub(crate) fn validator_set_of_epoch(
+    consensus_db: &ConsensusDB,
+    epoch: u64,
+) -> Result<(u64, ValidatorSet), (StatusCode, JsonResponse<ErrorResponse>)> {
     // Get block number for the target epoch
     let all_epoch_blocks = match consensus_db.get_all::<EpochByBlockNumberSchema>() {
         Ok(blocks) => blocks,
@@ -282,7 +397,7 @@ pub fn get_validator_count_by_epoch(
         })?;
 
     // Get validator set from config storage using block_number
-    let validator_count = match GLOBAL_CONFIG_STORAGE.get() {
+    let validator_set = match GLOBAL_CONFIG_STORAGE.get() {
         Some(config_storage) => {
             match config_storage
                 .fetch_config_bytes(OnChainConfig::ValidatorSet, target_block_number.into())
@@ -291,11 +406,7 @@ pub fn get_validator_count_by_epoch(
                     Ok(bytes) => {
                         let bytes: Bytes = bytes;
                         match ValidatorSet::deserialize_into_config(bytes.as_ref()) {
-                            Ok(validator_set) => {
-                                let count = validator_set.active_validators.len();
-                                info!("Epoch {} validator count: {}", epoch, count);
-                                count
-                            }
+                            Ok(validator_set) => validator_set,
                             Err(e) => {
                                 error!("Failed to deserialize ValidatorSet: {:?}", e);
                                 return Err(error_response(
@@ -331,10 +442,7 @@ pub fn get_validator_count_by_epoch(
         }
     };
 
-    let response =
-        ValidatorCountResponse { epoch, block_number: target_block_number, validator_count };
-
-    Ok((StatusCode::OK, JsonResponse(response)))
+    Ok((target_block_number, validator_set))
 }
 
 /// Helper function to get block by epoch and round
@@ -0,0 +1,135 @@
+//! Snapshot of where the node's consensus stands, for operators: the epoch and round it is in,
+//! the validators of the epoch, the last committed block and how many blocks wait in the block
+//! buffer. Read from the primary consensus DB, as a query replica may lag behind. Also serves
+//! the scores of the consensus peers.
+
+use crate::https::consensus::{validator_set_of_epoch, ErrorResponse, LedgerInfoResponse};
+use aptos_consensus::{
+    consensusdb::ConsensusDB,
+    peer_score::{peer_scores, PeerScoreSnapshot},
+};
+use axum::{
+    extract::State, http::StatusCode, response::Json as JsonResponse, routing::get, Router,
+};
+use block_buffer_manager::BlockBufferManager;
+use gaptos::{
+    aptos_consensus::counters::{CURRENT_ROUND, EPOCH},
+    aptos_logger::warn,
+    aptos_storage_interface::DbReader,
+    aptos_types::validator_verifier::ValidatorVerifier,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub(crate) struct ConsensusStateSource {
+    pub consensus_db: Option<Arc<ConsensusDB>>,
+    pub block_buffer_manager: Option<Arc<BlockBufferManager>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ValidatorResponse {
+    pub address: String,
+    pub voting_power: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PipelineResponse {
+    pub ordered: u64,
+    pub executed: u64,
+    pub committed: u64,
+    pub persisted: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConsensusStateResponse {
+    pub epoch: u64,
+    /// Round the local consensus is in, 0 before the first round of the epoch started.
+    pub round: u64,
+    /// Validators of `epoch` in validator order, `None` if the set could not be read.
+    pub validators: Option<Vec<ValidatorResponse>>,
+    pub total_voting_power: Option<u128>,
+    pub last_committed: LedgerInfoResponse,
+    /// `None` until the block buffer is initialized.
+    pub pipeline: Option<PipelineResponse>,
+    /// Ordered blocks not committed yet.
+    pub pending_blocks: Option<u64>,
+}
+
+fn validators(consensus_db: &ConsensusDB, epoch: u64) -> Option<(Vec<ValidatorResponse>, u128)> {
+    let (_, validator_set) = validator_set_of_epoch(consensus_db, epoch)
+        .map_err(|(_, JsonResponse(e))| {
+            warn!("no validator set for epoch {}: {}", epoch, e.error);
+        })
+        .ok()?;
+    let verifier: ValidatorVerifier = (&validator_set).into();
+    let validators = verifier
+        .get_ordered_account_addresses_iter()
+        .map(|address| ValidatorResponse {
+            address: address.to_hex_literal(),
+            voting_power: verifier.get_voting_power(&address).unwrap_or(0),
+        })
+        .collect();
+    Some((validators, verifier.total_voting_power()))
+}
+
+// example:
+// curl http://127.0.0.1:1024/consensus/state
+async fn get_consensus_state(
+    State(source): State<ConsensusStateSource>,
+) -> Result<JsonResponse<ConsensusStateResponse>, (StatusCode, JsonResponse<ErrorResponse>)> {
+    let internal_error =
+        |error: String| (StatusCode::INTERNAL_SERVER_ERROR, JsonResponse(ErrorResponse { error }));
+    let consensus_db = source
+        .consensus_db
+        .as_ref()
+        .ok_or_else(|| internal_error("ConsensusDB is not initialized".to_string()))?;
+    let ledger_info = DbReader::get_latest_ledger_info(consensus_db.as_ref())
+        .map_err(|e| internal_error(format!("failed to read the latest ledger info: {e}")))?;
+    let ledger_info = ledger_info.ledger_info();
+    // The gauge is 0 until consensus started an epoch, the committed ledger info tells which
+    // epoch it is going to start then
+    let epoch = (EPOCH.get().max(0) as u64).max(ledger_info.next_block_epoch());
+    let validators = validators(consensus_db, epoch);
+    let progress = match &source.block_buffer_manager {
+        Some(block_buffer_manager) => block_buffer_manager.watermarks().await,
+        None => None,
+    };
+    Ok(JsonResponse(ConsensusStateResponse {
+        epoch,
+        round: CURRENT_ROUND.get().max(0) as u64,
+        total_voting_power: validators.as_ref().map(|(_, total)| *total),
+        validators: validators.map(|(validators, _)| validators),
+        last_committed: LedgerInfoResponse {
+            epoch: ledger_info.epoch(),
+            round: ledger_info.round(),
+            block_number: ledger_info.block_number(),
+            block_hash: hex::encode(ledger_info.block_hash().as_ref()),
+        },
+        pipeline: progress.map(|progress| PipelineResponse {
+            ordered: progress.ordered,
+            executed: progress.executed,
+            committed: progress.committed,
+            persisted: progress.persisted,
+        }),
+        pending_blocks: progress
+            .map(|progress| progress.ordered.saturating_sub(progress.committed)),
+    }))
+}
+
+// example:
+// curl http://127.0.0.1:1024/consensus/peer_scores
+async fn get_peer_scores() -> JsonResponse<Vec<PeerScoreSnapshot>> {
+    JsonResponse(peer_scores().snapshot())
+}
+
+/// `GET /consensus/state` and `GET /consensus/peer_scores`, the peers that misbehaved with the
+/// highest score first.
+pub(crate) fn consensus_state_routes<S: Clone + Send + Sync + 'static>(
+    source: ConsensusStateSource,
+) -> Router<S> {
+    Router::new()
+        .route("/consensus/state", get(get_consensus_state))
+        .route("/consensus/peer_scores", get(get_peer_scores))
+        .with_state(source)
+}
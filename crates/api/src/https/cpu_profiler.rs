@@ -0,0 +1,80 @@
+//! On-demand CPU profiling. `GET /debug/pprof/cpu?seconds=30` samples every thread for the
+//! requested time and answers with the profile in pprof protobuf format, readable by
+//! `go tool pprof` or `pprof`. Nothing is sampled between requests.
+
+use crate::https::consensus::ErrorResponse;
+use axum::{
+    extract::Query,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json as JsonResponse, Response},
+};
+use gaptos::aptos_logger::info;
+use pprof::{protos::Message, ProfilerGuard};
+use serde::Deserialize;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+/// Sampling frequency in Hz, off by one from 100 so samples don't align with periodic work.
+const SAMPLING_FREQUENCY: i32 = 99;
+const DEFAULT_PROFILE_SECS: u64 = 30;
+const MAX_PROFILE_SECS: u64 = 300;
+
+/// The profiler samples the whole process, so only one profile runs at a time.
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Deserialize, Debug)]
+pub struct CpuProfileRequest {
+    seconds: Option<u64>,
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, JsonResponse(ErrorResponse { error })).into_response()
+}
+
+fn profile_cpu(duration: Duration) -> Result<Vec<u8>, String> {
+    let guard = ProfilerGuard::new(SAMPLING_FREQUENCY)
+        .map_err(|e| format!("failed to start the profiler: {e}"))?;
+    std::thread::sleep(duration);
+    let report = guard.report().build().map_err(|e| format!("failed to build the profile: {e}"))?;
+    let profile = report.pprof().map_err(|e| format!("failed to encode the profile: {e}"))?;
+    let mut content = Vec::new();
+    profile.write_to_vec(&mut content).map_err(|e| format!("failed to encode the profile: {e}"))?;
+    Ok(content)
+}
+
+// example:
+// curl -o cpu.pb 'http://127.0.0.1:1024/debug/pprof/cpu?seconds=30'
+pub async fn get_cpu_profile(Query(request): Query<CpuProfileRequest>) -> Response {
+    let seconds = request.seconds.unwrap_or(DEFAULT_PROFILE_SECS);
+    if seconds == 0 || seconds > MAX_PROFILE_SECS {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("seconds must be between 1 and {MAX_PROFILE_SECS}"),
+        );
+    }
+    if PROFILING.swap(true, Ordering::AcqRel) {
+        return error(StatusCode::CONFLICT, "a CPU profile is already running".to_string());
+    }
+    info!("cpu profiling for {}s", seconds);
+    // The flag is cleared by the task, which runs to the end even if the client goes away
+    let result = tokio::task::spawn_blocking(move || {
+        let result = profile_cpu(Duration::from_secs(seconds));
+        PROFILING.store(false, Ordering::Release);
+        result
+    })
+    .await;
+    match result {
+        Ok(Ok(content)) => (
+            [
+                (header::CONTENT_TYPE, "application/octet-stream"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"cpu.pb\""),
+            ],
+            content,
+        )
+            .into_response(),
+        Ok(Err(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, format!("profiling task failed: {e}")),
+    }
+}
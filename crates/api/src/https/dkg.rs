@@ -1,4 +1,10 @@
-use aptos_consensus::consensusdb::ConsensusDB;
+use aptos_consensus::{
+    consensusdb::ConsensusDB,
+    counters::{
+        RAND_LAST_DECIDED_TIMESTAMP, RAND_LAST_RECONSTRUCTION_FAILURE_EPOCH,
+        RAND_RECONSTRUCTION_FAILURES, RAND_ROUNDS_COMPLETED, RAND_SHARES_RECEIVED,
+    },
+};
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Json as JsonResponse},
@@ -13,17 +19,34 @@ use gaptos::{
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use super::query_replica::QueryReplica;
+
 pub struct DkgState {
     consensus_db: Option<Arc<ConsensusDB>>,
+    query_replica: Option<Arc<QueryReplica>>,
 }
 
 impl DkgState {
     pub fn new(consensus_db: Option<Arc<ConsensusDB>>) -> Self {
-        Self { consensus_db }
+        Self { consensus_db, query_replica: None }
+    }
+
+    pub fn with_query_replica(mut self, query_replica: Arc<QueryReplica>) -> Self {
+        self.query_replica = Some(query_replica);
+        self
+    }
+
+    /// The DB queries should read from: the replica once it has a snapshot, else the primary.
+    pub fn consensus_db(&self) -> Option<Arc<ConsensusDB>> {
+        self.query_replica
+            .as_ref()
+            .and_then(|replica| replica.snapshot())
+            .map(|snapshot| snapshot.db.clone())
+            .or_else(|| self.consensus_db.clone())
     }
 
-    pub fn consensus_db(&self) -> Option<&Arc<ConsensusDB>> {
-        self.consensus_db.as_ref()
+    pub fn query_replica(&self) -> Option<&Arc<QueryReplica>> {
+        self.query_replica.as_ref()
     }
 }
 
@@ -58,6 +81,35 @@ pub struct DKGStatusResponse {
     pub round: u64,
     pub block_number: u64,
     pub participating_nodes: usize,
+    pub randomness: RandomnessHealth,
+}
+
+/// Randomness generation counters of this node since it started.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RandomnessHealth {
+    pub rounds_completed: u64,
+    pub shares_received: u64,
+    pub reconstruction_failures: u64,
+    /// Unix time in seconds, 0 if no randomness was decided yet.
+    pub last_decided_timestamp_secs: u64,
+    /// Whether a failed aggregation stalls randomness until the next epoch.
+    pub stalled: bool,
+}
+
+impl RandomnessHealth {
+    fn current(epoch: u64) -> Self {
+        let shares_received = ["fast", "slow"]
+            .iter()
+            .map(|path| RAND_SHARES_RECEIVED.with_label_values(&[path]).get())
+            .sum();
+        Self {
+            rounds_completed: RAND_ROUNDS_COMPLETED.get(),
+            shares_received,
+            reconstruction_failures: RAND_RECONSTRUCTION_FAILURES.get(),
+            last_decided_timestamp_secs: RAND_LAST_DECIDED_TIMESTAMP.get().max(0) as u64,
+            stalled: RAND_LAST_RECONSTRUCTION_FAILURE_EPOCH.get() == epoch as i64,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -78,7 +130,7 @@ impl DkgState {
         info!("Getting DKG status");
 
         // Get ConsensusDB
-        let consensus_db = match self.consensus_db.as_ref() {
+        let consensus_db = match self.consensus_db() {
             Some(db) => db,
             None => {
                 error!("ConsensusDB is not initialized");
@@ -188,7 +240,13 @@ impl DkgState {
                 .into_response();
         };
 
-        let response = DKGStatusResponse { epoch, round, block_number: block, participating_nodes };
+        let response = DKGStatusResponse {
+            epoch,
+            round,
+            block_number: block,
+            participating_nodes,
+            randomness: RandomnessHealth::current(epoch),
+        };
 
         info!(
             "Successfully retrieved DKG status: epoch={}, round={}, block={}, nodes={}",
@@ -197,13 +255,34 @@ impl DkgState {
         JsonResponse(response).into_response()
     }
 
+    /// Randomness health, answering 503 while randomness is stalled so probes can alert on it
+    /// Example: curl https://127.0.0.1:1024/dkg/health
+    pub fn get_dkg_health(&self) -> impl IntoResponse {
+        let epoch = self
+            .consensus_db()
+            .and_then(|db| DbReader::get_latest_ledger_info(db.as_ref()).ok())
+            .map(|ledger_info| ledger_info.ledger_info().epoch());
+        let Some(epoch) = epoch else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                JsonResponse(ErrorResponse {
+                    error: "Latest ledger info is unavailable".to_string(),
+                }),
+            )
+                .into_response();
+        };
+        let health = RandomnessHealth::current(epoch);
+        let status = if health.stalled { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+        (status, JsonResponse(health)).into_response()
+    }
+
     /// Get randomness for a specific block number
     /// Example: curl "https://127.0.0.1:1024/dkg/randomness/100"
     pub fn get_randomness(&self, block_number: u64) -> impl IntoResponse {
         info!("Getting randomness for block {}", block_number);
 
         // Get ConsensusDB
-        let consensus_db = match self.consensus_db.as_ref() {
+        let consensus_db = match self.consensus_db() {
             Some(db) => db,
             None => {
                 error!("ConsensusDB is not initialized");
@@ -0,0 +1,258 @@
+//! Liveness and readiness probes for load balancers and the sentinel. `/health/live` answers as
+//! long as the server runs, `/health/ready` answers 503 when any stage of the node is off, with
+//! the state of every stage either way.
+
+use aptos_consensus::{
+    consensusdb::ConsensusDB,
+    counters::{ROUNDS_BEHIND, ROUNDS_BEHIND_UPDATED_AT},
+};
+use aptos_mempool::core_mempool::MempoolInspector;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::get,
+    Router,
+};
+use block_buffer_manager::BlockBufferManager;
+use gaptos::aptos_storage_interface::DbReader;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HealthThresholds {
+    pub max_rounds_behind: u64,
+    /// Seconds since the rounds behind were last measured, after which they are stale.
+    pub max_rounds_behind_age_secs: u64,
+    /// Ordered blocks not executed yet.
+    pub max_execution_lag: u64,
+    pub max_pending_txns: usize,
+}
+
+impl HealthThresholds {
+    /// `updated_at` is the unix time the rounds behind were measured, 0 if they never were.
+    fn sync_check(&self, rounds_behind: u64, updated_at: u64, now: u64) -> SyncCheck {
+        let age_secs = (updated_at > 0).then(|| now.saturating_sub(updated_at));
+        SyncCheck {
+            ok: rounds_behind <= self.max_rounds_behind &&
+                age_secs.is_none_or(|age| age <= self.max_rounds_behind_age_secs),
+            rounds_behind,
+            max_rounds_behind: self.max_rounds_behind,
+            age_secs,
+            max_age_secs: self.max_rounds_behind_age_secs,
+        }
+    }
+
+    /// Configured via HEALTH_MAX_ROUNDS_BEHIND, HEALTH_MAX_ROUNDS_BEHIND_AGE_SECS,
+    /// HEALTH_MAX_EXECUTION_LAG_BLOCKS and HEALTH_MAX_PENDING_TXNS. The pending default is the
+    /// size of reth's pending sub-pool.
+    pub fn from_env() -> Self {
+        Self {
+            max_rounds_behind: env_u64("HEALTH_MAX_ROUNDS_BEHIND", 10),
+            max_rounds_behind_age_secs: env_u64("HEALTH_MAX_ROUNDS_BEHIND_AGE_SECS", 60),
+            max_execution_lag: env_u64("HEALTH_MAX_EXECUTION_LAG_BLOCKS", 32),
+            max_pending_txns: env_u64("HEALTH_MAX_PENDING_TXNS", 10_000) as usize,
+        }
+    }
+}
+
+/// What the readiness probe checks. Stages the server has no handle on are left out.
+#[derive(Clone)]
+pub(crate) struct HealthState {
+    pub consensus_db: Option<Arc<ConsensusDB>>,
+    pub block_buffer_manager: Option<Arc<BlockBufferManager>>,
+    pub mempool_inspector: Option<MempoolInspector>,
+    pub thresholds: HealthThresholds,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LivenessResponse {
+    pub live: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SyncCheck {
+    pub ok: bool,
+    pub rounds_behind: u64,
+    pub max_rounds_behind: u64,
+    /// Seconds since `rounds_behind` was measured, `None` on nodes that do not run consensus.
+    pub age_secs: Option<u64>,
+    pub max_age_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExecutionCheck {
+    /// False until the block buffer is initialized.
+    pub ok: bool,
+    pub ordered: Option<u64>,
+    pub executed: Option<u64>,
+    pub committed: Option<u64>,
+    pub persisted: Option<u64>,
+    /// How long the oldest block waiting for its execution result has been ordered.
+    pub oldest_unexecuted_ms: Option<u64>,
+    /// How long the oldest executed block waiting for its commit has been executed.
+    pub oldest_uncommitted_ms: Option<u64>,
+    pub max_execution_lag: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MempoolCheck {
+    pub ok: bool,
+    /// `None` if the execution layer pool does not report its size.
+    pub pending: Option<usize>,
+    pub parked: Option<usize>,
+    pub max_pending_txns: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConsensusDbCheck {
+    pub ok: bool,
+    pub epoch: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub sync: SyncCheck,
+    pub execution: Option<ExecutionCheck>,
+    pub mempool: Option<MempoolCheck>,
+    pub consensus_db: Option<ConsensusDbCheck>,
+}
+
+impl HealthState {
+    fn sync_check(&self) -> SyncCheck {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let updated_at = ROUNDS_BEHIND_UPDATED_AT.get().max(0) as u64;
+        self.thresholds.sync_check(ROUNDS_BEHIND.get().max(0) as u64, updated_at, now)
+    }
+
+    async fn execution_check(&self) -> Option<ExecutionCheck> {
+        let max_execution_lag = self.thresholds.max_execution_lag;
+        let watermarks = self.block_buffer_manager.as_ref()?.watermarks().await;
+        let millis = |age: Option<Duration>| age.map(|age| age.as_millis() as u64);
+        Some(ExecutionCheck {
+            ok: watermarks.is_some_and(|watermarks| {
+                watermarks.ordered.saturating_sub(watermarks.executed) <= max_execution_lag
+            }),
+            ordered: watermarks.map(|watermarks| watermarks.ordered),
+            executed: watermarks.map(|watermarks| watermarks.executed),
+            committed: watermarks.map(|watermarks| watermarks.committed),
+            persisted: watermarks.map(|watermarks| watermarks.persisted),
+            oldest_unexecuted_ms: watermarks
+                .and_then(|watermarks| millis(watermarks.oldest_unexecuted_age)),
+            oldest_uncommitted_ms: watermarks
+                .and_then(|watermarks| millis(watermarks.oldest_uncommitted_age)),
+            max_execution_lag,
+        })
+    }
+
+    fn mempool_check(&self) -> Option<MempoolCheck> {
+        let pool = self.mempool_inspector.as_ref()?.pool_size();
+        let max_pending_txns = self.thresholds.max_pending_txns;
+        Some(MempoolCheck {
+            ok: pool.is_none_or(|pool| pool.pending < max_pending_txns),
+            pending: pool.map(|pool| pool.pending),
+            parked: pool.map(|pool| pool.parked),
+            max_pending_txns,
+        })
+    }
+
+    fn consensus_db_check(&self) -> Option<ConsensusDbCheck> {
+        let db = self.consensus_db.as_ref()?;
+        Some(match DbReader::get_latest_ledger_info(db.as_ref()) {
+            Ok(ledger_info) => ConsensusDbCheck {
+                ok: true,
+                epoch: Some(ledger_info.ledger_info().epoch()),
+                error: None,
+            },
+            Err(e) => ConsensusDbCheck { ok: false, epoch: None, error: Some(e.to_string()) },
+        })
+    }
+}
+
+// example:
+// curl http://127.0.0.1:1024/health/live
+async fn get_live() -> JsonResponse<LivenessResponse> {
+    JsonResponse(LivenessResponse { live: true })
+}
+
+// example:
+// curl http://127.0.0.1:1024/health/ready
+async fn get_ready(State(state): State<HealthState>) -> Response {
+    let sync = state.sync_check();
+    let execution = state.execution_check().await;
+    let mempool = state.mempool_check();
+    let consensus_db = state.consensus_db_check();
+    let ready = sync.ok &&
+        execution.as_ref().is_none_or(|check| check.ok) &&
+        mempool.as_ref().is_none_or(|check| check.ok) &&
+        consensus_db.as_ref().is_none_or(|check| check.ok);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let response = ReadinessResponse { ready, sync, execution, mempool, consensus_db };
+    (status, JsonResponse(response)).into_response()
+}
+
+/// `GET /health/live` and `GET /health/ready`.
+pub(crate) fn health_routes<S: Clone + Send + Sync + 'static>(state: HealthState) -> Router<S> {
+    Router::new()
+        .route("/health/live", get(get_live))
+        .route("/health/ready", get(get_ready))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use block_buffer_manager::block_buffer_manager::BlockBufferManagerConfig;
+
+    fn thresholds() -> HealthThresholds {
+        HealthThresholds {
+            max_rounds_behind: 10,
+            max_rounds_behind_age_secs: 60,
+            max_execution_lag: 32,
+            max_pending_txns: 100,
+        }
+    }
+
+    fn state(block_buffer_manager: Option<Arc<BlockBufferManager>>) -> HealthState {
+        HealthState {
+            consensus_db: None,
+            block_buffer_manager,
+            mempool_inspector: None,
+            thresholds: thresholds(),
+        }
+    }
+
+    #[test]
+    fn stale_rounds_behind_are_not_in_sync() {
+        let thresholds = thresholds();
+        assert!(thresholds.sync_check(0, 0, 1_000).ok);
+        assert!(thresholds.sync_check(10, 990, 1_000).ok);
+        assert!(!thresholds.sync_check(11, 990, 1_000).ok);
+
+        let stale = thresholds.sync_check(0, 900, 1_000);
+        assert!(!stale.ok);
+        assert_eq!(stale.age_secs, Some(100));
+    }
+
+    #[tokio::test]
+    async fn ready_without_handles() {
+        let response = get_ready(State(state(None))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn not_ready_before_the_block_buffer_is_initialized() {
+        let block_buffer_manager = BlockBufferManager::new(BlockBufferManagerConfig::default());
+        let response = get_ready(State(state(Some(block_buffer_manager)))).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,162 @@
+//! Additional listeners of the https server, each serving a subset of the routes with its own
+//! TLS settings. They are read from the YAML file named by GRAVITY_HTTPS_LISTENERS, e.g.
+//!
+//! ```yaml
+//! - address: 0.0.0.0:443
+//!   cert_pem: /etc/gravity/cert.pem
+//!   key_pem: /etc/gravity/key.pem
+//!   routes: [tx, consensus, dkg]
+//! - address: 0.0.0.0:8081
+//!   cert_pem: /etc/gravity/cert.pem
+//!   key_pem: /etc/gravity/key.pem
+//!   client_ca_pem: /etc/gravity/operators-ca.pem
+//!   routes: [debug]
+//! ```
+//!
+//! The listener of `https_server_address` in the node config serves the route groups listed in
+//! GRAVITY_HTTPS_ROUTES, e.g. `tx,health`, and requires client certificates when
+//! GRAVITY_HTTPS_CLIENT_CA_PEM is set, see [`super::auth`].
+//!
+//! A listener without a list of routes serves [`RouteGroup::defaults`]: every group, or all but
+//! `consensus`, `dkg` and `mempool` when GRAVITY_HTTPS_HIDE_INTERNAL_ROUTES is set. Those expose
+//! the internals of a validator and are then only served when listed.
+
+use serde::Deserialize;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteGroup {
+    /// `/tx/submit_tx` and `/tx/get_tx_by_hash`, only served with TLS.
+    Tx,
+    /// `/admin/config`, only served with TLS and GRAVITY_ADMIN_TOKEN.
+    Admin,
+    /// `/consensus/*`
+    Consensus,
+    /// `/dkg/*`, including the randomness health probe `/dkg/health`.
+    Dkg,
+    /// `/mempool/*`, inspection of the consensus-side mempool.
+    Mempool,
+    /// `/health/live` and `/health/ready`, for load balancers and the sentinel.
+    Health,
+    /// Transaction journeys, onchain config reads, quorum store batches, failpoints and CPU and
+    /// heap profiling. Requires GRAVITY_DEBUG_TOKEN when set, and in release builds is only
+    /// served with the token or with client certificates.
+    Debug,
+}
+
+impl RouteGroup {
+    pub const ALL: [RouteGroup; 7] = [
+        RouteGroup::Tx,
+        RouteGroup::Admin,
+        RouteGroup::Consensus,
+        RouteGroup::Dkg,
+        RouteGroup::Mempool,
+        RouteGroup::Health,
+        RouteGroup::Debug,
+    ];
+
+    /// Groups served by a listener that does not list its routes.
+    pub fn defaults() -> Vec<RouteGroup> {
+        Self::defaults_with(hide_internal_routes_from_env())
+    }
+
+    fn defaults_with(hide_internal: bool) -> Vec<RouteGroup> {
+        Self::ALL.into_iter().filter(|group| !(hide_internal && group.is_internal())).collect()
+    }
+
+    /// Whether the group exposes the internals of a validator, so it is left out of the
+    /// defaults when GRAVITY_HTTPS_HIDE_INTERNAL_ROUTES is set.
+    pub fn is_internal(self) -> bool {
+        matches!(self, RouteGroup::Consensus | RouteGroup::Dkg | RouteGroup::Mempool)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HttpsListener {
+    pub address: String,
+    #[serde(default)]
+    pub cert_pem: Option<PathBuf>,
+    #[serde(default)]
+    pub key_pem: Option<PathBuf>,
+    /// CA the TLS clients must present a certificate of. Ignored without TLS.
+    #[serde(default)]
+    pub client_ca_pem: Option<PathBuf>,
+    #[serde(default = "RouteGroup::defaults")]
+    pub routes: Vec<RouteGroup>,
+}
+
+impl HttpsListener {
+    pub fn has_tls(&self) -> bool {
+        self.cert_pem.is_some() && self.key_pem.is_some()
+    }
+
+    pub fn has_client_auth(&self) -> bool {
+        self.has_tls() && self.client_ca_pem.is_some()
+    }
+}
+
+/// CA of the client certificates required by the listener of `https_server_address`.
+/// Can be configured via GRAVITY_HTTPS_CLIENT_CA_PEM environment variable
+pub fn client_ca_pem_from_env() -> Option<PathBuf> {
+    std::env::var_os("GRAVITY_HTTPS_CLIENT_CA_PEM").filter(|path| !path.is_empty()).map(Into::into)
+}
+
+/// Whether listeners that do not list their routes leave out the internal route groups.
+/// Can be configured via GRAVITY_HTTPS_HIDE_INTERNAL_ROUTES environment variable
+pub fn hide_internal_routes_from_env() -> bool {
+    std::env::var("GRAVITY_HTTPS_HIDE_INTERNAL_ROUTES")
+        .map(|value| matches!(value.trim(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// Route groups of the listener of `https_server_address`.
+/// Can be configured via GRAVITY_HTTPS_ROUTES environment variable
+pub fn primary_routes_from_env() -> Result<Vec<RouteGroup>, String> {
+    match std::env::var("GRAVITY_HTTPS_ROUTES") {
+        Ok(routes) => parse_route_groups(&routes),
+        Err(_) => Ok(RouteGroup::defaults()),
+    }
+}
+
+pub(crate) fn parse_route_groups(routes: &str) -> Result<Vec<RouteGroup>, String> {
+    routes
+        .split(',')
+        .map(str::trim)
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            serde_yaml::from_str(group).map_err(|_| format!("unknown https route group {group:?}"))
+        })
+        .collect()
+}
+
+/// Additional listeners configured via GRAVITY_HTTPS_LISTENERS environment variable
+pub fn https_listeners_from_env() -> Result<Vec<HttpsListener>, String> {
+    let Some(path) = std::env::var_os("GRAVITY_HTTPS_LISTENERS") else {
+        return Ok(vec![]);
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read https listeners {path:?}: {e}"))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| format!("failed to parse https listeners {path:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_groups_are_only_left_out_when_hidden() {
+        assert_eq!(RouteGroup::defaults_with(false), RouteGroup::ALL);
+        let hidden = RouteGroup::defaults_with(true);
+        for group in RouteGroup::ALL {
+            assert_eq!(hidden.contains(&group), !group.is_internal(), "{group:?}");
+        }
+        let listener: HttpsListener = serde_yaml::from_str("address: 0.0.0.0:8080").unwrap();
+        assert_eq!(listener.routes, RouteGroup::defaults());
+        assert_eq!(
+            parse_route_groups("mempool, dkg").unwrap(),
+            [RouteGroup::Mempool, RouteGroup::Dkg]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Inspection endpoints of the consensus-side mempool, to debug transactions that are pooled
+//! but never make it into a block.
+
+use crate::https::consensus::ErrorResponse;
+use alloy_primitives::Address;
+use aptos_mempool::core_mempool::{AccountMempoolState, MempoolInspector, MempoolStats};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::get,
+    Router,
+};
+use gaptos::api_types::account::ExternalAccountAddress;
+use greth_compat::ExternalAccountAddressExt;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SenderBucketResponse {
+    pub bucket: u8,
+    pub snapshot_txns: usize,
+    pub dispatched: usize,
+    pub awaiting_primary: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MempoolStatsResponse {
+    /// `None` if the execution layer pool does not report its size.
+    pub pending: Option<usize>,
+    pub parked: Option<usize>,
+    pub oldest_txn_age_ms: Option<u64>,
+    pub buckets: Vec<SenderBucketResponse>,
+    pub snapshot_age_ms: Option<u64>,
+    pub seen_from_clients: usize,
+    pub seen_from_peers: usize,
+    pub seen_from_upstream: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountTxnResponse {
+    pub hash: String, // hex encoded
+    pub nonce: u64,
+    pub parked: bool,
+    pub age_ms: u64,
+    pub dispatched: bool,
+    pub rebroadcast: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountResponse {
+    pub address: String, // hex encoded
+    pub bucket: u8,
+    pub txns: Vec<AccountTxnResponse>,
+    pub committed_nonce: Option<u64>,
+    pub nonce_gap: Option<u64>,
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+impl From<MempoolStats> for MempoolStatsResponse {
+    fn from(stats: MempoolStats) -> Self {
+        Self {
+            pending: stats.pool.map(|pool| pool.pending),
+            parked: stats.pool.map(|pool| pool.parked),
+            oldest_txn_age_ms: stats.pool.and_then(|pool| pool.oldest_txn_age).map(millis),
+            buckets: stats
+                .buckets
+                .into_iter()
+                .map(|bucket| SenderBucketResponse {
+                    bucket: bucket.bucket,
+                    snapshot_txns: bucket.snapshot_txns,
+                    dispatched: bucket.dispatched,
+                    awaiting_primary: bucket.awaiting_primary,
+                })
+                .collect(),
+            snapshot_age_ms: stats.snapshot_age.map(millis),
+            seen_from_clients: stats.seen_from_clients,
+            seen_from_peers: stats.seen_from_peers,
+            seen_from_upstream: stats.seen_from_upstream,
+        }
+    }
+}
+
+fn account_response(address: String, state: AccountMempoolState) -> AccountResponse {
+    AccountResponse {
+        address,
+        bucket: state.bucket,
+        txns: state
+            .txns
+            .into_iter()
+            .map(|state| AccountTxnResponse {
+                hash: hex::encode(state.txn.hash.as_bytes()),
+                nonce: state.txn.nonce,
+                parked: state.txn.parked,
+                age_ms: millis(state.txn.age),
+                dispatched: state.dispatched,
+                rebroadcast: state.rebroadcast,
+            })
+            .collect(),
+        committed_nonce: state.committed_nonce,
+        nonce_gap: state.nonce_gap,
+    }
+}
+
+/// Accepts a 20 byte EVM address or a 32 byte account address, with or without `0x`.
+fn parse_address(addr: &str) -> Result<ExternalAccountAddress, String> {
+    let bytes = hex::decode(addr.strip_prefix("0x").unwrap_or(addr))
+        .map_err(|e| format!("invalid address '{addr}': {e}"))?;
+    match bytes.len() {
+        20 => Ok(ExternalAccountAddress::from_evm(Address::from_slice(&bytes))),
+        32 => Ok(ExternalAccountAddress::new(bytes.try_into().expect("checked length"))),
+        len => Err(format!("address '{addr}' has {len} bytes, expected 20 or 32")),
+    }
+}
+
+fn error(status: StatusCode, error: String) -> Response {
+    (status, JsonResponse(ErrorResponse { error })).into_response()
+}
+
+// example:
+// curl http://127.0.0.1:1024/mempool/stats
+async fn get_stats(
+    State(inspector): State<MempoolInspector>,
+) -> JsonResponse<MempoolStatsResponse> {
+    JsonResponse(inspector.stats().into())
+}
+
+// example:
+// curl http://127.0.0.1:1024/mempool/account/0x6d2e03b7effeae98bd302a9f836d0d6ab0002766
+async fn get_account(
+    State(inspector): State<MempoolInspector>,
+    Path(addr): Path<String>,
+) -> Response {
+    let sender = match parse_address(&addr) {
+        Ok(sender) => sender,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    match inspector.account(&sender) {
+        Some(state) => JsonResponse(account_response(addr, state)).into_response(),
+        None => error(
+            StatusCode::NOT_FOUND,
+            "the transaction pool cannot list the transactions of a sender".to_string(),
+        ),
+    }
+}
+
+/// `GET /mempool/stats` and `GET /mempool/account/:addr`.
+pub(crate) fn mempool_routes<S: Clone + Send + Sync + 'static>(
+    inspector: MempoolInspector,
+) -> Router<S> {
+    Router::new()
+        .route("/mempool/stats", get(get_stats))
+        .route("/mempool/account/:addr", get(get_account))
+        .with_state(inspector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evm_and_account_addresses_parse_to_the_same_account() {
+        let evm = Address::repeat_byte(0xab);
+        let account = ExternalAccountAddress::from_evm(evm);
+        assert_eq!(parse_address(&evm.to_string()).unwrap(), account);
+        assert_eq!(parse_address(&hex::encode(evm)).unwrap(), account);
+        assert_eq!(parse_address(&hex::encode(account.bytes())).unwrap(), account);
+        assert!(parse_address("0xabcd").is_err());
+        assert!(parse_address("0xzz").is_err());
+    }
+}
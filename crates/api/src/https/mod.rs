@@ -1,11 +1,24 @@
+mod admin;
+mod auth;
+mod batch;
 pub mod consensus;
+mod consensus_state;
+mod cpu_profiler;
 pub mod dkg;
+mod health;
 pub mod heap_profiler;
+pub mod listener;
+mod mempool;
+pub mod query_replica;
 mod set_failpoints;
 mod tx;
 use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
-use aptos_consensus::consensusdb::ConsensusDB;
+use crate::config_storage::ConfigReadHistory;
+use admin::{admin_routes, admin_token};
+use aptos_consensus::{consensusdb::ConsensusDB, quorum_store::quorum_store_db::QuorumStoreDB};
+use aptos_mempool::core_mempool::MempoolInspector;
+use auth::{debug_token, require_token, tls_config};
 use axum::{
     body::Body,
     extract::{DefaultBodyLimit, Path, State},
@@ -15,18 +28,41 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use axum_server::tls_rustls::RustlsConfig;
+use batch::batch_routes;
+use block_buffer_manager::BlockBufferManager;
+use consensus_state::{consensus_state_routes, ConsensusStateSource};
+use cpu_profiler::get_cpu_profile;
 use dkg::DkgState;
 use gaptos::{aptos_crypto::HashValue, aptos_logger::info};
-use heap_profiler::control_profiler;
+use health::{health_routes, HealthState, HealthThresholds};
+use heap_profiler::{control_profiler, ControlProfileRequest};
+use listener::{HttpsListener, RouteGroup};
+use mempool::mempool_routes;
+use query_replica::{freshness_headers, query_replica_refresh_interval, QueryReplica};
 use set_failpoints::{set_failpoint, FailpointConf};
-use tx::{get_tx_by_hash, submit_tx, TxRequest};
+use tx::{get_tx_by_hash, get_tx_journey, submit_tx, TxRequest};
 
 pub struct HttpsServer {
     pub address: String,
     pub cert_pem: Option<PathBuf>,
     pub key_pem: Option<PathBuf>,
+    /// Client certificates of this CA are required when set, see [`auth`].
+    pub client_ca_pem: Option<PathBuf>,
+    /// Route groups served on `address`, see [`listener`].
+    pub routes: Vec<RouteGroup>,
     pub consensus_db: Option<Arc<ConsensusDB>>,
+    /// Where query replica checkpoints are kept when CONSENSUS_QUERY_REPLICA_REFRESH_SECS is set.
+    pub query_replica_dir: Option<PathBuf>,
+    /// Listeners served next to `address`, see [`listener`].
+    pub extra_listeners: Vec<HttpsListener>,
+    /// Serves the `/mempool/*` routes when set.
+    pub mempool_inspector: Option<MempoolInspector>,
+    /// Execution progress checked by `/health/ready` when set.
+    pub block_buffer_manager: Option<Arc<BlockBufferManager>>,
+    /// Serves the `/debug/batch/*` routes when set.
+    pub quorum_store_db: Option<Arc<QuorumStoreDB>>,
+    /// Serves `/debug/onchain_config_reads` when set.
+    pub config_reads: Option<ConfigReadHistory>,
 }
 
 async fn ensure_https(req: Request<Body>, next: Next) -> Response {
@@ -36,125 +72,307 @@ async fn ensure_https(req: Request<Body>, next: Next) -> Response {
     next.run(req).await
 }
 
-impl HttpsServer {
-    pub fn new(
-        address: String,
-        cert_pem: Option<PathBuf>,
-        key_pem: Option<PathBuf>,
-        consensus_db: Option<Arc<ConsensusDB>>,
-    ) -> Self {
-        Self { address, cert_pem, key_pem, consensus_db }
-    }
+/// The routes of `listener`. GSDK-013: sensitive routes are only registered with TLS.
+fn router(
+    dkg_state_arc: Arc<DkgState>,
+    mempool_inspector: Option<&MempoolInspector>,
+    quorum_store_db: Option<&Arc<QuorumStoreDB>>,
+    config_reads: Option<&ConfigReadHistory>,
+    health_state: &HealthState,
+    consensus_state: &ConsensusStateSource,
+    listener: &HttpsListener,
+) -> Router {
+    let submit_tx_lambda = |Json(request): Json<TxRequest>| async move { submit_tx(request).await };
 
-    pub async fn serve(self) {
-        rustls::crypto::ring::default_provider().install_default().unwrap();
+    let get_tx_by_hash_lambda =
+        |Path(request): Path<HashValue>| async move { get_tx_by_hash(request).await };
 
-        let consensus_db = self.consensus_db.clone();
-        let dkg_state = DkgState::new(consensus_db);
+    let get_tx_journey_lambda = |Path(hash): Path<HashValue>| async move { get_tx_journey(hash) };
 
-        let submit_tx_lambda =
-            |Json(request): Json<TxRequest>| async move { submit_tx(request).await };
+    let set_fail_point_lambda =
+        |Json(request): Json<FailpointConf>| async move { set_failpoint(request).await };
 
-        let get_tx_by_hash_lambda =
-            |Path(request): Path<HashValue>| async move { get_tx_by_hash(request).await };
+    let control_profiler_lambda =
+        |Json(request): Json<ControlProfileRequest>| async move { control_profiler(request).await };
 
-        let set_fail_point_lambda =
-            |Json(request): Json<FailpointConf>| async move { set_failpoint(request).await };
+    let get_dkg_status_lambda =
+        |State(state): State<Arc<DkgState>>| async move { state.get_dkg_status() };
 
-        let control_profiler_lambda = |Json(request): Json<
-            heap_profiler::ControlProfileRequest,
-        >| async move { control_profiler(request).await };
+    let get_dkg_health_lambda =
+        |State(state): State<Arc<DkgState>>| async move { state.get_dkg_health() };
 
-        let get_dkg_status_lambda =
-            |State(state): State<Arc<DkgState>>| async move { state.get_dkg_status() };
+    let get_latest_ledger_info_lambda = |State(state): State<Arc<DkgState>>| async move {
+        consensus::get_latest_ledger_info(state)
+    };
 
-        let get_latest_ledger_info_lambda = |State(state): State<Arc<DkgState>>| async move {
-            consensus::get_latest_ledger_info(state)
+    let get_randomness_lambda = |State(state): State<Arc<DkgState>>,
+                                 Path(block_number): Path<u64>| async move {
+        state.get_randomness(block_number)
+    };
+
+    let get_ledger_info_by_epoch_lambda =
+        |State(state): State<Arc<DkgState>>, Path(epoch): Path<u64>| async move {
+            consensus::get_ledger_info_by_epoch(State(state), Path(epoch))
         };
 
-        let get_randomness_lambda =
-            |State(state): State<Arc<DkgState>>, Path(block_number): Path<u64>| async move {
-                state.get_randomness(block_number)
-            };
+    let get_block_lambda = |State(state): State<Arc<DkgState>>,
+                            Path((epoch, round)): Path<(u64, u64)>| async move {
+        consensus::get_block(State(state), Path((epoch, round)))
+    };
+
+    let get_qc_lambda = |State(state): State<Arc<DkgState>>,
+                         Path((epoch, round)): Path<(u64, u64)>| async move {
+        consensus::get_qc(State(state), Path((epoch, round)))
+    };
 
-        let get_ledger_info_by_epoch_lambda =
-            |State(state): State<Arc<DkgState>>, Path(epoch): Path<u64>| async move {
-                consensus::get_ledger_info_by_epoch(State(state), Path(epoch))
-            };
+    let get_validator_count_lambda = |State(state): State<Arc<DkgState>>,
+                                      Path(epoch): Path<u64>| async move {
+        consensus::get_validator_count_by_epoch(State(state), Path(epoch))
+    };
 
-        let get_block_lambda =
-            |State(state): State<Arc<DkgState>>, Path((epoch, round)): Path<(u64, u64)>| async move {
-                consensus::get_block(State(state), Path((epoch, round)))
-            };
+    let get_all_evidence_lambda = |State(state): State<Arc<DkgState>>| async move {
+        consensus::get_double_sign_evidence(state, None)
+    };
 
-        let get_qc_lambda = |State(state): State<Arc<DkgState>>,
-                             Path((epoch, round)): Path<(u64, u64)>| async move {
-            consensus::get_qc(State(state), Path((epoch, round)))
+    let get_evidence_by_epoch_lambda =
+        |State(state): State<Arc<DkgState>>, Path(epoch): Path<u64>| async move {
+            consensus::get_double_sign_evidence(state, Some(epoch))
         };
 
-        let get_validator_count_lambda =
-            |State(state): State<Arc<DkgState>>, Path(epoch): Path<u64>| async move {
-                consensus::get_validator_count_by_epoch(State(state), Path(epoch))
-            };
+    let get_commit_payloads_lambda =
+        |State(state): State<Arc<DkgState>>, Path((start, end)): Path<(u64, u64)>| async move {
+            consensus::get_commit_payloads(state, start, end)
+        };
 
-        let dkg_state_arc = Arc::new(dkg_state);
-        let has_tls = self.cert_pem.is_some() && self.key_pem.is_some();
-
-        let https_routes = Router::new()
-            .route("/tx/submit_tx", post(submit_tx_lambda))
-            .route("/tx/get_tx_by_hash/:hash_value", get(get_tx_by_hash_lambda))
-            .layer(middleware::from_fn(ensure_https));
-        let http_routes = Router::new()
-            .route("/dkg/status", get(get_dkg_status_lambda))
-            .route("/dkg/randomness/:block_number", get(get_randomness_lambda))
-            .route("/consensus/latest_ledger_info", get(get_latest_ledger_info_lambda))
-            .route("/consensus/ledger_info/:epoch", get(get_ledger_info_by_epoch_lambda))
-            .route("/consensus/block/:epoch/:round", get(get_block_lambda))
-            .route("/consensus/qc/:epoch/:round", get(get_qc_lambda))
-            .route("/consensus/validator_count/:epoch", get(get_validator_count_lambda))
-            .route("/set_failpoint", post(set_fail_point_lambda))
-            .route("/mem_prof", post(control_profiler_lambda));
-
-        // GSDK-013: Only register sensitive https_routes when TLS is configured
-        let app = if has_tls {
-            Router::new().merge(https_routes).merge(http_routes)
-        } else {
+    let groups = &listener.routes;
+    let mut https_routes = Router::new();
+    let mut http_routes = Router::new();
+    for group in groups {
+        match group {
+            RouteGroup::Tx => {
+                https_routes = https_routes
+                    .route("/tx/submit_tx", post(submit_tx_lambda))
+                    .route("/tx/get_tx_by_hash/:hash_value", get(get_tx_by_hash_lambda));
+            }
+            RouteGroup::Admin => {
+                if let Some(token) = admin_token() {
+                    https_routes = https_routes.merge(admin_routes(token));
+                }
+            }
+            RouteGroup::Dkg => {
+                http_routes = http_routes
+                    .route("/dkg/status", get(get_dkg_status_lambda))
+                    .route("/dkg/health", get(get_dkg_health_lambda))
+                    .route("/dkg/randomness/:block_number", get(get_randomness_lambda));
+            }
+            RouteGroup::Mempool => {
+                if let Some(inspector) = mempool_inspector {
+                    http_routes = http_routes.merge(mempool_routes(inspector.clone()));
+                }
+            }
+            RouteGroup::Health => {
+                http_routes = http_routes.merge(health_routes(health_state.clone()));
+            }
+            RouteGroup::Consensus => {
+                http_routes = http_routes
+                    .route("/consensus/latest_ledger_info", get(get_latest_ledger_info_lambda))
+                    .route("/consensus/ledger_info/:epoch", get(get_ledger_info_by_epoch_lambda))
+                    .route("/consensus/block/:epoch/:round", get(get_block_lambda))
+                    .route("/consensus/qc/:epoch/:round", get(get_qc_lambda))
+                    .route("/consensus/validator_count/:epoch", get(get_validator_count_lambda))
+                    .route("/consensus/evidence", get(get_all_evidence_lambda))
+                    .route("/consensus/evidence/:epoch", get(get_evidence_by_epoch_lambda))
+                    .route(
+                        "/consensus/commit_payloads/:start/:end",
+                        get(get_commit_payloads_lambda),
+                    )
+                    .merge(consensus_state_routes(consensus_state.clone()));
+            }
+            RouteGroup::Debug => {
+                let mut debug_routes = Router::new()
+                    .route("/tx/journey/:hash", get(get_tx_journey_lambda))
+                    .route("/set_failpoint", post(set_fail_point_lambda))
+                    .route("/mem_prof", post(control_profiler_lambda))
+                    .route("/debug/pprof/heap", post(control_profiler_lambda))
+                    .route("/debug/pprof/cpu", get(get_cpu_profile));
+                if let Some(db) = quorum_store_db {
+                    debug_routes = debug_routes.merge(batch_routes(db.clone()));
+                }
+                if let Some(reads) = config_reads.cloned() {
+                    debug_routes = debug_routes.route(
+                        "/debug/onchain_config_reads",
+                        get(|| async move { Json(reads.recent()) }),
+                    );
+                }
+                if let Some(token) = debug_token() {
+                    debug_routes = debug_routes
+                        .layer(middleware::from_fn_with_state(Arc::new(token), require_token));
+                } else if !listener.has_client_auth() && !cfg!(debug_assertions) {
+                    info!(
+                        "WARNING: neither GRAVITY_DEBUG_TOKEN nor client certificates configured \
+                         for {}. Debug endpoints are disabled.",
+                        listener.address
+                    );
+                    continue;
+                }
+                http_routes = http_routes.merge(debug_routes);
+            }
+        }
+    }
+    let https_routes = https_routes.layer(middleware::from_fn(ensure_https));
+    let http_routes =
+        http_routes.layer(middleware::from_fn_with_state(dkg_state_arc.clone(), freshness_headers));
+
+    let app = if listener.has_tls() {
+        Router::new().merge(https_routes).merge(http_routes)
+    } else {
+        if groups.iter().any(|group| matches!(group, RouteGroup::Tx | RouteGroup::Admin)) {
             info!("WARNING: TLS not configured. Consensus/DKG sensitive endpoints are disabled. Only serving public HTTP routes.");
-            Router::new().merge(http_routes)
         }
-        .layer(DefaultBodyLimit::max(1_048_576)) // GSDK-011: 1 MB max request body
-        .with_state(dkg_state_arc);
-
-        let addr: SocketAddr = self
-            .address
-            .parse()
-            .unwrap_or_else(|e| panic!("Invalid bind address '{}': {e}", self.address)); // GSDK-014
-
-        match (self.cert_pem.clone(), self.key_pem.clone()) {
-            (Some(cert_path), Some(key_path)) => {
-                // configure certificate and private key used by https
-                let config =
-                    RustlsConfig::from_pem_file(cert_path, key_path).await.unwrap_or_else(|e| {
-                        panic!(
-                            "error {:?}, cert {:?}, key {:?} doesn't work",
-                            e, self.cert_pem, self.key_pem
-                        )
-                    });
-                info!("https server listen address {}", addr);
-                axum_server::bind_rustls(addr, config)
-                    .serve(app.into_make_service())
-                    .await
-                    .unwrap_or_else(|e| {
-                        panic!("failed to bind rustls due to {e:?}");
-                    });
-            }
-            _ => {
-                info!("http server listen address {}", addr);
-                axum_server::bind(addr).serve(app.into_make_service()).await.unwrap_or_else(|e| {
-                    panic!("failed to bind http due to {e:?}");
+        Router::new().merge(http_routes)
+    };
+    app.layer(DefaultBodyLimit::max(1_048_576)) // GSDK-011: 1 MB max request body
+        .with_state(dkg_state_arc)
+}
+
+async fn serve_listener(listener: HttpsListener, app: Router) {
+    let addr: SocketAddr = listener
+        .address
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid bind address '{}': {e}", listener.address)); // GSDK-014
+
+    match (&listener.cert_pem, &listener.key_pem) {
+        (Some(cert_path), Some(key_path)) => {
+            // configure certificate and private key used by https
+            let config = tls_config(cert_path, key_path, listener.client_ca_pem.as_deref())
+                .await
+                .unwrap_or_else(|e| panic!("{e}"));
+            info!("https server listen address {}", addr);
+            axum_server::bind_rustls(addr, config)
+                .serve(app.into_make_service())
+                .await
+                .unwrap_or_else(|e| {
+                    panic!("failed to bind rustls due to {e:?}");
                 });
-            }
         }
+        _ => {
+            info!("http server listen address {}", addr);
+            axum_server::bind(addr).serve(app.into_make_service()).await.unwrap_or_else(|e| {
+                panic!("failed to bind http due to {e:?}");
+            });
+        }
+    }
+}
+
+impl HttpsServer {
+    pub fn new(
+        address: String,
+        cert_pem: Option<PathBuf>,
+        key_pem: Option<PathBuf>,
+        consensus_db: Option<Arc<ConsensusDB>>,
+        query_replica_dir: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            address,
+            cert_pem,
+            key_pem,
+            client_ca_pem: None,
+            routes: RouteGroup::defaults(),
+            consensus_db,
+            query_replica_dir,
+            extra_listeners: vec![],
+            mempool_inspector: None,
+            block_buffer_manager: None,
+            quorum_store_db: None,
+            config_reads: None,
+        }
+    }
+
+    pub fn with_extra_listeners(mut self, extra_listeners: Vec<HttpsListener>) -> Self {
+        self.extra_listeners = extra_listeners;
+        self
+    }
+
+    pub fn with_client_ca_pem(mut self, client_ca_pem: Option<PathBuf>) -> Self {
+        self.client_ca_pem = client_ca_pem;
+        self
+    }
+
+    pub fn with_routes(mut self, routes: Vec<RouteGroup>) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    pub fn with_mempool_inspector(mut self, mempool_inspector: MempoolInspector) -> Self {
+        self.mempool_inspector = Some(mempool_inspector);
+        self
+    }
+
+    pub fn with_block_buffer_manager(
+        mut self,
+        block_buffer_manager: Arc<BlockBufferManager>,
+    ) -> Self {
+        self.block_buffer_manager = Some(block_buffer_manager);
+        self
+    }
+
+    pub fn with_quorum_store_db(mut self, quorum_store_db: Arc<QuorumStoreDB>) -> Self {
+        self.quorum_store_db = Some(quorum_store_db);
+        self
+    }
+
+    pub fn with_config_reads(mut self, config_reads: ConfigReadHistory) -> Self {
+        self.config_reads = Some(config_reads);
+        self
+    }
+
+    pub async fn serve(self) {
+        rustls::crypto::ring::default_provider().install_default().unwrap();
+
+        let consensus_db = self.consensus_db.clone();
+        let health_state = HealthState {
+            consensus_db: consensus_db.clone(),
+            block_buffer_manager: self.block_buffer_manager.clone(),
+            mempool_inspector: self.mempool_inspector.clone(),
+            thresholds: HealthThresholds::from_env(),
+        };
+        let consensus_state = ConsensusStateSource {
+            consensus_db: consensus_db.clone(),
+            block_buffer_manager: self.block_buffer_manager.clone(),
+        };
+        let mut dkg_state = DkgState::new(consensus_db.clone());
+        if let (Some(primary), Some(dir), Some(interval)) =
+            (consensus_db, self.query_replica_dir.clone(), query_replica_refresh_interval())
+        {
+            let replica = Arc::new(QueryReplica::new(dir));
+            tokio::spawn(replica.clone().run(primary, interval));
+            dkg_state = dkg_state.with_query_replica(replica);
+        }
+        let dkg_state_arc = Arc::new(dkg_state);
+
+        let primary = HttpsListener {
+            address: self.address,
+            cert_pem: self.cert_pem,
+            key_pem: self.key_pem,
+            client_ca_pem: self.client_ca_pem,
+            routes: self.routes,
+        };
+        let mempool_inspector = self.mempool_inspector;
+        let quorum_store_db = self.quorum_store_db;
+        let config_reads = self.config_reads;
+        let listeners = std::iter::once(primary).chain(self.extra_listeners).map(|listener| {
+            let app = router(
+                dkg_state_arc.clone(),
+                mempool_inspector.as_ref(),
+                quorum_store_db.as_ref(),
+                config_reads.as_ref(),
+                &health_state,
+                &consensus_state,
+                &listener,
+            );
+            serve_listener(listener, app)
+        });
+        futures::future::join_all(listeners).await;
     }
 }
 
@@ -163,8 +381,11 @@ pub async fn https_server(
     cert_pem: Option<PathBuf>,
     key_pem: Option<PathBuf>,
     consensus_db: Option<Arc<ConsensusDB>>,
+    query_replica_dir: Option<PathBuf>,
+    extra_listeners: Vec<HttpsListener>,
 ) {
-    let server = HttpsServer::new(address, cert_pem, key_pem, consensus_db);
+    let server = HttpsServer::new(address, cert_pem, key_pem, consensus_db, query_replica_dir)
+        .with_extra_listeners(extra_listeners);
     server.serve().await;
 }
 
@@ -202,7 +423,7 @@ mod test {
         let address = "127.0.0.1:5425".to_owned();
         let cert_pem = Some(PathBuf::from(dir.clone() + "/src/https/test/cert.pem"));
         let key_pem = Some(PathBuf::from(dir.clone() + "/src/https/test/key.pem"));
-        let _handler = tokio::spawn(https_server(address, cert_pem, key_pem, None));
+        let _handler = tokio::spawn(https_server(address, cert_pem, key_pem, None, None, vec![]));
         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         // read a local binary pem encoded certificate
         let pem = std::fs::read(dir.clone() + "/src/https/test/cert.pem").unwrap();
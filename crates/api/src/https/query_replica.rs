@@ -0,0 +1,137 @@
+use aptos_consensus::consensusdb::ConsensusDB;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use gaptos::{
+    aptos_logger::{error, info},
+    aptos_storage_interface::DbReader,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::dkg::DkgState;
+
+/// Directory under the node storage dir that holds query replica checkpoints.
+pub const QUERY_REPLICA_DIR_NAME: &str = "consensus_query_replica";
+
+/// How often the query replica is refreshed from the primary ConsensusDB.
+/// Configured via CONSENSUS_QUERY_REPLICA_REFRESH_SECS; unset or 0 serves queries from the
+/// primary directly.
+pub fn query_replica_refresh_interval() -> Option<Duration> {
+    static INTERVAL: OnceLock<Option<Duration>> = OnceLock::new();
+    *INTERVAL.get_or_init(|| {
+        std::env::var("CONSENSUS_QUERY_REPLICA_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    })
+}
+
+/// A point-in-time checkpoint of ConsensusDB opened for reads.
+pub struct ReplicaSnapshot {
+    pub db: Arc<ConsensusDB>,
+    /// Unix milliseconds at which the checkpoint was taken.
+    pub taken_at_ms: u64,
+    /// Latest committed block number visible in the checkpoint.
+    pub block_number: Option<u64>,
+    generation: u64,
+}
+
+/// Checkpoint-based read replica of ConsensusDB for the HTTP query endpoints.
+///
+/// Heavy range scans over blocks, QCs and ledger infos run against a periodically refreshed
+/// RocksDB checkpoint instead of the instance consensus writes to. Checkpoints hard-link the
+/// SST files, so a refresh costs little more than a flush. Each refresh opens a new
+/// generation directory and removes the one from two refreshes ago, leaving in-flight
+/// requests on the previous snapshot a full interval to finish.
+pub struct QueryReplica {
+    root: PathBuf,
+    current: RwLock<Option<Arc<ReplicaSnapshot>>>,
+}
+
+impl QueryReplica {
+    pub fn new(root: PathBuf) -> Self {
+        // Checkpoints from a previous run are stale and never reopened.
+        std::fs::remove_dir_all(&root).unwrap_or(());
+        Self { root, current: RwLock::new(None) }
+    }
+
+    pub fn snapshot(&self) -> Option<Arc<ReplicaSnapshot>> {
+        self.current.read().unwrap().clone()
+    }
+
+    fn generation_dir(&self, generation: u64) -> PathBuf {
+        self.root.join(generation.to_string())
+    }
+
+    pub fn refresh(&self, primary: &ConsensusDB) -> anyhow::Result<()> {
+        let generation = self.snapshot().map_or(0, |snapshot| snapshot.generation + 1);
+        let dir = self.generation_dir(generation);
+        std::fs::create_dir_all(&dir)?;
+        let taken_at_ms =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        primary.create_checkpoint_in(&dir)?;
+
+        let db = Arc::new(ConsensusDB::new(&dir, &PathBuf::new()));
+        let block_number = DbReader::get_latest_ledger_info(db.as_ref())
+            .ok()
+            .map(|info| info.ledger_info().block_number());
+        *self.current.write().unwrap() =
+            Some(Arc::new(ReplicaSnapshot { db, taken_at_ms, block_number, generation }));
+
+        if let Some(stale) = generation.checked_sub(2) {
+            std::fs::remove_dir_all(self.generation_dir(stale)).unwrap_or(());
+        }
+        Ok(())
+    }
+
+    pub async fn run(self: Arc<Self>, primary: Arc<ConsensusDB>, interval: Duration) {
+        info!("Serving consensus queries from a replica refreshed every {:?}", interval);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let replica = self.clone();
+            let primary = primary.clone();
+            match tokio::task::spawn_blocking(move || replica.refresh(&primary)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!("Failed to refresh consensus query replica: {:?}", e),
+                Err(e) => error!("Consensus query replica refresh panicked: {:?}", e),
+            }
+        }
+    }
+}
+
+/// Tags every response with where consensus data was read from and, for the replica, how
+/// fresh it is: `x-consensus-db-source` is `primary` or `replica`, and replica responses add
+/// `x-consensus-db-snapshot-ms` (unix ms of the checkpoint) and
+/// `x-consensus-db-snapshot-block` (latest committed block in it).
+pub async fn freshness_headers(
+    State(state): State<Arc<DkgState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let snapshot = state.query_replica().and_then(|replica| replica.snapshot());
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    match snapshot {
+        Some(snapshot) => {
+            headers.insert("x-consensus-db-source", HeaderValue::from_static("replica"));
+            headers.insert("x-consensus-db-snapshot-ms", HeaderValue::from(snapshot.taken_at_ms));
+            if let Some(block_number) = snapshot.block_number {
+                headers.insert("x-consensus-db-snapshot-block", HeaderValue::from(block_number));
+            }
+        }
+        None => {
+            headers.insert("x-consensus-db-source", HeaderValue::from_static("primary"));
+        }
+    }
+    response
+}
@@ -1,6 +1,12 @@
-use axum::{http::StatusCode, response::Json as JsonResponse};
+use crate::https::consensus::ErrorResponse;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse},
+};
 use gaptos::{aptos_crypto::HashValue, aptos_logger::info};
 use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use txn_metrics::{is_txn_life_enabled, TxnLifeTime};
 
 #[derive(Serialize, Deserialize)]
 pub struct TxRequest {
@@ -34,3 +40,75 @@ pub async fn get_tx_by_hash(request: HashValue) -> Result<JsonResponse<TxRespons
     info!("get transaction by hash {}", request);
     Ok(JsonResponse(TxResponse { tx: vec![] }))
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxnStage {
+    pub stage: String,
+    pub timestamp_usecs: u64,
+    pub batch_id: Option<String>,
+    pub block_id: Option<String>, // hex encoded
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxnJourneyResponse {
+    pub hash: String, // hex encoded
+    pub stages: Vec<TxnStage>,
+}
+
+fn stage(
+    stage: &str,
+    time: SystemTime,
+    batch_id: Option<String>,
+    block_id: Option<String>,
+) -> TxnStage {
+    let timestamp_usecs =
+        time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_micros() as u64);
+    TxnStage { stage: stage.to_string(), timestamp_usecs, batch_id, block_id }
+}
+
+// Timeline of the stages this node recorded for a transaction, requires TXN_LIFE_ENABLED.
+// example:
+// curl http://127.0.0.1:1024/tx/journey/aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
+pub fn get_tx_journey(hash: HashValue) -> impl IntoResponse {
+    if !is_txn_life_enabled() {
+        return (
+            StatusCode::NOT_FOUND,
+            JsonResponse(ErrorResponse { error: "TXN_LIFE_ENABLED is not set".to_string() }),
+        )
+            .into_response();
+    }
+    let Some(journey) = TxnLifeTime::get_txn_life_time().get_journey(&hash) else {
+        return (
+            StatusCode::NOT_FOUND,
+            JsonResponse(ErrorResponse { error: format!("Transaction {hash} is not tracked") }),
+        )
+            .into_response();
+    };
+
+    let mut stages = vec![];
+    if let Some(time) = journey.added {
+        stages.push(stage("added", time, None, None));
+    }
+    if let Some((batch_id, time)) = journey.batch {
+        stages.push(stage("batch", time, Some(batch_id.to_string()), None));
+    }
+    if let Some(time) = journey.broadcast {
+        stages.push(stage("broadcast", time, None, None));
+    }
+    if let Some(time) = journey.proof {
+        stages.push(stage("proof", time, None, None));
+    }
+    if let Some((block_id, time)) = journey.block {
+        stages.push(stage("block", time, None, Some(hex::encode(block_id.as_ref()))));
+    }
+    if let Some(time) = journey.executed {
+        stages.push(stage("executed", time, None, None));
+    }
+    if let Some(time) = journey.committed {
+        stages.push(stage("committed", time, None, None));
+    }
+    stages.sort_by_key(|stage| stage.timestamp_usecs);
+
+    (StatusCode::OK, JsonResponse(TxnJourneyResponse { hash: hex::encode(hash.as_ref()), stages }))
+        .into_response()
+}
@@ -2,13 +2,19 @@ mod bootstrap;
 pub mod config_storage;
 pub mod consensus_api;
 mod consensus_mempool_handler;
+mod consensus_pruner;
+pub use genesis_bundle;
 mod https;
 mod logger;
 mod network;
+mod role_profile;
+pub mod trusted_checkpoint;
+mod watermark_metrics;
 
 pub use bootstrap::check_bootstrap_config;
 use clap::Parser;
 pub use gaptos::aptos_config::config::NodeConfig;
+pub use role_profile::{NodeRole, RoleProfile};
 use std::path::PathBuf;
 
 /// Runs an Gravity validator or fullnode
@@ -22,4 +28,52 @@ pub struct GravityNodeArgs {
     #[arg(long = "relayer_config", value_name = "RELAYER_CONFIG", global = true)]
     /// Path to relayer configuration file (JSON format with URI to RPC URL mappings).
     pub relayer_config_path: Option<PathBuf>,
+
+    #[arg(
+        long = "expected_genesis_hash",
+        value_name = "GENESIS_HASH",
+        env = "GRAVITY_EXPECTED_GENESIS_HASH",
+        global = true
+    )]
+    /// Genesis hash the execution layer must report at startup. The node refuses to start on a
+    /// mismatch. Without it, the hash recorded in the consensus storage directory at the first
+    /// start is checked instead.
+    pub expected_genesis_hash: Option<String>,
+
+    #[arg(long = "observer", global = true)]
+    /// Follow the chain as a consensus observer: ordered blocks and commit decisions are taken
+    /// from the validators that enable `consensus_observer.publisher_enabled` and executed
+    /// locally, without voting.
+    pub observer: bool,
+
+    #[arg(
+        long = "trusted_checkpoint",
+        value_name = "PATH_OR_URL",
+        env = "GRAVITY_TRUSTED_CHECKPOINT",
+        requires = "trusted_waypoint",
+        global = true
+    )]
+    /// BCS encoded trusted checkpoint, a file or an http(s) URL. Its epoch and validator set
+    /// are served until the execution layer can read them, and consensus starts in the
+    /// checkpoint epoch if the execution layer already has the state of the checkpoint block.
+    pub trusted_checkpoint: Option<String>,
+
+    #[arg(
+        long = "trusted_waypoint",
+        value_name = "WAYPOINT",
+        env = "GRAVITY_TRUSTED_WAYPOINT",
+        global = true
+    )]
+    /// Waypoint of the trusted checkpoint's ledger info, obtained out of band.
+    pub trusted_waypoint: Option<String>,
+
+    #[arg(
+        long = "genesis_bundle",
+        value_name = "PATH",
+        env = "GRAVITY_GENESIS_BUNDLE",
+        global = true
+    )]
+    /// BCS encoded genesis bundle built by `gravity-cli genesis build-bundle`. Supplies the
+    /// genesis waypoint and the chain id and execution genesis hash the node must run on.
+    pub genesis_bundle: Option<PathBuf>,
 }
@@ -0,0 +1,288 @@
+//! Role presets, so the `node_role` field of the node config gives every module consistent
+//! defaults:
+//!
+//! ```yaml
+//! node_role: rpc
+//! base:
+//!   role: full_node
+//! ```
+//!
+//! A preset fills in the node config fields and the environment variables the operator left
+//! unset, see [`RoleProfile`], and the node refuses to start if the rest of its config
+//! contradicts the role. `node_role` is not a field of the gaptos [`NodeConfig`], so it is taken
+//! out of the file before the rest is parsed.
+
+use crate::consensus_pruner::consensus_prune_retention;
+use gaptos::aptos_config::config::{NodeConfig, RoleType};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::{io::Write, path::Path};
+
+/// Top level key of the node config holding the role.
+const NODE_ROLE_KEY: &str = "node_role";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    /// Votes in consensus.
+    Validator,
+    /// Follows the chain, keeping only recent consensus history.
+    Fullnode,
+    /// Follows the chain and keeps all consensus history.
+    Archive,
+    /// A fullnode serving transaction submission and lookups to clients.
+    Rpc,
+}
+
+/// Defaults of a role. Fields are set in the node config unless the file sets them, environment
+/// variables unless they are already set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoleProfile {
+    /// `mempool.shared_mempool_max_concurrent_inbound_syncs`
+    pub mempool_inbound_syncs: usize,
+    /// `mempool.max_network_channel_size`
+    pub mempool_channel_size: usize,
+    /// `consensus_observer.observer_enabled`, following the chain from the ordered blocks and
+    /// commit decisions the validators publish.
+    pub observer_enabled: bool,
+    /// `consensus_observer.publisher_enabled`
+    pub publisher_enabled: bool,
+    /// Route groups of the `https_server_address` listener, GRAVITY_HTTPS_ROUTES.
+    pub https_routes: &'static str,
+    /// `inspection_service.expose_configuration`, the node config on the metrics port.
+    pub expose_configuration: bool,
+    /// Transaction journeys and latency histograms, TXN_LIFE_ENABLED.
+    pub txn_life_enabled: bool,
+    /// CONSENSUS_PRUNE_KEEP_EPOCHS, `None` keeps all consensus history.
+    pub prune_keep_epochs: Option<u64>,
+}
+
+impl NodeRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeRole::Validator => "validator",
+            NodeRole::Fullnode => "fullnode",
+            NodeRole::Archive => "archive",
+            NodeRole::Rpc => "rpc",
+        }
+    }
+
+    pub fn profile(self) -> RoleProfile {
+        match self {
+            NodeRole::Validator => RoleProfile {
+                mempool_inbound_syncs: 4,
+                mempool_channel_size: 1024,
+                observer_enabled: false,
+                publisher_enabled: true,
+                https_routes: "consensus,dkg,admin,health,debug",
+                expose_configuration: false,
+                txn_life_enabled: false,
+                prune_keep_epochs: Some(2),
+            },
+            NodeRole::Fullnode => RoleProfile {
+                mempool_inbound_syncs: 16,
+                mempool_channel_size: 1024,
+                observer_enabled: true,
+                publisher_enabled: false,
+                https_routes: "health,debug",
+                expose_configuration: true,
+                txn_life_enabled: false,
+                prune_keep_epochs: Some(2),
+            },
+            NodeRole::Archive => RoleProfile {
+                prune_keep_epochs: None,
+                https_routes: "consensus,dkg,health,debug",
+                ..NodeRole::Fullnode.profile()
+            },
+            // Takes client transactions, so it buffers more gossip and tracks journeys
+            NodeRole::Rpc => RoleProfile {
+                mempool_inbound_syncs: 32,
+                mempool_channel_size: 4096,
+                https_routes: "tx,mempool,health,debug",
+                expose_configuration: false,
+                txn_life_enabled: true,
+                ..NodeRole::Fullnode.profile()
+            },
+        }
+    }
+
+    /// Environment variables the role sets when they are unset.
+    pub fn env_defaults(self) -> Vec<(&'static str, String)> {
+        let profile = self.profile();
+        let mut defaults = vec![("GRAVITY_HTTPS_ROUTES", profile.https_routes.to_string())];
+        if profile.txn_life_enabled {
+            defaults.push(("TXN_LIFE_ENABLED", "1".to_string()));
+        }
+        if let Some(epochs) = profile.prune_keep_epochs {
+            defaults.push(("CONSENSUS_PRUNE_KEEP_EPOCHS", epochs.to_string()));
+        }
+        defaults
+    }
+
+    /// Applies the profile to `config`, leaving the fields set in `raw`, the node config file
+    /// it was parsed from. Sets the environment defaults, so it must run before the threads
+    /// that read them are spawned.
+    pub fn apply(self, config: &mut NodeConfig, raw: &Value) {
+        self.apply_fields(config, raw);
+        for (name, value) in self.env_defaults() {
+            if std::env::var_os(name).is_none() {
+                std::env::set_var(name, value);
+            }
+        }
+    }
+
+    fn apply_fields(self, config: &mut NodeConfig, raw: &Value) {
+        let profile = self.profile();
+        let unset =
+            |path: &[&str]| path.iter().try_fold(raw, |value, key| value.get(*key)).is_none();
+        if unset(&["mempool", "shared_mempool_max_concurrent_inbound_syncs"]) {
+            config.mempool.shared_mempool_max_concurrent_inbound_syncs =
+                profile.mempool_inbound_syncs;
+        }
+        if unset(&["mempool", "max_network_channel_size"]) {
+            config.mempool.max_network_channel_size = profile.mempool_channel_size;
+        }
+        if unset(&["consensus_observer", "observer_enabled"]) {
+            config.consensus_observer.observer_enabled = profile.observer_enabled;
+        }
+        if unset(&["consensus_observer", "publisher_enabled"]) {
+            config.consensus_observer.publisher_enabled = profile.publisher_enabled;
+        }
+        if unset(&["inspection_service", "expose_configuration"]) {
+            config.inspection_service.expose_configuration = profile.expose_configuration;
+        }
+    }
+
+    /// Checks that the node config and flags do not contradict the role.
+    pub fn check(self, config: &NodeConfig, observer: bool) -> Result<(), String> {
+        let is_validator = config.base.role == RoleType::Validator;
+        match self {
+            NodeRole::Validator if !is_validator => {
+                return Err("node_role validator needs `base.role: validator`".to_string());
+            }
+            NodeRole::Validator if observer || config.consensus_observer.observer_enabled => {
+                return Err("node_role validator cannot run as a consensus observer".to_string());
+            }
+            NodeRole::Fullnode | NodeRole::Archive | NodeRole::Rpc if is_validator => {
+                return Err(format!("node_role {} needs `base.role: full_node`", self.as_str()));
+            }
+            _ => {}
+        }
+        if self == NodeRole::Archive && consensus_prune_retention().is_some() {
+            return Err("node_role archive keeps all consensus history, unset \
+                        CONSENSUS_PRUNE_KEEP_EPOCHS and CONSENSUS_PRUNE_KEEP_BLOCKS"
+                .to_string());
+        }
+        if self == NodeRole::Rpc && config.https_server_address.is_empty() {
+            return Err("node_role rpc needs `https_server_address`".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Loads the node config at `path` and applies the preset of its `node_role`, if it has one.
+pub fn load_node_config(path: &Path) -> Result<(NodeConfig, Option<NodeRole>), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{e}"))?;
+    let (role, raw) = split_node_role(&content)?;
+    let Some(role) = role else {
+        let config = NodeConfig::load_from_path(path).map_err(|e| format!("{e:?}"))?;
+        return Ok((config, None));
+    };
+
+    // The rest is loaded from the same directory, so relative paths in it resolve the same way
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut file = tempfile::Builder::new()
+        .prefix(".node_config")
+        .suffix(".yaml")
+        .tempfile_in(dir)
+        .map_err(|e| format!("failed to create a file next to the node config: {e}"))?;
+    let rest = serde_yaml::to_string(&raw).map_err(|e| format!("{e}"))?;
+    file.write_all(rest.as_bytes()).map_err(|e| format!("{e}"))?;
+    let mut config = NodeConfig::load_from_path(file.path()).map_err(|e| format!("{e:?}"))?;
+    role.apply(&mut config, &raw);
+    Ok((config, Some(role)))
+}
+
+/// Takes the role out of the node config file content.
+fn split_node_role(content: &str) -> Result<(Option<NodeRole>, Value), String> {
+    let mut raw: Value = serde_yaml::from_str(content).map_err(|e| format!("{e}"))?;
+    let role = raw.as_mapping_mut().and_then(|map| map.remove(&Value::from(NODE_ROLE_KEY)));
+    let role = role
+        .map(serde_yaml::from_value)
+        .transpose()
+        .map_err(|e| format!("invalid {NODE_ROLE_KEY}: {e}"))?;
+    Ok((role, raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::https::listener::{parse_route_groups, RouteGroup};
+
+    #[test]
+    fn role_is_taken_out_of_the_node_config() {
+        let (role, raw) = split_node_role("node_role: rpc\nbase:\n  role: full_node\n").unwrap();
+        assert_eq!(role, Some(NodeRole::Rpc));
+        assert!(raw.get(NODE_ROLE_KEY).is_none());
+        assert!(raw.get("base").is_some());
+
+        let (role, _) = split_node_role("base:\n  role: validator\n").unwrap();
+        assert_eq!(role, None);
+        assert!(split_node_role("node_role: leader\n").is_err());
+    }
+
+    #[test]
+    fn fullnode_and_rpc_profiles_differ() {
+        let (fullnode, rpc) = (NodeRole::Fullnode.profile(), NodeRole::Rpc.profile());
+        assert!(rpc.mempool_inbound_syncs > fullnode.mempool_inbound_syncs);
+        assert!(rpc.mempool_channel_size > fullnode.mempool_channel_size);
+        assert!(rpc.https_routes.split(',').any(|group| group == "tx"));
+        assert!(!fullnode.https_routes.split(',').any(|group| group == "tx"));
+        assert!(rpc.txn_life_enabled && !fullnode.txn_life_enabled);
+        assert_eq!(NodeRole::Archive.profile().prune_keep_epochs, None);
+        assert!(NodeRole::Validator.profile().publisher_enabled);
+        assert!(!NodeRole::Validator.profile().observer_enabled);
+    }
+
+    #[test]
+    fn profile_routes_are_known_groups() {
+        for role in [NodeRole::Validator, NodeRole::Fullnode, NodeRole::Archive, NodeRole::Rpc] {
+            let routes = parse_route_groups(role.profile().https_routes).unwrap();
+            assert!(routes.contains(&RouteGroup::Health), "{}", role.as_str());
+        }
+        assert!(parse_route_groups("tx,ledger").is_err());
+    }
+
+    #[test]
+    fn profile_leaves_the_fields_the_file_sets() {
+        let (_, raw) = split_node_role(
+            "node_role: fullnode\nmempool:\n  max_network_channel_size: 7\nconsensus_observer:\n  \
+             observer_enabled: false\n",
+        )
+        .unwrap();
+        let mut config = NodeConfig::default();
+        config.mempool.max_network_channel_size = 7;
+        NodeRole::Fullnode.apply_fields(&mut config, &raw);
+
+        assert_eq!(config.mempool.max_network_channel_size, 7);
+        assert!(!config.consensus_observer.observer_enabled);
+        assert_eq!(config.mempool.shared_mempool_max_concurrent_inbound_syncs, 16);
+        assert!(config.inspection_service.expose_configuration);
+    }
+
+    #[test]
+    fn config_must_agree_with_the_role() {
+        let mut config = NodeConfig::default();
+        config.base.role = RoleType::FullNode;
+        assert!(NodeRole::Validator.check(&config, false).is_err());
+        assert!(NodeRole::Fullnode.check(&config, false).is_ok());
+        assert!(NodeRole::Rpc.check(&config, false).is_err());
+        config.https_server_address = "127.0.0.1:1024".to_string();
+        assert!(NodeRole::Rpc.check(&config, false).is_ok());
+
+        config.base.role = RoleType::Validator;
+        assert!(NodeRole::Fullnode.check(&config, false).is_err());
+        assert!(NodeRole::Validator.check(&config, true).is_err());
+        assert!(NodeRole::Validator.check(&config, false).is_ok());
+    }
+}
@@ -0,0 +1,214 @@
+//! Bootstrap of a fresh node from a trusted checkpoint.
+//!
+//! A node reads the validator set from its execution layer, which cannot answer for the current
+//! epoch before it has executed past the epoch change. A checkpoint carries the ledger info that
+//! ended the previous epoch and the validator set it installed, so the node can verify peers and
+//! block sync before its execution layer catches up. The checkpoint is only trusted if its
+//! ledger info matches a waypoint the operator obtained out of band.
+
+use aptos_consensus::consensusdb::ConsensusDB;
+use gaptos::{
+    aptos_logger::info,
+    aptos_storage_interface::{DbReader, DbWriter},
+    aptos_types::{
+        ledger_info::LedgerInfoWithSignatures, on_chain_config::ValidatorSet,
+        validator_verifier::ValidatorVerifier, waypoint::Waypoint,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// BCS encoded content of a checkpoint file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrustedCheckpoint {
+    /// Ledger info of the last block of the epoch before the checkpoint.
+    pub ledger_info: LedgerInfoWithSignatures,
+    /// Validator set installed by that ledger info.
+    pub validator_set: ValidatorSet,
+}
+
+impl TrustedCheckpoint {
+    /// Loads a checkpoint from a file or an `http(s)://` URL and checks it against `waypoint`.
+    pub async fn load(source: &str, waypoint: &Waypoint) -> Result<Self, String> {
+        let bytes = if source.starts_with("http://") || source.starts_with("https://") {
+            let response = reqwest::get(source)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("failed to download trusted checkpoint {source}: {e}"))?;
+            response
+                .bytes()
+                .await
+                .map_err(|e| format!("failed to download trusted checkpoint {source}: {e}"))?
+                .to_vec()
+        } else {
+            std::fs::read(source)
+                .map_err(|e| format!("failed to read trusted checkpoint {source}: {e}"))?
+        };
+        let checkpoint: Self = bcs::from_bytes(&bytes)
+            .map_err(|e| format!("failed to decode trusted checkpoint {source}: {e}"))?;
+        checkpoint.verify(waypoint)?;
+        Ok(checkpoint)
+    }
+
+    /// Checks that the ledger info matches the waypoint and installs the validator set.
+    pub fn verify(&self, waypoint: &Waypoint) -> Result<(), String> {
+        let ledger_info = self.ledger_info.ledger_info();
+        waypoint
+            .verify(ledger_info)
+            .map_err(|e| format!("trusted checkpoint does not match the waypoint: {e}"))?;
+        let next_epoch_state = ledger_info
+            .next_epoch_state()
+            .ok_or_else(|| "trusted checkpoint ledger info does not end an epoch".to_string())?;
+        let verifier: ValidatorVerifier = (&self.validator_set).into();
+        if *next_epoch_state.verifier != verifier {
+            return Err(format!(
+                "trusted checkpoint validator set is not the one installed for epoch {}",
+                next_epoch_state.epoch
+            ));
+        }
+        Ok(())
+    }
+
+    /// Stores the ledger info as the latest one, so consensus starts in the checkpoint epoch and
+    /// block syncs from there. The execution layer, at `execution_block_number`, must already
+    /// have the state of the checkpoint block, for instance restored from a snapshot: consensus
+    /// never sends it the blocks before the ledger info. Otherwise the node syncs from its own
+    /// ledger and the checkpoint only serves the configs, see
+    /// [`ConfigStorageWrapper::with_trusted_checkpoint`]. Nothing is written if the database
+    /// already reached that epoch.
+    ///
+    /// [`ConfigStorageWrapper::with_trusted_checkpoint`]:
+    /// crate::config_storage::ConfigStorageWrapper::with_trusted_checkpoint
+    pub fn apply(
+        &self,
+        consensus_db: &ConsensusDB,
+        execution_block_number: u64,
+    ) -> Result<(), String> {
+        let latest = DbReader::get_latest_ledger_info(consensus_db)
+            .map_err(|e| format!("failed to read the latest ledger info: {e}"))?;
+        if latest.ledger_info().next_block_epoch() >= self.epoch() {
+            return Ok(());
+        }
+        if execution_block_number < self.block_number() {
+            info!(
+                "execution layer at block {} is behind the trusted checkpoint at block {}, \
+                 syncing from the local ledger",
+                execution_block_number,
+                self.block_number()
+            );
+            return Ok(());
+        }
+        info!(
+            "starting from trusted checkpoint at epoch {} block {}",
+            self.epoch(),
+            self.block_number()
+        );
+        consensus_db
+            .save_transactions(None, Some(&self.ledger_info), true)
+            .map_err(|e| format!("failed to store the trusted checkpoint: {e}"))
+    }
+
+    /// Epoch the checkpoint starts.
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.ledger_info().next_block_epoch()
+    }
+
+    /// Last block before the checkpoint.
+    pub fn block_number(&self) -> u64 {
+        self.ledger_info.ledger_info().block_number()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::{
+        aptos_crypto::HashValue,
+        aptos_types::{
+            aggregate_signature::AggregateSignature, block_info::BlockInfo,
+            epoch_state::EpochState, ledger_info::LedgerInfo,
+            validator_verifier::random_validator_verifier,
+        },
+    };
+    use std::path::PathBuf;
+
+    /// Checkpoint ending epoch `epoch - 1` at `block_number`.
+    fn checkpoint(epoch: u64, block_number: u64) -> TrustedCheckpoint {
+        let (_, verifier) = random_validator_verifier(2, None, false);
+        let validator_set: ValidatorSet = (&verifier).into();
+        let info = BlockInfo::new(
+            epoch - 1,
+            block_number,
+            HashValue::random(),
+            HashValue::random(),
+            0,
+            block_number,
+            Some(EpochState::new(epoch, verifier)),
+        );
+        let ledger_info = LedgerInfoWithSignatures::new(
+            LedgerInfo::new_with_block_info(
+                info,
+                HashValue::zero(),
+                HashValue::random(),
+                block_number,
+            ),
+            AggregateSignature::empty(),
+        );
+        TrustedCheckpoint { ledger_info, validator_set }
+    }
+
+    fn waypoint(checkpoint: &TrustedCheckpoint) -> Waypoint {
+        Waypoint::new_epoch_boundary(checkpoint.ledger_info.ledger_info()).unwrap()
+    }
+
+    #[test]
+    fn verifies_against_the_waypoint_and_the_next_epoch_state() {
+        let trusted = checkpoint(3, 100);
+        assert_eq!((trusted.epoch(), trusted.block_number()), (3, 100));
+        assert!(trusted.verify(&waypoint(&trusted)).is_ok());
+
+        let other = checkpoint(3, 100);
+        assert!(trusted.verify(&waypoint(&other)).unwrap_err().contains("waypoint"));
+
+        let mut swapped = trusted.clone();
+        swapped.validator_set = other.validator_set;
+        assert!(swapped.verify(&waypoint(&trusted)).unwrap_err().contains("epoch 3"));
+    }
+
+    #[tokio::test]
+    async fn loads_from_a_file() {
+        let trusted = checkpoint(3, 100);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.bcs");
+        std::fs::write(&path, bcs::to_bytes(&trusted).unwrap()).unwrap();
+        let source = path.to_str().unwrap();
+
+        let loaded = TrustedCheckpoint::load(source, &waypoint(&trusted)).await.unwrap();
+        assert_eq!(loaded.ledger_info, trusted.ledger_info);
+        assert!(TrustedCheckpoint::load(source, &waypoint(&checkpoint(3, 100))).await.is_err());
+
+        std::fs::write(&path, b"not a checkpoint").unwrap();
+        let err = TrustedCheckpoint::load(source, &waypoint(&trusted)).await.unwrap_err();
+        assert!(err.contains("failed to decode"), "{err}");
+    }
+
+    #[test]
+    fn applies_once_the_execution_layer_has_the_checkpoint_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = ConsensusDB::new(dir.path(), &PathBuf::new());
+        let start = checkpoint(1, 0);
+        db.save_transactions(None, Some(&start.ledger_info), true).unwrap();
+        let latest = || DbReader::get_latest_ledger_info(&db).unwrap();
+
+        let trusted = checkpoint(3, 100);
+        // An execution layer at genesis would never get the blocks before the checkpoint
+        trusted.apply(&db, 0).unwrap();
+        assert_eq!(latest(), start.ledger_info);
+
+        trusted.apply(&db, 100).unwrap();
+        assert_eq!(latest(), trusted.ledger_info);
+
+        // A database in the checkpoint epoch is left alone
+        checkpoint(3, 100).apply(&db, 200).unwrap();
+        assert_eq!(latest(), trusted.ledger_info);
+    }
+}
@@ -0,0 +1,64 @@
+use block_buffer_manager::{BlockBufferManager, Watermarks};
+use gaptos::aptos_metrics_core::{
+    register_gauge_vec, register_int_gauge_vec, GaugeVec, IntGaugeVec,
+};
+use once_cell::sync::Lazy;
+use std::{sync::Arc, time::Duration};
+use tokio::runtime::Runtime;
+
+/// How often the watermarks of the block buffer are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+static PIPELINE_WATERMARK: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "gravity_pipeline_watermark",
+        "Highest block number that reached each stage of the pipeline",
+        &["stage"]
+    )
+    .unwrap()
+});
+
+static PIPELINE_QUEUE_AGE_SECONDS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "gravity_pipeline_queue_age_seconds",
+        "How long the oldest block waiting for execution or commit has been waiting, 0 if none",
+        &["queue"]
+    )
+    .unwrap()
+});
+
+fn observe(watermarks: &Watermarks) {
+    for (stage, block_number) in [
+        ("ordered", watermarks.ordered),
+        ("executed", watermarks.executed),
+        ("committed", watermarks.committed),
+        ("persisted", watermarks.persisted),
+    ] {
+        PIPELINE_WATERMARK.with_label_values(&[stage]).set(block_number as i64);
+    }
+    for (queue, age) in [
+        ("unexecuted", watermarks.oldest_unexecuted_age),
+        ("uncommitted", watermarks.oldest_uncommitted_age),
+    ] {
+        PIPELINE_QUEUE_AGE_SECONDS
+            .with_label_values(&[queue])
+            .set(age.unwrap_or_default().as_secs_f64());
+    }
+}
+
+/// Publishes the watermarks of the block buffer as metrics until the node drops the buffer.
+pub fn spawn_watermark_metrics(block_buffer_manager: &Arc<BlockBufferManager>) -> Runtime {
+    let runtime = gaptos::aptos_runtimes::spawn_named_runtime("Watermarks".into(), Some(1));
+    let weak = Arc::downgrade(block_buffer_manager);
+    runtime.spawn(async move {
+        let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(manager) = weak.upgrade() else { break };
+            if let Some(watermarks) = manager.watermarks().await {
+                observe(&watermarks);
+            }
+        }
+    });
+    runtime
+}
@@ -2,15 +2,18 @@ use anyhow::format_err;
 use aptos_executor_types::StateComputeResult;
 use gaptos::{
     api_types::{self, account::ExternalAccountAddress, u256_define::TxnHash},
+    aptos_crypto::HashValue,
+    aptos_metrics_core::{register_int_counter, IntCounter},
     aptos_types::{
-        block_info::EpochBlockInfo, epoch_state::EpochState, idl::convert_validator_set,
+        account_address::AccountAddress, block_info::EpochBlockInfo, epoch_state::EpochState,
+        idl::convert_validator_set,
     },
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     sync::{
         atomic::{AtomicU8, Ordering},
-        Arc,
+        Arc, OnceLock,
     },
     time::{Duration, SystemTime},
 };
@@ -31,9 +34,25 @@ use gaptos::api_types::{
     ExternalBlock, VerifiedTxn, VerifiedTxnWithAccountSeqNum,
 };
 
+/// Blocks ordered at or below the committed height whose committed id is no longer known, so
+/// they cannot be checked against the committed chain.
+fn unknown_committed_blocks() -> &'static IntCounter {
+    static COUNTER: OnceLock<IntCounter> = OnceLock::new();
+    COUNTER.get_or_init(|| {
+        register_int_counter!(
+            "block_buffer_manager_unknown_committed_blocks_total",
+            "Blocks ordered at or below the committed height with an id that cannot be checked"
+        )
+        .unwrap()
+    })
+}
+
 // Type alias to reduce complexity
 type TxFilterFn = Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>;
 
+/// Called once with whether the pool admitted an external transaction.
+pub type OnTxnVerified = Box<dyn FnOnce(bool) + Send>;
+
 pub struct TxnItem {
     pub txns: Vec<VerifiedTxnWithAccountSeqNum>,
     pub gas_limit: u64,
@@ -64,7 +83,72 @@ pub trait TxPool: Send + Sync + 'static {
     // add external txns to the tx pool
     fn add_external_txn(&self, txns: VerifiedTxn) -> bool;
 
+    /// Adds a transaction for pools that verify it after the call returns. Returns false if it
+    /// was rejected right away, and calls `on_verified` once the pool has admitted or rejected
+    /// it.
+    fn add_external_txn_deferred(&self, txn: VerifiedTxn, on_verified: OnTxnVerified) -> bool {
+        let added = self.add_external_txn(txn);
+        on_verified(added);
+        added
+    }
+
     fn remove_txns(&self, txns: Vec<VerifiedTxn>);
+
+    /// Called when the node enters a new epoch, so the pool can re-validate transactions that
+    /// were admitted against the previous epoch's on-chain config.
+    fn on_new_epoch(&self, _epoch: u64) {}
+
+    /// Price per unit of gas the transaction pays, used by fee-priority batch formation in the
+    /// quorum store. Pools that cannot price a transaction return 0.
+    fn gas_unit_price(&self, _txn: &VerifiedTxn) -> u64 {
+        0
+    }
+
+    /// Number of pooled transactions by state, for the mempool inspection API. Pools that do
+    /// not track it return `None`.
+    fn stats(&self) -> Option<TxPoolStats> {
+        None
+    }
+
+    /// [`TxPool::stats`] without the age of the oldest transaction, cheap enough for the
+    /// readiness probe. Pools that can count their transactions without walking them override it.
+    fn size(&self) -> Option<TxPoolStats> {
+        self.stats()
+    }
+
+    /// Pooled transactions of `sender` ordered by nonce, for the mempool inspection API. Pools
+    /// that do not track it return `None`.
+    fn sender_txns(&self, _sender: &ExternalAccountAddress) -> Option<Vec<PooledTxn>> {
+        None
+    }
+
+    /// Nonce of the next transaction of `sender` on chain, for the mempool inspection API.
+    /// Pools that cannot read the chain state return `None`.
+    fn committed_nonce(&self, _sender: &ExternalAccountAddress) -> Option<u64> {
+        None
+    }
+}
+
+/// Number of pooled transactions by state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxPoolStats {
+    /// Transactions that can be included in the next block.
+    pub pending: usize,
+    /// Transactions waiting for a lower nonce of their sender.
+    pub parked: usize,
+    /// Time since the oldest pending or parked transaction was added.
+    pub oldest_txn_age: Option<Duration>,
+}
+
+/// A pooled transaction of one sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PooledTxn {
+    pub hash: TxnHash,
+    pub nonce: u64,
+    /// Waiting for a lower nonce of the sender.
+    pub parked: bool,
+    /// Time since the transaction was added to the pool.
+    pub age: Duration,
 }
 
 pub struct EmptyTxPool {}
@@ -99,11 +183,64 @@ impl TxPool for EmptyTxPool {
     fn remove_txns(&self, _txns: Vec<VerifiedTxn>) {}
 }
 
+/// Where the proposal of a block is being built or voted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTxnContext {
+    pub epoch: u64,
+    pub round: u64,
+    pub timestamp_usecs: u64,
+    pub parent_id: BlockId,
+}
+
+/// Source of the system transactions consensus injects into blocks, e.g. deposits bridged from
+/// another chain or epoch-boundary maintenance. Unlike user transactions they do not go through
+/// the mempool: the proposer asks its provider for them and every voter checks them against its
+/// own provider, so a block only carries what the providers of a quorum accepted.
+///
+/// Only used while the `system_txns` protocol feature is active. The execution layer receives
+/// them in [`BlockExecutionMeta::system_txns`].
+pub trait SystemTxnProvider: Send + Sync + 'static {
+    /// System transactions to propose in a block, in execution order. Opaque to consensus.
+    fn system_txns(&self, ctx: &SystemTxnContext) -> Vec<Vec<u8>>;
+
+    /// Fails if a proposal must not carry `txns`. Called for every proposal with system
+    /// transactions before voting for it.
+    fn verify(&self, ctx: &SystemTxnContext, txns: &[Vec<u8>]) -> anyhow::Result<()>;
+}
+
+struct RegisteredSystemTxnProvider(Arc<BlockBufferManager>);
+
+impl SystemTxnProvider for RegisteredSystemTxnProvider {
+    fn system_txns(&self, ctx: &SystemTxnContext) -> Vec<Vec<u8>> {
+        self.0.system_txn_provider().map_or_else(Vec::new, |provider| provider.system_txns(ctx))
+    }
+
+    fn verify(&self, ctx: &SystemTxnContext, txns: &[Vec<u8>]) -> anyhow::Result<()> {
+        match self.0.system_txn_provider() {
+            Some(provider) => provider.verify(ctx, txns),
+            None => anyhow::bail!("no system txn provider is registered"),
+        }
+    }
+}
+
 pub struct TxnBuffer {
     // (txns, gas_limit)
     txns: Mutex<Vec<TxnItem>>,
 }
 
+/// Returned by `get_ordered_blocks` while the buffer switches epochs. The execution layer then
+/// calls `consume_epoch_change` and resumes after the epoch's last block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochChangeInProgress;
+
+impl std::fmt::Display for EpochChangeInProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Buffer is in epoch change")
+    }
+}
+
+impl std::error::Error for EpochChangeInProgress {}
+
 pub struct BlockHashRef {
     pub block_id: BlockId,
     pub num: u64,
@@ -123,12 +260,88 @@ impl BlockKey {
     }
 }
 
+/// Per-block inputs for the execution layer that `ExternalBlockMeta` has no field for.
+///
+/// Consensus resolves these while the block's epoch is current and hands them over with the
+/// ordered block, so execution does not need to look them up in process-wide maps that may
+/// already have moved on to the next epoch. Per-block randomness already travels in
+/// `ExternalBlockMeta::randomness`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockExecutionMeta {
+    /// Reth address of the block proposer. `None` for NIL blocks or when the proposer has no
+    /// registered reth address.
+    pub proposer_reth_address: Option<[u8; 20]>,
+    /// When consensus needs the execution result to keep up with its rounds. Stamped by the
+    /// block buffer manager when the block is ordered, see
+    /// `BlockBufferManagerConfig::execution_budget`. A block whose result comes back past it
+    /// is logged in `set_compute_res`.
+    pub soft_deadline: Option<SystemTime>,
+    /// System transactions injected by consensus, to execute before the user transactions of
+    /// the block. See [`SystemTxnProvider`].
+    pub system_txns: Vec<Vec<u8>>,
+}
+
+impl BlockExecutionMeta {
+    pub fn past_soft_deadline(&self) -> bool {
+        self.soft_deadline.is_some_and(|deadline| SystemTime::now() > deadline)
+    }
+}
+
+/// How the execution layer fared with one block, fed back into payload sizing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub block_number: u64,
+    pub txns: u64,
+    /// Gas used by the block.
+    pub gas: u64,
+    /// From the block starting execution, once it was handed to the execution layer and its
+    /// parent's result came back, to its result. Time spent queued behind the parent is not
+    /// counted.
+    pub execution_time: Duration,
+}
+
+/// Highest block number that reached each stage of the pipeline, and how long the oldest block
+/// of each queue between two stages has been waiting, see [`BlockBufferManager::watermarks`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Watermarks {
+    pub ordered: u64,
+    pub executed: u64,
+    pub committed: u64,
+    /// Persisted by the execution layer.
+    pub persisted: u64,
+    /// Since the oldest ordered block without an execution result was ordered.
+    pub oldest_unexecuted_age: Option<Duration>,
+    /// Since the oldest executed block not committed yet got its execution result.
+    pub oldest_uncommitted_age: Option<Duration>,
+}
+
+/// Notifications for embedders, see [`BlockBufferManager::subscribe_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsensusEvent {
+    /// Consensus started the epoch.
+    EpochStarted { epoch: u64 },
+    /// The validators of `epoch` differ from those of the previous epoch, in validator order.
+    ValidatorSetUpdated { epoch: u64, validators: Vec<(AccountAddress, u64)> },
+    /// Blocks up to `block_number` are committed.
+    CommitAdvanced { epoch: u64, block_number: u64 },
+    /// Executing `block_number` of `epoch` ends the epoch, `next_epoch` starts after it.
+    ReconfigurationDetected { epoch: u64, block_number: u64, next_epoch: u64 },
+}
+
+/// Events a subscriber may fall behind by before it misses some.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Execution reports kept for payload sizing, see
+/// [`BlockBufferManager::recent_execution_reports`].
+const MAX_EXECUTION_REPORTS: usize = 256;
+
 #[derive(Debug)]
 pub enum BlockState {
     Ordered {
         block: ExternalBlock,
         parent_id: BlockId,
         round: u64,
+        execution_meta: BlockExecutionMeta,
     },
     Computed {
         id: BlockId,
@@ -197,6 +410,21 @@ pub struct BlockStateMachine {
     /// Set after `release_inflight_blocks` has advanced `current_epoch` and pruned suffix blocks.
     /// This flag is consumed by the reth execution loop through `consume_epoch_change`.
     epoch_change_ready: bool,
+    /// Ordered blocks the execution layer has already executed, kept until they are persisted so
+    /// they can be handed out again by `request_redelivery`.
+    executed_blocks: HashMap<BlockKey, (ExternalBlock, BlockId, BlockExecutionMeta)>,
+    /// Inclusive block number range of the current epoch that `get_ordered_blocks` serves from
+    /// `executed_blocks` after a re-delivery request.
+    redelivery: Option<(u64, u64)>,
+    /// Id and timestamp of the latest ordered block, to check that its child does not go back
+    /// in time.
+    latest_ordered_timestamp: Option<(BlockId, u64)>,
+    /// Payloads the execution layer attached to blocks not committed yet, see
+    /// `set_commit_payload`.
+    commit_payloads: HashMap<BlockKey, Vec<u8>>,
+    /// Epoch whose compute results carry the hash of their commit payload, see
+    /// `set_signed_commit_payloads`.
+    signed_commit_payload_epoch: Option<u64>,
 }
 
 impl BlockStateMachine {
@@ -216,6 +444,18 @@ impl BlockStateMachine {
             })
     }
 
+    /// The retained block at `key`, if it falls in the requested re-delivery range.
+    fn redelivered_block(
+        &self,
+        key: BlockKey,
+    ) -> Option<&(ExternalBlock, BlockId, BlockExecutionMeta)> {
+        let (from, until) = self.redelivery?;
+        if key.epoch != self.current_epoch || !(from..=until).contains(&key.block_number) {
+            return None;
+        }
+        self.executed_blocks.get(&key)
+    }
+
     /// Record a profile measurement for the given block key.
     fn record_profile(&mut self, key: BlockKey, f: impl FnOnce(&mut BlockProfile)) {
         f(self.profile.entry(key).or_default());
@@ -236,11 +476,41 @@ pub struct EpochChangeCache {
     pub epoch_state: EpochState,
 }
 
+/// Used unless BLOCK_BUFFER_MAX_REORG_DEPTH is set, see `BlockBufferManagerConfig::from_env`.
+const DEFAULT_MAX_REORG_DEPTH: u64 = 256;
+
 pub struct BlockBufferManagerConfig {
     pub wait_for_change_timeout: Duration,
     pub max_wait_timeout: Duration,
     pub remove_committed_blocks_interval: Duration,
     pub max_block_size: usize,
+    /// How many executed blocks the execution layer may rewind and re-execute at once through
+    /// `request_redelivery`.
+    pub max_reorg_depth: u64,
+    /// Time the execution layer has for a block once it is ordered. `None` leaves blocks
+    /// without a soft deadline.
+    pub execution_budget: Option<Duration>,
+}
+
+impl BlockBufferManagerConfig {
+    /// The default config with the tunables set in the environment:
+    /// - BLOCK_BUFFER_MAX_REORG_DEPTH: `max_reorg_depth`.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(depth) =
+            std::env::var("BLOCK_BUFFER_MAX_REORG_DEPTH").ok().and_then(|s| s.parse::<u64>().ok())
+        {
+            config.max_reorg_depth = depth;
+        }
+        config
+    }
+
+    /// Derives the execution budget from the consensus round timeout. The second half of the
+    /// round is left for signing and aggregating the commit votes.
+    pub fn with_round_timeout(mut self, round_timeout: Duration) -> Self {
+        self.execution_budget = Some(round_timeout / 2);
+        self
+    }
 }
 
 impl Default for BlockBufferManagerConfig {
@@ -250,6 +520,8 @@ impl Default for BlockBufferManagerConfig {
             max_wait_timeout: Duration::from_secs(5),
             remove_committed_blocks_interval: Duration::from_secs(1),
             max_block_size: 256,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            execution_budget: None,
         }
     }
 }
@@ -269,6 +541,16 @@ impl Default for BlockBufferManagerConfig {
 ///
 /// The divergence window is bounded by network propagation + processing time,
 /// typically under 1 second.
+///
+/// # Finality
+///
+/// Blocks up to `latest_commit_block_number` are final for both layers:
+///
+/// 1. Consensus never orders a different block at a committed height. `set_ordered_blocks` rejects
+///    such a block instead of handing it to the execution layer.
+/// 2. The execution layer never rewinds into committed blocks. `request_redelivery` rejects a start
+///    at or below the committed height, and a rewind deeper than
+///    `BlockBufferManagerConfig::max_reorg_depth` blocks.
 pub struct BlockBufferManager {
     txn_buffer: TxnBuffer,
     block_state_machine: Mutex<BlockStateMachine>,
@@ -276,6 +558,10 @@ pub struct BlockBufferManager {
     config: BlockBufferManagerConfig,
     // latest_epoch_change_block_number moved into BlockStateMachine
     ready_notifier: Arc<Notify>,
+    /// Latest execution reports, oldest first. Read synchronously by the proposal generator.
+    execution_reports: std::sync::Mutex<VecDeque<ExecutionReport>>,
+    events: tokio::sync::broadcast::Sender<ConsensusEvent>,
+    system_txn_provider: std::sync::RwLock<Option<Arc<dyn SystemTxnProvider>>>,
 }
 
 impl BlockBufferManager {
@@ -295,18 +581,28 @@ impl BlockBufferManager {
                 latest_epoch_change_block_number: 0,
                 epoch_change_block_info: None,
                 epoch_change_ready: false,
+                executed_blocks: HashMap::new(),
+                redelivery: None,
+                latest_ordered_timestamp: None,
+                commit_payloads: HashMap::new(),
+                signed_commit_payload_epoch: None,
             }),
             buffer_state: AtomicU8::new(BufferState::Uninitialized as u8),
             config,
             ready_notifier: Arc::new(Notify::new()),
+            execution_reports: Default::default(),
+            events: tokio::sync::broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            system_txn_provider: Default::default(),
         };
         let block_buffer_manager = Arc::new(block_buffer_manager);
-        let clone = block_buffer_manager.clone();
-        // spawn task to remove committed blocks
+        let weak = Arc::downgrade(&block_buffer_manager);
+        let interval = block_buffer_manager.config.remove_committed_blocks_interval;
+        // spawn task to remove committed blocks, until the owning node drops the manager
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(clone.config.remove_committed_blocks_interval).await;
-                clone.remove_committed_blocks().await.unwrap();
+                tokio::time::sleep(interval).await;
+                let Some(manager) = weak.upgrade() else { break };
+                manager.remove_committed_blocks().await.unwrap();
             }
         });
         block_buffer_manager
@@ -321,6 +617,12 @@ impl BlockBufferManager {
         info!("remove_committed_blocks latest_persist_block_num: {:?}", latest_persist_block_num);
         block_state_machine.blocks.retain(|key, _| key.block_number >= latest_persist_block_num);
         block_state_machine.profile.retain(|key, _| key.block_number >= latest_persist_block_num);
+        block_state_machine
+            .executed_blocks
+            .retain(|key, _| key.block_number >= latest_persist_block_num);
+        block_state_machine
+            .commit_payloads
+            .retain(|key, _| key.block_number >= latest_persist_block_num);
         let _ = block_state_machine.sender.send(());
         Ok(())
     }
@@ -536,6 +838,7 @@ impl BlockBufferManager {
         parent_id: BlockId,
         block: ExternalBlock,
         round: u64,
+        mut execution_meta: BlockExecutionMeta,
     ) -> Result<(), anyhow::Error> {
         self.wait_until_ready().await;
         info!(
@@ -598,6 +901,40 @@ impl BlockBufferManager {
             return Ok(());
         }
         let block_num = block.block_meta.block_number;
+        if block_num <= block_state_machine.latest_commit_block_number {
+            match block_state_machine.block_number_to_block_id.get(&block_num) {
+                Some(committed_id) if *committed_id != block.block_meta.block_id => {
+                    let msg = format!(
+                        "set_ordered_blocks: block {} {:?} conflicts with committed block {:?} \
+                         (latest committed block: {})",
+                        block_num,
+                        block.block_meta.block_id,
+                        committed_id,
+                        block_state_machine.latest_commit_block_number
+                    );
+                    warn!("{}", msg);
+                    return Err(anyhow::anyhow!("{msg}"));
+                }
+                // Replayed after a restart, the execution layer already has it
+                Some(_) => warn!(
+                    "set_ordered_blocks: ignoring block {} at or below the latest committed \
+                     block {}",
+                    block_num, block_state_machine.latest_commit_block_number
+                ),
+                // The committed id was pruned or never known, so a conflict would go unnoticed
+                None => {
+                    unknown_committed_blocks().inc();
+                    warn!(
+                        "set_ordered_blocks: ignoring block {} {:?} at or below the latest \
+                         committed block {} without a known committed id to check it against",
+                        block_num,
+                        block.block_meta.block_id,
+                        block_state_machine.latest_commit_block_number
+                    );
+                }
+            }
+            return Ok(());
+        }
         // Try to find parent in current epoch first, then try previous epoch
         // Guard against underflow when block_number == 0
         let actual_parent_id = if let Some(parent_block_num) = block_num.checked_sub(1) {
@@ -630,9 +967,32 @@ impl BlockBufferManager {
             actual_parent_id
         };
 
-        block_state_machine
-            .blocks
-            .insert(block_key, BlockState::Ordered { block: block.clone(), parent_id, round });
+        // Only checked against the parent, never against the local clock, so that every node
+        // hands the execution layer the same blocks. The EVM `TIMESTAMP` must not decrease.
+        // Nil and reconfiguration suffix blocks keep the timestamp of their parent. Blocks
+        // ordered again returned above, and the parent is matched by id, so a re-delivery or a
+        // new fork is never compared with a block that is not its parent.
+        if let Some((latest_id, latest_usecs)) = block_state_machine.latest_ordered_timestamp {
+            if latest_id == parent_id && block.block_meta.usecs < latest_usecs {
+                let msg = format!(
+                    "set_ordered_blocks: block {} has timestamp {} before its parent's {}",
+                    block_num, block.block_meta.usecs, latest_usecs
+                );
+                warn!("{}", msg);
+                return Err(anyhow::anyhow!("{msg}"));
+            }
+        }
+        block_state_machine.latest_ordered_timestamp =
+            Some((block.block_meta.block_id, block.block_meta.usecs));
+
+        if execution_meta.soft_deadline.is_none() {
+            execution_meta.soft_deadline =
+                self.config.execution_budget.map(|budget| SystemTime::now() + budget);
+        }
+        block_state_machine.blocks.insert(
+            block_key,
+            BlockState::Ordered { block: block.clone(), parent_id, round, execution_meta },
+        );
 
         // Record time for set_ordered_blocks
         let profile =
@@ -648,7 +1008,7 @@ impl BlockBufferManager {
         start_num: u64,
         max_size: Option<usize>,
         expected_epoch: u64,
-    ) -> Result<Vec<(ExternalBlock, BlockId)>, anyhow::Error> {
+    ) -> Result<Vec<(ExternalBlock, BlockId, BlockExecutionMeta)>, anyhow::Error> {
         self.wait_until_ready().await;
 
         let start = Instant::now();
@@ -673,7 +1033,7 @@ impl BlockBufferManager {
             if block_state_machine.epoch_change_ready ||
                 block_state_machine.current_epoch != expected_epoch
             {
-                return Err(anyhow::anyhow!("Buffer is in epoch change"));
+                return Err(EpochChangeInProgress.into());
             }
 
             // get block num, block num + 1
@@ -682,17 +1042,21 @@ impl BlockBufferManager {
             loop {
                 let block_key = BlockKey::new(expected_epoch, current_num);
                 match block_state_machine.blocks.get(&block_key) {
-                    Some(BlockState::Ordered { block, parent_id, .. }) => {
-                        result.push((block.clone(), *parent_id));
+                    Some(BlockState::Ordered { block, parent_id, execution_meta, .. }) => {
+                        result.push((block.clone(), *parent_id, execution_meta.clone()));
                         // Record time for get_ordered_blocks
                         block_state_machine.record_profile(block_key, |p| {
                             p.get_ordered_blocks_time = Some(SystemTime::now());
                         });
                     }
                     Some(_state) => {
-                        // Block exists but is not Ordered (e.g., already Computed as a suffix
-                        // after epoch change). Stop collecting — don't error, just break.
-                        break;
+                        // Already executed once. Only hand it out again if the execution layer
+                        // asked for it; otherwise (e.g. a suffix block already Computed after
+                        // epoch change) stop collecting — don't error, just break.
+                        match block_state_machine.redelivered_block(block_key) {
+                            Some(redelivered) => result.push(redelivered.clone()),
+                            None => break,
+                        }
                     }
                     None => {
                         // No more blocks available
@@ -911,7 +1275,7 @@ impl BlockBufferManager {
         block_id: BlockId,
         block_timestamp_usecs: u64,
         block_round: u64,
-        block_hash: [u8; 32],
+        block_hash: HashValue,
         epoch_state: EpochState,
     ) {
         // Store the epoch change block's info so suffix blocks and
@@ -922,7 +1286,7 @@ impl BlockBufferManager {
                 block_number: epoch_change_block_num,
                 epoch_start_round: block_round,
                 epoch_start_timestamp_usecs: block_timestamp_usecs,
-                block_hash: gaptos::aptos_crypto::HashValue::new(block_hash),
+                block_hash,
             },
             epoch_state: epoch_state.clone(),
         });
@@ -998,15 +1362,33 @@ impl BlockBufferManager {
                 .calculate_new_epoch_state(&events, block_num, &mut block_state_machine)
                 .await?;
             let epoch_change_state = new_epoch_state.clone();
-            let compute_result = StateComputeResult::new(
+            let mut compute_result = StateComputeResult::new(
                 ComputeRes { data: block_hash, txn_num: txn_len as u64, txn_status, events },
                 new_epoch_state,
                 None,
             );
-            block_state_machine.blocks.insert(
-                block_key,
-                BlockState::Computed { id: block_id, compute_result: compute_result.clone() },
-            );
+            if block_state_machine.signed_commit_payload_epoch == Some(epoch) {
+                if let Some(payload) = block_state_machine.commit_payloads.get(&block_key) {
+                    compute_result =
+                        compute_result.with_commit_payload_hash(HashValue::sha3_256_of(payload));
+                }
+            }
+            if let Some(BlockState::Ordered { block, parent_id, execution_meta, .. }) =
+                block_state_machine.blocks.insert(
+                    block_key,
+                    BlockState::Computed { id: block_id, compute_result: compute_result.clone() },
+                )
+            {
+                if execution_meta.past_soft_deadline() {
+                    warn!(
+                        "set_compute_res: block {} missed its soft execution deadline {:?}",
+                        block_num, execution_meta.soft_deadline
+                    );
+                }
+                block_state_machine
+                    .executed_blocks
+                    .insert(block_key, (block, parent_id, execution_meta));
+            }
 
             // Record time for set_compute_res
             block_state_machine.record_profile(block_key, |p| {
@@ -1032,6 +1414,11 @@ impl BlockBufferManager {
             // these suffix blocks. Reth will independently detect the stale epoch and silently
             // discard them without sending any ExecutionResult, so there is no conflict.
             if let Some(epoch_state) = epoch_change_state {
+                self.publish_event(ConsensusEvent::ReconfigurationDetected {
+                    epoch,
+                    block_number: block_num,
+                    next_epoch: epoch_state.epoch,
+                });
                 Self::handle_epoch_change_suffix_blocks(
                     &mut block_state_machine,
                     block_num,
@@ -1039,7 +1426,7 @@ impl BlockBufferManager {
                     block_id,
                     block_timestamp_usecs,
                     block_round,
-                    block_hash,
+                    compute_result.root_hash(),
                     epoch_state,
                 );
             }
@@ -1047,11 +1434,120 @@ impl BlockBufferManager {
             let _ = block_state_machine.sender.send(());
             return Ok(());
         }
+        if block_state_machine.redelivered_block(block_key).is_some() {
+            return Self::check_redelivered_compute_res(
+                &mut block_state_machine,
+                block_key,
+                block_id,
+                block_hash,
+            );
+        }
         Err(anyhow::anyhow!(
             "There is no Ordered Block but try to push compute result for block {block_id:?}"
         ))
     }
 
+    /// Accepts the result of re-executing a re-delivered block. The block already went through
+    /// the pipeline, so the result is only checked against the first execution.
+    fn check_redelivered_compute_res(
+        block_state_machine: &mut BlockStateMachine,
+        block_key: BlockKey,
+        block_id: BlockId,
+        block_hash: [u8; 32],
+    ) -> Result<(), anyhow::Error> {
+        let (id, previous_hash) = match block_state_machine.blocks.get(&block_key) {
+            Some(BlockState::Computed { id, compute_result }) |
+            Some(BlockState::Committed { id, compute_result, .. }) => {
+                (*id, compute_result.execution_output.data)
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "set_compute_res: re-delivered block {} is no longer executed",
+                    block_key.block_number
+                ))
+            }
+        };
+        if id != block_id {
+            return Err(anyhow::anyhow!(
+                "set_compute_res: block id mismatch: expected {:?}, got {:?}",
+                id,
+                block_id
+            ));
+        }
+        if previous_hash != block_hash {
+            return Err(anyhow::anyhow!(
+                "set_compute_res: re-executing block {} produced hash {:?}, previously {:?}",
+                block_key.block_number,
+                BlockId::from_bytes(block_hash.as_slice()),
+                BlockId::from_bytes(previous_hash.as_slice()),
+            ));
+        }
+        info!("set_compute_res: re-delivered block {} re-executed", block_key.block_number);
+        if block_state_machine.redelivery.is_some_and(|(_, until)| until == block_key.block_number)
+        {
+            info!("re-delivery finished at block {}", block_key.block_number);
+            block_state_machine.redelivery = None;
+        }
+        Ok(())
+    }
+
+    /// Hands blocks from `from_block_number` on to the execution layer again, including those it
+    /// has already executed. For an execution layer that restarted and lost blocks it had
+    /// executed but not persisted; call it before resuming `get_ordered_blocks` at
+    /// `from_block_number`. Returns how many executed blocks will be re-delivered.
+    ///
+    /// Fails if some of these blocks are no longer buffered; they were persisted by the
+    /// execution layer, so asking for them means its storage went backwards.
+    pub async fn request_redelivery(&self, from_block_number: u64) -> Result<usize, anyhow::Error> {
+        self.wait_until_ready().await;
+        let mut block_state_machine = self.block_state_machine.lock().await;
+        if from_block_number <= block_state_machine.latest_commit_block_number {
+            return Err(anyhow::anyhow!(
+                "request_redelivery: block {} is at or below the latest committed block {}",
+                from_block_number,
+                block_state_machine.latest_commit_block_number
+            ));
+        }
+        let epoch = block_state_machine.current_epoch;
+        let until = block_state_machine
+            .executed_blocks
+            .keys()
+            .filter(|key| key.epoch == epoch && key.block_number >= from_block_number)
+            .map(|key| key.block_number)
+            .max();
+        let Some(until) = until else {
+            block_state_machine.redelivery = None;
+            return Ok(0);
+        };
+        if until - from_block_number >= self.config.max_reorg_depth {
+            return Err(anyhow::anyhow!(
+                "request_redelivery: rewinding blocks {}..={} exceeds the max reorg depth {}",
+                from_block_number,
+                until,
+                self.config.max_reorg_depth
+            ));
+        }
+        for block_number in from_block_number..=until {
+            if !block_state_machine
+                .executed_blocks
+                .contains_key(&BlockKey::new(epoch, block_number))
+            {
+                return Err(anyhow::anyhow!(
+                    "request_redelivery: block {} of epoch {} is no longer buffered",
+                    block_number,
+                    epoch
+                ));
+            }
+        }
+        info!(
+            "request_redelivery: re-delivering blocks {}..={} of epoch {}",
+            from_block_number, until, epoch
+        );
+        block_state_machine.redelivery = Some((from_block_number, until));
+        let _ = block_state_machine.sender.send(());
+        Ok((until - from_block_number + 1) as usize)
+    }
+
     pub async fn set_commit_blocks(
         &self,
         block_ids: &[BlockHashRef],
@@ -1059,6 +1555,7 @@ impl BlockBufferManager {
     ) -> Result<Vec<Receiver<()>>, anyhow::Error> {
         self.wait_until_ready().await;
         let mut persist_notifiers = Vec::new();
+        let mut committed = None;
         let mut block_state_machine = self.block_state_machine.lock().await;
         for block_id_num_hash in block_ids {
             info!(
@@ -1083,13 +1580,21 @@ impl BlockBufferManager {
                                 persist_notifier = Some(tx);
                                 persist_notifiers.push(rx);
                             }
+                            // The ledger info carries the signed executed state id, the execution
+                            // layer is handed its own block hash
                             *state = BlockState::Committed {
-                                hash: block_id_num_hash.hash,
+                                hash: block_id_num_hash
+                                    .hash
+                                    .map(|_| compute_result.execution_output.data),
                                 compute_result: compute_result.clone(),
                                 id: block_id_num_hash.block_id,
                                 persist_notifier,
                             };
 
+                            if !is_suffix {
+                                committed = committed.max(Some(block_id_num_hash.num));
+                            }
+
                             // Record time for set_commit_blocks
                             block_state_machine.record_profile(block_key, |p| {
                                 p.set_commit_blocks_time = Some(SystemTime::now());
@@ -1155,9 +1660,123 @@ impl BlockBufferManager {
             }
         }
         let _ = block_state_machine.sender.send(());
+        if let Some(block_number) = committed {
+            self.publish_event(ConsensusEvent::CommitAdvanced { epoch, block_number });
+        }
         Ok(persist_notifiers)
     }
 
+    /// Whether the compute results of `epoch` carry the hash of their block's commit payload, so
+    /// the commit votes sign it. Set by consensus at the start of every epoch.
+    pub async fn set_signed_commit_payloads(&self, epoch: u64, signed: bool) {
+        let mut block_state_machine = self.block_state_machine.lock().await;
+        block_state_machine.signed_commit_payload_epoch = signed.then_some(epoch);
+    }
+
+    /// Attaches an application defined payload, e.g. a digest of the transaction outcomes, to an
+    /// ordered block. It must be attached before the compute result is reported: in an epoch
+    /// that signs commit payloads its hash becomes part of the signed executed state id.
+    /// Consensus persists it next to the ledger info that commits the block. A block re-delivered
+    /// after it was executed keeps the payload of its first execution.
+    pub async fn set_commit_payload(
+        &self,
+        block_id: BlockId,
+        block_num: u64,
+        epoch: u64,
+        payload: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        self.wait_until_ready().await;
+        let mut block_state_machine = self.block_state_machine.lock().await;
+        let block_key = BlockKey::new(epoch, block_num);
+        let Some(state) = block_state_machine.blocks.get(&block_key) else {
+            return Err(anyhow::anyhow!(
+                "set_commit_payload: no block {} of epoch {}",
+                block_num,
+                epoch
+            ));
+        };
+        if state.get_block_id() != block_id {
+            return Err(anyhow::anyhow!(
+                "set_commit_payload: block id mismatch for block {}: {:?} != {:?}",
+                block_num,
+                block_id,
+                state.get_block_id()
+            ));
+        }
+        if !matches!(state, BlockState::Ordered { .. }) {
+            if block_state_machine.redelivered_block(block_key).is_some() {
+                return Ok(());
+            }
+            return Err(anyhow::anyhow!(
+                "set_commit_payload: block {} of epoch {} is already executed",
+                block_num,
+                epoch
+            ));
+        }
+        block_state_machine.commit_payloads.insert(block_key, payload);
+        Ok(())
+    }
+
+    /// Removes the payloads attached to `blocks` and returns them by block number, for consensus
+    /// to persist when it commits them.
+    pub async fn take_commit_payloads(
+        &self,
+        blocks: &[BlockHashRef],
+        epoch: u64,
+    ) -> Vec<(u64, Vec<u8>)> {
+        let mut block_state_machine = self.block_state_machine.lock().await;
+        blocks
+            .iter()
+            .filter_map(|block| {
+                let payload =
+                    block_state_machine.commit_payloads.remove(&BlockKey::new(epoch, block.num))?;
+                Some((block.num, payload))
+            })
+            .collect()
+    }
+
+    /// Records how the execution layer fared with a block.
+    pub fn report_execution(&self, report: ExecutionReport) {
+        let mut reports = self.execution_reports.lock().unwrap();
+        if reports.len() == MAX_EXECUTION_REPORTS {
+            reports.pop_front();
+        }
+        reports.push_back(report);
+    }
+
+    /// Epoch, validator set and commit notifications. A subscriber that falls behind by more than
+    /// a few hundred events gets `RecvError::Lagged` and skips to the latest ones.
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<ConsensusEvent> {
+        self.events.subscribe()
+    }
+
+    /// Sends `event` to the current subscribers, if any.
+    pub fn publish_event(&self, event: ConsensusEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Registers the source of system transactions. Consensus looks it up for every proposal,
+    /// so it can be set before or after the consensus engine starts.
+    pub fn set_system_txn_provider(&self, provider: Arc<dyn SystemTxnProvider>) {
+        *self.system_txn_provider.write().unwrap() = Some(provider);
+    }
+
+    pub fn system_txn_provider(&self) -> Option<Arc<dyn SystemTxnProvider>> {
+        self.system_txn_provider.read().unwrap().clone()
+    }
+
+    /// Provider forwarding to the one registered at the time of each call. Until one is
+    /// registered it proposes no system transactions and rejects every proposal carrying some.
+    pub fn registered_system_txn_provider(self: &Arc<Self>) -> Arc<dyn SystemTxnProvider> {
+        Arc::new(RegisteredSystemTxnProvider(self.clone()))
+    }
+
+    /// The `len` latest execution reports, oldest first.
+    pub fn recent_execution_reports(&self, len: usize) -> Vec<ExecutionReport> {
+        let reports = self.execution_reports.lock().unwrap();
+        reports.iter().skip(reports.len().saturating_sub(len)).copied().collect()
+    }
+
     pub async fn get_committed_blocks(
         &self,
         start_num: u64,
@@ -1257,6 +1876,72 @@ impl BlockBufferManager {
         block_state_machine.latest_commit_block_number
     }
 
+    /// Highest block the execution layer has persisted.
+    pub async fn latest_finalized_block_number(&self) -> u64 {
+        let block_state_machine = self.block_state_machine.lock().await;
+        block_state_machine.latest_finalized_block_number
+    }
+
+    /// How far each stage of the pipeline got, the one place to tell how far execution or
+    /// commit is behind. `None` until the buffer is initialized, so that health probes do not
+    /// wait for it.
+    pub async fn watermarks(&self) -> Option<Watermarks> {
+        if !self.is_ready() {
+            return None;
+        }
+        let block_state_machine = self.block_state_machine.lock().await;
+        Some(Self::watermarks_of(&block_state_machine))
+    }
+
+    /// [`Self::watermarks`], waiting for the buffer to be initialized.
+    pub async fn ready_watermarks(&self) -> Watermarks {
+        self.wait_until_ready().await;
+        let block_state_machine = self.block_state_machine.lock().await;
+        Self::watermarks_of(&block_state_machine)
+    }
+
+    fn watermarks_of(block_state_machine: &BlockStateMachine) -> Watermarks {
+        let base = block_state_machine.latest_commit_block_number;
+        let mut watermarks = Watermarks {
+            ordered: base,
+            executed: base,
+            committed: base,
+            persisted: block_state_machine.latest_finalized_block_number,
+            oldest_unexecuted_age: None,
+            oldest_uncommitted_age: None,
+        };
+        let now = SystemTime::now();
+        let age = |since: Option<SystemTime>| {
+            since.map(|since| now.duration_since(since).unwrap_or_default())
+        };
+        for (key, state) in &block_state_machine.blocks {
+            let number = key.block_number;
+            let profile = block_state_machine.profile.get(key);
+            match state {
+                BlockState::Ordered { .. } => {
+                    watermarks.ordered = watermarks.ordered.max(number);
+                    let waiting = age(profile.and_then(|p| p.set_ordered_block_time));
+                    watermarks.oldest_unexecuted_age =
+                        watermarks.oldest_unexecuted_age.max(waiting);
+                }
+                BlockState::Computed { .. } => {
+                    watermarks.ordered = watermarks.ordered.max(number);
+                    watermarks.executed = watermarks.executed.max(number);
+                    let waiting = age(profile.and_then(|p| p.set_compute_res_time));
+                    watermarks.oldest_uncommitted_age =
+                        watermarks.oldest_uncommitted_age.max(waiting);
+                }
+                BlockState::Committed { .. } => {
+                    watermarks.ordered = watermarks.ordered.max(number);
+                    watermarks.executed = watermarks.executed.max(number);
+                    watermarks.committed = watermarks.committed.max(number);
+                }
+                BlockState::Historical { .. } => {}
+            }
+        }
+        watermarks
+    }
+
     pub async fn block_number_to_block_id(&self) -> HashMap<u64, BlockId> {
         self.wait_until_ready().await;
         let block_state_machine = self.block_state_machine.lock().await;
@@ -1335,6 +2020,13 @@ impl BlockBufferManager {
         block_state_machine
             .profile
             .retain(|key, _| key.block_number <= latest_epoch_change_block_number);
+        block_state_machine
+            .executed_blocks
+            .retain(|key, _| key.block_number <= latest_epoch_change_block_number);
+        block_state_machine
+            .commit_payloads
+            .retain(|key, _| key.block_number <= latest_epoch_change_block_number);
+        block_state_machine.redelivery = None;
         block_state_machine.epoch_change_ready = true;
         self.buffer_state.store(BufferState::EpochChange as u8, Ordering::SeqCst);
         let _ = block_state_machine.sender.send(());
@@ -1344,6 +2036,7 @@ impl BlockBufferManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gaptos::api_types::ExternalBlockMeta;
     use tokio::time::{sleep, timeout};
 
     fn test_config() -> BlockBufferManagerConfig {
@@ -1352,6 +2045,26 @@ mod tests {
             max_wait_timeout: Duration::from_millis(100),
             remove_committed_blocks_interval: Duration::from_secs(60),
             max_block_size: 256,
+            max_reorg_depth: 256,
+            execution_budget: None,
+        }
+    }
+
+    fn test_block(block_number: u8) -> ExternalBlock {
+        ExternalBlock {
+            block_meta: ExternalBlockMeta {
+                block_id: BlockId([block_number; 32]),
+                block_number: block_number as u64,
+                usecs: 0,
+                epoch: 1,
+                randomness: None,
+                block_hash: None,
+                proposer_index: None,
+                failed_proposer_indices: vec![],
+            },
+            txns: vec![],
+            extra_data: vec![],
+            enable_randomness: false,
         }
     }
 
@@ -1459,4 +2172,391 @@ mod tests {
         assert_eq!(block_state_machine.latest_commit_block_number, 1);
         assert_eq!(block_state_machine.latest_finalized_block_number, 1);
     }
+
+    #[tokio::test]
+    async fn request_redelivery_hands_out_executed_blocks_again() {
+        let manager = BlockBufferManager::new(test_config());
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+
+        let mut parent_id = BlockId([0; 32]);
+        for block_number in 1..=2u8 {
+            let block = ExternalBlock {
+                block_meta: ExternalBlockMeta {
+                    block_id: BlockId([block_number; 32]),
+                    block_number: block_number as u64,
+                    usecs: 0,
+                    epoch: 1,
+                    randomness: None,
+                    block_hash: None,
+                    proposer_index: None,
+                    failed_proposer_indices: vec![],
+                },
+                txns: vec![],
+                extra_data: vec![],
+                enable_randomness: false,
+            };
+            manager
+                .set_ordered_blocks(parent_id, block, block_number as u64, Default::default())
+                .await
+                .unwrap();
+            parent_id = BlockId([block_number; 32]);
+        }
+        for block_number in 1..=2u8 {
+            let blocks = manager.get_ordered_blocks(block_number as u64, Some(1), 1).await.unwrap();
+            assert_eq!(blocks.len(), 1);
+            manager
+                .set_compute_res(
+                    BlockId([block_number; 32]),
+                    [block_number; 32],
+                    block_number as u64,
+                    1,
+                    Arc::new(None),
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(manager.request_redelivery(1).await.unwrap(), 2);
+        let blocks = manager.get_ordered_blocks(1, None, 1).await.unwrap();
+        let numbers: Vec<u64> = blocks.iter().map(|(b, _, _)| b.block_meta.block_number).collect();
+        assert_eq!(numbers, vec![1, 2]);
+
+        let error = manager
+            .set_compute_res(BlockId([1; 32]), [9; 32], 1, 1, Arc::new(None), vec![])
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("re-executing block 1"));
+        manager
+            .set_compute_res(BlockId([2; 32]), [2; 32], 2, 1, Arc::new(None), vec![])
+            .await
+            .unwrap();
+        assert!(manager.block_state_machine.lock().await.redelivery.is_none());
+
+        // Block 1 was persisted and pruned meanwhile.
+        {
+            let mut block_state_machine = manager.block_state_machine.lock().await;
+            block_state_machine.executed_blocks.retain(|key, _| key.block_number >= 2);
+        }
+        assert!(manager.request_redelivery(1).await.unwrap_err().to_string().contains("block 1"));
+    }
+
+    #[tokio::test]
+    async fn committed_blocks_are_final() {
+        let manager = BlockBufferManager::new(test_config());
+        let mut block_number_to_block_id = HashMap::new();
+        block_number_to_block_id.insert(4, (1, BlockId([4; 32])));
+        block_number_to_block_id.insert(5, (1, BlockId([5; 32])));
+        manager.init(5, block_number_to_block_id, 1).await.unwrap();
+
+        // Consensus can replay a committed block, but not order a different one at its height
+        manager
+            .set_ordered_blocks(BlockId([3; 32]), test_block(4), 4, Default::default())
+            .await
+            .unwrap();
+        let mut conflicting = test_block(4);
+        conflicting.block_meta.block_id = BlockId([40; 32]);
+        let error = manager
+            .set_ordered_blocks(BlockId([3; 32]), conflicting, 4, Default::default())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("conflicts with committed block"));
+        assert!(!manager
+            .block_state_machine
+            .lock()
+            .await
+            .blocks
+            .contains_key(&BlockKey::new(1, 4)));
+
+        let error = manager.request_redelivery(5).await.unwrap_err();
+        assert!(error.to_string().contains("latest committed block 5"));
+    }
+
+    #[tokio::test]
+    async fn request_redelivery_respects_max_reorg_depth() {
+        let manager = BlockBufferManager::new(BlockBufferManagerConfig {
+            max_reorg_depth: 1,
+            ..test_config()
+        });
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+
+        let mut parent_id = BlockId([0; 32]);
+        for block_number in 1..=2u8 {
+            manager
+                .set_ordered_blocks(
+                    parent_id,
+                    test_block(block_number),
+                    block_number as u64,
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+            manager.get_ordered_blocks(block_number as u64, Some(1), 1).await.unwrap();
+            manager
+                .set_compute_res(
+                    BlockId([block_number; 32]),
+                    [block_number; 32],
+                    block_number as u64,
+                    1,
+                    Arc::new(None),
+                    vec![],
+                )
+                .await
+                .unwrap();
+            parent_id = BlockId([block_number; 32]);
+        }
+
+        let error = manager.request_redelivery(1).await.unwrap_err();
+        assert!(error.to_string().contains("max reorg depth 1"));
+        assert_eq!(manager.request_redelivery(2).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn ordered_blocks_carry_soft_deadline() {
+        let manager =
+            BlockBufferManager::new(test_config().with_round_timeout(Duration::from_secs(2)));
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+
+        let ordered_at = SystemTime::now();
+        manager
+            .set_ordered_blocks(BlockId([0; 32]), test_block(1), 1, Default::default())
+            .await
+            .unwrap();
+        let blocks = manager.get_ordered_blocks(1, None, 1).await.unwrap();
+        let execution_meta = &blocks[0].2;
+        let deadline = execution_meta.soft_deadline.unwrap();
+        assert!(deadline >= ordered_at + Duration::from_secs(1));
+        assert!(deadline <= SystemTime::now() + Duration::from_secs(1));
+        assert!(!execution_meta.past_soft_deadline());
+    }
+
+    #[tokio::test]
+    async fn ordered_block_timestamps_do_not_decrease() {
+        let manager = BlockBufferManager::new(test_config());
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+
+        let order = |block_number: u8, usecs: u64| {
+            let mut block = test_block(block_number);
+            block.block_meta.usecs = usecs;
+            let parent_id = BlockId([block_number - 1; 32]);
+            manager.set_ordered_blocks(parent_id, block, block_number as u64, Default::default())
+        };
+        order(1, 2_000).await.unwrap();
+        // A nil block keeps the timestamp of its parent
+        order(2, 2_000).await.unwrap();
+        let error = order(3, 1_000).await.unwrap_err();
+        assert!(error.to_string().contains("before its parent's 2000"));
+        order(3, 3_000).await.unwrap();
+        // Ordering a block again is not an error, whatever its timestamp
+        order(3, 1_000).await.unwrap();
+        order(2, 500).await.unwrap();
+        order(4, 3_000).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn watermarks_track_each_stage() {
+        let manager = BlockBufferManager::new(test_config());
+        assert_eq!(manager.watermarks().await, None);
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+        assert_eq!(manager.ready_watermarks().await, Watermarks::default());
+
+        for block_number in 1..=3u8 {
+            let parent_id = BlockId([block_number - 1; 32]);
+            manager
+                .set_ordered_blocks(
+                    parent_id,
+                    test_block(block_number),
+                    block_number as u64,
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+        }
+        for block_number in 1..=2u8 {
+            manager.get_ordered_blocks(block_number as u64, Some(1), 1).await.unwrap();
+            manager
+                .set_compute_res(
+                    BlockId([block_number; 32]),
+                    [block_number; 32],
+                    block_number as u64,
+                    1,
+                    Arc::new(None),
+                    vec![],
+                )
+                .await
+                .unwrap();
+        }
+        let commit =
+            BlockHashRef { block_id: BlockId([1; 32]), num: 1, hash: None, persist_notifier: None };
+        manager.set_commit_blocks(&[commit], 1).await.unwrap();
+
+        let watermarks = manager.watermarks().await.unwrap();
+        assert_eq!(
+            (watermarks.ordered, watermarks.executed, watermarks.committed, watermarks.persisted),
+            (3, 2, 1, 0)
+        );
+        // Block 3 waits for execution and block 2 for commit
+        assert!(watermarks.oldest_unexecuted_age.is_some());
+        assert!(watermarks.oldest_uncommitted_age.is_some());
+
+        let commit =
+            BlockHashRef { block_id: BlockId([2; 32]), num: 2, hash: None, persist_notifier: None };
+        manager.set_commit_blocks(&[commit], 1).await.unwrap();
+        let watermarks = manager.watermarks().await.unwrap();
+        assert_eq!(watermarks.committed, 2);
+        assert_eq!(watermarks.oldest_uncommitted_age, None);
+    }
+
+    #[tokio::test]
+    async fn commit_advances_once_per_batch() {
+        let manager = BlockBufferManager::new(test_config());
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+        let mut events = manager.subscribe_events();
+
+        let mut commits = vec![];
+        for block_number in 1..=2u8 {
+            let block_id = BlockId([block_number; 32]);
+            manager
+                .set_ordered_blocks(
+                    BlockId([block_number - 1; 32]),
+                    test_block(block_number),
+                    block_number as u64,
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+            manager.get_ordered_blocks(block_number as u64, Some(1), 1).await.unwrap();
+            manager
+                .set_compute_res(
+                    block_id,
+                    [block_number; 32],
+                    block_number as u64,
+                    1,
+                    Arc::new(None),
+                    vec![],
+                )
+                .await
+                .unwrap();
+            commits.push(BlockHashRef {
+                block_id,
+                num: block_number as u64,
+                hash: None,
+                persist_notifier: None,
+            });
+        }
+        manager.set_commit_blocks(&commits, 1).await.unwrap();
+
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ConsensusEvent::CommitAdvanced { epoch: 1, block_number: 2 }
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn commit_payloads_are_taken_once() {
+        let manager = BlockBufferManager::new(test_config());
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+        manager
+            .set_ordered_blocks(BlockId([0; 32]), test_block(1), 1, Default::default())
+            .await
+            .unwrap();
+
+        assert!(manager.set_commit_payload(BlockId([2; 32]), 1, 1, vec![7]).await.is_err());
+        assert!(manager.set_commit_payload(BlockId([1; 32]), 2, 1, vec![7]).await.is_err());
+        manager.set_commit_payload(BlockId([1; 32]), 1, 1, vec![7]).await.unwrap();
+
+        let block =
+            BlockHashRef { block_id: BlockId([1; 32]), num: 1, hash: None, persist_notifier: None };
+        let blocks = [block];
+        assert_eq!(manager.take_commit_payloads(&blocks, 1).await, vec![(1, vec![7])]);
+        assert!(manager.take_commit_payloads(&blocks, 1).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn signed_commit_payloads_are_part_of_the_executed_state_id() {
+        let manager = BlockBufferManager::new(test_config());
+        manager.init(0, HashMap::new(), 1).await.unwrap();
+        manager.set_signed_commit_payloads(1, true).await;
+        for block_number in 1..=2u8 {
+            manager
+                .set_ordered_blocks(
+                    BlockId([block_number - 1; 32]),
+                    test_block(block_number),
+                    block_number as u64,
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+            manager
+                .set_commit_payload(BlockId([block_number; 32]), block_number as u64, 1, vec![7])
+                .await
+                .unwrap();
+        }
+
+        manager
+            .set_compute_res(BlockId([1; 32]), [9; 32], 1, 1, Arc::new(None), vec![])
+            .await
+            .unwrap();
+        assert!(manager.set_commit_payload(BlockId([1; 32]), 1, 1, vec![8]).await.is_err());
+        let signed = manager.get_executed_res(BlockId([1; 32]), 1, 1).await.unwrap();
+        assert_eq!(signed.execution_block_hash(), HashValue::new([9; 32]));
+        assert_eq!(
+            signed.root_hash(),
+            HashValue::sha3_256_of(&[[9; 32], *HashValue::sha3_256_of(&[7])].concat())
+        );
+
+        // Epochs that do not sign commit payloads keep the execution layer block hash
+        manager.set_signed_commit_payloads(1, false).await;
+        manager
+            .set_compute_res(BlockId([2; 32]), [10; 32], 2, 1, Arc::new(None), vec![])
+            .await
+            .unwrap();
+        let unsigned = manager.get_executed_res(BlockId([2; 32]), 2, 1).await.unwrap();
+        assert_eq!(unsigned.root_hash(), HashValue::new([10; 32]));
+
+        // The execution layer is handed its own block hash, not the signed one
+        let commit = BlockHashRef {
+            block_id: BlockId([1; 32]),
+            num: 1,
+            hash: Some(*signed.root_hash()),
+            persist_notifier: None,
+        };
+        manager.set_commit_blocks(&[commit], 1).await.unwrap();
+        let committed = manager.get_committed_blocks(1, None, 1).await.unwrap();
+        assert_eq!(committed[0].hash, Some([9; 32]));
+    }
+
+    struct FixedSystemTxns(Vec<Vec<u8>>);
+
+    impl SystemTxnProvider for FixedSystemTxns {
+        fn system_txns(&self, _ctx: &SystemTxnContext) -> Vec<Vec<u8>> {
+            self.0.clone()
+        }
+
+        fn verify(&self, _ctx: &SystemTxnContext, txns: &[Vec<u8>]) -> anyhow::Result<()> {
+            anyhow::ensure!(txns == self.0, "unexpected system txns");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registered_system_txn_provider_forwards_to_the_one_set_later() {
+        let manager = BlockBufferManager::new(test_config());
+        let provider = manager.registered_system_txn_provider();
+        let ctx = SystemTxnContext {
+            epoch: 1,
+            round: 2,
+            timestamp_usecs: 3,
+            parent_id: BlockId([0; 32]),
+        };
+
+        assert!(provider.system_txns(&ctx).is_empty());
+        assert!(provider.verify(&ctx, &[vec![1]]).is_err());
+
+        manager.set_system_txn_provider(Arc::new(FixedSystemTxns(vec![vec![1]])));
+        assert_eq!(provider.system_txns(&ctx), vec![vec![1]]);
+        provider.verify(&ctx, &[vec![1]]).unwrap();
+        assert!(provider.verify(&ctx, &[vec![2]]).is_err());
+    }
 }
@@ -1,14 +1,22 @@
+#[cfg(feature = "global")]
 use std::sync::{Arc, OnceLock};
 
-use block_buffer_manager::BlockBufferManager;
-
 pub mod block_buffer_manager;
+
+#[cfg(feature = "global")]
 static GLOBAL_BLOCK_BUFFER_MANAGER: OnceLock<Arc<BlockBufferManager>> = OnceLock::new();
 
+/// Process-wide instance for embedders written before each node owned its own manager.
+/// In-tree code takes the handle of the node it belongs to instead, so several nodes can run in
+/// one process.
+#[cfg(feature = "global")]
 pub fn get_block_buffer_manager() -> &'static Arc<BlockBufferManager> {
     GLOBAL_BLOCK_BUFFER_MANAGER.get_or_init(|| {
-        BlockBufferManager::new(block_buffer_manager::BlockBufferManagerConfig::default())
+        BlockBufferManager::new(block_buffer_manager::BlockBufferManagerConfig::from_env())
     })
 }
 
-pub use block_buffer_manager::TxPool;
+pub use block_buffer_manager::{
+    BlockBufferManager, ConsensusEvent, EpochChangeInProgress, OnTxnVerified, PooledTxn,
+    SystemTxnContext, SystemTxnProvider, TxPool, TxPoolStats, Watermarks,
+};
@@ -0,0 +1,181 @@
+//! The execution engine side of the `ExecutionChannel` service, for engines written in Rust.
+
+use crate::{
+    convert,
+    proto::{self, path},
+};
+use anyhow::{anyhow, Context};
+use block_buffer_manager::{block_buffer_manager::BlockExecutionMeta, EpochChangeInProgress};
+use gaptos::api_types::{
+    compute_res::TxnStatus, events::contract_event::GravityEvent, u256_define::BlockId,
+    ExternalBlock,
+};
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, Endpoint},
+    Code, Request, Status,
+};
+
+fn status_error(status: Status) -> anyhow::Error {
+    match status.code() {
+        Code::FailedPrecondition => EpochChangeInProgress.into(),
+        _ => status.into(),
+    }
+}
+
+/// A block consensus has committed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedBlock {
+    pub block_id: BlockId,
+    pub block_number: u64,
+    pub block_hash: Option<[u8; 32]>,
+    /// Consensus waits for [`ExecutionChannelClient::notify_persisted`] covering this block.
+    pub wait_for_persistence: bool,
+}
+
+/// Client of a node serving [`crate::ExecutionChannelService`].
+///
+/// The methods mirror the ones the in-process execution layer calls on the block buffer
+/// manager and fail with the same [`EpochChangeInProgress`] while it switches epochs, so an
+/// engine loop written against one works against the other. Other failures carry the
+/// [`Status`] of the call.
+#[derive(Clone)]
+pub struct ExecutionChannelClient {
+    inner: Grpc<Channel>,
+}
+
+impl ExecutionChannelClient {
+    /// Connects to `endpoint`, e.g. `http://127.0.0.1:50051`.
+    pub async fn connect(endpoint: String) -> anyhow::Result<Self> {
+        let channel = Endpoint::from_shared(endpoint.clone())?
+            .connect()
+            .await
+            .with_context(|| format!("failed to connect to {endpoint}"))?;
+        Ok(Self { inner: Grpc::new(channel) })
+    }
+
+    async fn unary<Req, Resp>(&self, path: &'static str, request: Req) -> anyhow::Result<Resp>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Resp: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = self.inner.clone();
+        grpc.ready().await.context("execution channel is not ready")?;
+        let response = grpc
+            .unary(
+                Request::new(request),
+                PathAndQuery::from_static(path),
+                ProstCodec::<Req, Resp>::default(),
+            )
+            .await
+            .map_err(status_error)?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn get_current_epoch(&self) -> anyhow::Result<u64> {
+        let response: proto::GetCurrentEpochResponse =
+            self.unary(path::GET_CURRENT_EPOCH, proto::GetCurrentEpochRequest {}).await?;
+        Ok(response.epoch)
+    }
+
+    pub async fn get_ordered_blocks(
+        &self,
+        start_num: u64,
+        max_size: Option<usize>,
+        expected_epoch: u64,
+    ) -> anyhow::Result<Vec<(ExternalBlock, BlockId, BlockExecutionMeta)>> {
+        let request = proto::GetOrderedBlocksRequest {
+            start_block_number: start_num,
+            max_size: max_size.map(|size| size as u64),
+            epoch: expected_epoch,
+        };
+        let response: proto::GetOrderedBlocksResponse =
+            self.unary(path::GET_ORDERED_BLOCKS, request).await?;
+        response.blocks.into_iter().map(convert::ordered_block_from_proto).collect()
+    }
+
+    /// Returns the new epoch and the block number of the epoch change.
+    pub async fn consume_epoch_change(&self) -> anyhow::Result<(u64, u64)> {
+        let response: proto::ConsumeEpochChangeResponse =
+            self.unary(path::CONSUME_EPOCH_CHANGE, proto::ConsumeEpochChangeRequest {}).await?;
+        Ok((response.epoch, response.epoch_change_block_number))
+    }
+
+    pub async fn set_compute_res(
+        &self,
+        block_id: BlockId,
+        block_hash: [u8; 32],
+        block_num: u64,
+        epoch: u64,
+        txn_status: &[TxnStatus],
+        events: &[GravityEvent],
+    ) -> anyhow::Result<()> {
+        let request = proto::SetComputeResultRequest {
+            block_id: block_id.as_bytes().to_vec(),
+            block_hash: block_hash.to_vec(),
+            block_number: block_num,
+            epoch,
+            txn_status: txn_status.iter().map(convert::txn_status_to_proto).collect(),
+            events: convert::events_to_proto(events)?,
+        };
+        let _: proto::SetComputeResultResponse =
+            self.unary(path::SET_COMPUTE_RESULT, request).await?;
+        Ok(())
+    }
+
+    pub async fn get_committed_blocks(
+        &self,
+        start_num: u64,
+        max_size: Option<usize>,
+        epoch: u64,
+    ) -> anyhow::Result<Vec<CommittedBlock>> {
+        let request = proto::GetCommittedBlocksRequest {
+            start_block_number: start_num,
+            max_size: max_size.map(|size| size as u64),
+            epoch,
+        };
+        let response: proto::GetCommittedBlocksResponse =
+            self.unary(path::GET_COMMITTED_BLOCKS, request).await?;
+        response
+            .blocks
+            .into_iter()
+            .map(|block| {
+                let block_hash = match block.block_hash {
+                    Some(hash) => Some(
+                        hash.as_slice()
+                            .try_into()
+                            .map_err(|_| anyhow!("block hash must be 32 bytes"))?,
+                    ),
+                    None => None,
+                };
+                Ok(CommittedBlock {
+                    block_id: convert::block_id(&block.block_id)?,
+                    block_number: block.block_number,
+                    block_hash,
+                    wait_for_persistence: block.wait_for_persistence,
+                })
+            })
+            .collect()
+    }
+
+    /// Reports every block up to `block_number` as persisted.
+    pub async fn notify_persisted(&self, block_number: u64) -> anyhow::Result<()> {
+        let _: proto::NotifyPersistedResponse = self
+            .unary(path::NOTIFY_PERSISTED, proto::NotifyPersistedRequest { block_number })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_state(
+        &self,
+        latest_commit_block_number: u64,
+        latest_finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        let request =
+            proto::SetStateRequest { latest_commit_block_number, latest_finalized_block_number };
+        let _: proto::SetStateResponse = self.unary(path::SET_STATE, request).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Conversions between the proto messages and the types of the block buffer manager.
+
+use crate::proto;
+use anyhow::{anyhow, Context};
+use block_buffer_manager::block_buffer_manager::BlockExecutionMeta;
+use gaptos::api_types::{
+    account::{ExternalAccountAddress, ExternalChainId},
+    compute_res::{ComputeRes, TxnStatus},
+    events::contract_event::GravityEvent,
+    u256_define::{BlockId, Random},
+    ExternalBlock, ExternalBlockMeta, ExtraDataType, VerifiedTxn,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn to_array<const N: usize>(bytes: &[u8], field: &str) -> anyhow::Result<[u8; N]> {
+    bytes.try_into().map_err(|_| anyhow!("{} must be {} bytes, got {}", field, N, bytes.len()))
+}
+
+pub(crate) fn block_id(bytes: &[u8]) -> anyhow::Result<BlockId> {
+    Ok(BlockId(to_array(bytes, "block id")?))
+}
+
+pub(crate) fn ordered_block_to_proto(
+    block: ExternalBlock,
+    parent_id: BlockId,
+    execution_meta: BlockExecutionMeta,
+) -> anyhow::Result<proto::OrderedBlock> {
+    let meta = block.block_meta;
+    let extra_data = bcs::to_bytes(&block.extra_data).context("failed to encode extra data")?;
+    Ok(proto::OrderedBlock {
+        parent_id: parent_id.as_bytes().to_vec(),
+        block_id: meta.block_id.as_bytes().to_vec(),
+        block_number: meta.block_number,
+        epoch: meta.epoch,
+        timestamp_usecs: meta.usecs,
+        randomness: meta.randomness.map(|randomness| randomness.0.to_vec()),
+        block_hash: meta
+            .block_hash
+            .map(|res| proto::BlockHash { hash: res.data.to_vec(), txn_num: res.txn_num }),
+        proposer_index: meta.proposer_index,
+        failed_proposer_indices: meta.failed_proposer_indices,
+        proposer_address: execution_meta.proposer_reth_address.map(|address| address.to_vec()),
+        transactions: block
+            .txns
+            .into_iter()
+            .map(|txn| proto::Transaction {
+                sender: txn.sender().bytes().to_vec(),
+                sequence_number: txn.seq_number(),
+                chain_id: txn.chain_id().into_u64(),
+                bytes: txn.bytes().to_vec(),
+            })
+            .collect(),
+        extra_data,
+        enable_randomness: block.enable_randomness,
+        soft_deadline_unix_ms: execution_meta.soft_deadline.map(|deadline| {
+            deadline.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+        }),
+        system_txns: execution_meta.system_txns,
+    })
+}
+
+pub(crate) fn ordered_block_from_proto(
+    block: proto::OrderedBlock,
+) -> anyhow::Result<(ExternalBlock, BlockId, BlockExecutionMeta)> {
+    let id = block_id(&block.block_id)?;
+    let parent_id = block_id(&block.parent_id)?;
+    let block_hash = match block.block_hash {
+        Some(hash) => {
+            Some(ComputeRes::new(to_array(&hash.hash, "block hash")?, hash.txn_num, vec![], vec![]))
+        }
+        None => None,
+    };
+    let proposer_reth_address = match block.proposer_address {
+        Some(address) => Some(to_array(&address, "proposer address")?),
+        None => None,
+    };
+    let txns = block
+        .transactions
+        .into_iter()
+        .map(|txn| {
+            Ok(VerifiedTxn::new(
+                txn.bytes,
+                ExternalAccountAddress::new(to_array(&txn.sender, "sender")?),
+                txn.sequence_number,
+                ExternalChainId::new(txn.chain_id),
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let extra_data = bcs::from_bytes::<Vec<ExtraDataType>>(&block.extra_data)
+        .context("failed to decode extra data")?;
+    let external_block = ExternalBlock {
+        block_meta: ExternalBlockMeta {
+            block_id: id,
+            block_number: block.block_number,
+            usecs: block.timestamp_usecs,
+            epoch: block.epoch,
+            randomness: block.randomness.map(|randomness| Random::from_bytes(&randomness)),
+            block_hash,
+            proposer_index: block.proposer_index,
+            failed_proposer_indices: block.failed_proposer_indices,
+        },
+        txns,
+        extra_data,
+        enable_randomness: block.enable_randomness,
+    };
+    let soft_deadline =
+        block.soft_deadline_unix_ms.map(|millis| UNIX_EPOCH + Duration::from_millis(millis));
+    Ok((
+        external_block,
+        parent_id,
+        BlockExecutionMeta { proposer_reth_address, soft_deadline, system_txns: block.system_txns },
+    ))
+}
+
+pub(crate) fn txn_status_to_proto(status: &TxnStatus) -> proto::TxnStatus {
+    proto::TxnStatus {
+        txn_hash: status.txn_hash.to_vec(),
+        sender: status.sender.to_vec(),
+        nonce: status.nonce,
+        is_discarded: status.is_discarded,
+    }
+}
+
+pub(crate) fn txn_status_from_proto(status: &proto::TxnStatus) -> anyhow::Result<TxnStatus> {
+    Ok(TxnStatus {
+        txn_hash: to_array(&status.txn_hash, "txn hash")?,
+        sender: to_array(&status.sender, "sender")?,
+        nonce: status.nonce,
+        is_discarded: status.is_discarded,
+    })
+}
+
+pub(crate) fn events_to_proto(events: &[GravityEvent]) -> anyhow::Result<Vec<Vec<u8>>> {
+    events.iter().map(|event| bcs::to_bytes(event).context("failed to encode event")).collect()
+}
+
+pub(crate) fn events_from_proto(events: &[Vec<u8>]) -> anyhow::Result<Vec<GravityEvent>> {
+    events.iter().map(|event| bcs::from_bytes(event).context("failed to decode event")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_block_round_trips() {
+        let block = ExternalBlock {
+            block_meta: ExternalBlockMeta {
+                block_id: BlockId([7; 32]),
+                block_number: 42,
+                usecs: 1_000_000,
+                epoch: 3,
+                randomness: Some(Random::from_bytes(&[9; 32])),
+                block_hash: Some(ComputeRes::new([5; 32], 1, vec![], vec![])),
+                proposer_index: Some(2),
+                failed_proposer_indices: vec![0, 1],
+            },
+            txns: vec![VerifiedTxn::new(
+                vec![1, 2, 3],
+                ExternalAccountAddress::new([4; 32]),
+                11,
+                ExternalChainId::new(1337),
+            )],
+            extra_data: vec![],
+            enable_randomness: true,
+        };
+        let execution_meta = BlockExecutionMeta {
+            proposer_reth_address: Some([8; 20]),
+            soft_deadline: Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_123)),
+            system_txns: vec![vec![10, 11]],
+        };
+
+        let proto = ordered_block_to_proto(block, BlockId([6; 32]), execution_meta.clone())
+            .expect("encode");
+        let (block, parent_id, meta) = ordered_block_from_proto(proto.clone()).expect("decode");
+
+        assert_eq!(parent_id, BlockId([6; 32]));
+        assert_eq!(meta, execution_meta);
+        assert_eq!(block.block_meta.block_id, BlockId([7; 32]));
+        assert_eq!(block.block_meta.block_number, 42);
+        assert_eq!(block.block_meta.usecs, 1_000_000);
+        assert_eq!(block.block_meta.epoch, 3);
+        assert_eq!(block.block_meta.proposer_index, Some(2));
+        assert_eq!(block.block_meta.failed_proposer_indices, vec![0, 1]);
+        assert!(block.enable_randomness);
+        assert_eq!(block.txns.len(), 1);
+        assert_eq!(block.txns[0].bytes().as_slice(), &[1, 2, 3]);
+        assert_eq!(block.txns[0].seq_number(), 11);
+        // Decoding and re-encoding gives back the same message.
+        let reencoded = ordered_block_to_proto(block, parent_id, meta).expect("re-encode");
+        assert_eq!(reencoded, proto);
+    }
+
+    #[test]
+    fn rejects_short_ids() {
+        let block = proto::OrderedBlock {
+            parent_id: vec![1; 32],
+            block_id: vec![1; 31],
+            extra_data: bcs::to_bytes(&Vec::<ExtraDataType>::new()).unwrap(),
+            ..Default::default()
+        };
+        assert!(ordered_block_from_proto(block).is_err());
+    }
+}
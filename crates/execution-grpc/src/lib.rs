@@ -0,0 +1,25 @@
+//! The consensus-execution boundary of a Gravity node over gRPC.
+//!
+//! In a single process the execution layer drives the node's [`BlockBufferManager`] directly.
+//! [`ExecutionChannelService`] serves the same calls over the network, as described in
+//! `proto/execution.proto`, so an execution engine can run in a separate process or be written
+//! in another language. [`ExecutionChannelClient`] is the client for engines written in Rust.
+//!
+//! Blocks that consensus replays during block sync go through the default
+//! `aptos_consensus::BlockBufferRecovery`, which feeds them to the block buffer manager. A
+//! remote engine therefore receives them as ordinary ordered blocks and does not have to
+//! implement `RecoveryApi` itself.
+//!
+//! [`BlockBufferManager`]: block_buffer_manager::BlockBufferManager
+
+mod client;
+mod convert;
+pub mod proto;
+mod randomness;
+mod server;
+#[cfg(test)]
+mod tests;
+
+pub use client::{CommittedBlock, ExecutionChannelClient};
+pub use randomness::BlockRandomness;
+pub use server::{serve, ExecutionChannelService};
@@ -0,0 +1,187 @@
+//! Messages of `proto/execution.proto`.
+//!
+//! Written out by hand instead of generated at build time, so building the node does not need
+//! `protoc`. Keep the tags in sync with the proto file.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Transaction {
+    #[prost(bytes = "vec", tag = "1")]
+    pub bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sender: Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    pub sequence_number: u64,
+    #[prost(uint64, tag = "4")]
+    pub chain_id: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BlockHash {
+    #[prost(bytes = "vec", tag = "1")]
+    pub hash: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub txn_num: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OrderedBlock {
+    #[prost(bytes = "vec", tag = "1")]
+    pub parent_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub block_id: Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(uint64, tag = "4")]
+    pub epoch: u64,
+    #[prost(uint64, tag = "5")]
+    pub timestamp_usecs: u64,
+    #[prost(bytes = "vec", optional, tag = "6")]
+    pub randomness: Option<Vec<u8>>,
+    #[prost(message, optional, tag = "7")]
+    pub block_hash: Option<BlockHash>,
+    #[prost(uint64, optional, tag = "8")]
+    pub proposer_index: Option<u64>,
+    #[prost(uint64, repeated, tag = "9")]
+    pub failed_proposer_indices: Vec<u64>,
+    #[prost(bytes = "vec", optional, tag = "10")]
+    pub proposer_address: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "11")]
+    pub transactions: Vec<Transaction>,
+    #[prost(bytes = "vec", tag = "12")]
+    pub extra_data: Vec<u8>,
+    #[prost(bool, tag = "13")]
+    pub enable_randomness: bool,
+    #[prost(uint64, optional, tag = "14")]
+    pub soft_deadline_unix_ms: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TxnStatus {
+    #[prost(bytes = "vec", tag = "1")]
+    pub txn_hash: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sender: Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    pub nonce: u64,
+    #[prost(bool, tag = "4")]
+    pub is_discarded: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CommittedBlock {
+    #[prost(bytes = "vec", tag = "1")]
+    pub block_id: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub block_number: u64,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub block_hash: Option<Vec<u8>>,
+    #[prost(bool, tag = "4")]
+    pub wait_for_persistence: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCurrentEpochRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCurrentEpochResponse {
+    #[prost(uint64, tag = "1")]
+    pub epoch: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetOrderedBlocksRequest {
+    #[prost(uint64, tag = "1")]
+    pub start_block_number: u64,
+    #[prost(uint64, optional, tag = "2")]
+    pub max_size: Option<u64>,
+    #[prost(uint64, tag = "3")]
+    pub epoch: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetOrderedBlocksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub blocks: Vec<OrderedBlock>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConsumeEpochChangeRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConsumeEpochChangeResponse {
+    #[prost(uint64, tag = "1")]
+    pub epoch: u64,
+    #[prost(uint64, tag = "2")]
+    pub epoch_change_block_number: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetComputeResultRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub block_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub block_hash: Vec<u8>,
+    #[prost(uint64, tag = "3")]
+    pub block_number: u64,
+    #[prost(uint64, tag = "4")]
+    pub epoch: u64,
+    #[prost(message, repeated, tag = "5")]
+    pub txn_status: Vec<TxnStatus>,
+    #[prost(bytes = "vec", repeated, tag = "6")]
+    pub events: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetComputeResultResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCommittedBlocksRequest {
+    #[prost(uint64, tag = "1")]
+    pub start_block_number: u64,
+    #[prost(uint64, optional, tag = "2")]
+    pub max_size: Option<u64>,
+    #[prost(uint64, tag = "3")]
+    pub epoch: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetCommittedBlocksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub blocks: Vec<CommittedBlock>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct NotifyPersistedRequest {
+    #[prost(uint64, tag = "1")]
+    pub block_number: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct NotifyPersistedResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetStateRequest {
+    #[prost(uint64, tag = "1")]
+    pub latest_commit_block_number: u64,
+    #[prost(uint64, tag = "2")]
+    pub latest_finalized_block_number: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetStateResponse {}
+
+/// Full method paths of the `ExecutionChannel` service.
+pub mod path {
+    pub const GET_CURRENT_EPOCH: &str = "/gravity.execution.v1.ExecutionChannel/GetCurrentEpoch";
+    pub const GET_ORDERED_BLOCKS: &str = "/gravity.execution.v1.ExecutionChannel/GetOrderedBlocks";
+    pub const CONSUME_EPOCH_CHANGE: &str =
+        "/gravity.execution.v1.ExecutionChannel/ConsumeEpochChange";
+    pub const SET_COMPUTE_RESULT: &str = "/gravity.execution.v1.ExecutionChannel/SetComputeResult";
+    pub const GET_COMMITTED_BLOCKS: &str =
+        "/gravity.execution.v1.ExecutionChannel/GetCommittedBlocks";
+    pub const NOTIFY_PERSISTED: &str = "/gravity.execution.v1.ExecutionChannel/NotifyPersisted";
+    pub const SET_STATE: &str = "/gravity.execution.v1.ExecutionChannel/SetState";
+}
+
+/// Name of the `ExecutionChannel` service.
+pub const SERVICE_NAME: &str = "gravity.execution.v1.ExecutionChannel";
@@ -0,0 +1,72 @@
+//! Per-block randomness for execution engines.
+
+use gaptos::{api_types::ExternalBlock, aptos_crypto::HashValue};
+
+/// Randomness of an ordered block, evaluated by the validators of its epoch with the weighted
+/// VUF keyed by the DKG transcript. Every node sees the same value for a block, and no one can
+/// predict it before the block is ordered, so execution may use it for lotteries, shuffles or
+/// leader selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockRandomness([u8; 32]);
+
+impl BlockRandomness {
+    /// `None` while randomness is disabled onchain and for blocks that need none, e.g. NIL
+    /// blocks or blocks of the first epoch, which starts without a DKG transcript.
+    pub fn of(block: &ExternalBlock) -> Option<Self> {
+        let randomness = block.block_meta.randomness.as_ref()?;
+        <[u8; 32]>::try_from(randomness.0.as_ref()).ok().map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Randomness for one use within the block, e.g. one value per transaction. Values of
+    /// different `domain`s are independent of each other.
+    pub fn derive(&self, domain: &[u8]) -> [u8; 32] {
+        let mut input = Vec::with_capacity(self.0.len() + 8 + domain.len());
+        input.extend_from_slice(&self.0);
+        input.extend_from_slice(&(domain.len() as u64).to_be_bytes());
+        input.extend_from_slice(domain);
+        *HashValue::sha3_256_of(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::api_types::{
+        u256_define::{BlockId, Random},
+        ExternalBlockMeta,
+    };
+
+    fn block(randomness: Option<Random>) -> ExternalBlock {
+        ExternalBlock {
+            block_meta: ExternalBlockMeta {
+                block_id: BlockId([1; 32]),
+                block_number: 1,
+                usecs: 0,
+                epoch: 2,
+                randomness,
+                block_hash: None,
+                proposer_index: None,
+                failed_proposer_indices: vec![],
+            },
+            txns: vec![],
+            extra_data: vec![],
+            enable_randomness: true,
+        }
+    }
+
+    #[test]
+    fn derives_independent_values_per_domain() {
+        assert_eq!(BlockRandomness::of(&block(None)), None);
+
+        let randomness =
+            BlockRandomness::of(&block(Some(Random::from_bytes(&[3; 32])))).expect("randomness");
+        assert_eq!(randomness.as_bytes(), &[3; 32]);
+        assert_eq!(randomness.derive(b"a"), randomness.derive(b"a"));
+        assert_ne!(randomness.derive(b"a"), randomness.derive(b"b"));
+        assert_ne!(randomness.derive(b"a"), *randomness.as_bytes());
+    }
+}
@@ -0,0 +1,293 @@
+//! The node side of the `ExecutionChannel` service.
+
+use crate::{
+    convert,
+    proto::{self, path},
+};
+use block_buffer_manager::{
+    block_buffer_manager::BlockHashRef, BlockBufferManager, EpochChangeInProgress,
+};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc::Sender, Mutex};
+use tonic::{
+    body::BoxBody,
+    codec::ProstCodec,
+    codegen::{empty_body, http, Body, BoxFuture, Service, StdError},
+    server::{Grpc, NamedService, UnaryService},
+    Code, Request, Response, Status,
+};
+use tracing::{info, warn};
+
+/// Serves a [`BlockBufferManager`] to an execution engine in another process.
+///
+/// Drives the block buffer manager exactly like the in-process execution layer does, so
+/// consensus cannot tell the two apart.
+#[derive(Clone)]
+pub struct ExecutionChannelService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    block_buffer_manager: Arc<BlockBufferManager>,
+    /// Persist notifiers of the committed blocks handed out so far, by block number. Released
+    /// once the engine reports the block persisted.
+    persist_notifiers: Mutex<BTreeMap<u64, Sender<()>>>,
+}
+
+impl ExecutionChannelService {
+    pub fn new(block_buffer_manager: Arc<BlockBufferManager>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                block_buffer_manager,
+                persist_notifiers: Mutex::new(BTreeMap::new()),
+            }),
+        }
+    }
+}
+
+/// Serves `block_buffer_manager` on `addr` until the server fails.
+pub async fn serve(
+    addr: SocketAddr,
+    block_buffer_manager: Arc<BlockBufferManager>,
+) -> anyhow::Result<()> {
+    info!("Serving the execution channel on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ExecutionChannelService::new(block_buffer_manager))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// [`EpochChangeInProgress`] maps to `FAILED_PRECONDITION`, which the client turns back into it.
+fn block_buffer_status(e: anyhow::Error) -> Status {
+    if e.is::<EpochChangeInProgress>() {
+        Status::failed_precondition(e.to_string())
+    } else {
+        Status::internal(format!("{e:#}"))
+    }
+}
+
+fn invalid_argument(e: anyhow::Error) -> Status {
+    Status::invalid_argument(format!("{e:#}"))
+}
+
+impl Inner {
+    async fn get_current_epoch(
+        self: Arc<Self>,
+        _request: proto::GetCurrentEpochRequest,
+    ) -> Result<proto::GetCurrentEpochResponse, Status> {
+        let epoch = self.block_buffer_manager.get_current_epoch().await;
+        Ok(proto::GetCurrentEpochResponse { epoch })
+    }
+
+    async fn get_ordered_blocks(
+        self: Arc<Self>,
+        request: proto::GetOrderedBlocksRequest,
+    ) -> Result<proto::GetOrderedBlocksResponse, Status> {
+        let blocks = self
+            .block_buffer_manager
+            .get_ordered_blocks(
+                request.start_block_number,
+                request.max_size.map(|size| size as usize),
+                request.epoch,
+            )
+            .await
+            .map_err(block_buffer_status)?;
+        let blocks = blocks
+            .into_iter()
+            .map(|(block, parent_id, execution_meta)| {
+                convert::ordered_block_to_proto(block, parent_id, execution_meta)
+            })
+            .collect::<anyhow::Result<_>>()
+            .map_err(|e| Status::internal(format!("{e:#}")))?;
+        Ok(proto::GetOrderedBlocksResponse { blocks })
+    }
+
+    async fn consume_epoch_change(
+        self: Arc<Self>,
+        _request: proto::ConsumeEpochChangeRequest,
+    ) -> Result<proto::ConsumeEpochChangeResponse, Status> {
+        let (epoch, epoch_change_block_number) =
+            self.block_buffer_manager.consume_epoch_change().await;
+        Ok(proto::ConsumeEpochChangeResponse { epoch, epoch_change_block_number })
+    }
+
+    async fn set_compute_result(
+        self: Arc<Self>,
+        request: proto::SetComputeResultRequest,
+    ) -> Result<proto::SetComputeResultResponse, Status> {
+        let block_id = convert::block_id(&request.block_id).map_err(invalid_argument)?;
+        let block_hash = request
+            .block_hash
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("block hash must be 32 bytes"))?;
+        let txn_status = request
+            .txn_status
+            .iter()
+            .map(convert::txn_status_from_proto)
+            .collect::<anyhow::Result<_>>()
+            .map_err(invalid_argument)?;
+        let events = convert::events_from_proto(&request.events).map_err(invalid_argument)?;
+        self.block_buffer_manager
+            .set_compute_res(
+                block_id,
+                block_hash,
+                request.block_number,
+                request.epoch,
+                Arc::new(Some(txn_status)),
+                events,
+            )
+            .await
+            .map_err(block_buffer_status)?;
+        Ok(proto::SetComputeResultResponse {})
+    }
+
+    async fn get_committed_blocks(
+        self: Arc<Self>,
+        request: proto::GetCommittedBlocksRequest,
+    ) -> Result<proto::GetCommittedBlocksResponse, Status> {
+        let blocks = self
+            .block_buffer_manager
+            .get_committed_blocks(
+                request.start_block_number,
+                request.max_size.map(|size| size as usize),
+                request.epoch,
+            )
+            .await
+            .map_err(block_buffer_status)?;
+        let mut persist_notifiers = self.persist_notifiers.lock().await;
+        let blocks = blocks
+            .into_iter()
+            .map(|block: BlockHashRef| {
+                let wait_for_persistence = match block.persist_notifier {
+                    Some(notifier) => {
+                        persist_notifiers.insert(block.num, notifier);
+                        true
+                    }
+                    None => false,
+                };
+                proto::CommittedBlock {
+                    block_id: block.block_id.as_bytes().to_vec(),
+                    block_number: block.num,
+                    block_hash: block.hash.map(|hash| hash.to_vec()),
+                    wait_for_persistence,
+                }
+            })
+            .collect();
+        Ok(proto::GetCommittedBlocksResponse { blocks })
+    }
+
+    async fn notify_persisted(
+        self: Arc<Self>,
+        request: proto::NotifyPersistedRequest,
+    ) -> Result<proto::NotifyPersistedResponse, Status> {
+        let persisted = {
+            let mut persist_notifiers = self.persist_notifiers.lock().await;
+            let pending = persist_notifiers.split_off(&(request.block_number + 1));
+            std::mem::replace(&mut *persist_notifiers, pending)
+        };
+        for (block_number, notifier) in persisted {
+            if notifier.send(()).await.is_err() {
+                warn!("Nobody is waiting for the persistence of block {}", block_number);
+            }
+        }
+        Ok(proto::NotifyPersistedResponse {})
+    }
+
+    async fn set_state(
+        self: Arc<Self>,
+        request: proto::SetStateRequest,
+    ) -> Result<proto::SetStateResponse, Status> {
+        self.block_buffer_manager
+            .set_state(request.latest_commit_block_number, request.latest_finalized_block_number)
+            .await
+            .map_err(block_buffer_status)?;
+        Ok(proto::SetStateResponse {})
+    }
+}
+
+/// Adapts a handler of [`Inner`] to a unary gRPC method.
+struct Unary<F> {
+    inner: Arc<Inner>,
+    handler: F,
+}
+
+impl<F, Fut, Req, Resp> UnaryService<Req> for Unary<F>
+where
+    F: Fn(Arc<Inner>, Req) -> Fut,
+    Fut: Future<Output = Result<Resp, Status>> + Send + 'static,
+    Resp: Send + 'static,
+{
+    type Response = Resp;
+    type Future = BoxFuture<Response<Resp>, Status>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let response = (self.handler)(self.inner.clone(), request.into_inner());
+        Box::pin(async move { response.await.map(Response::new) })
+    }
+}
+
+fn unary<F, Fut, Req, Resp, B>(
+    inner: Arc<Inner>,
+    handler: F,
+    request: http::Request<B>,
+) -> BoxFuture<http::Response<BoxBody>, Infallible>
+where
+    F: Fn(Arc<Inner>, Req) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Resp, Status>> + Send + 'static,
+    Req: prost::Message + Default + Send + 'static,
+    Resp: prost::Message + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    Box::pin(async move {
+        let mut grpc = Grpc::new(ProstCodec::<Resp, Req>::default());
+        Ok(grpc.unary(Unary { inner, handler }, request).await)
+    })
+}
+
+impl<B> Service<http::Request<B>> for ExecutionChannelService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        match request.uri().path() {
+            path::GET_CURRENT_EPOCH => unary(inner, Inner::get_current_epoch, request),
+            path::GET_ORDERED_BLOCKS => unary(inner, Inner::get_ordered_blocks, request),
+            path::CONSUME_EPOCH_CHANGE => unary(inner, Inner::consume_epoch_change, request),
+            path::SET_COMPUTE_RESULT => unary(inner, Inner::set_compute_result, request),
+            path::GET_COMMITTED_BLOCKS => unary(inner, Inner::get_committed_blocks, request),
+            path::NOTIFY_PERSISTED => unary(inner, Inner::notify_persisted, request),
+            path::SET_STATE => unary(inner, Inner::set_state, request),
+            _ => Box::pin(async move {
+                let mut response = http::Response::new(empty_body());
+                let headers = response.headers_mut();
+                headers.insert(Status::GRPC_STATUS, (Code::Unimplemented as i32).into());
+                headers.insert(http::header::CONTENT_TYPE, tonic::metadata::GRPC_CONTENT_TYPE);
+                Ok(response)
+            }),
+        }
+    }
+}
+
+impl NamedService for ExecutionChannelService {
+    const NAME: &'static str = proto::SERVICE_NAME;
+}
@@ -0,0 +1,100 @@
+use crate::{ExecutionChannelClient, ExecutionChannelService};
+use block_buffer_manager::{
+    block_buffer_manager::{BlockBufferManagerConfig, BlockExecutionMeta},
+    BlockBufferManager, EpochChangeInProgress,
+};
+use gaptos::api_types::{
+    compute_res::TxnStatus, u256_define::BlockId, ExternalBlock, ExternalBlockMeta,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Code, Status};
+
+/// A block buffer manager at epoch 1 with nothing committed, served to a connected client.
+async fn serve_manager() -> (Arc<BlockBufferManager>, ExecutionChannelClient) {
+    let block_buffer_manager = BlockBufferManager::new(BlockBufferManagerConfig {
+        wait_for_change_timeout: Duration::from_millis(5),
+        max_wait_timeout: Duration::from_millis(100),
+        remove_committed_blocks_interval: Duration::from_secs(60),
+        max_block_size: 256,
+        max_reorg_depth: 256,
+        execution_budget: None,
+    });
+    block_buffer_manager.init(0, HashMap::new(), 1).await.unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        Server::builder()
+            .add_service(ExecutionChannelService::new(block_buffer_manager.clone()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let client = ExecutionChannelClient::connect(format!("http://{addr}")).await.unwrap();
+    (block_buffer_manager, client)
+}
+
+fn block(block_number: u8) -> ExternalBlock {
+    ExternalBlock {
+        block_meta: ExternalBlockMeta {
+            block_id: BlockId([block_number; 32]),
+            block_number: block_number as u64,
+            usecs: 1_000 * block_number as u64,
+            epoch: 1,
+            randomness: None,
+            block_hash: None,
+            proposer_index: Some(3),
+            failed_proposer_indices: vec![1, 2],
+        },
+        txns: vec![],
+        extra_data: vec![],
+        enable_randomness: false,
+    }
+}
+
+#[tokio::test]
+async fn ordered_blocks_and_results_round_trip() {
+    let (block_buffer_manager, client) = serve_manager().await;
+    assert_eq!(client.get_current_epoch().await.unwrap(), 1);
+
+    let execution_meta =
+        BlockExecutionMeta { proposer_reth_address: Some([7; 20]), ..Default::default() };
+    block_buffer_manager
+        .set_ordered_blocks(BlockId([0; 32]), block(1), 1, execution_meta.clone())
+        .await
+        .unwrap();
+
+    let blocks = client.get_ordered_blocks(1, None, 1).await.unwrap();
+    assert_eq!(blocks.len(), 1);
+    let (received, parent_id, received_meta) = &blocks[0];
+    let meta = &received.block_meta;
+    assert_eq!((meta.block_id, meta.block_number, meta.epoch), (BlockId([1; 32]), 1, 1));
+    assert_eq!(meta.usecs, 1_000);
+    assert_eq!((meta.proposer_index, &meta.failed_proposer_indices), (Some(3), &vec![1, 2]));
+    assert_eq!(*parent_id, BlockId([0; 32]));
+    assert_eq!(received_meta.proposer_reth_address, execution_meta.proposer_reth_address);
+
+    let txn_status =
+        [TxnStatus { txn_hash: [4; 32], sender: [5; 32], nonce: 6, is_discarded: true }];
+    client.set_compute_res(BlockId([1; 32]), [9; 32], 1, 1, &txn_status, &[]).await.unwrap();
+    let result = block_buffer_manager.get_executed_res(BlockId([1; 32]), 1, 1).await.unwrap();
+    assert_eq!(result.execution_output.data, [9; 32]);
+}
+
+#[tokio::test]
+async fn epoch_change_keeps_its_type_over_the_wire() {
+    let (_block_buffer_manager, client) = serve_manager().await;
+
+    let error = client.get_ordered_blocks(1, None, 2).await.unwrap_err();
+    assert!(error.is::<EpochChangeInProgress>(), "{error:#}");
+}
+
+#[tokio::test]
+async fn other_failures_carry_the_status() {
+    let (_block_buffer_manager, client) = serve_manager().await;
+
+    // Nothing was ordered at block 1
+    let error =
+        client.set_compute_res(BlockId([1; 32]), [9; 32], 1, 1, &[], &[]).await.unwrap_err();
+    assert!(!error.is::<EpochChangeInProgress>());
+    assert_eq!(error.downcast_ref::<Status>().map(Status::code), Some(Code::Internal));
+}
@@ -0,0 +1,181 @@
+//! Genesis bundle of a new chain.
+//!
+//! Operators of a new chain distribute one artifact, built by `gravity-cli genesis build-bundle`,
+//! instead of a waypoint file, a chain id and a genesis hash. The bundle carries the genesis
+//! validator set with the waypoint of its genesis ledger info, the chain id and the genesis block
+//! hash of the execution layer. A node started with it takes the waypoint from the bundle and
+//! refuses to start on an execution layer of another chain.
+
+use gaptos::{
+    aptos_config::config::{InitialSafetyRulesConfig, NodeConfig, WaypointConfig},
+    aptos_crypto::hash::ACCUMULATOR_PLACEHOLDER_HASH,
+    aptos_types::{
+        ledger_info::LedgerInfoWithSignatures, on_chain_config::ValidatorSet, waypoint::Waypoint,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+
+/// BCS encoded content of a genesis bundle file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenesisBundle {
+    pub chain_id: u64,
+    /// Waypoint of the genesis ledger info installing `validator_set`.
+    pub waypoint: Waypoint,
+    pub validator_set: ValidatorSet,
+    /// Hash of the genesis block of the execution layer.
+    pub execution_genesis_hash: [u8; 32],
+}
+
+impl GenesisBundle {
+    /// Loads a bundle and checks that its waypoint matches its validator set.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("failed to read genesis bundle {}: {e}", path.display()))?;
+        let bundle: Self = bcs::from_bytes(&bytes)
+            .map_err(|e| format!("failed to decode genesis bundle {}: {e}", path.display()))?;
+        bundle.verify()?;
+        Ok(bundle)
+    }
+
+    pub fn verify(&self) -> Result<(), String> {
+        let genesis = LedgerInfoWithSignatures::genesis(
+            *ACCUMULATOR_PLACEHOLDER_HASH,
+            self.validator_set.clone(),
+        );
+        self.waypoint
+            .verify(genesis.ledger_info())
+            .map_err(|e| format!("genesis bundle waypoint does not match its validator set: {e}"))
+    }
+
+    /// Execution genesis hash as `0x` prefixed hex.
+    pub fn execution_genesis_hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.execution_genesis_hash))
+    }
+
+    /// Checks the chain id and installs the bundle waypoint as the waypoint of the node and of
+    /// its safety rules. A waypoint already configured must be the bundle one.
+    pub fn apply(&self, chain_id: u64, node_config: &mut NodeConfig) -> Result<(), String> {
+        if chain_id != self.chain_id {
+            return Err(format!(
+                "chain id {chain_id} does not match the genesis bundle chain id {}",
+                self.chain_id
+            ));
+        }
+        self.install_waypoint(&mut node_config.base.waypoint, "base.waypoint")?;
+        if let InitialSafetyRulesConfig::FromFile { waypoint, .. } =
+            &mut node_config.consensus.safety_rules.initial_safety_rules_config
+        {
+            self.install_waypoint(waypoint, "safety rules waypoint")?;
+        }
+        Ok(())
+    }
+
+    fn install_waypoint(&self, config: &mut WaypointConfig, name: &str) -> Result<(), String> {
+        let configured = match config {
+            WaypointConfig::FromConfig(waypoint) => Some(*waypoint),
+            // The bundle replaces the waypoint file, which need not exist
+            WaypointConfig::FromFile(path) => match std::fs::read_to_string(&*path) {
+                Ok(waypoint) => Some(
+                    Waypoint::from_str(waypoint.trim())
+                        .map_err(|e| format!("invalid {name} in {}: {e}", path.display()))?,
+                ),
+                Err(_) => None,
+            },
+            // Safety rules check a stored waypoint against the genesis ledger info themselves
+            WaypointConfig::FromStorage(_) => return Ok(()),
+            WaypointConfig::None => None,
+        };
+        if let Some(configured) = configured.filter(|waypoint| *waypoint != self.waypoint) {
+            return Err(format!(
+                "{name} {configured} does not match the genesis bundle waypoint {}",
+                self.waypoint
+            ));
+        }
+        *config = WaypointConfig::FromConfig(self.waypoint);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::aptos_crypto::HashValue;
+
+    fn bundle() -> GenesisBundle {
+        let validator_set = ValidatorSet::new(vec![]);
+        let genesis =
+            LedgerInfoWithSignatures::genesis(*ACCUMULATOR_PLACEHOLDER_HASH, validator_set.clone());
+        GenesisBundle {
+            chain_id: 7,
+            waypoint: Waypoint::new_epoch_boundary(genesis.ledger_info()).unwrap(),
+            validator_set,
+            execution_genesis_hash: [0xab; 32],
+        }
+    }
+
+    /// Waypoint of another genesis.
+    fn other_waypoint() -> Waypoint {
+        let genesis =
+            LedgerInfoWithSignatures::genesis(HashValue::random(), ValidatorSet::new(vec![]));
+        Waypoint::new_epoch_boundary(genesis.ledger_info()).unwrap()
+    }
+
+    #[test]
+    fn loads_a_bundle_whose_waypoint_matches_its_validator_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis_bundle.bcs");
+        std::fs::write(&path, bcs::to_bytes(&bundle()).unwrap()).unwrap();
+        let loaded = GenesisBundle::load(&path).unwrap();
+        assert_eq!(loaded.waypoint, bundle().waypoint);
+        assert_eq!(loaded.execution_genesis_hash_hex(), format!("0x{}", "ab".repeat(32)));
+
+        let mismatching = GenesisBundle { waypoint: other_waypoint(), ..bundle() };
+        assert!(mismatching.verify().is_err());
+        std::fs::write(&path, bcs::to_bytes(&mismatching).unwrap()).unwrap();
+        assert!(GenesisBundle::load(&path).is_err());
+
+        std::fs::write(&path, b"not a bundle").unwrap();
+        assert!(GenesisBundle::load(&path).is_err());
+        assert!(GenesisBundle::load(&dir.path().join("missing.bcs")).is_err());
+    }
+
+    #[test]
+    fn apply_installs_the_waypoint_of_the_chain() {
+        let bundle = bundle();
+        let mut node_config = NodeConfig::default();
+        assert!(bundle.apply(8, &mut node_config).is_err());
+
+        bundle.apply(7, &mut node_config).unwrap();
+        assert_eq!(node_config.base.waypoint, WaypointConfig::FromConfig(bundle.waypoint));
+        // Applying it again finds its own waypoint
+        bundle.apply(7, &mut node_config).unwrap();
+
+        node_config.base.waypoint = WaypointConfig::FromConfig(other_waypoint());
+        assert!(bundle.apply(7, &mut node_config).is_err());
+    }
+
+    #[test]
+    fn install_waypoint_replaces_a_missing_waypoint_file_only() {
+        let bundle = bundle();
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut config = WaypointConfig::FromFile(dir.path().join("missing.txt"));
+        bundle.install_waypoint(&mut config, "waypoint").unwrap();
+        assert_eq!(config, WaypointConfig::FromConfig(bundle.waypoint));
+
+        let path = dir.path().join("waypoint.txt");
+        std::fs::write(&path, format!("{}\n", bundle.waypoint)).unwrap();
+        let mut config = WaypointConfig::FromFile(path.clone());
+        bundle.install_waypoint(&mut config, "waypoint").unwrap();
+        assert_eq!(config, WaypointConfig::FromConfig(bundle.waypoint));
+
+        std::fs::write(&path, other_waypoint().to_string()).unwrap();
+        let mut config = WaypointConfig::FromFile(path.clone());
+        assert!(bundle.install_waypoint(&mut config, "waypoint").is_err());
+        assert_eq!(config, WaypointConfig::FromFile(path.clone()));
+
+        std::fs::write(&path, "garbage").unwrap();
+        assert!(bundle.install_waypoint(&mut WaypointConfig::FromFile(path), "waypoint").is_err());
+    }
+}
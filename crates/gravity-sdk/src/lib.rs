@@ -1,3 +1,75 @@
-pub use api;
-pub use block_buffer_manager;
-pub use gaptos;
+//! The integration surface of a Gravity node for execution layers.
+//!
+//! An execution layer embeds consensus with [`consensus::ConsensusEngine`] and talks to it
+//! through the node's [`execution::BlockBufferManager`]: ordered blocks are pulled with
+//! `get_ordered_blocks`, results are returned with `set_compute_res`, and the commit stream is
+//! read with `get_committed_blocks`. Blocks replayed during block sync go through
+//! [`recovery::RecoveryApi`]. An engine running in another process uses [`remote`] instead.
+//!
+//! Everything re-exported from the modules below follows semver: a breaking change to any of
+//! them bumps the major version of this crate. The whole-crate re-exports at the bottom are
+//! internals that change with every release. They are deprecated and only kept until existing
+//! embedders have moved to the modules.
+
+/// Starting consensus inside the execution layer's process.
+pub mod consensus {
+    pub use api::{
+        check_bootstrap_config,
+        config_storage::{ConfigReadHistory, ConfigStorageWrapper},
+        consensus_api::{ConsensusEngine, ConsensusEngineArgs},
+        trusted_checkpoint::TrustedCheckpoint,
+        GravityNodeArgs, NodeConfig, NodeRole,
+    };
+    pub use gaptos::api_types::config_storage::{
+        BlockNumber, ConfigStorage, OnChainConfig, OnChainConfigResType,
+    };
+    pub use genesis_bundle::GenesisBundle;
+}
+
+/// The consensus-execution boundary of a node.
+pub mod execution {
+    /// Process-wide block buffer manager, see the `global-block-buffer-manager` feature.
+    #[cfg(feature = "global-block-buffer-manager")]
+    pub use block_buffer_manager::get_block_buffer_manager;
+    pub use block_buffer_manager::{
+        block_buffer_manager::{
+            BlockBufferManagerConfig, BlockExecutionMeta, BlockHashRef, EmptyTxPool, TxnItem,
+        },
+        BlockBufferManager, SystemTxnContext, SystemTxnProvider, TxPool,
+    };
+    pub use gaptos::api_types::{
+        compute_res::ComputeRes, u256_define::BlockId, ExternalBlock, VerifiedTxn,
+    };
+}
+
+/// Replaying blocks that block sync has verified as committed.
+pub mod recovery {
+    pub use aptos_consensus::{BlockBufferRecovery, RecoveryApi};
+}
+
+/// The execution boundary over gRPC, for engines in a separate process.
+pub mod remote {
+    pub use execution_grpc::{
+        serve, BlockRandomness, CommittedBlock, ExecutionChannelClient, ExecutionChannelService,
+    };
+}
+
+// `#[deprecated]` has no effect on `use` items, so the crates are wrapped in modules.
+
+#[doc(hidden)]
+#[deprecated(note = "use the `consensus` module instead")]
+pub mod api {
+    pub use ::api::*;
+}
+
+#[doc(hidden)]
+#[deprecated(note = "use the `execution` module instead")]
+pub mod block_buffer_manager {
+    pub use ::block_buffer_manager::*;
+}
+
+#[doc(hidden)]
+#[deprecated(note = "use the `consensus` and `execution` modules instead")]
+pub mod gaptos {
+    pub use ::gaptos::*;
+}
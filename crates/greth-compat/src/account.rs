@@ -0,0 +1,78 @@
+//! Conversions between EVM addresses and the 32 byte account addresses of consensus.
+//!
+//! An EVM address is stored right aligned: the first 12 bytes of the account address are zero.
+//! Account addresses that do not have this form have no EVM counterpart.
+
+use alloy_primitives::Address;
+use gaptos::api_types::account::ExternalAccountAddress;
+
+/// Length of the zero prefix of an account address holding an EVM address.
+const EVM_PADDING: usize = 32 - Address::len_bytes();
+
+pub trait ExternalAccountAddressExt: Sized {
+    fn from_evm(address: Address) -> Self;
+
+    /// The EVM address this account address holds, `None` if it is not EVM compatible.
+    fn to_evm(&self) -> Option<Address>;
+
+    fn is_evm_compatible(&self) -> bool {
+        self.to_evm().is_some()
+    }
+
+    /// EIP-55 checksummed form of the EVM address, `None` if the address is not EVM compatible.
+    fn to_checksum(&self) -> Option<String> {
+        self.to_evm().map(|address| address.to_checksum(None))
+    }
+}
+
+impl ExternalAccountAddressExt for ExternalAccountAddress {
+    fn from_evm(address: Address) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[EVM_PADDING..].copy_from_slice(address.as_slice());
+        ExternalAccountAddress::new(bytes)
+    }
+
+    fn to_evm(&self) -> Option<Address> {
+        let bytes = self.bytes();
+        let (padding, address) = bytes.split_at(EVM_PADDING);
+        padding.iter().all(|b| *b == 0).then(|| Address::from_slice(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn evm_address_roundtrips(bytes in any::<[u8; 20]>()) {
+            let address = Address::from(bytes);
+            let account = ExternalAccountAddress::from_evm(address);
+            prop_assert!(account.is_evm_compatible());
+            prop_assert_eq!(account.to_evm(), Some(address));
+            prop_assert_eq!(account.to_checksum(), Some(address.to_checksum(None)));
+        }
+
+        #[test]
+        fn account_address_roundtrips_if_compatible(bytes in any::<[u8; 32]>()) {
+            let account = ExternalAccountAddress::new(bytes);
+            match account.to_evm() {
+                Some(address) => {
+                    prop_assert_eq!(ExternalAccountAddress::from_evm(address).bytes(), bytes)
+                }
+                None => prop_assert!(bytes[..EVM_PADDING].iter().any(|b| *b != 0)),
+            }
+        }
+    }
+
+    #[test]
+    fn checksum_follows_eip55() {
+        let address: Address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+        assert_eq!(
+            ExternalAccountAddress::from_evm(address).to_checksum().as_deref(),
+            Some("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+        );
+        assert_eq!(ExternalAccountAddress::new([1; 32]).to_checksum(), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! The single entry point from gravity_node into greth.
+//!
+//! greth moves quickly and a revision bump regularly renames types or changes their generic
+//! parameters. The node therefore only talks to the execution layer through the traits in this
+//! crate ([`ExecutionPipe`], [`ChainStateReader`], [`AccountStateReader`]) and the concrete
+//! types in [`types`], so that a bump is absorbed here instead of across the node.
+//!
+//! The modules below mirror greth's paths but only re-export the items the node needs to launch
+//! reth itself, so the surface a bump can break stays listed here. New code should prefer adding a
+//! trait here over growing them.
+
+mod account;
+mod pipe;
+mod provider;
+pub mod types;
+
+pub use account::ExternalAccountAddressExt;
+pub use pipe::ExecutionPipe;
+pub use provider::{AccountState, AccountStateReader, ChainStateReader};
+
+pub mod gravity_storage {
+    pub mod block_view_storage {
+        pub use greth::gravity_storage::block_view_storage::BlockViewStorage;
+    }
+}
+
+pub mod reth {
+    pub mod chainspec {
+        pub use greth::reth::chainspec::EthereumChainSpecParser;
+    }
+    pub mod cli {
+        pub use greth::reth::cli::Commands;
+    }
+    pub mod rpc {
+        pub mod builder {
+            pub mod auth {
+                pub use greth::reth::rpc::builder::auth::AuthServerHandle;
+            }
+        }
+    }
+}
+
+pub mod reth_chainspec {
+    pub use greth::reth_chainspec::{
+        ChainSpec, ChainSpecBuilder, ChainSpecProvider, EthereumHardfork, EthereumHardforks,
+        ForkCondition, GravityHardfork,
+    };
+}
+
+pub mod reth_cli {
+    pub mod chainspec {
+        pub use greth::reth_cli::chainspec::{parse_genesis, ChainSpecParser};
+    }
+}
+
+pub mod reth_cli_commands {
+    pub mod launcher {
+        pub use greth::reth_cli_commands::launcher::FnLauncher;
+    }
+    pub mod node {
+        pub use greth::reth_cli_commands::node::NoArgs;
+    }
+}
+
+pub mod reth_cli_runner {
+    pub use greth::reth_cli_runner::CliRunner;
+}
+
+pub mod reth_cli_util {
+    pub mod sigsegv_handler {
+        pub use greth::reth_cli_util::sigsegv_handler::install;
+    }
+}
+
+pub mod reth_db {
+    pub use greth::reth_db::DatabaseEnv;
+}
+
+pub mod reth_node_api {
+    pub use greth::reth_node_api::TreeConfig;
+}
+
+pub mod reth_node_builder {
+    pub use greth::reth_node_builder::{EngineNodeLauncher, NodeBuilder, WithLaunchContext};
+}
+
+pub mod reth_node_core {
+    pub mod args {
+        pub use greth::reth_node_core::args::LogArgs;
+    }
+}
+
+pub mod reth_node_ethereum {
+    pub use greth::reth_node_ethereum::{EthEvmConfig, EthereumNode};
+    pub mod consensus {
+        pub use greth::reth_node_ethereum::consensus::EthBeaconConsensus;
+    }
+    pub mod node {
+        pub use greth::reth_node_ethereum::node::EthereumAddOns;
+    }
+}
+
+pub mod reth_pipe_exec_layer_ext_v2 {
+    pub use greth::reth_pipe_exec_layer_ext_v2::{
+        new_pipe_exec_layer_api, ExecutionArgs, ExecutionResult, OrderedBlock,
+    };
+}
+
+pub mod reth_pipe_exec_layer_relayer {
+    pub use greth::reth_pipe_exec_layer_relayer::OracleRelayerManager;
+}
+
+pub mod reth_primitives {
+    pub use greth::reth_primitives::{Recovered, TransactionSigned};
+}
+
+pub mod reth_provider {
+    pub use greth::reth_provider::{BlockHashReader, BlockNumReader, BlockReader};
+    pub mod providers {
+        pub use greth::reth_provider::providers::BlockchainProvider;
+    }
+}
+
+pub mod reth_tracing {
+    pub use greth::reth_tracing::FileWorkerGuard;
+}
+
+pub mod reth_transaction_pool {
+    pub use greth::reth_transaction_pool::{
+        BestTransactions, EthPooledTransaction, PoolConfig, PoolTransaction, TransactionOrigin,
+        TransactionPool, ValidPoolTransaction,
+    };
+    pub mod error {
+        pub use greth::reth_transaction_pool::error::PoolErrorKind;
+    }
+}
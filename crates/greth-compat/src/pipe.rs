@@ -0,0 +1,62 @@
+use crate::types::{RethEthCall, RethPipeExecLayerApi};
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use gaptos::api_types::config_storage::{BlockNumber, OnChainConfig, OnChainConfigResType};
+use greth::reth_pipe_exec_layer_ext_v2::{ExecutionResult, OrderedBlock};
+
+/// The pipelined execution API of greth, as used by the node.
+#[async_trait]
+pub trait ExecutionPipe: Send + Sync {
+    /// Hands an ordered block to the execution layer.
+    fn push_ordered_block(&self, block: OrderedBlock);
+
+    /// Waits for the next executed block, `None` once the execution layer has shut down.
+    async fn pull_executed_block_hash(&self) -> Option<ExecutionResult>;
+
+    /// Tells the execution layer that the block has been committed by consensus.
+    fn commit_executed_block_hash(&self, block_id: B256, block_hash: Option<B256>);
+
+    /// Waits until the block has been persisted to the execution layer database.
+    async fn wait_for_block_persistence(&self, block_number: u64);
+
+    /// The consensus block id of an executed block.
+    fn get_block_id(&self, block_number: u64) -> Option<B256>;
+
+    /// Reads an on-chain config as of the given block.
+    fn fetch_config_bytes(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Option<OnChainConfigResType>;
+}
+
+#[async_trait]
+impl<EthApi: RethEthCall> ExecutionPipe for RethPipeExecLayerApi<EthApi> {
+    fn push_ordered_block(&self, block: OrderedBlock) {
+        RethPipeExecLayerApi::push_ordered_block(self, block);
+    }
+
+    async fn pull_executed_block_hash(&self) -> Option<ExecutionResult> {
+        RethPipeExecLayerApi::pull_executed_block_hash(self).await
+    }
+
+    fn commit_executed_block_hash(&self, block_id: B256, block_hash: Option<B256>) {
+        RethPipeExecLayerApi::commit_executed_block_hash(self, block_id, block_hash);
+    }
+
+    async fn wait_for_block_persistence(&self, block_number: u64) {
+        RethPipeExecLayerApi::wait_for_block_persistence(self, block_number).await;
+    }
+
+    fn get_block_id(&self, block_number: u64) -> Option<B256> {
+        RethPipeExecLayerApi::get_block_id(self, block_number)
+    }
+
+    fn fetch_config_bytes(
+        &self,
+        config_name: OnChainConfig,
+        block_number: BlockNumber,
+    ) -> Option<OnChainConfigResType> {
+        RethPipeExecLayerApi::fetch_config_bytes(self, config_name, block_number)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::types::RethBlockChainProvider;
+use alloy_primitives::{Address, U256};
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use anyhow::anyhow;
+use greth::{
+    reth_chainspec::ChainKind,
+    reth_provider::{
+        AccountReader, BlockNumReader, ChainSpecProvider, HeaderProvider, StateProviderBox,
+        StateProviderFactory,
+    },
+};
+
+/// The committed balance and nonce of an account.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccountState {
+    pub balance: U256,
+    pub nonce: u64,
+}
+
+/// Account reads against one state snapshot.
+pub trait AccountStateReader: Send {
+    /// `None` if the account does not exist.
+    fn account(&self, address: &Address) -> anyhow::Result<Option<AccountState>>;
+
+    /// Whether a contract is deployed at the address.
+    fn has_code(&self, address: &Address) -> anyhow::Result<bool>;
+}
+
+impl AccountStateReader for StateProviderBox {
+    fn account(&self, address: &Address) -> anyhow::Result<Option<AccountState>> {
+        let account = self.basic_account(address).map_err(|e| anyhow!("{e}"))?;
+        Ok(account.map(|account| AccountState { balance: account.balance, nonce: account.nonce }))
+    }
+
+    fn has_code(&self, address: &Address) -> anyhow::Result<bool> {
+        let account = self.basic_account(address).map_err(|e| anyhow!("{e}"))?;
+        Ok(account.is_some_and(|account| account.has_bytecode()))
+    }
+}
+
+/// Read access to the chain persisted by the execution layer.
+pub trait ChainStateReader: Send + Sync {
+    fn chain_id(&self) -> u64;
+
+    /// The highest block persisted to the execution layer database.
+    fn latest_block_number(&self) -> anyhow::Result<u64>;
+
+    /// A snapshot of the state at the latest persisted block.
+    fn latest_state(&self) -> anyhow::Result<Box<dyn AccountStateReader>>;
+
+    /// A snapshot of the state after block `number`, `None` until the block is made canonical.
+    fn state_at(&self, number: u64) -> anyhow::Result<Option<Box<dyn AccountStateReader>>>;
+
+    /// Gas used by a canonical block, `None` until the block is made canonical.
+    fn block_gas_used(&self, number: u64) -> anyhow::Result<Option<u64>>;
+}
+
+impl ChainStateReader for RethBlockChainProvider {
+    fn chain_id(&self) -> u64 {
+        match self.chain_spec().chain.into_kind() {
+            ChainKind::Named(n) => n as u64,
+            ChainKind::Id(id) => id,
+        }
+    }
+
+    fn latest_block_number(&self) -> anyhow::Result<u64> {
+        self.recover_block_number().map_err(|e| anyhow!("{e}"))
+    }
+
+    fn latest_state(&self) -> anyhow::Result<Box<dyn AccountStateReader>> {
+        let state = self.latest().map_err(|e| anyhow!("{e}"))?;
+        Ok(Box::new(state))
+    }
+
+    fn state_at(&self, number: u64) -> anyhow::Result<Option<Box<dyn AccountStateReader>>> {
+        if self.header_by_number(number).map_err(|e| anyhow!("{e}"))?.is_none() {
+            return Ok(None);
+        }
+        let state = self
+            .state_by_block_number_or_tag(BlockNumberOrTag::Number(number))
+            .map_err(|e| anyhow!("{e}"))?;
+        Ok(Some(Box::new(state)))
+    }
+
+    fn block_gas_used(&self, number: u64) -> anyhow::Result<Option<u64>> {
+        let header = self.header_by_number(number).map_err(|e| anyhow!("{e}"))?;
+        Ok(header.map(|header| header.gas_used))
+    }
+}
@@ -0,0 +1,42 @@
+//! Concrete greth types the node is instantiated with.
+
+use alloy_rpc_types_eth::TransactionRequest;
+use greth::{
+    gravity_storage::block_view_storage::BlockViewStorage,
+    reth_db::DatabaseEnv,
+    reth_node_api::NodeTypesWithDBAdapter,
+    reth_node_ethereum::EthereumNode,
+    reth_pipe_exec_layer_ext_v2::PipeExecLayerApi,
+    reth_provider::providers::BlockchainProvider,
+    reth_rpc_api::eth::{helpers::EthCall, RpcTypes},
+    reth_transaction_pool::{
+        blobstore::DiskFileBlobStore, CoinbaseTipOrdering, EthPooledTransaction,
+        EthTransactionValidator, Pool, TransactionValidationTaskExecutor,
+    },
+};
+use std::sync::Arc;
+
+pub type RethBlockChainProvider =
+    BlockchainProvider<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>;
+
+pub type RethTransactionPool = Pool<
+    TransactionValidationTaskExecutor<
+        EthTransactionValidator<RethBlockChainProvider, EthPooledTransaction>,
+    >,
+    CoinbaseTipOrdering<EthPooledTransaction>,
+    DiskFileBlobStore,
+>;
+
+/// The eth API the pipe exec layer is built with.
+pub trait RethEthCall:
+    EthCall<NetworkTypes: RpcTypes<TransactionRequest = TransactionRequest>>
+{
+}
+
+impl<T> RethEthCall for T where
+    T: EthCall<NetworkTypes: RpcTypes<TransactionRequest = TransactionRequest>>
+{
+}
+
+pub type RethPipeExecLayerApi<EthApi> =
+    PipeExecLayerApi<BlockViewStorage<RethBlockChainProvider>, EthApi>;
@@ -1,7 +1,43 @@
-use gaptos::aptos_infallible::RwLock as InfallibleRwLock;
+use gaptos::{
+    aptos_infallible::RwLock as InfallibleRwLock, aptos_types::on_chain_config::ValidatorSet,
+};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
+/// Per-epoch data shared by the consensus engine with the pipeline and execution adapters.
+///
+/// Built once when an epoch starts and handed out as an `Arc`, so blocks of an epoch always
+/// resolve against that epoch's validator set, even while the next epoch is being set up.
+#[derive(Debug, Default)]
+pub struct EpochContext {
+    epoch: u64,
+    /// validator_index -> reth_account_address
+    proposer_reth_addresses: HashMap<u64, Vec<u8>>,
+}
+
+impl EpochContext {
+    pub fn new(epoch: u64, validator_set: &ValidatorSet) -> Self {
+        let proposer_reth_addresses = validator_set
+            .active_validators
+            .iter()
+            .map(|validator| {
+                (validator.config().validator_index, validator.reth_account_address.clone())
+            })
+            .collect();
+        Self { epoch, proposer_reth_addresses }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Get the reth account address for a given validator index
+    /// Returns None if the validator index is not in this epoch's validator set
+    pub fn reth_address_by_index(&self, validator_index: u64) -> Option<&[u8]> {
+        self.proposer_reth_addresses.get(&validator_index).map(Vec::as_slice)
+    }
+}
+
 /// Global map from validator_index to reth_account_address for current epoch
 /// This is updated when a new epoch starts
 static PROPOSER_RETH_ADDRESS_MAP: Lazy<InfallibleRwLock<HashMap<u64, Vec<u8>>>> =
@@ -9,15 +45,15 @@ static PROPOSER_RETH_ADDRESS_MAP: Lazy<InfallibleRwLock<HashMap<u64, Vec<u8>>>>
 
 /// Get the reth account address for a given validator index
 /// Returns None if the validator index is not found in the current epoch's validator set
+#[deprecated(note = "use the EpochContext of the block's epoch instead")]
 pub fn get_reth_address_by_index(validator_index: u64) -> Option<Vec<u8>> {
     PROPOSER_RETH_ADDRESS_MAP.read().get(&validator_index).cloned()
 }
 
 /// Update the proposer reth address map for a new epoch
 /// Maps validator_index -> reth_account_address
-pub fn update_proposer_reth_index_map(
-    validator_set: &gaptos::aptos_types::on_chain_config::ValidatorSet,
-) {
+#[deprecated(note = "build an EpochContext for the new epoch instead")]
+pub fn update_proposer_reth_index_map(validator_set: &ValidatorSet) {
     let mut reth_address_map = HashMap::new();
     for validator in validator_set.active_validators.iter() {
         let validator_index = validator.config().validator_index;
@@ -0,0 +1,259 @@
+//! Knobs that can be inspected and changed while the node runs.
+//!
+//! A module registers each tunable it wants to expose with [`RuntimeConfigRegistry::register`]
+//! and reads the returned [`Knob`] every time it uses the value, instead of caching the
+//! environment variable in a `OnceLock`. The environment variable still provides the value at
+//! startup. The admin API of the node lists the registered knobs and updates them.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+};
+
+/// Whether a knob takes whole numbers or fractions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KnobKind {
+    Integer,
+    Fraction,
+}
+
+/// The value of a knob, a number in the JSON of the admin API.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KnobValue {
+    Integer(u64),
+    Fraction(f64),
+}
+
+impl std::fmt::Display for KnobValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KnobValue::Integer(value) => write!(f, "{value}"),
+            KnobValue::Fraction(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+pub struct Knob {
+    name: &'static str,
+    description: &'static str,
+    env: &'static str,
+    kind: KnobKind,
+    max: f64,
+    /// Bits of the `f64` value.
+    value: AtomicU64,
+}
+
+impl Knob {
+    /// The value of an integer knob, or a fraction knob rounded down.
+    pub fn get(&self) -> u64 {
+        self.get_f64() as u64
+    }
+
+    pub fn get_f64(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn to_value(&self, value: f64) -> KnobValue {
+        match self.kind {
+            KnobKind::Integer => KnobValue::Integer(value as u64),
+            KnobKind::Fraction => KnobValue::Fraction(value),
+        }
+    }
+
+    /// Checks that `value` is a valid value of the knob.
+    fn check(&self, value: f64) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            value.is_finite() && value >= 0.0,
+            "{} must be a non-negative number, got {value}",
+            self.name
+        );
+        anyhow::ensure!(
+            self.kind == KnobKind::Fraction || value.fract() == 0.0,
+            "{} must be a whole number, got {value}",
+            self.name
+        );
+        anyhow::ensure!(
+            value <= self.max,
+            "{} must be at most {}, got {value}",
+            self.name,
+            self.to_value(self.max)
+        );
+        Ok(())
+    }
+
+    fn view(&self) -> KnobView {
+        KnobView {
+            name: self.name,
+            description: self.description,
+            env: self.env,
+            kind: self.kind,
+            max: self.to_value(self.max),
+            value: self.to_value(self.get_f64()),
+        }
+    }
+}
+
+/// A knob as reported by the admin API.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct KnobView {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Environment variable giving the value at startup.
+    pub env: &'static str,
+    pub kind: KnobKind,
+    pub max: KnobValue,
+    pub value: KnobValue,
+}
+
+#[derive(Default)]
+pub struct RuntimeConfigRegistry {
+    knobs: RwLock<BTreeMap<&'static str, Arc<Knob>>>,
+}
+
+impl RuntimeConfigRegistry {
+    /// The registry shared by all modules of the process.
+    pub fn global() -> &'static Self {
+        static REGISTRY: OnceLock<RuntimeConfigRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(Self::default)
+    }
+
+    /// Registers a whole number knob starting at the value of `env`, or `default` if it is
+    /// unset. An invalid value or one above `max` is logged and replaced by `default`.
+    /// Registering a name again returns the existing knob.
+    pub fn register(
+        &self,
+        name: &'static str,
+        description: &'static str,
+        env: &'static str,
+        default: u64,
+        max: u64,
+    ) -> Arc<Knob> {
+        self.register_knob(name, description, env, KnobKind::Integer, default as f64, max as f64)
+    }
+
+    /// Like [`Self::register`], for a knob taking fractions.
+    pub fn register_fraction(
+        &self,
+        name: &'static str,
+        description: &'static str,
+        env: &'static str,
+        default: f64,
+        max: f64,
+    ) -> Arc<Knob> {
+        self.register_knob(name, description, env, KnobKind::Fraction, default, max)
+    }
+
+    fn register_knob(
+        &self,
+        name: &'static str,
+        description: &'static str,
+        env: &'static str,
+        kind: KnobKind,
+        default: f64,
+        max: f64,
+    ) -> Arc<Knob> {
+        let mut knobs = self.knobs.write().unwrap();
+        knobs
+            .entry(name)
+            .or_insert_with(|| {
+                let knob = Knob {
+                    name,
+                    description,
+                    env,
+                    kind,
+                    max,
+                    value: AtomicU64::new(default.to_bits()),
+                };
+                if let Ok(value) = std::env::var(env) {
+                    let parsed = value
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|e| anyhow::anyhow!("{name} must be a number: {e}"))
+                        .and_then(|parsed| knob.check(parsed).map(|()| parsed));
+                    match parsed {
+                        Ok(parsed) => knob.value.store(parsed.to_bits(), Ordering::Relaxed),
+                        Err(e) => tracing::error!(
+                            "Ignoring {env}={value:?}, {name} stays at {}: {e:#}",
+                            knob.to_value(default)
+                        ),
+                    }
+                }
+                Arc::new(knob)
+            })
+            .clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<KnobView> {
+        self.knobs.read().unwrap().get(name).map(|knob| knob.view())
+    }
+
+    pub fn list(&self) -> Vec<KnobView> {
+        self.knobs.read().unwrap().values().map(|knob| knob.view()).collect()
+    }
+
+    /// Changes the value of a registered knob and returns the previous one.
+    pub fn set(&self, name: &str, value: f64) -> anyhow::Result<KnobValue> {
+        let knobs = self.knobs.read().unwrap();
+        let knob = knobs.get(name).ok_or_else(|| anyhow::anyhow!("unknown knob '{name}'"))?;
+        knob.check(value)?;
+        Ok(knob.to_value(f64::from_bits(knob.value.swap(value.to_bits(), Ordering::Relaxed))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_updates_registered_knobs() {
+        let registry = RuntimeConfigRegistry::default();
+        let knob = registry.register("batch_size", "Blocks per batch", "UNSET_TEST_KNOB", 8, 64);
+        assert_eq!(knob.get(), 8);
+
+        assert_eq!(registry.set("batch_size", 16.0).unwrap(), KnobValue::Integer(8));
+        assert_eq!(knob.get(), 16);
+        assert_eq!(registry.get("batch_size").unwrap().value, KnobValue::Integer(16));
+        assert!(registry.set("batch_size", 65.0).is_err());
+        assert!(registry.set("batch_size", 1.5).is_err());
+        assert!(registry.set("batch_size", -1.0).is_err());
+        assert!(registry.set("unknown", 1.0).is_err());
+
+        // Registering again hands out the same knob
+        let again = registry.register("batch_size", "Blocks per batch", "UNSET_TEST_KNOB", 8, 64);
+        assert_eq!(again.get(), 16);
+        assert_eq!(registry.list().len(), 1);
+    }
+
+    #[test]
+    fn fraction_knobs_keep_fractions() {
+        let registry = RuntimeConfigRegistry::default();
+        let knob = registry.register_fraction("rate", "Per second", "UNSET_TEST_KNOB", 0.5, 10.0);
+        assert_eq!(knob.get_f64(), 0.5);
+        assert_eq!(registry.set("rate", 2.25).unwrap(), KnobValue::Fraction(0.5));
+        assert_eq!(knob.get_f64(), 2.25);
+        assert!(registry.set("rate", f64::NAN).is_err());
+        assert!(registry.set("rate", 10.5).is_err());
+    }
+
+    #[test]
+    fn invalid_startup_values_keep_the_default() {
+        let registry = RuntimeConfigRegistry::default();
+        std::env::set_var("RUNTIME_CONFIG_TEST_TOO_LARGE", "65");
+        std::env::set_var("RUNTIME_CONFIG_TEST_NOT_A_NUMBER", "lots");
+        std::env::set_var("RUNTIME_CONFIG_TEST_VALID", " 32 ");
+        let too_large = registry.register("a", "", "RUNTIME_CONFIG_TEST_TOO_LARGE", 8, 64);
+        let not_a_number = registry.register("b", "", "RUNTIME_CONFIG_TEST_NOT_A_NUMBER", 8, 64);
+        let valid = registry.register("c", "", "RUNTIME_CONFIG_TEST_VALID", 8, 64);
+        assert_eq!((too_large.get(), not_a_number.get(), valid.get()), (8, 8, 32));
+    }
+}
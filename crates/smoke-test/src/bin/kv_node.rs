@@ -0,0 +1,65 @@
+//! A validator of a smoke-test cluster, started by [`smoke_test::Cluster`].
+//!
+//! Usage: `kv_node <node_dir> <execution_channel_addr> <latest_block_number>`
+//!
+//! Transactions are read from stdin as `key=value` lines and blocks are executed by whoever
+//! connects to the execution channel.
+
+use anyhow::Context;
+use api::{
+    check_bootstrap_config,
+    consensus_api::{ConsensusEngine, ConsensusEngineArgs},
+};
+use block_buffer_manager::BlockBufferManager;
+use smoke_test::{
+    genesis::{DKG_STATE_FILE, VALIDATOR_SET_FILE},
+    node::{KvConfigStorage, KvTxPool, CHAIN_ID},
+};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let [_, node_dir, execution_channel, latest_block_number] = args.as_slice() else {
+        anyhow::bail!("usage: kv_node <node_dir> <execution_channel_addr> <latest_block_number>");
+    };
+    let node_dir = PathBuf::from(node_dir);
+    let execution_channel: SocketAddr =
+        execution_channel.parse().context("invalid execution channel address")?;
+    let latest_block_number: u64 =
+        latest_block_number.parse().context("invalid latest block number")?;
+
+    let (node_config, _) = check_bootstrap_config(Some(node_dir.join("validator.yaml")));
+    let validator_set = std::fs::read(node_dir.join(VALIDATOR_SET_FILE))
+        .with_context(|| format!("failed to read {VALIDATOR_SET_FILE}"))?;
+    let mut config_storage = KvConfigStorage::new(validator_set);
+    // Only written for clusters with randomness
+    let dkg_state_path = node_dir.join(DKG_STATE_FILE);
+    if dkg_state_path.exists() {
+        let dkg_state = std::fs::read(&dkg_state_path)
+            .with_context(|| format!("failed to read {DKG_STATE_FILE}"))?;
+        config_storage = config_storage.with_randomness(dkg_state)?;
+    }
+    let pool = KvTxPool::default();
+    pool.spawn_stdin_reader();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    runtime.block_on(async move {
+        let block_buffer_manager = BlockBufferManager::new(Default::default());
+        let _engine = ConsensusEngine::init(
+            ConsensusEngineArgs {
+                node_config,
+                chain_id: CHAIN_ID,
+                latest_block_number,
+                config_storage: Some(Arc::new(config_storage)),
+                config_reads: None,
+                block_buffer_manager: block_buffer_manager.clone(),
+                trusted_checkpoint: None,
+                genesis_bundle: None,
+                recovery_api: None,
+            },
+            Box::new(pool),
+        )
+        .await;
+        execution_grpc::serve(execution_channel, block_buffer_manager).await
+    })
+}
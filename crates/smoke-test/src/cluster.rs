@@ -0,0 +1,356 @@
+//! A local cluster of `kv_node` validators, each driven by a [`KvState`] executor in this
+//! process.
+
+use crate::{
+    executor::{spawn_executor, CommittedKvBlock, EpochChange, KvState},
+    genesis::{node_dir, write_configs, NodePorts},
+};
+use anyhow::{bail, ensure, Context};
+use execution_grpc::ExecutionChannelClient;
+use gaptos::api_types;
+use std::{
+    fs::File,
+    path::PathBuf,
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    io::AsyncWriteExt,
+    process::{Child, ChildStdin, Command},
+    task::JoinHandle,
+};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub struct ClusterBuilder {
+    node_binary: PathBuf,
+    num_nodes: usize,
+    epoch_length: Option<u64>,
+    next_validator_set: Option<Vec<u8>>,
+    root: Option<PathBuf>,
+    seed: [u8; 32],
+    randomness: bool,
+}
+
+impl ClusterBuilder {
+    /// `node_binary` is the `kv_node` binary of this crate, `env!("CARGO_BIN_EXE_kv_node")` in
+    /// its tests.
+    pub fn new(node_binary: impl Into<PathBuf>) -> Self {
+        Self {
+            node_binary: node_binary.into(),
+            num_nodes: 4,
+            epoch_length: None,
+            next_validator_set: None,
+            root: None,
+            seed: [0; 32],
+            randomness: false,
+        }
+    }
+
+    pub fn num_nodes(mut self, num_nodes: usize) -> Self {
+        self.num_nodes = num_nodes;
+        self
+    }
+
+    /// Ends an epoch every `epoch_length` blocks. Epochs never change by default.
+    pub fn epoch_length(mut self, epoch_length: u64) -> Self {
+        self.epoch_length = Some(epoch_length);
+        self
+    }
+
+    /// Payload of the `NewEpoch` events, a BCS encoded onchain validator set. Defaults to the
+    /// genesis validator set.
+    pub fn next_validator_set(mut self, validator_set: Vec<u8>) -> Self {
+        self.next_validator_set = Some(validator_set);
+        self
+    }
+
+    /// Directory of the node configs, data and logs. Defaults to a new directory under the
+    /// system temp directory, which is kept so the logs of a failed run can be read.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    pub fn seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Enables randomness in the first epoch, so blocks carry randomness and transactions
+    /// setting a key to [`crate::node::RANDOM_VALUE`] store a value derived from it. Later
+    /// epochs run without randomness, as the nodes do not run a DKG.
+    pub fn randomness(mut self) -> Self {
+        self.randomness = true;
+        self
+    }
+
+    /// Writes the configs and starts every node.
+    pub async fn build(self) -> anyhow::Result<Cluster> {
+        ensure!(self.num_nodes > 0, "a cluster needs at least one node");
+        let root = match self.root {
+            Some(root) => root,
+            None => {
+                let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+                std::env::temp_dir()
+                    .join(format!("gravity-smoke-test-{}-{nanos}", std::process::id()))
+            }
+        };
+        let ports = (0..self.num_nodes)
+            .map(|_| NodePorts::allocate())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let validator_set = write_configs(&root, &ports, self.seed, self.randomness)?;
+
+        let epoch_change = match self.epoch_length {
+            Some(length) => {
+                ensure!(length > 0, "epoch length must be positive");
+                let validator_set = match self.next_validator_set {
+                    Some(bytes) => bytes,
+                    None => bcs::to_bytes(&validator_set)?,
+                };
+                // The block buffer manager rejects a payload it cannot decode, which would
+                // otherwise only show up as a stuck cluster
+                bcs::from_bytes::<api_types::on_chain_config::validator_set::ValidatorSet>(
+                    &validator_set,
+                )
+                .context(
+                    "the next validator set does not decode as an onchain validator set, \
+                     pass one with ClusterBuilder::next_validator_set",
+                )?;
+                Some(EpochChange { length, validator_set })
+            }
+            None => None,
+        };
+
+        let mut cluster = Cluster {
+            root,
+            node_binary: self.node_binary,
+            epoch_change,
+            nodes: ports.into_iter().map(Node::new).collect(),
+        };
+        for index in 0..cluster.nodes.len() {
+            cluster.start_node(index).await?;
+        }
+        Ok(cluster)
+    }
+}
+
+struct Node {
+    ports: NodePorts,
+    state: Arc<Mutex<KvState>>,
+    process: Option<NodeProcess>,
+}
+
+struct NodeProcess {
+    child: Child,
+    stdin: ChildStdin,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Node {
+    fn new(ports: NodePorts) -> Self {
+        Self { ports, state: Default::default(), process: None }
+    }
+}
+
+/// Nodes run as subprocesses because the config storage and the logger of a node are process
+/// globals. Their execution layer runs here, against the execution channel of each node.
+pub struct Cluster {
+    root: PathBuf,
+    node_binary: PathBuf,
+    epoch_change: Option<EpochChange>,
+    nodes: Vec<Node>,
+}
+
+impl Cluster {
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Starts node `index` from its committed state. Its consensus DB is kept across restarts,
+    /// so consensus replays the blocks ordered after that state.
+    pub async fn start_node(&mut self, index: usize) -> anyhow::Result<()> {
+        let dir = node_dir(&self.root, index);
+        let node = &mut self.nodes[index];
+        ensure!(node.process.is_none(), "node {index} is already running");
+        let latest_committed = {
+            let mut state = node.state.lock().unwrap();
+            state.rewind_to_committed();
+            state.latest_committed()
+        };
+        let execution_channel = format!("127.0.0.1:{}", node.ports.execution_channel);
+
+        let log = File::options().create(true).append(true).open(dir.join("node.log"))?;
+        let mut child = Command::new(&self.node_binary)
+            .arg(&dir)
+            .arg(&execution_channel)
+            .arg(latest_committed.to_string())
+            .stdin(Stdio::piped())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to start {}", self.node_binary.display()))?;
+        let stdin = child.stdin.take().context("stdin of the node is not piped")?;
+
+        let start = Instant::now();
+        let client = loop {
+            match ExecutionChannelClient::connect(format!("http://{execution_channel}")).await {
+                Ok(client) => break client,
+                Err(e) if start.elapsed() > CONNECT_TIMEOUT => {
+                    return Err(
+                        e.context(format!("node {index} did not serve its execution channel"))
+                    )
+                }
+                Err(_) => {
+                    if let Some(status) = child.try_wait()? {
+                        bail!("node {index} exited with {status}, see {}", dir.display());
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        };
+        let tasks = spawn_executor(client, node.state.clone(), self.epoch_change.clone());
+        node.process = Some(NodeProcess { child, stdin, tasks });
+        Ok(())
+    }
+
+    /// Kills node `index`. What it committed is kept for [`Cluster::start_node`].
+    pub async fn stop_node(&mut self, index: usize) -> anyhow::Result<()> {
+        let Some(mut process) = self.nodes[index].process.take() else {
+            bail!("node {index} is not running");
+        };
+        for task in &process.tasks {
+            task.abort();
+        }
+        process.child.kill().await?;
+        Ok(())
+    }
+
+    /// Sends a transaction setting `key` to `value` to the pool of node `index`.
+    pub async fn submit(&mut self, index: usize, key: &str, value: &str) -> anyhow::Result<()> {
+        ensure!(
+            !key.contains(['=', '\n']) && !value.contains('\n'),
+            "keys cannot contain '=' or newlines, values cannot contain newlines"
+        );
+        let process = self.nodes[index].process.as_mut().context("node is not running")?;
+        process.stdin.write_all(format!("{key}={value}\n").as_bytes()).await?;
+        process.stdin.flush().await?;
+        Ok(())
+    }
+
+    pub fn committed_block_number(&self, index: usize) -> u64 {
+        self.nodes[index].state.lock().unwrap().latest_committed()
+    }
+
+    /// Epoch of the latest block node `index` committed.
+    pub fn epoch(&self, index: usize) -> u64 {
+        self.nodes[index].state.lock().unwrap().epoch()
+    }
+
+    /// Committed value of `key` on node `index`.
+    pub fn value(&self, index: usize, key: &str) -> Option<String> {
+        self.nodes[index].state.lock().unwrap().value(key)
+    }
+
+    /// Waits until every running node committed `block_number`.
+    pub async fn wait_for_block(&self, block_number: u64, timeout: Duration) -> anyhow::Result<()> {
+        self.wait_for(timeout, &format!("block {block_number}"), |state| {
+            state.latest_committed() >= block_number
+        })
+        .await
+    }
+
+    /// Waits until every running node committed a block of `epoch`.
+    pub async fn wait_for_epoch(&self, epoch: u64, timeout: Duration) -> anyhow::Result<()> {
+        self.wait_for(timeout, &format!("epoch {epoch}"), |state| state.epoch() >= epoch).await
+    }
+
+    /// Waits until every running node committed a value for `key`.
+    pub async fn wait_for_key(&self, key: &str, timeout: Duration) -> anyhow::Result<()> {
+        self.wait_for(timeout, key, |state| state.value(key).is_some()).await
+    }
+
+    /// Waits until every running node committed `key` set to `value`.
+    pub async fn wait_for_value(
+        &self,
+        key: &str,
+        value: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<()> {
+        self.wait_for(timeout, &format!("{key}={value}"), |state| {
+            state.value(key).as_deref() == Some(value)
+        })
+        .await
+    }
+
+    async fn wait_for(
+        &self,
+        timeout: Duration,
+        what: &str,
+        reached: impl Fn(&KvState) -> bool,
+    ) -> anyhow::Result<()> {
+        let start = Instant::now();
+        loop {
+            let behind = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.process.is_some())
+                .filter(|(_, node)| !reached(&node.state.lock().unwrap()))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+            if behind.is_empty() {
+                return Ok(());
+            }
+            if start.elapsed() > timeout {
+                bail!(
+                    "nodes {behind:?} did not reach {what} within {timeout:?}, logs are in {}",
+                    self.root.display()
+                );
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Checks that the nodes agree on every block they all committed.
+    pub fn assert_consistent(&self) -> anyhow::Result<()> {
+        let states = self.nodes.iter().map(|node| node.state.lock().unwrap()).collect::<Vec<_>>();
+        let Some((first, rest)) = states.split_first() else { return Ok(()) };
+        for (number, block) in first.committed() {
+            for (offset, state) in rest.iter().enumerate() {
+                let Some(other) = state.committed().get(number) else { continue };
+                if other != block {
+                    bail!(
+                        "block {number} differs between node 0 and node {}: {block:?} != {other:?}",
+                        offset + 1
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Committed blocks of node `index`, by block number.
+    pub fn committed_blocks(&self, index: usize) -> Vec<(u64, CommittedKvBlock)> {
+        let state = self.nodes[index].state.lock().unwrap();
+        state.committed().iter().map(|(number, block)| (*number, *block)).collect()
+    }
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        for process in self.nodes.iter_mut().filter_map(|node| node.process.take()) {
+            for task in &process.tasks {
+                task.abort();
+            }
+            // Dropping the child kills it
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! A key-value execution layer that drives a node over its execution channel.
+
+use crate::node::{parse_kv_txn, RANDOM_VALUE};
+use block_buffer_manager::EpochChangeInProgress;
+use execution_grpc::{BlockRandomness, ExecutionChannelClient};
+use gaptos::api_types::{
+    events::contract_event::GravityEvent, u256_define::BlockId, ExternalBlock,
+};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tiny_keccak::{Hasher, Sha3};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Makes the executor end an epoch every `length` blocks by emitting a `NewEpoch` event with
+/// `validator_set`, the next epoch's validators.
+#[derive(Clone, Debug)]
+pub struct EpochChange {
+    pub length: u64,
+    pub validator_set: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+struct ExecutedBlock {
+    epoch: u64,
+    hash: [u8; 32],
+    randomness: Option<BlockRandomness>,
+    writes: Vec<(String, String)>,
+}
+
+/// A block consensus committed, with the hash the executor computed for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommittedKvBlock {
+    pub block_id: BlockId,
+    pub block_hash: [u8; 32],
+    pub epoch: u64,
+    pub randomness: Option<BlockRandomness>,
+}
+
+/// What one node executed and committed. It outlives the node process, so a restarted node
+/// continues from the blocks it had committed.
+#[derive(Default)]
+pub struct KvState {
+    executed: BTreeMap<u64, ExecutedBlock>,
+    committed: BTreeMap<u64, CommittedKvBlock>,
+}
+
+impl KvState {
+    pub fn committed(&self) -> &BTreeMap<u64, CommittedKvBlock> {
+        &self.committed
+    }
+
+    pub fn latest_committed(&self) -> u64 {
+        self.committed.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// Epoch of the latest committed block, 0 before the first commit.
+    pub fn epoch(&self) -> u64 {
+        self.committed.values().next_back().map_or(0, |block| block.epoch)
+    }
+
+    /// Value of `key` after the committed blocks.
+    pub fn value(&self, key: &str) -> Option<String> {
+        self.committed.keys().rev().filter_map(|number| self.executed.get(number)).find_map(
+            |block| block.writes.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.clone()),
+        )
+    }
+
+    /// Drops the blocks executed after the latest committed one, which the node executes again
+    /// when it restarts.
+    pub fn rewind_to_committed(&mut self) {
+        let latest = self.latest_committed();
+        self.executed.retain(|number, _| *number <= latest);
+    }
+
+    fn execute(&mut self, block: &ExternalBlock) -> [u8; 32] {
+        let number = block.block_meta.block_number;
+        let parent_hash =
+            number.checked_sub(1).and_then(|n| self.executed.get(&n)).map_or([0; 32], |b| b.hash);
+        let mut hasher = Sha3::v256();
+        hasher.update(&parent_hash);
+        hasher.update(&number.to_be_bytes());
+        let randomness = BlockRandomness::of(block);
+        if let Some(randomness) = &randomness {
+            hasher.update(randomness.as_bytes());
+        }
+        let mut writes = vec![];
+        for (index, txn) in block.txns.iter().enumerate() {
+            let bytes = txn.bytes();
+            hasher.update(&(bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+            writes.extend(parse_kv_txn(bytes).map(|(key, value)| match &randomness {
+                Some(randomness) if value == RANDOM_VALUE => {
+                    (key, hex::encode(randomness.derive(&(index as u64).to_be_bytes())))
+                }
+                _ => (key, value),
+            }));
+        }
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        self.executed.insert(
+            number,
+            ExecutedBlock { epoch: block.block_meta.epoch, hash, randomness, writes },
+        );
+        hash
+    }
+}
+
+/// Starts the execute and commit loops of one node. Aborting the returned tasks stops them.
+pub fn spawn_executor(
+    client: ExecutionChannelClient,
+    state: Arc<Mutex<KvState>>,
+    epoch_change: Option<EpochChange>,
+) -> Vec<JoinHandle<()>> {
+    vec![
+        tokio::spawn(execute_loop(client.clone(), state.clone(), epoch_change)),
+        tokio::spawn(commit_loop(client, state)),
+    ]
+}
+
+async fn execute_loop(
+    client: ExecutionChannelClient,
+    state: Arc<Mutex<KvState>>,
+    epoch_change: Option<EpochChange>,
+) {
+    let mut next = state.lock().unwrap().latest_committed() + 1;
+    loop {
+        let epoch = match client.get_current_epoch().await {
+            Ok(epoch) => epoch,
+            Err(e) => {
+                warn!("Failed to get the current epoch: {:#}", e);
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        let blocks = match client.get_ordered_blocks(next, None, epoch).await {
+            Ok(blocks) => blocks,
+            Err(e) if e.is::<EpochChangeInProgress>() => {
+                // A stale epoch only needs a new read, an epoch change has to be consumed
+                if client.get_current_epoch().await.ok() == Some(epoch) {
+                    match client.consume_epoch_change().await {
+                        Ok((_, epoch_change_block_number)) => next = epoch_change_block_number + 1,
+                        Err(e) => warn!("Failed to consume the epoch change: {:#}", e),
+                    }
+                }
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to get ordered blocks from {}: {:#}", next, e);
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        for (block, _parent_id, _execution_meta) in blocks {
+            let meta = &block.block_meta;
+            let hash = state.lock().unwrap().execute(&block);
+            let events = match &epoch_change {
+                Some(change) if meta.block_number % change.length == 0 => {
+                    vec![GravityEvent::NewEpoch(meta.epoch + 1, change.validator_set.clone())]
+                }
+                _ => vec![],
+            };
+            if let Err(e) = client
+                .set_compute_res(meta.block_id, hash, meta.block_number, meta.epoch, &[], &events)
+                .await
+            {
+                warn!("Failed to set the result of block {}: {:#}", meta.block_number, e);
+                break;
+            }
+            next = meta.block_number + 1;
+            // The rest of the batch belongs to the ending epoch and is never committed
+            if !events.is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+async fn commit_loop(client: ExecutionChannelClient, state: Arc<Mutex<KvState>>) {
+    let mut next = state.lock().unwrap().latest_committed() + 1;
+    loop {
+        // Blocks are committed in the epoch they were executed in
+        let executed_epoch = state.lock().unwrap().executed.get(&next).map(|block| block.epoch);
+        let epoch = match executed_epoch {
+            Some(epoch) => epoch,
+            None => {
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        let blocks = match client.get_committed_blocks(next, None, epoch).await {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                warn!("Failed to get committed blocks from {}: {:#}", next, e);
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+        let mut persist_up_to = None;
+        {
+            let mut state = state.lock().unwrap();
+            for block in blocks {
+                let Some(executed) = state.executed.get(&block.block_number) else { break };
+                let committed = CommittedKvBlock {
+                    block_id: block.block_id,
+                    block_hash: block.block_hash.unwrap_or(executed.hash),
+                    epoch: executed.epoch,
+                    randomness: executed.randomness,
+                };
+                state.committed.insert(block.block_number, committed);
+                if block.wait_for_persistence {
+                    persist_up_to = Some(block.block_number);
+                }
+                next = block.block_number + 1;
+            }
+        }
+        // The state is kept in memory, so a block is persisted as soon as it is committed
+        if let Some(block_number) = persist_up_to {
+            if let Err(e) = client.notify_persisted(block_number).await {
+                warn!("Failed to report block {} persisted: {:#}", block_number, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::kv_txn;
+    use gaptos::api_types::{u256_define::Random, ExternalBlockMeta};
+
+    fn block(number: u64, txns: &[(&str, &str)]) -> ExternalBlock {
+        ExternalBlock {
+            block_meta: ExternalBlockMeta {
+                block_id: BlockId([number as u8; 32]),
+                block_number: number,
+                usecs: 0,
+                epoch: 1,
+                randomness: None,
+                block_hash: None,
+                proposer_index: None,
+                failed_proposer_indices: vec![],
+            },
+            txns: txns.iter().map(|(key, value)| kv_txn(key, value)).collect(),
+            extra_data: vec![],
+            enable_randomness: false,
+        }
+    }
+
+    fn commit(state: &mut KvState, number: u64) {
+        let executed = &state.executed[&number];
+        let committed = CommittedKvBlock {
+            block_id: BlockId([number as u8; 32]),
+            block_hash: executed.hash,
+            epoch: executed.epoch,
+            randomness: executed.randomness,
+        };
+        state.committed.insert(number, committed);
+    }
+
+    #[test]
+    fn hashes_chain_and_values_follow_commits() {
+        let mut a = KvState::default();
+        let mut b = KvState::default();
+        for state in [&mut a, &mut b] {
+            state.execute(&block(1, &[("k", "1")]));
+            state.execute(&block(2, &[("k", "2")]));
+            commit(state, 1);
+        }
+        assert_eq!(a.executed[&2].hash, b.executed[&2].hash);
+        assert_ne!(a.executed[&1].hash, a.executed[&2].hash);
+        assert_eq!(a.value("k").as_deref(), Some("1"));
+
+        // Executed but uncommitted blocks are dropped on restart and executed again
+        a.rewind_to_committed();
+        assert!(!a.executed.contains_key(&2));
+        a.execute(&block(2, &[("k", "3")]));
+        commit(&mut a, 2);
+        assert_ne!(a.executed[&2].hash, b.executed[&2].hash);
+        assert_eq!(a.value("k").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn random_values_are_derived_from_the_block_randomness() {
+        let mut with_randomness = block(1, &[("a", RANDOM_VALUE), ("b", "1"), ("c", RANDOM_VALUE)]);
+        with_randomness.block_meta.randomness = Some(Random::from_bytes(&[7; 32]));
+        let randomness = BlockRandomness::of(&with_randomness).unwrap();
+
+        let mut state = KvState::default();
+        state.execute(&with_randomness);
+        state.execute(&block(2, &[("d", RANDOM_VALUE)]));
+        commit(&mut state, 1);
+        commit(&mut state, 2);
+
+        let derived = |index: u64| hex::encode(randomness.derive(&index.to_be_bytes()));
+        assert_eq!(state.value("a"), Some(derived(0)));
+        assert_eq!(state.value("b").as_deref(), Some("1"));
+        assert_eq!(state.value("c"), Some(derived(2)));
+        assert_ne!(state.value("a"), state.value("c"));
+        assert_eq!(state.committed()[&1].randomness, Some(randomness));
+        // Blocks without randomness keep the value as is
+        assert_eq!(state.value("d").as_deref(), Some(RANDOM_VALUE));
+        assert_eq!(state.committed()[&2].randomness, None);
+    }
+}
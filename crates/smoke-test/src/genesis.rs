@@ -0,0 +1,231 @@
+//! Keys, validator set and config files of a local cluster.
+
+use anyhow::Context;
+use gaptos::{
+    aptos_crypto::{
+        bls12381, hash::ACCUMULATOR_PLACEHOLDER_HASH, PrivateKey, Uniform, ValidCryptoMaterial,
+    },
+    aptos_keygen::KeyGen,
+    aptos_types::{
+        account_address::AccountAddress,
+        dkg::{DKGSessionMetadata, DKGSessionState, DKGState, DKGTrait, DefaultDKG},
+        ledger_info::LedgerInfoWithSignatures,
+        on_chain_config::{OnChainRandomnessConfig, ValidatorSet},
+        validator_config::ValidatorConfig,
+        validator_info::ValidatorInfo,
+        validator_verifier::{ValidatorConsensusInfo, ValidatorConsensusInfoMoveStruct},
+        waypoint::Waypoint,
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+use std::{fs, net::TcpListener, path::Path};
+
+/// The genesis validator set, BCS encoded, in every node directory.
+pub const VALIDATOR_SET_FILE: &str = "validator_set.bcs";
+
+/// The onchain DKG state, BCS encoded, in every node directory of a cluster with randomness.
+pub const DKG_STATE_FILE: &str = "dkg_state.bcs";
+
+/// Ports a node listens on.
+#[derive(Clone, Copy, Debug)]
+pub struct NodePorts {
+    pub validator_network: u16,
+    pub execution_channel: u16,
+    pub inspection: u16,
+    pub https: u16,
+}
+
+impl NodePorts {
+    pub fn allocate() -> anyhow::Result<Self> {
+        Ok(Self {
+            validator_network: free_port()?,
+            execution_channel: free_port()?,
+            inspection: free_port()?,
+            https: free_port()?,
+        })
+    }
+}
+
+/// A port nothing listens on right now. Another process may still take it before the node
+/// binds it, which is acceptable for tests.
+fn free_port() -> anyhow::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+/// Same layout as the identity file written by `gravity_cli genesis generate-key`.
+#[derive(Serialize)]
+struct IdentityFile {
+    account_address: String,
+    account_private_key: String,
+    consensus_private_key: String,
+    network_private_key: String,
+}
+
+/// Writes the config directory of every node under `root` and returns the genesis validator
+/// set. Keys are derived from `seed`, so the same seed gives the same cluster. With
+/// `randomness`, the nodes also get a DKG transcript for the first epoch, see [`dkg_state`].
+pub fn write_configs(
+    root: &Path,
+    ports: &[NodePorts],
+    seed: [u8; 32],
+    randomness: bool,
+) -> anyhow::Result<ValidatorSet> {
+    let mut key_gen = KeyGen::from_seed(seed);
+    let mut validators = Vec::with_capacity(ports.len());
+    let mut identities = Vec::with_capacity(ports.len());
+    let mut consensus_infos = Vec::with_capacity(ports.len());
+    let mut dealer_key = None;
+    for (index, ports) in ports.iter().enumerate() {
+        let network_private_key = key_gen.generate_x25519_private_key()?;
+        let consensus_private_key = key_gen.generate_bls12381_private_key();
+        let account_private_key = key_gen.generate_ed25519_private_key();
+
+        // Derived from the consensus key, like the genesis tool does
+        let consensus_public_key = consensus_private_key.public_key();
+        let account_address = {
+            use tiny_keccak::{Hasher, Sha3};
+            let mut hasher = Sha3::v256();
+            hasher.update(&consensus_public_key.to_bytes());
+            let mut output = [0u8; 32];
+            hasher.finalize(&mut output);
+            AccountAddress::new(output)
+        };
+        consensus_infos.push(ValidatorConsensusInfo::new(
+            account_address,
+            consensus_public_key.clone(),
+            1,
+        ));
+        let network_address = format!(
+            "/ip4/127.0.0.1/tcp/{}/noise-ik/{}/handshake/0",
+            ports.validator_network,
+            hex::encode(network_private_key.public_key().to_bytes())
+        );
+        let validator_config = ValidatorConfig::new(
+            consensus_public_key,
+            bcs::to_bytes(&vec![network_address.clone()])?,
+            bcs::to_bytes(&vec![network_address])?,
+            index as u64,
+        );
+        validators.push(ValidatorInfo::new(account_address, 1, validator_config, vec![]));
+        identities.push(IdentityFile {
+            account_address: account_address.to_hex(),
+            account_private_key: hex::encode(account_private_key.to_bytes()),
+            consensus_private_key: hex::encode(consensus_private_key.to_bytes()),
+            network_private_key: hex::encode(network_private_key.to_bytes()),
+        });
+        dealer_key.get_or_insert(consensus_private_key);
+    }
+
+    let validator_set = ValidatorSet::new(validators);
+    let genesis =
+        LedgerInfoWithSignatures::genesis(*ACCUMULATOR_PLACEHOLDER_HASH, validator_set.clone());
+    let waypoint = Waypoint::new_epoch_boundary(genesis.ledger_info())?;
+    let validator_set_bytes = bcs::to_bytes(&validator_set)?;
+    let dkg_state_bytes = match (randomness, &dealer_key) {
+        (true, Some(dealer_key)) => {
+            Some(bcs::to_bytes(&dkg_state(&consensus_infos, dealer_key, seed))?)
+        }
+        _ => None,
+    };
+
+    for (index, (ports, identity)) in ports.iter().zip(identities).enumerate() {
+        let dir = node_dir(root, index);
+        fs::create_dir_all(dir.join("data"))
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        fs::write(dir.join("identity.yaml"), serde_yaml::to_string(&identity)?)?;
+        fs::write(dir.join("waypoint.txt"), waypoint.to_string())?;
+        fs::write(dir.join(VALIDATOR_SET_FILE), &validator_set_bytes)?;
+        if let Some(dkg_state_bytes) = &dkg_state_bytes {
+            fs::write(dir.join(DKG_STATE_FILE), dkg_state_bytes)?;
+        }
+        fs::write(dir.join("validator.yaml"), validator_yaml(&dir, ports))?;
+    }
+    Ok(validator_set)
+}
+
+/// A DKG state as if the genesis validators had completed a DKG in epoch 0, so consensus
+/// derives the randomness of epoch 1 from it. A single validator deals the transcript, which
+/// is enough for consensus: it only decrypts its own share and does not verify the transcript.
+/// There is no DKG for later epochs, so they run without randomness.
+fn dkg_state(
+    validators: &[ValidatorConsensusInfo],
+    dealer_key: &bls12381::PrivateKey,
+    seed: [u8; 32],
+) -> DKGState {
+    let validators =
+        validators.iter().cloned().map(ValidatorConsensusInfoMoveStruct::from).collect::<Vec<_>>();
+    let metadata = DKGSessionMetadata {
+        dealer_epoch: 0,
+        randomness_config: OnChainRandomnessConfig::default_enabled().into(),
+        dealer_validator_set: validators.clone(),
+        target_validator_set: validators,
+    };
+    let params = DefaultDKG::new_public_params(&metadata);
+    let mut rng = StdRng::from_seed(seed);
+    let input_secret = <DefaultDKG as DKGTrait>::InputSecret::generate(&mut rng);
+    let transcript =
+        DefaultDKG::generate_transcript(&mut rng, &params, &input_secret, 0, dealer_key);
+    DKGState {
+        last_completed: Some(DKGSessionState {
+            metadata,
+            start_time_us: 0,
+            transcript: bcs::to_bytes(&transcript).expect("transcripts serialize"),
+        }),
+        in_progress: None,
+    }
+}
+
+pub fn node_dir(root: &Path, index: usize) -> std::path::PathBuf {
+    root.join(format!("node{index}"))
+}
+
+/// The validator template of `cluster/templates`, trimmed to the validator network and with
+/// peers discovered from the genesis validator set.
+fn validator_yaml(dir: &Path, ports: &NodePorts) -> String {
+    let dir = dir.display();
+    format!(
+        r#"base:
+  role: "validator"
+  data_dir: "{dir}/data"
+  waypoint:
+    from_file: "{dir}/waypoint.txt"
+
+consensus:
+  safety_rules:
+    backend:
+      type: "on_disk_storage"
+      path: {dir}/data/secure_storage.json
+    initial_safety_rules_config:
+      from_file:
+        waypoint:
+          from_file: {dir}/waypoint.txt
+        identity_blob_path: {dir}/identity.yaml
+  enable_pipeline: true
+
+validator_network:
+  network_id: validator
+  listen_address: "/ip4/127.0.0.1/tcp/{validator_network}"
+  discovery_method:
+    onchain
+  mutual_authentication: true
+  identity:
+    type: "from_file"
+    path: {dir}/identity.yaml
+
+storage:
+  dir: "{dir}/data"
+
+log_file_path: "{dir}/consensus_log/validator.log"
+
+inspection_service:
+  port: {inspection}
+  address: 127.0.0.1
+
+https_server_address: 127.0.0.1:{https}
+"#,
+        validator_network = ports.validator_network,
+        inspection = ports.inspection,
+        https = ports.https,
+    )
+}
@@ -0,0 +1,20 @@
+//! End-to-end tests of a local multi-validator cluster.
+//!
+//! [`ClusterBuilder`] writes the genesis and configs of N validators and starts each one as a
+//! `kv_node` subprocess running the consensus engine. This process is the execution layer of
+//! every node: it drives blocks through the node's `ExecutionChannel` with a key-value
+//! executor, so tests can submit transactions and check that the nodes commit the same blocks,
+//! change epochs, recover after a restart and agree on values derived from block randomness.
+//!
+//! The cluster tests start real validators and are ignored by default:
+//!
+//! ```text
+//! cargo test -p smoke-test -- --ignored --test-threads 1
+//! ```
+
+pub mod cluster;
+pub mod executor;
+pub mod genesis;
+pub mod node;
+
+pub use cluster::{Cluster, ClusterBuilder};
@@ -0,0 +1,174 @@
+//! The node side of a smoke-test validator: the onchain config it reads and the pool its
+//! transactions come from.
+
+use block_buffer_manager::TxPool;
+use bytes::Bytes;
+use gaptos::{
+    api_types::{
+        account::{ExternalAccountAddress, ExternalChainId},
+        config_storage::{BlockNumber, ConfigStorage, OnChainConfig, OnChainConfigResType},
+        u256_define::TxnHash,
+        VerifiedTxn,
+    },
+    aptos_types::on_chain_config::{
+        OnChainConsensusConfig, OnChainRandomnessConfig, RandomnessConfigMoveStruct,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    io::BufRead,
+    sync::{Arc, Mutex},
+};
+use tiny_keccak::{Hasher, Sha3};
+
+pub const CHAIN_ID: u64 = 1337;
+
+/// Serves the genesis validator set for every block, and nothing else unless randomness is
+/// enabled, so every other config takes its default.
+pub struct KvConfigStorage {
+    validator_set: Bytes,
+    randomness: Option<RandomnessConfigs>,
+}
+
+/// Configs enabling randomness, which needs validator transactions and a completed DKG.
+struct RandomnessConfigs {
+    consensus_config: Bytes,
+    randomness_config: Bytes,
+    dkg_state: Bytes,
+}
+
+impl KvConfigStorage {
+    pub fn new(validator_set: Vec<u8>) -> Self {
+        Self { validator_set: validator_set.into(), randomness: None }
+    }
+
+    /// Enables randomness with `dkg_state`, a BCS encoded onchain DKG state, for every block.
+    pub fn with_randomness(mut self, dkg_state: Vec<u8>) -> anyhow::Result<Self> {
+        let mut consensus_config = OnChainConsensusConfig::default();
+        consensus_config.enable_validator_txns();
+        let randomness_config: RandomnessConfigMoveStruct =
+            OnChainRandomnessConfig::default_enabled().into();
+        self.randomness = Some(RandomnessConfigs {
+            // Stored onchain as a byte vector holding the BCS encoded config
+            consensus_config: bcs::to_bytes(&bcs::to_bytes(&consensus_config)?)?.into(),
+            randomness_config: bcs::to_bytes(&randomness_config)?.into(),
+            dkg_state: dkg_state.into(),
+        });
+        Ok(self)
+    }
+}
+
+impl ConfigStorage for KvConfigStorage {
+    fn fetch_config_bytes(
+        &self,
+        config_name: OnChainConfig,
+        _block_number: BlockNumber,
+    ) -> Option<OnChainConfigResType> {
+        let randomness = self.randomness.as_ref();
+        let bytes = match config_name {
+            OnChainConfig::ValidatorSet => self.validator_set.clone(),
+            OnChainConfig::ConsensusConfig => randomness?.consensus_config.clone(),
+            OnChainConfig::RandomnessConfig => randomness?.randomness_config.clone(),
+            OnChainConfig::DKGState => randomness?.dkg_state.clone(),
+            _ => return None,
+        };
+        Some(bytes.into())
+    }
+}
+
+fn sha3(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3::v256();
+    hasher.update(bytes);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// A transaction setting `key` to `value`. Each one has its own sender at sequence number 0,
+/// so transactions never wait on each other's nonces.
+pub fn kv_txn(key: &str, value: &str) -> VerifiedTxn {
+    let bytes = format!("{key}={value}").into_bytes();
+    let sender = ExternalAccountAddress::new(sha3(&bytes));
+    VerifiedTxn::new(bytes, sender, 0, ExternalChainId::new(CHAIN_ID))
+}
+
+/// Value that the executor replaces with randomness of the block the transaction is in, hex
+/// encoded. It stays as is in blocks without randomness.
+pub const RANDOM_VALUE: &str = "$random";
+
+/// The key and value a transaction of [`kv_txn`] sets.
+pub fn parse_kv_txn(bytes: &[u8]) -> Option<(String, String)> {
+    let (key, value) = std::str::from_utf8(bytes).ok()?.split_once('=')?;
+    Some((key.to_string(), value.to_string()))
+}
+
+type TxFilterFn = Box<dyn Fn((ExternalAccountAddress, u64, TxnHash)) -> bool>;
+
+/// Transaction pool fed with `key=value` lines, one transaction per line.
+#[derive(Clone, Default)]
+pub struct KvTxPool {
+    txns: Arc<Mutex<BTreeMap<[u8; 32], VerifiedTxn>>>,
+}
+
+impl KvTxPool {
+    /// Reads transactions from stdin until it is closed.
+    pub fn spawn_stdin_reader(&self) {
+        let pool = self.clone();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                match line.split_once('=') {
+                    Some((key, value)) => {
+                        pool.add_external_txn(kv_txn(key, value));
+                    }
+                    None => tracing::warn!("Ignoring transaction line without '=': {}", line),
+                }
+            }
+        });
+    }
+
+    fn filtered(&self, filter: Option<TxFilterFn>) -> Vec<VerifiedTxn> {
+        self.txns
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|txn| {
+                filter.as_ref().is_none_or(|filter| {
+                    let hash = TxnHash::from_bytes(txn.committed_hash().as_slice());
+                    filter((txn.sender().clone(), txn.seq_number(), hash))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl TxPool for KvTxPool {
+    fn best_txns(
+        &self,
+        filter: Option<TxFilterFn>,
+        limit: usize,
+        _max_bytes: u64,
+    ) -> Box<dyn Iterator<Item = VerifiedTxn>> {
+        Box::new(self.filtered(filter).into_iter().take(limit))
+    }
+
+    fn get_broadcast_txns(
+        &self,
+        filter: Option<TxFilterFn>,
+    ) -> Box<dyn Iterator<Item = VerifiedTxn>> {
+        Box::new(self.filtered(filter).into_iter())
+    }
+
+    fn add_external_txn(&self, txn: VerifiedTxn) -> bool {
+        let hash = sha3(txn.bytes());
+        self.txns.lock().unwrap().insert(hash, txn).is_none()
+    }
+
+    fn remove_txns(&self, txns: Vec<VerifiedTxn>) {
+        let mut pool = self.txns.lock().unwrap();
+        for txn in txns {
+            pool.remove(&sha3(txn.bytes()));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use smoke_test::{node::RANDOM_VALUE, Cluster, ClusterBuilder};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+fn builder() -> ClusterBuilder {
+    ClusterBuilder::new(env!("CARGO_BIN_EXE_kv_node"))
+}
+
+async fn submit_and_wait(cluster: &mut Cluster, index: usize, key: &str, value: &str) {
+    cluster.submit(index, key, value).await.unwrap();
+    cluster.wait_for_value(key, value, TIMEOUT).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "starts a 4-validator cluster"]
+async fn commits_the_same_blocks_on_every_node() {
+    let mut cluster = builder().build().await.unwrap();
+    for index in 0..cluster.num_nodes() {
+        submit_and_wait(&mut cluster, index, &format!("key{index}"), "value").await;
+    }
+    cluster.wait_for_block(10, TIMEOUT).await.unwrap();
+    cluster.assert_consistent().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "starts a 4-validator cluster"]
+async fn changes_epochs() {
+    let mut cluster = builder().epoch_length(5).build().await.unwrap();
+    let start_epoch = cluster.epoch(0).max(1);
+    cluster.wait_for_epoch(start_epoch + 2, TIMEOUT).await.unwrap();
+    // Transactions still commit after the epoch changes
+    submit_and_wait(&mut cluster, 1, "after", "epochs").await;
+    cluster.assert_consistent().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "starts a 4-validator cluster"]
+async fn restarted_node_catches_up() {
+    let mut cluster = builder().build().await.unwrap();
+    submit_and_wait(&mut cluster, 0, "before", "restart").await;
+
+    // Three of four validators keep making progress without the fourth
+    cluster.stop_node(3).await.unwrap();
+    let stopped_at = cluster.committed_block_number(3);
+    submit_and_wait(&mut cluster, 0, "while", "stopped").await;
+    cluster.wait_for_block(stopped_at + 5, TIMEOUT).await.unwrap();
+
+    cluster.start_node(3).await.unwrap();
+    let latest = cluster.committed_block_number(0);
+    cluster.wait_for_block(latest, TIMEOUT).await.unwrap();
+    assert_eq!(cluster.value(3, "while").as_deref(), Some("stopped"));
+    submit_and_wait(&mut cluster, 3, "after", "restart").await;
+    cluster.assert_consistent().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "starts a 4-validator cluster"]
+async fn blocks_carry_randomness() {
+    let mut cluster = builder().randomness().build().await.unwrap();
+    cluster.submit(0, "lottery0", RANDOM_VALUE).await.unwrap();
+    cluster.submit(1, "lottery1", RANDOM_VALUE).await.unwrap();
+    for key in ["lottery0", "lottery1"] {
+        cluster.wait_for_key(key, TIMEOUT).await.unwrap();
+    }
+
+    // Every node derived the same value from the randomness of the block
+    let values = ["lottery0", "lottery1"].map(|key| cluster.value(0, key).unwrap());
+    for value in &values {
+        assert_ne!(value, RANDOM_VALUE);
+        assert_eq!(hex::decode(value).unwrap().len(), 32);
+    }
+    assert_ne!(values[0], values[1]);
+    for index in 1..cluster.num_nodes() {
+        assert_eq!(cluster.value(index, "lottery0").as_ref(), Some(&values[0]));
+        assert_eq!(cluster.value(index, "lottery1").as_ref(), Some(&values[1]));
+    }
+    let blocks = cluster.committed_blocks(0);
+    assert!(blocks.iter().any(|(_, block)| block.randomness.is_some()));
+    cluster.assert_consistent().unwrap();
+}
@@ -0,0 +1,16 @@
+//! Addresses of the system contracts deployed at genesis, shared by the node, which checks they
+//! are deployed before it starts, and the CLI, which calls them.
+
+use alloy_primitives::{address, Address};
+
+/// ValidatorManagement contract address (from SystemAddresses.VALIDATOR_MANAGER)
+pub const VALIDATOR_MANAGER_ADDRESS: Address = address!("00000000000000000000000000000001625F2001");
+
+/// Staking contract address (from SystemAddresses.STAKING)
+pub const STAKING_ADDRESS: Address = address!("00000000000000000000000000000001625F2000");
+
+/// Reconfiguration contract address (from SystemAddresses.RECONFIGURATION)
+pub const RECONFIGURATION_ADDRESS: Address = address!("00000000000000000000000000000001625F2003");
+
+/// EpochConfig contract address (from SystemAddresses.EPOCH_CONFIG)
+pub const EPOCH_CONFIG_ADDRESS: Address = address!("00000000000000000000000000000001625F1005");
@@ -163,21 +163,63 @@ const MAX_TXN_INITIAL_ADD_TIME_CAPACITY: usize = 100_000;
 const MAX_TXN_HASH_TO_KEY_CAPACITY: usize = 200_000;
 const MAX_TXN_BATCH_ID_CAPACITY: usize = 10_000;
 const MAX_TXN_BLOCK_ID_CAPACITY: usize = 1_000;
+const MAX_TXN_JOURNEY_CAPACITY: usize = 100_000;
+// Journeys outlive commit so they can still be looked up afterwards, until they get this old
+const TXN_JOURNEY_RETENTION_SECS: u64 = 600;
 
 // Key type for transaction tracking: (AccountAddress, sequence_number)
 type TxnKey = (AccountAddress, u64);
 
+/// The stages a transaction has gone through on this node, each with the time it was first
+/// recorded. Stages the node never saw, e.g. the batch of a transaction pulled from a peer's
+/// proof, stay `None`.
+#[derive(Clone, Debug)]
+pub struct TxnJourney {
+    pub first_seen: SystemTime,
+    pub added: Option<SystemTime>,
+    pub batch: Option<(BatchId, SystemTime)>,
+    pub broadcast: Option<SystemTime>,
+    pub proof: Option<SystemTime>,
+    // The latest block the txn was proposed in, which is the one it commits in
+    pub block: Option<(HashValue, SystemTime)>,
+    pub executed: Option<SystemTime>,
+    pub committed: Option<SystemTime>,
+}
+
+impl TxnJourney {
+    fn new(now: SystemTime) -> Self {
+        Self {
+            first_seen: now,
+            added: None,
+            batch: None,
+            broadcast: None,
+            proof: None,
+            block: None,
+            executed: None,
+            committed: None,
+        }
+    }
+}
+
 pub struct TxnLifeTime {
     // Primary storage: (address, nonce) -> initial add time
     txn_initial_add_time: DashMap<TxnKey, SystemTime>,
     // Reverse mapping: hash -> (address, nonce) for batch/block recording
     txn_hash_to_key: DashMap<HashValue, TxnKey>,
-    // Reverse index: (address, nonce) -> [hashes] for O(1) cleanup in record_committed (#239)
-    txn_key_to_hashes: DashMap<TxnKey, Vec<HashValue>>,
+    // Reverse index: (address, nonce) -> hashes for O(1) cleanup in record_committed (#239)
+    txn_key_to_hashes: DashMap<TxnKey, HashSet<HashValue>>,
     // Tracks txns in a batch
     txn_batch_id: DashMap<BatchId, HashSet<TxnKey>>,
     // Tracks txns in a block (block_id is HashValue)
     txn_block_id: DashMap<HashValue, HashSet<TxnKey>>,
+    // Batches included in a block, dropped from txn_batch_id once the block commits
+    txn_block_batches: DashMap<HashValue, HashSet<BatchId>>,
+    // Per txn stage timeline, kept past commit for /tx/journey
+    txn_journeys: DashMap<TxnKey, TxnJourney>,
+    // hash -> (address, nonce) for journey lookups, kept as long as the journey
+    txn_journey_keys: DashMap<HashValue, TxnKey>,
+    // Most journeys kept at once, MAX_TXN_JOURNEY_CAPACITY outside of tests
+    journey_capacity: usize,
 }
 
 static INSTANCE: OnceLock<TxnLifeTime> = OnceLock::new();
@@ -186,7 +228,7 @@ static INSTANCE: OnceLock<TxnLifeTime> = OnceLock::new();
 /// Reads `TXN_LIFE_ENABLED` env var. Defaults to false (disabled).
 static TXN_LIFE_ENABLED: OnceLock<bool> = OnceLock::new();
 
-fn is_txn_life_enabled() -> bool {
+pub fn is_txn_life_enabled() -> bool {
     *TXN_LIFE_ENABLED.get_or_init(|| {
         std::env::var("TXN_LIFE_ENABLED")
             .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
@@ -196,13 +238,65 @@ fn is_txn_life_enabled() -> bool {
 
 impl TxnLifeTime {
     pub fn get_txn_life_time() -> &'static TxnLifeTime {
-        INSTANCE.get_or_init(|| TxnLifeTime {
+        INSTANCE.get_or_init(|| TxnLifeTime::new(MAX_TXN_JOURNEY_CAPACITY))
+    }
+
+    fn new(journey_capacity: usize) -> Self {
+        TxnLifeTime {
             txn_initial_add_time: DashMap::new(),
             txn_hash_to_key: DashMap::new(),
             txn_key_to_hashes: DashMap::new(),
             txn_batch_id: DashMap::new(),
             txn_block_id: DashMap::new(),
-        })
+            txn_block_batches: DashMap::new(),
+            txn_journeys: DashMap::new(),
+            txn_journey_keys: DashMap::new(),
+            journey_capacity,
+        }
+    }
+
+    /// The recorded journey of the transaction with this hash, if it is still tracked.
+    pub fn get_journey(&self, txn_hash: &HashValue) -> Option<TxnJourney> {
+        if !is_txn_life_enabled() {
+            return None;
+        }
+        let txn_key = *self.txn_journey_keys.get(txn_hash)?.value();
+        self.txn_journeys.get(&txn_key).map(|journey| journey.value().clone())
+    }
+
+    fn index_hash(&self, txn_key: TxnKey, txn_hash: HashValue) {
+        self.txn_hash_to_key.insert(txn_hash, txn_key);
+        self.txn_key_to_hashes.entry(txn_key).or_default().insert(txn_hash);
+    }
+
+    fn track_journey(&self, txn_key: TxnKey, txn_hash: HashValue, now: SystemTime) {
+        if self.txn_journeys.len() >= self.journey_capacity {
+            self.cleanup_old_journeys(now);
+        }
+        self.txn_journeys.entry(txn_key).or_insert_with(|| TxnJourney::new(now));
+        self.txn_journey_keys.insert(txn_hash, txn_key);
+    }
+
+    fn update_journey(&self, txn_key: &TxnKey, f: impl FnOnce(&mut TxnJourney)) {
+        if let Some(mut journey) = self.txn_journeys.get_mut(txn_key) {
+            f(journey.value_mut());
+        }
+    }
+
+    fn update_batch_journeys(&self, batch_id: BatchId, f: impl Fn(&mut TxnJourney)) {
+        if let Some(txn_keys_entry) = self.txn_batch_id.get(&batch_id) {
+            for txn_key in txn_keys_entry.value().iter() {
+                self.update_journey(txn_key, &f);
+            }
+        }
+    }
+
+    fn update_block_journeys(&self, block_id: HashValue, f: impl Fn(&mut TxnJourney)) {
+        if let Some(txn_keys_entry) = self.txn_block_id.get(&block_id) {
+            for txn_key in txn_keys_entry.value().iter() {
+                self.update_journey(txn_key, &f);
+            }
+        }
     }
 
     pub fn record_added(&self, txn: &SignedTransaction) {
@@ -226,8 +320,12 @@ impl TxnLifeTime {
         }
 
         // Store the mapping from hash to key and reverse index
-        self.txn_hash_to_key.insert(txn_hash, txn_key);
-        self.txn_key_to_hashes.entry(txn_key).or_default().push(txn_hash);
+        self.index_hash(txn_key, txn_hash);
+
+        self.track_journey(txn_key, txn_hash, now);
+        self.update_journey(&txn_key, |journey| {
+            journey.added.get_or_insert(now);
+        });
     }
 
     pub fn record_batch(&self, batch_id: BatchId, batch: &Vec<SignedTransaction>) {
@@ -241,8 +339,12 @@ impl TxnLifeTime {
             let txn_hash = txn.committed_hash();
 
             // Update hash to key mapping and reverse index
-            self.txn_hash_to_key.insert(txn_hash, txn_key);
-            self.txn_key_to_hashes.entry(txn_key).or_default().push(txn_hash);
+            self.index_hash(txn_key, txn_hash);
+
+            self.track_journey(txn_key, txn_hash, now);
+            self.update_journey(&txn_key, |journey| {
+                journey.batch.get_or_insert((batch_id, now));
+            });
 
             if let Some(initial_add_time_entry) = self.txn_initial_add_time.get(&txn_key) {
                 if let Ok(duration) = now.duration_since(*initial_add_time_entry.value()) {
@@ -276,6 +378,9 @@ impl TxnLifeTime {
                 // No state update needed in txn_time anymore
             }
         }
+        self.update_batch_journeys(batch_id, |journey| {
+            journey.broadcast.get_or_insert(now);
+        });
     }
 
     pub fn record_before_persist(&self, batch_id: BatchId) {
@@ -328,6 +433,9 @@ impl TxnLifeTime {
                 // No state update needed in txn_time anymore
             }
         }
+        self.update_batch_journeys(batch_id, |journey| {
+            journey.proof.get_or_insert(now);
+        });
     }
 
     // Helper function to avoid code duplication in record_block paths for "Added to Block"
@@ -346,6 +454,7 @@ impl TxnLifeTime {
         block_record_time: SystemTime,
     ) {
         let mut current_block_txn_keys = vec![];
+        let mut current_block_batch_ids = vec![];
         for p in &proof_with_data.proofs {
             let batch_id = p.batch_id();
             if let Some(txn_keys_entry) = self.txn_batch_id.get(&batch_id) {
@@ -353,8 +462,12 @@ impl TxnLifeTime {
                     self.observe_added_to_block(txn_key, block_record_time);
                     current_block_txn_keys.push(txn_key);
                 }
+                current_block_batch_ids.push(batch_id);
             }
         }
+        if !current_block_batch_ids.is_empty() {
+            self.txn_block_batches.entry(block_id).or_default().extend(current_block_batch_ids);
+        }
         if !current_block_txn_keys.is_empty() {
             // Use entry().or_default().extend() to append if block_id already has txns from other
             // sources (e.g. hybrid)
@@ -374,8 +487,8 @@ impl TxnLifeTime {
                     for txn in txns.iter() {
                         let txn_key = (txn.sender(), txn.sequence_number());
                         let txn_hash = txn.committed_hash();
-                        self.txn_hash_to_key.insert(txn_hash, txn_key);
-                        self.txn_key_to_hashes.entry(txn_key).or_default().push(txn_hash);
+                        self.index_hash(txn_key, txn_hash);
+                        self.track_journey(txn_key, txn_hash, now);
                         self.observe_added_to_block(txn_key, now);
                         current_block_txn_keys.insert(txn_key);
                     }
@@ -399,12 +512,18 @@ impl TxnLifeTime {
 
                     // Process inline transactions part
                     let mut inline_txn_keys = vec![];
-                    for (_, txn_hashes_in_vec) in vec_payload {
+                    for (batch_info, txn_hashes_in_vec) in vec_payload {
+                        if self.txn_batch_id.contains_key(&batch_info.batch_id()) {
+                            self.txn_block_batches
+                                .entry(block_id)
+                                .or_default()
+                                .insert(batch_info.batch_id());
+                        }
                         for txn in txn_hashes_in_vec.iter() {
                             let txn_key = (txn.sender(), txn.sequence_number());
                             let txn_hash = txn.committed_hash();
-                            self.txn_hash_to_key.insert(txn_hash, txn_key);
-                            self.txn_key_to_hashes.entry(txn_key).or_default().push(txn_hash);
+                            self.index_hash(txn_key, txn_hash);
+                            self.track_journey(txn_key, txn_hash, now);
                             self.observe_added_to_block(txn_key, now);
                             inline_txn_keys.push(txn_key);
                         }
@@ -415,6 +534,10 @@ impl TxnLifeTime {
                 }
                 Payload::OptQuorumStore(_) => {}
             }
+            self.update_block_journeys(block_id, |journey| {
+                journey.block = Some((block_id, now));
+                journey.executed = None;
+            });
         }
     }
 
@@ -448,6 +571,9 @@ impl TxnLifeTime {
                 }
             }
         }
+        self.update_block_journeys(block_id, |journey| {
+            journey.executed.get_or_insert(now);
+        });
     }
 
     pub fn record_block_committed(&self, block_id: HashValue) {
@@ -465,6 +591,27 @@ impl TxnLifeTime {
                 }
             }
         }
+        self.release_block(block_id);
+    }
+
+    /// Drops the batch and block tracking of a committed block without observing it. For the
+    /// blocks committed along with the one passed to `record_block_committed`.
+    pub fn release_committed_block(&self, block_id: HashValue) {
+        if !is_txn_life_enabled() {
+            return;
+        }
+        self.release_block(block_id);
+    }
+
+    fn release_block(&self, block_id: HashValue) {
+        // Everything that referenced the committed block's txns by block or batch is done; the
+        // per txn entries go in record_committed.
+        self.txn_block_id.remove(&block_id);
+        if let Some((_, batch_ids)) = self.txn_block_batches.remove(&block_id) {
+            for batch_id in batch_ids {
+                self.txn_batch_id.remove(&batch_id);
+            }
+        }
     }
 
     pub fn record_committed(&self, sender: &AccountAddress, sequence_number: u64) {
@@ -480,6 +627,10 @@ impl TxnLifeTime {
             }
         }
 
+        self.update_journey(&txn_key, |journey| {
+            journey.committed.get_or_insert(now);
+        });
+
         // Remove from primary storage
         self.txn_initial_add_time.remove(&txn_key);
 
@@ -488,18 +639,32 @@ impl TxnLifeTime {
                 self.txn_hash_to_key.remove(&hash);
             }
         }
+        // Batch and block tracking is dropped per block in record_block_committed, scanning
+        // them here would cost O(map) per committed txn
+    }
 
-        // Remove from batch tracking
-        for mut entry in self.txn_batch_id.iter_mut() {
-            entry.value_mut().remove(&txn_key);
-        }
-        self.txn_batch_id.retain(|_batch_id, txn_set| !txn_set.is_empty());
-
-        // Remove from block tracking
-        for mut entry in self.txn_block_id.iter_mut() {
-            entry.value_mut().remove(&txn_key);
+    /// Drops the journeys past their retention. If that does not free enough room, also evicts
+    /// the oldest journeys until a fifth of the capacity is free, so the next ones do not each
+    /// trigger a cleanup.
+    fn cleanup_old_journeys(&self, now: SystemTime) {
+        self.txn_journeys.retain(|_txn_key, journey| {
+            now.duration_since(journey.first_seen)
+                .map_or(true, |duration| duration.as_secs() <= TXN_JOURNEY_RETENTION_SECS)
+        });
+        let target = self.journey_capacity - self.journey_capacity / 5;
+        if self.txn_journeys.len() > target {
+            let mut by_age: Vec<(SystemTime, TxnKey)> = self
+                .txn_journeys
+                .iter()
+                .map(|journey| (journey.value().first_seen, *journey.key()))
+                .collect();
+            by_age.sort_unstable_by_key(|(first_seen, _)| *first_seen);
+            let excess = by_age.len() - target;
+            for (_, txn_key) in by_age.into_iter().take(excess) {
+                self.txn_journeys.remove(&txn_key);
+            }
         }
-        self.txn_block_id.retain(|_block_id, txn_set| !txn_set.is_empty());
+        self.txn_journey_keys.retain(|_txn_hash, txn_key| self.txn_journeys.contains_key(txn_key));
     }
 
     /// Cleanup old entries when capacity limits are exceeded
@@ -547,5 +712,105 @@ impl TxnLifeTime {
                 !txn_set.is_empty()
             });
         }
+
+        // Blocks that never commit, e.g. on a fork, are never dropped by record_block_committed
+        if self.txn_block_batches.len() >= MAX_TXN_BLOCK_ID_CAPACITY {
+            self.txn_block_batches.retain(|_block_id, batch_ids| {
+                batch_ids.retain(|batch_id| self.txn_batch_id.contains_key(batch_id));
+                !batch_ids.is_empty()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn txn(nonce: u64) -> (TxnKey, HashValue) {
+        ((AccountAddress::ONE, nonce), HashValue::sha3_256_of(&nonce.to_le_bytes()))
+    }
+
+    fn track_block(txn_life_time: &TxnLifeTime, block_id: HashValue, nonces: &[u64]) {
+        let added = SystemTime::now() - Duration::from_secs(1);
+        for &nonce in nonces {
+            let (txn_key, _) = txn(nonce);
+            txn_life_time.txn_initial_add_time.insert(txn_key, added);
+            txn_life_time.txn_block_id.entry(block_id).or_default().insert(txn_key);
+        }
+        let batch_id = BatchId::new_for_test(nonces[0]);
+        txn_life_time.txn_batch_id.insert(batch_id, HashSet::new());
+        txn_life_time.txn_block_batches.entry(block_id).or_default().insert(batch_id);
+    }
+
+    #[test]
+    fn only_the_certified_block_is_observed() {
+        let _ = TXN_LIFE_ENABLED.set(true);
+        let txn_life_time = TxnLifeTime::new(MAX_TXN_JOURNEY_CAPACITY);
+        let (parent, certified) = (HashValue::random(), HashValue::random());
+        track_block(&txn_life_time, parent, &[0, 1, 2]);
+        track_block(&txn_life_time, certified, &[3]);
+
+        let observed = get_txn_added_to_block_committed_histogram().get_sample_count();
+        txn_life_time.release_committed_block(parent);
+        txn_life_time.record_block_committed(certified);
+
+        // One sample per txn of the block the ledger info certifies, like before the parents
+        // were released too
+        assert_eq!(get_txn_added_to_block_committed_histogram().get_sample_count(), observed + 1);
+        assert!(txn_life_time.txn_block_id.is_empty());
+        assert!(txn_life_time.txn_block_batches.is_empty());
+        assert!(txn_life_time.txn_batch_id.is_empty());
+    }
+
+    #[test]
+    fn journeys_over_capacity_evict_the_oldest() {
+        let txn_life_time = TxnLifeTime::new(10);
+        let start = SystemTime::now();
+        for nonce in 0..10 {
+            let (txn_key, txn_hash) = txn(nonce);
+            txn_life_time.track_journey(txn_key, txn_hash, start + Duration::from_secs(nonce));
+        }
+        assert_eq!(txn_life_time.txn_journeys.len(), 10);
+
+        let (txn_key, txn_hash) = txn(10);
+        txn_life_time.track_journey(txn_key, txn_hash, start + Duration::from_secs(10));
+
+        // Down to 8 before the new one is added
+        assert_eq!(txn_life_time.txn_journeys.len(), 9);
+        for nonce in 0..2 {
+            let (txn_key, txn_hash) = txn(nonce);
+            assert!(!txn_life_time.txn_journeys.contains_key(&txn_key));
+            assert!(!txn_life_time.txn_journey_keys.contains_key(&txn_hash));
+        }
+        for nonce in 2..=10 {
+            let (txn_key, txn_hash) = txn(nonce);
+            assert!(txn_life_time.txn_journeys.contains_key(&txn_key));
+            assert_eq!(txn_life_time.txn_journey_keys.get(&txn_hash).map(|k| *k), Some(txn_key));
+        }
+    }
+
+    #[test]
+    fn expired_journeys_go_before_the_cap_applies() {
+        let txn_life_time = TxnLifeTime::new(10);
+        let now = SystemTime::now();
+        let expired = now - Duration::from_secs(TXN_JOURNEY_RETENTION_SECS + 1);
+        for nonce in 0..5 {
+            let (txn_key, txn_hash) = txn(nonce);
+            txn_life_time.track_journey(txn_key, txn_hash, expired);
+        }
+        for nonce in 5..10 {
+            let (txn_key, txn_hash) = txn(nonce);
+            txn_life_time.track_journey(txn_key, txn_hash, now);
+        }
+
+        let (txn_key, txn_hash) = txn(10);
+        txn_life_time.track_journey(txn_key, txn_hash, now);
+
+        // Dropping the expired ones freed enough room, no live journey was evicted
+        assert_eq!(txn_life_time.txn_journeys.len(), 6);
+        assert!((5..=10).all(|nonce| txn_life_time.txn_journeys.contains_key(&txn(nonce).0)));
+        assert_eq!(txn_life_time.txn_journey_keys.len(), 6);
     }
 }
@@ -119,6 +119,10 @@ pub struct StateComputeResult {
     pub execution_output: ComputeRes,
     epoch_state: Option<EpochState>,
     block_end_info: Option<BlockEndInfo>,
+    /// Hash of the payload the execution layer attached to the block, when the epoch signs
+    /// commit payloads. It is folded into `root_hash`, so the commit votes sign it.
+    #[serde(default)]
+    commit_payload_hash: Option<HashValue>,
 }
 
 impl StateComputeResult {
@@ -127,7 +131,12 @@ impl StateComputeResult {
         epoch_state: Option<EpochState>,
         block_end_info: Option<BlockEndInfo>,
     ) -> Self {
-        Self { execution_output, epoch_state, block_end_info }
+        Self { execution_output, epoch_state, block_end_info, commit_payload_hash: None }
+    }
+
+    pub fn with_commit_payload_hash(mut self, commit_payload_hash: HashValue) -> Self {
+        self.commit_payload_hash = Some(commit_payload_hash);
+        self
     }
 
     pub fn version(&self) -> Version {
@@ -162,13 +171,31 @@ impl StateComputeResult {
             },
             epoch_state: None,
             block_end_info: None,
+            commit_payload_hash: None,
         }
     }
 
+    /// The executed state id the commit votes sign: the execution layer block hash, followed
+    /// by the commit payload hash if there is one, hashed with SHA3-256.
     pub fn root_hash(&self) -> HashValue {
+        let block_hash = self.execution_block_hash();
+        match self.commit_payload_hash {
+            Some(commit_payload_hash) => HashValue::sha3_256_of(
+                &[block_hash.as_ref(), commit_payload_hash.as_ref()].concat(),
+            ),
+            None => block_hash,
+        }
+    }
+
+    /// The hash of the block in the execution layer.
+    pub fn execution_block_hash(&self) -> HashValue {
         HashValue::new(self.execution_output.data)
     }
 
+    pub fn commit_payload_hash(&self) -> Option<HashValue> {
+        self.commit_payload_hash
+    }
+
     pub fn epoch_state(&self) -> &Option<EpochState> {
         &self.epoch_state
     }
@@ -0,0 +1,344 @@
+"""
+Whole-node Soak Test
+
+4 genesis validators under randomized load and fault injection, with
+invariants checked continuously:
+- no block hash divergence between nodes at the same height
+- committed height on every node never goes backwards
+- mempool (txpool pending + queued) stays below a bound
+
+Faults are node restarts (keeping >= 3 validators alive) and consensus
+failpoints, which require a node built with `make gravity_node FEATURE=failpoints`.
+Without that feature the failpoint endpoint rejects requests; those rounds are
+counted as failpoint_errors and only restarts take effect.
+
+At the end a JSON report is written to $GRAVITY_ARTIFACTS_DIR (or next to
+cluster.toml) and the test fails if any invariant was violated.
+
+Usage:
+    # default 1 hour
+    python gravity_e2e/runner.py soak
+
+    # custom duration (seconds) and reproducible fault schedule
+    SOAK_DURATION=14400 SOAK_SEED=42 python gravity_e2e/runner.py soak
+"""
+
+import asyncio
+import json
+import logging
+import os
+import random
+import time
+from dataclasses import asdict, dataclass, field
+from pathlib import Path
+from typing import Dict, List, Optional
+
+import aiohttp
+import pytest
+from eth_account import Account
+
+from gravity_e2e.cluster.manager import Cluster
+from gravity_e2e.cluster.node import Node, NodeState
+from gravity_e2e.utils.transaction_builder import TransactionBuilder, run_sync
+
+LOG = logging.getLogger(__name__)
+
+# ── Configuration ────────────────────────────────────────────────────
+SOAK_DURATION = int(os.environ.get("SOAK_DURATION", "3600"))
+SOAK_SEED = int(os.environ.get("SOAK_SEED", str(int(time.time()))))
+
+# Min validators that must stay alive (BFT requires > 2/3, so 3 out of 4)
+MIN_ALIVE_VALIDATORS = 3
+
+# Upper bound on txpool pending + queued on any node
+MAX_MEMPOOL_TXNS = int(os.environ.get("SOAK_MAX_MEMPOOL_TXNS", "50000"))
+
+# Interval between invariant checks (seconds)
+CHECK_INTERVAL = 10
+
+# Number of already-committed heights sampled per divergence check
+HASH_SAMPLES = 5
+
+# Interval between fault injections (seconds)
+FAULT_INTERVAL_MIN = 30
+FAULT_INTERVAL_MAX = 90
+
+# How long an injected fault stays active before it is cleared (seconds)
+FAULT_DURATION_MIN = 10
+FAULT_DURATION_MAX = 40
+
+# Load shape: bursts of random size separated by random pauses
+LOAD_BURST_MAX = int(os.environ.get("SOAK_LOAD_BURST_MAX", "200"))
+LOAD_PAUSE_MAX = 2.0
+
+# Failpoints that degrade liveness without breaking safety. Each entry is
+# (name, action) in the `fail` crate syntax.
+FAILPOINTS = [
+    ("consensus::send::vote", "20%return"),
+    ("consensus::send::commit_vote", "20%return"),
+    ("consensus::send::proposal", "10%return"),
+    ("consensus::process_proposal_msg", "10%return"),
+    ("consensus::pull_payload", "10%return"),
+]
+
+
+@dataclass
+class Violation:
+    """A single invariant violation."""
+
+    invariant: str
+    node: Optional[str]
+    detail: str
+    elapsed_secs: float
+
+
+@dataclass
+class SoakReport:
+    """Machine-readable summary written at the end of the run."""
+
+    seed: int
+    duration_secs: int
+    elapsed_secs: float = 0.0
+    txns_sent: int = 0
+    txn_send_errors: int = 0
+    restarts: int = 0
+    failpoints_set: int = 0
+    failpoint_errors: int = 0
+    checks: int = 0
+    max_mempool_txns: int = 0
+    final_heights: Dict[str, int] = field(default_factory=dict)
+    violations: List[Violation] = field(default_factory=list)
+
+    @property
+    def passed(self) -> bool:
+        return not self.violations
+
+
+class SoakTestContext:
+    """Drives load, faults and invariant checks against a running cluster."""
+
+    def __init__(self, cluster: Cluster, duration: int, seed: int):
+        self.cluster = cluster
+        self.duration = duration
+        self.rng = random.Random(seed)
+        self.report = SoakReport(seed=seed, duration_secs=duration)
+        self.nodes: List[Node] = list(cluster.nodes.values())
+        self._start = time.monotonic()
+        # Highest block number each node has ever reported
+        self._max_heights: Dict[str, int] = {}
+
+    @property
+    def elapsed(self) -> float:
+        return time.monotonic() - self._start
+
+    @property
+    def should_stop(self) -> bool:
+        return self.elapsed >= self.duration
+
+    def _violate(self, invariant: str, node: Optional[str], detail: str):
+        LOG.error(f"❌ Invariant '{invariant}' violated on {node or 'cluster'}: {detail}")
+        self.report.violations.append(
+            Violation(invariant, node, detail, round(self.elapsed, 1))
+        )
+
+    async def _live_nodes(self) -> List[Node]:
+        live = []
+        for node in self.nodes:
+            state, _ = await node.get_state()
+            if state == NodeState.RUNNING:
+                live.append(node)
+        return live
+
+    # ── Load ─────────────────────────────────────────────────────────
+
+    async def load_loop(self):
+        sender = self.cluster.faucet
+        assert sender, "Faucet not configured"
+        builders: Dict[str, TransactionBuilder] = {}
+
+        while not self.should_stop:
+            live = await self._live_nodes()
+            if not live:
+                await asyncio.sleep(1)
+                continue
+            node = self.rng.choice(live)
+            tb = builders.setdefault(node.id, TransactionBuilder(node.w3, sender))
+
+            burst = self.rng.randint(1, LOAD_BURST_MAX)
+            await tb.get_nonce(refresh=True)
+            for _ in range(burst):
+                try:
+                    result = await tb.send_ether(
+                        Account.create().address, 1, wait_for_receipt=False
+                    )
+                except Exception as e:
+                    LOG.debug(f"send to {node.id} failed: {e}")
+                    result = None
+                if result is not None and result.error is None:
+                    self.report.txns_sent += 1
+                else:
+                    # Node likely went down mid-burst; resync the nonce next burst
+                    self.report.txn_send_errors += 1
+                    break
+            await asyncio.sleep(self.rng.uniform(0, LOAD_PAUSE_MAX))
+
+    # ── Faults ───────────────────────────────────────────────────────
+
+    async def _set_failpoint(self, node: Node, name: str, actions: str) -> bool:
+        try:
+            async with aiohttp.ClientSession() as session:
+                async with session.post(
+                    f"{node.http_url}/set_failpoint",
+                    json={"name": name, "actions": actions},
+                    timeout=aiohttp.ClientTimeout(total=5),
+                ) as resp:
+                    if resp.status == 200:
+                        return True
+                    LOG.warning(
+                        f"⚠️  set_failpoint {name} on {node.id} failed: "
+                        f"{resp.status} {await resp.text()}"
+                    )
+        except Exception as e:
+            LOG.warning(f"⚠️  set_failpoint {name} on {node.id} failed: {e}")
+        self.report.failpoint_errors += 1
+        return False
+
+    async def _inject_failpoint(self, live: List[Node]):
+        node = self.rng.choice(live)
+        name, actions = self.rng.choice(FAILPOINTS)
+        if not await self._set_failpoint(node, name, actions):
+            return
+        self.report.failpoints_set += 1
+        hold = self.rng.uniform(FAULT_DURATION_MIN, FAULT_DURATION_MAX)
+        LOG.info(f"💉 {node.id}: {name}={actions} for {hold:.0f}s")
+        await asyncio.sleep(hold)
+        await self._set_failpoint(node, name, "off")
+
+    async def _inject_restart(self, live: List[Node]):
+        node = self.rng.choice(live)
+        down = self.rng.uniform(FAULT_DURATION_MIN, FAULT_DURATION_MAX)
+        LOG.info(f"🔪 Restarting {node.id} after {down:.0f}s down")
+        await node.stop()
+        await asyncio.sleep(down)
+        if not await node.start():
+            self._violate("node_restart", node.id, "node failed to come back up")
+        self.report.restarts += 1
+
+    async def fault_loop(self):
+        while not self.should_stop:
+            await asyncio.sleep(self.rng.uniform(FAULT_INTERVAL_MIN, FAULT_INTERVAL_MAX))
+            if self.should_stop:
+                break
+            live = await self._live_nodes()
+            if len(live) <= MIN_ALIVE_VALIDATORS or self.rng.random() < 0.5:
+                if live:
+                    await self._inject_failpoint(live)
+            else:
+                await self._inject_restart(live)
+
+    # ── Invariants ───────────────────────────────────────────────────
+
+    async def _check_monotonic(self, heights: Dict[str, int]):
+        for node_id, height in heights.items():
+            prev = self._max_heights.get(node_id, -1)
+            if height < prev:
+                self._violate(
+                    "monotonic_commits", node_id, f"height went back from {prev} to {height}"
+                )
+            else:
+                self._max_heights[node_id] = height
+
+    async def _check_hash_divergence(self, live: List[Node], heights: Dict[str, int]):
+        common = min(heights.values())
+        if common <= 0:
+            return
+        samples = {common} | {
+            self.rng.randint(1, common) for _ in range(HASH_SAMPLES - 1)
+        }
+        for height in sorted(samples):
+            hashes = {}
+            for node in live:
+                try:
+                    block = await run_sync(node.w3.eth.get_block, height)
+                    hashes[node.id] = block["hash"].hex()
+                except Exception as e:
+                    LOG.debug(f"get_block({height}) on {node.id} failed: {e}")
+            if len(set(hashes.values())) > 1:
+                self._violate("no_hash_divergence", None, f"height {height}: {hashes}")
+
+    async def _check_mempool(self, live: List[Node]):
+        for node in live:
+            try:
+                resp = await run_sync(node.w3.provider.make_request, "txpool_status", [])
+                status = resp["result"]
+                size = int(status["pending"], 16) + int(status["queued"], 16)
+            except Exception as e:
+                LOG.debug(f"txpool_status on {node.id} failed: {e}")
+                continue
+            self.report.max_mempool_txns = max(self.report.max_mempool_txns, size)
+            if size > MAX_MEMPOOL_TXNS:
+                self._violate(
+                    "mempool_bounded", node.id, f"{size} txns > bound {MAX_MEMPOOL_TXNS}"
+                )
+
+    async def check_loop(self):
+        while not self.should_stop:
+            await asyncio.sleep(CHECK_INTERVAL)
+            live = await self._live_nodes()
+            heights = {}
+            for node in live:
+                try:
+                    heights[node.id] = node.get_block_number()
+                except Exception:
+                    pass
+            if not heights:
+                continue
+            self.report.checks += 1
+            await self._check_monotonic(heights)
+            await self._check_hash_divergence(
+                [n for n in live if n.id in heights], heights
+            )
+            await self._check_mempool(live)
+            LOG.info(
+                f"🩺 [{self.elapsed / 60:.1f} min] heights={heights} "
+                f"txns={self.report.txns_sent} violations={len(self.report.violations)}"
+            )
+
+    # ── Report ───────────────────────────────────────────────────────
+
+    def write_report(self) -> Path:
+        self.report.elapsed_secs = round(self.elapsed, 1)
+        self.report.final_heights = dict(self._max_heights)
+
+        artifacts_dir = os.environ.get("GRAVITY_ARTIFACTS_DIR")
+        out_dir = Path(artifacts_dir) if artifacts_dir else self.cluster.config_path.parent
+        out_dir.mkdir(parents=True, exist_ok=True)
+        path = out_dir / f"soak_report_{time.strftime('%Y%m%d_%H%M%S')}.json"
+
+        data = asdict(self.report)
+        data["passed"] = self.report.passed
+        path.write_text(json.dumps(data, indent=2))
+        return path
+
+
+@pytest.mark.asyncio
+async def test_soak(cluster: Cluster):
+    """Run load and faults for SOAK_DURATION seconds and assert invariants throughout."""
+    assert await cluster.set_full_live(timeout=120), "Cluster failed to become fully live"
+    assert await cluster.check_block_increasing(timeout=60), "Block production halted"
+
+    LOG.info(f"🚀 Soak test: duration={SOAK_DURATION}s seed={SOAK_SEED}")
+    ctx = SoakTestContext(cluster, SOAK_DURATION, SOAK_SEED)
+    try:
+        await asyncio.gather(ctx.load_loop(), ctx.fault_loop(), ctx.check_loop())
+    finally:
+        # Clear any failpoint left active and bring stopped nodes back
+        if ctx.report.failpoints_set:
+            for node in await ctx._live_nodes():
+                for name, _ in FAILPOINTS:
+                    await ctx._set_failpoint(node, name, "off")
+        await cluster.set_full_live(timeout=120)
+        report_path = ctx.write_report()
+        LOG.info(f"📄 Soak report written to {report_path}")
+
+    assert ctx.report.passed, f"{len(ctx.report.violations)} invariant violations, see {report_path}"